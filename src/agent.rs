@@ -1,7 +1,61 @@
 // src/agent.rs
-use anyhow::{Context, Result};
+use anyhow::Result;
 use chrono::Local;
-use std::process::Command;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use crate::config::{CryoConfig, MessageOrder, PromptPosition};
+use crate::message::Message;
+
+/// The agent command itself is broken: it doesn't parse, is empty, names
+/// a program that is missing or not executable, or its arguments exceed
+/// the OS limit. Retrying can't fix it, so the daemon pauses instead of
+/// backing off forever.
+#[derive(Debug)]
+pub struct AgentCommandError {
+    pub command: String,
+    pub reason: String,
+}
+
+impl std::fmt::Display for AgentCommandError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "agent command {:?}: {}", self.command, self.reason)
+    }
+}
+
+impl std::error::Error for AgentCommandError {}
+
+impl AgentCommandError {
+    fn error(command: &str, reason: impl Into<String>) -> anyhow::Error {
+        anyhow::Error::new(Self {
+            command: command.to_string(),
+            reason: reason.into(),
+        })
+    }
+
+    /// The `AgentCommandError` behind `err`, if that is what it is.
+    pub fn find(err: &anyhow::Error) -> Option<&Self> {
+        err.chain().find_map(|e| e.downcast_ref::<Self>())
+    }
+}
+
+/// Turn a failed `Command::spawn` into an error: a missing or
+/// non-executable program, or an argument list too long for the OS (E2BIG),
+/// is an [`AgentCommandError`], anything else (fork limits, I/O) is transient.
+fn spawn_error(agent_command: &str, e: std::io::Error) -> anyhow::Error {
+    match e.kind() {
+        std::io::ErrorKind::NotFound | std::io::ErrorKind::PermissionDenied => {
+            AgentCommandError::error(agent_command, format!("cannot run the program: {e}"))
+        }
+        std::io::ErrorKind::ArgumentListTooLong => AgentCommandError::error(
+            agent_command,
+            format!(
+                "prompt too long for the command line, try agent_prompt_position = \"stdin\": {e}"
+            ),
+        ),
+        _ => anyhow::anyhow!("Failed to spawn agent: {e}"),
+    }
+}
 
 /// Supported agent types.
 enum AgentKind {
@@ -26,8 +80,11 @@ enum AgentKind {
 ///
 /// Unknown programs are treated as custom agents (prompt passed as positional arg).
 fn resolve_agent(agent_cmd: &str) -> Result<(AgentKind, String, Vec<String>)> {
-    let parts = shell_words::split(agent_cmd.trim()).context("Failed to parse agent command")?;
-    let program = parts.first().context("Agent command is empty")?;
+    let parts = shell_words::split(agent_cmd.trim())
+        .map_err(|e| AgentCommandError::error(agent_cmd, format!("failed to parse: {e}")))?;
+    let program = parts
+        .first()
+        .ok_or_else(|| AgentCommandError::error(agent_cmd, "command is empty"))?;
     let exe = program.rsplit('/').next().unwrap_or(program);
     let args = parts[1..].to_vec();
 
@@ -70,10 +127,57 @@ pub fn agent_program(agent_cmd: &str) -> Result<String> {
     Ok(program)
 }
 
+/// How the agent command is launched, beyond the `agent` string itself.
+#[derive(Debug, Clone, Default)]
+pub struct LaunchOptions {
+    /// Appended after the agent's own (and injected) arguments, before the prompt
+    pub extra_args: Vec<String>,
+    pub prompt_position: PromptPosition,
+    /// Start the agent as the leader of a new process group
+    pub process_group: bool,
+}
+
+impl LaunchOptions {
+    /// `agent_args`, `agent_prompt_position` and `kill_process_group` from
+    /// cryo.toml.
+    pub fn from_config(config: &CryoConfig) -> Self {
+        Self {
+            extra_args: config.agent_args.clone(),
+            prompt_position: config.agent_prompt_position,
+            process_group: config.kill_process_group,
+        }
+    }
+}
+
+/// The plan a session focuses on, and where every plan stands.
+pub struct PlanFocus {
+    pub name: String,
+    /// All plans by name, with whether each is complete
+    pub plans: Vec<(String, bool)>,
+}
+
 pub struct AgentConfig {
     pub session_number: u32,
     pub task: String,
     pub delayed_wake: Option<String>,
+    /// plan.md changed since the previous session ended
+    pub plan_changed: bool,
+    /// Checkpoint JSON saved with `cryo-agent checkpoint save`
+    pub checkpoint: Option<String>,
+    /// The `plans/` plan this session works on, if the project has several
+    pub focus: Option<PlanFocus>,
+    /// Attachments on unread inbox messages (names in `messages/attachments/`)
+    pub attachments: Vec<String>,
+    /// Inbox directory relative to the project (`inbox_dir` in cryo.toml)
+    pub inbox_dir: String,
+    /// Only messages read through `cryo-agent receive` count as read
+    /// (`require_inbox_ack` in cryo.toml)
+    pub require_inbox_ack: bool,
+    /// cryo.log excerpt selected by `history_mode` (None = no history section)
+    pub history: Option<String>,
+    /// Unread inbox messages to include verbatim (`prompt_style = "embedded"`);
+    /// empty means the agent reads the inbox itself
+    pub messages: Vec<(String, Message)>,
 }
 
 pub fn build_prompt(config: &AgentConfig) -> String {
@@ -84,12 +188,81 @@ pub fn build_prompt(config: &AgentConfig) -> String {
         None => String::new(),
     };
 
+    let plan_section = if config.plan_changed {
+        "\n## Plan Changed\n\nThe plan was modified since your last session — re-read plan.md (and plans/, if you have one) before continuing.\n"
+    } else {
+        ""
+    };
+
+    let focus_section = match &config.focus {
+        Some(focus) => {
+            let others: Vec<String> = focus
+                .plans
+                .iter()
+                .filter(|(name, _)| *name != focus.name)
+                .map(|(name, complete)| {
+                    if *complete {
+                        format!("{name} (complete)")
+                    } else {
+                        name.clone()
+                    }
+                })
+                .collect();
+            let others = if others.is_empty() {
+                String::new()
+            } else {
+                format!(" Other plans: {}.", others.join(", "))
+            };
+            format!(
+                "\n## Focus Plan\n\nThis session works on {}.{others}\n`cryo-agent hibernate --complete` finishes this plan only; `cryo-agent focus-plan <name>` picks the plan for the next session.\n",
+                crate::plans::plan_file(&focus.name)
+            )
+        }
+        None => String::new(),
+    };
+
+    let checkpoint_section = match &config.checkpoint {
+        Some(data) => format!(
+            "\n## Checkpoint\n\nSaved with `cryo-agent checkpoint save` in an earlier session:\n\n```json\n{}\n```\n",
+            data.trim_end()
+        ),
+        None => String::new(),
+    };
+
+    let (history_section, history_hint) = match &config.history {
+        Some(log) => (
+            format!("\n## Previous Sessions\n\n```\n{}\n```\n", log.trim_end()),
+            "See Previous Sessions below; cryo.log has the full history",
+        ),
+        None => (String::new(), "Read cryo.log for previous session history"),
+    };
+
+    let (messages_section, inbox_hint) = if config.messages.is_empty() {
+        let hint = if config.require_inbox_ack {
+            "for new messages; read them with `cryo-agent receive`, or they stay unread for the next session"
+        } else {
+            "(or run `cryo-agent receive`) for new messages"
+        };
+        (String::new(), hint)
+    } else {
+        (
+            format_messages(&config.messages, &config.inbox_dir),
+            "(or see Messages below) for new messages",
+        )
+    };
+
+    let attachment_lines: String = config
+        .attachments
+        .iter()
+        .map(|a| format!("\n- Attached file: messages/attachments/{a}"))
+        .collect();
+
     format!(
         r#"# Cryochamber Session
 
 Current time: {current_time}
 Session number: {session_number}
-{delayed}
+{delayed}{plan_changed}{focus}
 ## Instructions
 
 Follow the cryochamber protocol in CLAUDE.md or AGENTS.md. Read plan.md for the full plan.
@@ -100,9 +273,9 @@ Follow the cryochamber protocol in CLAUDE.md or AGENTS.md. Read plan.md for the
 
 ## Context
 
-- Read cryo.log for previous session history
-- Check messages/inbox/ for new messages
-
+- {history_hint}
+- Check {inbox_dir}/ {inbox_hint}{attachments}
+{checkpoint}{messages}{history}
 ## Reminders
 
 - Use `cryo-agent hibernate` to end your session (--wake or --complete)
@@ -111,16 +284,88 @@ Follow the cryochamber protocol in CLAUDE.md or AGENTS.md. Read plan.md for the
 "#,
         session_number = config.session_number,
         delayed = delayed_section,
+        plan_changed = plan_section,
+        focus = focus_section,
+        checkpoint = checkpoint_section,
         task = config.task,
+        attachments = attachment_lines,
+        inbox_dir = config.inbox_dir.trim_end_matches('/'),
+        history = history_section,
+        messages = messages_section,
     )
 }
 
+/// Sort inbox messages by timestamp in `order`, falling back to the
+/// filename so messages sent in the same second keep a stable order on
+/// every platform.
+pub fn sort_messages(messages: &mut [(String, Message)], order: MessageOrder) {
+    messages.sort_by(|(a_name, a), (b_name, b)| {
+        let oldest_first = a
+            .timestamp
+            .cmp(&b.timestamp)
+            .then_with(|| a_name.cmp(b_name));
+        match order {
+            MessageOrder::OldestFirst => oldest_first,
+            MessageOrder::NewestFirst => oldest_first.reverse(),
+        }
+    });
+}
+
+/// Most message text an embedded prompt carries. The prompt is one argv
+/// element, which Linux caps at 128 KiB (MAX_ARG_STRLEN).
+const EMBEDDED_MESSAGES_MAX_BYTES: usize = 64 * 1024;
+
+/// How many of `messages`, in order, an embedded prompt carries in full:
+/// their bodies stay within [`EMBEDDED_MESSAGES_MAX_BYTES`].
+pub fn embedded_message_count(messages: &[(String, Message)]) -> usize {
+    let mut total = 0;
+    messages
+        .iter()
+        .take_while(|(_, msg)| {
+            total += msg.body.len();
+            total <= EMBEDDED_MESSAGES_MAX_BYTES
+        })
+        .count()
+}
+
+/// The "## Messages" prompt section: each unread message with its headers.
+/// Messages past [`embedded_message_count`] are only listed, for the agent
+/// to read from the inbox.
+fn format_messages(messages: &[(String, Message)], inbox_dir: &str) -> String {
+    let mut out = String::from("\n## Messages\n");
+    let (embedded, skipped) = messages.split_at(embedded_message_count(messages));
+    for (filename, msg) in embedded {
+        out.push_str(&format!("\n### {filename}\n\n"));
+        if !msg.from.is_empty() {
+            out.push_str(&format!("From: {}\n", msg.from));
+        }
+        if !msg.subject.is_empty() {
+            out.push_str(&format!("Subject: {}\n", msg.subject));
+        }
+        out.push_str(&format!("\n{}\n", msg.body.trim_end()));
+    }
+    if !skipped.is_empty() {
+        out.push_str(&format!(
+            "\n{} more message(s) too long to include here; read them in {}/ or with `cryo-agent receive`:\n",
+            skipped.len(),
+            inbox_dir.trim_end_matches('/')
+        ));
+        for (filename, _) in skipped {
+            out.push_str(&format!("- {filename}\n"));
+        }
+    }
+    out
+}
+
 /// Build a `Command` for the given agent, ready to execute with the prompt.
-pub fn build_command(agent_command: &str, prompt: &str) -> Result<Command> {
+/// With [`PromptPosition::Stdin`] the prompt is left off the command line
+/// and stdin is piped; the spawn functions write it there.
+pub fn build_command(agent_command: &str, prompt: &str, launch: &LaunchOptions) -> Result<Command> {
     let (kind, program, args) = resolve_agent(agent_command)?;
 
     let mut cmd = Command::new(&program);
     cmd.args(&args);
+    cmd.args(&launch.extra_args);
 
     match kind {
         AgentKind::Claude => {
@@ -128,32 +373,223 @@ pub fn build_command(agent_command: &str, prompt: &str) -> Result<Command> {
         }
         AgentKind::Opencode | AgentKind::Codex | AgentKind::Custom | AgentKind::Mock => {}
     }
-    cmd.arg(prompt);
+    match launch.prompt_position {
+        PromptPosition::Arg => {
+            cmd.arg(prompt);
+        }
+        PromptPosition::Stdin => {
+            cmd.stdin(Stdio::piped());
+        }
+    }
 
     Ok(cmd)
 }
 
+/// The program and arguments `build_command` would run, prompt included
+/// unless it goes to stdin.
+pub fn command_argv(
+    agent_command: &str,
+    prompt: &str,
+    launch: &LaunchOptions,
+) -> Result<Vec<String>> {
+    let cmd = build_command(agent_command, prompt, launch)?;
+    Ok(std::iter::once(cmd.get_program())
+        .chain(cmd.get_args())
+        .map(|a| a.to_string_lossy().into_owned())
+        .collect())
+}
+
 /// Spawn agent as a child process.
 /// Returns the Child handle for the daemon to monitor.
 ///
+/// The agent runs in `work_dir`, the project directory.
 /// If `agent_log` is provided, stdout/stderr are redirected to that file.
 /// Otherwise the child inherits the parent's stdout/stderr.
 ///
 /// Prepends the directory containing the `cryo` binary to PATH so that `cryo-agent`
 /// is discoverable by the agent subprocess (e.g. when running from `target/debug/`).
 pub fn spawn_agent(
+    work_dir: &Path,
     agent_command: &str,
     prompt: &str,
+    launch: &LaunchOptions,
     agent_log: Option<std::fs::File>,
     provider_env: &std::collections::HashMap<String, String>,
 ) -> anyhow::Result<std::process::Child> {
-    let mut cmd = build_command(agent_command, prompt)?;
+    let mut cmd = prepare_command(work_dir, agent_command, prompt, launch, provider_env)?;
 
     if let Some(log) = agent_log {
         let err = log.try_clone()?;
         cmd.stdout(log).stderr(err);
     }
 
+    let mut child = cmd.spawn().map_err(|e| spawn_error(agent_command, e))?;
+    feed_prompt(&mut child, prompt);
+    Ok(child)
+}
+
+/// Threads copying a piped agent's output into its log. Wait for them
+/// after the agent exits, before reading the log.
+#[derive(Default)]
+pub struct OutputCopy {
+    threads: Vec<std::thread::JoinHandle<()>>,
+}
+
+impl OutputCopy {
+    /// Wait up to `timeout` for the copies to reach end of output. A
+    /// grandchild that inherited the pipes can keep them open, so the
+    /// threads still running then are left behind. Returns true if all
+    /// finished.
+    pub fn wait(&mut self, timeout: std::time::Duration) -> bool {
+        let deadline = std::time::Instant::now() + timeout;
+        for thread in std::mem::take(&mut self.threads) {
+            while !thread.is_finished() && std::time::Instant::now() < deadline {
+                std::thread::sleep(std::time::Duration::from_millis(10));
+            }
+            if !thread.is_finished() {
+                return false;
+            }
+            let _ = thread.join();
+        }
+        true
+    }
+}
+
+/// Like [`spawn_agent`], but pipes stdout/stderr through `redactor`
+/// line by line before appending to `agent_log`.
+pub fn spawn_agent_redacted(
+    work_dir: &Path,
+    agent_command: &str,
+    prompt: &str,
+    launch: &LaunchOptions,
+    agent_log: std::fs::File,
+    provider_env: &std::collections::HashMap<String, String>,
+    redactor: &crate::redact::Redactor,
+) -> anyhow::Result<(std::process::Child, OutputCopy)> {
+    spawn_piped(
+        work_dir,
+        agent_command,
+        prompt,
+        launch,
+        agent_log,
+        provider_env,
+        redactor,
+        None,
+    )
+}
+
+/// Like [`spawn_agent_redacted`], but also keeps the scrubbed stdout and
+/// stderr in `capture` for the session recording.
+#[allow(clippy::too_many_arguments)]
+pub fn spawn_agent_recorded(
+    work_dir: &Path,
+    agent_command: &str,
+    prompt: &str,
+    launch: &LaunchOptions,
+    agent_log: std::fs::File,
+    provider_env: &std::collections::HashMap<String, String>,
+    redactor: &crate::redact::Redactor,
+    capture: &crate::recording::OutputCapture,
+) -> anyhow::Result<(std::process::Child, OutputCopy)> {
+    spawn_piped(
+        work_dir,
+        agent_command,
+        prompt,
+        launch,
+        agent_log,
+        provider_env,
+        redactor,
+        Some(capture),
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn spawn_piped(
+    work_dir: &Path,
+    agent_command: &str,
+    prompt: &str,
+    launch: &LaunchOptions,
+    agent_log: std::fs::File,
+    provider_env: &std::collections::HashMap<String, String>,
+    redactor: &crate::redact::Redactor,
+    capture: Option<&crate::recording::OutputCapture>,
+) -> anyhow::Result<(std::process::Child, OutputCopy)> {
+    let mut cmd = prepare_command(work_dir, agent_command, prompt, launch, provider_env)?;
+    cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
+
+    let mut child = cmd.spawn().map_err(|e| spawn_error(agent_command, e))?;
+    feed_prompt(&mut child, prompt);
+
+    let mut copy = OutputCopy::default();
+    if let Some(out) = child.stdout.take() {
+        let sink = capture.map(|c| c.stdout_sink());
+        let log = agent_log.try_clone()?;
+        copy.threads
+            .push(copy_redacted(out, log, redactor.clone(), sink));
+    }
+    if let Some(err) = child.stderr.take() {
+        let sink = capture.map(|c| c.stderr_sink());
+        copy.threads
+            .push(copy_redacted(err, agent_log, redactor.clone(), sink));
+    }
+    Ok((child, copy))
+}
+
+/// Forward `reader` into `log` on a background thread, scrubbing each line
+/// and appending it to `sink` when given.
+fn copy_redacted(
+    reader: impl std::io::Read + Send + 'static,
+    mut log: std::fs::File,
+    redactor: crate::redact::Redactor,
+    sink: Option<std::sync::Arc<std::sync::Mutex<String>>>,
+) -> std::thread::JoinHandle<()> {
+    use std::io::{BufRead, Write};
+    std::thread::spawn(move || {
+        let mut reader = std::io::BufReader::new(reader);
+        let mut buf = Vec::new();
+        while matches!(reader.read_until(b'\n', &mut buf), Ok(n) if n > 0) {
+            let line = redactor.redact(&String::from_utf8_lossy(&buf));
+            if let Some(sink) = &sink {
+                sink.lock().unwrap().push_str(&line);
+            }
+            if log.write_all(line.as_bytes()).is_err() {
+                break;
+            }
+            buf.clear();
+        }
+    })
+}
+
+/// Write the prompt to a child spawned with piped stdin
+/// ([`PromptPosition::Stdin`]), then close it. Runs on a thread so a large
+/// prompt can't block the daemon on an agent that reads slowly.
+fn feed_prompt(child: &mut std::process::Child, prompt: &str) {
+    if let Some(mut stdin) = child.stdin.take() {
+        let prompt = prompt.to_string();
+        std::thread::spawn(move || {
+            use std::io::Write;
+            let _ = stdin.write_all(prompt.as_bytes());
+        });
+    }
+}
+
+/// Build the agent command with working directory, PATH and provider env applied.
+fn prepare_command(
+    work_dir: &Path,
+    agent_command: &str,
+    prompt: &str,
+    launch: &LaunchOptions,
+    provider_env: &std::collections::HashMap<String, String>,
+) -> anyhow::Result<Command> {
+    let mut cmd = build_command(agent_command, prompt, launch)?;
+    cmd.current_dir(work_dir);
+    if launch.process_group {
+        // Its PID doubles as the group ID, so the daemon can signal the
+        // agent together with everything it forked
+        use std::os::unix::process::CommandExt;
+        cmd.process_group(0);
+    }
+
     if let Ok(exe) = std::env::current_exe() {
         if let Some(bin_dir) = exe.parent() {
             let path = std::env::var("PATH").unwrap_or_default();
@@ -167,8 +603,5 @@ pub fn spawn_agent(
         cmd.envs(provider_env);
     }
 
-    let child = cmd
-        .spawn()
-        .map_err(|e| anyhow::anyhow!("Failed to spawn agent: {e}"))?;
-    Ok(child)
+    Ok(cmd)
 }
@@ -6,7 +6,9 @@ use std::path::Path;
 use cryochamber::config;
 use cryochamber::message;
 use cryochamber::protocol;
+use cryochamber::recording;
 use cryochamber::state::{self, CryoState};
+use cryochamber::store;
 
 #[derive(Parser)]
 #[command(name = "cryo", about = "Long-term AI agent task scheduler")]
@@ -20,8 +22,19 @@ enum Commands {
     /// Initialize a working directory with protocol file and template plan
     Init {
         /// Agent command to target (determines CLAUDE.md vs AGENTS.md)
-        #[arg(long, default_value = "opencode")]
-        agent: String,
+        /// [default: $CRYO_AGENT, else opencode]
+        #[arg(long)]
+        agent: Option<String>,
+        /// House rules merged into the protocol file
+        /// [default: ~/.config/cryo/protocol.md, if present]
+        #[arg(long, value_name = "PATH")]
+        protocol_template: Option<std::path::PathBuf>,
+        /// Copy plan.md from a local file instead of the template
+        #[arg(long, value_name = "PATH", conflicts_with = "plan_url")]
+        plan: Option<std::path::PathBuf>,
+        /// Fetch plan.md over HTTP (a raw gist or wiki URL)
+        #[arg(long, value_name = "URL")]
+        plan_url: Option<String>,
     },
     /// Begin a new plan: initialize and run the first task
     Start {
@@ -34,6 +47,18 @@ enum Commands {
         /// Maximum session duration in seconds (overrides cryo.toml)
         #[arg(long)]
         max_session_duration: Option<u64>,
+        /// Task for the first session (overrides `first_task` in cryo.toml)
+        #[arg(long)]
+        task: Option<String>,
+        /// Run a single session in the foreground and exit (for cron/CI)
+        #[arg(long)]
+        once: bool,
+        /// Continue a previous run from its session number and schedule
+        #[arg(long, conflicts_with = "fresh")]
+        resume: bool,
+        /// Discard a previous run's state and start again at session 1
+        #[arg(long)]
+        fresh: bool,
     },
     /// Show current status: next wake time, last result
     Status,
@@ -42,9 +67,22 @@ enum Commands {
         /// Kill all listed daemons
         #[arg(long)]
         kill_all: bool,
+        /// Only list crashed, blocked or failing daemons
+        #[arg(long)]
+        unhealthy: bool,
+    },
+    /// Kill the running daemon and restart it, optionally with new overrides
+    Restart {
+        /// Agent command to use from now on (overrides cryo.toml)
+        #[arg(long)]
+        agent: Option<String>,
+        /// Max retry attempts on agent spawn failure (overrides cryo.toml)
+        #[arg(long)]
+        max_retries: Option<u32>,
+        /// Maximum session duration in seconds (overrides cryo.toml)
+        #[arg(long)]
+        max_session_duration: Option<u64>,
     },
-    /// Kill the running daemon and restart it
-    Restart,
     /// Stop the daemon and remove state
     Cancel,
     /// Stop the daemon and remove all runtime files (confirms first)
@@ -52,9 +90,26 @@ enum Commands {
         /// Skip confirmation prompt
         #[arg(long)]
         force: bool,
+        /// Keep the last session number so the next `cryo start` continues from it
+        #[arg(long)]
+        keep_session_number: bool,
+    },
+    /// View or replace the project's plan.md
+    Plan {
+        #[command(subcommand)]
+        action: PlanAction,
     },
     /// Print the session log
-    Log,
+    Log {
+        /// Show secrets verbatim instead of masking them as ***
+        #[arg(long)]
+        no_redact: bool,
+    },
+    /// Print a saved session recording (prompt, argv, output); needs `record_session = true`
+    Replay {
+        /// Session number (default: the latest recording)
+        session: Option<u32>,
+    },
     /// Watch the session log in real-time
     Watch {
         /// Show full log from the beginning (default: start from current position)
@@ -74,17 +129,46 @@ enum Commands {
         /// Message subject (default: derived from body)
         #[arg(long)]
         subject: Option<String>,
+        /// Answer the agent's question with this ID (see `cryo-agent wait-for-reply`)
+        #[arg(long)]
+        reply_to: Option<String>,
+        /// Attach a file (repeatable); it is copied to messages/attachments/
+        #[arg(long = "attach", value_name = "FILE")]
+        attachments: Vec<std::path::PathBuf>,
         /// Wake the agent immediately after sending
         #[arg(long)]
         wake: bool,
+        /// Don't wake the agent, even with `auto_wake_on_send = true`
+        #[arg(long, conflicts_with = "wake")]
+        no_wake: bool,
     },
     /// Read messages from the agent's outbox
-    Receive,
+    Receive {
+        /// Show already-delivered messages and where each one was posted
+        #[arg(long)]
+        delivered: bool,
+        /// Print the messages as a JSON array (`[]` when there are none)
+        #[arg(long)]
+        json: bool,
+        /// Exit with code 5 when there are no messages
+        #[arg(long)]
+        fail_if_empty: bool,
+    },
     /// Send a wake message to the daemon's inbox
     Wake {
         /// Message to include in the agent's prompt
         message: Option<String>,
     },
+    /// Manage the message store
+    Messages {
+        #[command(subcommand)]
+        action: MessagesAction,
+    },
+    /// List, approve or reject the agent's review requests
+    Review {
+        #[command(subcommand)]
+        action: ReviewAction,
+    },
     /// Execute a fallback action (internal — used by timers)
     #[command(hide = true)]
     FallbackExec {
@@ -100,6 +184,9 @@ enum Commands {
         /// Port to listen on (overrides cryo.toml web_port)
         #[arg(long)]
         port: Option<u16>,
+        /// Log poll interval in milliseconds (overrides cryo.toml web_poll_ms)
+        #[arg(long)]
+        poll_ms: Option<u64>,
         /// Run in foreground instead of installing a service
         #[arg(long, conflicts_with = "stop")]
         foreground: bool,
@@ -117,42 +204,233 @@ enum Commands {
         host: String,
         #[arg(long)]
         port: u16,
+        #[arg(long, default_value_t = 500)]
+        poll_ms: u64,
     },
 }
 
-fn main() -> Result<()> {
+#[derive(Subcommand)]
+enum PlanAction {
+    /// Print the current plan.md
+    Show,
+    /// Copy an external plan file into the project as plan.md
+    Set {
+        /// Path to the plan file
+        path: String,
+        /// Overwrite plan.md even if it was edited more recently than <path>
+        #[arg(long)]
+        force: bool,
+    },
+    /// Open plan.md in $EDITOR
+    Edit,
+}
+
+#[derive(Subcommand)]
+enum MessagesAction {
+    /// Import file-based messages into messages/messages.db
+    Migrate,
+}
+
+#[derive(Subcommand)]
+enum ReviewAction {
+    /// Show review requests (pending ones unless --all)
+    List {
+        /// Include approved and rejected reviews
+        #[arg(long)]
+        all: bool,
+    },
+    /// Approve a pending review and wake the agent
+    Approve {
+        /// Review ID
+        id: String,
+        /// Note passed to the agent with the decision
+        #[arg(long)]
+        note: Option<String>,
+        /// Who decided (default: human)
+        #[arg(long, default_value = "human")]
+        from: String,
+    },
+    /// Reject a pending review and wake the agent
+    Reject {
+        /// Review ID
+        id: String,
+        /// Note passed to the agent with the decision
+        #[arg(long)]
+        note: Option<String>,
+        /// Who decided (default: human)
+        #[arg(long, default_value = "human")]
+        from: String,
+    },
+}
+
+/// Stable process exit codes for scripts wrapping `cryo`.
+/// Any error without a more specific code exits with 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Exit {
+    /// Config or usage error: no project, no plan.md, bad cryo.toml or agent
+    Usage = 2,
+    /// A daemon is already running in this directory
+    AlreadyRunning = 3,
+    /// The session ran but the agent failed (`cryo start --once`)
+    SessionFailed = 4,
+    /// Nothing to read (`cryo receive --fail-if-empty`)
+    Empty = 5,
+    /// The session never started, e.g. the socket could not be bound
+    /// (`cryo start --once`)
+    CouldNotStart = 6,
+}
+
+/// An error tagged with the exit code `main` should return.
+#[derive(Debug)]
+struct ExitError {
+    code: Exit,
+    error: anyhow::Error,
+}
+
+impl std::fmt::Display for ExitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.error)
+    }
+}
+
+impl std::error::Error for ExitError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.error.source()
+    }
+}
+
+trait WithExit<T> {
+    /// Tag the error (if any) with an exit code.
+    fn exit_code(self, code: Exit) -> Result<T>;
+}
+
+impl<T, E: Into<anyhow::Error>> WithExit<T> for std::result::Result<T, E> {
+    fn exit_code(self, code: Exit) -> Result<T> {
+        self.map_err(|e| {
+            ExitError {
+                code,
+                error: e.into(),
+            }
+            .into()
+        })
+    }
+}
+
+fn main() {
     let cli = Cli::parse();
+    if let Err(e) = run(cli) {
+        eprintln!("Error: {e:?}");
+        let code = e.downcast_ref::<ExitError>().map_or(1, |e| e.code as i32);
+        std::process::exit(code);
+    }
+}
 
+fn run(cli: Cli) -> Result<()> {
     match cli.command {
-        Commands::Init { agent } => cmd_init(&agent),
+        Commands::Init {
+            agent,
+            protocol_template,
+            plan,
+            plan_url,
+        } => cmd_init(
+            &agent.unwrap_or_else(config::default_agent),
+            protocol_template.as_deref(),
+            plan.as_deref(),
+            plan_url.as_deref(),
+        ),
         Commands::Start {
             agent,
             max_retries,
             max_session_duration,
-        } => cmd_start(agent, max_retries, max_session_duration),
+            task,
+            once,
+            resume,
+            fresh,
+        } => cmd_start(
+            agent,
+            max_retries,
+            max_session_duration,
+            task,
+            once,
+            StartMode::from_flags(resume, fresh),
+        ),
         Commands::Status => cmd_status(),
-        Commands::Ps { kill_all } => cmd_ps(kill_all),
-        Commands::Restart => cmd_restart(),
+        Commands::Ps {
+            kill_all,
+            unhealthy,
+        } => cmd_ps(kill_all, unhealthy),
+        Commands::Restart {
+            agent,
+            max_retries,
+            max_session_duration,
+        } => cmd_restart(agent, max_retries, max_session_duration),
         Commands::Cancel => cmd_cancel(),
-        Commands::Clean { force } => cmd_clean(force),
-        Commands::Log => cmd_log(),
+        Commands::Clean {
+            force,
+            keep_session_number,
+        } => cmd_clean(force, keep_session_number),
+        Commands::Plan { action } => match action {
+            PlanAction::Show => cmd_plan_show(),
+            PlanAction::Set { path, force } => cmd_plan_set(&path, force),
+            PlanAction::Edit => cmd_plan_edit(),
+        },
+        Commands::Messages { action } => match action {
+            MessagesAction::Migrate => cmd_messages_migrate(),
+        },
+        Commands::Review { action } => match action {
+            ReviewAction::List { all } => cmd_review_list(all),
+            ReviewAction::Approve { id, note, from } => {
+                cmd_review_decide(&id, true, note.as_deref(), &from)
+            }
+            ReviewAction::Reject { id, note, from } => {
+                cmd_review_decide(&id, false, note.as_deref(), &from)
+            }
+        },
+        Commands::Log { no_redact } => cmd_log(no_redact),
+        Commands::Replay { session } => cmd_replay(session),
         Commands::Watch { all, viewpoint } => cmd_watch(all, &viewpoint),
         Commands::Send {
             body,
             from,
             subject,
+            reply_to,
+            attachments,
             wake,
-        } => cmd_send(&body, &from, subject.as_deref(), wake),
+            no_wake,
+        } => cmd_send(
+            &body,
+            &from,
+            subject.as_deref(),
+            reply_to.as_deref(),
+            &attachments,
+            wake,
+            no_wake,
+        ),
         Commands::Wake { message } => cmd_wake(message.as_deref()),
         Commands::Web {
             host,
             port,
+            poll_ms,
             foreground,
             stop,
-        } => cmd_web(host, port, foreground, stop),
+        } => cmd_web(host, port, poll_ms, foreground, stop),
         Commands::Daemon => cmd_daemon(),
-        Commands::WebDaemon { host, port } => cmd_web_daemon(host, port),
-        Commands::Receive => cmd_receive(),
+        Commands::WebDaemon {
+            host,
+            port,
+            poll_ms,
+        } => cmd_web_daemon(host, port, poll_ms),
+        Commands::Receive {
+            delivered,
+            json,
+            fail_if_empty,
+        } => {
+            if delivered {
+                cmd_receive_delivered(json, fail_if_empty)
+            } else {
+                cmd_receive(json, fail_if_empty)
+            }
+        }
         Commands::FallbackExec {
             action,
             target,
@@ -166,7 +444,12 @@ fn main() -> Result<()> {
             };
             let config = cryochamber::config::load_config(&cryochamber::config::config_path(&dir))?
                 .unwrap_or_default();
-            fb.execute(&dir, &config.fallback_alert)
+            let group = cryochamber::notifier::NotificationGroup::for_project(
+                &config.project_name(&dir),
+                &dir,
+            );
+            let notifier = cryochamber::notifier::Notifier::probe().with_group(group);
+            fb.execute(&dir, &config.fallback_alert, &notifier)
         }
     }
 }
@@ -174,7 +457,10 @@ fn main() -> Result<()> {
 /// Check that this directory is a valid cryo project (cryo.toml must exist).
 fn require_valid_project(dir: &Path) -> Result<()> {
     if !config::config_path(dir).exists() {
-        anyhow::bail!("No cryochamber project in this directory. Run `cryo init` first.");
+        return Err(anyhow::anyhow!(
+            "No cryochamber project in this directory. Run `cryo init` first."
+        ))
+        .exit_code(Exit::Usage);
     }
     Ok(())
 }
@@ -193,9 +479,38 @@ fn require_live_daemon(dir: &Path) -> Result<CryoState> {
     Ok(cryo_state)
 }
 
-fn cmd_init(agent_cmd: &str) -> Result<()> {
+fn cmd_init(
+    agent_cmd: &str,
+    protocol_template: Option<&Path>,
+    plan: Option<&Path>,
+    plan_url: Option<&str>,
+) -> Result<()> {
     let dir = cryochamber::work_dir()?;
 
+    // Read the plan source before writing anything, so a bad path or URL
+    // doesn't leave a half-initialized project behind. An existing plan.md
+    // is kept, so there is nothing to read then.
+    let plan_kept = dir.join("plan.md").exists();
+    let plan_source = match (plan, plan_url) {
+        _ if plan_kept => None,
+        (Some(path), _) => {
+            let content = std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read plan {}", path.display()))
+                .exit_code(Exit::Usage)?;
+            if content.trim().is_empty() {
+                return Err(anyhow::anyhow!("{} is empty", path.display())).exit_code(Exit::Usage);
+            }
+            Some((path.display().to_string(), content))
+        }
+        (None, Some(url)) => {
+            let config = config::load_config(&config::config_path(&dir))?.unwrap_or_default();
+            let agent = cryochamber::channel::http_agent(&config)?;
+            let content = protocol::fetch_plan(url, &agent, protocol::PLAN_FETCH_TIMEOUT)?;
+            Some((url.to_string(), content))
+        }
+        (None, None) => None,
+    };
+
     // Write cryo.toml first (project config)
     if protocol::write_config_file(&dir, agent_cmd)? {
         println!("  cryo.toml (created)");
@@ -204,16 +519,43 @@ fn cmd_init(agent_cmd: &str) -> Result<()> {
     }
 
     let filename = protocol::protocol_filename(agent_cmd);
-    if protocol::write_protocol_file(&dir, filename)? {
-        println!("  {filename} (created)");
+    let template = protocol::load_template(protocol_template).exit_code(Exit::Usage)?;
+    if let Some((path, content)) = &template {
+        for warning in protocol::template_warnings(content) {
+            eprintln!("Warning: {}: {warning}", path.display());
+        }
+    }
+    let template_content = template.as_ref().map(|(_, content)| content.as_str());
+    if protocol::write_protocol_file_with(&dir, filename, template_content)? {
+        match &template {
+            Some((path, _)) => println!("  {filename} (created, template {})", path.display()),
+            None => println!("  {filename} (created)"),
+        }
     } else {
         println!("  {filename} (exists, kept)");
     }
 
-    if protocol::write_template_plan(&dir)? {
-        println!("  plan.md (created)");
-    } else {
-        println!("  plan.md (exists, kept)");
+    match &plan_source {
+        Some((source, content)) => {
+            for warning in protocol::plan_warnings(content) {
+                eprintln!("Warning: {source}: {warning}");
+            }
+            if protocol::write_plan(&dir, content)? {
+                println!("  plan.md (created from {source})");
+            } else {
+                println!("  plan.md (exists, kept; {source} not used)");
+            }
+        }
+        None => {
+            let unused = plan.map(|path| path.display().to_string());
+            if protocol::write_template_plan(&dir)? {
+                println!("  plan.md (created)");
+            } else if let Some(source) = unused.as_deref().or(plan_url) {
+                println!("  plan.md (exists, kept; {source} not used)");
+            } else {
+                println!("  plan.md (exists, kept)");
+            }
+        }
     }
 
     if protocol::write_readme(&dir)? {
@@ -222,7 +564,7 @@ fn cmd_init(agent_cmd: &str) -> Result<()> {
         println!("  README.md (exists, kept)");
     }
 
-    message::ensure_dirs(&dir)?;
+    store::open(&dir)?.ensure()?;
 
     println!("\nCryochamber initialized. Next steps:");
     println!("  1. Edit plan.md with your task plan");
@@ -248,42 +590,125 @@ fn validate_agent_command(agent_cmd: &str) -> Result<()> {
     }
 }
 
+/// How `cryo start` treats state left by a previous run.
+#[derive(Clone, Copy, PartialEq)]
+enum StartMode {
+    /// `--resume`: keep the session number, schedule and overrides.
+    Resume,
+    /// `--fresh`: start again at session 1.
+    Fresh,
+    /// Neither flag: ask on a terminal, otherwise start fresh
+    /// (`--once` keeps counting, for external schedulers).
+    Ask,
+}
+
+impl StartMode {
+    fn from_flags(resume: bool, fresh: bool) -> Self {
+        match (resume, fresh) {
+            (true, _) => StartMode::Resume,
+            (_, true) => StartMode::Fresh,
+            _ => StartMode::Ask,
+        }
+    }
+}
+
+/// Decide whether to continue `existing`, the state of a stopped daemon.
+fn resolve_start_mode(mode: StartMode, existing: &CryoState, once: bool) -> bool {
+    use std::io::IsTerminal;
+
+    match mode {
+        StartMode::Resume => true,
+        StartMode::Fresh => false,
+        StartMode::Ask if once => true,
+        StartMode::Ask if std::io::stdin().is_terminal() => {
+            let wake = match &existing.next_wake {
+                Some(wake) => format!(", next wake {wake}"),
+                None => String::new(),
+            };
+            confirm(&format!(
+                "Found state from a previous run (session {}{wake}). Resume it?",
+                existing.session_number
+            ))
+        }
+        StartMode::Ask => {
+            eprintln!(
+                "Note: starting fresh; a previous run reached session {}. Pass --resume to continue it.",
+                existing.session_number
+            );
+            false
+        }
+    }
+}
+
 fn cmd_start(
     agent_override: Option<String>,
     max_retries_override: Option<u32>,
     max_session_duration_override: Option<u64>,
+    first_task_override: Option<String>,
+    once: bool,
+    mode: StartMode,
 ) -> Result<()> {
     let dir = cryochamber::work_dir()?;
 
     // Require init: protocol file or cryo.toml must exist
     require_valid_project(&dir)?;
 
-    // Require plan.md in the working directory
-    if !dir.join("plan.md").exists() {
-        anyhow::bail!("No plan.md found in the working directory. Create one or run `cryo init`.");
+    // Require plan.md (or plans/) in the working directory
+    if !dir.join("plan.md").exists() && cryochamber::plans::list(&dir)?.is_empty() {
+        return Err(anyhow::anyhow!(
+            "No plan.md found in the working directory. Create one or run `cryo init`."
+        ))
+        .exit_code(Exit::Usage);
     }
 
     // Guard: refuse to start if an instance is already active
-    if let Some(existing) = state::load_state(&state::state_path(&dir))? {
-        if state::is_locked(&existing) {
-            anyhow::bail!(
+    let existing = state::load_state(&state::state_path(&dir))?;
+    if let Some(existing) = &existing {
+        if state::is_locked(existing) {
+            return Err(anyhow::anyhow!(
                 "A cryochamber session is already running (PID: {:?}). Use `cryo cancel` to stop it first.",
                 existing.pid
-            );
+            ))
+            .exit_code(Exit::AlreadyRunning);
         }
     }
+    // A stopped daemon's state: continue it or start over
+    let previous = existing.filter(|st| st.session_number > 0);
+    let resume = match &previous {
+        Some(st) => resolve_start_mode(mode, st, once),
+        None => false,
+    };
+    if mode == StartMode::Fresh {
+        // Also drop the number kept by `cryo clean --keep-session-number`
+        let _ = std::fs::remove_file(state::session_counter_path(&dir));
+    }
 
     // Load config from cryo.toml (fall back to defaults for legacy projects)
-    let cfg = config::load_config(&config::config_path(&dir))?.unwrap_or_default();
+    let cfg = config::load_config(&config::config_path(&dir))
+        .exit_code(Exit::Usage)?
+        .unwrap_or_default();
+    for warning in config::config_warnings(&config::config_path(&dir))? {
+        eprintln!("Warning: cryo.toml: {warning}");
+    }
+    for warning in cryochamber::plan::PlanFrontMatter::load(&dir).1 {
+        eprintln!("Warning: plan.md: {warning}");
+    }
 
-    // Resolve effective values: CLI override > cryo.toml > hardcoded default
+    // Resolve effective values: CLI override > cryo.toml > CRYO_AGENT > hardcoded default.
+    // An agent that came from CRYO_AGENT is pinned as an override, since the
+    // daemon may run as a service without this shell's environment.
+    let agent_override = agent_override.or_else(|| config::env_agent().filter(|a| *a == cfg.agent));
     let effective_agent = agent_override.as_deref().unwrap_or(&cfg.agent);
 
     // Validate agent command using effective agent value
-    validate_agent_command(effective_agent)?;
+    validate_agent_command(effective_agent).exit_code(Exit::Usage)?;
+    cfg.check_env_files(&dir).exit_code(Exit::Usage)?;
 
     // Ensure message dirs exist (needed for inbox watching)
-    message::ensure_dirs(&dir)?;
+    store::open(&dir)?.ensure()?;
+
+    // A result from a previous run would be mistaken for this one's
+    let _ = std::fs::remove_file(state::result_path(&dir));
 
     // Build slim CryoState with override fields only when CLI flags were explicitly provided
     let cryo_state = CryoState {
@@ -293,11 +718,80 @@ fn cmd_start(
         agent_override,
         max_retries_override,
         max_session_duration_override,
+        first_task_override,
         next_wake: None,
         last_report_time: None,
         provider_index: None,
+        ..Default::default()
+    };
+
+    let previous = previous.filter(|_| resume);
+    // A checkpoint belongs to the run that saved it
+    if previous.is_none() {
+        let _ = std::fs::remove_file(state::checkpoint_path(&dir));
+    }
+
+    if once {
+        // Keep counting sessions across runs driven by an external scheduler
+        let cryo_state = CryoState {
+            session_number: previous.as_ref().map_or(0, |st| st.session_number),
+            provider_index: previous.as_ref().and_then(|st| st.provider_index),
+            schedule: previous.as_ref().and_then(|st| st.schedule.clone()),
+            plan_started_at: previous.as_ref().and_then(|st| st.plan_started_at.clone()),
+            plan_hash: previous.as_ref().and_then(|st| st.plan_hash.clone()),
+            last_outcome: previous.as_ref().and_then(|st| st.last_outcome.clone()),
+            focus_plan: previous.as_ref().and_then(|st| st.focus_plan.clone()),
+            focus_request: previous.as_ref().and_then(|st| st.focus_request.clone()),
+            completed_plans: previous
+                .as_ref()
+                .map(|st| st.completed_plans.clone())
+                .unwrap_or_default(),
+            plan_tasks: previous
+                .as_ref()
+                .map(|st| st.plan_tasks.clone())
+                .unwrap_or_default(),
+            inbox_unhandled: previous
+                .as_ref()
+                .map(|st| st.inbox_unhandled.clone())
+                .unwrap_or_default(),
+            provider_rotation: previous
+                .as_ref()
+                .and_then(|st| st.provider_rotation.clone()),
+            session_day: previous.as_ref().and_then(|st| st.session_day.clone()),
+            sessions_today: previous.as_ref().map_or(0, |st| st.sessions_today),
+            first_task_override: cryo_state.first_task_override.clone().or_else(|| {
+                previous
+                    .as_ref()
+                    .and_then(|st| st.first_task_override.clone())
+            }),
+            ..cryo_state
+        };
+        state::save_state(&state::state_path(&dir), &cryo_state)?;
+        return run_once(dir);
+    }
+    let cryo_state = match previous {
+        Some(mut st) => {
+            st.merge_overrides(
+                cryo_state.agent_override,
+                max_retries_override,
+                max_session_duration_override,
+            );
+            println!("Resuming after session {}.", st.session_number);
+            if cryo_state.first_task_override.is_some() {
+                eprintln!("Note: --task only applies to a plan's first session; ignored.");
+            }
+            CryoState {
+                pid: None,
+                retry_count: 0,
+                blocked_reason: None,
+                ..st
+            }
+        }
+        None => cryo_state,
     };
     state::save_state(&state::state_path(&dir), &cryo_state)?;
+    let log_path = cryochamber::log::log_path(&dir);
+    let sessions_before = cryochamber::log::session_count(&log_path)?;
 
     // CRYO_NO_SERVICE=1 disables OS service installation (useful for tests / debugging)
     if std::env::var("CRYO_NO_SERVICE").is_ok() {
@@ -305,7 +799,7 @@ fn cmd_start(
         println!("Cryochamber started (background process).");
     } else {
         let exe = std::env::current_exe().context("Failed to resolve cryo executable path")?;
-        let log_path = cryochamber::log::log_path(&dir);
+        warn_service_persistence();
         cryochamber::service::install("daemon", &dir, &exe, &["daemon"], &log_path, false)?;
         println!("Cryochamber started (service installed, survives reboot).");
     }
@@ -319,6 +813,10 @@ fn cmd_start(
                 break;
             }
         }
+        // A short plan can finish (and release the lock) before we look
+        if cryochamber::log::session_count(&log_path)? > sessions_before {
+            break;
+        }
         if std::time::Instant::now() > deadline {
             anyhow::bail!("Daemon did not start within 10 seconds. Check cryo.log for errors.");
         }
@@ -330,19 +828,72 @@ fn cmd_start(
     Ok(())
 }
 
+/// Run one session in the foreground; exit non-zero if the agent failed.
+fn run_once(dir: std::path::PathBuf) -> Result<()> {
+    use cryochamber::daemon::{AlreadyRunning, CouldNotStart, SessionLoopOutcome};
+
+    let store = store::open(&dir)?;
+    let daemon = cryochamber::daemon::Daemon::new(dir, store);
+    let outcome = match daemon.run_once() {
+        Ok(outcome) => outcome,
+        Err(e) if e.chain().any(|c| c.is::<AlreadyRunning>()) => {
+            return Err(e).exit_code(Exit::AlreadyRunning)
+        }
+        Err(e) if e.is::<CouldNotStart>() => return Err(e).exit_code(Exit::CouldNotStart),
+        Err(e) => return Err(e).exit_code(Exit::SessionFailed),
+    };
+    match outcome {
+        SessionLoopOutcome::PlanComplete => println!("Session complete: plan complete."),
+        SessionLoopOutcome::Hibernate { wake_time, .. } => println!(
+            "Session complete: agent requested next wake at {}.",
+            wake_time.format("%Y-%m-%dT%H:%M")
+        ),
+        SessionLoopOutcome::AwaitMessage { .. } => {
+            println!("Session complete: agent is waiting for a message.")
+        }
+        SessionLoopOutcome::Aborted { .. } => {
+            println!("Session aborted by the agent. See cryo.log.")
+        }
+        SessionLoopOutcome::Held { until, reason } => println!(
+            "No session started: {reason}. Next session at {} or later.",
+            until.format("%Y-%m-%dT%H:%M")
+        ),
+        SessionLoopOutcome::ValidationFailed { .. } => {
+            return Err(anyhow::anyhow!(
+                "Session failed: agent exited without hibernating. See cryo.log."
+            ))
+            .exit_code(Exit::SessionFailed);
+        }
+        SessionLoopOutcome::PlanMissing => {
+            return Err(anyhow::anyhow!(
+                "Session skipped: plan.md is missing or empty."
+            ))
+            .exit_code(Exit::SessionFailed);
+        }
+    }
+    Ok(())
+}
+
 fn cmd_daemon() -> Result<()> {
     let dir = cryochamber::work_dir()?;
-    let daemon = cryochamber::daemon::Daemon::new(dir);
-    daemon.run()
+    let store = store::open(&dir)?;
+    cryochamber::Daemon::new(dir, store).run()
 }
 
-fn cmd_web(host: Option<String>, port: Option<u16>, foreground: bool, stop: bool) -> Result<()> {
+fn cmd_web(
+    host: Option<String>,
+    port: Option<u16>,
+    poll_ms: Option<u64>,
+    foreground: bool,
+    stop: bool,
+) -> Result<()> {
     let dir = cryochamber::work_dir()?;
     require_valid_project(&dir)?;
 
     let cfg = config::load_config(&config::config_path(&dir))?.unwrap_or_default();
     let host = host.unwrap_or(cfg.web_host);
     let port = port.unwrap_or(cfg.web_port);
+    let poll_ms = poll_ms.unwrap_or(cfg.web_poll_ms);
 
     if stop {
         if cryochamber::service::uninstall("web", &dir)? {
@@ -355,16 +906,25 @@ fn cmd_web(host: Option<String>, port: Option<u16>, foreground: bool, stop: bool
 
     if foreground {
         let rt = tokio::runtime::Runtime::new()?;
-        rt.block_on(cryochamber::web::serve(dir, &host, port))
+        rt.block_on(cryochamber::web::serve(dir, &host, port, poll_ms))
     } else {
         let exe = std::env::current_exe().context("Failed to resolve cryo executable path")?;
         let port_str = port.to_string();
+        let poll_str = poll_ms.to_string();
         let log_path = dir.join("cryo-web.log");
         cryochamber::service::install(
             "web",
             &dir,
             &exe,
-            &["web-daemon", "--host", &host, "--port", &port_str],
+            &[
+                "web-daemon",
+                "--host",
+                &host,
+                "--port",
+                &port_str,
+                "--poll-ms",
+                &poll_str,
+            ],
             &log_path,
             true,
         )?;
@@ -375,17 +935,19 @@ fn cmd_web(host: Option<String>, port: Option<u16>, foreground: bool, stop: bool
     }
 }
 
-fn cmd_web_daemon(host: String, port: u16) -> Result<()> {
+fn cmd_web_daemon(host: String, port: u16, poll_ms: u64) -> Result<()> {
     let dir = cryochamber::work_dir()?;
     let rt = tokio::runtime::Runtime::new()?;
-    rt.block_on(cryochamber::web::serve(dir, &host, port))
+    rt.block_on(cryochamber::web::serve(dir, &host, port, poll_ms))
 }
 
 fn cmd_status() -> Result<()> {
     let dir = cryochamber::work_dir()?;
     require_valid_project(&dir)?;
 
-    let cfg = config::load_config(&config::config_path(&dir))?.unwrap_or_default();
+    let cfg = config::load_config(&config::config_path(&dir))
+        .exit_code(Exit::Usage)?
+        .unwrap_or_default();
 
     match state::load_state(&state::state_path(&dir))? {
         None => {
@@ -394,19 +956,70 @@ fn cmd_status() -> Result<()> {
             println!("  Agent: {}", cfg.agent);
         }
         Some(st) => {
+            println!("Project: {}", cfg.project_name(&dir));
             // Runtime state first
-            println!(
-                "Daemon: {}",
-                if state::is_locked(&st) {
-                    "running"
-                } else {
-                    "stopped"
-                }
-            );
+            if state::is_locked(&st) {
+                println!("Daemon: running");
+            } else if let Some(stopped) = st.stop_description() {
+                println!("Daemon: stopped ({stopped})");
+            } else {
+                println!("Daemon: stopped");
+            }
             println!("Session: {}", st.session_number);
             if let Some(pid) = st.pid {
                 println!("PID: {pid}");
             }
+            if let Some(reason) = &st.blocked_reason {
+                println!("Blocked: {reason}");
+            }
+            if let Some(result) = st.last_result() {
+                println!("Last result: {result}");
+            }
+            if state::result_path(&dir).exists() {
+                println!("Result file: .cryo/result.json");
+            }
+            if st.awaiting_message {
+                println!("Next wake: waiting for message");
+            } else if let Some(wake) = &st.next_wake {
+                println!("Next wake: {wake}");
+            }
+            if let Some(schedule) = &st.schedule {
+                println!("Schedule: every \"{schedule}\"");
+            }
+            if let Some(elapsed) = st.plan_elapsed(chrono::Local::now().naive_local()) {
+                let elapsed = cryochamber::web::format_relative_time(elapsed.num_milliseconds());
+                if cfg.max_total_runtime_hours > 0 {
+                    println!(
+                        "Plan runtime: {elapsed} of {}h",
+                        cfg.max_total_runtime_hours
+                    );
+                } else {
+                    println!("Plan runtime: {elapsed}");
+                }
+            }
+            let plans = cryochamber::plans::list(&dir)?;
+            if !plans.is_empty() {
+                let done = plans
+                    .iter()
+                    .filter(|p| st.completed_plans.contains(p))
+                    .count();
+                println!("Plans: {done} of {} complete", plans.len());
+                for name in &plans {
+                    if st.completed_plans.contains(name) {
+                        println!("  {name}: complete");
+                        continue;
+                    }
+                    let focus = if st.focus_plan.as_ref() == Some(name) {
+                        " (focus)"
+                    } else {
+                        ""
+                    };
+                    match st.plan_tasks.get(name) {
+                        Some(task) => println!("  {name}{focus}: {task}"),
+                        None => println!("  {name}{focus}: not started"),
+                    }
+                }
+            }
 
             // Config
             let effective_agent = st.agent_override.as_deref().unwrap_or(&cfg.agent);
@@ -424,6 +1037,13 @@ fn cmd_status() -> Result<()> {
                         cfg.providers.len()
                     );
                 }
+                if let Some(reason) = &st.provider_rotation {
+                    let next = cfg.providers.get((idx + 1) % cfg.providers.len());
+                    println!(
+                        "  rotation pending: next session uses {} (\"{reason}\")",
+                        next.map_or("unknown", |p| p.name.as_str())
+                    );
+                }
             }
             let effective_timeout = st
                 .max_session_duration_override
@@ -431,6 +1051,16 @@ fn cmd_status() -> Result<()> {
             if effective_timeout > 0 {
                 println!("Session timeout: {effective_timeout}s");
             }
+            println!(
+                "Inbox: {}",
+                if cfg.watch_inbox {
+                    "new messages wake the agent (watch_inbox)"
+                } else if cfg.auto_wake_on_send {
+                    "`cryo send` wakes the agent (auto_wake_on_send)"
+                } else {
+                    "messages wait for the next wake (use `cryo send --wake`)"
+                }
+            );
 
             let log = cryochamber::log::log_path(&dir);
             if let Some(latest) = cryochamber::log::read_latest_session(&log)? {
@@ -447,57 +1077,176 @@ fn cmd_status() -> Result<()> {
     Ok(())
 }
 
-fn cmd_restart() -> Result<()> {
+fn cmd_restart(
+    agent_override: Option<String>,
+    max_retries_override: Option<u32>,
+    max_session_duration_override: Option<u64>,
+) -> Result<()> {
     let dir = cryochamber::work_dir()?;
-    let cryo_state = require_live_daemon(&dir)?;
+    let mut cryo_state = require_live_daemon(&dir)?;
+
+    // Check a new agent before stopping the one that works
+    if let Some(agent) = &agent_override {
+        validate_agent_command(agent).exit_code(Exit::Usage)?;
+    }
 
     // Uninstall old service (systemd/launchd stop may already kill the process)
     let _ = cryochamber::service::uninstall("daemon", &dir);
 
     // Kill existing daemon process only if still alive after service removal
     if state::is_locked(&cryo_state) {
-        if let Some(pid) = cryo_state.pid {
-            cryochamber::process::terminate_pid(pid)?;
-        }
+        cryochamber::process::stop_daemon(&dir, &cryo_state)?;
     }
 
-    // Clear PID, keep session_number and overrides
-    let updated = CryoState {
+    // Clear PID, keep session_number and overrides (merged with new flags)
+    let changes = cryo_state.merge_overrides(
+        agent_override,
+        max_retries_override,
+        max_session_duration_override,
+    );
+    let mut updated = CryoState {
         pid: None,
         ..cryo_state
     };
+    updated.mark_stopped(
+        state::StopReason::Cancelled,
+        chrono::Local::now().naive_local(),
+    );
     state::save_state(&state::state_path(&dir), &updated)?;
 
     let exe = std::env::current_exe().context("Failed to resolve cryo executable path")?;
     let log_path = cryochamber::log::log_path(&dir);
+    warn_service_persistence();
     cryochamber::service::install("daemon", &dir, &exe, &["daemon"], &log_path, false)?;
 
     println!("Restarted (service reinstalled).");
+    if !changes.is_empty() {
+        println!("Overrides: {}", changes.join(", "));
+    }
     println!("Use `cryo watch` or `cryo web` to follow progress.");
     Ok(())
 }
 
-fn cmd_ps(kill_all: bool) -> Result<()> {
-    // list() auto-cleans dead PIDs from the registry
+fn cmd_ps(kill_all: bool, unhealthy: bool) -> Result<()> {
+    // list() auto-cleans dead PIDs from the registry, keeping crashed daemons
     let entries = cryochamber::registry::list()?;
-
-    if entries.is_empty() {
-        println!("No cryo daemons running.");
+    let rows: Vec<_> = entries
+        .iter()
+        .map(|entry| (entry, ps_details(entry)))
+        .filter(|(_, details)| !unhealthy || details.problem.is_some())
+        .collect();
+
+    if rows.is_empty() {
+        if unhealthy {
+            println!("No unhealthy cryo daemons.");
+        } else {
+            println!("No cryo daemons running.");
+        }
         return Ok(());
     }
 
-    for entry in &entries {
+    for (entry, details) in &rows {
         if kill_all {
-            cryochamber::process::terminate_pid(entry.pid)?;
-            println!("Killed PID {:>6}  {}", entry.pid, entry.dir);
-        } else {
-            println!("PID {:>6}  {}", entry.pid, entry.dir);
+            if entry.crashed {
+                continue;
+            }
+            let dir = std::path::Path::new(&entry.dir);
+            match state::load_state(&state::state_path(dir)).ok().flatten() {
+                Some(st) if st.embedded && st.pid == Some(entry.pid) => {
+                    cryochamber::process::stop_daemon(dir, &st)?;
+                    println!(
+                        "Stopped embedded daemon in PID {:>6}  {}",
+                        entry.pid, entry.dir
+                    );
+                }
+                _ => {
+                    cryochamber::process::terminate_pid(entry.pid, entry.start_time.as_deref())?;
+                    println!("Killed PID {:>6}  {}", entry.pid, entry.dir);
+                }
+            }
+            continue;
+        }
+        let flag = if entry.crashed { "  [CRASHED]" } else { "" };
+        println!("PID {:>6}  {}{flag}", entry.pid, entry.dir);
+        if !details.info.is_empty() {
+            println!("            {}", details.info.join(", "));
+        }
+        if let Some(problem) = &details.problem {
+            println!("            ! {problem}");
         }
     }
 
     Ok(())
 }
 
+/// What `cryo ps` shows under a daemon's PID line.
+struct PsDetails {
+    /// Uptime, session number and next wake, as far as they are known.
+    info: Vec<String>,
+    /// Why the daemon needs attention: crashed, blocked, or last session failed.
+    problem: Option<String>,
+}
+
+fn ps_details(entry: &cryochamber::registry::DaemonEntry) -> PsDetails {
+    let dir = Path::new(&entry.dir);
+    let now = chrono::Local::now().naive_local();
+    let st = state::load_state(&state::state_path(dir)).ok().flatten();
+    let mut info = Vec::new();
+
+    if !entry.crashed {
+        let started = entry
+            .started_at
+            .as_deref()
+            .and_then(|s| chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S").ok());
+        if let Some(started) = started {
+            let uptime = (now - started).num_milliseconds();
+            info.push(format!(
+                "up {}",
+                cryochamber::web::format_relative_time(uptime)
+            ));
+        }
+    }
+    if let Some(st) = &st {
+        if let Some(stopped) = st.stop_description() {
+            info.push(format!("stopped: {stopped}"));
+        }
+        info.push(format!("session {}", st.session_number));
+        if st.awaiting_message {
+            info.push("waiting for message".to_string());
+        } else if let Some(wake) = &st.next_wake {
+            info.push(format!("next wake {wake}"));
+        }
+    }
+
+    let problem = if entry.crashed {
+        Some("daemon exited without cleanup; run `cryo restart` or `cryo cancel`".to_string())
+    } else if let Some(reason) = st.as_ref().and_then(|st| st.blocked_reason.clone()) {
+        Some(format!("blocked: {reason}"))
+    } else {
+        last_session_failure(dir)
+    };
+
+    PsDetails { info, problem }
+}
+
+/// Describe the most recent session in `dir`'s log if it did not succeed.
+fn last_session_failure(dir: &Path) -> Option<String> {
+    let cfg = config::load_config(&config::config_path(dir))
+        .ok()
+        .flatten()
+        .unwrap_or_default();
+    let format = cryochamber::log::LogFormat::from_config(&cfg);
+    let sessions = cryochamber::log::parse_sessions_since(
+        &cryochamber::log::log_path(dir),
+        chrono::NaiveDateTime::MIN,
+        &format,
+    )
+    .ok()?;
+    let last = sessions.last()?;
+    (last.outcome != cryochamber::log::SessionOutcome::Success)
+        .then(|| format!("last session {} {}", last.session_number, last.outcome))
+}
+
 fn cmd_cancel() -> Result<()> {
     let dir = cryochamber::work_dir()?;
     require_valid_project(&dir)?;
@@ -507,6 +1256,9 @@ fn cmd_cancel() -> Result<()> {
     if service_removed {
         println!("Service removed.");
     }
+    if cryochamber::service::uninstall_wake_timer(&dir)? {
+        println!("Wake timer removed.");
+    }
 
     let sp = state::state_path(&dir);
     match state::load_state(&sp)? {
@@ -519,7 +1271,7 @@ fn cmd_cancel() -> Result<()> {
             // Kill daemon process if still alive
             if state::is_locked(&cryo_state) {
                 if let Some(pid) = cryo_state.pid {
-                    cryochamber::process::terminate_pid(pid)?;
+                    cryochamber::process::stop_daemon(&dir, &cryo_state)?;
                     println!("Killed daemon (PID {pid}).");
                 }
             }
@@ -543,9 +1295,11 @@ fn confirm(prompt: &str) -> bool {
     matches!(input.trim(), "y" | "Y" | "yes" | "Yes")
 }
 
-fn cmd_clean(force: bool) -> Result<()> {
+fn cmd_clean(force: bool, keep_session_number: bool) -> Result<()> {
     let dir = cryochamber::work_dir()?;
     require_valid_project(&dir)?;
+    let keep_session_number = keep_session_number
+        || config::load_config(&config::config_path(&dir))?.is_some_and(|c| c.keep_session_number);
 
     if !force && !confirm("Stop daemon and remove all runtime files?") {
         println!("Aborted.");
@@ -556,6 +1310,9 @@ fn cmd_clean(force: bool) -> Result<()> {
     if cryochamber::service::uninstall("daemon", &dir)? {
         println!("Removed daemon service.");
     }
+    if cryochamber::service::uninstall_wake_timer(&dir)? {
+        println!("Removed wake timer.");
+    }
     if cryochamber::service::uninstall("gh-sync", &dir)? {
         println!("Removed gh-sync service.");
     }
@@ -571,12 +1328,26 @@ fn cmd_clean(force: bool) -> Result<()> {
     if let Some(cryo_state) = state::load_state(&sp)? {
         if state::is_locked(&cryo_state) {
             if let Some(pid) = cryo_state.pid {
-                cryochamber::process::terminate_pid(pid)?;
+                cryochamber::process::stop_daemon(&dir, &cryo_state)?;
                 println!("Killed daemon (PID {pid}).");
             }
         }
     }
 
+    // The highest session number on record, before timer.json and cryo.log go
+    let last_session = if keep_session_number {
+        [
+            state::load_state(&sp)?.map_or(0, |s| s.session_number),
+            cryochamber::log::session_count(&cryochamber::log::log_path(&dir))?,
+            state::load_session_counter(&dir)?.unwrap_or(0),
+        ]
+        .into_iter()
+        .max()
+        .unwrap_or(0)
+    } else {
+        0
+    };
+
     // Remove runtime files
     let runtime_files = [
         "timer.json",
@@ -596,6 +1367,22 @@ fn cmd_clean(force: bool) -> Result<()> {
         }
     }
 
+    // Remove rotated agent logs (cryo-agent.log.<n>.gz)
+    for entry in std::fs::read_dir(&dir)?.flatten() {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if name.starts_with("cryo-agent.log.") && name.ends_with(".gz") {
+            std::fs::remove_file(entry.path())?;
+            println!("Removed {name}");
+        }
+    }
+
+    // Remove an out-of-tree runtime dir (runtime_dir / CRYO_RUNTIME_DIR)
+    let runtime_dir = cryochamber::socket::runtime_dir(&dir)?;
+    if !runtime_dir.starts_with(&dir) && runtime_dir.exists() {
+        std::fs::remove_dir_all(&runtime_dir)?;
+        println!("Removed {}/", runtime_dir.display());
+    }
+
     // Remove runtime directories
     let runtime_dirs = ["messages", ".cryo"];
     for name in &runtime_dirs {
@@ -606,22 +1393,135 @@ fn cmd_clean(force: bool) -> Result<()> {
         }
     }
 
+    if last_session > 0 {
+        state::save_session_counter(&dir, last_session)?;
+        println!("Kept session number {last_session} in .cryo/session-counter");
+    }
+
     println!("Clean.");
     Ok(())
 }
 
-fn cmd_log() -> Result<()> {
+fn cmd_plan_show() -> Result<()> {
+    let dir = cryochamber::work_dir()?;
+    let plan = dir.join("plan.md");
+    if !plan.exists() {
+        anyhow::bail!("No plan.md found. Run `cryo init` or `cryo plan set <path>`.");
+    }
+    print!("{}", std::fs::read_to_string(plan)?);
+    Ok(())
+}
+
+fn cmd_plan_set(source: &str, force: bool) -> Result<()> {
+    let dir = cryochamber::work_dir()?;
+    require_valid_project(&dir)?;
+
+    let source = Path::new(source);
+    if !source.is_file() {
+        anyhow::bail!("Plan file not found: {}", source.display());
+    }
+    let dest = dir.join("plan.md");
+    if !cryochamber::session::should_copy_plan(source, &dest) {
+        println!("plan.md is already {}; nothing to do.", source.display());
+        return Ok(());
+    }
+    if !force && cryochamber::session::dest_is_newer(source, &dest) {
+        anyhow::bail!(
+            "plan.md was modified more recently than {}. Use --force to overwrite it.",
+            source.display()
+        );
+    }
+    std::fs::copy(source, &dest)
+        .with_context(|| format!("Failed to copy {} to plan.md", source.display()))?;
+    println!("plan.md updated from {}", source.display());
+    Ok(())
+}
+
+fn cmd_plan_edit() -> Result<()> {
+    let dir = cryochamber::work_dir()?;
+    require_valid_project(&dir)?;
+
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+    let parts = shell_words::split(&editor).context("Failed to parse $EDITOR")?;
+    let (program, args) = parts.split_first().context("$EDITOR is empty")?;
+    let status = std::process::Command::new(program)
+        .args(args)
+        .arg(dir.join("plan.md"))
+        .status()
+        .with_context(|| format!("Failed to launch editor: {program}"))?;
+    if !status.success() {
+        anyhow::bail!("Editor exited with {status}");
+    }
+    Ok(())
+}
+
+fn cmd_log(no_redact: bool) -> Result<()> {
     let dir = cryochamber::work_dir()?;
     let log = cryochamber::log::log_path(&dir);
     if log.exists() {
         let contents = std::fs::read_to_string(log)?;
-        println!("{contents}");
+        if no_redact {
+            println!("{contents}");
+        } else {
+            // Also mask logs written before a secret was added to cryo.toml
+            let cfg = config::load_config(&config::config_path(&dir))?.unwrap_or_default();
+            let redactor = cryochamber::redact::Redactor::for_project(&cfg, &dir)
+                .or_else(|_| cryochamber::redact::Redactor::from_config(&cfg))?;
+            println!("{}", redactor.redact(&contents));
+        }
     } else {
         println!("No log file found.");
     }
     Ok(())
 }
 
+fn cmd_replay(session: Option<u32>) -> Result<()> {
+    let dir = cryochamber::work_dir()?;
+    let available = recording::list(&dir)?;
+    let Some(session) = session.or_else(|| available.last().copied()) else {
+        println!("No session recordings found. Set record_session = true in cryo.toml.");
+        return Ok(());
+    };
+    if !available.contains(&session) {
+        let list: Vec<String> = available.iter().map(|n| n.to_string()).collect();
+        anyhow::bail!(
+            "No recording for session {session} (recorded: {})",
+            if list.is_empty() {
+                "none".to_string()
+            } else {
+                list.join(", ")
+            }
+        );
+    }
+    let rec = recording::load(&dir, session)?;
+
+    println!("Session #{}", rec.session);
+    println!("Started:  {}", rec.started_at.format("%Y-%m-%dT%H:%M:%S"));
+    if let Some(finished) = rec.finished_at {
+        println!("Finished: {}", finished.format("%Y-%m-%dT%H:%M:%S"));
+    }
+    println!("Agent:    {}", rec.agent);
+    // The prompt is printed in full below
+    let argv: Vec<&str> = rec
+        .argv
+        .iter()
+        .map(|a| if *a == rec.prompt { "<prompt>" } else { a })
+        .collect();
+    println!("Argv:     {}", shell_words::join(argv));
+    if let Some(provider) = &rec.provider {
+        println!("Provider: {provider}");
+    }
+    for (key, value) in &rec.env {
+        println!("Env:      {key}={value}");
+    }
+    println!("\n--- prompt ---\n{}", rec.prompt.trim_end());
+    println!("\n--- stdout ---\n{}", rec.stdout.trim_end());
+    println!("\n--- stderr ---\n{}", rec.stderr.trim_end());
+    Ok(())
+}
+
 fn build_inbox_message(from: &str, subject: &str, body: &str) -> message::Message {
     message::Message {
         from: from.to_string(),
@@ -629,6 +1529,14 @@ fn build_inbox_message(from: &str, subject: &str, body: &str) -> message::Messag
         body: body.to_string(),
         timestamp: chrono::Local::now().naive_local(),
         metadata: std::collections::BTreeMap::new(),
+        attachments: Vec::new(),
+    }
+}
+
+/// Warn when the OS service about to be installed would not survive logout or reboot.
+fn warn_service_persistence() {
+    for warning in cryochamber::service::persistence_warnings() {
+        eprintln!("Warning: {warning}");
     }
 }
 
@@ -649,14 +1557,19 @@ fn signal_daemon_wake(dir: &std::path::Path) -> bool {
 /// After writing an inbox message, notify the daemon and print status.
 /// When watch_inbox is true, the inotify watcher handles wake — no signal needed.
 /// When watch_inbox is false, send SIGUSR1.
-fn notify_daemon_wake(dir: &std::path::Path) -> Result<()> {
-    let watch_inbox = config::load_config(&config::config_path(dir))?
+/// Tell a running daemon about a new inbox message. An explicit wake
+/// (`cryo wake`, `cryo send --wake`) always signals the daemon so it runs
+/// now; otherwise the inbox watcher picks the message up, subject to
+/// `min_session_gap_secs`.
+fn notify_daemon_wake(dir: &std::path::Path, explicit: bool) -> Result<()> {
+    let watch_inbox = config::load_config(&config::config_path(dir))
+        .exit_code(Exit::Usage)?
         .map(|c| c.watch_inbox)
         .unwrap_or(true);
 
     if !is_daemon_running(dir) {
         eprintln!("Warning: no daemon is running. Message queued for the next `cryo start`.");
-    } else if watch_inbox {
+    } else if watch_inbox && !explicit {
         println!("Daemon will pick it up shortly.");
     } else if signal_daemon_wake(dir) {
         println!("Wake signal sent. Daemon waking now.");
@@ -669,19 +1582,33 @@ fn notify_daemon_wake(dir: &std::path::Path) -> Result<()> {
 fn cmd_wake(wake_message: Option<&str>) -> Result<()> {
     let dir = cryochamber::work_dir()?;
     require_valid_project(&dir)?;
-    message::ensure_dirs(&dir)?;
+    let store = store::open(&dir)?;
+    store.ensure()?;
 
     let body = wake_message.unwrap_or("Manual wake requested by operator.");
     let msg = build_inbox_message("operator", "Wake", body);
-    message::write_message(&dir, "inbox", &msg)?;
+    store.write("inbox", &msg)?;
 
-    notify_daemon_wake(&dir)
+    notify_daemon_wake(&dir, true)
 }
 
-fn cmd_send(body: &str, from: &str, subject: Option<&str>, wake: bool) -> Result<()> {
+fn cmd_send(
+    body: &str,
+    from: &str,
+    subject: Option<&str>,
+    reply_to: Option<&str>,
+    attachments: &[std::path::PathBuf],
+    wake: bool,
+    no_wake: bool,
+) -> Result<()> {
     let dir = cryochamber::work_dir()?;
     require_valid_project(&dir)?;
-    message::ensure_dirs(&dir)?;
+    let store = store::open(&dir)?;
+    store.ensure()?;
+    let cfg = config::load_config(&config::config_path(&dir))
+        .exit_code(Exit::Usage)?
+        .unwrap_or_default();
+    let auto_wake = cfg.auto_wake_on_send;
 
     let subject = subject.unwrap_or_else(|| {
         // Truncate at a char boundary to avoid panic on non-ASCII input
@@ -691,34 +1618,157 @@ fn cmd_send(body: &str, from: &str, subject: Option<&str>, wake: bool) -> Result
         }
         &body[..end]
     });
-    let msg = build_inbox_message(from, subject, body);
-    let path = message::write_message(&dir, "inbox", &msg)?;
-    println!(
-        "Message sent to {}",
-        path.strip_prefix(&dir).unwrap_or(&path).display()
-    );
+    let mut msg = build_inbox_message(from, subject, body);
+    if let Some(id) = reply_to {
+        msg.metadata
+            .insert(message::IN_REPLY_TO_KEY.to_string(), id.to_string());
+    }
+    for path in attachments {
+        msg.attachments.push(message::attach_file(&dir, path)?);
+    }
+    let id = store.write("inbox", &msg)?;
+    match cfg.message_store {
+        config::MessageStoreKind::File => println!(
+            "Message sent to {}",
+            std::path::Path::new(&cfg.inbox_dir).join(&id).display()
+        ),
+        _ => println!("Message sent to {id}"),
+    }
 
-    if wake {
-        notify_daemon_wake(&dir)?;
+    if wake || (auto_wake && !no_wake) {
+        notify_daemon_wake(&dir, wake)?;
     }
 
     Ok(())
 }
 
-fn cmd_receive() -> Result<()> {
+fn cmd_messages_migrate() -> Result<()> {
+    let dir = cryochamber::work_dir()?;
+    require_valid_project(&dir)?;
+    let cfg = config::load_config(&config::config_path(&dir))
+        .exit_code(Exit::Usage)?
+        .unwrap_or_default();
+
+    let db = store::SqliteStore::open(&dir)?;
+    let imported = store::sqlite::migrate_files(&dir, &db)?;
+    println!("Imported {imported} message(s) into messages/messages.db");
+    if cfg.message_store != config::MessageStoreKind::Sqlite {
+        println!("Set message_store = \"sqlite\" in cryo.toml to use it, then `cryo restart`.");
+    }
+    Ok(())
+}
+
+fn cmd_review_list(all: bool) -> Result<()> {
+    let dir = cryochamber::work_dir()?;
+    require_valid_project(&dir)?;
+    let reviews = if all {
+        cryochamber::review::list(&dir)?
+    } else {
+        cryochamber::review::pending(&dir)?
+    };
+    if reviews.is_empty() {
+        println!("No {}reviews.", if all { "" } else { "pending " });
+        return Ok(());
+    }
+    for review in &reviews {
+        println!(
+            "{} [{}] {}",
+            review.id,
+            review.status.as_str(),
+            review.requested_at.format("%Y-%m-%d %H:%M")
+        );
+        println!("  {}", review.prompt);
+        if let Some(deadline) = review.deadline.filter(|_| review.is_pending()) {
+            println!("  Alert at: {}", deadline.format("%Y-%m-%d %H:%M"));
+        }
+        if let Some(by) = &review.decided_by {
+            println!("  Decided by: {by}");
+        }
+        if let Some(note) = &review.note {
+            println!("  Note: {note}");
+        }
+    }
+    Ok(())
+}
+
+fn cmd_review_decide(id: &str, approve: bool, note: Option<&str>, from: &str) -> Result<()> {
     let dir = cryochamber::work_dir()?;
-    let messages = message::read_outbox(&dir)?;
+    require_valid_project(&dir)?;
+    let store = store::open(&dir)?;
+    store.ensure()?;
+    let review = cryochamber::review::answer(
+        &dir,
+        store.as_ref(),
+        id,
+        approve,
+        note,
+        from,
+        chrono::Local::now().naive_local(),
+    )?;
+    println!("Review {} {}.", review.id, review.status.as_str());
+    notify_daemon_wake(&dir, false)
+}
 
+/// One message as printed by `cryo receive --json`.
+fn message_json(
+    filename: &str,
+    msg: &message::Message,
+    archived: bool,
+    receipt: Option<&message::DeliveryReceipt>,
+) -> serde_json::Value {
+    let mut value = serde_json::json!({
+        "filename": filename,
+        "from": msg.from,
+        "to": msg.recipients(),
+        "subject": msg.subject,
+        "timestamp": msg.timestamp.format("%Y-%m-%dT%H:%M:%S").to_string(),
+        "body": msg.body,
+        "attachments": msg.attachments,
+        "archived": archived,
+    });
+    if let Some(receipt) = receipt {
+        value["delivery"] = serde_json::json!(receipt);
+    }
+    value
+}
+
+/// With `--fail-if-empty`, turn an empty `cryo receive` into exit code 5.
+fn check_not_empty(empty: bool, what: &str, fail_if_empty: bool) -> Result<()> {
+    if empty && fail_if_empty {
+        return Err(anyhow::anyhow!("No {what}.")).exit_code(Exit::Empty);
+    }
+    Ok(())
+}
+
+fn cmd_receive(json: bool, fail_if_empty: bool) -> Result<()> {
+    let dir = cryochamber::work_dir()?;
+    let messages = store::open(&dir)?.read_outbox()?;
+
+    if json {
+        let values: Vec<_> = messages
+            .iter()
+            .map(|(filename, msg)| message_json(filename, msg, false, None))
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&values)?);
+        return check_not_empty(messages.is_empty(), "messages in outbox", fail_if_empty);
+    }
     if messages.is_empty() {
         println!("No messages in outbox.");
-        return Ok(());
+        return check_not_empty(true, "messages in outbox", fail_if_empty);
     }
 
     for (filename, msg) in &messages {
         println!("--- {} ---", filename);
         println!("From: {}", msg.from);
+        let to = msg.recipients();
+        if !to.is_empty() {
+            println!("To: {}", to.join(", "));
+        }
         println!("Subject: {}", msg.subject);
         println!("Time: {}", msg.timestamp.format("%Y-%m-%dT%H:%M:%S"));
+        for name in &msg.attachments {
+            println!("Attachment: messages/attachments/{name}");
+        }
         println!();
         println!("{}", msg.body);
         println!();
@@ -727,6 +1777,54 @@ fn cmd_receive() -> Result<()> {
     Ok(())
 }
 
+fn cmd_receive_delivered(json: bool, fail_if_empty: bool) -> Result<()> {
+    let dir = cryochamber::work_dir()?;
+    let messages = store::open(&dir)?.read_outbox_archive()?;
+
+    if json {
+        let values: Vec<_> = messages
+            .iter()
+            .map(|(filename, msg, receipt)| message_json(filename, msg, true, receipt.as_ref()))
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&values)?);
+        return check_not_empty(messages.is_empty(), "delivered messages", fail_if_empty);
+    }
+    if messages.is_empty() {
+        println!("No delivered messages.");
+        return check_not_empty(true, "delivered messages", fail_if_empty);
+    }
+
+    for (filename, msg, receipt) in &messages {
+        println!("--- {} ---", filename);
+        println!("From: {}", msg.from);
+        let to = msg.recipients();
+        if !to.is_empty() {
+            println!("To: {}", to.join(", "));
+        }
+        println!("Subject: {}", msg.subject);
+        println!("Time: {}", msg.timestamp.format("%Y-%m-%dT%H:%M:%S"));
+        match receipt {
+            Some(r) => {
+                let target = r
+                    .url
+                    .as_deref()
+                    .or(r.remote_id.as_deref())
+                    .unwrap_or("(no remote id)");
+                println!(
+                    "Delivered: {} {} at {}",
+                    r.channel,
+                    target,
+                    r.delivered_at.format("%Y-%m-%dT%H:%M:%S")
+                );
+            }
+            None => println!("Delivered: (no receipt)"),
+        }
+        println!();
+    }
+
+    Ok(())
+}
+
 fn cmd_watch(show_all: bool, viewpoint: &str) -> Result<()> {
     use std::io::Read;
 
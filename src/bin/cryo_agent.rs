@@ -1,10 +1,11 @@
 // src/bin/cryo_agent.rs
-use anyhow::Result;
+use anyhow::{Context, Result};
 use clap::{Parser, Subcommand};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use cryochamber::message;
 use cryochamber::socket::{self, Request};
+use cryochamber::store;
 use cryochamber::todo::TodoList;
 
 #[derive(Parser)]
@@ -16,11 +17,22 @@ struct Cli {
 
 #[derive(Subcommand)]
 enum Commands {
+    /// Check that a cryochamber daemon is serving this session (exits non-zero if not)
+    Ping,
+    /// Show the active provider and the environment injected for this session
+    Env {
+        /// Print values instead of redacting them
+        #[arg(long)]
+        show_values: bool,
+    },
     /// End session and schedule next wake
     Hibernate {
         /// Wake time in ISO8601 format
         #[arg(long)]
         wake: Option<String>,
+        /// Wake after a duration instead of at a time: "30s", "90m", "1h30m", "2d"
+        #[arg(long, value_name = "DURATION", conflicts_with_all = ["wake", "until_message", "complete", "every"])]
+        wake_in: Option<String>,
         /// Mark plan as complete (no more wakes)
         #[arg(long)]
         complete: bool,
@@ -30,22 +42,152 @@ enum Commands {
         /// Human-readable session summary
         #[arg(long)]
         summary: Option<String>,
+        /// Sleep until a human sends a message (no time-based wake)
+        #[arg(long, conflicts_with_all = ["wake", "complete"])]
+        until_message: bool,
+        /// Wake on a recurring cron schedule, e.g. "0 9 * * 1-5" (weekdays at 9).
+        /// Later hibernates without --wake reuse it until --complete
+        #[arg(long, value_name = "CRON", conflicts_with_all = ["wake", "until_message", "complete"])]
+        every: Option<String>,
+        /// Wake at the scheduled time or as soon as a message arrives, whichever is first
+        #[arg(long, conflicts_with_all = ["until_message", "complete"])]
+        wake_on_message: bool,
+        /// JSON file with the plan's machine-readable result (with --complete)
+        #[arg(long, requires = "complete")]
+        result: Option<PathBuf>,
     },
     /// Leave a note for the next session
     Note {
         /// Note text
         text: String,
     },
+    /// Update this session's summary; kept if the session is killed before hibernating
+    SetSummary {
+        /// What has been done so far
+        summary: String,
+    },
+    /// Add a line to this session's summary; used by hibernate without --summary
+    SummaryAppend {
+        /// What was just done
+        text: String,
+    },
+    /// Ask for more time: push this session's timeout (and a wake already set) later
+    Defer {
+        /// How much later: "30s", "30m", "1h"
+        #[arg(long, value_name = "DURATION")]
+        by: String,
+    },
+    /// Search past and unread messages (prints JSON, newest first)
+    Messages {
+        /// Mailbox to search: inbox, outbox or all
+        #[arg(long, default_value = "all", value_parser = ["inbox", "outbox", "all"])]
+        direction: String,
+        /// Sender to match; "human" means anyone but the agent and cryochamber
+        #[arg(long)]
+        from: Option<String>,
+        /// Only messages newer than this: a duration ("2h", "1d") or a time ("2026-03-01T09:00")
+        #[arg(long)]
+        since: Option<String>,
+        /// Show at most this many messages
+        #[arg(long, default_value = "20")]
+        limit: usize,
+    },
+    /// Set the task for the next session
+    NextTask {
+        /// Task description, passed verbatim to the next session
+        task: String,
+    },
+    /// Pick the plan in plans/ the next session works on
+    FocusPlan {
+        /// Plan name, e.g. "api" for plans/api.md
+        name: String,
+    },
+    /// Start the next session on the next provider, e.g. when this one is degraded
+    RotateProvider {
+        /// What is wrong with the current provider, e.g. "rate limited"
+        #[arg(long)]
+        reason: String,
+    },
+    /// Record a custom event in the session log (e.g. a deploy or a test run)
+    Event {
+        /// Event kind, a single word such as "deploy"
+        #[arg(long)]
+        kind: String,
+        /// Optional JSON payload, e.g. '{"version":"1.2"}'
+        #[arg(long)]
+        data: Option<String>,
+    },
+    /// Copy the tail of a tool's output file into cryo.log as a labeled block
+    AttachLog {
+        /// File to attach, e.g. build.log
+        #[arg(long)]
+        file: PathBuf,
+        /// Label for the block (default: the file name)
+        #[arg(long)]
+        label: Option<String>,
+    },
+    /// End this session on purpose (not a crash: no retry, no provider rotation)
+    Abort {
+        /// Why the session is stopping, e.g. "wrong branch checked out"
+        #[arg(long)]
+        reason: String,
+        /// Next wake time in ISO8601 format (default: the next scheduled wake)
+        #[arg(long)]
+        wake: Option<String>,
+    },
     /// Send message to human (writes to outbox)
     Send {
         /// Message text
         text: String,
+        /// Attach a file (repeatable); it is copied to messages/attachments/
+        #[arg(long = "attach", value_name = "FILE")]
+        attachments: Vec<PathBuf>,
+        /// Address the message to someone (repeatable): a GitHub login or a
+        /// Zulip name or email. Without it the message goes to everyone
+        #[arg(long, value_name = "WHO", value_delimiter = ',')]
+        to: Vec<String>,
+        /// Message subject (default: "Reply")
+        #[arg(long)]
+        subject: Option<String>,
     },
     /// Reply to human (alias for send, writes to outbox)
     Reply {
         /// Reply message text
         text: String,
+        /// Attach a file (repeatable); it is copied to messages/attachments/
+        #[arg(long = "attach", value_name = "FILE")]
+        attachments: Vec<PathBuf>,
+        /// Address the message to someone (repeatable): a GitHub login or a
+        /// Zulip name or email. Without it the message goes to everyone
+        #[arg(long, value_name = "WHO", value_delimiter = ',')]
+        to: Vec<String>,
+        /// Message subject (default: "Reply")
+        #[arg(long)]
+        subject: Option<String>,
+    },
+    /// Ask a human to approve or reject a risky step, then end the session.
+    /// The decision arrives as an inbox message on the next wake
+    RequestReview {
+        /// The yes/no question, e.g. "Deploy to prod?"
+        #[arg(long)]
+        prompt: String,
+        /// Review ID to quote when approving (default: review-<timestamp>)
+        #[arg(long)]
+        id: Option<String>,
+    },
+    /// Block until a human answers (prints the reply; exits non-zero on timeout)
+    WaitForReply {
+        /// Question ID the reply must carry (`cryo send --reply-to <id>`);
+        /// without it, any new inbox message counts
+        #[arg(long)]
+        id: Option<String>,
+        /// Seconds to wait before giving up
+        #[arg(long, default_value = "300")]
+        timeout: u64,
     },
+    /// Stream a line per new inbox message (`<file>\t<from>\t<subject>`)
+    /// until the session ends. Only one watcher per session
+    WatchInbox,
     /// Set a fallback alert (dead-man switch)
     Alert {
         /// Action type (email, webhook)
@@ -57,6 +199,15 @@ enum Commands {
     },
     /// Read inbox messages from human
     Receive,
+    /// Mark inbox messages as handled, so only they are archived after the session
+    Handled {
+        /// Inbox filenames, as printed by `cryo-agent receive`
+        #[arg(required = true)]
+        files: Vec<String>,
+        /// Keep them in the inbox for the next session instead
+        #[arg(long)]
+        keep: bool,
+    },
     /// Print current time or compute a future time
     Time {
         /// Offset from now (e.g. "+30 minutes", "+2 hours", "+1 day")
@@ -67,6 +218,22 @@ enum Commands {
         #[command(subcommand)]
         action: TodoAction,
     },
+    /// Keep structured state (e.g. a bisect range) across sessions
+    Checkpoint {
+        #[command(subcommand)]
+        action: CheckpointAction,
+    },
+}
+
+#[derive(Subcommand)]
+enum CheckpointAction {
+    /// Replace the checkpoint with a JSON value, e.g. '{"low":100,"high":200}'
+    Save {
+        /// JSON value to store
+        data: String,
+    },
+    /// Print the saved checkpoint (fails if there is none)
+    Load,
 }
 
 #[derive(Subcommand)]
@@ -104,20 +271,74 @@ fn send(dir: &Path, req: &Request) -> Result<()> {
     }
 }
 
+/// True if a bare hibernate has a wake time to fall back on: a recurring
+/// schedule left by an earlier `hibernate --every`, or a `wake_cadence` in
+/// cryo.toml or the plan.md front-matter.
+fn has_default_wake(dir: &Path) -> bool {
+    let scheduled = cryochamber::state::load_state(&cryochamber::state::state_path(dir))
+        .ok()
+        .flatten()
+        .is_some_and(|st| st.schedule.is_some());
+    let mut config = cryochamber::config::load_config(&cryochamber::config::config_path(dir))
+        .ok()
+        .flatten()
+        .unwrap_or_default();
+    cryochamber::plan::PlanFrontMatter::load(dir)
+        .0
+        .apply(&mut config);
+    scheduled || config.wake_cadence.is_some()
+}
+
+/// Read a `--result` file, checking it is valid JSON before it reaches the daemon.
+fn read_result_file(path: &Path) -> Result<String> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read result file {}", path.display()))?;
+    serde_json::from_str::<serde_json::Value>(&contents)
+        .with_context(|| format!("Result file {} is not valid JSON", path.display()))?;
+    Ok(contents)
+}
+
 fn main() -> Result<()> {
     let cli = Cli::parse();
-    let dir = cryochamber::work_dir()?;
+    let dir = cryochamber::project_dir()?;
 
     match cli.command {
+        Commands::Env { show_values } => send(&dir, &Request::Env { show_values }),
+        Commands::Ping => {
+            send(&dir, &Request::Ping)?;
+            let hello = socket::hello(&dir)?;
+            if hello.version != socket::PROTOCOL_VERSION {
+                eprintln!(
+                    "Warning: agent/daemon protocol mismatch — cryo-agent speaks v{}, daemon v{}; \
+                     upgrade the older one",
+                    socket::PROTOCOL_VERSION,
+                    hello.version
+                );
+            }
+            Ok(())
+        }
         Commands::Hibernate {
             wake,
             complete,
             exit,
             summary,
+            until_message,
+            wake_on_message,
+            result,
+            every,
+            wake_in,
         } => {
-            if !complete && wake.is_none() {
-                anyhow::bail!("Either --wake or --complete is required");
+            if let Some(expr) = &every {
+                cryochamber::schedule::CronSchedule::parse(expr)
+                    .with_context(|| format!("Invalid --every schedule {expr:?}"))?;
+            } else if let Some(d) = &wake_in {
+                cryochamber::schedule::parse_wake_in(d)?;
+            } else if !complete && wake.is_none() && !until_message && !has_default_wake(&dir) {
+                anyhow::bail!(
+                    "One of --wake, --wake-in, --every, --complete, or --until-message is required"
+                );
             }
+            let result = result.map(|path| read_result_file(&path)).transpose()?;
             send(
                 &dir,
                 &Request::Hibernate {
@@ -125,11 +346,113 @@ fn main() -> Result<()> {
                     complete,
                     exit_code: exit,
                     summary,
+                    until_message,
+                    wake_on_message,
+                    result,
+                    every,
+                    wake_in,
                 },
             )
         }
         Commands::Note { text } => send(&dir, &Request::Note { text }),
-        Commands::Send { text } | Commands::Reply { text } => send(&dir, &Request::Reply { text }),
+        Commands::NextTask { task } => send(&dir, &Request::NextTask { task }),
+        Commands::FocusPlan { name } => send(&dir, &Request::FocusPlan { name }),
+        Commands::RotateProvider { reason } => send(&dir, &Request::RotateProvider { reason }),
+        Commands::Messages {
+            direction,
+            from,
+            since,
+            limit,
+        } => {
+            let direction = match direction.as_str() {
+                "inbox" => store::MessageDirection::Inbox,
+                "outbox" => store::MessageDirection::Outbox,
+                _ => store::MessageDirection::All,
+            };
+            let query = store::MessageQuery {
+                direction,
+                from,
+                since,
+                limit: Some(limit),
+            };
+            send(&dir, &Request::QueryMessages { query })
+        }
+        Commands::SetSummary { summary } => send(&dir, &Request::SetSummary { summary }),
+        Commands::SummaryAppend { text } => send(&dir, &Request::SummaryAppend { text }),
+        Commands::Defer { by } => {
+            cryochamber::schedule::parse_wake_in(&by)?;
+            send(&dir, &Request::Defer { by })
+        }
+        Commands::Abort { reason, wake } => send(&dir, &Request::Abort { reason, wake }),
+        Commands::RequestReview { prompt, id } => {
+            send(&dir, &Request::RequestReview { prompt, id })
+        }
+        Commands::Event { kind, data } => {
+            if kind.is_empty() || kind.contains(char::is_whitespace) {
+                anyhow::bail!("Event kind must be a single word, got {kind:?}");
+            }
+            if let Some(data) = &data {
+                serde_json::from_str::<serde_json::Value>(data)
+                    .with_context(|| format!("--data is not valid JSON: {data}"))?;
+            }
+            send(&dir, &Request::Event { kind, data })
+        }
+        Commands::AttachLog { file, label } => {
+            let bytes =
+                std::fs::read(&file).with_context(|| format!("Cannot read {}", file.display()))?;
+            let max = cryochamber::config::load_config(&cryochamber::config::config_path(&dir))?
+                .unwrap_or_default()
+                .attach_log_max_bytes;
+            let text = String::from_utf8_lossy(&bytes);
+            let label = label.unwrap_or_else(|| file.display().to_string());
+            send(
+                &dir,
+                &Request::AttachLog {
+                    label,
+                    content: cryochamber::log::tail(&text, max).to_string(),
+                    total_bytes: bytes.len() as u64,
+                },
+            )
+        }
+        Commands::Send {
+            text,
+            attachments,
+            to,
+            subject,
+        }
+        | Commands::Reply {
+            text,
+            attachments,
+            to,
+            subject,
+        } => {
+            let attachments = attachments
+                .iter()
+                .map(|path| message::attach_file(&dir, path))
+                .collect::<Result<Vec<_>>>()?;
+            let to = to
+                .iter()
+                .map(|r| r.trim().trim_start_matches('@').to_string())
+                .filter(|r| !r.is_empty())
+                .collect();
+            send(
+                &dir,
+                &Request::Reply {
+                    text,
+                    attachments,
+                    to,
+                    subject,
+                },
+            )
+        }
+        Commands::WaitForReply { id, timeout } => send(
+            &dir,
+            &Request::WaitForReply {
+                id,
+                timeout_secs: timeout,
+            },
+        ),
+        Commands::WatchInbox => cmd_watch_inbox(&dir),
         Commands::Alert {
             action,
             target,
@@ -143,13 +466,40 @@ fn main() -> Result<()> {
             },
         ),
         Commands::Receive => cmd_receive(&dir),
+        Commands::Handled { files, keep } => send(&dir, &Request::Handled { files, keep }),
         Commands::Time { offset } => cmd_time(offset.as_deref()),
         Commands::Todo { action } => cmd_todo(&dir, action),
+        Commands::Checkpoint { action } => match action {
+            CheckpointAction::Save { data } => {
+                serde_json::from_str::<serde_json::Value>(&data)
+                    .with_context(|| format!("Checkpoint is not valid JSON: {data}"))?;
+                send(&dir, &Request::CheckpointSave { data })
+            }
+            CheckpointAction::Load => send(&dir, &Request::CheckpointLoad),
+        },
     }
 }
 
+fn cmd_watch_inbox(dir: &Path) -> Result<()> {
+    use std::io::Write;
+
+    let mut acknowledged = false;
+    socket::subscribe(dir, &Request::WatchInbox, |resp| {
+        if !acknowledged {
+            // The first line only confirms the subscription
+            acknowledged = true;
+            eprintln!("{}", resp.message);
+            return Ok(());
+        }
+        let mut stdout = std::io::stdout().lock();
+        writeln!(stdout, "{}", resp.message)?;
+        stdout.flush()?;
+        Ok(())
+    })
+}
+
 fn cmd_receive(dir: &Path) -> Result<()> {
-    let messages = message::read_inbox(dir)?;
+    let messages = store::open(dir)?.read_inbox()?;
     if messages.is_empty() {
         println!("No messages.");
         return Ok(());
@@ -162,10 +512,20 @@ fn cmd_receive(dir: &Path) -> Result<()> {
         if !msg.subject.is_empty() {
             println!("Subject: {}", msg.subject);
         }
+        for name in &msg.attachments {
+            println!("Attachment: messages/attachments/{name}");
+        }
         println!();
         println!("{}", msg.body);
         println!();
     }
+    // Tell the daemon these were read (best effort; no daemon is fine)
+    let files = messages.into_iter().map(|(filename, _)| filename).collect();
+    let _ = socket::send_request_timeout(
+        dir,
+        &Request::AckInbox { files },
+        std::time::Duration::from_secs(2),
+    );
     Ok(())
 }
 
@@ -5,6 +5,9 @@ use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
+use cryochamber::channel::ChannelWorker;
+use cryochamber::process::{lock_holder, read_pid, release_pid_lock, sync_pid_path, PidLock};
+
 #[derive(Parser)]
 #[command(name = "cryo-gh", about = "Cryochamber GitHub Discussion sync")]
 struct Cli {
@@ -22,6 +25,9 @@ enum Commands {
         /// Discussion title (default: derived from plan.md)
         #[arg(long)]
         title: Option<String>,
+        /// Discussion category to post in, e.g. "Announcements" (default: General)
+        #[arg(long)]
+        category: Option<String>,
     },
     /// Pull new Discussion comments into messages/inbox/
     Pull,
@@ -37,6 +43,8 @@ enum Commands {
     Unsync,
     /// Show sync status
     Status,
+    /// Return dead-lettered outbox messages to the outbox for another try
+    RetryDead,
     /// Run the sync loop (internal — use `cryo-gh sync` instead)
     #[command(hide = true)]
     SyncDaemon {
@@ -53,29 +61,43 @@ fn main() -> Result<()> {
     let cli = Cli::parse();
 
     match cli.command {
-        Commands::Init { repo, title } => cmd_gh_init(&repo, title.as_deref()),
+        Commands::Init {
+            repo,
+            title,
+            category,
+        } => cmd_gh_init(&repo, title.as_deref(), category.as_deref()),
         Commands::Pull => cmd_gh_pull(),
         Commands::Push => cmd_gh_push(),
         Commands::Sync { interval } => cmd_gh_sync(interval),
         Commands::Unsync => cmd_gh_unsync(),
         Commands::Status => cmd_gh_status(),
+        Commands::RetryDead => cmd_gh_retry_dead(),
         Commands::SyncDaemon { interval } => cmd_gh_sync_daemon(interval),
     }
 }
 
-fn cmd_gh_init(repo: &str, title: Option<&str>) -> Result<()> {
+fn cmd_gh_init(repo: &str, title: Option<&str>, category: Option<&str>) -> Result<()> {
     let dir = cryochamber::work_dir()?;
 
     let (owner, repo_name) = repo
         .split_once('/')
         .context("--repo must be in 'owner/repo' format")?;
 
-    let default_title = format!(
-        "[Cryo] {}",
-        dir.file_name().unwrap_or_default().to_string_lossy()
-    );
+    let cfg = cryochamber::config::load_config(&cryochamber::config::config_path(&dir))?
+        .unwrap_or_default();
+    let default_title = format!("[Cryo] {}", cfg.project_name(&dir));
     let title = title.unwrap_or(&default_title);
 
+    // Re-running init for the same repo keeps the category chosen last time
+    let previous = cryochamber::gh_sync::load_sync_state(&gh_sync_path(&dir))
+        .ok()
+        .flatten()
+        .filter(|state| state.repo == repo);
+    let category = category
+        .map(String::from)
+        .or_else(|| previous.and_then(|state| state.category));
+    let category = category.as_deref();
+
     let plan_content = std::fs::read_to_string(dir.join("plan.md")).unwrap_or_default();
     let body = if plan_content.is_empty() {
         "Cryochamber sync Discussion.".to_string()
@@ -85,7 +107,7 @@ fn cmd_gh_init(repo: &str, title: Option<&str>) -> Result<()> {
 
     println!("Creating GitHub Discussion in {repo}...");
     let (node_id, number) =
-        cryochamber::channel::github::create_discussion(owner, repo_name, title, &body)?;
+        cryochamber::channel::github::create_discussion(owner, repo_name, title, &body, category)?;
     println!("Created Discussion #{number}");
 
     let self_login = cryochamber::channel::github::whoami().ok();
@@ -97,6 +119,8 @@ fn cmd_gh_init(repo: &str, title: Option<&str>) -> Result<()> {
         last_read_cursor: None,
         self_login,
         last_pushed_session: None,
+        last_pushed_at: None,
+        category: category.map(String::from),
     };
     cryochamber::gh_sync::save_sync_state(&gh_sync_path(&dir), &sync_state)?;
     println!("Saved gh-sync.json");
@@ -129,7 +153,7 @@ fn cmd_gh_pull() -> Result<()> {
         cryochamber::gh_sync::save_sync_state(&gh_sync_path(&dir), &sync_state)?;
     }
 
-    let inbox = cryochamber::message::read_inbox(&dir)?;
+    let inbox = cryochamber::store::open(&dir)?.read_inbox()?;
     println!("Inbox: {} message(s)", inbox.len());
 
     Ok(())
@@ -140,33 +164,18 @@ fn cmd_gh_push() -> Result<()> {
     let mut sync_state = cryochamber::gh_sync::load_sync_state(&gh_sync_path(&dir))?
         .context("gh-sync.json not found. Run 'cryo-gh init' first.")?;
 
-    let log = cryochamber::log::log_path(&dir);
-    let latest = cryochamber::log::read_latest_session(&log)?;
-
-    let Some(session_output) = latest else {
-        println!("No session log found. Nothing to push.");
-        return Ok(());
+    let (owner, repo) = sync_state.owner_repo()?;
+    let channel = cryochamber::channel::github::GitHubChannel {
+        owner: owner.to_string(),
+        repo: repo.to_string(),
+        discussion_number: sync_state.discussion_number,
+        discussion_node_id: sync_state.discussion_node_id.clone(),
     };
-
-    let state_file = cryochamber::state::state_path(&dir);
-    let session_num = cryochamber::state::load_state(&state_file)?
-        .map(|s| s.session_number)
-        .unwrap_or(0);
-
-    if sync_state.last_pushed_session == Some(session_num) {
-        println!("Session {session_num} already pushed. Skipping.");
+    let destination = format!("Discussion #{}", sync_state.discussion_number);
+    let pushed = cryochamber::channel::push_session(&channel, &dir, &destination, &mut sync_state)?;
+    if pushed.is_none() {
         return Ok(());
     }
-
-    let comment = format!("## Session {session_num}\n\n```\n{session_output}\n```");
-
-    println!(
-        "Posting session summary to Discussion #{}...",
-        sync_state.discussion_number
-    );
-    cryochamber::channel::github::post_comment(&sync_state.discussion_node_id, &comment)?;
-
-    sync_state.last_pushed_session = Some(session_num);
     cryochamber::gh_sync::save_sync_state(&gh_sync_path(&dir), &sync_state)?;
 
     println!("Push complete.");
@@ -196,7 +205,7 @@ fn cmd_gh_sync(interval_override: Option<u64>) -> Result<()> {
         .context("gh-sync.json not found. Run 'cryo-gh init' first.")?;
 
     // Ensure message dirs exist
-    cryochamber::message::ensure_dirs(&dir)?;
+    cryochamber::store::open(&dir)?.ensure()?;
 
     let exe = std::env::current_exe().context("Failed to resolve cryo-gh executable path")?;
     let interval_str = interval.to_string();
@@ -228,6 +237,7 @@ fn cmd_gh_unsync() -> Result<()> {
     } else {
         println!("No sync service installed for this directory.");
     }
+    report_released_lock(&dir)?;
 
     Ok(())
 }
@@ -236,6 +246,12 @@ fn cmd_gh_sync_daemon(interval_override: Option<u64>) -> Result<()> {
     let interval = resolve_interval(interval_override)?;
     let dir = cryochamber::work_dir()?;
     let sync_path = gh_sync_path(&dir);
+    let cfg = cryochamber::config::load_config(&cryochamber::config::config_path(&dir))?
+        .unwrap_or_default();
+    let worker = ChannelWorker::new(cfg.channel_timeout_secs);
+    let max_attempts = cfg.outbox_max_attempts;
+    // One sync daemon per project, or every message would be posted twice
+    let _lock = PidLock::acquire(&sync_pid_path(&dir, "gh-sync"))?;
 
     eprintln!("Sync daemon started (PID {})", std::process::id());
 
@@ -247,7 +263,7 @@ fn cmd_gh_sync_daemon(interval_override: Option<u64>) -> Result<()> {
     // Set up outbox watcher for immediate push on new messages
     use notify::Watcher;
     let (tx, rx) = std::sync::mpsc::channel();
-    let outbox_path = dir.join("messages").join("outbox");
+    let outbox_path = cryochamber::message::project_dirs(&dir)?.outbox;
     let _watcher = {
         let tx = tx.clone();
         let mut watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
@@ -260,7 +276,7 @@ fn cmd_gh_sync_daemon(interval_override: Option<u64>) -> Result<()> {
         .context("Failed to create outbox watcher")?;
         watcher
             .watch(&outbox_path, notify::RecursiveMode::NonRecursive)
-            .context("Failed to watch messages/outbox/")?;
+            .with_context(|| format!("Failed to watch {}", outbox_path.display()))?;
         watcher
     };
 
@@ -282,30 +298,37 @@ fn cmd_gh_sync_daemon(interval_override: Option<u64>) -> Result<()> {
         }
 
         // Reload sync state each cycle (pull updates the cursor)
-        let mut sync_state = cryochamber::gh_sync::load_sync_state(&sync_path)?
+        let sync_state = cryochamber::gh_sync::load_sync_state(&sync_path)?
             .context("gh-sync.json disappeared")?;
 
-        // Pull: Discussion → inbox
-        let (owner, repo) = sync_state.owner_repo()?;
-        match cryochamber::channel::github::pull_comments(
-            owner,
-            repo,
-            sync_state.discussion_number,
-            sync_state.last_read_cursor.as_deref(),
-            sync_state.self_login.as_deref(),
-            &dir,
-        ) {
-            Ok(new_cursor) => {
+        // Pull: Discussion → inbox. The worker saves the cursor itself, so a
+        // pull that finishes after timing out still records its progress.
+        let pull = {
+            let (dir, sync_path) = (dir.clone(), sync_path.clone());
+            let mut sync_state = sync_state.clone();
+            worker.run("pull", move || {
+                let (owner, repo) = sync_state.owner_repo()?;
+                let new_cursor = cryochamber::channel::github::pull_comments(
+                    owner,
+                    repo,
+                    sync_state.discussion_number,
+                    sync_state.last_read_cursor.as_deref(),
+                    sync_state.self_login.as_deref(),
+                    &dir,
+                )?;
                 if let Some(cursor) = new_cursor {
                     sync_state.last_read_cursor = Some(cursor);
                     cryochamber::gh_sync::save_sync_state(&sync_path, &sync_state)?;
                 }
-            }
-            Err(e) => eprintln!("Sync: pull error: {e}"),
+                Ok(())
+            })
+        };
+        if let Err(e) = pull {
+            eprintln!("Sync: pull error: {e}");
         }
 
         // Push: outbox → Discussion
-        if let Err(e) = push_outbox(&dir, &sync_state) {
+        if let Err(e) = push_outbox(&dir, &sync_state, &worker, max_attempts) {
             eprintln!("Sync: push error: {e}");
         }
 
@@ -325,30 +348,42 @@ fn cmd_gh_sync_daemon(interval_override: Option<u64>) -> Result<()> {
 }
 
 /// Read outbox messages and post each as a Discussion comment, then archive them.
-fn push_outbox(dir: &Path, sync_state: &cryochamber::gh_sync::GhSyncState) -> Result<()> {
-    let messages = cryochamber::message::read_outbox(dir)?;
+/// Each post+archive runs on `worker`, so a hung request is abandoned and the
+/// message stays in the outbox for the next cycle. A message whose post fails
+/// `max_attempts` times is moved to the dead-letter queue.
+fn push_outbox(
+    dir: &Path,
+    sync_state: &cryochamber::gh_sync::GhSyncState,
+    worker: &ChannelWorker,
+    max_attempts: u32,
+) -> Result<()> {
+    let messages = cryochamber::store::open(dir)?.read_outbox()?;
     if messages.is_empty() {
         return Ok(());
     }
 
-    let outbox = dir.join("messages").join("outbox");
-    let archive = outbox.join("archive");
-    std::fs::create_dir_all(&archive)?;
-
-    for (filename, msg) in &messages {
-        let body = format!("**{}** ({})\n\n{}", msg.from, msg.subject, msg.body);
-        match cryochamber::channel::github::post_comment(&sync_state.discussion_node_id, &body) {
-            Ok(()) => {
-                eprintln!("Sync: posted outbox/{filename} to Discussion");
-                let src = outbox.join(filename);
-                let dst = archive.join(filename);
-                if src.exists() {
-                    std::fs::rename(&src, &dst)?;
-                }
-            }
-            Err(e) => {
-                eprintln!("Sync: failed to post outbox/{filename}: {e}");
-            }
+    for (filename, msg) in messages {
+        let body = cryochamber::channel::github::outbox_comment_body(&msg);
+        let node_id = sync_state.discussion_node_id.clone();
+        let dir = dir.to_path_buf();
+        let name = filename.clone();
+        let posted = worker.run(&format!("post outbox/{filename}"), move || {
+            let store = cryochamber::store::open(&dir)?;
+            let (comment_id, url) = cryochamber::channel::github::post_comment(&node_id, &body)
+                .map_err(|e| {
+                    cryochamber::channel::record_post_failure(&*store, &name, e, max_attempts)
+                })?;
+            let receipt = cryochamber::message::DeliveryReceipt {
+                channel: "github".to_string(),
+                remote_id: Some(comment_id),
+                url,
+                delivered_at: chrono::Local::now().naive_local(),
+            };
+            store.archive_outbox(&name, &receipt)
+        });
+        match posted {
+            Ok(()) => eprintln!("Sync: posted outbox/{filename} to Discussion"),
+            Err(e) => eprintln!("Sync: failed to post outbox/{filename}: {e}"),
         }
     }
 
@@ -369,7 +404,71 @@ fn cmd_gh_status() -> Result<()> {
                     .as_deref()
                     .unwrap_or("(none — will read all)")
             );
+            print_sync_daemon(&dir);
+            print_dead_letters(&dir)?;
+        }
+    }
+    Ok(())
+}
+
+/// Report whether this project's sync daemon is running, for `status`.
+fn print_sync_daemon(dir: &Path) {
+    let path = sync_pid_path(dir, "gh-sync");
+    match read_pid(&path) {
+        Some(pid) if lock_holder(&path) == Some(pid) => {
+            println!("Sync daemon: running (PID {pid})")
         }
+        Some(pid) => println!(
+            "Sync daemon: not running (stale lock from PID {pid}; `cryo-gh unsync` removes it)"
+        ),
+        None => println!("Sync daemon: not running"),
+    }
+}
+
+/// Stop a sync daemon the service manager didn't own and drop its lock.
+fn report_released_lock(dir: &Path) -> Result<()> {
+    let path = sync_pid_path(dir, "gh-sync");
+    let existed = path.exists();
+    match release_pid_lock(&path)? {
+        Some(pid) => println!("Stopped sync daemon (PID {pid})."),
+        None if existed => println!("Removed stale lock {}.", path.display()),
+        None => {}
+    }
+    Ok(())
+}
+
+/// List dead-lettered outbox messages, if any, for `status`.
+fn print_dead_letters(dir: &Path) -> Result<()> {
+    let dead = cryochamber::store::open(dir)?.read_dead_letters()?;
+    if dead.is_empty() {
+        return Ok(());
+    }
+    println!(
+        "Dead letters: {} (retry with: cryo-gh retry-dead)",
+        dead.len()
+    );
+    for (id, _, failure) in &dead {
+        match failure {
+            Some(f) => println!(
+                "  {id}: {} attempts, last error: {}",
+                f.attempts, f.last_error
+            ),
+            None => println!("  {id}"),
+        }
+    }
+    Ok(())
+}
+
+fn cmd_gh_retry_dead() -> Result<()> {
+    let dir = cryochamber::work_dir()?;
+    let requeued = cryochamber::store::open(&dir)?.requeue_dead_letters()?;
+    if requeued.is_empty() {
+        println!("No dead letters.");
+    } else {
+        println!(
+            "Requeued {} message(s) for the next sync cycle.",
+            requeued.len()
+        );
     }
     Ok(())
 }
@@ -5,7 +5,9 @@ use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
-use cryochamber::channel::zulip::ZulipClient;
+use cryochamber::channel::zulip::{OutboxRoute, ZulipClient};
+use cryochamber::channel::ChannelWorker;
+use cryochamber::process::{lock_holder, read_pid, release_pid_lock, sync_pid_path, PidLock};
 
 #[derive(Parser)]
 #[command(name = "cryo-zulip", about = "Cryochamber Zulip sync")]
@@ -42,6 +44,8 @@ enum Commands {
     Unsync,
     /// Show sync status
     Status,
+    /// Return dead-lettered outbox messages to the outbox for another try
+    RetryDead,
     /// Run the sync loop (internal — use `cryo-zulip sync` instead)
     #[command(hide = true)]
     SyncDaemon {
@@ -68,6 +72,7 @@ fn main() -> Result<()> {
         Commands::Sync { interval } => cmd_sync(interval),
         Commands::Unsync => cmd_unsync(),
         Commands::Status => cmd_status(),
+        Commands::RetryDead => cmd_retry_dead(),
         Commands::SyncDaemon { interval } => cmd_sync_daemon(interval),
     }
 }
@@ -75,7 +80,10 @@ fn main() -> Result<()> {
 fn cmd_init(config_path: &str, stream_name: &str, topic: Option<&str>) -> Result<()> {
     let dir = cryochamber::work_dir()?;
 
-    let client = ZulipClient::from_zuliprc(Path::new(config_path))?;
+    let cfg = cryochamber::config::load_config(&cryochamber::config::config_path(&dir))?
+        .unwrap_or_default();
+    let client = ZulipClient::from_zuliprc(Path::new(config_path))?
+        .with_agent(cryochamber::channel::http_agent(&cfg)?);
 
     println!("Validating credentials...");
     let (_user_id, self_email) = client.get_profile()?;
@@ -92,7 +100,9 @@ fn cmd_init(config_path: &str, stream_name: &str, topic: Option<&str>) -> Result
         self_email,
         topic: topic.map(|t| t.to_string()),
         last_message_id: None,
+        last_dm_id: None,
         last_pushed_session: None,
+        last_pushed_at: None,
     };
     cryochamber::zulip_sync::save_sync_state(&zulip_sync_path(&dir), &sync_state)?;
 
@@ -112,8 +122,11 @@ fn load_client_from_project(
     let sync_state = cryochamber::zulip_sync::load_sync_state(&zulip_sync_path(dir))?
         .context("zulip-sync.json not found. Run 'cryo-zulip init' first.")?;
     let rc_path = dir.join(".cryo").join("zuliprc");
+    let cfg = cryochamber::config::load_config(&cryochamber::config::config_path(dir))?
+        .unwrap_or_default();
     let client = ZulipClient::from_zuliprc(&rc_path)
-        .context("Failed to load .cryo/zuliprc. Re-run 'cryo-zulip init'.")?;
+        .context("Failed to load .cryo/zuliprc. Re-run 'cryo-zulip init'.")?
+        .with_agent(cryochamber::channel::http_agent(&cfg)?);
     Ok((client, sync_state))
 }
 
@@ -122,22 +135,44 @@ fn cmd_pull() -> Result<()> {
     let (client, mut sync_state) = load_client_from_project(&dir)?;
 
     println!("Pulling messages from stream '{}'...", sync_state.stream);
+    pull_inbox(&client, &mut sync_state, &dir, &zulip_sync_path(&dir))?;
+
+    let inbox = cryochamber::store::open(&dir)?.read_inbox()?;
+    println!("Inbox: {} message(s)", inbox.len());
+    Ok(())
+}
+
+/// Pull new stream messages, then direct messages to the bot (replies to
+/// `cryo-agent send --to`), into the inbox. Each cursor is saved as soon as
+/// it moves, so a failed DM pull doesn't replay the stream.
+fn pull_inbox(
+    client: &ZulipClient,
+    sync_state: &mut cryochamber::zulip_sync::ZulipSyncState,
+    dir: &Path,
+    sync_path: &Path,
+) -> Result<()> {
+    // Message IDs are server-wide: with no DM cursor yet, skip DMs older
+    // than the stream position rather than replaying them all
+    let dm_anchor = sync_state.last_dm_id.or(sync_state.last_message_id);
+
     let new_last_id = client.pull_messages(
         sync_state.stream_id,
         sync_state.last_message_id,
         Some(&sync_state.self_email),
-        &dir,
+        dir,
     )?;
-
-    if let Some(id) = new_last_id {
-        if sync_state.last_message_id != Some(id) {
-            sync_state.last_message_id = Some(id);
-            cryochamber::zulip_sync::save_sync_state(&zulip_sync_path(&dir), &sync_state)?;
-        }
+    if new_last_id.is_some() && sync_state.last_message_id != new_last_id {
+        sync_state.last_message_id = new_last_id;
+        cryochamber::zulip_sync::save_sync_state(sync_path, sync_state)
+            .context("failed to save state")?;
     }
 
-    let inbox = cryochamber::message::read_inbox(&dir)?;
-    println!("Inbox: {} message(s)", inbox.len());
+    let new_dm_id = client.pull_direct_messages(dm_anchor, Some(&sync_state.self_email), dir)?;
+    if new_dm_id.is_some() && sync_state.last_dm_id != new_dm_id {
+        sync_state.last_dm_id = new_dm_id;
+        cryochamber::zulip_sync::save_sync_state(sync_path, sync_state)
+            .context("failed to save state")?;
+    }
     Ok(())
 }
 
@@ -145,34 +180,16 @@ fn cmd_push() -> Result<()> {
     let dir = cryochamber::work_dir()?;
     let (client, mut sync_state) = load_client_from_project(&dir)?;
 
-    let log = cryochamber::log::log_path(&dir);
-    let latest = cryochamber::log::read_latest_session(&log)?;
-
-    let Some(session_output) = latest else {
-        println!("No session log found. Nothing to push.");
-        return Ok(());
+    let channel = cryochamber::channel::zulip::ZulipChannel {
+        client,
+        stream_id: sync_state.stream_id,
+        topic: sync_state.topic_name().to_string(),
     };
-
-    let state_file = cryochamber::state::state_path(&dir);
-    let session_num = cryochamber::state::load_state(&state_file)?
-        .map(|s| s.session_number)
-        .unwrap_or(0);
-
-    if sync_state.last_pushed_session == Some(session_num) {
-        println!("Session {session_num} already pushed. Skipping.");
+    let destination = format!("stream '{}'", sync_state.stream);
+    let pushed = cryochamber::channel::push_session(&channel, &dir, &destination, &mut sync_state)?;
+    if pushed.is_none() {
         return Ok(());
     }
-
-    let topic = sync_state.topic_name();
-    let comment = format!("## Session {session_num}\n\n```\n{session_output}\n```");
-
-    println!(
-        "Posting session summary to stream '{}'...",
-        sync_state.stream
-    );
-    client.send_message(sync_state.stream_id, topic, &comment)?;
-
-    sync_state.last_pushed_session = Some(session_num);
     cryochamber::zulip_sync::save_sync_state(&zulip_sync_path(&dir), &sync_state)?;
 
     println!("Push complete.");
@@ -199,7 +216,7 @@ fn cmd_sync(interval_override: Option<u64>) -> Result<()> {
     let sync_state = cryochamber::zulip_sync::load_sync_state(&sync_path)?
         .context("zulip-sync.json not found. Run 'cryo-zulip init' first.")?;
 
-    cryochamber::message::ensure_dirs(&dir)?;
+    cryochamber::store::open(&dir)?.ensure()?;
 
     let exe = std::env::current_exe().context("Failed to resolve cryo-zulip executable path")?;
     let interval_str = interval.to_string();
@@ -230,6 +247,7 @@ fn cmd_unsync() -> Result<()> {
     } else {
         println!("No sync service installed for this directory.");
     }
+    report_released_lock(&dir)?;
     Ok(())
 }
 
@@ -237,6 +255,12 @@ fn cmd_sync_daemon(interval_override: Option<u64>) -> Result<()> {
     let interval = resolve_interval(interval_override)?;
     let dir = cryochamber::work_dir()?;
     let sync_path = zulip_sync_path(&dir);
+    let cfg = cryochamber::config::load_config(&cryochamber::config::config_path(&dir))?
+        .unwrap_or_default();
+    let worker = ChannelWorker::new(cfg.channel_timeout_secs);
+    let max_attempts = cfg.outbox_max_attempts;
+    // One sync daemon per project, or every message would be posted twice
+    let _lock = PidLock::acquire(&sync_pid_path(&dir, "zulip-sync"))?;
 
     eprintln!("Zulip sync daemon started (PID {})", std::process::id());
 
@@ -246,7 +270,7 @@ fn cmd_sync_daemon(interval_override: Option<u64>) -> Result<()> {
 
     use notify::Watcher;
     let (tx, rx) = std::sync::mpsc::channel();
-    let outbox_path = dir.join("messages").join("outbox");
+    let outbox_path = cryochamber::message::project_dirs(&dir)?.outbox;
     let _watcher = {
         let tx = tx.clone();
         let mut watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
@@ -259,7 +283,7 @@ fn cmd_sync_daemon(interval_override: Option<u64>) -> Result<()> {
         .context("Failed to create outbox watcher")?;
         watcher
             .watch(&outbox_path, notify::RecursiveMode::NonRecursive)
-            .context("Failed to watch messages/outbox/")?;
+            .with_context(|| format!("Failed to watch {}", outbox_path.display()))?;
         watcher
     };
 
@@ -279,7 +303,7 @@ fn cmd_sync_daemon(interval_override: Option<u64>) -> Result<()> {
             break;
         }
 
-        let (client, mut sync_state) = match load_client_from_project(&dir) {
+        let (client, sync_state) = match load_client_from_project(&dir) {
             Ok(pair) => pair,
             Err(e) => {
                 eprintln!("Zulip sync: config error: {e}");
@@ -288,30 +312,21 @@ fn cmd_sync_daemon(interval_override: Option<u64>) -> Result<()> {
             }
         };
 
-        // Pull: Zulip → inbox
-        match client.pull_messages(
-            sync_state.stream_id,
-            sync_state.last_message_id,
-            Some(&sync_state.self_email),
-            &dir,
-        ) {
-            Ok(new_last_id) => {
-                if let Some(id) = new_last_id {
-                    if sync_state.last_message_id != Some(id) {
-                        sync_state.last_message_id = Some(id);
-                        if let Err(e) =
-                            cryochamber::zulip_sync::save_sync_state(&sync_path, &sync_state)
-                        {
-                            eprintln!("Zulip sync: failed to save state: {e}");
-                        }
-                    }
-                }
-            }
-            Err(e) => eprintln!("Zulip sync: pull error: {e}"),
+        // Pull: Zulip → inbox. The worker saves the cursor itself, so a pull
+        // that finishes after timing out still records its progress.
+        let pull = {
+            let (client, dir, sync_path) = (client.clone(), dir.clone(), sync_path.clone());
+            let mut sync_state = sync_state.clone();
+            worker.run("pull", move || {
+                pull_inbox(&client, &mut sync_state, &dir, &sync_path)
+            })
+        };
+        if let Err(e) = pull {
+            eprintln!("Zulip sync: pull error: {e}");
         }
 
         // Push: outbox → Zulip
-        if let Err(e) = push_outbox(&dir, &client, &sync_state) {
+        if let Err(e) = push_outbox(&dir, &client, &sync_state, &worker, max_attempts) {
             eprintln!("Zulip sync: push error: {e}");
         }
 
@@ -328,36 +343,49 @@ fn cmd_sync_daemon(interval_override: Option<u64>) -> Result<()> {
     Ok(())
 }
 
+/// Post each outbox message to the topic, then archive it. Each post+archive
+/// runs on `worker`, so a hung request is abandoned and the message stays in
+/// the outbox for the next cycle. A message whose post fails `max_attempts`
+/// times is moved to the dead-letter queue.
 fn push_outbox(
     dir: &Path,
     client: &ZulipClient,
     sync_state: &cryochamber::zulip_sync::ZulipSyncState,
+    worker: &ChannelWorker,
+    max_attempts: u32,
 ) -> Result<()> {
-    let messages = cryochamber::message::read_outbox(dir)?;
+    let messages = cryochamber::store::open(dir)?.read_outbox()?;
     if messages.is_empty() {
         return Ok(());
     }
 
-    let outbox = dir.join("messages").join("outbox");
-    let archive = outbox.join("archive");
-    std::fs::create_dir_all(&archive)?;
-
-    let topic = sync_state.topic_name();
-
-    for (filename, msg) in &messages {
-        let body = format!("**{}** ({})\n\n{}", msg.from, msg.subject, msg.body);
-        match client.send_message(sync_state.stream_id, topic, &body) {
-            Ok(_) => {
-                eprintln!("Zulip sync: posted outbox/{filename}");
-                let src = outbox.join(filename);
-                let dst = archive.join(filename);
-                if src.exists() {
-                    std::fs::rename(&src, &dst)?;
-                }
-            }
-            Err(e) => {
-                eprintln!("Zulip sync: failed to post outbox/{filename}: {e}");
-            }
+    let topic = sync_state.topic_name().to_string();
+
+    for (filename, msg) in messages {
+        let route = cryochamber::channel::zulip::route_outbox_message(&msg);
+        let (client, topic, dir) = (client.clone(), topic.clone(), dir.to_path_buf());
+        let stream_id = sync_state.stream_id;
+        let name = filename.clone();
+        let posted = worker.run(&format!("post outbox/{filename}"), move || {
+            let store = cryochamber::store::open(&dir)?;
+            let sent = match &route {
+                OutboxRoute::Topic(body) => client.send_message(stream_id, &topic, body),
+                OutboxRoute::Direct { to, content } => client.send_direct_message(to, content),
+            };
+            let msg_id = sent.map_err(|e| {
+                cryochamber::channel::record_post_failure(&*store, &name, e, max_attempts)
+            })?;
+            let receipt = cryochamber::message::DeliveryReceipt {
+                channel: "zulip".to_string(),
+                remote_id: Some(msg_id.to_string()),
+                url: None,
+                delivered_at: chrono::Local::now().naive_local(),
+            };
+            store.archive_outbox(&name, &receipt)
+        });
+        match posted {
+            Ok(()) => eprintln!("Zulip sync: posted outbox/{filename}"),
+            Err(e) => eprintln!("Zulip sync: failed to post outbox/{filename}: {e}"),
         }
     }
 
@@ -387,7 +415,71 @@ fn cmd_status() -> Result<()> {
                     .map(|s| s.to_string())
                     .unwrap_or_else(|| "(none)".to_string())
             );
+            print_sync_daemon(&dir);
+            print_dead_letters(&dir)?;
         }
     }
     Ok(())
 }
+
+/// Report whether this project's sync daemon is running, for `status`.
+fn print_sync_daemon(dir: &Path) {
+    let path = sync_pid_path(dir, "zulip-sync");
+    match read_pid(&path) {
+        Some(pid) if lock_holder(&path) == Some(pid) => {
+            println!("Sync daemon: running (PID {pid})")
+        }
+        Some(pid) => println!(
+            "Sync daemon: not running (stale lock from PID {pid}; `cryo-zulip unsync` removes it)"
+        ),
+        None => println!("Sync daemon: not running"),
+    }
+}
+
+/// Stop a sync daemon the service manager didn't own and drop its lock.
+fn report_released_lock(dir: &Path) -> Result<()> {
+    let path = sync_pid_path(dir, "zulip-sync");
+    let existed = path.exists();
+    match release_pid_lock(&path)? {
+        Some(pid) => println!("Stopped sync daemon (PID {pid})."),
+        None if existed => println!("Removed stale lock {}.", path.display()),
+        None => {}
+    }
+    Ok(())
+}
+
+/// List dead-lettered outbox messages, if any, for `status`.
+fn print_dead_letters(dir: &Path) -> Result<()> {
+    let dead = cryochamber::store::open(dir)?.read_dead_letters()?;
+    if dead.is_empty() {
+        return Ok(());
+    }
+    println!(
+        "Dead letters: {} (retry with: cryo-zulip retry-dead)",
+        dead.len()
+    );
+    for (id, _, failure) in &dead {
+        match failure {
+            Some(f) => println!(
+                "  {id}: {} attempts, last error: {}",
+                f.attempts, f.last_error
+            ),
+            None => println!("  {id}"),
+        }
+    }
+    Ok(())
+}
+
+fn cmd_retry_dead() -> Result<()> {
+    let dir = cryochamber::work_dir()?;
+    let requeued = cryochamber::store::open(&dir)?.requeue_dead_letters()?;
+    if requeued.is_empty() {
+        println!("No dead letters.");
+    } else {
+        println!(
+            "Requeued {} message(s) for the next sync cycle.",
+            requeued.len()
+        );
+    }
+    Ok(())
+}
@@ -24,6 +24,10 @@ impl MessageChannel for FileChannel {
     }
 
     fn post_reply(&self, body: &str) -> Result<()> {
+        self.post_reply_with_attachments(body, &[])
+    }
+
+    fn post_reply_with_attachments(&self, body: &str, attachments: &[String]) -> Result<()> {
         message::ensure_dirs(&self.dir)?;
         let msg = Message {
             from: "cryochamber".to_string(),
@@ -31,6 +35,7 @@ impl MessageChannel for FileChannel {
             body: body.to_string(),
             timestamp: Local::now().naive_local(),
             metadata: BTreeMap::new(),
+            attachments: attachments.to_vec(),
         };
         message::write_message(&self.dir, "outbox", &msg)?;
         Ok(())
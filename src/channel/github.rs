@@ -3,6 +3,7 @@ use chrono::NaiveDateTime;
 use std::collections::BTreeMap;
 use std::process::Command;
 
+use crate::channel::MessageChannel;
 use crate::message::Message;
 
 /// Get the login of the currently authenticated `gh` user.
@@ -70,7 +71,7 @@ pub fn build_fetch_comments_query(
 pub fn build_post_comment_mutation(discussion_node_id: &str, body: &str) -> String {
     let escaped = escape_graphql(body);
     format!(
-        r#"mutation {{ addDiscussionComment(input: {{discussionId: "{discussion_node_id}", body: "{escaped}"}}) {{ comment {{ id }} }} }}"#
+        r#"mutation {{ addDiscussionComment(input: {{discussionId: "{discussion_node_id}", body: "{escaped}"}}) {{ comment {{ id url }} }} }}"#
     )
 }
 
@@ -124,12 +125,67 @@ pub fn parse_discussion_comments(json: &serde_json::Value) -> Result<(Vec<Messag
             body,
             timestamp,
             metadata,
+            attachments: Vec::new(),
         });
     }
 
     Ok((messages, end_cursor, has_next))
 }
 
+/// Links in a comment body to files GitHub hosts for attachments
+/// (drag-and-drop uploads and pasted images), in order, without duplicates.
+pub fn attachment_urls(body: &str) -> Vec<String> {
+    static RE: std::sync::LazyLock<regex::Regex> = std::sync::LazyLock::new(|| {
+        regex::Regex::new(
+            r#"https://(?:github\.com/user-attachments/(?:assets|files)/|user-images\.githubusercontent\.com/)[^\s)\]"'<>]+"#,
+        )
+        .expect("valid attachment regex")
+    });
+    let mut urls: Vec<String> = Vec::new();
+    for m in RE.find_iter(body) {
+        if !urls.iter().any(|u| u == m.as_str()) {
+            urls.push(m.as_str().to_string());
+        }
+    }
+    urls
+}
+
+/// Comment body for an outbox message. Recipients in `to` are @-mentioned
+/// so GitHub notifies them; Discussions have no upload API, so attachments
+/// are listed by name.
+pub fn outbox_comment_body(msg: &Message) -> String {
+    let mentions: Vec<String> = msg.recipients().iter().map(|r| format!("@{r}")).collect();
+    let header = if mentions.is_empty() {
+        format!("**{}** ({})", msg.from, msg.subject)
+    } else {
+        format!(
+            "**{}** ({}) to {}",
+            msg.from,
+            msg.subject,
+            mentions.join(" ")
+        )
+    };
+    crate::channel::with_attachment_list(&format!("{header}\n\n{}", msg.body), &msg.attachments)
+}
+
+/// Download the attachments linked from `msg.body` into `messages/attachments/`
+/// and list them on the message. A failed download (e.g. a private repo's
+/// file needing a browser session) is skipped; the link stays in the body.
+fn download_attachments(msg: &mut Message, agent: &ureq::Agent, work_dir: &std::path::Path) {
+    for url in attachment_urls(&msg.body) {
+        let name = url.rsplit('/').next().unwrap_or("attachment").to_string();
+        let fetched = agent
+            .get(&url)
+            .call()
+            .map_err(anyhow::Error::from)
+            .and_then(|mut resp| Ok(resp.body_mut().read_to_vec()?));
+        match fetched.and_then(|bytes| crate::message::save_attachment(work_dir, &name, &bytes)) {
+            Ok(stored) => msg.attachments.push(stored),
+            Err(e) => eprintln!("Sync: could not download attachment {url}: {e}"),
+        }
+    }
+}
+
 pub fn parse_create_discussion_response(json: &serde_json::Value) -> Result<(String, u64)> {
     let discussion = &json["data"]["createDiscussion"]["discussion"];
     let id = discussion["id"]
@@ -142,6 +198,17 @@ pub fn parse_create_discussion_response(json: &serde_json::Value) -> Result<(Str
     Ok((id, number))
 }
 
+/// Extract `(comment_id, url)` from an `addDiscussionComment` response.
+pub fn parse_post_comment_response(json: &serde_json::Value) -> Result<(String, Option<String>)> {
+    let comment = &json["data"]["addDiscussionComment"]["comment"];
+    let id = comment["id"]
+        .as_str()
+        .context("Missing comment.id")?
+        .to_string();
+    let url = comment["url"].as_str().map(|s| s.to_string());
+    Ok((id, url))
+}
+
 /// Enable GitHub Discussions on a repository via `gh repo edit`.
 fn enable_discussions(owner: &str, repo: &str) -> Result<()> {
     let status = Command::new("gh")
@@ -181,13 +248,51 @@ fn query_repo_and_categories(owner: &str, repo: &str) -> Result<(String, Vec<ser
     Ok((repo_node_id, categories))
 }
 
-/// Create a new GitHub Discussion. Returns (node_id, number).
-/// Automatically enables Discussions if not already enabled.
+/// Pick the category node ID to post in: the one named `name` (matched
+/// case-insensitively), or "General" (else the first) when no name is given.
+/// An unknown name is an error listing the categories that exist.
+pub fn select_category<'a>(
+    categories: &'a [serde_json::Value],
+    name: Option<&str>,
+) -> Result<&'a str> {
+    let found = match name {
+        Some(name) => categories.iter().find(|c| {
+            c["name"]
+                .as_str()
+                .is_some_and(|n| n.eq_ignore_ascii_case(name))
+        }),
+        None => categories
+            .iter()
+            .find(|c| c["name"].as_str() == Some("General"))
+            .or_else(|| categories.first()),
+    };
+    match (found.and_then(|c| c["id"].as_str()), name) {
+        (Some(id), _) => Ok(id),
+        (None, Some(name)) => {
+            let available: Vec<&str> = categories
+                .iter()
+                .filter_map(|c| c["name"].as_str())
+                .collect();
+            anyhow::bail!(
+                "Discussion category {name:?} not found. Available: {}",
+                available.join(", ")
+            )
+        }
+        (None, None) => {
+            anyhow::bail!("No discussion categories found even after enabling Discussions.")
+        }
+    }
+}
+
+/// Create a new GitHub Discussion in `category` (default: General).
+/// Returns (node_id, number). Automatically enables Discussions if not
+/// already enabled.
 pub fn create_discussion(
     owner: &str,
     repo: &str,
     title: &str,
     body: &str,
+    category: Option<&str>,
 ) -> Result<(String, u64)> {
     let (mut repo_node_id, mut categories) = query_repo_and_categories(owner, repo)?;
 
@@ -201,12 +306,8 @@ pub fn create_discussion(
         categories = result.1;
     }
 
-    let category_id = categories
-        .iter()
-        .find(|c| c["name"].as_str() == Some("General"))
-        .or_else(|| categories.first())
-        .and_then(|c| c["id"].as_str())
-        .context("No discussion categories found even after enabling Discussions.")?;
+    let category_id = select_category(&categories, category)
+        .with_context(|| format!("Cannot create a Discussion in {owner}/{repo}"))?;
 
     let mutation = build_create_discussion_mutation(&repo_node_id, category_id, title, body);
     let result = gh_graphql(&mutation)?;
@@ -225,7 +326,11 @@ pub fn pull_comments(
     skip_author: Option<&str>,
     work_dir: &std::path::Path,
 ) -> Result<Option<String>> {
-    crate::message::ensure_dirs(work_dir)?;
+    let store = crate::store::open(work_dir)?;
+    store.ensure()?;
+    let cfg =
+        crate::config::load_config(&crate::config::config_path(work_dir))?.unwrap_or_default();
+    let agent = super::http_agent(&cfg)?;
     let mut cursor = last_cursor.map(|s| s.to_string());
 
     loop {
@@ -233,13 +338,14 @@ pub fn pull_comments(
         let json = gh_graphql(&query)?;
         let (messages, new_cursor, has_next) = parse_discussion_comments(&json)?;
 
-        for msg in &messages {
+        for mut msg in messages {
             if let Some(skip) = skip_author {
                 if msg.from == skip {
                     continue;
                 }
             }
-            crate::message::write_message(work_dir, "inbox", msg)?;
+            download_attachments(&mut msg, &agent, work_dir);
+            store.write("inbox", &msg)?;
         }
 
         if !new_cursor.is_empty() {
@@ -254,9 +360,43 @@ pub fn pull_comments(
     Ok(cursor)
 }
 
-/// Post a comment to a Discussion.
-pub fn post_comment(discussion_node_id: &str, body: &str) -> Result<()> {
+/// Post a comment to a Discussion. Returns the new comment's `(id, url)`.
+pub fn post_comment(discussion_node_id: &str, body: &str) -> Result<(String, Option<String>)> {
     let mutation = build_post_comment_mutation(discussion_node_id, body);
-    gh_graphql(&mutation)?;
-    Ok(())
+    let result = gh_graphql(&mutation)?;
+    parse_post_comment_response(&result)
+}
+
+/// A GitHub Discussion as a `MessageChannel`: comments in, comments out.
+pub struct GitHubChannel {
+    pub owner: String,
+    pub repo: String,
+    pub discussion_number: u64,
+    pub discussion_node_id: String,
+}
+
+impl MessageChannel for GitHubChannel {
+    fn read_inbox(&self) -> Result<Vec<Message>> {
+        let mut messages = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let query = build_fetch_comments_query(
+                &self.owner,
+                &self.repo,
+                self.discussion_number,
+                cursor.as_deref(),
+            );
+            let (page, new_cursor, has_next) = parse_discussion_comments(&gh_graphql(&query)?)?;
+            messages.extend(page);
+            if !has_next || new_cursor.is_empty() {
+                return Ok(messages);
+            }
+            cursor = Some(new_cursor);
+        }
+    }
+
+    fn post_reply(&self, body: &str) -> Result<()> {
+        post_comment(&self.discussion_node_id, body)?;
+        Ok(())
+    }
 }
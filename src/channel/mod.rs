@@ -2,11 +2,18 @@ pub mod file;
 pub mod github;
 pub mod zulip;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
+use chrono::NaiveDateTime;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::time::Duration;
 
+use crate::config::CryoConfig;
 use crate::message::Message;
+use crate::store::MessageStore;
 
-/// Abstraction over message I/O. Both file-based and GitHub Discussion
+/// Abstraction over message I/O. The file, GitHub Discussion and Zulip
 /// backends implement this trait. The agent always sees files; the
 /// sync utility selects the channel.
 pub trait MessageChannel {
@@ -15,4 +22,238 @@ pub trait MessageChannel {
 
     /// Post a reply visible to humans.
     fn post_reply(&self, body: &str) -> Result<()>;
+
+    /// Post a reply with files from `messages/attachments/`. Backends that
+    /// can't upload files list the attachment names in the body instead.
+    fn post_reply_with_attachments(&self, body: &str, attachments: &[String]) -> Result<()> {
+        self.post_reply(&with_attachment_list(body, attachments))
+    }
+
+    /// Wrap `body` in a block collapsed under `title`. The default is an
+    /// HTML `<details>` element, which GitHub renders.
+    fn collapsible(&self, title: &str, body: &str) -> String {
+        format!("<details><summary>{title}</summary>\n\n{body}\n\n</details>")
+    }
+}
+
+/// Which session a sync backend last pushed, kept in its sync state file so
+/// each session is posted once.
+pub trait PushTracker {
+    fn last_pushed_session(&self) -> Option<u32>;
+    /// Local time of the last push; message counts in the next push start here
+    fn last_pushed_at(&self) -> Option<NaiveDateTime>;
+    fn record_push(&mut self, session: u32, at: NaiveDateTime);
+}
+
+/// Post the latest session's summary and log through `channel`, unless that
+/// session was already pushed. `destination` names the target in progress
+/// output. Returns the session number posted, after recording it in
+/// `tracker`; the caller saves the tracker's state file.
+pub fn push_session(
+    channel: &dyn MessageChannel,
+    dir: &Path,
+    destination: &str,
+    tracker: &mut dyn PushTracker,
+) -> Result<Option<u32>> {
+    let log = crate::log::log_path(dir);
+    let Some(session_output) = crate::log::read_latest_session(&log)? else {
+        println!("No session log found. Nothing to push.");
+        return Ok(None);
+    };
+
+    // The session in the log, not timer.json's count: a session that has
+    // started since would otherwise be reported in place of this one
+    let session_num = match crate::log::session_number(&session_output) {
+        Some(n) => n,
+        None => crate::state::load_state(&crate::state::state_path(dir))?
+            .map(|s| s.session_number)
+            .unwrap_or(0),
+    };
+    if tracker.last_pushed_session() == Some(session_num) {
+        println!("Session {session_num} already pushed. Skipping.");
+        return Ok(None);
+    }
+
+    let digest = crate::report::SessionDigest::collect(dir, session_num, tracker.last_pushed_at())?;
+    let cfg = crate::config::load_config(&crate::config::config_path(dir))?.unwrap_or_default();
+    let comment = format!(
+        "{}\n{}",
+        digest.render(cfg.summary_template.as_deref()),
+        channel.collapsible("Session log", &format!("```\n{session_output}\n```"))
+    );
+
+    println!("Posting session summary to {destination}...");
+    channel.post_reply(&comment)?;
+
+    tracker.record_push(session_num, chrono::Local::now().naive_local());
+    Ok(Some(session_num))
+}
+
+/// Append a list of attachment names to `body`, for backends that can't
+/// upload files. The files stay in the project's `messages/attachments/`.
+pub fn with_attachment_list(body: &str, attachments: &[String]) -> String {
+    if attachments.is_empty() {
+        return body.to_string();
+    }
+    let list: Vec<String> = attachments.iter().map(|a| format!("- `{a}`")).collect();
+    format!(
+        "{body}\n\nAttachments (in `messages/attachments/`):\n{}",
+        list.join("\n")
+    )
+}
+
+/// Proxy env vars, most specific first. Channel clients only speak HTTPS.
+const PROXY_ENV_VARS: &[&str] = &[
+    "HTTPS_PROXY",
+    "https_proxy",
+    "HTTP_PROXY",
+    "http_proxy",
+    "ALL_PROXY",
+    "all_proxy",
+];
+
+/// The proxy channel clients should use: `config.proxy`, else the first set
+/// proxy env var read through `env`. Hosts in `config.no_proxy` and the
+/// `NO_PROXY` env var bypass it.
+pub fn proxy_from(
+    config: &CryoConfig,
+    env: impl Fn(&str) -> Option<String>,
+) -> Result<Option<ureq::Proxy>> {
+    let url = config.proxy.clone().or_else(|| {
+        PROXY_ENV_VARS
+            .iter()
+            .filter_map(|var| env(var))
+            .find(|v| !v.trim().is_empty())
+    });
+    let Some(url) = url else {
+        return Ok(None);
+    };
+    let parsed =
+        ureq::Proxy::new(url.trim()).with_context(|| format!("Invalid proxy URL: {url}"))?;
+
+    let mut builder = ureq::Proxy::builder(parsed.protocol())
+        .host(parsed.host())
+        .port(parsed.port());
+    if let Some(user) = parsed.username() {
+        builder = builder.username(user);
+    }
+    if let Some(password) = parsed.password() {
+        builder = builder.password(password);
+    }
+    let env_no_proxy = env("NO_PROXY")
+        .or_else(|| env("no_proxy"))
+        .unwrap_or_default();
+    for host in config
+        .no_proxy
+        .iter()
+        .map(String::as_str)
+        .chain(env_no_proxy.split(','))
+        .map(str::trim)
+        .filter(|h| !h.is_empty())
+    {
+        builder = builder.no_proxy(host);
+    }
+    Ok(Some(builder.build()?))
+}
+
+/// HTTP agent for channel clients (Zulip API, GitHub attachment downloads),
+/// routed through the proxy from [`proxy_from`].
+pub fn http_agent(config: &CryoConfig) -> Result<ureq::Agent> {
+    let proxy = proxy_from(config, |var| std::env::var(var).ok())?;
+    Ok(ureq::Agent::config_builder().proxy(proxy).build().into())
+}
+
+/// Count a failed post of outbox message `id`. After `max_attempts`
+/// failures (0 = never) the message moves to the dead-letter queue, so one
+/// message the channel keeps rejecting stops being retried with the rest.
+/// Returns `error` with the attempt count or the dead-lettering noted.
+pub fn record_post_failure(
+    store: &dyn MessageStore,
+    id: &str,
+    error: anyhow::Error,
+    max_attempts: u32,
+) -> anyhow::Error {
+    let failure = match store.record_outbox_failure(id, &format!("{error:#}")) {
+        Ok(failure) => failure,
+        Err(e) => return anyhow::anyhow!("{error:#} (could not record the failure: {e})"),
+    };
+    if max_attempts == 0 || failure.attempts < max_attempts {
+        let limit = if max_attempts == 0 {
+            String::new()
+        } else {
+            format!(" of {max_attempts}")
+        };
+        return anyhow::anyhow!("{error:#} (attempt {}{limit})", failure.attempts);
+    }
+    match store.dead_letter_outbox(id) {
+        Ok(()) => anyhow::anyhow!(
+            "{error:#} (moved to dead-letter after {} attempts)",
+            failure.attempts
+        ),
+        Err(e) => anyhow::anyhow!("{error:#} (could not move to dead-letter: {e})"),
+    }
+}
+
+/// Runs blocking channel I/O (GitHub/Zulip requests) on a worker thread with
+/// a per-operation timeout, so a hung request can't wedge a sync loop.
+///
+/// At most one operation is in flight: while a timed-out call is still stuck,
+/// later calls fail fast instead of piling up threads. A timed-out operation
+/// keeps running in the background, so it should do its own bookkeeping
+/// (archive, save cursor) rather than leave it to the caller.
+#[derive(Clone)]
+pub struct ChannelWorker {
+    timeout: Option<Duration>,
+    busy: Arc<AtomicBool>,
+}
+
+/// Clears the worker's busy flag when the operation ends, even on panic.
+struct BusyGuard(Arc<AtomicBool>);
+
+impl Drop for BusyGuard {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::SeqCst);
+    }
+}
+
+impl ChannelWorker {
+    /// `timeout_secs == 0` runs operations inline with no timeout.
+    pub fn new(timeout_secs: u64) -> Self {
+        Self {
+            timeout: (timeout_secs > 0).then(|| Duration::from_secs(timeout_secs)),
+            busy: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Run `op`, giving up after the timeout. `what` names the operation in errors.
+    pub fn run<T, F>(&self, what: &str, op: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce() -> Result<T> + Send + 'static,
+    {
+        let Some(timeout) = self.timeout else {
+            return op();
+        };
+        if self.busy.swap(true, Ordering::SeqCst) {
+            anyhow::bail!("{what} skipped: an earlier channel request is still running");
+        }
+        let guard = BusyGuard(Arc::clone(&self.busy));
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            let result = op();
+            // Free the worker before the caller sees the result, so an
+            // immediate follow-up request isn't rejected as still running
+            drop(guard);
+            let _ = tx.send(result);
+        });
+        match rx.recv_timeout(timeout) {
+            Ok(result) => result,
+            Err(mpsc::RecvTimeoutError::Timeout) => {
+                anyhow::bail!("{what} timed out after {}s", timeout.as_secs())
+            }
+            Err(mpsc::RecvTimeoutError::Disconnected) => {
+                anyhow::bail!("{what} failed: worker thread panicked")
+            }
+        }
+    }
 }
@@ -2,9 +2,38 @@ use anyhow::{Context, Result};
 use std::collections::BTreeMap;
 use std::path::Path;
 
+use crate::channel::MessageChannel;
 use crate::message::Message;
 
+/// Where an outbox message is posted on Zulip.
+#[derive(Debug, Clone, PartialEq)]
+pub enum OutboxRoute {
+    /// The sync topic, @-mentioning any recipients by full name
+    Topic(String),
+    /// A direct message, when every recipient is an email address
+    Direct { to: Vec<String>, content: String },
+}
+
+/// Route an outbox message by its `to` recipients: all emails become a
+/// direct message; names (or no recipients) post to the topic.
+pub fn route_outbox_message(msg: &Message) -> OutboxRoute {
+    let recipients = msg.recipients();
+    let content = format!("**{}** ({})\n\n{}", msg.from, msg.subject, msg.body);
+    if !recipients.is_empty() && recipients.iter().all(|r| r.contains('@')) {
+        return OutboxRoute::Direct {
+            to: recipients,
+            content,
+        };
+    }
+    if recipients.is_empty() {
+        return OutboxRoute::Topic(content);
+    }
+    let mentions: Vec<String> = recipients.iter().map(|r| format!("@**{r}**")).collect();
+    OutboxRoute::Topic(format!("{}\n{content}", mentions.join(" ")))
+}
+
 /// Credentials parsed from a zuliprc INI file.
+#[derive(Clone)]
 pub struct ZulipCredentials {
     pub email: String,
     pub api_key: String,
@@ -12,6 +41,7 @@ pub struct ZulipCredentials {
 }
 
 /// HTTP client for the Zulip REST API.
+#[derive(Clone)]
 pub struct ZulipClient {
     creds: ZulipCredentials,
     agent: ureq::Agent,
@@ -61,6 +91,12 @@ impl ZulipClient {
         })
     }
 
+    /// Send requests through `agent`, e.g. one from [`crate::channel::http_agent`].
+    pub fn with_agent(mut self, agent: ureq::Agent) -> Self {
+        self.agent = agent;
+        self
+    }
+
     /// Access credentials (for testing).
     pub fn credentials(&self) -> &ZulipCredentials {
         &self.creds
@@ -151,11 +187,33 @@ impl ZulipClient {
         skip_email: Option<&str>,
     ) -> Result<(Vec<Message>, bool, Option<u64>)> {
         let narrow = format!(r#"[{{"operator":"stream","operand":{}}}]"#, stream_id);
+        self.fetch_messages(&narrow, anchor, num_after, skip_email)
+    }
+
+    /// GET /api/v1/messages -- fetch the bot's direct messages since anchor.
+    /// Returns (messages, found_newest, raw_max_id).
+    pub fn get_direct_messages(
+        &self,
+        anchor: &str,
+        num_after: u32,
+        skip_email: Option<&str>,
+    ) -> Result<(Vec<Message>, bool, Option<u64>)> {
+        let narrow = r#"[{"operator":"is","operand":"dm"}]"#;
+        self.fetch_messages(narrow, anchor, num_after, skip_email)
+    }
+
+    fn fetch_messages(
+        &self,
+        narrow: &str,
+        anchor: &str,
+        num_after: u32,
+        skip_email: Option<&str>,
+    ) -> Result<(Vec<Message>, bool, Option<u64>)> {
         let num_after_str = num_after.to_string();
         let json = self.get(
             "/messages",
             &[
-                ("narrow", &narrow),
+                ("narrow", narrow),
                 ("anchor", anchor),
                 ("num_before", "0"),
                 ("num_after", &num_after_str),
@@ -183,6 +241,18 @@ impl ZulipClient {
         Ok(msg_id)
     }
 
+    /// POST /api/v1/messages -- send a direct message to users by email.
+    pub fn send_direct_message(&self, emails: &[String], content: &str) -> Result<u64> {
+        let to = serde_json::to_string(emails)?;
+        let json = self.post(
+            "/messages",
+            &[("type", "direct"), ("to", &to), ("content", content)],
+        )?;
+        json["id"]
+            .as_u64()
+            .context("send_direct_message: response JSON missing numeric 'id' field")
+    }
+
     /// Pull all messages since last_message_id, writing each to inbox.
     /// Returns the new last_message_id.
     pub fn pull_messages(
@@ -192,7 +262,33 @@ impl ZulipClient {
         skip_email: Option<&str>,
         work_dir: &Path,
     ) -> Result<Option<u64>> {
-        crate::message::ensure_dirs(work_dir)?;
+        self.pull_with(last_message_id, work_dir, |anchor| {
+            self.get_messages(stream_id, anchor, 1000, skip_email)
+        })
+    }
+
+    /// Pull the bot's direct messages since last_message_id (replies to
+    /// `cryo-agent send --to`), writing each to inbox. Returns the new
+    /// last_message_id.
+    pub fn pull_direct_messages(
+        &self,
+        last_message_id: Option<u64>,
+        skip_email: Option<&str>,
+        work_dir: &Path,
+    ) -> Result<Option<u64>> {
+        self.pull_with(last_message_id, work_dir, |anchor| {
+            self.get_direct_messages(anchor, 1000, skip_email)
+        })
+    }
+
+    fn pull_with(
+        &self,
+        last_message_id: Option<u64>,
+        work_dir: &Path,
+        fetch: impl Fn(&str) -> Result<(Vec<Message>, bool, Option<u64>)>,
+    ) -> Result<Option<u64>> {
+        let store = crate::store::open(work_dir)?;
+        store.ensure()?;
         let mut anchor = match last_message_id {
             Some(id) => id.to_string(),
             None => "oldest".to_string(),
@@ -200,8 +296,7 @@ impl ZulipClient {
         let mut newest_id = last_message_id;
 
         loop {
-            let (messages, found_newest, raw_max_id) =
-                self.get_messages(stream_id, &anchor, 1000, skip_email)?;
+            let (messages, found_newest, raw_max_id) = fetch(&anchor)?;
 
             for msg in &messages {
                 if let Some(id_str) = msg.metadata.get("zulip_message_id") {
@@ -215,7 +310,7 @@ impl ZulipClient {
                         }
                     }
                 }
-                crate::message::write_message(work_dir, "inbox", msg)?;
+                store.write("inbox", msg)?;
             }
 
             if found_newest {
@@ -285,7 +380,11 @@ pub fn parse_get_messages_response(
             .unwrap_or("unknown")
             .to_string();
         let content = msg["content"].as_str().unwrap_or("").to_string();
-        let subject = msg["subject"].as_str().unwrap_or("").to_string();
+        let subject = match msg["subject"].as_str().unwrap_or("") {
+            // Direct messages have no topic
+            "" if msg["type"] == "private" => "Direct message".to_string(),
+            subject => subject.to_string(),
+        };
         let ts_unix = msg["timestamp"].as_i64().unwrap_or(0);
         let timestamp = chrono::DateTime::from_timestamp(ts_unix, 0)
             .map(|dt| dt.naive_utc())
@@ -302,6 +401,7 @@ pub fn parse_get_messages_response(
             body: content,
             timestamp,
             metadata,
+            attachments: Vec::new(),
         });
     }
 
@@ -343,3 +443,30 @@ fn base64_encode(data: &[u8]) -> String {
 
     String::from_utf8(result).unwrap()
 }
+
+/// A Zulip stream topic as a `MessageChannel`.
+pub struct ZulipChannel {
+    pub client: ZulipClient,
+    pub stream_id: u64,
+    pub topic: String,
+}
+
+impl MessageChannel for ZulipChannel {
+    fn read_inbox(&self) -> Result<Vec<Message>> {
+        let (messages, _, _) = self
+            .client
+            .get_messages(self.stream_id, "oldest", 1000, None)?;
+        Ok(messages)
+    }
+
+    fn post_reply(&self, body: &str) -> Result<()> {
+        self.client
+            .send_message(self.stream_id, &self.topic, body)?;
+        Ok(())
+    }
+
+    /// Zulip has no `<details>`; a spoiler block collapses instead.
+    fn collapsible(&self, title: &str, body: &str) -> String {
+        format!("````spoiler {title}\n{body}\n````")
+    }
+}
@@ -1,5 +1,5 @@
 // src/config.rs
-use anyhow::Result;
+use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
@@ -19,6 +19,181 @@ pub enum RotateOn {
     Never,
 }
 
+/// What happens to inbox messages once a session has consumed them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum InboxDisposition {
+    /// Move to `messages/inbox/archive/` (default)
+    #[default]
+    Archive,
+    /// Delete the file
+    Delete,
+    /// Rename in place with a `.consumed` suffix
+    Flag,
+}
+
+/// Where inbox/outbox messages are stored.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MessageStoreKind {
+    /// One markdown file per message under `messages/` (default)
+    #[default]
+    File,
+    /// A single SQLite database, `messages/messages.db`
+    Sqlite,
+}
+
+/// Signal sent first when the daemon stops an agent (timeout or shutdown).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum KillSignal {
+    /// SIGTERM (default)
+    #[default]
+    Term,
+    /// SIGINT, for wrappers that only flush cleanly on Ctrl-C
+    Int,
+}
+
+impl KillSignal {
+    pub fn as_libc(self) -> i32 {
+        match self {
+            KillSignal::Term => libc::SIGTERM,
+            KillSignal::Int => libc::SIGINT,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            KillSignal::Term => "SIGTERM",
+            KillSignal::Int => "SIGINT",
+        }
+    }
+}
+
+/// How the daemon recognizes a finished plan. `cryo-agent hibernate
+/// --complete` always works; the other modes add a fallback for agents
+/// that forget the socket call.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CompleteMarker {
+    /// Only `hibernate --complete` (default)
+    #[default]
+    Socket,
+    /// `complete_marker_text` on a line of the agent's output
+    Output,
+    /// The agent created `.cryo/COMPLETE`
+    File,
+    /// Either of the above
+    Any,
+}
+
+impl CompleteMarker {
+    pub fn checks_output(self) -> bool {
+        matches!(self, CompleteMarker::Output | CompleteMarker::Any)
+    }
+
+    pub fn checks_file(self) -> bool {
+        matches!(self, CompleteMarker::File | CompleteMarker::Any)
+    }
+}
+
+/// What an agent exiting with code 0 without `cryo-agent hibernate` means,
+/// for agents that can't call the socket but finish cleanly.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CleanExit {
+    /// A failed session: retry with backoff (default)
+    #[default]
+    Fail,
+    /// Hibernate until the next `--every` slot, else `clean_exit_wake_in`
+    Reschedule,
+    /// The plan is complete
+    Complete,
+}
+
+/// Verbosity of the daemon's operational diagnostics.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LogLevel {
+    /// Only session boundaries and failures
+    Quiet,
+    /// Session boundaries, failures, and scheduling chatter (default)
+    #[default]
+    Normal,
+    /// Everything, including socket requests and each event-loop wake
+    Verbose,
+}
+
+/// Timezone for `cryo.log` timestamps.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LogTimezone {
+    /// UTC, with a `Z` suffix on session headers (default)
+    #[default]
+    Utc,
+    /// The machine's local time, with its UTC offset on session headers
+    Local,
+}
+
+/// How much of `cryo.log` goes into the session prompt.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum HistoryMode {
+    /// No history section; the agent reads cryo.log itself
+    None,
+    /// The most recent completed session
+    LastSession,
+    /// The whole log
+    Full,
+}
+
+/// Whether the session prompt points the agent at files or carries their content.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PromptStyle {
+    /// Name cryo.log and the inbox; the agent reads them itself (default)
+    #[default]
+    Slim,
+    /// Include unread messages and previous-session history in the prompt
+    Embedded,
+}
+
+/// Randomness added to the retry backoff, so daemons failing against the
+/// same provider don't retry in lockstep.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RetryJitter {
+    /// Exactly the exponential backoff (default)
+    #[default]
+    None,
+    /// Uniform in `[0, backoff]`
+    Full,
+    /// Uniform in `[backoff/2, backoff]`
+    Equal,
+}
+
+/// Order of inbox messages embedded in the session prompt.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MessageOrder {
+    /// Oldest message first, in the order they were sent (default)
+    #[default]
+    OldestFirst,
+    /// Most recent message first
+    NewestFirst,
+}
+
+/// Where the agent command receives the session prompt.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PromptPosition {
+    /// Last command-line argument (default)
+    #[default]
+    Arg,
+    /// Written to the agent's stdin, which is then closed
+    Stdin,
+}
+
 /// A named provider profile with environment variables to inject.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProviderConfig {
@@ -27,26 +202,216 @@ pub struct ProviderConfig {
     /// Environment variables to set when spawning the agent
     #[serde(default)]
     pub env: HashMap<String, String>,
+    /// Dotenv-style file (relative to the project) loaded before `env`;
+    /// keeps API keys out of cryo.toml. Inline `env` entries win.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub env_file: Option<String>,
+    /// Session timeout in seconds while this provider is active
+    /// (overrides the global `max_session_duration`; 0 = no timeout)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_session_duration: Option<u64>,
+}
+
+impl ProviderConfig {
+    /// The env to inject for this provider: `env_file` (if set) overlaid
+    /// with the inline `env` map. Fails if the file is missing or malformed.
+    pub fn load_env(&self, dir: &Path) -> Result<HashMap<String, String>> {
+        let mut env = match &self.env_file {
+            Some(file) => read_env_file(&dir.join(file)).with_context(|| {
+                format!("Provider '{}': cannot load env_file {file}", self.name)
+            })?,
+            None => HashMap::new(),
+        };
+        env.extend(self.env.iter().map(|(k, v)| (k.clone(), v.clone())));
+        Ok(env)
+    }
+}
+
+/// Parse a dotenv file: `KEY=VALUE` lines, optional `export ` prefix,
+/// `#` comments, and single- or double-quoted values.
+pub fn read_env_file(path: &Path) -> Result<HashMap<String, String>> {
+    let contents = std::fs::read_to_string(path)?;
+    let mut env = HashMap::new();
+    for (i, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let (key, value) = line
+            .split_once('=')
+            .with_context(|| format!("line {}: expected KEY=VALUE", i + 1))?;
+        let key = key.trim();
+        if key.is_empty() || key.contains(char::is_whitespace) {
+            anyhow::bail!("line {}: invalid variable name {key:?}", i + 1);
+        }
+        let value = value.trim();
+        let value = ['"', '\'']
+            .into_iter()
+            .find_map(|q| value.strip_prefix(q)?.strip_suffix(q))
+            .unwrap_or(value);
+        env.insert(key.to_string(), value.to_string());
+    }
+    Ok(env)
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CryoConfig {
+    /// Display name for reports and titles (default: directory name)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project_name: Option<String>,
+
     /// Agent command (e.g. "opencode", "claude", "codex")
     #[serde(default = "default_agent")]
     pub agent: String,
 
+    /// Task given to the plan's first session; later sessions continue
+    /// from the task the agent set or the previous one
+    #[serde(default = "default_first_task")]
+    pub first_task: String,
+
     /// Max retry attempts on agent failure (0 = no retry)
     #[serde(default = "default_max_retries")]
     pub max_retries: u32,
 
+    /// Randomize retry backoff: "none", "full" or "equal"
+    #[serde(default)]
+    pub retry_jitter: RetryJitter,
+
     /// Session timeout in seconds (0 = no timeout)
     #[serde(default)]
     pub max_session_duration: u64,
 
+    /// Longest a session may run once `cryo-agent defer` has extended its
+    /// timeout, in seconds (0 = twice the session timeout)
+    #[serde(default)]
+    pub max_session_duration_hard_cap: u64,
+
+    /// Wall-clock hours the whole plan may run, counted from its first
+    /// session; past it the daemon alerts and stops (0 = no cap)
+    #[serde(default)]
+    pub max_total_runtime_hours: u64,
+
+    /// Extra ways to detect plan completion when the agent exits without
+    /// hibernating: "socket" (none), "output", "file" or "any"
+    #[serde(default)]
+    pub complete_marker: CompleteMarker,
+
+    /// Line in the agent's output that means the plan is complete
+    /// (with `complete_marker = "output"` or "any")
+    #[serde(default = "default_complete_marker_text")]
+    pub complete_marker_text: String,
+
+    /// What an exit with code 0 without `cryo-agent hibernate` means:
+    /// "fail", "reschedule" or "complete"
+    #[serde(default)]
+    pub on_clean_exit_without_hibernate: CleanExit,
+
+    /// Wake interval for `on_clean_exit_without_hibernate = "reschedule"`
+    /// when no `--every` schedule is stored (e.g. "1h", "30m")
+    #[serde(default = "default_clean_exit_wake_in")]
+    pub clean_exit_wake_in: String,
+
+    /// Signal used to stop the agent on timeout or shutdown: "term" or "int"
+    #[serde(default)]
+    pub kill_signal: KillSignal,
+
+    /// Seconds to wait after `kill_signal` before escalating to SIGKILL
+    #[serde(default = "default_kill_grace_secs")]
+    pub kill_grace_secs: u64,
+
+    /// Run the agent in its own process group and signal the whole group on
+    /// timeout or shutdown, so processes it forked are stopped too
+    #[serde(default = "default_kill_process_group")]
+    pub kill_process_group: bool,
+
     /// Watch inbox for reactive wake
     #[serde(default = "default_watch_inbox")]
     pub watch_inbox: bool,
 
+    /// Minimum seconds between the end of a session and a reactive wake
+    /// (new inbox or watch_dirs files). Scheduled wakes and `cryo wake`
+    /// are not delayed. 0 = no gap (default)
+    #[serde(default)]
+    pub min_session_gap_secs: u64,
+
+    /// Sleep this long ("6h", "1h30m") when the agent hibernates without a
+    /// wake time and no `--every` schedule is stored (default: none, such
+    /// a hibernate is rejected). plan.md front-matter may override it.
+    #[serde(default)]
+    pub wake_cadence: Option<String>,
+
+    /// Daily window ("22:00-07:00") in which no session starts; a session
+    /// due inside it waits for the end (default: none). plan.md
+    /// front-matter may override it.
+    #[serde(default)]
+    pub quiet_hours: Option<String>,
+
+    /// Sessions started per calendar day before the next one waits for
+    /// midnight (0 = unlimited, default). plan.md front-matter may
+    /// override it.
+    #[serde(default)]
+    pub max_sessions_per_day: u32,
+
+    /// Extra directories whose new files are imported into the inbox and
+    /// wake the agent (relative paths are resolved against the project dir)
+    #[serde(default)]
+    pub watch_dirs: Vec<String>,
+
+    /// What to do with consumed inbox messages: "archive", "delete", or "flag"
+    #[serde(default)]
+    pub inbox_disposition: InboxDisposition,
+
+    /// Keep inbox messages the agent never read (`cryo-agent receive`, or
+    /// naming the file in its output) for the next session instead of
+    /// disposing of them, for at most `inbox_grace_sessions` sessions
+    #[serde(default)]
+    pub require_inbox_ack: bool,
+
+    /// Sessions a message the agent neither handled nor kept may stay in
+    /// the inbox, once the agent marks messages with `cryo-agent handled`
+    #[serde(default = "default_inbox_grace_sessions")]
+    pub inbox_grace_sessions: u32,
+
+    /// Reject `cryo-agent hibernate` without a non-empty summary; with
+    /// `--complete` the summary is sent to the outbox as the final report
+    #[serde(default)]
+    pub require_summary: bool,
+
+    /// Message storage backend: "file" or "sqlite"
+    #[serde(default)]
+    pub message_store: MessageStoreKind,
+
+    /// Inbox directory, relative to the project
+    #[serde(default = "default_inbox_dir")]
+    pub inbox_dir: String,
+
+    /// Outbox directory, relative to the project
+    #[serde(default = "default_outbox_dir")]
+    pub outbox_dir: String,
+
+    /// Where to put the daemon socket instead of `.cryo/` (for projects on
+    /// NFS/SMB); a per-project subdirectory is created inside it. The daemon
+    /// registry is shared by all projects and doesn't move with it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub runtime_dir: Option<String>,
+
+    /// Also register an OS timer (systemd/launchd) for each scheduled wake so
+    /// it survives a reboot when the daemon was not installed as a service
+    #[serde(default)]
+    pub persist_wake_across_reboot: bool,
+
+    /// Custom header for session pushes to GitHub/Zulip and periodic
+    /// reports, with placeholders like `{session}` and `{outcome}`
+    /// (unset = built-in format)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary_template: Option<String>,
+
+    /// Make `cryo send` wake the agent as if `--wake` were passed
+    /// (`--no-wake` opts out per message)
+    #[serde(default)]
+    pub auto_wake_on_send: bool,
+
     /// Web UI host (default: 127.0.0.1)
     #[serde(default = "default_web_host")]
     pub web_host: String,
@@ -55,6 +420,11 @@ pub struct CryoConfig {
     #[serde(default = "default_web_port")]
     pub web_port: u16,
 
+    /// How often `cryo web` polls cryo.log for new lines, in milliseconds
+    /// (timer.json is polled at four times this interval). Default: 500
+    #[serde(default = "default_web_poll_ms")]
+    pub web_poll_ms: u64,
+
     /// Fallback alert method: "notify" (desktop popup), "outbox" (file only), "none"
     #[serde(default = "default_fallback_alert")]
     pub fallback_alert: String,
@@ -82,10 +452,143 @@ pub struct CryoConfig {
     /// GitHub sync polling interval in seconds (default: 5)
     #[serde(default = "default_poll_interval")]
     pub gh_poll_interval: u64,
+
+    /// Give up on a single GitHub/Zulip request in the sync daemons after
+    /// this many seconds and retry next cycle (0 = no timeout, default: 60)
+    #[serde(default = "default_channel_timeout_secs")]
+    pub channel_timeout_secs: u64,
+
+    /// Failed posts of one outbox message before the sync daemons move it
+    /// to `outbox/dead-letter/` (0 = retry forever, default: 5)
+    #[serde(default = "default_outbox_max_attempts")]
+    pub outbox_max_attempts: u32,
+
+    /// HTTP proxy for the GitHub and Zulip clients, e.g.
+    /// "http://proxy.corp:3128" (default: `HTTPS_PROXY`/`HTTP_PROXY`)
+    #[serde(default)]
+    pub proxy: Option<String>,
+
+    /// Hosts that bypass the proxy ("internal.corp", ".corp", "*.corp"),
+    /// in addition to the `NO_PROXY` env var
+    #[serde(default)]
+    pub no_proxy: Vec<String>,
+
+    /// URL the daemon POSTs a JSON event to when a session starts (session
+    /// number, task, provider). Failures are logged and ignored.
+    #[serde(default)]
+    pub session_start_webhook: Option<String>,
+
+    /// URL the daemon POSTs a JSON event to when a session ends (outcome,
+    /// summary, duration). Failures are logged and ignored.
+    #[serde(default)]
+    pub session_end_webhook: Option<String>,
+
+    /// `cryo clean` keeps the session number, as if run with
+    /// `--keep-session-number`
+    #[serde(default)]
+    pub keep_session_number: bool,
+
+    /// Regexes to scrub from logs and prompts. They add to the built-in
+    /// secret detection, which always scrubs provider env values named like
+    /// a secret (`*KEY*`, `*TOKEN*`, ...); they don't replace it.
+    #[serde(default)]
+    pub redact_patterns: Vec<String>,
+
+    /// Regex with named groups `tokens` and/or `cost` matched against agent
+    /// output; matches are summed per session and rolled up in reports
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage_pattern: Option<String>,
+
+    /// Hours a `cryo-agent request-review` may go unanswered before the
+    /// fallback alert fires (0 = the usual 7-day until-message alert)
+    #[serde(default = "default_review_deadline_hours")]
+    pub review_deadline_hours: u64,
+
+    /// Senders (a message's `from`) whose channel replies `approve <id>` /
+    /// `reject <id>` decide reviews; empty = only `cryo review` and the web UI
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub review_approvers: Vec<String>,
+
+    /// Daemon diagnostic verbosity: "quiet", "normal", or "verbose"
+    #[serde(default)]
+    pub log_level: LogLevel,
+
+    /// Timezone for `cryo.log` session headers and events: "utc" or "local"
+    #[serde(default)]
+    pub log_timezone: LogTimezone,
+
+    /// strftime pattern for both session headers and events in `cryo.log`
+    /// (unset = full timestamp on headers, `%H:%M:%S` on events)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_timestamp_format: Option<String>,
+
+    /// Previous-session history included in the prompt: "none",
+    /// "last-session", or "full" (unset = depends on `prompt_style`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub history_mode: Option<HistoryMode>,
+
+    /// "slim" (point the agent at cryo.log and the inbox) or "embedded"
+    /// (put unread messages and session history into the prompt)
+    #[serde(default)]
+    pub prompt_style: PromptStyle,
+
+    /// "oldest-first" or "newest-first": order of the messages embedded in
+    /// the prompt, by timestamp
+    #[serde(default)]
+    pub message_order: MessageOrder,
+
+    /// Extra arguments for the agent command, placed after the subcommand
+    /// cryochamber injects (`opencode run`, `codex exec`) and before the prompt
+    #[serde(default)]
+    pub agent_args: Vec<String>,
+
+    /// How the prompt reaches the agent: "arg" (last argument, default)
+    /// or "stdin"
+    #[serde(default)]
+    pub agent_prompt_position: PromptPosition,
+
+    /// Most bytes of a file `cryo-agent attach-log` copies into cryo.log;
+    /// longer files keep only their tail (default: 8192)
+    #[serde(default = "default_attach_log_max_bytes")]
+    pub attach_log_max_bytes: usize,
+
+    /// Rotate `cryo-agent.log` to `cryo-agent.log.1.gz` before a session
+    /// once it is larger than this (0 = never; default: 100 MiB)
+    #[serde(default = "default_max_agent_log_bytes")]
+    pub max_agent_log_bytes: u64,
+
+    /// How many gzipped generations of `cryo-agent.log` to keep (default: 3)
+    #[serde(default = "default_agent_log_keep")]
+    pub agent_log_keep: u32,
+
+    /// Save each session's prompt, agent argv, provider env keys and output
+    /// to `.cryo/recordings/session-<n>.json` (see `cryo replay`)
+    #[serde(default)]
+    pub record_session: bool,
+
+    /// How many session recordings to keep; older ones are pruned (default: 20)
+    #[serde(default = "default_recordings_keep")]
+    pub recordings_keep: usize,
+}
+
+/// Env var naming the agent to use when neither `--agent` nor cryo.toml
+/// sets one.
+pub const AGENT_ENV: &str = "CRYO_AGENT";
+
+/// `CRYO_AGENT`, if set and non-empty.
+pub fn env_agent() -> Option<String> {
+    std::env::var(AGENT_ENV)
+        .ok()
+        .filter(|s| !s.trim().is_empty())
+}
+
+/// `CRYO_AGENT`, falling back to "opencode".
+pub fn default_agent() -> String {
+    env_agent().unwrap_or_else(|| "opencode".to_string())
 }
 
-fn default_agent() -> String {
-    "opencode".to_string()
+fn default_inbox_grace_sessions() -> u32 {
+    3
 }
 
 fn default_max_retries() -> u32 {
@@ -96,6 +599,14 @@ fn default_watch_inbox() -> bool {
     true
 }
 
+fn default_inbox_dir() -> String {
+    "messages/inbox".to_string()
+}
+
+fn default_outbox_dir() -> String {
+    "messages/outbox".to_string()
+}
+
 fn default_web_host() -> String {
     "127.0.0.1".to_string()
 }
@@ -104,10 +615,30 @@ fn default_web_port() -> u16 {
     3945
 }
 
+fn default_attach_log_max_bytes() -> usize {
+    8192
+}
+
+fn default_max_agent_log_bytes() -> u64 {
+    100 * 1024 * 1024
+}
+
+fn default_agent_log_keep() -> u32 {
+    3
+}
+
+fn default_web_poll_ms() -> u64 {
+    500
+}
+
 fn default_fallback_alert() -> String {
     "notify".to_string()
 }
 
+fn default_first_task() -> String {
+    "Execute the first task from the plan".to_string()
+}
+
 fn default_report_time() -> String {
     "09:00".to_string()
 }
@@ -116,15 +647,76 @@ fn default_poll_interval() -> u64 {
     5
 }
 
+fn default_channel_timeout_secs() -> u64 {
+    60
+}
+
+fn default_outbox_max_attempts() -> u32 {
+    5
+}
+
+fn default_review_deadline_hours() -> u64 {
+    24
+}
+
+fn default_kill_grace_secs() -> u64 {
+    2
+}
+
+fn default_kill_process_group() -> bool {
+    true
+}
+
+fn default_complete_marker_text() -> String {
+    "[CRYO:PLAN-COMPLETE]".to_string()
+}
+
+fn default_clean_exit_wake_in() -> String {
+    "1h".to_string()
+}
+
+fn default_recordings_keep() -> usize {
+    20
+}
+
 impl Default for CryoConfig {
     fn default() -> Self {
         Self {
+            project_name: None,
             agent: default_agent(),
+            first_task: default_first_task(),
             max_retries: default_max_retries(),
+            retry_jitter: RetryJitter::default(),
             max_session_duration: 0,
+            max_session_duration_hard_cap: 0,
+            max_total_runtime_hours: 0,
+            complete_marker: CompleteMarker::default(),
+            complete_marker_text: default_complete_marker_text(),
+            on_clean_exit_without_hibernate: CleanExit::default(),
+            clean_exit_wake_in: default_clean_exit_wake_in(),
+            kill_signal: KillSignal::default(),
+            kill_grace_secs: default_kill_grace_secs(),
+            kill_process_group: default_kill_process_group(),
             watch_inbox: default_watch_inbox(),
+            min_session_gap_secs: 0,
+            wake_cadence: None,
+            quiet_hours: None,
+            max_sessions_per_day: 0,
+            watch_dirs: Vec::new(),
+            inbox_disposition: InboxDisposition::default(),
+            require_inbox_ack: false,
+            inbox_grace_sessions: default_inbox_grace_sessions(),
+            require_summary: false,
+            message_store: MessageStoreKind::default(),
+            inbox_dir: default_inbox_dir(),
+            outbox_dir: default_outbox_dir(),
+            runtime_dir: None,
+            persist_wake_across_reboot: false,
+            summary_template: None,
+            auto_wake_on_send: false,
             web_host: default_web_host(),
             web_port: default_web_port(),
+            web_poll_ms: default_web_poll_ms(),
             fallback_alert: default_fallback_alert(),
             report_time: default_report_time(),
             report_interval: 0,
@@ -132,11 +724,66 @@ impl Default for CryoConfig {
             providers: Vec::new(),
             zulip_poll_interval: default_poll_interval(),
             gh_poll_interval: default_poll_interval(),
+            channel_timeout_secs: default_channel_timeout_secs(),
+            outbox_max_attempts: default_outbox_max_attempts(),
+            proxy: None,
+            no_proxy: Vec::new(),
+            session_start_webhook: None,
+            session_end_webhook: None,
+            keep_session_number: false,
+            redact_patterns: Vec::new(),
+            usage_pattern: None,
+            review_deadline_hours: default_review_deadline_hours(),
+            review_approvers: Vec::new(),
+            log_level: LogLevel::default(),
+            log_timezone: LogTimezone::default(),
+            log_timestamp_format: None,
+            history_mode: None,
+            prompt_style: PromptStyle::default(),
+            message_order: MessageOrder::default(),
+            agent_args: Vec::new(),
+            agent_prompt_position: PromptPosition::default(),
+            attach_log_max_bytes: default_attach_log_max_bytes(),
+            max_agent_log_bytes: default_max_agent_log_bytes(),
+            agent_log_keep: default_agent_log_keep(),
+            record_session: false,
+            recordings_keep: default_recordings_keep(),
         }
     }
 }
 
 impl CryoConfig {
+    /// Load every provider's `env_file` so a missing or malformed one is
+    /// reported at startup instead of when that provider's session spawns.
+    pub fn check_env_files(&self, dir: &Path) -> Result<()> {
+        for provider in &self.providers {
+            provider.load_env(dir)?;
+        }
+        Ok(())
+    }
+
+    /// History to put into the prompt: `history_mode` if set, else the
+    /// last session for an embedded prompt and none for a slim one.
+    pub fn effective_history_mode(&self) -> HistoryMode {
+        self.history_mode.unwrap_or(match self.prompt_style {
+            PromptStyle::Embedded => HistoryMode::LastSession,
+            PromptStyle::Slim => HistoryMode::None,
+        })
+    }
+
+    /// Project display name: `project_name` if set, else the directory name.
+    pub fn project_name(&self, dir: &Path) -> String {
+        self.project_name
+            .clone()
+            .filter(|n| !n.trim().is_empty())
+            .or_else(|| {
+                dir.file_name()
+                    .and_then(|n| n.to_str())
+                    .map(|n| n.to_string())
+            })
+            .unwrap_or_else(|| "unknown".to_string())
+    }
+
     /// Merge CLI overrides from timer.json into this config.
     /// Only overrides fields that were explicitly set (Some).
     pub fn apply_overrides(&mut self, state: &CryoState) {
@@ -165,6 +812,195 @@ pub fn load_config(path: &Path) -> Result<Option<CryoConfig>> {
     Ok(Some(config))
 }
 
+/// Field names of a struct, read from its derived `Deserialize` impl, so
+/// the list of accepted keys can't drift from the struct definition.
+fn struct_fields<'de, T: Deserialize<'de>>() -> &'static [&'static str] {
+    use serde::de::{self, Visitor};
+
+    struct FieldNames<'a>(&'a mut &'static [&'static str]);
+
+    impl<'de> de::Deserializer<'de> for FieldNames<'_> {
+        type Error = de::value::Error;
+
+        fn deserialize_any<V: Visitor<'de>>(self, _: V) -> Result<V::Value, Self::Error> {
+            Err(de::Error::custom("not a struct"))
+        }
+
+        fn deserialize_struct<V: Visitor<'de>>(
+            self,
+            _name: &'static str,
+            fields: &'static [&'static str],
+            _visitor: V,
+        ) -> Result<V::Value, Self::Error> {
+            *self.0 = fields;
+            Err(de::Error::custom("field names only"))
+        }
+
+        serde::forward_to_deserialize_any! {
+            bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+            bytes byte_buf option unit unit_struct newtype_struct seq tuple
+            tuple_struct map enum identifier ignored_any
+        }
+    }
+
+    let mut fields: &'static [&'static str] = &[];
+    let _ = T::deserialize(FieldNames(&mut fields));
+    fields
+}
+
+/// Levenshtein distance, for "did you mean" hints on misspelled keys.
+pub(crate) fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut cur = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let sub = prev[j] + usize::from(ca != *cb);
+            cur.push(sub.min(prev[j + 1] + 1).min(cur[j] + 1));
+        }
+        prev = cur;
+    }
+    prev[b.len()]
+}
+
+/// Line (1-based) where `key` is assigned in `contents`, at any nesting.
+fn key_line(contents: &str, key: &str) -> Option<usize> {
+    contents
+        .lines()
+        .position(|line| {
+            line.trim_start()
+                .strip_prefix(key)
+                .is_some_and(|rest| rest.trim_start().starts_with('='))
+        })
+        .map(|i| i + 1)
+}
+
+fn unknown_key_warning(contents: &str, key: &str, known: &[&str], table: &str) -> String {
+    let location = key_line(contents, key)
+        .map(|n| format!("line {n}: "))
+        .unwrap_or_default();
+    let hint = known
+        .iter()
+        .filter(|k| edit_distance(key, k) <= 2)
+        .min_by_key(|k| edit_distance(key, k))
+        .map(|k| format!(" (did you mean `{k}`?)"))
+        .unwrap_or_default();
+    format!("{location}unknown key `{key}`{table} is ignored{hint}")
+}
+
+/// Problems in a parsed cryo.toml that serde lets through: unknown keys
+/// (usually typos, silently ignored otherwise) and values that parse but
+/// can't work. Returns every problem found, each prefixed with its line
+/// when it can be located.
+pub fn validate_config(contents: &str, config: &CryoConfig) -> Vec<String> {
+    let mut warnings = Vec::new();
+
+    if let Ok(table) = contents.parse::<toml::Table>() {
+        let known = struct_fields::<CryoConfig>();
+        for key in table.keys().filter(|k| !known.contains(&k.as_str())) {
+            warnings.push(unknown_key_warning(contents, key, known, ""));
+        }
+        let provider_fields = struct_fields::<ProviderConfig>();
+        if let Some(providers) = table.get("providers").and_then(|p| p.as_array()) {
+            for provider in providers.iter().filter_map(|p| p.as_table()) {
+                for key in provider
+                    .keys()
+                    .filter(|k| !provider_fields.contains(&k.as_str()))
+                {
+                    warnings.push(unknown_key_warning(
+                        contents,
+                        key,
+                        provider_fields,
+                        " in [[providers]]",
+                    ));
+                }
+            }
+        }
+    }
+
+    let at = |key: &str| {
+        key_line(contents, key)
+            .map(|n| format!("line {n}: "))
+            .unwrap_or_default()
+    };
+    let mut seen = std::collections::HashSet::new();
+    for provider in &config.providers {
+        if provider.name.trim().is_empty() {
+            warnings.push(format!("{}provider name is empty", at("name")));
+        } else if !seen.insert(provider.name.as_str()) {
+            warnings.push(format!(
+                "provider `{}` is defined more than once",
+                provider.name
+            ));
+        }
+    }
+    if config.rotate_on != RotateOn::Never && config.providers.len() < 2 {
+        warnings.push(format!(
+            "{}rotate_on has no effect with fewer than two providers",
+            at("rotate_on")
+        ));
+    }
+    if config.report_interval > 0
+        && chrono::NaiveTime::parse_from_str(&config.report_time, "%H:%M").is_err()
+    {
+        warnings.push(format!(
+            "{}report_time {:?} is not HH:MM, so no reports will be sent",
+            at("report_time"),
+            config.report_time
+        ));
+    }
+    if config.web_port == 0 {
+        warnings.push(format!(
+            "{}web_port must be between 1 and 65535",
+            at("web_port")
+        ));
+    }
+    if config.web_poll_ms == 0 {
+        warnings.push(format!("{}web_poll_ms must be positive", at("web_poll_ms")));
+    }
+    for pattern in &config.redact_patterns {
+        if let Err(e) = regex::Regex::new(pattern) {
+            warnings.push(format!(
+                "{}invalid redact pattern {pattern:?}: {e}",
+                at("redact_patterns")
+            ));
+        }
+    }
+    if let Some(pattern) = &config.usage_pattern {
+        if let Err(e) = crate::usage::UsageExtractor::new(pattern) {
+            warnings.push(format!("{}{e:#}", at("usage_pattern")));
+        }
+    }
+    if let Err(e) = crate::schedule::parse_wake_in(&config.clean_exit_wake_in) {
+        warnings.push(format!(
+            "{}clean_exit_wake_in: {e:#}",
+            at("clean_exit_wake_in")
+        ));
+    }
+    if let Some(cadence) = &config.wake_cadence {
+        if let Err(e) = crate::schedule::parse_wake_in(cadence) {
+            warnings.push(format!("{}wake_cadence: {e:#}", at("wake_cadence")));
+        }
+    }
+    if let Some(quiet) = &config.quiet_hours {
+        if let Err(e) = crate::schedule::QuietHours::parse(quiet) {
+            warnings.push(format!("{}{e:#}", at("quiet_hours")));
+        }
+    }
+    warnings
+}
+
+/// Load cryo.toml at `path` and return [`validate_config`]'s warnings
+/// (empty if there is no file). Parse errors are returned as errors.
+pub fn config_warnings(path: &Path) -> Result<Vec<String>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let contents = std::fs::read_to_string(path)?;
+    let config: CryoConfig = toml::from_str(&contents)?;
+    Ok(validate_config(&contents, &config))
+}
+
 pub fn save_config(path: &Path, config: &CryoConfig) -> Result<()> {
     let toml = toml::to_string_pretty(config)?;
     std::fs::write(path, toml)?;
@@ -196,6 +1032,40 @@ mod tests {
         assert!(config.watch_inbox, "Should use default watch_inbox");
     }
 
+    #[test]
+    fn test_project_name_falls_back_to_dir() {
+        let dir = std::path::Path::new("/tmp/my-project");
+        let mut config = CryoConfig::default();
+        assert_eq!(config.project_name(dir), "my-project");
+        config.project_name = Some("Nightly Bench".to_string());
+        assert_eq!(config.project_name(dir), "Nightly Bench");
+    }
+
+    #[test]
+    fn test_load_inbox_disposition() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cryo.toml");
+        std::fs::write(&path, "inbox_disposition = \"flag\"\n").unwrap();
+        let config = load_config(&path).unwrap().unwrap();
+        assert_eq!(config.inbox_disposition, InboxDisposition::Flag);
+        std::fs::write(&path, "").unwrap();
+        let config = load_config(&path).unwrap().unwrap();
+        assert_eq!(config.inbox_disposition, InboxDisposition::Archive);
+    }
+
+    #[test]
+    fn test_load_log_level() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cryo.toml");
+        std::fs::write(&path, "log_level = \"verbose\"\n").unwrap();
+        let config = load_config(&path).unwrap().unwrap();
+        assert_eq!(config.log_level, LogLevel::Verbose);
+
+        std::fs::write(&path, "agent = \"claude\"\n").unwrap();
+        let config = load_config(&path).unwrap().unwrap();
+        assert_eq!(config.log_level, LogLevel::Normal);
+    }
+
     #[test]
     fn test_apply_overrides_all_fields() {
         let mut config = CryoConfig::default();
@@ -209,6 +1079,7 @@ mod tests {
             next_wake: None,
             last_report_time: None,
             provider_index: None,
+            ..Default::default()
         };
         config.apply_overrides(&state);
         assert_eq!(config.agent, "claude");
@@ -230,6 +1101,7 @@ mod tests {
             next_wake: None,
             last_report_time: None,
             provider_index: None,
+            ..Default::default()
         };
         config.apply_overrides(&state);
         assert_eq!(config.agent, original.agent);
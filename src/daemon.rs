@@ -13,18 +13,73 @@ use notify::{RecommendedWatcher, RecursiveMode, Watcher};
 use signal_hook::consts::{SIGINT, SIGTERM, SIGUSR1};
 use signal_hook::flag;
 use std::path::{Path, PathBuf};
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
 use std::sync::mpsc;
 use std::sync::Arc;
 use std::time::Duration;
 
-use crate::config::CryoConfig;
+use crate::config::{CleanExit, CryoConfig, LogLevel, MessageStoreKind, RetryJitter};
 use crate::fallback::FallbackAction;
-use crate::state::{self, CryoState};
+use crate::log::SessionOutcome;
+use crate::state::{self, CryoState, StopReason};
+use crate::store::MessageStore;
+
+/// Verbosity of the daemons' own diagnostics, from `log_level` in
+/// `cryo.toml`. Process-wide, so helper threads follow it too; a process
+/// embedding several daemons (`Daemon::spawn`) uses the most verbose of their
+/// levels. Session boundaries go through [`notice!`] and are kept at every
+/// level; only failures and warnings go straight to `eprintln!`.
+static LOG_LEVEL: AtomicU8 = AtomicU8::new(u8::MAX);
+
+fn log_enabled(level: LogLevel) -> bool {
+    let current = match LOG_LEVEL.load(Ordering::Relaxed) {
+        u8::MAX => LogLevel::Normal as u8,
+        l => l,
+    };
+    current >= level as u8
+}
+
+/// Raise the process-wide level to `level` (see [`LOG_LEVEL`]).
+fn set_log_level(level: LogLevel) {
+    let level = level as u8;
+    let _ = LOG_LEVEL.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |l| {
+        (l == u8::MAX || l < level).then_some(level)
+    });
+}
+
+/// Session boundaries (start, completion, stop), printed at every level.
+macro_rules! notice {
+    ($($arg:tt)*) => {
+        if log_enabled(LogLevel::Quiet) {
+            eprintln!($($arg)*);
+        }
+    };
+}
+
+/// Operational chatter, printed at `normal` and `verbose`.
+macro_rules! info {
+    ($($arg:tt)*) => {
+        if log_enabled(LogLevel::Normal) {
+            eprintln!($($arg)*);
+        }
+    };
+}
+
+/// Extra diagnostics, printed only at `verbose`.
+macro_rules! verbose {
+    ($($arg:tt)*) => {
+        if log_enabled(LogLevel::Verbose) {
+            eprintln!($($arg)*);
+        }
+    };
+}
 
 /// Format for persisting `next_wake` in timer.json (minute precision, no seconds).
 const WAKE_TIME_FMT: &str = "%Y-%m-%dT%H:%M";
 
+/// Task for sessions after the first when the agent didn't set one.
+const CONTINUE_TASK: &str = "Continue the plan";
+
 use crate::process::send_signal;
 
 /// Events the daemon responds to.
@@ -32,10 +87,74 @@ use crate::process::send_signal;
 pub enum DaemonEvent {
     /// New file appeared in messages/inbox/.
     InboxChanged,
+    /// Explicit wake request (SIGUSR1 from `cryo wake` / `cryo send --wake`).
+    WakeRequested,
+    /// New file appeared in one of the configured `watch_dirs`.
+    DropDirChanged,
     /// SIGTERM or SIGINT received.
     Shutdown,
 }
 
+/// Collapse the wake triggers that queued up while a session ran into at
+/// most one of each kind, so a burst of inbox events (or an inbox event plus
+/// `cryo wake`) leads to one follow-up session rather than one per event.
+/// A forced wake covers an inbox change. Returns how many events were dropped.
+fn coalesce_queued_events(
+    rx: &mpsc::Receiver<DaemonEvent>,
+    tx: &mpsc::Sender<DaemonEvent>,
+) -> usize {
+    let (mut shutdown, mut wake, mut inbox, mut drop_dir) = (false, false, false, false);
+    let mut queued = 0;
+    while let Ok(event) = rx.try_recv() {
+        queued += 1;
+        match event {
+            DaemonEvent::Shutdown => shutdown = true,
+            DaemonEvent::WakeRequested => wake = true,
+            DaemonEvent::InboxChanged => inbox = true,
+            DaemonEvent::DropDirChanged => drop_dir = true,
+        }
+    }
+    let mut kept = Vec::new();
+    if shutdown {
+        kept.push(DaemonEvent::Shutdown);
+    }
+    if wake {
+        kept.push(DaemonEvent::WakeRequested);
+    } else if inbox {
+        kept.push(DaemonEvent::InboxChanged);
+    }
+    if drop_dir {
+        kept.push(DaemonEvent::DropDirChanged);
+    }
+    let dropped = queued - kept.len();
+    for event in kept {
+        let _ = tx.send(event);
+    }
+    dropped
+}
+
+/// Why a session failed, which decides how it is retried.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailureKind {
+    /// The agent could not be started or reached (fork or I/O error).
+    /// Retried with backoff indefinitely.
+    Transient,
+    /// The agent ran but ended without hibernating. Deterministic bugs land
+    /// here, so it is retried at most `max_retries` times.
+    Logic,
+}
+
+/// What the event loop does after a failed session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RetryDecision {
+    /// Backoff elapsed: run the session again.
+    Retry,
+    /// Shutdown was requested while backing off.
+    Shutdown,
+    /// Logic failures used up `max_retries`: wait for a message or `cryo wake`.
+    GiveUp,
+}
+
 /// Tracks retry state with exponential backoff.
 #[derive(Debug)]
 pub struct RetryState {
@@ -43,6 +162,9 @@ pub struct RetryState {
     pub max_retries: u32,
     pub provider_index: usize,
     provider_count: usize,
+    jitter: RetryJitter,
+    /// splitmix64 state for the jitter
+    rng: std::cell::Cell<u64>,
 }
 
 impl RetryState {
@@ -52,15 +174,34 @@ impl RetryState {
             max_retries,
             provider_index: 0,
             provider_count,
+            jitter: RetryJitter::None,
+            rng: std::cell::Cell::new(0),
         }
     }
 
+    /// Randomize each backoff per `jitter` (`retry_jitter` in cryo.toml),
+    /// drawing from a generator seeded with `seed`.
+    pub fn with_jitter(mut self, jitter: RetryJitter, seed: u64) -> Self {
+        self.jitter = jitter;
+        self.rng.set(seed);
+        self
+    }
+
     /// Calculate backoff duration for current attempt.
     /// Doubles each time: 5s, 10s, 20s, ..., capped at 3600s (1 hour).
     /// Always returns a duration (retries indefinitely with backoff).
+    /// With jitter the result is a random point in the jitter range.
     pub fn next_backoff(&self) -> Duration {
         let secs = 5u64.checked_shl(self.attempt).unwrap_or(3600).min(3600);
-        Duration::from_secs(secs)
+        apply_jitter(Duration::from_secs(secs), self.jitter, self.next_random())
+    }
+
+    fn next_random(&self) -> u64 {
+        let state = self.rng.get().wrapping_add(0x9e3779b97f4a7c15);
+        self.rng.set(state);
+        let z = (state ^ (state >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        let z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
     }
 
     pub fn record_failure(&mut self) {
@@ -76,6 +217,12 @@ impl RetryState {
         self.attempt >= self.max_retries
     }
 
+    /// Whether to stop retrying after the failure just recorded.
+    /// Transient failures never give up; logic failures stop once exhausted.
+    pub fn gives_up(&self, kind: FailureKind) -> bool {
+        kind == FailureKind::Logic && self.exhausted()
+    }
+
     /// Advance to the next provider. Returns true if we wrapped back to index 0
     /// (all providers have been tried in this cycle). Resets retry attempt counter.
     pub fn rotate_provider(&mut self) -> bool {
@@ -88,6 +235,32 @@ impl RetryState {
     }
 }
 
+/// Pick a duration in `backoff`'s `jitter` range from `random`: full jitter
+/// is `[0, backoff]`, equal jitter `[backoff/2, backoff]`.
+pub fn apply_jitter(backoff: Duration, jitter: RetryJitter, random: u64) -> Duration {
+    let millis = backoff.as_millis() as u64;
+    let low = match jitter {
+        RetryJitter::None => return backoff,
+        RetryJitter::Full => 0,
+        RetryJitter::Equal => millis / 2,
+    };
+    Duration::from_millis(low + random % (millis - low + 1))
+}
+
+/// Jitter seed that differs between daemons, even several in one process.
+fn jitter_seed(dir: &Path) -> u64 {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos() as u64);
+    let dir_hash = dir
+        .to_string_lossy()
+        .bytes()
+        .fold(0xcbf29ce484222325u64, |h, b| {
+            (h ^ u64::from(b)).wrapping_mul(0x100000001b3)
+        });
+    nanos ^ dir_hash ^ u64::from(std::process::id()).rotate_left(32)
+}
+
 /// Watches `messages/inbox/` for new files and sends events to a channel.
 pub struct InboxWatcher {
     _watcher: RecommendedWatcher,
@@ -95,45 +268,468 @@ pub struct InboxWatcher {
 
 impl InboxWatcher {
     /// Start watching the inbox directory. Sends `DaemonEvent::InboxChanged`
-    /// to `tx` when a new file is created.
+    /// to `tx` when a message file (`*.md`) is created or renamed in, but
+    /// not when one is renamed to `*.md.consumed` after a session.
     pub fn start(inbox_path: &Path, tx: mpsc::Sender<DaemonEvent>) -> Result<Self> {
+        Self::watch(
+            inbox_path,
+            tx,
+            || DaemonEvent::InboxChanged,
+            |path| path.extension().is_some_and(|ext| ext == "md"),
+        )
+    }
+
+    /// Start watching an external drop directory. Sends
+    /// `DaemonEvent::DropDirChanged` to `tx` when a new file is created.
+    pub fn start_drop_dir(path: &Path, tx: mpsc::Sender<DaemonEvent>) -> Result<Self> {
+        Self::watch(path, tx, || DaemonEvent::DropDirChanged, |_| true)
+    }
+
+    fn watch(
+        path: &Path,
+        tx: mpsc::Sender<DaemonEvent>,
+        event_for_create: fn() -> DaemonEvent,
+        relevant: fn(&Path) -> bool,
+    ) -> Result<Self> {
         let mut watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
             if let Ok(event) = res {
-                if event.kind.is_create() {
-                    let _ = tx.send(DaemonEvent::InboxChanged);
+                // Messages are written to a temp file and renamed into place
+                let renamed_in = matches!(
+                    event.kind,
+                    notify::EventKind::Modify(notify::event::ModifyKind::Name(
+                        notify::event::RenameMode::To | notify::event::RenameMode::Both
+                    ))
+                );
+                // The new name comes last in a rename
+                let arrived = event.paths.last().is_some_and(|p| relevant(p));
+                if (event.kind.is_create() || renamed_in) && arrived {
+                    let _ = tx.send(event_for_create());
                 }
             }
         })
         .context("Failed to create file watcher")?;
 
         watcher
-            .watch(inbox_path, RecursiveMode::NonRecursive)
-            .with_context(|| format!("Failed to watch {}", inbox_path.display()))?;
+            .watch(path, RecursiveMode::NonRecursive)
+            .with_context(|| format!("Failed to watch {}", path.display()))?;
 
         Ok(Self { _watcher: watcher })
     }
 }
 
+/// Polls a database-backed message store for new inbox messages — the
+/// counterpart of `InboxWatcher` when there are no inbox files to watch.
+pub struct InboxPoller {
+    stop: Arc<AtomicBool>,
+}
+
+impl InboxPoller {
+    /// Sends `DaemonEvent::InboxChanged` to `tx` whenever an inbox id
+    /// appears that was not there on the previous poll.
+    pub fn start(
+        store: Box<dyn MessageStore>,
+        interval: Duration,
+        tx: mpsc::Sender<DaemonEvent>,
+    ) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let stop_flag = Arc::clone(&stop);
+        std::thread::spawn(move || {
+            let mut known: std::collections::HashSet<String> =
+                store.list_inbox().unwrap_or_default().into_iter().collect();
+            while !stop_flag.load(Ordering::Relaxed) {
+                std::thread::sleep(interval);
+                let Ok(ids) = store.list_inbox() else {
+                    continue;
+                };
+                if ids.iter().any(|id| !known.contains(id))
+                    && tx.send(DaemonEvent::InboxChanged).is_err()
+                {
+                    break;
+                }
+                known = ids.into_iter().collect();
+            }
+        });
+        Self { stop }
+    }
+}
+
+impl Drop for InboxPoller {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// A daemon running on a background thread, returned by `Daemon::spawn`.
+pub struct DaemonHandle {
+    dir: PathBuf,
+    shutdown: Arc<AtomicBool>,
+    wake_requested: Arc<AtomicBool>,
+    session_active: Arc<AtomicBool>,
+    thread: std::thread::JoinHandle<Result<()>>,
+}
+
+impl DaemonHandle {
+    /// Project directory the daemon runs in.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// The daemon's current state from `timer.json`, if any.
+    pub fn status(&self) -> Result<Option<CryoState>> {
+        state::load_state(&state::state_path(&self.dir))
+    }
+
+    /// True until the event loop has returned.
+    pub fn is_running(&self) -> bool {
+        !self.thread.is_finished()
+    }
+
+    /// True while an agent session is running.
+    pub fn in_session(&self) -> bool {
+        self.session_active.load(Ordering::Acquire)
+    }
+
+    /// Wake the agent now, like `cryo wake` (SIGUSR1).
+    pub fn wake(&self) {
+        self.wake_requested.store(true, Ordering::Relaxed);
+    }
+
+    /// Stop the daemon like SIGTERM: a running agent is terminated, then the
+    /// loop exits. Blocks until it has and returns the loop's result.
+    pub fn cancel(self) -> Result<()> {
+        self.shutdown.store(true, Ordering::Relaxed);
+        self.join()
+    }
+
+    /// Block until the event loop returns (plan complete or stopped).
+    pub fn join(self) -> Result<()> {
+        self.thread
+            .join()
+            .unwrap_or_else(|_| Err(anyhow::anyhow!("Daemon thread panicked")))
+    }
+
+    /// Route SIGTERM/SIGINT (stop) and SIGUSR1 (wake) for this process to
+    /// the daemon, as `cryo daemon` does.
+    pub fn forward_signals(&self) -> Result<()> {
+        flag::register(SIGTERM, Arc::clone(&self.shutdown))
+            .context("Failed to register SIGTERM handler")?;
+        flag::register(SIGINT, Arc::clone(&self.shutdown))
+            .context("Failed to register SIGINT handler")?;
+        flag::register(SIGUSR1, Arc::clone(&self.wake_requested))
+            .context("Failed to register SIGUSR1 handler")?;
+        Ok(())
+    }
+}
+
 /// What the daemon should do after a session completes.
 pub enum SessionLoopOutcome {
     PlanComplete,
     Hibernate {
         wake_time: NaiveDateTime,
         fallback: Option<FallbackAction>,
+        /// Agent asked to be woken early by an inbox message.
+        wake_on_message: bool,
+    },
+    /// Agent hibernated with `--until-message`: no scheduled wake.
+    AwaitMessage {
+        fallback: Option<FallbackAction>,
+        /// When the fallback fires; `None` means after
+        /// `UNTIL_MESSAGE_FALLBACK_HOURS`.
+        deadline: Option<NaiveDateTime>,
     },
     ValidationFailed {
         quick_exit: bool,
     },
+    /// plan.md was missing or empty, so the agent was not started.
+    PlanMissing,
+    /// Agent ended the session with `cryo-agent abort`. Not a failure:
+    /// sleep until `wake_time` (`--wake`, else the next scheduled wake), or
+    /// until a message if there is none.
+    Aborted {
+        wake_time: Option<NaiveDateTime>,
+    },
+    /// `cryo start --once` started no session: `quiet_hours` or
+    /// `max_sessions_per_day` hold it back until `until`.
+    Held {
+        until: NaiveDateTime,
+        reason: String,
+    },
+}
+
+/// Another daemon holds the project's lock in `timer.json`.
+#[derive(Debug)]
+pub struct AlreadyRunning {
+    pub pid: Option<u32>,
+}
+
+impl std::fmt::Display for AlreadyRunning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Another daemon is already running (PID: {:?}). Use `cryo cancel` first.",
+            self.pid
+        )
+    }
+}
+
+impl std::error::Error for AlreadyRunning {}
+
+/// Context on a `run_once` error from before the agent was spawned: the
+/// project is locked, or setting up (cryo.toml, the socket) failed. Such a
+/// run never had a session, so it is not a failed one.
+#[derive(Debug)]
+pub struct CouldNotStart;
+
+impl std::fmt::Display for CouldNotStart {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Could not start a session")
+    }
+}
+
+/// Reason recorded in `CryoState::blocked_reason` when plan.md is gone.
+const PLAN_MISSING_REASON: &str = "plan.md missing";
+
+/// How long an `--until-message` hibernation may sit idle before the
+/// fallback alert fires.
+const UNTIL_MESSAGE_FALLBACK_HOURS: i64 = 24 * 7;
+
+/// Alert sent when a wait for a message passes `UNTIL_MESSAGE_FALLBACK_HOURS`.
+fn until_message_fallback() -> FallbackAction {
+    FallbackAction {
+        action: "notify".to_string(),
+        target: "operator".to_string(),
+        message: format!(
+            "Agent has been waiting for a message for {} days.",
+            UNTIL_MESSAGE_FALLBACK_HOURS / 24
+        ),
+    }
 }
 
-/// Gracefully terminate a child process: SIGTERM, wait 2s, SIGKILL if needed.
-fn terminate_child(child: &mut std::process::Child, pid: u32) {
-    send_signal(pid, libc::SIGTERM);
-    std::thread::sleep(Duration::from_secs(2));
-    if child.try_wait().ok().flatten().is_none() {
-        send_signal(pid, libc::SIGKILL);
+/// How long to wait after the agent exits for its output to reach
+/// cryo-agent.log. A process it forked may hold the pipes open for longer.
+const OUTPUT_DRAIN_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Longest single wait in the event loop. A suspend is only noticed once the
+/// wait returns, so this bounds how late a missed wake can be seen.
+const SUSPEND_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Gracefully terminate a child process: send `config.kill_signal`, wait up to
+/// `kill_grace_secs` for it to exit, then SIGKILL. With `kill_process_group`
+/// the signals go to the agent's whole process group, and the wait lasts
+/// until the processes it forked have exited as well. Returns true if
+/// SIGKILL was needed.
+fn terminate_child(child: &mut std::process::Child, pid: u32, config: &CryoConfig) -> bool {
+    let group = config.kill_process_group;
+    let signal = |signal| {
+        if group {
+            crate::process::signal_group(pid, signal)
+        } else {
+            send_signal(pid, signal)
+        }
+    };
+    signal(config.kill_signal.as_libc());
+    let deadline = std::time::Instant::now() + Duration::from_secs(config.kill_grace_secs);
+    let mut exited = false;
+    loop {
+        // Reaping the agent first keeps its zombie out of the group check
+        if !exited && child.try_wait().ok().flatten().is_some() {
+            exited = true;
+        }
+        if exited && !(group && crate::process::is_group_alive(pid)) {
+            break;
+        }
+        if std::time::Instant::now() >= deadline {
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+    let lingering = !exited || (group && crate::process::is_group_alive(pid));
+    if lingering {
+        signal(libc::SIGKILL);
     }
     let _ = child.wait(); // reap to prevent zombie
+    lingering
+}
+
+/// Wake time for a timed hibernate: `--wake` if given, else `now` plus
+/// `--wake-in`, else the next fire of `--every`, else the next fire of the
+/// stored `schedule`.
+fn timed_wake(
+    wake: Option<&str>,
+    wake_in: Option<&str>,
+    every: Option<&str>,
+    schedule: Option<&str>,
+    now: NaiveDateTime,
+) -> Result<NaiveDateTime> {
+    if let Some(wake) = wake {
+        return NaiveDateTime::parse_from_str(wake, WAKE_TIME_FMT)
+            .map_err(|e| anyhow::anyhow!("Invalid wake time: {e}"));
+    }
+    if let Some(wake_in) = wake_in {
+        let by = crate::schedule::parse_wake_in(wake_in)?;
+        return now
+            .checked_add_signed(by)
+            .with_context(|| format!("Wake time {wake_in:?} from now is out of range"));
+    }
+    let Some(expr) = every.or(schedule) else {
+        anyhow::bail!("No wake time: pass --wake or --every");
+    };
+    crate::schedule::CronSchedule::parse(expr)
+        .map_err(|e| anyhow::anyhow!("Invalid schedule: {e}"))?
+        .next_after(now)
+        .with_context(|| format!("Schedule \"{expr}\" never fires"))
+}
+
+/// Wake time for a clean exit under `on_clean_exit_without_hibernate =
+/// "reschedule"`: the stored schedule's next slot, else `clean_exit_wake_in`.
+fn clean_exit_wake(
+    config: &CryoConfig,
+    schedule: Option<&str>,
+    now: NaiveDateTime,
+) -> Result<NaiveDateTime> {
+    match schedule {
+        Some(_) => timed_wake(None, None, None, schedule, now),
+        None => timed_wake(None, Some(&config.clean_exit_wake_in), None, None, now),
+    }
+}
+
+/// Why no session may start at `now`, and when one may: the end of
+/// `quiet_hours`, or midnight once `max_sessions_per_day` sessions have
+/// started today. None when a session can start now.
+pub fn session_hold(
+    config: &CryoConfig,
+    cryo_state: &CryoState,
+    now: NaiveDateTime,
+) -> Option<(NaiveDateTime, String)> {
+    let today = now.date();
+    if config.max_sessions_per_day > 0
+        && cryo_state.sessions_on(today) >= config.max_sessions_per_day
+    {
+        let midnight = today.succ_opt()?.and_time(chrono::NaiveTime::MIN);
+        return Some((
+            midnight,
+            format!(
+                "max_sessions_per_day ({}) reached",
+                config.max_sessions_per_day
+            ),
+        ));
+    }
+    let quiet = config.quiet_hours.as_deref()?;
+    let end = crate::schedule::QuietHours::parse(quiet)
+        .ok()?
+        .end_after(now)?;
+    Some((end, format!("quiet hours ({quiet})")))
+}
+
+/// Name of provider `index`, for log lines.
+fn provider_name(config: &CryoConfig, index: usize) -> &str {
+    config
+        .providers
+        .get(index)
+        .map_or("unknown", |p| p.name.as_str())
+}
+
+/// Take a `cryo-agent rotate-provider` request from the last session: the
+/// provider after the one it ran on, with the agent's reason. None without
+/// a request or with fewer than two providers.
+fn take_provider_rotation(
+    cryo_state: &mut CryoState,
+    provider_count: usize,
+) -> Option<(usize, String)> {
+    let reason = cryo_state.provider_rotation.take()?;
+    if provider_count < 2 {
+        return None;
+    }
+    let current = cryo_state.provider_index.unwrap_or(0) % provider_count;
+    Some(((current + 1) % provider_count, reason))
+}
+
+/// `cryo-agent env` output: the active provider, then one `KEY=value` line
+/// per injected variable in key order, values redacted unless `show_values`.
+fn describe_env(
+    provider: Option<&str>,
+    env: &std::collections::HashMap<String, String>,
+    show_values: bool,
+) -> String {
+    let mut out = format!("provider: {}", provider.unwrap_or("(none)"));
+    let mut keys: Vec<&String> = env.keys().collect();
+    keys.sort();
+    for key in keys {
+        let value = if show_values {
+            env[key].as_str()
+        } else {
+            crate::redact::REDACTED
+        };
+        out.push_str(&format!("\n{key}={value}"));
+    }
+    out
+}
+
+/// Working summary after `cryo-agent summary-append text`: the pieces so
+/// far, joined with "; ".
+fn append_summary(summary: Option<String>, text: &str) -> String {
+    match summary {
+        Some(summary) if !summary.trim().is_empty() => format!("{summary}; {text}"),
+        _ => text.to_string(),
+    }
+}
+
+/// Record the `set-summary` text of a session that ended without hibernating.
+fn log_working_summary(logger: &mut crate::log::EventLogger, summary: Option<&str>) -> Result<()> {
+    match summary {
+        Some(summary) => logger.log_event(&format!("last summary: \"{summary}\"")),
+        None => Ok(()),
+    }
+}
+
+/// Record the tokens and cost `usage_pattern` found in this session's output.
+fn log_usage(
+    logger: &mut crate::log::EventLogger,
+    extractor: Option<&crate::usage::UsageExtractor>,
+    output: &crate::log::SessionOutput,
+) -> Result<()> {
+    match extractor.and_then(|ex| ex.extract(&output.read())) {
+        Some(usage) => logger.log_event(&usage.to_event()),
+        None => Ok(()),
+    }
+}
+
+/// `.cryo/COMPLETE`, which an agent can create to mark the plan done
+/// (with `complete_marker = "file"` or "any").
+pub fn complete_file_path(dir: &Path) -> PathBuf {
+    dir.join(".cryo").join("COMPLETE")
+}
+
+/// How an agent that exited without `hibernate --complete` said the plan
+/// is done, per `complete_marker`; `None` if it didn't. The output marker
+/// must be a line of its own, so an agent quoting it mid-sentence (e.g.
+/// echoing its instructions) doesn't end the plan.
+fn detect_completion(
+    config: &CryoConfig,
+    dir: &Path,
+    output: &crate::log::SessionOutput,
+) -> Option<&'static str> {
+    if config.complete_marker.checks_file() && complete_file_path(dir).exists() {
+        return Some(".cryo/COMPLETE");
+    }
+    if config.complete_marker.checks_output()
+        && output
+            .read()
+            .lines()
+            .any(|line| line.trim() == config.complete_marker_text.trim())
+    {
+        return Some("output marker");
+    }
+    None
+}
+
+/// Note in the session log that the agent outlived its grace period.
+fn log_kill_escalation(logger: &mut crate::log::EventLogger, config: &CryoConfig) -> Result<()> {
+    logger.log_event(&format!(
+        "agent still running {}s after {} — sent SIGKILL",
+        config.kill_grace_secs,
+        config.kill_signal.name()
+    ))
 }
 
 /// Compute how long to sleep given optional wake and report deadlines.
@@ -155,15 +751,51 @@ fn compute_sleep_timeout(
     }
 }
 
+/// Time left before a reactive wake may start a session, or `None` if the
+/// `min_session_gap_secs` cooldown since `last_session_end` has passed.
+fn session_gap_remaining(
+    last_session_end: Option<std::time::Instant>,
+    gap: Duration,
+    now: std::time::Instant,
+) -> Option<Duration> {
+    let elapsed = now.saturating_duration_since(last_session_end?);
+    gap.checked_sub(elapsed).filter(|left| !left.is_zero())
+}
+
+/// Re-arm the alert of a wait for a message persisted in `state`. A state
+/// saved without a deadline gets a fresh `UNTIL_MESSAGE_FALLBACK_HOURS`.
+fn restore_message_fallback(
+    state: &state::CryoState,
+    now: NaiveDateTime,
+) -> Option<(NaiveDateTime, FallbackAction)> {
+    if !state.awaiting_message {
+        return None;
+    }
+    let deadline = state
+        .message_deadline
+        .as_ref()
+        .and_then(|s| NaiveDateTime::parse_from_str(s, WAKE_TIME_FMT).ok())
+        .unwrap_or_else(|| now + chrono::Duration::hours(UNTIL_MESSAGE_FALLBACK_HOURS));
+    let fallback = state
+        .message_fallback
+        .clone()
+        .unwrap_or_else(until_message_fallback);
+    Some((deadline, fallback))
+}
+
 /// Restore the initial `(next_wake, run_now)` pair from persisted state.
 ///
 /// - If `next_wake` is persisted and in the future → wait (don't run now).
 /// - If `next_wake` is persisted and in the past → run now (delayed wake check will fire).
 /// - If no `next_wake` → run immediately (fresh start).
+/// - If the agent is awaiting a message → wait for the inbox.
 fn restore_wake_state(
     state: &state::CryoState,
     now: NaiveDateTime,
 ) -> (Option<NaiveDateTime>, bool) {
+    if state.awaiting_message {
+        return (None, false);
+    }
     let next_wake = state
         .next_wake
         .as_ref()
@@ -191,6 +823,206 @@ fn detect_delayed_wake(scheduled: NaiveDateTime, now: NaiveDateTime) -> Option<S
     }
 }
 
+/// Wall-clock time that passed beyond the monotonic clock while the daemon
+/// waited. `Instant` stops while the host is suspended, so a gap over a
+/// minute means it slept for about that long.
+fn suspended_for(
+    wall_elapsed: chrono::Duration,
+    mono_elapsed: Duration,
+) -> Option<chrono::Duration> {
+    let gap = wall_elapsed - chrono::Duration::from_std(mono_elapsed).ok()?;
+    (gap > chrono::Duration::minutes(1)).then_some(gap)
+}
+
+/// Wake time to keep after a suspend. A slot of the recurring `schedule`
+/// missed while asleep moves to the next slot after `now`, instead of
+/// running late; a one-off wake stays put and runs now as a delayed wake.
+fn wake_after_suspend(
+    next_wake: Option<NaiveDateTime>,
+    schedule: Option<&str>,
+    now: NaiveDateTime,
+) -> Option<NaiveDateTime> {
+    let wake = next_wake?;
+    if wake > now {
+        return Some(wake);
+    }
+    let Some(cron) = schedule.and_then(|s| crate::schedule::CronSchedule::parse(s).ok()) else {
+        return Some(wake);
+    };
+    let is_slot = cron.next_after(wake - chrono::Duration::minutes(1)) == Some(wake);
+    match cron.next_after(now) {
+        Some(next) if is_slot => Some(next),
+        _ => Some(wake),
+    }
+}
+
+/// Write the agent's `--result` payload to `.cryo/result.json`.
+fn save_result(dir: &Path, result: &str) -> Result<()> {
+    let path = state::result_path(dir);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&path, result).with_context(|| format!("writing {}", path.display()))
+}
+
+/// Validate `data` as JSON and store it as the checkpoint.
+fn save_checkpoint(dir: &Path, data: &str) -> Result<()> {
+    serde_json::from_str::<serde_json::Value>(data).context("checkpoint is not valid JSON")?;
+    let path = state::checkpoint_path(dir);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&path, data).with_context(|| format!("writing {}", path.display()))
+}
+
+/// True if `plan.md` exists in `dir` and has non-whitespace content.
+fn plan_is_present(dir: &Path) -> bool {
+    std::fs::read_to_string(dir.join("plan.md")).is_ok_and(|s| !s.trim().is_empty())
+        || !crate::plans::list(dir).unwrap_or_default().is_empty()
+}
+
+/// Pick the `plans/` plan for the coming session and record it as the
+/// focus: the one asked for with `focus-plan`, the same one again after a
+/// failed session, else the next unfinished one. None without `plans/`.
+fn choose_focus(cryo_state: &mut CryoState, plans: &[String]) -> Option<String> {
+    let open = |name: &String| plans.contains(name) && !cryo_state.completed_plans.contains(name);
+    let requested = cryo_state.focus_request.take().filter(open);
+    let retry = cryo_state
+        .focus_plan
+        .clone()
+        .filter(|name| open(name) && cryo_state.last_outcome == Some(SessionOutcome::Failed));
+    let focus = requested.or(retry).or_else(|| {
+        crate::plans::next_focus(
+            plans,
+            &cryo_state.completed_plans,
+            cryo_state.focus_plan.as_deref(),
+        )
+    });
+    cryo_state.focus_plan = focus.clone();
+    focus
+}
+
+/// FNV-1a hash of `plan.md` and the `plans/*.md` files, or None if there
+/// is no plan to read. Stable across builds, unlike `DefaultHasher`, since
+/// it is persisted in `timer.json`.
+fn plan_hash(dir: &Path) -> Option<String> {
+    let mut files = vec!["plan.md".to_string()];
+    files.extend(
+        crate::plans::list(dir)
+            .unwrap_or_default()
+            .iter()
+            .map(|name| crate::plans::plan_file(name)),
+    );
+    let mut bytes = Vec::new();
+    for file in files {
+        if let Ok(content) = std::fs::read(dir.join(&file)) {
+            // The name too, so moving text between plans counts as a change
+            bytes.extend(file.as_bytes());
+            bytes.push(0);
+            bytes.extend(content);
+            bytes.push(0);
+        }
+    }
+    if bytes.is_empty() {
+        return None;
+    }
+    Some(format!("{:016x}", crate::fnv1a(&bytes)))
+}
+
+/// Session timeout for this run. A `--max-session-duration` given on the
+/// command line wins; otherwise the active provider's override, then the
+/// global `max_session_duration`.
+fn session_timeout_secs(
+    config: &CryoConfig,
+    cryo_state: &CryoState,
+    provider: Option<&crate::config::ProviderConfig>,
+) -> u64 {
+    if cryo_state.max_session_duration_override.is_some() {
+        return config.max_session_duration;
+    }
+    provider
+        .and_then(|p| p.max_session_duration)
+        .unwrap_or(config.max_session_duration)
+}
+
+/// Longest a session may run after `cryo-agent defer`: the hard cap, or
+/// twice the session timeout when none is set.
+fn defer_cap_secs(config: &CryoConfig, timeout_secs: u64) -> u64 {
+    match config.max_session_duration_hard_cap {
+        0 => timeout_secs.saturating_mul(2),
+        cap => cap.max(timeout_secs),
+    }
+}
+
+/// The session deadline moved `by` later but no further than `cap_secs`
+/// after `started`; `None` if it can't move at all.
+fn deferred_deadline(
+    deadline: std::time::Instant,
+    started: std::time::Instant,
+    by: Duration,
+    cap_secs: u64,
+) -> Option<std::time::Instant> {
+    let moved = (deadline + by).min(started + Duration::from_secs(cap_secs));
+    (moved > deadline).then_some(moved)
+}
+
+/// A `wait-for-reply` request whose response is held until an answer lands
+/// in the inbox or the wait times out.
+struct PendingReply {
+    id: Option<String>,
+    deadline: std::time::Instant,
+    /// Inbox files already checked (or present when the wait began)
+    seen: std::collections::HashSet<String>,
+    responder: crate::socket::Responder,
+}
+
+/// A `cryo-agent watch-inbox` connection, told about each new inbox
+/// message until the session ends or the agent disconnects.
+struct InboxSubscriber {
+    /// Inbox files already announced (or present when the watch began)
+    seen: std::collections::HashSet<String>,
+    stream: crate::socket::StreamResponder,
+    /// Signals inbox arrivals; the inbox is only listed after one
+    changes: mpsc::Receiver<DaemonEvent>,
+    /// Keeps the watcher or poller behind `changes` running
+    _source: InboxSource,
+    /// A message was still mid-write at the last check
+    retry: bool,
+}
+
+/// What tells an [`InboxSubscriber`] about new messages: a file watcher on
+/// the inbox directory, or a poller for database-backed stores.
+enum InboxSource {
+    Watcher { _watcher: InboxWatcher },
+    Poller { _poller: InboxPoller },
+}
+
+/// Inbox files the agent disposed of itself with `cryo-agent handled`.
+#[derive(Default)]
+struct InboxMarks {
+    handled: std::collections::BTreeSet<String>,
+    kept: std::collections::BTreeSet<String>,
+}
+
+impl InboxMarks {
+    fn is_empty(&self) -> bool {
+        self.handled.is_empty() && self.kept.is_empty()
+    }
+
+    /// Record `files` as handled or kept; the latest mark wins.
+    fn mark(&mut self, files: &[String], keep: bool) {
+        let (to, from) = if keep {
+            (&mut self.kept, &mut self.handled)
+        } else {
+            (&mut self.handled, &mut self.kept)
+        };
+        for file in files {
+            from.remove(file);
+            to.insert(file.clone());
+        }
+    }
+}
+
 /// The persistent daemon process.
 pub struct Daemon {
     dir: PathBuf,
@@ -198,59 +1030,82 @@ pub struct Daemon {
     log_path: PathBuf,
     shutdown: Arc<AtomicBool>,
     wake_requested: Arc<AtomicBool>,
+    /// Set while an agent session runs (see [`DaemonHandle::in_session`])
+    session_active: Arc<AtomicBool>,
+    store: Box<dyn MessageStore>,
+    /// Desktop notifier, probed once so a missing one is reported once
+    notifier: crate::notifier::Notifier,
+    /// Running inside another program (see `CryoState::embedded`)
+    embedded: bool,
 }
 
 impl Daemon {
-    pub fn new(dir: PathBuf) -> Self {
+    /// A daemon for the project in `dir` that keeps messages in `store`.
+    pub fn new(dir: PathBuf, store: Box<dyn MessageStore>) -> Self {
         let state_path = dir.join("timer.json");
         let log_path = dir.join("cryo.log");
+        let project_name = crate::config::load_config(&crate::config::config_path(&dir))
+            .ok()
+            .flatten()
+            .unwrap_or_default()
+            .project_name(&dir);
+        let group = crate::notifier::NotificationGroup::for_project(&project_name, &dir);
         Self {
             dir,
             state_path,
             log_path,
+            store,
+            notifier: crate::notifier::Notifier::probe().with_group(group),
             shutdown: Arc::new(AtomicBool::new(false)),
             wake_requested: Arc::new(AtomicBool::new(false)),
+            session_active: Arc::new(AtomicBool::new(false)),
+            embedded: false,
         }
     }
 
     /// Run the daemon event loop. Blocks until SIGTERM or plan completion.
-    pub fn run(&self) -> Result<()> {
-        // Register signal handlers
-        flag::register(SIGTERM, Arc::clone(&self.shutdown))
-            .context("Failed to register SIGTERM handler")?;
-        flag::register(SIGINT, Arc::clone(&self.shutdown))
-            .context("Failed to register SIGINT handler")?;
-        flag::register(SIGUSR1, Arc::clone(&self.wake_requested))
-            .context("Failed to register SIGUSR1 handler")?;
-
-        let mut cryo_state =
-            state::load_state(&self.state_path)?.context("No cryochamber state found")?;
-
-        // Guard: refuse to start if another daemon is already running
-        if state::is_locked(&cryo_state) {
-            anyhow::bail!(
-                "Another daemon is already running (PID: {:?}). Use `cryo cancel` first.",
-                cryo_state.pid
-            );
-        }
+    pub fn run(self) -> Result<()> {
+        let handle = self.start_thread()?;
+        handle.forward_signals()?;
+        handle.join()
+    }
 
-        // Load project config from cryo.toml (fall back to defaults for legacy projects)
-        let mut config =
-            crate::config::load_config(&crate::config::config_path(&self.dir))?.unwrap_or_default();
-        config.apply_overrides(&cryo_state);
+    /// Run the event loop on a background thread. The returned handle wakes
+    /// or stops it the way SIGUSR1 and SIGTERM do, without process signals,
+    /// so one process can drive several projects. `cryo wake` and `cryo stop`
+    /// reach it over the socket instead of signalling the host process.
+    pub fn spawn(mut self) -> Result<DaemonHandle> {
+        self.embedded = true;
+        self.start_thread()
+    }
 
-        // Save PID so other commands can detect the running daemon
-        cryo_state.pid = Some(std::process::id());
-        state::save_state(&self.state_path, &cryo_state)?;
+    fn start_thread(self) -> Result<DaemonHandle> {
+        let dir = self.dir.clone();
+        let shutdown = Arc::clone(&self.shutdown);
+        let wake_requested = Arc::clone(&self.wake_requested);
+        let session_active = Arc::clone(&self.session_active);
+        let thread = std::thread::Builder::new()
+            .name("cryo-daemon".into())
+            .spawn(move || {
+                let result = self.run_loop();
+                if result.is_err() {
+                    self.record_error_stop();
+                }
+                result
+            })
+            .context("Failed to spawn daemon thread")?;
+        Ok(DaemonHandle {
+            dir,
+            shutdown,
+            wake_requested,
+            session_active,
+            thread,
+        })
+    }
 
-        // Create .cryo/ directory and bind socket server
-        let sock_path = crate::socket::socket_path(&self.dir);
-        if let Some(parent) = sock_path.parent() {
-            std::fs::create_dir_all(parent)?;
-        }
-        let server = crate::socket::SocketServer::bind(&sock_path)?;
-        server.set_nonblocking(true)?;
-        eprintln!("Daemon: socket listening at {}", sock_path.display());
+    fn run_loop(&self) -> Result<()> {
+        let (mut cryo_state, config, server) = self.acquire()?;
+        let sock_path = crate::socket::socket_path(&self.dir)?;
 
         // Register in global daemon registry (with socket path)
         if let Err(e) = crate::registry::register(&self.dir, Some(&sock_path)) {
@@ -259,11 +1114,33 @@ impl Daemon {
 
         // Set up inbox watcher
         let (tx, rx) = mpsc::channel();
-        let inbox_path = self.dir.join("messages").join("inbox");
-        let _watcher = if config.watch_inbox && inbox_path.exists() {
-            match InboxWatcher::start(&inbox_path, tx.clone()) {
-                Ok(w) => {
-                    eprintln!("Daemon: watching messages/inbox/ for new messages");
+        let inbox_path = crate::message::dirs(&self.dir, &config).inbox;
+        // Always watch the inbox: whether a message wakes the agent is decided
+        // per hibernation (`watch_inbox`, `--wake-on-message`, `--until-message`)
+        let poller = match config.message_store {
+            MessageStoreKind::File => None,
+            kind => match crate::store::open_kind(&self.dir, kind) {
+                Ok(store) => {
+                    info!("Daemon: polling the message store for new messages");
+                    Some(InboxPoller::start(
+                        store,
+                        Duration::from_secs(1),
+                        tx.clone(),
+                    ))
+                }
+                Err(e) => {
+                    eprintln!("Daemon: failed to open the message store for polling: {e}");
+                    None
+                }
+            },
+        };
+        let _watcher = if poller.is_none() && inbox_path.exists() {
+            match InboxWatcher::start(&inbox_path, tx.clone()) {
+                Ok(w) => {
+                    info!(
+                        "Daemon: watching {}/ for new messages",
+                        config.inbox_dir.trim_end_matches('/')
+                    );
                     Some(w)
                 }
                 Err(e) => {
@@ -275,10 +1152,36 @@ impl Daemon {
             None
         };
 
+        // Watch extra drop directories; missing ones are skipped, not fatal
+        let drop_dirs = self.drop_dirs(&config);
+        let _drop_watchers: Vec<InboxWatcher> = drop_dirs
+            .iter()
+            .filter_map(|path| {
+                if !path.is_dir() {
+                    eprintln!(
+                        "Daemon: watch_dirs entry {} does not exist, skipping",
+                        path.display()
+                    );
+                    return None;
+                }
+                match InboxWatcher::start_drop_dir(path, tx.clone()) {
+                    Ok(w) => {
+                        info!("Daemon: watching {} for new files", path.display());
+                        Some(w)
+                    }
+                    Err(e) => {
+                        eprintln!("Daemon: failed to watch {}: {e}", path.display());
+                        None
+                    }
+                }
+            })
+            .collect();
+
         // Spawn a thread that forwards signals to the event channel,
         // so recv_timeout() unblocks immediately on SIGTERM/SIGINT/SIGUSR1.
         let shutdown_flag = Arc::clone(&self.shutdown);
         let wake_flag = Arc::clone(&self.wake_requested);
+        let requeue_tx = tx.clone();
         let signal_tx = tx;
         std::thread::spawn(move || loop {
             std::thread::sleep(Duration::from_millis(250));
@@ -287,7 +1190,7 @@ impl Daemon {
                 break;
             }
             if wake_flag.swap(false, Ordering::Relaxed) {
-                let _ = signal_tx.send(DaemonEvent::InboxChanged);
+                let _ = signal_tx.send(DaemonEvent::WakeRequested);
             }
         });
 
@@ -308,33 +1211,64 @@ impl Daemon {
             );
         }
         if let Some(nrt) = next_report_time {
-            eprintln!("Daemon: next report at {}", nrt.format("%Y-%m-%d %H:%M"));
+            info!("Daemon: next report at {}", nrt.format("%Y-%m-%d %H:%M"));
         }
 
         let provider_count = config.providers.len();
-        let mut retry = RetryState::new(config.max_retries, provider_count);
+        let mut retry = RetryState::new(config.max_retries, provider_count)
+            .with_jitter(config.retry_jitter, jitter_seed(&self.dir));
         // Restore persisted next_wake from state (survives daemon restart).
         let (mut next_wake, mut run_now) =
             restore_wake_state(&cryo_state, Local::now().naive_local());
         let mut inbox_wake = false;
-        let mut pending_fallback: Option<(NaiveDateTime, FallbackAction)> = None;
+        // Reactive wake held back by min_session_gap_secs
+        let mut deferred_wake = false;
+        let mut last_session_end: Option<std::time::Instant> = None;
+        let session_gap = Duration::from_secs(config.min_session_gap_secs);
+        let mut pending_fallback =
+            restore_message_fallback(&cryo_state, Local::now().naive_local());
+        // Whether a new inbox message should wake the agent right now
+        let mut reactive =
+            config.watch_inbox || cryo_state.awaiting_message || cryo_state.wake_on_message;
+        // Breaks without a more specific reason are shutdown requests
+        let mut stop_reason = StopReason::Cancelled;
 
         loop {
             if self.shutdown.load(Ordering::Relaxed) {
-                eprintln!("Daemon: received shutdown signal");
+                info!("Daemon: received shutdown signal");
                 break;
             }
 
             if run_now {
+                if let Some((until, why)) =
+                    session_hold(&config, &cryo_state, Local::now().naive_local())
+                {
+                    info!(
+                        "Daemon: {why}, holding the next session until {}",
+                        until.format(WAKE_TIME_FMT)
+                    );
+                    run_now = false;
+                    next_wake = Some(until);
+                    cryo_state.next_wake = Some(until.format(WAKE_TIME_FMT).to_string());
+                    let _ = state::save_state(&self.state_path, &cryo_state);
+                    continue;
+                }
                 run_now = false;
+                // Whatever was deferred will be seen by this session
+                deferred_wake = false;
                 let is_inbox_wake = inbox_wake;
                 inbox_wake = false;
+                // The OS wake timer has served its purpose: launchd would fire
+                // it again next year. The next timed hibernation sets a new one.
+                if config.persist_wake_across_reboot {
+                    let _ = crate::service::uninstall_wake_timer(&self.dir);
+                }
 
                 // Detect delayed wake: if the scheduled wake time has long passed
                 // (e.g. computer was sleeping), notify the agent instead of failing.
                 // Skip this check for inbox-triggered wakes — the agent should handle
                 // the user's message without a spurious delay warning.
-                let delayed_wake = if is_inbox_wake { None } else { next_wake.and_then(|wake| {
+                let delayed_wake = next_wake.filter(|_| !is_inbox_wake).and_then(|wake| {
                     let now = Local::now().naive_local();
                     detect_delayed_wake(wake, now).map(|delay_str| {
                         // Cancel premature fallback — the session is about to run
@@ -347,56 +1281,188 @@ impl Daemon {
                             delay_str,
                         )
                     })
-                }) };
+                });
+                if let Some(reason) = self.check_runtime_cap(&config, &mut cryo_state) {
+                    eprintln!("Daemon: {reason}. Stopping.");
+                    self.send_runtime_cap_alert(&config.fallback_alert, &reason);
+                    cryo_state.blocked_reason = Some(reason);
+                    let _ = state::save_state(&self.state_path, &cryo_state);
+                    stop_reason = StopReason::Failed;
+                    break;
+                }
                 let saved_wake = next_wake.take();
 
+                // Pick up anything dropped while we weren't watching
+                self.import_drop_dirs(&drop_dirs);
+
                 cryo_state.session_number += 1;
+                cryo_state.count_session(Local::now().date_naive());
                 cryo_state.next_wake = None;
+                cryo_state.awaiting_message = false;
+                cryo_state.message_deadline = None;
+                cryo_state.message_fallback = None;
+                cryo_state.wake_on_message = false;
+                reactive = config.watch_inbox;
+                if let Some((index, reason)) =
+                    take_provider_rotation(&mut cryo_state, config.providers.len())
+                {
+                    info!(
+                        "Daemon: rotating provider: {} -> {} (reason: agent request: {reason})",
+                        provider_name(&config, cryo_state.provider_index.unwrap_or(0)),
+                        provider_name(&config, index),
+                    );
+                    retry.provider_index = index;
+                }
                 if !config.providers.is_empty() {
                     cryo_state.provider_index = Some(retry.provider_index);
                 }
                 let _ = state::save_state(&self.state_path, &cryo_state);
 
-                // Build provider env for this session
                 let active_provider = config.providers.get(retry.provider_index);
-                let provider_env: std::collections::HashMap<String, String> =
-                    active_provider.map(|p| p.env.clone()).unwrap_or_default();
-                let provider_name = active_provider.map(|p| p.name.as_str());
-
-                match self.run_one_session(
+                self.session_active.store(true, Ordering::Release);
+                let result = self.run_one_session(
                     &config,
-                    &cryo_state,
+                    &mut cryo_state,
                     &server,
                     delayed_wake.as_deref(),
-                    &provider_env,
-                    provider_name,
-                ) {
+                    active_provider,
+                );
+                self.session_active.store(false, Ordering::Release);
+                last_session_end = Some(std::time::Instant::now());
+                let coalesced = coalesce_queued_events(&rx, &requeue_tx);
+                if coalesced > 0 {
+                    info!("Daemon: coalesced {coalesced} wake event(s) queued during the session");
+                }
+                self.record_outcome(&mut cryo_state, &result);
+                match result {
+                    Ok(SessionLoopOutcome::PlanMissing) => {
+                        // No agent ran, so this doesn't count as a session
+                        cryo_state.session_number -= 1;
+                        next_wake = saved_wake;
+                        eprintln!("Daemon: plan.md is missing or empty, skipping session");
+                        if cryo_state.blocked_reason.is_none() {
+                            self.send_plan_missing_alert(&config.fallback_alert);
+                        }
+                        cryo_state.blocked_reason = Some(PLAN_MISSING_REASON.to_string());
+                        let _ = state::save_state(&self.state_path, &cryo_state);
+                        if self.handle_failure_retry(
+                            &mut retry,
+                            &config.fallback_alert,
+                            FailureKind::Transient,
+                            &server,
+                        ) == RetryDecision::Shutdown
+                        {
+                            break;
+                        }
+                        run_now = true;
+                        continue;
+                    }
                     Ok(outcome) => {
+                        cryo_state.blocked_reason = None;
                         // Persist session number only after successful completion
                         state::save_state(&self.state_path, &cryo_state)?;
+                        // The agent is back: whatever fallback an earlier session
+                        // armed is stale. Hibernate and AwaitMessage re-arm below.
+                        if !matches!(outcome, SessionLoopOutcome::ValidationFailed { .. }) {
+                            pending_fallback = None;
+                        }
                         match outcome {
                             SessionLoopOutcome::PlanComplete => {
                                 retry.reset();
-                                drop(pending_fallback);
-                                eprintln!("Daemon: plan complete. Shutting down.");
+                                if self.complete_focus_plan(&mut cryo_state) > 0 {
+                                    // Straight on to the next plan
+                                    let _ = state::save_state(&self.state_path, &cryo_state);
+                                    run_now = true;
+                                    continue;
+                                }
+                                if config.persist_wake_across_reboot {
+                                    let _ = crate::service::uninstall_wake_timer(&self.dir);
+                                }
+                                notice!("Daemon: plan complete. Shutting down.");
+                                stop_reason = StopReason::Completed;
                                 break;
                             }
                             SessionLoopOutcome::Hibernate {
                                 wake_time,
                                 fallback,
+                                wake_on_message,
                             } => {
                                 retry.reset();
                                 next_wake = Some(wake_time);
                                 cryo_state.next_wake =
                                     Some(wake_time.format(WAKE_TIME_FMT).to_string());
+                                cryo_state.wake_on_message = wake_on_message;
+                                reactive = config.watch_inbox || wake_on_message;
                                 let _ = state::save_state(&self.state_path, &cryo_state);
+                                if config.persist_wake_across_reboot {
+                                    self.register_wake_backstop(wake_time);
+                                }
                                 pending_fallback =
                                     fallback.map(|fb| (wake_time + chrono::Duration::hours(1), fb));
-                                eprintln!(
+                                info!(
                                     "Daemon: next wake at {}",
                                     wake_time.format("%Y-%m-%d %H:%M")
                                 );
                             }
+                            SessionLoopOutcome::AwaitMessage { fallback, deadline } => {
+                                retry.reset();
+                                cryo_state.awaiting_message = true;
+                                reactive = true;
+                                // Even without a wake time, alert someone if the
+                                // agent is left waiting for too long.
+                                let deadline = deadline.unwrap_or_else(|| {
+                                    Local::now().naive_local()
+                                        + chrono::Duration::hours(UNTIL_MESSAGE_FALLBACK_HOURS)
+                                });
+                                cryo_state.message_deadline =
+                                    Some(deadline.format(WAKE_TIME_FMT).to_string());
+                                cryo_state.message_fallback = fallback.clone();
+                                let _ = state::save_state(&self.state_path, &cryo_state);
+                                let fb = fallback.unwrap_or_else(until_message_fallback);
+                                pending_fallback = Some((deadline, fb));
+                                info!("Daemon: waiting for an inbox message");
+                            }
+                            SessionLoopOutcome::Aborted { wake_time } => {
+                                // Intentional stop: no backoff, no provider rotation
+                                retry.reset();
+                                if let Some(wake_time) = wake_time {
+                                    next_wake = Some(wake_time);
+                                    cryo_state.next_wake =
+                                        Some(wake_time.format(WAKE_TIME_FMT).to_string());
+                                    info!(
+                                        "Daemon: session aborted, next wake at {}",
+                                        wake_time.format("%Y-%m-%d %H:%M")
+                                    );
+                                } else {
+                                    cryo_state.awaiting_message = true;
+                                    reactive = true;
+                                    let fb = until_message_fallback();
+                                    let deadline = Local::now().naive_local()
+                                        + chrono::Duration::hours(UNTIL_MESSAGE_FALLBACK_HOURS);
+                                    cryo_state.message_deadline =
+                                        Some(deadline.format(WAKE_TIME_FMT).to_string());
+                                    pending_fallback = Some((deadline, fb));
+                                    info!("Daemon: session aborted, waiting for an inbox message");
+                                }
+                                let _ = state::save_state(&self.state_path, &cryo_state);
+                            }
+                            SessionLoopOutcome::PlanMissing => {
+                                // Normally caught above, before the session is counted
+                                eprintln!("Daemon: plan.md is missing or empty, waiting for the next wake");
+                                next_wake = saved_wake;
+                            }
+                            SessionLoopOutcome::Held { until, reason } => {
+                                // The loop holds sessions before running them; if
+                                // one comes back held anyway, wait as it says
+                                info!(
+                                    "Daemon: {reason}, holding the next session until {}",
+                                    until.format(WAKE_TIME_FMT)
+                                );
+                                next_wake = Some(until);
+                                cryo_state.next_wake =
+                                    Some(until.format(WAKE_TIME_FMT).to_string());
+                                let _ = state::save_state(&self.state_path, &cryo_state);
+                            }
                             SessionLoopOutcome::ValidationFailed { quick_exit } => {
                                 next_wake = saved_wake;
 
@@ -410,18 +1476,10 @@ impl Daemon {
                                     };
 
                                 if should_rotate {
-                                    let old_name = config
-                                        .providers
-                                        .get(retry.provider_index)
-                                        .map(|p| p.name.as_str())
-                                        .unwrap_or("unknown");
+                                    let old_name = provider_name(&config, retry.provider_index);
                                     let wrapped = retry.rotate_provider();
-                                    let new_name = config
-                                        .providers
-                                        .get(retry.provider_index)
-                                        .map(|p| p.name.as_str())
-                                        .unwrap_or("unknown");
-                                    eprintln!(
+                                    let new_name = provider_name(&config, retry.provider_index);
+                                    info!(
                                         "Daemon: rotating provider: {} -> {} (reason: {})",
                                         old_name,
                                         new_name,
@@ -430,12 +1488,15 @@ impl Daemon {
 
                                     // Persist immediately so `cryo status` reflects the change
                                     cryo_state.provider_index = Some(retry.provider_index);
+                                    // That already moved off the provider the agent asked to leave
+                                    cryo_state.provider_rotation = None;
                                     let _ = state::save_state(&self.state_path, &cryo_state);
 
                                     if wrapped {
                                         // All providers tried — apply backoff before next cycle
-                                        eprintln!("Daemon: all providers tried, backing off before next cycle");
-                                        if self.sleep_or_shutdown(Duration::from_secs(60)) {
+                                        info!("Daemon: all providers tried, backing off before next cycle");
+                                        if self.sleep_or_shutdown(Duration::from_secs(60), &server)
+                                        {
                                             break;
                                         }
                                     }
@@ -443,20 +1504,62 @@ impl Daemon {
                                     continue;
                                 }
 
-                                // No rotation — use standard retry with backoff
-                                if self.handle_failure_retry(&mut retry, &config.fallback_alert) {
-                                    break;
+                                // No rotation — bounded retry with backoff
+                                match self.handle_failure_retry(
+                                    &mut retry,
+                                    &config.fallback_alert,
+                                    FailureKind::Logic,
+                                    &server,
+                                ) {
+                                    RetryDecision::Retry => {
+                                        run_now = true;
+                                        continue;
+                                    }
+                                    RetryDecision::Shutdown => break,
+                                    RetryDecision::GiveUp => {
+                                        retry.reset();
+                                        next_wake = None;
+                                        cryo_state.next_wake = None;
+                                        cryo_state.awaiting_message = true;
+                                        cryo_state.blocked_reason = Some(format!(
+                                            "agent failed {} sessions in a row without hibernating",
+                                            config.max_retries.max(1)
+                                        ));
+                                        reactive = true;
+                                        let _ = state::save_state(&self.state_path, &cryo_state);
+                                    }
                                 }
-                                run_now = true;
-                                continue;
                             }
                         }
                     }
+                    Err(e) if crate::agent::AgentCommandError::find(&e).is_some() => {
+                        // Retrying a broken command can't help: alert once and
+                        // wait for a message or `cryo wake` (e.g. after a fix)
+                        cryo_state.session_number -= 1;
+                        let reason = format!("agent command invalid: {e:#}");
+                        eprintln!("Daemon: {reason}");
+                        if cryo_state.blocked_reason.as_deref() != Some(reason.as_str()) {
+                            self.send_agent_command_alert(&config.fallback_alert, &reason);
+                        }
+                        retry.reset();
+                        next_wake = None;
+                        cryo_state.next_wake = None;
+                        cryo_state.awaiting_message = true;
+                        cryo_state.blocked_reason = Some(reason);
+                        reactive = true;
+                        let _ = state::save_state(&self.state_path, &cryo_state);
+                    }
                     Err(e) => {
                         cryo_state.session_number -= 1;
                         next_wake = saved_wake;
                         eprintln!("Daemon: session failed: {e}");
-                        if self.handle_failure_retry(&mut retry, &config.fallback_alert) {
+                        if self.handle_failure_retry(
+                            &mut retry,
+                            &config.fallback_alert,
+                            FailureKind::Transient,
+                            &server,
+                        ) == RetryDecision::Shutdown
+                        {
                             break;
                         }
                         run_now = true;
@@ -467,6 +1570,12 @@ impl Daemon {
 
             // Check fallback only when idle (not about to run a session)
             self.check_fallback(&mut pending_fallback, &config.fallback_alert);
+            if pending_fallback.is_none() && cryo_state.message_deadline.is_some() {
+                // Fired: a restart must not send the alert again
+                cryo_state.message_deadline = None;
+                cryo_state.message_fallback = None;
+                let _ = state::save_state(&self.state_path, &cryo_state);
+            }
 
             // Check if periodic report is due
             if let Some(report_time) = next_report_time {
@@ -475,28 +1584,130 @@ impl Daemon {
                 }
             }
 
+            // Run a deferred reactive wake once the session gap has passed
+            let gap_left =
+                session_gap_remaining(last_session_end, session_gap, std::time::Instant::now());
+            if deferred_wake && gap_left.is_none() {
+                info!("Daemon: session gap elapsed, handling deferred inbox wake");
+                run_now = true;
+                inbox_wake = true;
+                continue;
+            }
+
             // Wait for next event
-            let timeout =
+            let mut timeout =
                 compute_sleep_timeout(next_wake, next_report_time, Local::now().naive_local());
+            if let (true, Some(left)) = (deferred_wake, gap_left) {
+                timeout = timeout.min(left);
+            }
+            verbose!("Daemon: sleeping for up to {}s", timeout.as_secs());
+
+            // UTC so a DST change doesn't read as a suspend
+            let wall_before = chrono::Utc::now();
+            let mono_before = std::time::Instant::now();
+            let event = self.wait_for_event(&rx, &server, timeout.min(SUSPEND_CHECK_INTERVAL));
+            let slept = suspended_for(chrono::Utc::now() - wall_before, mono_before.elapsed());
+            let now = Local::now().naive_local();
+            if let Some(slept) = slept {
+                info!(
+                    "Daemon: host was suspended for about {}m",
+                    slept.num_minutes()
+                );
+                // The agent couldn't check in while the host slept
+                if let Some((deadline, _)) = pending_fallback.as_mut() {
+                    *deadline += slept;
+                    if cryo_state.message_deadline.is_some() {
+                        cryo_state.message_deadline =
+                            Some(deadline.format(WAKE_TIME_FMT).to_string());
+                        let _ = state::save_state(&self.state_path, &cryo_state);
+                    }
+                }
+                let rescheduled =
+                    wake_after_suspend(next_wake, cryo_state.schedule.as_deref(), now);
+                if rescheduled != next_wake {
+                    if let (Some(missed), Some(next)) = (next_wake, rescheduled) {
+                        info!(
+                            "Daemon: skipped the {} slot missed while suspended, next wake {}",
+                            missed.format(WAKE_TIME_FMT),
+                            next.format(WAKE_TIME_FMT)
+                        );
+                        cryo_state.next_wake = Some(next.format(WAKE_TIME_FMT).to_string());
+                        let _ = state::save_state(&self.state_path, &cryo_state);
+                    }
+                    next_wake = rescheduled;
+                }
+            }
 
-            match rx.recv_timeout(timeout) {
+            match event {
                 Ok(DaemonEvent::InboxChanged) => {
                     // Drain any additional queued InboxChanged events to coalesce
                     // multiple file-system notifications into a single session.
                     while let Ok(DaemonEvent::InboxChanged) = rx.try_recv() {}
-                    eprintln!("Daemon: inbox changed, waking up");
+                    if !reactive {
+                        verbose!("Daemon: inbox changed, not waking (reactive wake is off)");
+                    } else if let Some(left) = session_gap_remaining(
+                        last_session_end,
+                        session_gap,
+                        std::time::Instant::now(),
+                    ) {
+                        if !deferred_wake {
+                            info!(
+                                "Daemon: inbox changed, deferring wake {}s (min_session_gap_secs)",
+                                left.as_secs().max(1)
+                            );
+                        }
+                        deferred_wake = true;
+                    } else {
+                        info!("Daemon: inbox changed, waking up");
+                        run_now = true;
+                        inbox_wake = true;
+                    }
+                }
+                Ok(DaemonEvent::WakeRequested) => {
+                    info!("Daemon: wake requested, waking up");
                     run_now = true;
                     inbox_wake = true;
                 }
-                Ok(DaemonEvent::Shutdown) => break,
+                Ok(DaemonEvent::DropDirChanged) => {
+                    // Give the writer a moment to finish before importing
+                    std::thread::sleep(Duration::from_millis(500));
+                    while let Ok(DaemonEvent::DropDirChanged) = rx.try_recv() {}
+                    if self.import_drop_dirs(&drop_dirs) > 0 {
+                        if session_gap_remaining(
+                            last_session_end,
+                            session_gap,
+                            std::time::Instant::now(),
+                        )
+                        .is_some()
+                        {
+                            info!(
+                                "Daemon: new files in watch_dirs, deferring wake (min_session_gap_secs)"
+                            );
+                            deferred_wake = true;
+                        } else {
+                            info!("Daemon: new files in watch_dirs, waking up");
+                            run_now = true;
+                            inbox_wake = true;
+                        }
+                    }
+                }
+                Ok(DaemonEvent::Shutdown) => {
+                    verbose!("Daemon: woke for shutdown");
+                    break;
+                }
                 Err(mpsc::RecvTimeoutError::Timeout) => {
-                    if next_wake.is_some() {
-                        eprintln!("Daemon: scheduled wake time reached");
+                    // The timeout may be an earlier deadline (report,
+                    // session gap); only run if the wake time has come.
+                    if next_wake.is_some_and(|w| w <= Local::now().naive_local()) {
+                        info!("Daemon: scheduled wake time reached");
                         run_now = true;
+                    } else {
+                        verbose!("Daemon: woke on timeout (report/fallback/suspend check)");
                     }
                 }
                 Err(mpsc::RecvTimeoutError::Disconnected) => {
                     eprintln!("Daemon: event channel disconnected");
+                    stop_reason = StopReason::Error;
                     break;
                 }
             }
@@ -504,100 +1715,574 @@ impl Daemon {
 
         // Cleanup: always unregister and remove socket, even if state save fails
         cryo_state.pid = None;
+        cryo_state.mark_stopped(stop_reason, Local::now().naive_local());
+        notice!("Daemon: stopped ({stop_reason})");
         if let Err(e) = state::save_state(&self.state_path, &cryo_state) {
             eprintln!("Daemon: failed to save final state: {e}");
         }
         crate::registry::unregister(&self.dir);
         crate::socket::SocketServer::cleanup(&sock_path);
-        eprintln!("Daemon: exited cleanly");
+        info!("Daemon: exited cleanly");
 
         Ok(())
     }
 
+    /// Wait up to `timeout` for the next event, answering socket requests
+    /// between sessions meanwhile (see [`Daemon::serve_between_sessions`]).
+    fn wait_for_event(
+        &self,
+        rx: &mpsc::Receiver<DaemonEvent>,
+        server: &crate::socket::SocketServer,
+        timeout: Duration,
+    ) -> std::result::Result<DaemonEvent, mpsc::RecvTimeoutError> {
+        let deadline = std::time::Instant::now() + timeout;
+        loop {
+            let left = deadline.saturating_duration_since(std::time::Instant::now());
+            match rx.recv_timeout(left.min(Duration::from_millis(250))) {
+                Err(mpsc::RecvTimeoutError::Timeout) if !left.is_zero() => {}
+                result => return result,
+            }
+            self.serve_between_sessions(server);
+        }
+    }
+
+    /// Answer one pending socket request while no session runs. `wake` and
+    /// `stop` set the same flags as SIGUSR1 and SIGTERM; anything that needs
+    /// a session is refused.
+    fn serve_between_sessions(&self, server: &crate::socket::SocketServer) {
+        let Ok(Some((request, responder))) = server.accept_one() else {
+            return;
+        };
+        verbose!("Daemon: socket request between sessions: {}", request.cmd());
+        let (ok, message) = match request {
+            crate::socket::Request::Wake => {
+                self.wake_requested.store(true, Ordering::Relaxed);
+                (true, "Waking the agent.".to_string())
+            }
+            crate::socket::Request::Stop => {
+                self.shutdown.store(true, Ordering::Relaxed);
+                (true, "Stopping the daemon.".to_string())
+            }
+            crate::socket::Request::Hello => (
+                true,
+                serde_json::to_string(&crate::socket::Hello::current()).unwrap_or_default(),
+            ),
+            crate::socket::Request::Ping => (
+                true,
+                format!("Daemon alive (pid {}, no session)", std::process::id()),
+            ),
+            _ => (false, "No session is running".to_string()),
+        };
+        let _ = responder.respond(&crate::socket::Response { ok, message });
+    }
+
+    /// Record an `error` stop after the event loop bailed out, if this
+    /// process still holds the project (a failed `acquire` does not).
+    fn record_error_stop(&self) {
+        let Ok(Some(mut cryo_state)) = state::load_state(&self.state_path) else {
+            return;
+        };
+        if cryo_state.pid != Some(std::process::id()) {
+            return;
+        }
+        cryo_state.pid = None;
+        cryo_state.mark_stopped(StopReason::Error, Local::now().naive_local());
+        if let Err(e) = state::save_state(&self.state_path, &cryo_state) {
+            eprintln!("Daemon: failed to save final state: {e}");
+        }
+    }
+
+    /// Load state and config, take the PID lock, and bind the agent socket.
+    /// Shared setup for [`Daemon::run`] and [`Daemon::run_once`].
+    fn acquire(&self) -> Result<(CryoState, CryoConfig, crate::socket::SocketServer)> {
+        let mut cryo_state =
+            state::load_state(&self.state_path)?.context("No cryochamber state found")?;
+
+        // Guard: refuse to start if another daemon is already running
+        if state::is_locked(&cryo_state) {
+            return Err(AlreadyRunning {
+                pid: cryo_state.pid,
+            }
+            .into());
+        }
+
+        // Load project config from cryo.toml (fall back to defaults for legacy projects)
+        let mut config =
+            crate::config::load_config(&crate::config::config_path(&self.dir))?.unwrap_or_default();
+        config.apply_overrides(&cryo_state);
+        set_log_level(config.log_level);
+        // Repeat cryo.toml warnings in cryo.log, where remote debugging starts
+        let config_path = crate::config::config_path(&self.dir);
+        for warning in crate::config::config_warnings(&config_path).unwrap_or_default() {
+            eprintln!("Daemon: cryo.toml: {warning}");
+        }
+        // Scheduling hints in plan.md's front-matter win over cryo.toml
+        let (front, warnings) = crate::plan::PlanFrontMatter::load(&self.dir);
+        for warning in warnings {
+            eprintln!("Daemon: plan.md: {warning}");
+        }
+        front.apply(&mut config);
+        // Fail fast on bad redact_patterns, usage_pattern or a missing
+        // provider env_file rather than on every session
+        crate::redact::Redactor::for_project(&config, &self.dir)?;
+        crate::usage::UsageExtractor::from_config(&config)?;
+
+        // Continue the numbering kept by `cryo clean --keep-session-number`
+        let kept_session = match cryo_state.session_number {
+            0 => state::load_session_counter(&self.dir)?,
+            _ => None,
+        };
+        if let Some(n) = kept_session {
+            info!("Daemon: continuing after session {n} (.cryo/session-counter)");
+            cryo_state.session_number = n;
+        }
+
+        // Save PID so other commands can detect the running daemon
+        cryo_state.pid = Some(std::process::id());
+        cryo_state.pid_started = crate::process::start_time(std::process::id());
+        cryo_state.embedded = self.embedded;
+        cryo_state.stop_reason = None;
+        cryo_state.stopped_at = None;
+        state::save_state(&self.state_path, &cryo_state)?;
+        if kept_session.is_some() {
+            // timer.json carries the number from here on
+            let _ = std::fs::remove_file(state::session_counter_path(&self.dir));
+        }
+
+        // Create .cryo/ directory and bind socket server
+        let sock_path = crate::socket::socket_path(&self.dir)?;
+        if let Some(parent) = sock_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let server = crate::socket::SocketServer::bind(&sock_path)?;
+        server.set_nonblocking(true)?;
+        info!("Daemon: socket listening at {}", sock_path.display());
+
+        Ok((cryo_state, config, server))
+    }
+
+    /// Run exactly one session in the foreground and return its outcome.
+    ///
+    /// Used by `cryo start --once` for external schedulers (cron, CI): no wake
+    /// is scheduled, no fallback is armed, and no retry is attempted. An
+    /// error from before the agent was spawned carries [`CouldNotStart`].
+    pub fn run_once(&self) -> Result<SessionLoopOutcome> {
+        flag::register(SIGTERM, Arc::clone(&self.shutdown))
+            .context("Failed to register SIGTERM handler")
+            .context(CouldNotStart)?;
+        flag::register(SIGINT, Arc::clone(&self.shutdown))
+            .context("Failed to register SIGINT handler")
+            .context(CouldNotStart)?;
+
+        let (mut cryo_state, config, server) = self.acquire().context(CouldNotStart)?;
+        let sock_path = crate::socket::socket_path(&self.dir)?;
+
+        let mut provider_index = cryo_state
+            .provider_index
+            .filter(|&i| i < config.providers.len())
+            .unwrap_or(0);
+        if let Some((index, reason)) =
+            take_provider_rotation(&mut cryo_state, config.providers.len())
+        {
+            info!(
+                "Daemon: rotating provider: {} -> {} (reason: agent request: {reason})",
+                provider_name(&config, provider_index),
+                provider_name(&config, index),
+            );
+            provider_index = index;
+            cryo_state.provider_index = Some(index);
+        }
+        let active_provider = config.providers.get(provider_index);
+
+        if let Some(reason) = self.check_runtime_cap(&config, &mut cryo_state) {
+            self.send_runtime_cap_alert(&config.fallback_alert, &reason);
+            cryo_state.blocked_reason = Some(reason.clone());
+            cryo_state.pid = None;
+            cryo_state.mark_stopped(StopReason::Failed, Local::now().naive_local());
+            let _ = state::save_state(&self.state_path, &cryo_state);
+            crate::socket::SocketServer::cleanup(&sock_path);
+            anyhow::bail!("{reason}");
+        }
+        if let Some((until, reason)) =
+            session_hold(&config, &cryo_state, Local::now().naive_local())
+        {
+            cryo_state.next_wake = Some(until.format(WAKE_TIME_FMT).to_string());
+            cryo_state.pid = None;
+            let _ = state::save_state(&self.state_path, &cryo_state);
+            crate::socket::SocketServer::cleanup(&sock_path);
+            return Ok(SessionLoopOutcome::Held { until, reason });
+        }
+        cryo_state.session_number += 1;
+        cryo_state.count_session(Local::now().date_naive());
+        cryo_state.next_wake = None;
+        cryo_state.awaiting_message = false;
+        let _ = state::save_state(&self.state_path, &cryo_state);
+
+        let mut result =
+            self.run_one_session(&config, &mut cryo_state, &server, None, active_provider);
+        self.record_outcome(&mut cryo_state, &result);
+        if matches!(result, Ok(SessionLoopOutcome::PlanComplete))
+            && self.complete_focus_plan(&mut cryo_state) > 0
+        {
+            // Other plans are still open: the next run should start now
+            result = Ok(SessionLoopOutcome::Hibernate {
+                wake_time: Local::now().naive_local(),
+                fallback: None,
+                wake_on_message: false,
+            });
+        }
+        match &result {
+            Err(e) => {
+                cryo_state.session_number -= 1;
+                if crate::agent::AgentCommandError::find(e).is_some() {
+                    cryo_state.blocked_reason = Some(format!("agent command invalid: {e:#}"));
+                }
+            }
+            Ok(SessionLoopOutcome::PlanMissing) => {
+                cryo_state.session_number -= 1;
+                cryo_state.blocked_reason = Some(PLAN_MISSING_REASON.to_string());
+            }
+            Ok(_) => cryo_state.blocked_reason = None,
+        }
+
+        cryo_state.pid = None;
+        let stop_reason = match &result {
+            Ok(SessionLoopOutcome::PlanComplete) => Some(StopReason::Completed),
+            Ok(SessionLoopOutcome::ValidationFailed { .. }) => Some(StopReason::Failed),
+            Err(_) => Some(StopReason::Error),
+            Ok(_) => None,
+        };
+        if let Some(reason) = stop_reason {
+            cryo_state.mark_stopped(reason, Local::now().naive_local());
+        }
+        if let Err(e) = state::save_state(&self.state_path, &cryo_state) {
+            eprintln!("Daemon: failed to save final state: {e}");
+        }
+        crate::socket::SocketServer::cleanup(&sock_path);
+        result
+    }
+
+    /// Run one session, then post `session_end_webhook` for it.
     fn run_one_session(
         &self,
         config: &CryoConfig,
-        cryo_state: &CryoState,
+        cryo_state: &mut CryoState,
         server: &crate::socket::SocketServer,
         delayed_wake: Option<&str>,
-        provider_env: &std::collections::HashMap<String, String>,
-        provider_name: Option<&str>,
+        provider: Option<&crate::config::ProviderConfig>,
     ) -> Result<SessionLoopOutcome> {
+        let started = std::time::Instant::now();
+        let result = self.run_session(config, cryo_state, server, delayed_wake, provider);
+        if let (Some(url), Some(outcome)) =
+            (&config.session_end_webhook, self.session_outcome(&result))
+        {
+            // Without a working redactor, leave the summary out
+            let summary = crate::redact::Redactor::for_project(config, &self.dir)
+                .ok()
+                .and_then(|r| cryo_state.last_summary.as_deref().map(|s| r.redact(s)));
+            let event = crate::webhook::SessionEvent::SessionEnd {
+                project: config.project_name(&self.dir),
+                session: cryo_state.session_number,
+                outcome: outcome.to_string(),
+                summary,
+                duration_secs: started.elapsed().as_secs(),
+                timestamp: Local::now().format("%Y-%m-%dT%H:%M:%S").to_string(),
+            };
+            self.post_webhook(config, url, &event);
+        }
+        result
+    }
+
+    /// POST a session event; a failure is only logged.
+    fn post_webhook(&self, config: &CryoConfig, url: &str, event: &crate::webhook::SessionEvent) {
+        let posted = crate::channel::http_agent(config).and_then(|agent| {
+            crate::webhook::post(&agent, url, event, crate::webhook::TIMEOUT_SECS)
+        });
+        if let Err(e) = posted {
+            eprintln!("Daemon: session webhook failed: {e:#}");
+        }
+    }
+
+    fn run_session(
+        &self,
+        config: &CryoConfig,
+        cryo_state: &mut CryoState,
+        server: &crate::socket::SocketServer,
+        delayed_wake: Option<&str>,
+        provider: Option<&crate::config::ProviderConfig>,
+    ) -> Result<SessionLoopOutcome> {
+        // Don't burn an agent run on a prompt that points at a missing plan
+        if !plan_is_present(&self.dir) {
+            return Ok(SessionLoopOutcome::PlanMissing);
+        }
+        cryo_state.last_summary = None;
+        let plan_changed =
+            cryo_state.plan_hash.is_some() && cryo_state.plan_hash != plan_hash(&self.dir);
+
         let agent_cmd = config.agent.clone();
+        let redactor = crate::redact::Redactor::for_project(config, &self.dir)?;
+        let usage_extractor = crate::usage::UsageExtractor::from_config(config)?;
 
-        let task = self
-            .get_task()
-            .unwrap_or_else(|| "Continue the plan".to_string());
+        let plans = crate::plans::list(&self.dir).unwrap_or_default();
+        let focus = choose_focus(cryo_state, &plans);
+        let task = self.get_task(config, cryo_state);
 
-        let timeout_secs = config.max_session_duration;
+        let timeout_secs = session_timeout_secs(config, cryo_state, provider);
+        let provider_env: std::collections::HashMap<String, String> = provider
+            .map(|p| p.load_env(&self.dir))
+            .transpose()?
+            .unwrap_or_default();
 
-        eprintln!(
+        notice!(
             "Daemon: Session #{}: Running agent...",
             cryo_state.session_number
         );
+        if let Some(url) = &config.session_start_webhook {
+            let event = crate::webhook::SessionEvent::SessionStart {
+                project: config.project_name(&self.dir),
+                session: cryo_state.session_number,
+                task: redactor.redact(&task),
+                provider: provider.map(|p| p.name.clone()),
+                timestamp: Local::now().format("%Y-%m-%dT%H:%M:%S").to_string(),
+            };
+            self.post_webhook(config, url, &event);
+        }
 
         // List inbox filenames for logging (agent reads files itself)
-        let inbox_filenames: Vec<String> = crate::message::list_inbox(&self.dir)?;
+        let mut inbox_filenames: Vec<String> = self.store.list_inbox()?;
+        let inbox = if inbox_filenames.is_empty() {
+            Vec::new()
+        } else {
+            self.store.read_inbox()?
+        };
+        // A message that lands between the two reads is in the prompt, so
+        // it counts as presented
+        for (filename, _) in &inbox {
+            if !inbox_filenames.contains(filename) {
+                inbox_filenames.push(filename.clone());
+            }
+        }
+        // Channel replies like `approve <id>` settle pending reviews
+        let decided_reviews = if inbox.is_empty() {
+            Vec::new()
+        } else {
+            crate::review::resolve_replies(
+                &self.dir,
+                &inbox,
+                &config.review_approvers,
+                Local::now().naive_local(),
+            )
+            .unwrap_or_else(|e| {
+                eprintln!("Daemon: failed to resolve review replies: {e:#}");
+                Vec::new()
+            })
+        };
+        let attachments: Vec<String> = inbox
+            .iter()
+            .flat_map(|(_, msg)| msg.attachments.iter().cloned())
+            .collect();
 
-        // Build prompt (slim — agent reads cryo.log and inbox files directly)
+        // Build prompt: slim points the agent at cryo.log and the inbox,
+        // embedded carries the messages and history itself
+        let messages = match config.prompt_style {
+            crate::config::PromptStyle::Slim => Vec::new(),
+            crate::config::PromptStyle::Embedded => {
+                let mut inbox = inbox;
+                crate::agent::sort_messages(&mut inbox, config.message_order);
+                inbox
+            }
+        };
+        // Messages carried in full by the prompt count as read
+        let embedded_inbox: std::collections::HashSet<String> = messages
+            .iter()
+            .take(crate::agent::embedded_message_count(&messages))
+            .map(|(filename, _)| filename.clone())
+            .collect();
         let agent_config = crate::agent::AgentConfig {
             session_number: cryo_state.session_number,
             task: task.clone(),
             delayed_wake: delayed_wake.map(|s| s.to_string()),
+            plan_changed,
+            checkpoint: state::load_checkpoint(&self.dir),
+            attachments,
+            inbox_dir: config.inbox_dir.clone(),
+            require_inbox_ack: config.require_inbox_ack,
+            history: match (&focus, config.effective_history_mode()) {
+                (Some(name), crate::config::HistoryMode::LastSession) => {
+                    crate::log::read_latest_plan_session(&self.log_path, name)?
+                }
+                (_, mode) => crate::log::read_history(&self.log_path, mode)?,
+            },
+            focus: focus.clone().map(|name| crate::agent::PlanFocus {
+                name,
+                plans: plans
+                    .iter()
+                    .map(|p| (p.clone(), cryo_state.completed_plans.contains(p)))
+                    .collect(),
+            }),
+            messages,
         };
-        let prompt = crate::agent::build_prompt(&agent_config);
+        let prompt = redactor.redact(&crate::agent::build_prompt(&agent_config));
+        let launch = crate::agent::LaunchOptions::from_config(config);
 
         // Begin event log
-        let mut logger = crate::log::EventLogger::begin(
+        let mut logger = crate::log::EventLogger::begin_redacted(
             &self.log_path,
             cryo_state.session_number,
             &task,
             &agent_cmd,
             &inbox_filenames,
+            redactor.clone(),
+            crate::log::LogFormat::from_config(config),
         )?;
 
         // Log delayed wake notice
         if let Some(notice) = delayed_wake {
             logger.log_event(&format!("delayed wake: {notice}"))?;
         }
+        if plan_changed {
+            logger
+                .log_event("plan changed: plan.md or plans/ was edited since the last session")?;
+        }
+        if let Some(name) = &focus {
+            logger.log_event(&format!("focus plan: {name}"))?;
+        }
+        for review in &decided_reviews {
+            logger.log_event(&format!(
+                "review {}: {} by {}",
+                review.id,
+                review.status.as_str(),
+                review.decided_by.as_deref().unwrap_or("human")
+            ))?;
+        }
 
         // Open agent log file for stdout/stderr redirection
+        let agent_log_path = crate::log::agent_log_path(&self.dir);
+        match crate::log::rotate_log(
+            &agent_log_path,
+            config.max_agent_log_bytes,
+            config.agent_log_keep,
+        ) {
+            Ok(Some(size)) => {
+                logger.log_event(&format!("agent log rotated ({size} bytes)"))?;
+            }
+            Ok(None) => {}
+            Err(e) => eprintln!("Daemon: failed to rotate cryo-agent.log: {e:#}"),
+        }
         let agent_log_file = std::fs::OpenOptions::new()
             .create(true)
             .append(true)
-            .open(crate::log::agent_log_path(&self.dir))?;
+            .open(&agent_log_path)?;
+        // Only this session's output counts towards its usage and markers
+        let session_output = crate::log::SessionOutput::start(&agent_log_path);
+        // A COMPLETE file left from an earlier plan must not end this one
+        let _ = std::fs::remove_file(complete_file_path(&self.dir));
+
+        // Opt-in recording, saved when this function returns
+        let recorder = if config.record_session {
+            Some(crate::recording::Recorder::new(
+                &self.dir,
+                config.recordings_keep,
+                crate::recording::SessionRecording {
+                    session: cryo_state.session_number,
+                    started_at: Local::now().naive_local(),
+                    agent: agent_cmd.clone(),
+                    argv: crate::agent::command_argv(&agent_cmd, &prompt, &launch)?
+                        .iter()
+                        .map(|a| redactor.redact(a))
+                        .collect(),
+                    provider: provider.map(|p| p.name.clone()),
+                    env: provider_env
+                        .keys()
+                        .map(|k| (k.clone(), crate::redact::REDACTED.to_string()))
+                        .collect(),
+                    prompt: prompt.clone(),
+                    ..Default::default()
+                },
+            ))
+        } else {
+            None
+        };
 
         // Spawn agent with stdout/stderr redirected to cryo-agent.log
-        let mut child =
-            crate::agent::spawn_agent(&agent_cmd, &prompt, Some(agent_log_file), provider_env)?;
+        let (mut child, mut output_copy) = if let Some(recorder) = &recorder {
+            crate::agent::spawn_agent_recorded(
+                &self.dir,
+                &agent_cmd,
+                &prompt,
+                &launch,
+                agent_log_file,
+                &provider_env,
+                &redactor,
+                recorder.capture(),
+            )?
+        } else if redactor.is_empty() {
+            let child = crate::agent::spawn_agent(
+                &self.dir,
+                &agent_cmd,
+                &prompt,
+                &launch,
+                Some(agent_log_file),
+                &provider_env,
+            )?;
+            (child, crate::agent::OutputCopy::default())
+        } else {
+            crate::agent::spawn_agent_redacted(
+                &self.dir,
+                &agent_cmd,
+                &prompt,
+                &launch,
+                agent_log_file,
+                &provider_env,
+                &redactor,
+            )?
+        };
         let child_pid = child.id();
         let spawn_time = std::time::Instant::now();
         logger.log_event(&format!("agent started (pid {child_pid})"))?;
-        if let Some(name) = provider_name {
-            logger.log_event(&format!("provider: {name}"))?;
+        if let Some(p) = provider {
+            logger.log_event(&format!("provider: {}", p.name))?;
         }
 
         // Poll loop: wait for socket commands + agent exit
-        let deadline = if timeout_secs > 0 {
-            Some(std::time::Instant::now() + Duration::from_secs(timeout_secs))
+        let mut deadline = if timeout_secs > 0 {
+            Some(spawn_time + Duration::from_secs(timeout_secs))
         } else {
             None
         };
 
         let mut hibernate_outcome: Option<SessionLoopOutcome> = None;
         let mut pending_fallback: Option<FallbackAction> = None;
+        let mut pending_reply: Option<PendingReply> = None;
+        let mut inbox_subscriber: Option<InboxSubscriber> = None;
+        // Inbox files the agent is known to have read
+        let mut acked_inbox = embedded_inbox;
+        let mut inbox_marks = InboxMarks::default();
+        // Latest `cryo-agent set-summary`, the summary of last resort
+        let mut working_summary: Option<String> = None;
 
         loop {
             // Check shutdown
             if self.shutdown.load(Ordering::Relaxed) {
-                terminate_child(&mut child, child_pid);
-                if !inbox_filenames.is_empty() {
-                    let _ = crate::message::archive_messages(&self.dir, &inbox_filenames);
+                if terminate_child(&mut child, child_pid, config) {
+                    log_kill_escalation(&mut logger, config)?;
                 }
+                output_copy.wait(OUTPUT_DRAIN_TIMEOUT);
+                log_usage(&mut logger, usage_extractor.as_ref(), &session_output)?;
+                let _ = self.settle_inbox(
+                    config,
+                    &mut logger,
+                    &inbox_filenames,
+                    &acked_inbox,
+                    &inbox_marks,
+                    &mut cryo_state.inbox_unhandled,
+                    &session_output,
+                );
                 if let Some(outcome) = hibernate_outcome {
                     logger.finish("daemon shutdown — using agent's hibernate outcome")?;
                     return Ok(outcome);
                 }
+                log_working_summary(&mut logger, working_summary.as_deref())?;
                 logger.finish("daemon shutdown — agent terminated")?;
                 return Ok(SessionLoopOutcome::ValidationFailed { quick_exit: false });
             }
@@ -606,14 +2291,25 @@ impl Daemon {
             if let Some(d) = deadline {
                 if std::time::Instant::now() >= d {
                     eprintln!("Daemon: session timeout ({timeout_secs}s) — killing agent");
-                    terminate_child(&mut child, child_pid);
-                    if !inbox_filenames.is_empty() {
-                        let _ = crate::message::archive_messages(&self.dir, &inbox_filenames);
+                    if terminate_child(&mut child, child_pid, config) {
+                        log_kill_escalation(&mut logger, config)?;
                     }
+                    output_copy.wait(OUTPUT_DRAIN_TIMEOUT);
+                    log_usage(&mut logger, usage_extractor.as_ref(), &session_output)?;
+                    let _ = self.settle_inbox(
+                        config,
+                        &mut logger,
+                        &inbox_filenames,
+                        &acked_inbox,
+                        &inbox_marks,
+                        &mut cryo_state.inbox_unhandled,
+                        &session_output,
+                    );
                     if let Some(outcome) = hibernate_outcome {
                         logger.finish("session timeout — using agent's hibernate outcome")?;
                         return Ok(outcome);
                     }
+                    log_working_summary(&mut logger, working_summary.as_deref())?;
                     logger.finish("session timeout — agent killed")?;
                     return Ok(SessionLoopOutcome::ValidationFailed { quick_exit: false });
                 }
@@ -622,7 +2318,50 @@ impl Daemon {
             // Try accept a socket connection (non-blocking)
             match server.accept_one() {
                 Ok(Some((request, responder))) => {
+                    verbose!("Daemon: socket request: {}", request.cmd());
                     match request {
+                        crate::socket::Request::Hello => {
+                            let hello = crate::socket::Hello::current();
+                            let _ = responder.respond(&crate::socket::Response {
+                                ok: true,
+                                message: serde_json::to_string(&hello)?,
+                            });
+                        }
+                        crate::socket::Request::Env { show_values } => {
+                            let _ = responder.respond(&crate::socket::Response {
+                                ok: true,
+                                message: describe_env(
+                                    provider.map(|p| p.name.as_str()),
+                                    &provider_env,
+                                    show_values,
+                                ),
+                            });
+                        }
+                        crate::socket::Request::Ping => {
+                            let _ = responder.respond(&crate::socket::Response {
+                                ok: true,
+                                message: format!(
+                                    "Daemon alive (pid {}, session {})",
+                                    std::process::id(),
+                                    cryo_state.session_number
+                                ),
+                            });
+                        }
+                        crate::socket::Request::Wake => {
+                            // Runs another session once this one ends
+                            self.wake_requested.store(true, Ordering::Relaxed);
+                            let _ = responder.respond(&crate::socket::Response {
+                                ok: true,
+                                message: "Wake queued after the running session.".into(),
+                            });
+                        }
+                        crate::socket::Request::Stop => {
+                            self.shutdown.store(true, Ordering::Relaxed);
+                            let _ = responder.respond(&crate::socket::Response {
+                                ok: true,
+                                message: "Stopping the daemon.".into(),
+                            });
+                        }
                         crate::socket::Request::Note { text } => {
                             logger.log_event(&format!("note: \"{text}\""))?;
                             let _ = responder.respond(&crate::socket::Response {
@@ -630,34 +2369,343 @@ impl Daemon {
                                 message: "Note recorded".into(),
                             });
                         }
+                        crate::socket::Request::QueryMessages { query } => {
+                            let now = Local::now().naive_local();
+                            let response = match crate::store::query_messages(
+                                self.store.as_ref(),
+                                &query,
+                                now,
+                            )
+                            .and_then(|found| Ok(serde_json::to_string(&found)?))
+                            {
+                                Ok(json) => crate::socket::Response {
+                                    ok: true,
+                                    message: json,
+                                },
+                                Err(e) => crate::socket::Response {
+                                    ok: false,
+                                    message: format!("{e:#}"),
+                                },
+                            };
+                            let _ = responder.respond(&response);
+                        }
+                        crate::socket::Request::NextTask { task } => {
+                            logger
+                                .log_event(&format!("next task: {}", crate::log::quote(&task)))?;
+                            let _ = responder.respond(&crate::socket::Response {
+                                ok: true,
+                                message: "Next task recorded".into(),
+                            });
+                        }
+                        crate::socket::Request::FocusPlan { name } => {
+                            let name = name.trim_end_matches(".md").to_string();
+                            let plans = crate::plans::list(&self.dir).unwrap_or_default();
+                            let error = if !plans.contains(&name) {
+                                Some(format!(
+                                    "No plan {}. Plans: {}",
+                                    crate::plans::plan_file(&name),
+                                    if plans.is_empty() {
+                                        "(none)".to_string()
+                                    } else {
+                                        plans.join(", ")
+                                    }
+                                ))
+                            } else if cryo_state.completed_plans.contains(&name) {
+                                Some(format!("Plan {name} is already complete"))
+                            } else {
+                                None
+                            };
+                            if let Some(message) = error {
+                                let _ = responder
+                                    .respond(&crate::socket::Response { ok: false, message });
+                                continue;
+                            }
+                            logger.log_event(&format!("focus plan requested: {name}"))?;
+                            let _ = responder.respond(&crate::socket::Response {
+                                ok: true,
+                                message: format!(
+                                    "Next session focuses on {}",
+                                    crate::plans::plan_file(&name)
+                                ),
+                            });
+                            cryo_state.focus_request = Some(name);
+                        }
+                        crate::socket::Request::RotateProvider { reason } => {
+                            if config.providers.len() < 2 {
+                                let _ = responder.respond(&crate::socket::Response {
+                                    ok: false,
+                                    message: format!(
+                                        "No other provider to rotate to ({} configured)",
+                                        config.providers.len()
+                                    ),
+                                });
+                                continue;
+                            }
+                            let current = cryo_state.provider_index.unwrap_or(0);
+                            let next =
+                                provider_name(config, (current + 1) % config.providers.len());
+                            logger.log_event(&format!(
+                                "rotate provider requested: \"{reason}\" ({} -> {next})",
+                                provider_name(config, current)
+                            ))?;
+                            let _ = responder.respond(&crate::socket::Response {
+                                ok: true,
+                                message: format!("Next session uses provider {next}"),
+                            });
+                            cryo_state.provider_rotation = Some(reason);
+                        }
+                        crate::socket::Request::Defer { by } => {
+                            let by = match crate::schedule::parse_wake_in(&by) {
+                                Ok(by) => by,
+                                Err(e) => {
+                                    let _ = responder.respond(&crate::socket::Response {
+                                        ok: false,
+                                        message: format!("{e:#}"),
+                                    });
+                                    continue;
+                                }
+                            };
+                            // Check the moved wake first, so an overflow changes nothing
+                            let moved_wake = match &hibernate_outcome {
+                                Some(SessionLoopOutcome::Hibernate { wake_time, .. }) => {
+                                    match wake_time.checked_add_signed(by) {
+                                        Some(moved) => Some(moved),
+                                        None => {
+                                            let _ = responder.respond(&crate::socket::Response {
+                                                ok: false,
+                                                message: format!(
+                                                    "Cannot defer the wake at {}: out of range",
+                                                    wake_time.format(WAKE_TIME_FMT)
+                                                ),
+                                            });
+                                            continue;
+                                        }
+                                    }
+                                }
+                                _ => None,
+                            };
+                            let mut changes = Vec::new();
+                            if let Some(d) = deadline {
+                                let cap = defer_cap_secs(config, timeout_secs);
+                                let by_std = by.to_std().unwrap_or_default();
+                                match deferred_deadline(d, spawn_time, by_std, cap) {
+                                    Some(moved) => {
+                                        let granted = (moved - d).as_secs();
+                                        deadline = Some(moved);
+                                        logger.log_event(&format!(
+                                            "defer: deadline +{granted}s (hard cap {cap}s)"
+                                        ))?;
+                                        changes.push(format!("timeout extended by {granted}s"));
+                                    }
+                                    None => changes
+                                        .push(format!("timeout already at the {cap}s hard cap")),
+                                }
+                            }
+                            if let (
+                                Some(SessionLoopOutcome::Hibernate { wake_time, .. }),
+                                Some(moved),
+                            ) = (&mut hibernate_outcome, moved_wake)
+                            {
+                                *wake_time = moved;
+                                let wake = wake_time.format("%Y-%m-%dT%H:%M");
+                                logger.log_event(&format!("defer: next wake {wake}"))?;
+                                changes.push(format!("next wake moved to {wake}"));
+                            }
+                            let _ = responder.respond(&if changes.is_empty() {
+                                crate::socket::Response {
+                                    ok: false,
+                                    message: "Nothing to defer: no session timeout and no wake set"
+                                        .into(),
+                                }
+                            } else {
+                                crate::socket::Response {
+                                    ok: true,
+                                    message: changes.join("; "),
+                                }
+                            });
+                        }
+                        crate::socket::Request::SetSummary { summary } => {
+                            verbose!("Daemon: working summary: {summary}");
+                            // In timer.json right away, so even a hard kill keeps it
+                            cryo_state.last_summary = Some(summary.clone());
+                            let _ = state::save_state(&self.state_path, cryo_state);
+                            working_summary = Some(summary);
+                            let _ = responder.respond(&crate::socket::Response {
+                                ok: true,
+                                message: "Summary updated".into(),
+                            });
+                        }
+                        crate::socket::Request::SummaryAppend { text } => {
+                            let text = text.trim();
+                            if text.is_empty() {
+                                let _ = responder.respond(&crate::socket::Response {
+                                    ok: false,
+                                    message: "Nothing to append: the text is empty".into(),
+                                });
+                                continue;
+                            }
+                            logger.log_event(&format!("summary+: {}", crate::log::quote(text)))?;
+                            let summary = append_summary(working_summary.take(), text);
+                            cryo_state.last_summary = Some(summary.clone());
+                            let _ = state::save_state(&self.state_path, cryo_state);
+                            working_summary = Some(summary);
+                            let _ = responder.respond(&crate::socket::Response {
+                                ok: true,
+                                message: "Summary appended".into(),
+                            });
+                        }
+                        crate::socket::Request::CheckpointSave { data } => {
+                            if let Err(e) = save_checkpoint(&self.dir, &data) {
+                                let _ = responder.respond(&crate::socket::Response {
+                                    ok: false,
+                                    message: format!("Failed to save checkpoint: {e:#}"),
+                                });
+                                continue;
+                            }
+                            logger.log_event(&format!(
+                                "checkpoint: {}",
+                                crate::log::compact_json(&data)
+                            ))?;
+                            let _ = responder.respond(&crate::socket::Response {
+                                ok: true,
+                                message: "Checkpoint saved".into(),
+                            });
+                        }
+                        crate::socket::Request::CheckpointLoad => {
+                            let _ = responder.respond(&match state::load_checkpoint(&self.dir) {
+                                Some(data) => crate::socket::Response {
+                                    ok: true,
+                                    message: data,
+                                },
+                                None => crate::socket::Response {
+                                    ok: false,
+                                    message: "No checkpoint saved".into(),
+                                },
+                            });
+                        }
+                        crate::socket::Request::AttachLog {
+                            label,
+                            content,
+                            total_bytes,
+                        } => {
+                            let shown = crate::log::tail(&content, config.attach_log_max_bytes);
+                            let size = if (shown.len() as u64) < total_bytes {
+                                format!("last {} of {total_bytes} bytes", shown.len())
+                            } else {
+                                format!("{total_bytes} bytes")
+                            };
+                            logger
+                                .log_block(&format!("attach-log: \"{label}\" ({size})"), shown)?;
+                            let _ = responder.respond(&crate::socket::Response {
+                                ok: true,
+                                message: format!("Attached '{label}' ({size})"),
+                            });
+                        }
+                        crate::socket::Request::Event { kind, data } => {
+                            let line = crate::log::format_custom_event(&kind, data.as_deref());
+                            logger.log_event(&line)?;
+                            let _ = responder.respond(&crate::socket::Response {
+                                ok: true,
+                                message: format!("Event '{kind}' recorded"),
+                            });
+                        }
                         crate::socket::Request::Hibernate {
                             wake,
                             complete,
                             exit_code,
                             summary,
+                            until_message,
+                            wake_on_message,
+                            result,
+                            every,
+                            wake_in,
                         } => {
+                            let summary = summary.or_else(|| working_summary.clone());
+                            if config.require_summary
+                                && summary.as_deref().is_none_or(|s| s.trim().is_empty())
+                            {
+                                logger.log_event("hibernate rejected: no summary")?;
+                                let _ = responder.respond(&crate::socket::Response {
+                                    ok: false,
+                                    message: "A summary is required (require_summary = true): \
+                                              pass --summary \"what I did, what's next\""
+                                        .into(),
+                                });
+                                continue;
+                            }
                             let summary_str = summary.as_deref().unwrap_or("(no summary)");
-                            if complete {
+                            cryo_state.last_summary = summary.clone();
+                            if until_message {
+                                logger.log_event(&format!(
+                                    "hibernate: until message, exit={exit_code}, summary=\"{summary_str}\""
+                                ))?;
+                                hibernate_outcome = Some(SessionLoopOutcome::AwaitMessage {
+                                    fallback: pending_fallback.take(),
+                                    deadline: None,
+                                });
+                            } else if complete {
+                                if let Some(result) = &result {
+                                    if let Err(e) = save_result(&self.dir, result) {
+                                        let _ = responder.respond(&crate::socket::Response {
+                                            ok: false,
+                                            message: format!("Failed to save result: {e}"),
+                                        });
+                                        continue;
+                                    }
+                                    logger.log_event("result saved to .cryo/result.json")?;
+                                }
+                                if config.require_summary {
+                                    let report = crate::report::final_report_message(
+                                        &config.project_name(&self.dir),
+                                        summary_str,
+                                    );
+                                    match self.store.write("outbox", &report) {
+                                        Ok(_) => logger.log_event("final report sent to outbox")?,
+                                        Err(e) => logger
+                                            .log_event(&format!("final report failed: {e}"))?,
+                                    }
+                                }
                                 logger.log_event(&format!(
                                     "hibernate: plan complete, exit={exit_code}, summary=\"{summary_str}\""
                                 ))?;
                                 hibernate_outcome = Some(SessionLoopOutcome::PlanComplete);
-                            } else if let Some(wake_str) = &wake {
-                                match chrono::NaiveDateTime::parse_from_str(wake_str, WAKE_TIME_FMT)
-                                {
+                            } else {
+                                // A bare hibernate without a schedule sleeps for wake_cadence
+                                let cadence = config
+                                    .wake_cadence
+                                    .as_deref()
+                                    .filter(|_| every.is_none() && cryo_state.schedule.is_none());
+                                match timed_wake(
+                                    wake.as_deref(),
+                                    wake_in.as_deref().or(cadence),
+                                    every.as_deref(),
+                                    cryo_state.schedule.as_deref(),
+                                    Local::now().naive_local(),
+                                ) {
                                     Ok(wake_time) => {
+                                        if let Some(expr) = every {
+                                            logger.log_event(&format!(
+                                                "schedule: every \"{expr}\""
+                                            ))?;
+                                            cryo_state.schedule = Some(expr);
+                                        }
+                                        let wake_str = wake_time.format(WAKE_TIME_FMT);
                                         logger.log_event(&format!(
                                             "hibernate: wake={wake_str}, exit={exit_code}, summary=\"{summary_str}\""
                                         ))?;
+                                        if wake_on_message {
+                                            logger.log_event("wake on message: enabled")?;
+                                        }
                                         hibernate_outcome = Some(SessionLoopOutcome::Hibernate {
                                             wake_time,
                                             fallback: pending_fallback.take(),
+                                            wake_on_message,
                                         });
                                     }
                                     Err(e) => {
                                         let _ = responder.respond(&crate::socket::Response {
                                             ok: false,
-                                            message: format!("Invalid wake time: {e}"),
+                                            message: format!("{e:#}"),
                                         });
                                         continue;
                                     }
@@ -665,13 +2713,226 @@ impl Daemon {
                             }
                             let _ = responder.respond(&crate::socket::Response {
                                 ok: true,
-                                message: if complete {
+                                message: if until_message {
+                                    "Hibernating until a message arrives.".into()
+                                } else if complete {
                                     "Plan complete. Shutting down.".into()
                                 } else {
                                     "Hibernating.".into()
                                 },
                             });
                         }
+                        crate::socket::Request::Abort { reason, wake } => {
+                            let now = Local::now().naive_local();
+                            let wake_time = match wake.as_deref() {
+                                Some(wake) => {
+                                    match NaiveDateTime::parse_from_str(wake, WAKE_TIME_FMT) {
+                                        Ok(wake_time) if wake_time > now => Some(wake_time),
+                                        Ok(_) => {
+                                            let _ = responder.respond(&crate::socket::Response {
+                                                ok: false,
+                                                message: format!("Wake time {wake} is in the past"),
+                                            });
+                                            continue;
+                                        }
+                                        Err(e) => {
+                                            let _ = responder.respond(&crate::socket::Response {
+                                                ok: false,
+                                                message: format!("Invalid wake time: {e}"),
+                                            });
+                                            continue;
+                                        }
+                                    }
+                                }
+                                // Keep the project's rhythm: the stored schedule, else
+                                // wake_cadence, as for a bare hibernate
+                                None => {
+                                    let cadence = config
+                                        .wake_cadence
+                                        .as_deref()
+                                        .filter(|_| cryo_state.schedule.is_none());
+                                    timed_wake(
+                                        None,
+                                        cadence,
+                                        None,
+                                        cryo_state.schedule.as_deref(),
+                                        now,
+                                    )
+                                    .ok()
+                                }
+                            };
+                            logger.log_event(&format!("abort: \"{reason}\""))?;
+                            cryo_state.last_summary = Some(reason);
+                            hibernate_outcome = Some(SessionLoopOutcome::Aborted { wake_time });
+                            let _ = responder.respond(&crate::socket::Response {
+                                ok: true,
+                                message: "Session aborted.".into(),
+                            });
+                        }
+                        crate::socket::Request::RequestReview { prompt, id } => {
+                            let review = match crate::review::request(
+                                &self.dir,
+                                id.as_deref(),
+                                &prompt,
+                                Local::now().naive_local(),
+                                config.review_deadline_hours,
+                            ) {
+                                Ok(review) => review,
+                                Err(e) => {
+                                    let _ = responder.respond(&crate::socket::Response {
+                                        ok: false,
+                                        message: format!("{e:#}"),
+                                    });
+                                    continue;
+                                }
+                            };
+                            if let Err(e) = self.store.write("outbox", &review.request_message()) {
+                                logger.log_event(&format!(
+                                    "review request failed: {} {e:#}",
+                                    review.id
+                                ))?;
+                                // Nobody was told, so nobody can decide it
+                                let _ = crate::review::remove(&self.dir, &review.id);
+                                let _ = responder.respond(&crate::socket::Response {
+                                    ok: false,
+                                    message: format!("Failed to send review {}: {e:#}", review.id),
+                                });
+                                continue;
+                            }
+                            logger.log_event(&format!(
+                                "review requested: {} \"{prompt}\"",
+                                review.id
+                            ))?;
+                            // Sleep until the decision arrives; never proceed on
+                            // a timeout, only alert someone.
+                            hibernate_outcome = Some(SessionLoopOutcome::AwaitMessage {
+                                fallback: Some(FallbackAction {
+                                    action: "review_unanswered".to_string(),
+                                    target: "operator".to_string(),
+                                    message: format!(
+                                        "Review {} is still waiting for a decision: {prompt}",
+                                        review.id
+                                    ),
+                                }),
+                                deadline: review.deadline,
+                            });
+                            let _ = responder.respond(&crate::socket::Response {
+                                ok: true,
+                                message: format!(
+                                    "Review {} requested. End the session now; the decision \
+                                     arrives in the inbox on the next wake.",
+                                    review.id
+                                ),
+                            });
+                        }
+                        crate::socket::Request::WaitForReply { id, timeout_secs } => {
+                            if pending_reply.is_some() {
+                                let _ = responder.respond(&crate::socket::Response {
+                                    ok: false,
+                                    message: "Already waiting for a reply".into(),
+                                });
+                            } else {
+                                let label = id.as_deref().unwrap_or("any message");
+                                logger.log_event(&format!(
+                                    "waiting for reply: \"{label}\" (timeout {timeout_secs}s)"
+                                ))?;
+                                let mut seen: std::collections::HashSet<String> =
+                                    inbox_filenames.iter().cloned().collect();
+                                seen.extend(self.store.list_inbox()?);
+                                pending_reply = Some(PendingReply {
+                                    id,
+                                    deadline: std::time::Instant::now()
+                                        + Duration::from_secs(timeout_secs),
+                                    seen,
+                                    responder,
+                                });
+                            }
+                        }
+                        crate::socket::Request::AckInbox { files } => {
+                            acked_inbox.extend(files);
+                            let _ = responder.respond(&crate::socket::Response {
+                                ok: true,
+                                message: "Inbox read".into(),
+                            });
+                        }
+                        crate::socket::Request::Handled { files, keep } => {
+                            let inbox = self.store.list_inbox().unwrap_or_default();
+                            let missing: Vec<&str> = files
+                                .iter()
+                                .filter(|f| !inbox.contains(f))
+                                .map(|f| f.as_str())
+                                .collect();
+                            if !missing.is_empty() {
+                                let _ = responder.respond(&crate::socket::Response {
+                                    ok: false,
+                                    message: format!("Not in the inbox: {}", missing.join(", ")),
+                                });
+                                continue;
+                            }
+                            inbox_marks.mark(&files, keep);
+                            acked_inbox.extend(files.iter().cloned());
+                            let verb = if keep { "kept" } else { "handled" };
+                            logger.log_event(&format!("inbox {verb}: {}", files.join(", ")))?;
+                            let _ = responder.respond(&crate::socket::Response {
+                                ok: true,
+                                message: if keep {
+                                    format!("{} message(s) kept for the next session", files.len())
+                                } else {
+                                    format!("{} message(s) marked handled", files.len())
+                                },
+                            });
+                        }
+                        crate::socket::Request::WatchInbox => {
+                            if inbox_subscriber.is_some() {
+                                let _ = responder.respond(&crate::socket::Response {
+                                    ok: false,
+                                    message:
+                                        "An inbox watcher is already connected for this session"
+                                            .into(),
+                                });
+                                continue;
+                            }
+                            let (changes_tx, changes) = mpsc::channel();
+                            let source = match self.inbox_source(config, changes_tx) {
+                                Ok(source) => source,
+                                Err(e) => {
+                                    let _ = responder.respond(&crate::socket::Response {
+                                        ok: false,
+                                        message: format!("Cannot watch the inbox: {e:#}"),
+                                    });
+                                    continue;
+                                }
+                            };
+                            let mut seen: std::collections::HashSet<String> =
+                                inbox_filenames.iter().cloned().collect();
+                            match self.store.list_inbox() {
+                                Ok(files) => seen.extend(files),
+                                Err(e) => {
+                                    eprintln!("Daemon: warning: failed to list inbox: {e:#}")
+                                }
+                            }
+                            let mut stream = match responder.into_stream() {
+                                Ok(stream) => stream,
+                                Err(e) => {
+                                    eprintln!("Daemon: failed to open inbox watch stream: {e}");
+                                    continue;
+                                }
+                            };
+                            let ack = stream.send(&crate::socket::Response {
+                                ok: true,
+                                message: "Watching inbox".into(),
+                            });
+                            if ack.is_ok() {
+                                logger.log_event("inbox watcher connected")?;
+                                inbox_subscriber = Some(InboxSubscriber {
+                                    seen,
+                                    stream,
+                                    changes,
+                                    _source: source,
+                                    retry: false,
+                                });
+                            }
+                        }
                         crate::socket::Request::Alert {
                             action,
                             target,
@@ -688,18 +2949,38 @@ impl Daemon {
                                 message: "Alert registered".into(),
                             });
                         }
-                        crate::socket::Request::Reply { text } => {
+                        crate::socket::Request::Reply {
+                            text,
+                            attachments,
+                            to,
+                            subject,
+                        } => {
                             // Write reply to outbox
+                            let mut metadata = std::collections::BTreeMap::new();
+                            if !to.is_empty() {
+                                metadata.insert(crate::message::TO_KEY.to_string(), to.join(", "));
+                            }
                             let msg = crate::message::Message {
                                 from: "agent".to_string(),
-                                subject: "Reply".to_string(),
+                                subject: subject.unwrap_or_else(|| "Reply".to_string()),
                                 body: text.clone(),
                                 timestamp: chrono::Local::now().naive_local(),
-                                metadata: std::collections::BTreeMap::new(),
+                                metadata,
+                                attachments,
                             };
-                            match crate::message::write_message(&self.dir, "outbox", &msg) {
+                            match self.store.write("outbox", &msg) {
                                 Ok(_) => {
-                                    logger.log_event(&format!("reply: \"{text}\""))?;
+                                    if to.is_empty() {
+                                        logger.log_event(&format!("reply: \"{text}\""))?;
+                                    } else {
+                                        logger.log_event(&format!(
+                                            "reply to {}: \"{text}\"",
+                                            to.join(", ")
+                                        ))?;
+                                    }
+                                    for name in &msg.attachments {
+                                        logger.log_event(&format!("attachment: {name}"))?;
+                                    }
                                     let _ = responder.respond(&crate::socket::Response {
                                         ok: true,
                                         message: "Reply sent".into(),
@@ -723,29 +3004,123 @@ impl Daemon {
                         if io_err.kind() != std::io::ErrorKind::WouldBlock {
                             eprintln!("Daemon: socket accept error: {e}");
                         }
+                    } else {
+                        // Malformed request from a client
+                        verbose!("Daemon: socket accept error: {e}");
                     }
                 }
             }
 
+            if let Some(wait) = pending_reply.as_mut() {
+                if let Some((filename, msg)) = self.find_reply(wait) {
+                    let wait = pending_reply.take().unwrap();
+                    let label = wait.id.as_deref().unwrap_or("any message");
+                    logger.log_event(&format!("reply received: \"{label}\" ({filename})"))?;
+                    // The agent has now read it; dispose of it with the session's inbox
+                    acked_inbox.insert(filename.clone());
+                    inbox_filenames.push(filename);
+                    let _ = wait.responder.respond(&crate::socket::Response {
+                        ok: true,
+                        message: msg.body,
+                    });
+                } else if std::time::Instant::now() >= wait.deadline {
+                    let wait = pending_reply.take().unwrap();
+                    let label = wait.id.as_deref().unwrap_or("any message");
+                    logger.log_event(&format!("wait for reply timed out: \"{label}\""))?;
+                    let _ = wait.responder.respond(&crate::socket::Response {
+                        ok: false,
+                        message: format!("No reply to \"{label}\" before the timeout"),
+                    });
+                }
+            }
+
+            if let Some(sub) = inbox_subscriber.as_mut() {
+                if let Err(e) = self.notify_subscriber(sub, &mut logger) {
+                    verbose!("Daemon: inbox watcher disconnected: {e}");
+                    logger.log_event("inbox watcher disconnected")?;
+                    inbox_subscriber = None;
+                }
+            }
+
             // Check if agent has exited
             match child.try_wait() {
                 Ok(Some(status)) => {
                     let code = status.code();
                     let elapsed = spawn_time.elapsed();
-                    logger.log_event(&format!(
-                        "agent exited (code {})",
-                        code.map(|c| c.to_string())
-                            .unwrap_or_else(|| "signal".into())
-                    ))?;
+                    let code_str = code
+                        .map(|c| c.to_string())
+                        .unwrap_or_else(|| "signal".into());
+                    logger.log_event(&format!("agent exited (code {code_str})"))?;
+                    if !output_copy.wait(OUTPUT_DRAIN_TIMEOUT) {
+                        verbose!("Daemon: agent output still open after exit (a child kept it)");
+                    }
+                    log_usage(&mut logger, usage_extractor.as_ref(), &session_output)?;
 
                     // Archive inbox messages now that agent has finished
-                    if !inbox_filenames.is_empty() {
-                        crate::message::archive_messages(&self.dir, &inbox_filenames)?;
-                    }
+                    self.settle_inbox(
+                        config,
+                        &mut logger,
+                        &inbox_filenames,
+                        &acked_inbox,
+                        &inbox_marks,
+                        &mut cryo_state.inbox_unhandled,
+                        &session_output,
+                    )?;
+
+                    let clean_exit = code == Some(0)
+                        && hibernate_outcome.is_none()
+                        && detect_completion(config, &self.dir, &session_output).is_none();
+                    let reschedule_at = if clean_exit
+                        && config.on_clean_exit_without_hibernate == CleanExit::Reschedule
+                    {
+                        let now = Local::now().naive_local();
+                        match clean_exit_wake(config, cryo_state.schedule.as_deref(), now) {
+                            Ok(wake_time) => Some(wake_time),
+                            Err(e) => {
+                                logger
+                                    .log_event(&format!("clean exit: cannot reschedule: {e:#}"))?;
+                                None
+                            }
+                        }
+                    } else {
+                        None
+                    };
 
                     if let Some(outcome) = hibernate_outcome {
-                        logger.finish("session complete")?;
+                        logger.finish(match outcome {
+                            SessionLoopOutcome::Aborted { .. } => "session aborted",
+                            _ => "session complete",
+                        })?;
                         return Ok(outcome);
+                    } else if let Some(how) = detect_completion(config, &self.dir, &session_output)
+                    {
+                        let _ = std::fs::remove_file(complete_file_path(&self.dir));
+                        logger.log_event(&format!(
+                            "hibernate: plan complete, detected {how}, exit={code_str}"
+                        ))?;
+                        log_working_summary(&mut logger, working_summary.as_deref())?;
+                        logger.finish("session complete")?;
+                        return Ok(SessionLoopOutcome::PlanComplete);
+                    } else if clean_exit
+                        && config.on_clean_exit_without_hibernate == CleanExit::Complete
+                    {
+                        logger
+                            .log_event("hibernate: plan complete, clean exit without hibernate")?;
+                        log_working_summary(&mut logger, working_summary.as_deref())?;
+                        logger.finish("session complete")?;
+                        return Ok(SessionLoopOutcome::PlanComplete);
+                    } else if let Some(wake_time) = reschedule_at {
+                        logger.log_event(&format!(
+                            "hibernate: wake={}, clean exit without hibernate",
+                            wake_time.format(WAKE_TIME_FMT)
+                        ))?;
+                        log_working_summary(&mut logger, working_summary.as_deref())?;
+                        logger.finish("session complete")?;
+                        return Ok(SessionLoopOutcome::Hibernate {
+                            wake_time,
+                            fallback: pending_fallback.take(),
+                            wake_on_message: false,
+                        });
                     } else {
                         // Quick-exit detection: agent exited fast without hibernating
                         if elapsed < Duration::from_secs(5) {
@@ -761,6 +3136,7 @@ impl Daemon {
                             ))?;
                         }
                         // Agent exited without calling hibernate — treat as crash
+                        log_working_summary(&mut logger, working_summary.as_deref())?;
                         logger.finish("agent exited without hibernate")?;
                         return Ok(SessionLoopOutcome::ValidationFailed {
                             quick_exit: elapsed < Duration::from_secs(5),
@@ -774,17 +3150,320 @@ impl Daemon {
                 }
             }
 
-            // If we got a hibernate command but agent hasn't exited yet,
-            // give it a moment then continue polling
-            if hibernate_outcome.is_some() {
-                // Agent sent hibernate but hasn't exited yet — wait a bit
-                // The agent should exit shortly after calling cryo hibernate
-                std::thread::sleep(Duration::from_millis(100));
+            // If we got a hibernate command but agent hasn't exited yet,
+            // give it a moment then continue polling
+            if hibernate_outcome.is_some() {
+                // Agent sent hibernate but hasn't exited yet — wait a bit
+                // The agent should exit shortly after calling cryo hibernate
+                std::thread::sleep(Duration::from_millis(100));
+                continue;
+            }
+
+            std::thread::sleep(Duration::from_millis(100));
+        }
+    }
+
+    /// First inbox message not yet checked by `wait` that answers its question.
+    fn find_reply(&self, wait: &mut PendingReply) -> Option<(String, crate::message::Message)> {
+        for filename in self.store.list_inbox().ok()? {
+            if wait.seen.contains(&filename) {
+                continue;
+            }
+            // A file that doesn't parse may still be mid-write; retry next poll
+            let Ok(msg) = self.store.read("inbox", &filename) else {
+                continue;
+            };
+            wait.seen.insert(filename.clone());
+            if msg.answers(wait.id.as_deref()) {
+                return Some((filename, msg));
+            }
+        }
+        None
+    }
+
+    /// Settle the inbox at the end of a session. Only messages presented to
+    /// it (or marked by the agent with `cryo-agent handled`) are disposed
+    /// of: one that arrived while the agent ran, e.g. from `cryo send
+    /// --wake`, stays in the inbox for the next session.
+    #[allow(clippy::too_many_arguments)]
+    fn settle_inbox(
+        &self,
+        config: &CryoConfig,
+        logger: &mut crate::log::EventLogger,
+        presented: &[String],
+        acked: &std::collections::HashSet<String>,
+        marks: &InboxMarks,
+        unhandled: &mut std::collections::BTreeMap<String, u32>,
+        output: &crate::log::SessionOutput,
+    ) -> Result<()> {
+        if marks.is_empty() {
+            self.settle_presented_inbox(config, logger, presented, acked, unhandled, output)?;
+        } else {
+            self.settle_marked_inbox(config, logger, presented, marks, unhandled)?;
+        }
+        let arrived: Vec<String> = self
+            .store
+            .list_inbox()?
+            .into_iter()
+            .filter(|f| !presented.contains(f))
+            .collect();
+        if !arrived.is_empty() {
+            logger.log_event(&format!(
+                "inbox arrived during session (kept for next session): {}",
+                arrived.join(", ")
+            ))?;
+        }
+        Ok(())
+    }
+
+    /// Dispose of the inbox messages presented to this session. Ones the
+    /// agent shows no sign of having read (not acknowledged and not named in
+    /// its output) are logged. With `require_inbox_ack` they are kept for the
+    /// next session, for at most `inbox_grace_sessions` sessions. Without it
+    /// an agent may well have read the file directly, so this isn't a warning.
+    fn settle_presented_inbox(
+        &self,
+        config: &CryoConfig,
+        logger: &mut crate::log::EventLogger,
+        presented: &[String],
+        acked: &std::collections::HashSet<String>,
+        unhandled: &mut std::collections::BTreeMap<String, u32>,
+        output: &crate::log::SessionOutput,
+    ) -> Result<()> {
+        if presented.is_empty() {
+            return Ok(());
+        }
+        let text = output.read();
+        let ignored: Vec<&String> = presented
+            .iter()
+            .filter(|f| !acked.contains(*f) && !text.contains(f.as_str()))
+            .collect();
+
+        let mut dispose: Vec<String> = presented.to_vec();
+        if config.require_inbox_ack {
+            let mut waiting = Vec::new();
+            let mut expired = Vec::new();
+            for file in ignored {
+                let sessions = unhandled.entry(file.clone()).or_insert(0);
+                *sessions += 1;
+                if *sessions >= config.inbox_grace_sessions {
+                    expired.push(file.clone());
+                } else {
+                    waiting.push(file.clone());
+                }
+            }
+            if !waiting.is_empty() {
+                logger.log_event(&format!(
+                    "warning: inbox messages may have been ignored (kept for next session): {}",
+                    waiting.join(", ")
+                ))?;
+                dispose.retain(|f| !waiting.contains(f));
+            }
+            if !expired.is_empty() {
+                logger.log_event(&format!(
+                    "warning: inbox messages ignored for {} sessions, disposing: {}",
+                    config.inbox_grace_sessions.max(1),
+                    expired.join(", ")
+                ))?;
+            }
+        } else if !ignored.is_empty() {
+            let names = ignored
+                .iter()
+                .map(|f| f.as_str())
+                .collect::<Vec<_>>()
+                .join(", ");
+            logger.log_event(&format!("inbox not confirmed read: {names}"))?;
+        }
+        if !dispose.is_empty() {
+            self.store.dispose(&dispose, config.inbox_disposition)?;
+        }
+        unhandled.retain(|f, _| !dispose.contains(f));
+        Ok(())
+    }
+
+    /// Settle the inbox of a session that marked messages: dispose of the
+    /// handled ones, leave the kept ones, and give the rest
+    /// `inbox_grace_sessions` sessions before disposing of them anyway.
+    fn settle_marked_inbox(
+        &self,
+        config: &CryoConfig,
+        logger: &mut crate::log::EventLogger,
+        presented: &[String],
+        marks: &InboxMarks,
+        unhandled: &mut std::collections::BTreeMap<String, u32>,
+    ) -> Result<()> {
+        let mut dispose: Vec<String> = marks.handled.iter().cloned().collect();
+        let mut waiting = Vec::new();
+        let mut expired = Vec::new();
+        for file in presented {
+            if marks.handled.contains(file) || marks.kept.contains(file) {
+                continue;
+            }
+            let sessions = unhandled.entry(file.clone()).or_insert(0);
+            *sessions += 1;
+            if *sessions >= config.inbox_grace_sessions {
+                expired.push(file.clone());
+            } else {
+                waiting.push(file.clone());
+            }
+        }
+        if !waiting.is_empty() {
+            logger.log_event(&format!(
+                "inbox not handled (kept for next session): {}",
+                waiting.join(", ")
+            ))?;
+        }
+        if !expired.is_empty() {
+            logger.log_event(&format!(
+                "warning: inbox not handled for {} sessions, disposing: {}",
+                config.inbox_grace_sessions.max(1),
+                expired.join(", ")
+            ))?;
+            dispose.extend(expired);
+        }
+        if !dispose.is_empty() {
+            self.store.dispose(&dispose, config.inbox_disposition)?;
+        }
+        unhandled.retain(|f, _| !dispose.contains(f) && !marks.kept.contains(f));
+        Ok(())
+    }
+
+    /// Watch the inbox for a `cryo-agent watch-inbox` subscriber, sending
+    /// `DaemonEvent::InboxChanged` to `tx` on each arrival.
+    fn inbox_source(
+        &self,
+        config: &CryoConfig,
+        tx: mpsc::Sender<DaemonEvent>,
+    ) -> Result<InboxSource> {
+        match config.message_store {
+            MessageStoreKind::File => {
+                let inbox_path = crate::message::dirs(&self.dir, config).inbox;
+                std::fs::create_dir_all(&inbox_path)?;
+                Ok(InboxSource::Watcher {
+                    _watcher: InboxWatcher::start(&inbox_path, tx)?,
+                })
+            }
+            kind => Ok(InboxSource::Poller {
+                _poller: InboxPoller::start(
+                    crate::store::open_kind(&self.dir, kind)?,
+                    Duration::from_secs(1),
+                    tx,
+                ),
+            }),
+        }
+    }
+
+    /// Announce inbox messages `sub` hasn't seen yet, one line each:
+    /// `<filename>\t<from>\t<subject>`. The inbox is only listed after
+    /// its watcher reports a change. Fails once the agent disconnects.
+    fn notify_subscriber(
+        &self,
+        sub: &mut InboxSubscriber,
+        logger: &mut crate::log::EventLogger,
+    ) -> Result<()> {
+        let changed = sub.changes.try_iter().count() > 0;
+        if !changed && !sub.retry {
+            return Ok(());
+        }
+        sub.retry = false;
+        let inbox = match self.store.list_inbox() {
+            Ok(inbox) => inbox,
+            Err(e) => {
+                eprintln!("Daemon: warning: failed to list inbox: {e:#}");
+                sub.retry = true;
+                return Ok(());
+            }
+        };
+        for filename in inbox {
+            if sub.seen.contains(&filename) {
                 continue;
             }
+            // A file that doesn't parse may still be mid-write; retry next tick
+            let Ok(msg) = self.store.read("inbox", &filename) else {
+                sub.retry = true;
+                continue;
+            };
+            sub.seen.insert(filename.clone());
+            sub.stream.send(&crate::socket::Response {
+                ok: true,
+                message: format!("{filename}\t{}\t{}", msg.from, msg.subject),
+            })?;
+            logger.log_event(&format!("inbox watcher notified: {filename}"))?;
+        }
+        Ok(())
+    }
 
-            std::thread::sleep(Duration::from_millis(100));
+    /// How a session ended, as recorded in `last_outcome`. None when no
+    /// agent ran because the plan was missing or the session was held.
+    fn session_outcome(&self, result: &Result<SessionLoopOutcome>) -> Option<SessionOutcome> {
+        Some(match result {
+            Ok(SessionLoopOutcome::PlanMissing | SessionLoopOutcome::Held { .. }) => return None,
+            Ok(SessionLoopOutcome::ValidationFailed { .. }) | Err(_)
+                if self.shutdown.load(Ordering::Relaxed) =>
+            {
+                SessionOutcome::Interrupted
+            }
+            Ok(SessionLoopOutcome::ValidationFailed { .. }) | Err(_) => SessionOutcome::Failed,
+            Ok(SessionLoopOutcome::Aborted { .. }) => SessionOutcome::Aborted,
+            Ok(_) => SessionOutcome::Success,
+        })
+    }
+
+    /// Record how the session that produced `result` ended in `last_outcome`.
+    /// A skipped session (plan.md missing) leaves the previous result alone.
+    fn record_outcome(&self, cryo_state: &mut CryoState, result: &Result<SessionLoopOutcome>) {
+        let Some(outcome) = self.session_outcome(result) else {
+            return;
+        };
+        // The focused plan's next task, for the next session on that plan
+        if let Some(name) = cryo_state.focus_plan.clone() {
+            let next_task = crate::log::parse_latest_session_next_task(&self.log_path)
+                .ok()
+                .flatten();
+            match next_task {
+                Some(task) => {
+                    cryo_state.plan_tasks.insert(name, task);
+                }
+                None if outcome == SessionOutcome::Success => {
+                    cryo_state
+                        .plan_tasks
+                        .entry(name.clone())
+                        .or_insert_with(|| crate::plans::continue_task(&name));
+                }
+                None => {}
+            }
         }
+        cryo_state.last_outcome = Some(outcome);
+        // Includes the agent's own edits, so only a human's later edit
+        // shows up as a change at the next wake
+        cryo_state.plan_hash = plan_hash(&self.dir);
+    }
+
+    /// Mark the focused `plans/` plan complete after `hibernate --complete`.
+    /// Returns how many plans are still open; 0 means the chamber is done,
+    /// and only then is the `--every` schedule dropped.
+    fn complete_focus_plan(&self, cryo_state: &mut CryoState) -> usize {
+        let Some(name) = cryo_state.focus_plan.clone() else {
+            cryo_state.schedule = None;
+            return 0;
+        };
+        if !cryo_state.completed_plans.contains(&name) {
+            cryo_state.completed_plans.push(name.clone());
+        }
+        let plans = crate::plans::list(&self.dir).unwrap_or_default();
+        let open = plans
+            .iter()
+            .filter(|p| !cryo_state.completed_plans.contains(p))
+            .count();
+        info!(
+            "Daemon: plan {} complete, {open} of {} plans left",
+            crate::plans::plan_file(&name),
+            plans.len()
+        );
+        if open == 0 {
+            cryo_state.schedule = None;
+        }
+        open
     }
 
     /// Execute a pending fallback if its deadline has passed.
@@ -796,8 +3475,8 @@ impl Daemon {
         if let Some((deadline, _)) = pending.as_ref() {
             if Local::now().naive_local() > *deadline {
                 let (_, fb) = pending.take().unwrap();
-                eprintln!("Daemon: fallback deadline passed, executing fallback action");
-                if let Err(e) = fb.execute(&self.dir, alert_method) {
+                info!("Daemon: fallback deadline passed, executing fallback action");
+                if let Err(e) = fb.execute(&self.dir, alert_method, &self.notifier) {
                     eprintln!("Daemon: fallback execution failed: {e}");
                 }
             }
@@ -805,42 +3484,213 @@ impl Daemon {
     }
 
     /// Handle a failure by retrying with exponential backoff (5s, 10s, ..., 1h cap).
-    /// Sends an alert once when max_retries is reached, then keeps retrying at 1h.
-    /// Returns true if the daemon should shut down.
-    fn handle_failure_retry(&self, retry: &mut RetryState, alert_method: &str) -> bool {
+    /// Sends an alert once when max_retries is reached; a transient failure then
+    /// keeps retrying, a logic failure gives up. Returns the [`RetryDecision`]:
+    /// retry now that the backoff has elapsed, shut down (requested while
+    /// backing off), or give up and wait for a message or `cryo wake`.
+    fn handle_failure_retry(
+        &self,
+        retry: &mut RetryState,
+        alert_method: &str,
+        kind: FailureKind,
+        server: &crate::socket::SocketServer,
+    ) -> RetryDecision {
         let backoff = retry.next_backoff();
         retry.record_failure();
+        if retry.gives_up(kind) {
+            eprintln!(
+                "Daemon: {} retries failed (agent exited without hibernating), sending alert. \
+                 Waiting for a message or `cryo wake` before trying again.",
+                retry.attempt
+            );
+            self.send_retry_alert(alert_method, kind);
+            return RetryDecision::GiveUp;
+        }
         // Send alert once when we first hit max_retries
         if retry.attempt == retry.max_retries {
             eprintln!(
                 "Daemon: {} retries failed, sending alert. Will keep retrying.",
                 retry.max_retries
             );
-            self.send_retry_alert(alert_method);
+            self.send_retry_alert(alert_method, kind);
+        }
+        info!("Daemon: retry {} in {}s", retry.attempt, backoff.as_secs());
+        if self.sleep_or_shutdown(backoff, server) {
+            RetryDecision::Shutdown
+        } else {
+            RetryDecision::Retry
         }
-        eprintln!("Daemon: retry {} in {}s", retry.attempt, backoff.as_secs());
-        self.sleep_or_shutdown(backoff)
     }
 
     /// Send a system alert when retries are exhausted.
-    fn send_retry_alert(&self, alert_method: &str) {
+    fn send_retry_alert(&self, alert_method: &str, kind: FailureKind) {
+        let message = match kind {
+            FailureKind::Transient => format!(
+                "Agent could not be started after multiple attempts. Daemon will keep retrying. Directory: {}",
+                self.dir.display()
+            ),
+            FailureKind::Logic => format!(
+                "Agent exited without hibernating after multiple attempts. Retries are paused until a message arrives or `cryo wake`. Directory: {}",
+                self.dir.display()
+            ),
+        };
         let fb = FallbackAction {
             action: "retry_exhausted".to_string(),
             target: "operator".to_string(),
+            message,
+        };
+        if let Err(e) = fb.execute(&self.dir, alert_method, &self.notifier) {
+            eprintln!("Daemon: retry alert failed: {e}");
+        }
+    }
+
+    /// Send a system alert when the agent command can't be run at all.
+    fn send_agent_command_alert(&self, alert_method: &str, reason: &str) {
+        let fb = FallbackAction {
+            action: "agent_command_invalid".to_string(),
+            target: "operator".to_string(),
             message: format!(
-                "Agent failed to hibernate after multiple attempts. Daemon will keep retrying. Directory: {}",
+                "{reason}. Sessions are paused until a message arrives or `cryo wake`; fix the command (e.g. `cryo restart --agent <cmd>`). Directory: {}",
                 self.dir.display()
             ),
         };
-        if let Err(e) = fb.execute(&self.dir, alert_method) {
-            eprintln!("Daemon: retry alert failed: {e}");
+        if let Err(e) = fb.execute(&self.dir, alert_method, &self.notifier) {
+            eprintln!("Daemon: agent command alert failed: {e}");
+        }
+    }
+
+    /// Record the plan's start on its first session, then check it against
+    /// `max_total_runtime_hours`. Returns why the plan must stop, if it must.
+    fn check_runtime_cap(&self, config: &CryoConfig, cryo_state: &mut CryoState) -> Option<String> {
+        let now = Local::now().naive_local();
+        cryo_state.mark_plan_started(now);
+        let cap = config.max_total_runtime_hours;
+        if !cryo_state.runtime_cap_reached(cap, now) {
+            return None;
+        }
+        let elapsed = cryo_state.plan_elapsed(now).unwrap_or_default();
+        Some(format!(
+            "plan has run for {}h, reaching max_total_runtime_hours = {cap}",
+            elapsed.num_hours()
+        ))
+    }
+
+    /// Send a system alert when the plan hits `max_total_runtime_hours`.
+    fn send_runtime_cap_alert(&self, alert_method: &str, reason: &str) {
+        let fb = FallbackAction {
+            action: "runtime_cap".to_string(),
+            target: "operator".to_string(),
+            message: format!(
+                "The {reason}; the daemon stopped before finishing the plan. Raise the cap and `cryo start` to continue. Directory: {}",
+                self.dir.display()
+            ),
+        };
+        if let Err(e) = fb.execute(&self.dir, alert_method, &self.notifier) {
+            eprintln!("Daemon: runtime cap alert failed: {e}");
+        }
+    }
+
+    /// Send a system alert when plan.md disappears from under the daemon.
+    fn send_plan_missing_alert(&self, alert_method: &str) {
+        let fb = FallbackAction {
+            action: "plan_missing".to_string(),
+            target: "operator".to_string(),
+            message: format!(
+                "plan.md is missing or empty; sessions are paused until it is restored. Directory: {}",
+                self.dir.display()
+            ),
+        };
+        if let Err(e) = fb.execute(&self.dir, alert_method, &self.notifier) {
+            eprintln!("Daemon: plan missing alert failed: {e}");
         }
     }
 
-    fn get_task(&self) -> Option<String> {
-        crate::log::parse_latest_session_task(&self.log_path)
+    /// Resolve `watch_dirs` against the project directory.
+    fn drop_dirs(&self, config: &CryoConfig) -> Vec<PathBuf> {
+        config.watch_dirs.iter().map(|d| self.dir.join(d)).collect()
+    }
+
+    /// Import files from every drop directory into the inbox.
+    /// Returns how many messages were imported.
+    fn import_drop_dirs(&self, drop_dirs: &[PathBuf]) -> usize {
+        let deliver = |msg: &crate::message::Message| self.store.write("inbox", msg).map(|_| ());
+        drop_dirs
+            .iter()
+            .map(|d| match crate::message::import_drop_dir_with(d, deliver) {
+                Ok(n) => n,
+                Err(e) => {
+                    eprintln!("Daemon: failed to import from {}: {e}", d.display());
+                    0
+                }
+            })
+            .sum()
+    }
+
+    /// Task for the coming session: the one the agent set with `next-task`,
+    /// else the first task until a session has succeeded, else the previous
+    /// session's task. With `plans/` all of this is tracked per plan.
+    fn get_task(&self, config: &CryoConfig, cryo_state: &CryoState) -> String {
+        if let Some(name) = &cryo_state.focus_plan {
+            return cryo_state
+                .plan_tasks
+                .get(name)
+                .cloned()
+                .unwrap_or_else(|| crate::plans::first_task(name));
+        }
+        let first_task = cryo_state
+            .first_task_override
+            .clone()
+            .unwrap_or_else(|| config.first_task.clone());
+        // cryo.log outlives `cryo start` and `--fresh`, so an earlier run's
+        // tasks must not leak into the first session of this one
+        if cryo_state.session_number <= 1 {
+            return first_task;
+        }
+        if let Ok(Some(task)) = crate::log::parse_latest_session_next_task(&self.log_path) {
+            return task;
+        }
+        match crate::log::parse_latest_session_task(&self.log_path)
             .ok()
             .flatten()
+        {
+            None => first_task,
+            // The first task only kicks the plan off; once a session got
+            // through it, later ones continue instead of starting over
+            Some(task)
+                if task == first_task
+                    && cryo_state.last_outcome == Some(SessionOutcome::Success) =>
+            {
+                CONTINUE_TASK.to_string()
+            }
+            Some(task) => task,
+        }
+    }
+
+    /// Register an OS timer for `wake_time` so the schedule survives a reboot.
+    /// Skipped when the daemon itself is installed as a service, which already
+    /// restarts it at boot. Failures are only warnings: the in-memory schedule
+    /// still works while the daemon stays up.
+    fn register_wake_backstop(&self, wake_time: NaiveDateTime) {
+        if crate::service::is_installed("daemon", &self.dir) {
+            return;
+        }
+        let result = std::env::current_exe()
+            .context("Failed to resolve cryo executable path")
+            .and_then(|exe| {
+                let log_path = crate::log::log_path(&self.dir);
+                crate::service::install_wake_timer(&self.dir, &exe, wake_time, &log_path)
+            });
+        match result {
+            Ok(()) if crate::service::verify_wake_timer(&self.dir) => {
+                info!("Daemon: OS wake timer registered as reboot backstop");
+            }
+            Ok(()) => {
+                eprintln!("Daemon: warning: OS wake timer registered but not active");
+            }
+            Err(e) => {
+                eprintln!("Daemon: warning: could not register OS wake timer: {e}");
+            }
+        }
     }
 
     /// Generate and send the periodic activity report.
@@ -852,20 +3702,47 @@ impl Daemon {
     ) {
         let since =
             chrono::Utc::now().naive_utc() - chrono::Duration::hours(config.report_interval as i64);
-        match crate::report::generate_report(&self.log_path, since) {
+        let format = crate::log::LogFormat::from_config(config);
+        match crate::report::generate_report(&self.log_path, since, &format) {
             Ok(summary) => {
-                let project_name = self
-                    .dir
-                    .file_name()
-                    .and_then(|n| n.to_str())
-                    .unwrap_or("unknown");
-                if let Err(e) = crate::report::send_report_notification(&summary, project_name) {
-                    eprintln!("Daemon: report notification failed: {e}");
+                let project_name = config.project_name(&self.dir);
+                // Same header as gh/zulip pushes, counting this report's window
+                let header = config.summary_template.as_deref().and_then(|template| {
+                    let since = Local::now().naive_local()
+                        - chrono::Duration::hours(config.report_interval as i64);
+                    match crate::report::SessionDigest::collect(
+                        &self.dir,
+                        cryo_state.session_number,
+                        Some(since),
+                    ) {
+                        Ok(digest) => Some(digest.render_template(template)),
+                        Err(e) => {
+                            eprintln!("Daemon: failed to render summary_template: {e}");
+                            None
+                        }
+                    }
+                });
+                match crate::report::send_report_notification(
+                    &summary,
+                    header.as_deref(),
+                    &project_name,
+                    &self.notifier,
+                    self.store.as_ref(),
+                ) {
+                    Ok(Some(warning)) => eprintln!("Daemon: warning: {warning}"),
+                    Ok(None) => {}
+                    Err(e) => eprintln!("Daemon: report notification failed: {e}"),
                 }
-                eprintln!(
+                info!(
                     "Daemon: report sent ({} sessions, {} failed)",
                     summary.total_sessions, summary.failed_sessions
                 );
+                if let Some(providers) = summary.provider_line() {
+                    info!("Daemon: report providers: {providers}");
+                }
+                if let Some(usage) = summary.usage_line() {
+                    info!("Daemon: report usage: {usage}");
+                }
             }
             Err(e) => {
                 eprintln!("Daemon: report generation failed: {e}");
@@ -887,16 +3764,17 @@ impl Daemon {
             Some(now),
         );
         if let Some(next) = next_report_time {
-            eprintln!("Daemon: next report at {}", next.format("%Y-%m-%d %H:%M"));
+            info!("Daemon: next report at {}", next.format("%Y-%m-%d %H:%M"));
         }
     }
 
     /// Sleep for `duration`, but return early if shutdown is signaled.
     /// Returns true if shutdown was requested.
-    fn sleep_or_shutdown(&self, duration: Duration) -> bool {
+    fn sleep_or_shutdown(&self, duration: Duration, server: &crate::socket::SocketServer) -> bool {
         let step = Duration::from_millis(250);
         let mut remaining = duration;
         while remaining > Duration::ZERO {
+            self.serve_between_sessions(server);
             if self.shutdown.load(Ordering::Relaxed) {
                 return true;
             }
@@ -912,6 +3790,130 @@ impl Daemon {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_plan_is_present() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(!plan_is_present(dir.path()));
+        std::fs::write(dir.path().join("plan.md"), "  \n").unwrap();
+        assert!(!plan_is_present(dir.path()));
+        std::fs::write(dir.path().join("plan.md"), "# Plan").unwrap();
+        assert!(plan_is_present(dir.path()));
+    }
+
+    #[test]
+    fn test_timed_wake_sources() {
+        let now = NaiveDateTime::parse_from_str("2026-03-06T10:00", WAKE_TIME_FMT).unwrap();
+        let monday_nine = NaiveDateTime::parse_from_str("2026-03-09T09:00", WAKE_TIME_FMT).unwrap();
+        let weekdays = Some("0 9 * * 1-5");
+
+        // --wake wins over any schedule
+        let wake = timed_wake(Some("2026-03-07T12:00"), None, weekdays, weekdays, now).unwrap();
+        assert_eq!(wake.format(WAKE_TIME_FMT).to_string(), "2026-03-07T12:00");
+        // --wake-in is relative to now and beats any schedule
+        let wake = timed_wake(None, Some("1h30m"), weekdays, weekdays, now).unwrap();
+        assert_eq!(wake.format(WAKE_TIME_FMT).to_string(), "2026-03-06T11:30");
+        // --every, then the stored schedule
+        assert_eq!(
+            timed_wake(None, None, weekdays, None, now).unwrap(),
+            monday_nine
+        );
+        assert_eq!(
+            timed_wake(None, None, None, weekdays, now).unwrap(),
+            monday_nine
+        );
+
+        assert!(timed_wake(None, None, None, None, now).is_err());
+        assert!(timed_wake(Some("tomorrow"), None, None, None, now).is_err());
+        assert!(timed_wake(None, Some("soon"), None, None, now).is_err());
+        assert!(timed_wake(None, Some("1000000000d"), None, None, now).is_err());
+        let end_of_time = NaiveDateTime::MAX - chrono::Duration::hours(1);
+        assert!(timed_wake(None, Some("2h"), None, None, end_of_time).is_err());
+        assert!(timed_wake(None, None, Some("0 0 31 2 *"), None, now).is_err());
+    }
+
+    #[test]
+    fn test_describe_env() {
+        let env = std::collections::HashMap::from([
+            ("B_KEY".to_string(), "b".to_string()),
+            ("A_KEY".to_string(), "a".to_string()),
+        ]);
+        assert_eq!(
+            describe_env(Some("anthropic"), &env, false),
+            "provider: anthropic\nA_KEY=***\nB_KEY=***"
+        );
+        assert_eq!(
+            describe_env(Some("anthropic"), &env, true),
+            "provider: anthropic\nA_KEY=a\nB_KEY=b"
+        );
+        assert_eq!(
+            describe_env(None, &Default::default(), false),
+            "provider: (none)"
+        );
+    }
+
+    #[test]
+    fn test_clean_exit_wake() {
+        let now = NaiveDateTime::parse_from_str("2026-03-06T10:00", WAKE_TIME_FMT).unwrap();
+        let config = CryoConfig {
+            clean_exit_wake_in: "30m".to_string(),
+            ..Default::default()
+        };
+        let wake = clean_exit_wake(&config, None, now).unwrap();
+        assert_eq!(wake.format(WAKE_TIME_FMT).to_string(), "2026-03-06T10:30");
+        // A stored schedule wins over the interval
+        let wake = clean_exit_wake(&config, Some("0 9 * * 1-5"), now).unwrap();
+        assert_eq!(wake.format(WAKE_TIME_FMT).to_string(), "2026-03-09T09:00");
+    }
+
+    #[test]
+    fn test_session_timeout_precedence() {
+        let config = CryoConfig {
+            max_session_duration: 3600,
+            ..Default::default()
+        };
+        let provider = crate::config::ProviderConfig {
+            name: "slow".to_string(),
+            env: Default::default(),
+            env_file: None,
+            max_session_duration: Some(7200),
+        };
+        let mut cryo_state = state::CryoState::default();
+        assert_eq!(session_timeout_secs(&config, &cryo_state, None), 3600);
+        assert_eq!(
+            session_timeout_secs(&config, &cryo_state, Some(&provider)),
+            7200
+        );
+        // An explicit CLI override (already merged into config) wins
+        cryo_state.max_session_duration_override = Some(3600);
+        assert_eq!(
+            session_timeout_secs(&config, &cryo_state, Some(&provider)),
+            3600
+        );
+    }
+
+    #[test]
+    fn test_defer_stops_at_hard_cap() {
+        let mut config = CryoConfig::default();
+        assert_eq!(defer_cap_secs(&config, 600), 1200);
+        config.max_session_duration_hard_cap = 900;
+        assert_eq!(defer_cap_secs(&config, 600), 900);
+        // A cap below the timeout itself never shortens the session
+        assert_eq!(defer_cap_secs(&config, 1800), 1800);
+
+        let start = std::time::Instant::now();
+        let deadline = start + Duration::from_secs(600);
+        let by = Duration::from_secs(120);
+        assert_eq!(
+            deferred_deadline(deadline, start, by, 900),
+            Some(deadline + by)
+        );
+        assert_eq!(
+            deferred_deadline(deadline, start, Duration::from_secs(600), 900),
+            Some(start + Duration::from_secs(900))
+        );
+        assert_eq!(deferred_deadline(deadline, start, by, 600), None);
+    }
+
     #[test]
     fn test_backoff_sequence() {
         let mut state = RetryState::new(5, 1);
@@ -937,6 +3939,46 @@ mod tests {
         assert!(state.exhausted());
     }
 
+    #[test]
+    fn test_backoff_jitter_bounds() {
+        let backoff = Duration::from_secs(20);
+        assert_eq!(apply_jitter(backoff, RetryJitter::None, 12345), backoff);
+        for random in [0, 1, 19_999, 20_000, 20_001, u64::MAX] {
+            let full = apply_jitter(backoff, RetryJitter::Full, random);
+            assert!(full <= backoff, "{full:?}");
+            let equal = apply_jitter(backoff, RetryJitter::Equal, random);
+            assert!(equal >= backoff / 2 && equal <= backoff, "{equal:?}");
+        }
+
+        for jitter in [RetryJitter::Full, RetryJitter::Equal] {
+            let mut state = RetryState::new(20, 1).with_jitter(jitter, 42);
+            let mut seen = std::collections::HashSet::new();
+            for _ in 0..15 {
+                let secs = 5u64.checked_shl(state.attempt).unwrap_or(3600).min(3600);
+                let cap = Duration::from_secs(secs);
+                let floor = match jitter {
+                    RetryJitter::Equal => cap / 2,
+                    _ => Duration::ZERO,
+                };
+                let backoff = state.next_backoff();
+                assert!(
+                    backoff >= floor && backoff <= cap,
+                    "{jitter:?}: {backoff:?}"
+                );
+                seen.insert(backoff);
+                state.record_failure();
+            }
+            assert!(seen.len() > 1, "{jitter:?} should vary");
+        }
+        // Different seeds give different sequences
+        let a = RetryState::new(5, 1).with_jitter(RetryJitter::Full, 1);
+        let b = RetryState::new(5, 1).with_jitter(RetryJitter::Full, 2);
+        assert_ne!(
+            (0..4).map(|_| a.next_backoff()).collect::<Vec<_>>(),
+            (0..4).map(|_| b.next_backoff()).collect::<Vec<_>>()
+        );
+    }
+
     #[test]
     fn test_backoff_caps_at_one_hour() {
         let mut state = RetryState::new(20, 1);
@@ -1013,6 +4055,85 @@ mod tests {
         assert_eq!(retry.provider_index, 0);
     }
 
+    #[test]
+    fn test_take_provider_rotation() {
+        let mut st = CryoState {
+            provider_index: Some(2),
+            provider_rotation: Some("rate limited".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            take_provider_rotation(&mut st, 3),
+            Some((0, "rate limited".to_string()))
+        );
+        assert_eq!(st.provider_rotation, None);
+        assert_eq!(take_provider_rotation(&mut st, 3), None);
+
+        // Nothing to rotate to with one provider; the request is dropped
+        st.provider_rotation = Some("slow".to_string());
+        assert_eq!(take_provider_rotation(&mut st, 1), None);
+        assert_eq!(st.provider_rotation, None);
+    }
+
+    #[test]
+    fn test_append_summary() {
+        let summary = append_summary(None, "Fixed the parser");
+        assert_eq!(summary, "Fixed the parser");
+        let summary = append_summary(Some(summary), "Added tests");
+        assert_eq!(summary, "Fixed the parser; Added tests");
+        // Appending after an empty set-summary starts over
+        assert_eq!(append_summary(Some(" ".to_string()), "Ran CI"), "Ran CI");
+    }
+
+    #[test]
+    fn test_detect_completion_needs_marker_line() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = dir.path().join("cryo-agent.log");
+        let config = CryoConfig {
+            complete_marker: crate::config::CompleteMarker::Output,
+            ..Default::default()
+        };
+        let output = crate::log::SessionOutput::start(&log);
+        std::fs::write(&log, "Print [CRYO:PLAN-COMPLETE] once all tasks are done\n").unwrap();
+        assert_eq!(detect_completion(&config, dir.path(), &output), None);
+        std::fs::write(&log, "All done.\n  [CRYO:PLAN-COMPLETE]  \n").unwrap();
+        assert_eq!(
+            detect_completion(&config, dir.path(), &output),
+            Some("output marker")
+        );
+    }
+
+    #[test]
+    fn test_session_hold() {
+        let at = |s: &str| NaiveDateTime::parse_from_str(s, WAKE_TIME_FMT).unwrap();
+        let mut config = CryoConfig {
+            quiet_hours: Some("22:00-07:00".to_string()),
+            max_sessions_per_day: 2,
+            ..Default::default()
+        };
+        let mut st = CryoState::default();
+        assert_eq!(session_hold(&config, &st, at("2026-03-01T12:00")), None);
+        let (until, why) = session_hold(&config, &st, at("2026-03-01T23:30")).unwrap();
+        assert_eq!(until, at("2026-03-02T07:00"));
+        assert_eq!(why, "quiet hours (22:00-07:00)");
+
+        // The daily cap holds until midnight; yesterday's sessions don't count
+        st.count_session(at("2026-03-01T09:00").date());
+        st.count_session(at("2026-03-01T10:00").date());
+        let (until, why) = session_hold(&config, &st, at("2026-03-01T12:00")).unwrap();
+        assert_eq!(until, at("2026-03-02T00:00"));
+        assert_eq!(why, "max_sessions_per_day (2) reached");
+        assert_eq!(
+            session_hold(&config, &st, at("2026-03-02T12:00")),
+            None,
+            "a new day starts a new count"
+        );
+
+        config.max_sessions_per_day = 0;
+        config.quiet_hours = None;
+        assert_eq!(session_hold(&config, &st, at("2026-03-01T23:30")), None);
+    }
+
     #[test]
     fn test_reset_clears_attempt_and_provider() {
         let mut retry = RetryState::new(5, 3);
@@ -1063,6 +4184,45 @@ mod tests {
         assert_eq!(event.unwrap(), DaemonEvent::InboxChanged);
     }
 
+    #[test]
+    fn test_inbox_watcher_detects_file_renamed_in() {
+        let dir = tempfile::tempdir().unwrap();
+        let inbox = dir.path().join("messages").join("inbox");
+        std::fs::create_dir_all(&inbox).unwrap();
+        let staged = dir.path().join("staged.md");
+        std::fs::write(&staged, "hello").unwrap();
+
+        let (tx, rx) = mpsc::channel();
+        let _watcher = InboxWatcher::start(&inbox, tx).unwrap();
+
+        std::fs::rename(&staged, inbox.join("test-message.md")).unwrap();
+
+        let event = rx.recv_timeout(Duration::from_secs(2));
+        assert_eq!(event.unwrap(), DaemonEvent::InboxChanged);
+    }
+
+    #[test]
+    fn test_inbox_watcher_ignores_flag_disposition() {
+        let dir = tempfile::tempdir().unwrap();
+        let inbox = dir.path().join("messages").join("inbox");
+        std::fs::create_dir_all(&inbox).unwrap();
+        std::fs::write(inbox.join("handled.md"), "hello").unwrap();
+
+        let (tx, rx) = mpsc::channel();
+        let _watcher = InboxWatcher::start(&inbox, tx).unwrap();
+
+        crate::message::dispose_messages(
+            dir.path(),
+            &["handled.md".to_string()],
+            crate::config::InboxDisposition::Flag,
+        )
+        .unwrap();
+        assert!(inbox.join("handled.md.consumed").exists());
+
+        let event = rx.recv_timeout(Duration::from_secs(1));
+        assert!(event.is_err(), "Marking a message consumed must not wake");
+    }
+
     #[test]
     fn test_inbox_watcher_ignores_non_create_events() {
         let dir = tempfile::tempdir().unwrap();
@@ -1114,6 +4274,22 @@ mod tests {
         assert_eq!(timeout, Duration::from_secs(120));
     }
 
+    #[test]
+    fn test_session_gap_remaining() {
+        let gap = Duration::from_secs(30);
+        let end = std::time::Instant::now();
+        assert_eq!(session_gap_remaining(None, gap, end), None);
+        assert_eq!(
+            session_gap_remaining(Some(end), gap, end + Duration::from_secs(10)),
+            Some(Duration::from_secs(20))
+        );
+        assert_eq!(
+            session_gap_remaining(Some(end), gap, end + Duration::from_secs(30)),
+            None
+        );
+        assert_eq!(session_gap_remaining(Some(end), Duration::ZERO, end), None);
+    }
+
     #[test]
     fn test_compute_sleep_timeout_report_only() {
         let now = chrono::NaiveDate::from_ymd_opt(2026, 3, 1)
@@ -1160,6 +4336,47 @@ mod tests {
         assert_eq!(result.unwrap(), "6m");
     }
 
+    #[test]
+    fn test_suspended_for() {
+        let secs = |s| Duration::from_secs(s);
+        // Both clocks agree: no suspend, even with some drift
+        assert!(suspended_for(chrono::Duration::seconds(600), secs(600)).is_none());
+        assert!(suspended_for(chrono::Duration::seconds(630), secs(600)).is_none());
+        // 8 hours of wall time in 10 monotonic minutes
+        assert_eq!(
+            suspended_for(chrono::Duration::hours(8), secs(600)),
+            Some(chrono::Duration::hours(8) - chrono::Duration::minutes(10))
+        );
+    }
+
+    #[test]
+    fn test_wake_after_suspend() {
+        let at = |s| NaiveDateTime::parse_from_str(s, WAKE_TIME_FMT).unwrap();
+        let now = at("2026-03-09T17:00");
+        let weekdays_at_nine = Some("0 9 * * 1-5");
+
+        // A missed schedule slot moves to the next one
+        assert_eq!(
+            wake_after_suspend(Some(at("2026-03-09T09:00")), weekdays_at_nine, now),
+            Some(at("2026-03-10T09:00"))
+        );
+        // A one-off wake still runs (late), schedule or not
+        assert_eq!(
+            wake_after_suspend(Some(at("2026-03-09T10:30")), weekdays_at_nine, now),
+            Some(at("2026-03-09T10:30"))
+        );
+        assert_eq!(
+            wake_after_suspend(Some(at("2026-03-09T09:00")), None, now),
+            Some(at("2026-03-09T09:00"))
+        );
+        // Nothing missed: unchanged
+        assert_eq!(
+            wake_after_suspend(Some(at("2026-03-09T18:00")), weekdays_at_nine, now),
+            Some(at("2026-03-09T18:00"))
+        );
+        assert_eq!(wake_after_suspend(None, weekdays_at_nine, now), None);
+    }
+
     fn make_state(next_wake: Option<&str>) -> state::CryoState {
         state::CryoState {
             session_number: 1,
@@ -1171,6 +4388,7 @@ mod tests {
             max_session_duration_override: None,
             last_report_time: None,
             provider_index: None,
+            ..Default::default()
         }
     }
 
@@ -1230,6 +4448,69 @@ mod tests {
         assert!(run_now, "Exactly at wake time → run now");
     }
 
+    #[test]
+    fn test_coalesce_queued_events() {
+        let (tx, rx) = mpsc::channel();
+        for _ in 0..5 {
+            tx.send(DaemonEvent::InboxChanged).unwrap();
+        }
+        tx.send(DaemonEvent::WakeRequested).unwrap();
+        tx.send(DaemonEvent::DropDirChanged).unwrap();
+        tx.send(DaemonEvent::DropDirChanged).unwrap();
+
+        assert_eq!(coalesce_queued_events(&rx, &tx), 6);
+        let kept: Vec<_> = rx.try_iter().collect();
+        assert_eq!(
+            kept,
+            vec![DaemonEvent::WakeRequested, DaemonEvent::DropDirChanged]
+        );
+
+        // Inbox changes alone stay inbox changes
+        tx.send(DaemonEvent::InboxChanged).unwrap();
+        tx.send(DaemonEvent::InboxChanged).unwrap();
+        assert_eq!(coalesce_queued_events(&rx, &tx), 1);
+        assert_eq!(rx.try_recv().unwrap(), DaemonEvent::InboxChanged);
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_restore_wake_state_awaiting_message() {
+        let mut state = make_state(None);
+        state.awaiting_message = true;
+        let now = NaiveDateTime::parse_from_str("2026-03-01T10:00", WAKE_TIME_FMT).unwrap();
+        let (next_wake, run_now) = restore_wake_state(&state, now);
+        assert!(next_wake.is_none());
+        assert!(!run_now, "awaiting a message should not run immediately");
+    }
+
+    #[test]
+    fn test_restore_message_fallback() {
+        let now = NaiveDateTime::parse_from_str("2026-03-01T10:00", WAKE_TIME_FMT).unwrap();
+        let mut state = make_state(None);
+        assert!(restore_message_fallback(&state, now).is_none());
+
+        state.awaiting_message = true;
+        let (deadline, fb) = restore_message_fallback(&state, now).unwrap();
+        assert_eq!(
+            deadline,
+            now + chrono::Duration::hours(UNTIL_MESSAGE_FALLBACK_HOURS)
+        );
+        assert_eq!(fb.target, "operator");
+
+        state.message_deadline = Some("2026-03-02T09:00".to_string());
+        state.message_fallback = Some(FallbackAction {
+            action: "email".to_string(),
+            target: "ops@example.com".to_string(),
+            message: "no reply yet".to_string(),
+        });
+        let (deadline, fb) = restore_message_fallback(&state, now).unwrap();
+        assert_eq!(
+            deadline.format(WAKE_TIME_FMT).to_string(),
+            "2026-03-02T09:00"
+        );
+        assert_eq!(fb.message, "no reply yet");
+    }
+
     #[test]
     fn test_restore_wake_state_invalid_format() {
         let now = chrono::NaiveDate::from_ymd_opt(2026, 3, 1)
@@ -1,13 +1,15 @@
 // src/fallback.rs
 use anyhow::Result;
 use chrono::Local;
+use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 use std::fmt;
 use std::path::Path;
 
-use crate::message::{self, Message};
+use crate::message::Message;
+use crate::notifier::Notifier;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FallbackAction {
     pub action: String,
     pub target: String,
@@ -36,13 +38,17 @@ impl FallbackAction {
     /// - `"notify"`: desktop notification + outbox file
     /// - `"outbox"`: outbox file only (no popup)
     /// - `"none"`: disable fallback alerts entirely
-    pub fn execute(&self, work_dir: &Path, alert_method: &str) -> Result<()> {
+    ///
+    /// Without a working `notifier`, `"notify"` behaves like `"outbox"`
+    /// and the notifier's warning is printed once.
+    pub fn execute(&self, work_dir: &Path, alert_method: &str, notifier: &Notifier) -> Result<()> {
         if alert_method == "none" {
             eprintln!("Fallback: alert suppressed (fallback_alert = \"none\")");
             return Ok(());
         }
 
-        message::ensure_dirs(work_dir)?;
+        let store = crate::store::open(work_dir)?;
+        store.ensure()?;
 
         let msg = Message {
             from: "cryochamber".to_string(),
@@ -53,25 +59,23 @@ impl FallbackAction {
                 ("fallback_action".to_string(), self.action.clone()),
                 ("fallback_target".to_string(), self.target.clone()),
             ]),
+            attachments: Vec::new(),
         };
 
-        let path = message::write_message(work_dir, "outbox", &msg)?;
-        println!(
-            "Fallback alert written to {}",
-            path.strip_prefix(work_dir).unwrap_or(&path).display()
-        );
+        let id = store.write("outbox", &msg)?;
+        println!("Fallback alert written to outbox: {id}");
 
-        if alert_method == "notify" {
-            if let Err(e) = self.send_notification() {
-                eprintln!("Fallback: desktop notification failed: {e}");
+        if alert_method == "notify" && !notifier.show(&self.notification(), "alert") {
+            if let Some(warning) = notifier.take_warning() {
+                eprintln!("Fallback: {warning}");
             }
         }
 
         Ok(())
     }
 
-    /// Send a desktop notification via notify-rust.
-    fn send_notification(&self) -> Result<()> {
+    /// The desktop notification for this alert.
+    fn notification(&self) -> notify_rust::Notification {
         let mut notification = notify_rust::Notification::new();
         notification
             .summary(&format!("Cryochamber Alert: {}", self.action))
@@ -87,7 +91,6 @@ impl FallbackAction {
             notification.subtitle("Dead-man switch fired");
             notification.sound_name("Sosumi");
         }
-        notification.show()?;
-        Ok(())
+        notification
     }
 }
@@ -1,4 +1,5 @@
 use anyhow::{Context, Result};
+use chrono::NaiveDateTime;
 use serde::{Deserialize, Serialize};
 use std::path::Path;
 
@@ -21,6 +22,12 @@ pub struct GhSyncState {
     /// Last session number that was pushed (to prevent duplicate posts)
     #[serde(default)]
     pub last_pushed_session: Option<u32>,
+    /// Local time of the last push (message counts in the next push start here)
+    #[serde(default)]
+    pub last_pushed_at: Option<NaiveDateTime>,
+    /// Discussion category chosen with `cryo-gh init --category`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
 }
 
 impl GhSyncState {
@@ -32,6 +39,21 @@ impl GhSyncState {
     }
 }
 
+impl crate::channel::PushTracker for GhSyncState {
+    fn last_pushed_session(&self) -> Option<u32> {
+        self.last_pushed_session
+    }
+
+    fn last_pushed_at(&self) -> Option<NaiveDateTime> {
+        self.last_pushed_at
+    }
+
+    fn record_push(&mut self, session: u32, at: NaiveDateTime) {
+        self.last_pushed_session = Some(session);
+        self.last_pushed_at = Some(at);
+    }
+}
+
 pub fn save_sync_state(path: &Path, state: &GhSyncState) -> Result<()> {
     let json = serde_json::to_string_pretty(state)?;
     std::fs::write(path, json)?;
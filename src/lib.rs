@@ -6,21 +6,69 @@ pub mod fallback;
 pub mod gh_sync;
 pub mod log;
 pub mod message;
+pub mod notifier;
+pub mod plan;
+pub mod plans;
 pub mod process;
 pub mod protocol;
+pub mod recording;
+pub mod redact;
 pub mod registry;
 pub mod report;
+pub mod review;
+pub mod schedule;
 pub mod service;
 pub mod session;
 pub mod socket;
 pub mod state;
+pub mod store;
 pub mod todo;
+pub mod usage;
 pub mod web;
+pub mod webhook;
 pub mod zulip_sync;
 
+pub use daemon::{Daemon, DaemonHandle};
+
 pub fn work_dir() -> anyhow::Result<std::path::PathBuf> {
     let dir = std::env::current_dir().context("Failed to get current directory")?;
     dir.canonicalize().or_else(|_| Ok(dir))
 }
 
+/// Find the nearest ancestor of `start` (including itself) that holds a
+/// cryochamber project — a `.cryo/` directory or `cryo.toml` — the way git
+/// finds `.git`. Returns `None` if the filesystem root is reached.
+///
+/// The walk stops at `$HOME`, whose `~/.cryo/` is the daemon registry, not
+/// a project: home itself only counts with a `cryo.toml` or `plan.md`.
+pub fn find_project_dir(start: &std::path::Path) -> Option<std::path::PathBuf> {
+    let home = std::env::var_os("HOME").map(std::path::PathBuf::from);
+    for d in start.ancestors() {
+        if home.as_deref() == Some(d) {
+            let is_project = d.join("cryo.toml").is_file() || d.join("plan.md").is_file();
+            return is_project.then(|| d.to_path_buf());
+        }
+        if d.join(".cryo").is_dir() || d.join("cryo.toml").is_file() {
+            return Some(d.to_path_buf());
+        }
+    }
+    None
+}
+
+/// Project root for `cryo-agent`, so it works from any subdirectory of the
+/// project. Falls back to the current directory when no project is found.
+pub fn project_dir() -> anyhow::Result<std::path::PathBuf> {
+    let dir = work_dir()?;
+    Ok(find_project_dir(&dir).unwrap_or(dir))
+}
+
+/// 64-bit FNV-1a hash of `bytes`. Unlike `DefaultHasher` it is the same
+/// across builds and Rust versions, for hashes that are persisted or used
+/// in paths.
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325u64, |h, b| {
+        (h ^ u64::from(*b)).wrapping_mul(0x100000001b3)
+    })
+}
+
 use anyhow::Context;
@@ -1,10 +1,14 @@
 // src/log.rs
 use anyhow::Result;
-use chrono::NaiveDateTime;
+use chrono::{DateTime, Duration, FixedOffset, Local, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
 use std::fs;
 use std::io::Write;
 use std::path::{Path, PathBuf};
 
+use crate::config::{CryoConfig, HistoryMode, LogTimezone};
+use crate::redact::Redactor;
+
 pub fn log_path(dir: &Path) -> PathBuf {
     dir.join("cryo.log")
 }
@@ -13,9 +17,87 @@ pub fn agent_log_path(dir: &Path) -> PathBuf {
     dir.join("cryo-agent.log")
 }
 
+/// Generation `n` of a rotated log: `cryo-agent.log.1.gz` is the newest.
+pub fn rotated_log_path(path: &Path, n: u32) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".{n}.gz"));
+    path.with_file_name(name)
+}
+
+/// Rotate `path` once it is larger than `max_bytes`: older generations
+/// shift up (`.1.gz` -> `.2.gz`), anything past `keep` is dropped, and the
+/// current file is gzipped to `.1.gz` and removed. With `keep = 0` it is
+/// removed without a copy. Returns the size rotated out, if any.
+/// `max_bytes = 0` never rotates.
+pub fn rotate_log(path: &Path, max_bytes: u64, keep: u32) -> Result<Option<u64>> {
+    use anyhow::Context;
+
+    let size = match fs::metadata(path) {
+        Ok(m) => m.len(),
+        Err(_) => return Ok(None),
+    };
+    if max_bytes == 0 || size <= max_bytes {
+        return Ok(None);
+    }
+    if keep > 0 {
+        let _ = fs::remove_file(rotated_log_path(path, keep));
+        for n in (1..keep).rev() {
+            let from = rotated_log_path(path, n);
+            if from.exists() {
+                fs::rename(&from, rotated_log_path(path, n + 1))
+                    .with_context(|| format!("Failed to rotate {}", from.display()))?;
+            }
+        }
+        let dest = rotated_log_path(path, 1);
+        let mut input = fs::File::open(path)?;
+        let mut encoder = flate2::write::GzEncoder::new(
+            fs::File::create(&dest)
+                .with_context(|| format!("Failed to create {}", dest.display()))?,
+            flate2::Compression::default(),
+        );
+        std::io::copy(&mut input, &mut encoder)?;
+        encoder.finish()?;
+    }
+    fs::remove_file(path).with_context(|| format!("Failed to remove {}", path.display()))?;
+    Ok(Some(size))
+}
+
+/// What an agent appended to `cryo-agent.log` during one session.
+pub struct SessionOutput {
+    path: PathBuf,
+    start: u64,
+}
+
+impl SessionOutput {
+    /// Mark the current end of `path`; [`SessionOutput::read`] returns what
+    /// is written after it.
+    pub fn start(path: &Path) -> Self {
+        let start = fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+        Self {
+            path: path.to_path_buf(),
+            start,
+        }
+    }
+
+    /// Output written since [`SessionOutput::start`] (empty if the log is
+    /// unreadable).
+    pub fn read(&self) -> String {
+        let mut bytes = Vec::new();
+        let _ = fs::File::open(&self.path).and_then(|mut f| {
+            use std::io::{Read, Seek, SeekFrom};
+            f.seek(SeekFrom::Start(self.start))?;
+            f.read_to_end(&mut bytes)
+        });
+        String::from_utf8_lossy(&bytes).into_owned()
+    }
+}
+
 pub const SESSION_START: &str = "--- CRYO SESSION";
 pub const SESSION_END: &str = "--- CRYO END ---";
 
+/// Most of cryo.log that `history_mode = "full"` puts into the prompt.
+pub const FULL_HISTORY_MAX_BYTES: usize = 64 * 1024;
+
 pub fn read_latest_session(log_path: &Path) -> Result<Option<String>> {
     if !log_path.exists() {
         return Ok(None);
@@ -38,6 +120,56 @@ pub fn read_latest_session(log_path: &Path) -> Result<Option<String>> {
     }
 }
 
+/// The slice of cryo.log that `history_mode` puts into the prompt, or `None`
+/// when the mode is `none` or there is no history yet.
+pub fn read_history(log_path: &Path, mode: HistoryMode) -> Result<Option<String>> {
+    match mode {
+        HistoryMode::None => Ok(None),
+        HistoryMode::LastSession => read_latest_session(log_path),
+        HistoryMode::Full => {
+            if !log_path.exists() {
+                return Ok(None);
+            }
+            let contents = fs::read_to_string(log_path)?;
+            let trimmed = contents.trim();
+            if trimmed.len() <= FULL_HISTORY_MAX_BYTES {
+                return Ok((!trimmed.is_empty()).then(|| trimmed.to_string()));
+            }
+            // Keep the newest sessions, starting at a session boundary
+            let recent = tail(trimmed, FULL_HISTORY_MAX_BYTES);
+            let recent = recent
+                .find(SESSION_START)
+                .map_or(recent, |start| &recent[start..]);
+            Ok(Some(format!("(earlier sessions omitted)\n{recent}")))
+        }
+    }
+}
+
+/// The most recent finished session in cryo.log that focused on `plan`
+/// (`plans/<plan>.md`), for `history_mode = "last-session"`.
+pub fn read_latest_plan_session(log_path: &Path, plan: &str) -> Result<Option<String>> {
+    if !log_path.exists() {
+        return Ok(None);
+    }
+    let contents = fs::read_to_string(log_path)?;
+    let marker = format!("] focus plan: {plan}");
+    let starts: Vec<usize> = contents
+        .match_indices(SESSION_START)
+        .map(|(i, _)| i)
+        .collect();
+    for (n, &start) in starts.iter().enumerate().rev() {
+        let end = starts.get(n + 1).copied().unwrap_or(contents.len());
+        let session = &contents[start..end];
+        let Some(session_end) = session.rfind(SESSION_END) else {
+            continue;
+        };
+        if session.lines().any(|line| line.ends_with(&marker)) {
+            return Ok(Some(session[..session_end + SESSION_END.len()].to_string()));
+        }
+    }
+    Ok(None)
+}
+
 /// Read the most recent session from cryo.log, whether or not it has finished.
 /// Returns from the last `SESSION_START` to EOF.
 pub fn read_current_session(log_path: &Path) -> Result<Option<String>> {
@@ -64,6 +196,46 @@ pub fn session_count(log_path: &Path) -> Result<u32> {
     Ok(contents.matches(SESSION_START).count() as u32)
 }
 
+/// `text` as a double-quoted log field: backslashes, quotes and line
+/// breaks are escaped so the field stays on one line and ends at the
+/// closing quote. [`unquote`] reverses it.
+pub fn quote(text: &str) -> String {
+    let mut out = String::with_capacity(text.len() + 2);
+    out.push('"');
+    for c in text.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '"' => out.push_str("\\\""),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// The text of a field written by [`quote`], or None if `field` isn't
+/// quoted.
+pub fn unquote(field: &str) -> Option<String> {
+    let inner = field.strip_prefix('"')?.strip_suffix('"')?;
+    let mut out = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('r') => out.push('\r'),
+            Some(c) => out.push(c),
+            None => out.push('\\'),
+        }
+    }
+    Some(out)
+}
+
 /// Extract `note: "..."` lines from the most recent session that has notes.
 /// Scans backward through sessions so a restart doesn't hide previous notes.
 pub fn parse_latest_session_notes(log_path: &Path) -> Result<Vec<String>> {
@@ -135,12 +307,60 @@ pub fn parse_latest_session_task(log_path: &Path) -> Result<Option<String>> {
     Ok(None)
 }
 
+/// Extract the last `next task: "..."` event from the current session in
+/// cryo.log, undoing the escaping of [`quote`].
+pub fn parse_latest_session_next_task(log_path: &Path) -> Result<Option<String>> {
+    let session = match read_current_session(log_path)? {
+        Some(s) => s,
+        None => return Ok(None),
+    };
+    let task = session.lines().rev().find_map(|line| {
+        let (_, rest) = line.split_once("] next task: ")?;
+        unquote(rest)
+    });
+    Ok(task)
+}
+
+/// Prefix for each line of an attached block (`cryo-agent attach-log`).
+pub const BLOCK_LINE_PREFIX: &str = "  | ";
+
+/// The last `max_bytes` of `text`, starting on a line boundary when the
+/// cut lands inside a line. Returns the text unchanged if it fits.
+pub fn tail(text: &str, max_bytes: usize) -> &str {
+    if text.len() <= max_bytes {
+        return text;
+    }
+    let mut start = text.len() - max_bytes;
+    while !text.is_char_boundary(start) {
+        start += 1;
+    }
+    let cut = &text[start..];
+    match cut.find('\n') {
+        Some(nl) if nl + 1 < cut.len() => &cut[nl + 1..],
+        _ => cut,
+    }
+}
+
 /// Outcome of a completed session.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum SessionOutcome {
     Success,
     Failed,
     Interrupted,
+    /// The agent stopped the session on purpose with `cryo-agent abort`.
+    Aborted,
+}
+
+impl std::fmt::Display for SessionOutcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            SessionOutcome::Success => "success",
+            SessionOutcome::Failed => "failed",
+            SessionOutcome::Interrupted => "interrupted",
+            SessionOutcome::Aborted => "aborted",
+        })
+    }
 }
 
 /// Summary of a single session extracted from cryo.log.
@@ -149,11 +369,173 @@ pub struct SessionSummary {
     pub session_number: u32,
     pub timestamp: NaiveDateTime,
     pub outcome: SessionOutcome,
+    /// Kinds of custom events (`cryo-agent event`) recorded in the session.
+    pub events: Vec<String>,
+    /// Provider that ran the session (`provider: X` event); `None` for
+    /// sessions without providers and for older logs.
+    pub provider: Option<String>,
+    /// Time from the first to the last event in the block; `None` when
+    /// the block has fewer than two parseable event timestamps.
+    pub duration: Option<Duration>,
+    /// Tokens and cost from the `usage:` event; `None` when `usage_pattern`
+    /// is unset or nothing matched.
+    pub usage: Option<crate::usage::Usage>,
 }
 
-/// Parse all sessions from `cryo.log` whose timestamp is >= `since`.
+/// `data` as single-line JSON for a log line, so a pretty-printed payload
+/// can't break cryo.log's one-event-per-line format. Whitespace outside
+/// strings is dropped, keeping the keys in their order. Text that isn't
+/// JSON is returned with its line breaks escaped.
+pub fn compact_json(data: &str) -> String {
+    if serde_json::from_str::<serde_json::Value>(data).is_err() {
+        return data
+            .replace('\\', "\\\\")
+            .replace('\n', "\\n")
+            .replace('\r', "\\r");
+    }
+    let mut out = String::with_capacity(data.len());
+    let (mut in_string, mut escaped) = (false, false);
+    for c in data.chars() {
+        if in_string {
+            in_string = escaped || c != '"';
+            escaped = !escaped && c == '\\';
+        } else if c.is_whitespace() {
+            continue;
+        } else {
+            in_string = c == '"';
+        }
+        out.push(c);
+    }
+    out
+}
+
+const CUSTOM_EVENT_PREFIX: &str = "event: ";
+
+/// Format a custom agent event as a log line body: `event: kind {data}`,
+/// with `data` as single-line JSON.
+pub fn format_custom_event(kind: &str, data: Option<&str>) -> String {
+    match data {
+        Some(data) => format!("{CUSTOM_EVENT_PREFIX}{kind} {}", compact_json(data)),
+        None => format!("{CUSTOM_EVENT_PREFIX}{kind}"),
+    }
+}
+
+/// Extract the provider name from a `[HH:MM:SS] provider: name` log line.
+fn parse_provider(line: &str) -> Option<&str> {
+    let (_, rest) = line.strip_prefix('[')?.split_once("] ")?;
+    Some(rest.strip_prefix("provider: ")?.trim())
+}
+
+/// Extract the kind from a `[HH:MM:SS] event: kind {data}` log line.
+fn parse_custom_event_kind(line: &str) -> Option<&str> {
+    // Only event lines start with '['; attached output is indented
+    let (_, rest) = line.strip_prefix('[')?.split_once("] ")?;
+    let rest = rest.strip_prefix(CUSTOM_EVENT_PREFIX)?;
+    rest.split_whitespace().next()
+}
+
+/// How `cryo.log` timestamps are written (`log_timezone`, `log_timestamp_format`).
+#[derive(Debug, Clone, Default)]
+pub struct LogFormat {
+    pub timezone: LogTimezone,
+    /// strftime pattern for headers and events alike; `None` keeps the
+    /// built-in full timestamp on headers and `%H:%M:%S` on events
+    pub timestamp: Option<String>,
+}
+
+impl LogFormat {
+    pub fn from_config(config: &CryoConfig) -> Self {
+        Self {
+            timezone: config.log_timezone,
+            timestamp: config.log_timestamp_format.clone(),
+        }
+    }
+
+    fn now(&self) -> DateTime<FixedOffset> {
+        match self.timezone {
+            LogTimezone::Utc => Utc::now().fixed_offset(),
+            LogTimezone::Local => Local::now().fixed_offset(),
+        }
+    }
+
+    fn header_timestamp(&self) -> String {
+        let fmt = match (&self.timestamp, self.timezone) {
+            (Some(fmt), _) => fmt.as_str(),
+            (None, LogTimezone::Utc) => "%Y-%m-%dT%H:%M:%SZ",
+            (None, LogTimezone::Local) => "%Y-%m-%dT%H:%M:%S%:z",
+        };
+        self.now().format(fmt).to_string()
+    }
+
+    fn event_timestamp(&self) -> String {
+        let fmt = self.timestamp.as_deref().unwrap_or("%H:%M:%S");
+        self.now().format(fmt).to_string()
+    }
+
+    /// Parse a session header timestamp into naive UTC. Tries the
+    /// configured pattern first, then the built-in ones, so logs written
+    /// under an earlier setting stay readable.
+    fn parse_header_timestamp(&self, s: &str) -> Option<NaiveDateTime> {
+        if let Some(fmt) = &self.timestamp {
+            if let Ok(t) = DateTime::parse_from_str(s, fmt) {
+                return Some(t.naive_utc());
+            }
+            if let Ok(t) = NaiveDateTime::parse_from_str(s, fmt) {
+                return match self.timezone {
+                    LogTimezone::Utc => Some(t),
+                    LogTimezone::Local => {
+                        Some(Local.from_local_datetime(&t).earliest()?.naive_utc())
+                    }
+                };
+            }
+        }
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%SZ")
+            .ok()
+            .or_else(|| DateTime::parse_from_rfc3339(s).ok().map(|t| t.naive_utc()))
+    }
+
+    /// Parse the `[...]` timestamp of an event line. Time-only stamps are
+    /// placed on `date`'s day; only differences between them are meaningful.
+    fn parse_event_timestamp(&self, line: &str, date: NaiveDateTime) -> Option<NaiveDateTime> {
+        let (stamp, _) = line.strip_prefix('[')?.split_once("] ")?;
+        let fmt = self.timestamp.as_deref().unwrap_or("%H:%M:%S");
+        DateTime::parse_from_str(stamp, fmt)
+            .map(|t| t.naive_local())
+            .or_else(|_| NaiveDateTime::parse_from_str(stamp, fmt))
+            .ok()
+            .or_else(|| {
+                Some(
+                    date.date()
+                        .and_time(NaiveTime::parse_from_str(stamp, fmt).ok()?),
+                )
+            })
+    }
+
+    /// First-to-last event span of a session block.
+    fn block_duration(&self, block: &str, date: NaiveDateTime) -> Option<Duration> {
+        let mut stamps = block
+            .lines()
+            .filter_map(|line| self.parse_event_timestamp(line, date));
+        let first = stamps.next()?;
+        let last = stamps.next_back()?;
+        let span = last - first;
+        // Time-only stamps wrap at midnight
+        Some(if span < Duration::zero() {
+            span + Duration::days(1)
+        } else {
+            span
+        })
+    }
+}
+
+/// Parse all sessions from `cryo.log` whose timestamp is >= `since` (UTC),
+/// reading headers written with `format`.
 /// Returns a vec of session summaries sorted chronologically.
-pub fn parse_sessions_since(log_path: &Path, since: NaiveDateTime) -> Result<Vec<SessionSummary>> {
+pub fn parse_sessions_since(
+    log_path: &Path,
+    since: NaiveDateTime,
+    format: &LogFormat,
+) -> Result<Vec<SessionSummary>> {
     if !log_path.exists() {
         return Ok(Vec::new());
     }
@@ -177,7 +559,7 @@ pub fn parse_sessions_since(log_path: &Path, since: NaiveDateTime) -> Result<Vec
 
         // Parse header: "--- CRYO SESSION N | 2026-02-28T14:30:45Z ---"
         let header_line = block.lines().next().unwrap_or("");
-        let (session_number, timestamp) = match parse_session_header(header_line) {
+        let (session_number, timestamp) = match parse_session_header(header_line, format) {
             Some(v) => v,
             None => continue,
         };
@@ -190,6 +572,8 @@ pub fn parse_sessions_since(log_path: &Path, since: NaiveDateTime) -> Result<Vec
         // since an agent can exit with code 0 without hibernating (still a failure).
         let outcome = if block.contains("--- CRYO INTERRUPTED ---") {
             SessionOutcome::Interrupted
+        } else if block.contains("] abort: ") {
+            SessionOutcome::Aborted
         } else if block.contains("quick exit detected")
             || block.contains("agent exited without hibernate")
         {
@@ -201,18 +585,37 @@ pub fn parse_sessions_since(log_path: &Path, since: NaiveDateTime) -> Result<Vec
             SessionOutcome::Failed
         };
 
+        let events = block
+            .lines()
+            .filter_map(parse_custom_event_kind)
+            .map(String::from)
+            .collect();
+        let provider = block.lines().find_map(parse_provider).map(String::from);
+        let usage = block.lines().find_map(crate::usage::Usage::parse_event);
+
         summaries.push(SessionSummary {
             session_number,
             timestamp,
             outcome,
+            events,
+            provider,
+            duration: format.block_duration(block, timestamp),
+            usage,
         });
     }
 
     Ok(summaries)
 }
 
-/// Parse a session header line into (session_number, timestamp).
-fn parse_session_header(line: &str) -> Option<(u32, NaiveDateTime)> {
+/// Parse a session header line into (session_number, UTC timestamp).
+/// Number of the session whose block starts `session`, e.g. the text
+/// returned by [`read_latest_session`].
+pub fn session_number(session: &str) -> Option<u32> {
+    let header = session.lines().next()?.strip_prefix(SESSION_START)?;
+    header.split('|').next()?.trim().parse().ok()
+}
+
+fn parse_session_header(line: &str, format: &LogFormat) -> Option<(u32, NaiveDateTime)> {
     // "--- CRYO SESSION 3 | 2026-02-28T14:30:45Z ---"
     let after_prefix = line.strip_prefix(SESSION_START)?.trim_start();
     let parts: Vec<&str> = after_prefix.splitn(2, '|').collect();
@@ -221,7 +624,7 @@ fn parse_session_header(line: &str) -> Option<(u32, NaiveDateTime)> {
     }
     let session_number: u32 = parts[0].trim().parse().ok()?;
     let ts_str = parts[1].trim().trim_end_matches("---").trim();
-    let timestamp = chrono::NaiveDateTime::parse_from_str(ts_str, "%Y-%m-%dT%H:%M:%SZ").ok()?;
+    let timestamp = format.parse_header_timestamp(ts_str)?;
     Some((session_number, timestamp))
 }
 
@@ -229,6 +632,8 @@ fn parse_session_header(line: &str) -> Option<(u32, NaiveDateTime)> {
 pub struct EventLogger {
     file: fs::File,
     finished: bool,
+    redactor: Redactor,
+    format: LogFormat,
 }
 
 impl EventLogger {
@@ -239,20 +644,41 @@ impl EventLogger {
         task: &str,
         agent_cmd: &str,
         inbox_filenames: &[String],
+    ) -> Result<Self, anyhow::Error> {
+        Self::begin_redacted(
+            log_path,
+            session_number,
+            task,
+            agent_cmd,
+            inbox_filenames,
+            Redactor::default(),
+            LogFormat::default(),
+        )
+    }
+
+    /// Begin a new session, scrubbing secrets from everything written and
+    /// stamping lines according to `format`.
+    pub fn begin_redacted(
+        log_path: &Path,
+        session_number: u32,
+        task: &str,
+        agent_cmd: &str,
+        inbox_filenames: &[String],
+        redactor: Redactor,
+        format: LogFormat,
     ) -> Result<Self, anyhow::Error> {
         let mut file = fs::OpenOptions::new()
             .create(true)
             .append(true)
             .open(log_path)?;
 
-        let now = chrono::Utc::now();
         writeln!(
             file,
             "--- CRYO SESSION {session_number} | {} ---",
-            now.format("%Y-%m-%dT%H:%M:%SZ")
+            format.header_timestamp()
         )?;
-        writeln!(file, "task: {task}")?;
-        writeln!(file, "agent: {agent_cmd}")?;
+        writeln!(file, "task: {}", redactor.redact(task))?;
+        writeln!(file, "agent: {}", redactor.redact(agent_cmd))?;
 
         if inbox_filenames.is_empty() {
             writeln!(file, "inbox: 0 messages")?;
@@ -269,13 +695,34 @@ impl EventLogger {
         Ok(Self {
             file,
             finished: false,
+            redactor,
+            format,
         })
     }
 
     /// Log a timestamped event.
     pub fn log_event(&mut self, event: &str) -> Result<(), anyhow::Error> {
-        let now = chrono::Utc::now();
-        writeln!(self.file, "[{}] {event}", now.format("%H:%M:%S"))?;
+        writeln!(
+            self.file,
+            "[{}] {}",
+            self.format.event_timestamp(),
+            self.redactor.redact(event)
+        )?;
+        self.file.flush()?;
+        Ok(())
+    }
+
+    /// Log a timestamped `header` followed by `body`, one indented line per
+    /// body line, so multi-line output can't be mistaken for events.
+    pub fn log_block(&mut self, header: &str, body: &str) -> Result<(), anyhow::Error> {
+        self.log_event(header)?;
+        for line in body.lines() {
+            writeln!(
+                self.file,
+                "{BLOCK_LINE_PREFIX}{}",
+                self.redactor.redact(line)
+            )?;
+        }
         self.file.flush()?;
         Ok(())
     }
@@ -333,6 +780,45 @@ mod tests {
         assert!(content.contains("--- CRYO END ---"));
     }
 
+    #[test]
+    fn test_event_logger_redacts_provider_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let log_path = dir.path().join("cryo.log");
+        let key = "sk-ant-test-0123456789";
+        let config = crate::config::CryoConfig {
+            providers: vec![crate::config::ProviderConfig {
+                name: "anthropic".to_string(),
+                env: std::collections::HashMap::from([(
+                    "ANTHROPIC_API_KEY".to_string(),
+                    key.to_string(),
+                )]),
+                env_file: None,
+                max_session_duration: None,
+            }],
+            ..Default::default()
+        };
+        let redactor = Redactor::from_config(&config).unwrap();
+
+        let mut logger = EventLogger::begin_redacted(
+            &log_path,
+            1,
+            &format!("use {key}"),
+            "claude -p",
+            &[],
+            redactor,
+            LogFormat::default(),
+        )
+        .unwrap();
+        logger
+            .log_event(&format!("note: \"ANTHROPIC_API_KEY={key}\""))
+            .unwrap();
+        logger.finish(&format!("done {key}")).unwrap();
+
+        let content = std::fs::read_to_string(&log_path).unwrap();
+        assert!(!content.contains(key), "key leaked into log: {content}");
+        assert!(content.contains("note: \"ANTHROPIC_API_KEY=***\""));
+    }
+
     #[test]
     fn test_parse_sessions_since_counts_correctly() {
         let dir = tempfile::tempdir().unwrap();
@@ -365,13 +851,122 @@ mod tests {
         let since =
             chrono::NaiveDateTime::parse_from_str("2020-01-01T00:00:00Z", "%Y-%m-%dT%H:%M:%SZ")
                 .unwrap();
-        let summaries = parse_sessions_since(&log_path, since).unwrap();
+        let summaries = parse_sessions_since(&log_path, since, &LogFormat::default()).unwrap();
         assert_eq!(summaries.len(), 3);
         assert_eq!(summaries[0].outcome, SessionOutcome::Success);
         assert_eq!(summaries[1].outcome, SessionOutcome::Failed);
         assert_eq!(summaries[2].outcome, SessionOutcome::Failed);
     }
 
+    #[test]
+    fn test_parse_sessions_since_provider_and_duration() {
+        let dir = tempfile::tempdir().unwrap();
+        let log_path = dir.path().join("cryo.log");
+        std::fs::write(
+            &log_path,
+            "--- CRYO SESSION 1 | 2026-03-01T23:59:00Z ---\n\
+             task: t\n\
+             [23:59:00] provider: openai\n\
+             [23:59:01] agent started (pid 1)\n\
+             [00:01:31] agent exited (code 0)\n\
+             [00:01:31] session complete\n\
+             --- CRYO END ---\n\
+             --- CRYO SESSION 2 | 2026-03-02T10:00:00Z ---\n\
+             task: t\n\
+             [10:00:00] agent started (pid 2)\n\
+             --- CRYO END ---\n",
+        )
+        .unwrap();
+
+        let since =
+            chrono::NaiveDateTime::parse_from_str("2020-01-01T00:00:00Z", "%Y-%m-%dT%H:%M:%SZ")
+                .unwrap();
+        let summaries = parse_sessions_since(&log_path, since, &LogFormat::default()).unwrap();
+        assert_eq!(summaries[0].provider.as_deref(), Some("openai"));
+        assert_eq!(summaries[0].duration, Some(Duration::seconds(151)));
+        assert_eq!(summaries[1].provider, None);
+        assert_eq!(summaries[1].duration, None, "one event is not a span");
+    }
+
+    #[test]
+    fn test_parse_sessions_since_aborted() {
+        let dir = tempfile::tempdir().unwrap();
+        let log_path = dir.path().join("cryo.log");
+
+        let mut logger = EventLogger::begin(&log_path, 1, "task", "claude", &[]).unwrap();
+        logger.log_event("abort: \"missing dependency\"").unwrap();
+        logger.log_event("agent exited (code 1)").unwrap();
+        logger.finish("session aborted").unwrap();
+
+        let since =
+            chrono::NaiveDateTime::parse_from_str("2020-01-01T00:00:00Z", "%Y-%m-%dT%H:%M:%SZ")
+                .unwrap();
+        let summaries = parse_sessions_since(&log_path, since, &LogFormat::default()).unwrap();
+        assert_eq!(summaries[0].outcome, SessionOutcome::Aborted);
+    }
+
+    #[test]
+    fn test_quote_roundtrip() {
+        let text = "line one\nsaid \"hi\" C:\\tmp";
+        let quoted = quote(text);
+        assert_eq!(quoted, r#""line one\nsaid \"hi\" C:\\tmp""#);
+        assert_eq!(unquote(&quoted).as_deref(), Some(text));
+        assert_eq!(unquote("plain"), None);
+    }
+
+    #[test]
+    fn test_compact_json() {
+        assert_eq!(
+            compact_json("{\n  \"low\": 100,\n  \"note\": \"a \\\" b\"\n}\n"),
+            r#"{"low":100,"note":"a \" b"}"#
+        );
+        assert_eq!(compact_json("not\njson"), r"not\njson");
+    }
+
+    #[test]
+    fn test_parse_custom_event_kind() {
+        let line = format_custom_event("deploy", Some(r#"{"version":"1.2"}"#));
+        assert_eq!(line, r#"event: deploy {"version":"1.2"}"#);
+        assert_eq!(
+            parse_custom_event_kind(&format!("[10:00:00] {line}")),
+            Some("deploy")
+        );
+        assert_eq!(parse_custom_event_kind("[10:00:00] note: \"hi\""), None);
+
+        let line = format_custom_event("deploy", Some("{\n  \"version\": \"1.2\"\n}"));
+        assert_eq!(line, r#"event: deploy {"version":"1.2"}"#);
+    }
+
+    #[test]
+    fn test_tail_cuts_at_line_boundary() {
+        assert_eq!(tail("short\n", 100), "short\n");
+        assert_eq!(tail("one\ntwo\nthree\n", 8), "three\n");
+        // No newline inside the window: keep the (char-aligned) cut
+        assert_eq!(tail("ééé", 3), "é");
+    }
+
+    #[test]
+    fn test_log_block_lines_are_not_events() {
+        let dir = tempfile::tempdir().unwrap();
+        let log_path = dir.path().join("cryo.log");
+        let mut logger = EventLogger::begin(&log_path, 1, "t", "agent", &[]).unwrap();
+        logger
+            .log_block(
+                "attach-log: \"ci\" (40 bytes)",
+                "[10:00:00] event: deploy\nall good",
+            )
+            .unwrap();
+        logger.finish("session complete").unwrap();
+
+        let content = std::fs::read_to_string(&log_path).unwrap();
+        assert!(content.contains("  | [10:00:00] event: deploy\n  | all good\n"));
+        let since =
+            chrono::NaiveDateTime::parse_from_str("2020-01-01T00:00:00Z", "%Y-%m-%dT%H:%M:%SZ")
+                .unwrap();
+        let summaries = parse_sessions_since(&log_path, since, &LogFormat::default()).unwrap();
+        assert!(summaries[0].events.is_empty());
+    }
+
     #[test]
     fn test_parse_sessions_since_filters_by_time() {
         let dir = tempfile::tempdir().unwrap();
@@ -385,7 +980,7 @@ mod tests {
         let since =
             chrono::NaiveDateTime::parse_from_str("2099-01-01T00:00:00Z", "%Y-%m-%dT%H:%M:%SZ")
                 .unwrap();
-        let summaries = parse_sessions_since(&log_path, since).unwrap();
+        let summaries = parse_sessions_since(&log_path, since, &LogFormat::default()).unwrap();
         assert_eq!(summaries.len(), 0);
     }
 
@@ -403,7 +998,7 @@ mod tests {
         let since =
             chrono::NaiveDateTime::parse_from_str("2020-01-01T00:00:00Z", "%Y-%m-%dT%H:%M:%SZ")
                 .unwrap();
-        let summaries = parse_sessions_since(&log_path, since).unwrap();
+        let summaries = parse_sessions_since(&log_path, since, &LogFormat::default()).unwrap();
         assert_eq!(summaries.len(), 1);
         assert_eq!(summaries[0].outcome, SessionOutcome::Interrupted);
     }
@@ -422,7 +1017,7 @@ mod tests {
         let since =
             chrono::NaiveDateTime::parse_from_str("2020-01-01T00:00:00Z", "%Y-%m-%dT%H:%M:%SZ")
                 .unwrap();
-        let summaries = parse_sessions_since(&log_path, since).unwrap();
+        let summaries = parse_sessions_since(&log_path, since, &LogFormat::default()).unwrap();
         assert_eq!(summaries.len(), 1);
         assert_eq!(summaries[0].outcome, SessionOutcome::Failed);
     }
@@ -435,7 +1030,7 @@ mod tests {
         let since =
             chrono::NaiveDateTime::parse_from_str("2020-01-01T00:00:00Z", "%Y-%m-%dT%H:%M:%SZ")
                 .unwrap();
-        let summaries = parse_sessions_since(&log_path, since).unwrap();
+        let summaries = parse_sessions_since(&log_path, since, &LogFormat::default()).unwrap();
         assert_eq!(summaries.len(), 0);
     }
 
@@ -627,9 +1222,56 @@ mod tests {
         assert!(task.is_none(), "No task line should return None");
     }
 
+    #[test]
+    fn test_parse_next_task_uses_latest_event() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cryo.log");
+        let content = "--- CRYO SESSION 1 | 2026-03-01T12:00:00Z ---\n\
+                       task: implement auth\n\
+                       [12:00:02] next task: \"write tests\"\n\
+                       [12:00:03] next task: \"Implement the REST handler\"\n\
+                       --- CRYO END ---\n";
+        std::fs::write(&path, content).unwrap();
+        let task = parse_latest_session_next_task(&path).unwrap();
+        assert_eq!(task.as_deref(), Some("Implement the REST handler"));
+        // The plain task line is unaffected
+        let task = parse_latest_session_task(&path).unwrap();
+        assert_eq!(task.as_deref(), Some("implement auth"));
+    }
+
+    #[test]
+    fn test_parse_next_task_keeps_line_breaks() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cryo.log");
+        let task = "Fix the \"login\" bug\nthen update the docs";
+        let content = format!(
+            "--- CRYO SESSION 1 | 2026-03-01T12:00:00Z ---\n\
+             [12:00:02] next task: {}\n\
+             --- CRYO END ---\n",
+            quote(task)
+        );
+        std::fs::write(&path, content).unwrap();
+        let parsed = parse_latest_session_next_task(&path).unwrap();
+        assert_eq!(parsed.as_deref(), Some(task));
+    }
+
+    #[test]
+    fn test_parse_next_task_absent() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cryo.log");
+        let content = "--- CRYO SESSION 1 | 2026-03-01T12:00:00Z ---\n\
+                       task: implement auth\n\
+                       --- CRYO END ---\n";
+        std::fs::write(&path, content).unwrap();
+        assert!(parse_latest_session_next_task(&path).unwrap().is_none());
+    }
+
     #[test]
     fn test_parse_session_header_valid() {
-        let result = parse_session_header("--- CRYO SESSION 5 | 2026-03-01T14:30:45Z ---");
+        let result = parse_session_header(
+            "--- CRYO SESSION 5 | 2026-03-01T14:30:45Z ---",
+            &LogFormat::default(),
+        );
         assert!(result.is_some());
         let (num, ts) = result.unwrap();
         assert_eq!(num, 5);
@@ -640,11 +1282,18 @@ mod tests {
     #[test]
     fn test_parse_session_header_malformed() {
         // Non-numeric session number
-        assert!(parse_session_header("--- CRYO SESSION abc | 2026-03-01T14:30:45Z ---").is_none());
+        assert!(parse_session_header(
+            "--- CRYO SESSION abc | 2026-03-01T14:30:45Z ---",
+            &LogFormat::default()
+        )
+        .is_none());
         // Invalid timestamp
-        assert!(parse_session_header("--- CRYO SESSION 5 | not-a-date ---").is_none());
+        assert!(
+            parse_session_header("--- CRYO SESSION 5 | not-a-date ---", &LogFormat::default())
+                .is_none()
+        );
         // Completely unrelated text
-        assert!(parse_session_header("random text").is_none());
+        assert!(parse_session_header("random text", &LogFormat::default()).is_none());
     }
 
     #[test]
@@ -666,9 +1315,136 @@ mod tests {
             .unwrap()
             .and_hms_opt(0, 0, 0)
             .unwrap();
-        let sessions = parse_sessions_since(&path, since).unwrap();
+        let sessions = parse_sessions_since(&path, since, &LogFormat::default()).unwrap();
         assert_eq!(sessions.len(), 2, "Should return sessions 2 and 3");
         assert_eq!(sessions[0].session_number, 2);
         assert_eq!(sessions[1].session_number, 3);
     }
+
+    /// Write a session with `format`, then parse it back with the same format.
+    fn roundtrip(format: LogFormat) -> (String, Vec<SessionSummary>) {
+        let dir = tempfile::tempdir().unwrap();
+        let log_path = dir.path().join("cryo.log");
+        let logger = EventLogger::begin_redacted(
+            &log_path,
+            1,
+            "task",
+            "agent",
+            &[],
+            Redactor::default(),
+            format.clone(),
+        )
+        .unwrap();
+        logger.finish("hibernate: complete").unwrap();
+        let contents = fs::read_to_string(&log_path).unwrap();
+        let since = Utc::now().naive_utc() - chrono::Duration::minutes(1);
+        let sessions = parse_sessions_since(&log_path, since, &format).unwrap();
+        (contents, sessions)
+    }
+
+    fn assert_recent(sessions: &[SessionSummary]) {
+        assert_eq!(sessions.len(), 1);
+        let age = Utc::now().naive_utc() - sessions[0].timestamp;
+        assert!(age.num_seconds().abs() < 60, "timestamp not UTC: {age}");
+        assert_eq!(sessions[0].outcome, SessionOutcome::Success);
+    }
+
+    #[test]
+    fn test_log_format_roundtrip_utc() {
+        let (contents, sessions) = roundtrip(LogFormat::default());
+        assert!(
+            contents.lines().next().unwrap().ends_with("Z ---"),
+            "{contents}"
+        );
+        assert_recent(&sessions);
+
+        let (contents, sessions) = roundtrip(LogFormat {
+            timezone: LogTimezone::Utc,
+            timestamp: Some("%Y-%m-%d %H:%M:%S".to_string()),
+        });
+        let event = contents.lines().find(|l| l.contains("hibernate")).unwrap();
+        assert_eq!(event.find("] "), Some(20), "{event}");
+        assert_recent(&sessions);
+    }
+
+    #[test]
+    fn test_log_format_roundtrip_local() {
+        let (contents, sessions) = roundtrip(LogFormat {
+            timezone: LogTimezone::Local,
+            timestamp: None,
+        });
+        let offset = Local::now().format("%:z").to_string();
+        assert!(
+            contents.lines().next().unwrap().contains(&offset),
+            "{contents}"
+        );
+        assert_recent(&sessions);
+
+        let (_, sessions) = roundtrip(LogFormat {
+            timezone: LogTimezone::Local,
+            timestamp: Some("%d/%m/%Y %H:%M:%S".to_string()),
+        });
+        assert_recent(&sessions);
+    }
+
+    #[test]
+    fn test_session_output_reads_only_new_output() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = agent_log_path(dir.path());
+        fs::write(&path, "earlier session\n").unwrap();
+        let output = SessionOutput::start(&path);
+        assert_eq!(output.read(), "");
+        fs::write(&path, "earlier session\nthis session\n").unwrap();
+        assert_eq!(output.read(), "this session\n");
+    }
+
+    #[test]
+    fn test_rotate_log_keeps_gzipped_generations() {
+        use std::io::Read;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = agent_log_path(dir.path());
+        let gunzip = |n| {
+            let mut s = String::new();
+            flate2::read::GzDecoder::new(fs::File::open(rotated_log_path(&path, n)).unwrap())
+                .read_to_string(&mut s)
+                .unwrap();
+            s
+        };
+
+        fs::write(&path, "small\n").unwrap();
+        assert_eq!(rotate_log(&path, 100, 2).unwrap(), None);
+        assert_eq!(rotate_log(&path, 0, 2).unwrap(), None);
+
+        for run in ["first", "second", "third"] {
+            fs::write(&path, run.repeat(10)).unwrap();
+            assert!(rotate_log(&path, 10, 2).unwrap().is_some());
+            assert!(!path.exists());
+        }
+        assert_eq!(gunzip(1), "third".repeat(10));
+        assert_eq!(gunzip(2), "second".repeat(10));
+        assert!(!rotated_log_path(&path, 3).exists());
+
+        fs::write(&path, "x".repeat(20)).unwrap();
+        assert_eq!(rotate_log(&path, 10, 0).unwrap(), Some(20));
+        assert!(!path.exists());
+        assert_eq!(gunzip(1), "third".repeat(10));
+    }
+
+    #[test]
+    fn test_parse_header_falls_back_to_builtin_patterns() {
+        // A UTC log stays readable after switching to local time
+        let local = LogFormat {
+            timezone: LogTimezone::Local,
+            timestamp: Some("%d/%m/%Y %H:%M:%S".to_string()),
+        };
+        let (n, ts) =
+            parse_session_header("--- CRYO SESSION 2 | 2026-03-01T14:30:45Z ---", &local).unwrap();
+        assert_eq!(n, 2);
+        assert_eq!(ts.to_string(), "2026-03-01 14:30:45");
+        let (_, ts) =
+            parse_session_header("--- CRYO SESSION 3 | 2026-03-01T15:30:45+01:00 ---", &local)
+                .unwrap();
+        assert_eq!(ts.to_string(), "2026-03-01 14:30:45");
+    }
 }
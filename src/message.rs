@@ -1,9 +1,12 @@
 // src/message.rs
 use anyhow::{Context, Result};
 use chrono::{Local, NaiveDateTime};
+use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 
+use crate::config::{CryoConfig, InboxDisposition};
+
 #[derive(Debug, Clone)]
 pub struct Message {
     pub from: String,
@@ -11,22 +14,172 @@ pub struct Message {
     pub body: String,
     pub timestamp: NaiveDateTime,
     pub metadata: BTreeMap<String, String>,
+    /// Attached files, as names relative to `messages/attachments/`
+    pub attachments: Vec<String>,
+}
+
+/// Frontmatter key linking an inbox message to the question it answers.
+pub const IN_REPLY_TO_KEY: &str = "in_reply_to";
+
+/// Frontmatter key addressing an outbox message to specific people
+/// (comma-separated). Unset means everyone watching the channel.
+pub const TO_KEY: &str = "to";
+
+impl Message {
+    /// True if this message answers question `id`. `None` matches any message.
+    pub fn answers(&self, id: Option<&str>) -> bool {
+        match id {
+            Some(id) => self.metadata.get(IN_REPLY_TO_KEY).map(String::as_str) == Some(id),
+            None => true,
+        }
+    }
+
+    /// Recipients named in the `to` frontmatter, without a leading `@`.
+    /// Empty for a broadcast.
+    pub fn recipients(&self) -> Vec<String> {
+        self.metadata
+            .get(TO_KEY)
+            .map(|to| {
+                to.split(',')
+                    .map(|r| r.trim().trim_start_matches('@').to_string())
+                    .filter(|r| !r.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// Where and when a sync backend delivered an outbox message.
+/// Stored as `<message>.receipt.json` next to the archived message.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeliveryReceipt {
+    /// Channel that posted the message (e.g. "github", "zulip")
+    pub channel: String,
+    /// Remote identifier of the posted message, if the backend returned one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remote_id: Option<String>,
+    /// Link to the posted message, if known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    pub delivered_at: NaiveDateTime,
+}
+
+/// Failed attempts to deliver an outbox message. Stored as
+/// `<message>.failure.json` next to the message, and kept with it when the
+/// message moves to `outbox/dead-letter/`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeliveryFailure {
+    pub attempts: u32,
+    pub last_error: String,
+    pub last_attempt_at: NaiveDateTime,
+}
+
+/// Where a project's inbox and outbox live (`inbox_dir`/`outbox_dir`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct MessageDirs {
+    pub inbox: PathBuf,
+    pub outbox: PathBuf,
+}
+
+impl MessageDirs {
+    /// Directory for `box_name` ("inbox" or "outbox").
+    pub fn box_dir(&self, box_name: &str) -> PathBuf {
+        match box_name {
+            "outbox" => self.outbox.clone(),
+            _ => self.inbox.clone(),
+        }
+    }
+
+    pub fn inbox_archive(&self) -> PathBuf {
+        self.inbox.join("archive")
+    }
+
+    pub fn outbox_archive(&self) -> PathBuf {
+        self.outbox.join("archive")
+    }
+
+    /// Outbox messages that failed delivery too many times.
+    pub fn outbox_dead_letter(&self) -> PathBuf {
+        self.outbox.join("dead-letter")
+    }
 }
 
-/// Create the messages directory structure: inbox/, outbox/, inbox/archive/.
+/// Resolve `config.inbox_dir`/`outbox_dir` against the project in `dir`.
+pub fn dirs(dir: &Path, config: &CryoConfig) -> MessageDirs {
+    MessageDirs {
+        inbox: dir.join(&config.inbox_dir),
+        outbox: dir.join(&config.outbox_dir),
+    }
+}
+
+/// Message directories for the project in `dir`, from its `cryo.toml`
+/// (`messages/inbox` and `messages/outbox` without one). A `cryo.toml` that
+/// doesn't parse is an error rather than a silent move to the defaults.
+pub fn project_dirs(dir: &Path) -> Result<MessageDirs> {
+    let config = crate::config::load_config(&crate::config::config_path(dir))?.unwrap_or_default();
+    Ok(dirs(dir, &config))
+}
+
+/// Create the message directories: inbox/, outbox/, inbox/archive/.
 pub fn ensure_dirs(dir: &Path) -> Result<()> {
-    let messages = dir.join("messages");
-    std::fs::create_dir_all(messages.join("inbox").join("archive"))?;
-    std::fs::create_dir_all(messages.join("outbox"))?;
+    let dirs = project_dirs(dir)?;
+    std::fs::create_dir_all(dirs.inbox_archive())?;
+    std::fs::create_dir_all(&dirs.outbox)?;
     Ok(())
 }
 
-/// Write a message to the given box (e.g. "inbox" or "outbox").
-/// Returns the path of the written file.
-pub fn write_message(dir: &Path, box_name: &str, msg: &Message) -> Result<PathBuf> {
-    let box_dir = dir.join("messages").join(box_name);
-    std::fs::create_dir_all(&box_dir)?;
+/// Directory holding message attachments: `messages/attachments/`.
+pub fn attachments_dir(dir: &Path) -> PathBuf {
+    dir.join("messages").join("attachments")
+}
+
+/// Store `bytes` in the attachments directory under a name derived from
+/// `name`, and return the stored name. A timestamp prefix (plus a counter on
+/// collision) keeps names unique; anything but `[A-Za-z0-9._-]` becomes `-`.
+pub fn save_attachment(dir: &Path, name: &str, bytes: &[u8]) -> Result<String> {
+    let att_dir = attachments_dir(dir);
+    std::fs::create_dir_all(&att_dir)?;
+
+    let base: String = Path::new(name)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    let base = if base.trim_matches('.').is_empty() {
+        "attachment".to_string()
+    } else {
+        base
+    };
+    let ts = Local::now().format("%Y-%m-%dT%H-%M-%S");
 
+    let mut stored = format!("{ts}_{base}");
+    let mut n = 1;
+    while att_dir.join(&stored).exists() {
+        stored = format!("{ts}_{n}_{base}");
+        n += 1;
+    }
+    std::fs::write(att_dir.join(&stored), bytes)
+        .with_context(|| format!("Failed to write attachment {stored}"))?;
+    Ok(stored)
+}
+
+/// Copy the file at `src` into the attachments directory; see [`save_attachment`].
+pub fn attach_file(dir: &Path, src: &Path) -> Result<String> {
+    let bytes = std::fs::read(src)
+        .with_context(|| format!("Failed to read attachment {}", src.display()))?;
+    save_attachment(dir, &src.to_string_lossy(), &bytes)
+}
+
+/// Filename for `msg`: its timestamp plus a slug of the subject.
+pub fn message_filename(msg: &Message) -> String {
     let slug = slugify(&msg.subject);
     let ts = msg.timestamp.format("%Y-%m-%dT%H-%M-%S");
     // When slug is empty (e.g. GitHub comments with no subject), use a short
@@ -40,7 +193,16 @@ pub fn write_message(dir: &Path, box_name: &str, msg: &Message) -> Result<PathBu
     } else {
         slug
     };
-    let filename = format!("{ts}_{disambig}.md");
+    format!("{ts}_{disambig}.md")
+}
+
+/// Write a message to the given box (e.g. "inbox" or "outbox").
+/// Returns the path of the written file.
+pub fn write_message(dir: &Path, box_name: &str, msg: &Message) -> Result<PathBuf> {
+    let box_dir = project_dirs(dir)?.box_dir(box_name);
+    std::fs::create_dir_all(&box_dir)?;
+
+    let filename = message_filename(msg);
     let path = box_dir.join(&filename);
 
     // Atomic write: write to tmp, then rename
@@ -55,7 +217,7 @@ pub fn write_message(dir: &Path, box_name: &str, msg: &Message) -> Result<PathBu
 /// Read all unread messages from inbox/, sorted by filename (timestamp order).
 /// Returns (filename, Message) pairs.
 pub fn read_inbox(dir: &Path) -> Result<Vec<(String, Message)>> {
-    let inbox = dir.join("messages").join("inbox");
+    let inbox = project_dirs(dir)?.inbox;
     if !inbox.exists() {
         return Ok(Vec::new());
     }
@@ -93,7 +255,7 @@ pub fn read_inbox(dir: &Path) -> Result<Vec<(String, Message)>> {
 
 /// List inbox filenames without parsing message bodies.
 pub fn list_inbox(dir: &Path) -> Result<Vec<String>> {
-    let inbox = dir.join("messages").join("inbox");
+    let inbox = project_dirs(dir)?.inbox;
     if !inbox.exists() {
         return Ok(Vec::new());
     }
@@ -116,7 +278,7 @@ pub fn list_inbox(dir: &Path) -> Result<Vec<String>> {
 
 /// Read all messages from outbox/, sorted by filename (timestamp order).
 pub fn read_outbox(dir: &Path) -> Result<Vec<(String, Message)>> {
-    let outbox = dir.join("messages").join("outbox");
+    let outbox = project_dirs(dir)?.outbox;
     if !outbox.exists() {
         return Ok(Vec::new());
     }
@@ -154,7 +316,7 @@ pub fn read_outbox(dir: &Path) -> Result<Vec<(String, Message)>> {
 
 /// Read all archived inbox messages from inbox/archive/, sorted by filename.
 pub fn read_inbox_archive(dir: &Path) -> Result<Vec<(String, Message)>> {
-    let archive = dir.join("messages").join("inbox").join("archive");
+    let archive = project_dirs(dir)?.inbox_archive();
     if !archive.exists() {
         return Ok(Vec::new());
     }
@@ -192,7 +354,7 @@ pub fn read_inbox_archive(dir: &Path) -> Result<Vec<(String, Message)>> {
 
 /// Move processed messages from inbox/ to inbox/archive/.
 pub fn archive_messages(dir: &Path, filenames: &[String]) -> Result<()> {
-    let inbox = dir.join("messages").join("inbox");
+    let inbox = project_dirs(dir)?.inbox;
     let archive = inbox.join("archive");
     std::fs::create_dir_all(&archive)?;
 
@@ -206,6 +368,288 @@ pub fn archive_messages(dir: &Path, filenames: &[String]) -> Result<()> {
     Ok(())
 }
 
+/// Suffix appended to inbox files handled with `inbox_disposition = "flag"`.
+pub const CONSUMED_SUFFIX: &str = ".consumed";
+
+/// Dispose of consumed inbox messages according to `disposition`.
+///
+/// Every option is a removal or a rename to a name other than `*.md`, so
+/// the inbox watcher (which only reacts to new `*.md` files) does not wake
+/// again.
+pub fn dispose_messages(
+    dir: &Path,
+    filenames: &[String],
+    disposition: InboxDisposition,
+) -> Result<()> {
+    let inbox = project_dirs(dir)?.inbox;
+    match disposition {
+        InboxDisposition::Archive => archive_messages(dir, filenames),
+        InboxDisposition::Delete => {
+            for filename in filenames {
+                let path = inbox.join(filename);
+                if path.exists() {
+                    std::fs::remove_file(&path)
+                        .with_context(|| format!("Failed to delete {filename}"))?;
+                }
+            }
+            Ok(())
+        }
+        InboxDisposition::Flag => {
+            for filename in filenames {
+                let src = inbox.join(filename);
+                let dst = inbox.join(format!("{filename}{CONSUMED_SUFFIX}"));
+                if src.exists() {
+                    std::fs::rename(&src, &dst)
+                        .with_context(|| format!("Failed to flag {filename}"))?;
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Sidecar receipt filename for an archived message ("a.md" -> "a.receipt.json").
+pub fn receipt_filename(message_filename: &str) -> String {
+    let stem = message_filename
+        .strip_suffix(".md")
+        .unwrap_or(message_filename);
+    format!("{stem}.receipt.json")
+}
+
+/// Sidecar failure filename for an outbox message ("a.md" -> "a.failure.json").
+pub fn failure_filename(message_filename: &str) -> String {
+    let stem = message_filename
+        .strip_suffix(".md")
+        .unwrap_or(message_filename);
+    format!("{stem}.failure.json")
+}
+
+fn read_failure(path: &Path) -> Option<DeliveryFailure> {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+}
+
+/// The failure record of outbox/`filename`, if delivering it has failed.
+pub fn read_outbox_failure(dir: &Path, filename: &str) -> Result<Option<DeliveryFailure>> {
+    Ok(read_failure(
+        &project_dirs(dir)?.outbox.join(failure_filename(filename)),
+    ))
+}
+
+/// Count a failed delivery of outbox/`filename`. Returns the updated record.
+pub fn record_outbox_failure(dir: &Path, filename: &str, error: &str) -> Result<DeliveryFailure> {
+    let path = project_dirs(dir)?.outbox.join(failure_filename(filename));
+    let failure = DeliveryFailure {
+        attempts: read_failure(&path).map_or(0, |f| f.attempts) + 1,
+        last_error: error.to_string(),
+        last_attempt_at: chrono::Local::now().naive_local(),
+    };
+    std::fs::write(&path, serde_json::to_string_pretty(&failure)?)?;
+    Ok(failure)
+}
+
+/// Move outbox/`filename` and its failure record to outbox/dead-letter/.
+pub fn dead_letter_outbox_message(dir: &Path, filename: &str) -> Result<()> {
+    let dirs = project_dirs(dir)?;
+    let dead = dirs.outbox_dead_letter();
+    std::fs::create_dir_all(&dead)?;
+    for name in [filename.to_string(), failure_filename(filename)] {
+        let src = dirs.outbox.join(&name);
+        if src.exists() {
+            std::fs::rename(&src, dead.join(&name))
+                .with_context(|| format!("Failed to dead-letter outbox/{name}"))?;
+        }
+    }
+    Ok(())
+}
+
+/// Read outbox/dead-letter/ with each message's failure record, sorted by filename.
+pub fn read_dead_letters(dir: &Path) -> Result<Vec<(String, Message, Option<DeliveryFailure>)>> {
+    let dead = project_dirs(dir)?.outbox_dead_letter();
+    if !dead.exists() {
+        return Ok(Vec::new());
+    }
+    let mut names: Vec<String> = std::fs::read_dir(&dead)?
+        .filter_map(|e| e.ok())
+        .filter(|e| {
+            e.path().extension().is_some_and(|ext| ext == "md")
+                && e.file_type().is_ok_and(|ft| ft.is_file())
+        })
+        .map(|e| e.file_name().to_string_lossy().to_string())
+        .collect();
+    names.sort();
+
+    let mut messages = Vec::new();
+    for name in names {
+        let path = dead.join(&name);
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        match parse_message(&content) {
+            Ok(msg) => {
+                let failure = read_failure(&dead.join(failure_filename(&name)));
+                messages.push((name, msg, failure));
+            }
+            Err(e) => {
+                eprintln!(
+                    "Warning: skipping malformed dead letter {}: {e}",
+                    path.display()
+                );
+            }
+        }
+    }
+    Ok(messages)
+}
+
+/// Move every dead letter back to outbox/ with a fresh attempt count.
+/// Returns the filenames requeued.
+pub fn requeue_dead_letters(dir: &Path) -> Result<Vec<String>> {
+    let dirs = project_dirs(dir)?;
+    let dead = dirs.outbox_dead_letter();
+    let mut requeued = Vec::new();
+    for (name, _, _) in read_dead_letters(dir)? {
+        std::fs::rename(dead.join(&name), dirs.outbox.join(&name))
+            .with_context(|| format!("Failed to requeue dead-letter/{name}"))?;
+        let _ = std::fs::remove_file(dead.join(failure_filename(&name)));
+        requeued.push(name);
+    }
+    Ok(requeued)
+}
+
+/// Move a delivered message from outbox/ to outbox/archive/ and write its receipt.
+pub fn archive_outbox_message(dir: &Path, filename: &str, receipt: &DeliveryReceipt) -> Result<()> {
+    let outbox = project_dirs(dir)?.outbox;
+    let archive = outbox.join("archive");
+    std::fs::create_dir_all(&archive)?;
+
+    let src = outbox.join(filename);
+    if src.exists() {
+        std::fs::rename(&src, archive.join(filename))
+            .with_context(|| format!("Failed to archive outbox/{filename}"))?;
+    }
+    let json = serde_json::to_string_pretty(receipt)?;
+    std::fs::write(archive.join(receipt_filename(filename)), json)?;
+    let _ = std::fs::remove_file(outbox.join(failure_filename(filename)));
+    Ok(())
+}
+
+/// Read delivered messages from outbox/archive/ with their receipts, sorted by filename.
+/// Messages archived without a receipt (e.g. by older versions) have `None`.
+pub fn read_outbox_archive(dir: &Path) -> Result<Vec<(String, Message, Option<DeliveryReceipt>)>> {
+    let archive = project_dirs(dir)?.outbox_archive();
+    if !archive.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut entries: Vec<_> = std::fs::read_dir(&archive)?
+        .filter_map(|e| e.ok())
+        .filter(|e| {
+            e.path().extension().is_some_and(|ext| ext == "md")
+                && e.file_type().is_ok_and(|ft| ft.is_file())
+        })
+        .collect();
+
+    entries.sort_by_key(|e| e.file_name());
+
+    let mut messages = Vec::new();
+    for entry in entries {
+        let content = std::fs::read_to_string(entry.path())
+            .with_context(|| format!("Failed to read {}", entry.path().display()))?;
+        match parse_message(&content) {
+            Ok(msg) => {
+                let filename = entry.file_name().to_string_lossy().to_string();
+                let receipt = std::fs::read_to_string(archive.join(receipt_filename(&filename)))
+                    .ok()
+                    .and_then(|s| serde_json::from_str(&s).ok());
+                messages.push((filename, msg, receipt));
+            }
+            Err(e) => {
+                eprintln!(
+                    "Warning: skipping malformed archived message {}: {e}",
+                    entry.path().display()
+                );
+            }
+        }
+    }
+
+    Ok(messages)
+}
+
+/// Move every file in an external drop directory into inbox/ as a message.
+///
+/// Files that already have message frontmatter are kept as-is; anything else
+/// becomes the body of a new message. Hidden files (e.g. `.tmp_*` from an
+/// atomic writer) and subdirectories are skipped. A file that can't be read
+/// as text is moved to `failed/` in the drop directory with a warning, so it
+/// doesn't hold up the files after it. Returns the number imported.
+pub fn import_drop_dir(dir: &Path, drop_dir: &Path) -> Result<usize> {
+    import_drop_dir_with(drop_dir, |msg| write_message(dir, "inbox", msg).map(|_| ()))
+}
+
+/// Like `import_drop_dir`, but hands each message to `deliver` instead of
+/// writing it to `messages/inbox/` (for stores other than files).
+pub fn import_drop_dir_with(
+    drop_dir: &Path,
+    mut deliver: impl FnMut(&Message) -> Result<()>,
+) -> Result<usize> {
+    if !drop_dir.is_dir() {
+        return Ok(0);
+    }
+    let mut entries: Vec<_> = std::fs::read_dir(drop_dir)?
+        .filter_map(|e| e.ok())
+        .filter(|e| {
+            !e.file_name().to_string_lossy().starts_with('.')
+                && e.file_type().is_ok_and(|ft| ft.is_file())
+        })
+        .collect();
+    entries.sort_by_key(|e| e.file_name());
+
+    let source = drop_dir
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "drop".to_string());
+    let mut imported = 0;
+    for entry in entries {
+        let path = entry.path();
+        let content = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) => {
+                let failed = drop_dir.join("failed");
+                let moved = std::fs::create_dir_all(&failed)
+                    .and_then(|_| std::fs::rename(&path, failed.join(entry.file_name())));
+                match moved {
+                    Ok(()) => eprintln!(
+                        "Warning: cannot import {}: {e}; moved to {}",
+                        path.display(),
+                        failed.display()
+                    ),
+                    Err(move_err) => eprintln!(
+                        "Warning: cannot import {}: {e} (and could not move it aside: {move_err})",
+                        path.display()
+                    ),
+                }
+                continue;
+            }
+        };
+        let msg = parse_message(&content).unwrap_or_else(|_| Message {
+            from: source.clone(),
+            subject: path
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            body: content.trim().to_string(),
+            timestamp: Local::now().naive_local(),
+            metadata: BTreeMap::from([("source".to_string(), path.display().to_string())]),
+            attachments: Vec::new(),
+        });
+        deliver(&msg)?;
+        std::fs::remove_file(&path)
+            .with_context(|| format!("Failed to remove {}", path.display()))?;
+        imported += 1;
+    }
+    Ok(imported)
+}
+
 /// Render a message as markdown with frontmatter.
 pub fn message_to_markdown(msg: &Message) -> String {
     let mut lines = Vec::new();
@@ -219,6 +663,9 @@ pub fn message_to_markdown(msg: &Message) -> String {
     for (key, value) in &msg.metadata {
         lines.push(format!("{key}: {value}"));
     }
+    if !msg.attachments.is_empty() {
+        lines.push(format!("attachments: {}", msg.attachments.join(", ")));
+    }
     lines.push("---".to_string());
     lines.push(String::new());
     lines.push(msg.body.clone());
@@ -244,6 +691,7 @@ pub fn parse_message(content: &str) -> Result<Message> {
     let mut subject = String::new();
     let mut timestamp = Local::now().naive_local();
     let mut metadata = BTreeMap::new();
+    let mut attachments = Vec::new();
 
     for line in frontmatter.lines() {
         let line = line.trim();
@@ -261,6 +709,14 @@ pub fn parse_message(content: &str) -> Result<Message> {
                         timestamp = ts;
                     }
                 }
+                "attachments" => {
+                    attachments = value
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|a| !a.is_empty())
+                        .map(str::to_string)
+                        .collect();
+                }
                 _ => {
                     metadata.insert(key.to_string(), value.to_string());
                 }
@@ -274,6 +730,7 @@ pub fn parse_message(content: &str) -> Result<Message> {
         body,
         timestamp,
         metadata,
+        attachments,
     })
 }
 
@@ -0,0 +1,205 @@
+// src/notifier.rs
+//! Desktop notifications for reports and fallback alerts. The platform's
+//! notifier is probed once; when it is missing (or a notification fails),
+//! callers fall back to the outbox and log a single warning. Notifications
+//! carry a per-project group so a new report replaces the project's last one.
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+/// Why notify-rust can't reach a notification server, or `None` if it may.
+/// On Linux it talks to the D-Bus session bus itself (no `notify-send`
+/// needed), so the bus must be reachable: `DBUS_SESSION_BUS_ADDRESS`, or
+/// the default socket at `$XDG_RUNTIME_DIR/bus`. Other platforms use native
+/// APIs and have nothing to probe; a failed notification still turns the
+/// notifier off.
+fn missing_backend(
+    bus_address: Option<std::ffi::OsString>,
+    runtime_dir: Option<std::ffi::OsString>,
+) -> Option<String> {
+    if !cfg!(target_os = "linux") {
+        return None;
+    }
+    if bus_address.is_some_and(|a| !a.is_empty()) {
+        return None;
+    }
+    if runtime_dir.is_some_and(|d| Path::new(&d).join("bus").exists()) {
+        return None;
+    }
+    Some("no D-Bus session bus (DBUS_SESSION_BUS_ADDRESS is unset)".to_string())
+}
+
+/// One project's notifications, so the notification server can replace
+/// the project's previous report (or alert) instead of stacking another.
+/// On Linux this sets the stacking hints dunst and notify-osd honor, and
+/// [`Notifier`] passes the id the server gave the last notification of the
+/// same kind as the replace id. macOS only groups notifications through
+/// UNUserNotificationCenter, which needs a signed app bundle that a CLI
+/// doesn't have, so there notifications stack as before.
+#[derive(Debug, Clone)]
+pub struct NotificationGroup {
+    project: String,
+    dir: PathBuf,
+}
+
+impl NotificationGroup {
+    pub fn for_project(project_name: &str, dir: &Path) -> Self {
+        Self {
+            project: project_name.to_string(),
+            dir: dir.to_path_buf(),
+        }
+    }
+
+    /// Tag for one kind of notification ("report", "alert"). The project
+    /// directory is part of it, so two projects with the same name don't
+    /// collide.
+    pub fn tag(&self, kind: &str) -> String {
+        format!(
+            "cryochamber:{}:{:08x}:{kind}",
+            self.project,
+            crate::fnv1a(self.dir.as_os_str().as_encoded_bytes()) as u32
+        )
+    }
+
+    fn apply(&self, notification: &mut notify_rust::Notification, kind: &str) {
+        #[cfg(target_os = "linux")]
+        {
+            let tag = self.tag(kind);
+            notification.hint(notify_rust::Hint::Custom(
+                "x-dunst-stack-tag".into(),
+                tag.clone(),
+            ));
+            notification.hint(notify_rust::Hint::Custom(
+                "x-canonical-private-synchronous".into(),
+                tag,
+            ));
+        }
+        #[cfg(not(target_os = "linux"))]
+        let _ = (notification, kind);
+    }
+}
+
+pub struct Notifier {
+    available: AtomicBool,
+    /// Why notifications stopped, until a caller has logged it
+    warning: Mutex<Option<String>>,
+    group: Option<NotificationGroup>,
+    /// Id the server assigned to the last grouped notification of each
+    /// kind, passed back so the next one replaces it
+    shown: Mutex<HashMap<String, u32>>,
+}
+
+impl Notifier {
+    /// Probe for the platform's notification backend.
+    pub fn probe() -> Self {
+        let missing = missing_backend(
+            std::env::var_os("DBUS_SESSION_BUS_ADDRESS"),
+            std::env::var_os("XDG_RUNTIME_DIR"),
+        );
+        match missing {
+            Some(reason) => Self::unavailable(&reason),
+            None => Self {
+                available: AtomicBool::new(true),
+                warning: Mutex::new(None),
+                group: None,
+                shown: Mutex::new(HashMap::new()),
+            },
+        }
+    }
+
+    /// A notifier that never shows anything; `reason` becomes the warning.
+    pub fn unavailable(reason: &str) -> Self {
+        Self {
+            available: AtomicBool::new(false),
+            warning: Mutex::new(Some(Self::warning_for(reason))),
+            group: None,
+            shown: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Group this notifier's notifications under one project.
+    pub fn with_group(mut self, group: NotificationGroup) -> Self {
+        self.group = Some(group);
+        self
+    }
+
+    fn warning_for(reason: &str) -> String {
+        format!("{reason}; desktop notifications are disabled, using the outbox instead")
+    }
+
+    pub fn is_available(&self) -> bool {
+        self.available.load(Ordering::Relaxed)
+    }
+
+    /// Show `notification`, replacing the project's last one of the same
+    /// `kind`. Returns false if there is no notifier or showing fails; a
+    /// failure disables the notifier so it isn't retried every time.
+    pub fn show(&self, notification: &notify_rust::Notification, kind: &str) -> bool {
+        if !self.is_available() {
+            return false;
+        }
+        let mut notification = notification.clone();
+        if let Some(group) = &self.group {
+            group.apply(&mut notification, kind);
+            #[cfg(target_os = "linux")]
+            if let Some(&id) = self.shown.lock().unwrap().get(kind) {
+                notification.id(id);
+            }
+        }
+        match notification.show() {
+            Ok(_handle) => {
+                #[cfg(target_os = "linux")]
+                if self.group.is_some() {
+                    self.shown
+                        .lock()
+                        .unwrap()
+                        .insert(kind.to_string(), _handle.id());
+                }
+                true
+            }
+            Err(e) => {
+                self.available.store(false, Ordering::Relaxed);
+                let reason = format!("desktop notification failed: {e}");
+                *self.warning.lock().unwrap() = Some(Self::warning_for(&reason));
+                false
+            }
+        }
+    }
+
+    /// The reason notifications are off, the first time it is asked for.
+    pub fn take_warning(&self) -> Option<String> {
+        self.warning.lock().unwrap().take()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_group_tags_are_stable_per_project_and_kind() {
+        let a = NotificationGroup::for_project("alpha", Path::new("/work/alpha"));
+        let same = NotificationGroup::for_project("alpha", Path::new("/work/alpha"));
+        let other = NotificationGroup::for_project("alpha", Path::new("/other/alpha"));
+        assert_eq!(a.tag("report"), same.tag("report"));
+        assert_ne!(a.tag("report"), a.tag("alert"));
+        assert_ne!(a.tag("report"), other.tag("report"));
+        assert!(a.tag("report").starts_with("cryochamber:alpha:"));
+        assert!(a.tag("report").ends_with(":report"));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_missing_backend_needs_session_bus() {
+        let runtime = tempfile::tempdir().unwrap();
+        let dir = Some(runtime.path().as_os_str().to_owned());
+        assert!(missing_backend(None, None)
+            .unwrap()
+            .contains("no D-Bus session bus"));
+        assert!(missing_backend(Some("".into()), dir.clone()).is_some());
+        assert!(missing_backend(Some("unix:path=/run/user/1000/bus".into()), None).is_none());
+        std::fs::write(runtime.path().join("bus"), "").unwrap();
+        assert!(missing_backend(None, dir).is_none());
+    }
+}
@@ -0,0 +1,172 @@
+// src/plan.rs
+//! Scheduling hints in a front-matter block at the top of `plan.md`:
+//!
+//! ```text
+//! ---
+//! wake_cadence: 6h
+//! quiet_hours: 22:00-07:00
+//! max_sessions_per_day: 4
+//! ---
+//! # Plan
+//! ```
+//!
+//! Each key overrides the same setting in `cryo.toml`, so a plan carries its
+//! own operating parameters wherever it is copied. Only flat `key: value`
+//! lines are read, as in message frontmatter. Without the block, nothing
+//! changes.
+use std::path::Path;
+
+use crate::config::CryoConfig;
+
+/// Keys the front-matter may set.
+pub const KEYS: &[&str] = &["wake_cadence", "quiet_hours", "max_sessions_per_day"];
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PlanFrontMatter {
+    pub wake_cadence: Option<String>,
+    pub quiet_hours: Option<String>,
+    pub max_sessions_per_day: Option<u32>,
+}
+
+impl PlanFrontMatter {
+    /// Parse the front-matter at the top of `content`. Unknown keys and
+    /// invalid values are skipped, each with a warning naming its line.
+    pub fn parse(content: &str) -> (Self, Vec<String>) {
+        let mut front = Self::default();
+        let mut warnings = Vec::new();
+        let Some(block) = front_matter(content) else {
+            return (front, warnings);
+        };
+        // The block starts after the opening `---` on line 1
+        for (n, line) in block.lines().enumerate().map(|(i, l)| (i + 2, l.trim())) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((key, value)) = line.split_once(':') else {
+                warnings.push(format!("line {n}: expected `key: value`, got {line:?}"));
+                continue;
+            };
+            let key = key.trim();
+            let value = value.trim().trim_matches(|c| c == '"' || c == '\'');
+            match key {
+                "wake_cadence" => match crate::schedule::parse_wake_in(value) {
+                    Ok(_) => front.wake_cadence = Some(value.to_string()),
+                    Err(e) => warnings.push(format!("line {n}: wake_cadence: {e:#}")),
+                },
+                "quiet_hours" => match crate::schedule::QuietHours::parse(value) {
+                    Ok(_) => front.quiet_hours = Some(value.to_string()),
+                    Err(e) => warnings.push(format!("line {n}: {e:#}")),
+                },
+                "max_sessions_per_day" => match value.parse() {
+                    Ok(max) => front.max_sessions_per_day = Some(max),
+                    Err(_) => warnings.push(format!(
+                        "line {n}: max_sessions_per_day {value:?} is not a whole number"
+                    )),
+                },
+                _ => {
+                    let hint = KEYS
+                        .iter()
+                        .filter(|k| crate::config::edit_distance(key, k) <= 2)
+                        .min_by_key(|k| crate::config::edit_distance(key, k))
+                        .map(|k| format!(" (did you mean `{k}`?)"))
+                        .unwrap_or_default();
+                    warnings.push(format!("line {n}: unknown key `{key}` is ignored{hint}"));
+                }
+            }
+        }
+        (front, warnings)
+    }
+
+    /// Front-matter of the project's `plan.md`; empty when there is none.
+    pub fn load(dir: &Path) -> (Self, Vec<String>) {
+        match std::fs::read_to_string(dir.join("plan.md")) {
+            Ok(content) => Self::parse(&content),
+            Err(_) => (Self::default(), Vec::new()),
+        }
+    }
+
+    /// Override `config` with the keys the front-matter sets.
+    pub fn apply(&self, config: &mut CryoConfig) {
+        if let Some(cadence) = &self.wake_cadence {
+            config.wake_cadence = Some(cadence.clone());
+        }
+        if let Some(quiet) = &self.quiet_hours {
+            config.quiet_hours = Some(quiet.clone());
+        }
+        if let Some(max) = self.max_sessions_per_day {
+            config.max_sessions_per_day = max;
+        }
+    }
+}
+
+/// The lines between a leading `---` line and the next `---` line.
+fn front_matter(content: &str) -> Option<&str> {
+    let rest = content.strip_prefix("---")?;
+    let rest = rest
+        .strip_prefix("\r\n")
+        .or_else(|| rest.strip_prefix('\n'))?;
+    if rest.starts_with("---") {
+        return Some("");
+    }
+    rest.find("\n---").map(|end| &rest[..end])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_front_matter() {
+        let plan = "---\nwake_cadence: 6h\nquiet_hours: \"22:00-07:00\"\n\
+                    max_sessions_per_day: 4\n---\n# Plan\n";
+        let (front, warnings) = PlanFrontMatter::parse(plan);
+        assert!(warnings.is_empty(), "{warnings:?}");
+        assert_eq!(
+            front,
+            PlanFrontMatter {
+                wake_cadence: Some("6h".to_string()),
+                quiet_hours: Some("22:00-07:00".to_string()),
+                max_sessions_per_day: Some(4),
+            }
+        );
+
+        let mut config = CryoConfig {
+            max_sessions_per_day: 10,
+            ..Default::default()
+        };
+        front.apply(&mut config);
+        assert_eq!(config.wake_cadence.as_deref(), Some("6h"));
+        assert_eq!(config.quiet_hours.as_deref(), Some("22:00-07:00"));
+        assert_eq!(config.max_sessions_per_day, 4);
+    }
+
+    #[test]
+    fn test_no_front_matter_changes_nothing() {
+        for plan in ["# Plan\n\n---\nwake_cadence: 1h\n---\n", "", "---\n"] {
+            let (front, warnings) = PlanFrontMatter::parse(plan);
+            assert_eq!(front, PlanFrontMatter::default(), "{plan:?}");
+            assert!(warnings.is_empty(), "{plan:?}: {warnings:?}");
+        }
+    }
+
+    #[test]
+    fn test_front_matter_warnings() {
+        let plan = "---\nwake_cadense: 6h\nquiet_hours: nights\n\
+                    max_sessions_per_day: -1\nwake_cadence: 2h\nno colon\n---\n";
+        let (front, warnings) = PlanFrontMatter::parse(plan);
+        assert_eq!(front.wake_cadence.as_deref(), Some("2h"));
+        assert_eq!(front.quiet_hours, None);
+        assert_eq!(front.max_sessions_per_day, None);
+        let all = warnings.join("\n");
+        assert!(
+            all.contains(
+                "line 2: unknown key `wake_cadense` is ignored (did you mean `wake_cadence`?)"
+            ),
+            "{all}"
+        );
+        assert!(all.contains("line 3: Invalid quiet hours"), "{all}");
+        assert!(all.contains("line 4: max_sessions_per_day \"-1\""), "{all}");
+        assert!(all.contains("line 6: expected `key: value`"), "{all}");
+        assert_eq!(warnings.len(), 4, "{all}");
+    }
+}
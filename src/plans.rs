@@ -0,0 +1,98 @@
+// src/plans.rs
+//! Several workstreams in one chamber. Each `plans/<name>.md` is a plan of
+//! its own; every session focuses on one of them, in name order unless the
+//! agent picks the next with `cryo-agent focus-plan`. `hibernate --complete`
+//! finishes only the focused plan, and the daemon stops once all are done.
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+
+pub const PLANS_DIR: &str = "plans";
+
+pub fn plans_dir(dir: &Path) -> PathBuf {
+    dir.join(PLANS_DIR)
+}
+
+/// Path of plan `name` relative to the project, as shown to the agent.
+pub fn plan_file(name: &str) -> String {
+    format!("{PLANS_DIR}/{name}.md")
+}
+
+/// Names of the non-empty `plans/*.md` files, sorted. Empty when there is
+/// no `plans/` directory, i.e. the project runs on `plan.md` alone.
+pub fn list(dir: &Path) -> Result<Vec<String>> {
+    let plans = plans_dir(dir);
+    if !plans.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut names: Vec<String> = std::fs::read_dir(&plans)?
+        .filter_map(|e| e.ok())
+        .filter(|e| {
+            e.path().extension().is_some_and(|ext| ext == "md")
+                && std::fs::read_to_string(e.path()).is_ok_and(|s| !s.trim().is_empty())
+        })
+        .filter_map(|e| {
+            let path = e.path();
+            path.file_stem().map(|s| s.to_string_lossy().to_string())
+        })
+        .collect();
+    names.sort();
+    Ok(names)
+}
+
+/// The plan to focus on after `current`: the next unfinished one in name
+/// order, wrapping around. None when every plan is complete.
+pub fn next_focus(plans: &[String], completed: &[String], current: Option<&str>) -> Option<String> {
+    let pending: Vec<&String> = plans.iter().filter(|p| !completed.contains(p)).collect();
+    let after = current.and_then(|c| pending.iter().find(|p| p.as_str() > c));
+    after.or(pending.first()).map(|p| p.to_string())
+}
+
+/// Task for a plan's first session.
+pub fn first_task(name: &str) -> String {
+    format!("Execute the first task from {}", plan_file(name))
+}
+
+/// Task for a plan's later sessions when the agent set no `next-task`.
+pub fn continue_task(name: &str) -> String {
+    format!("Continue {}", plan_file(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_list_plans() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(list(dir.path()).unwrap().is_empty());
+
+        let plans = plans_dir(dir.path());
+        std::fs::create_dir_all(&plans).unwrap();
+        std::fs::write(plans.join("docs.md"), "# Docs\n").unwrap();
+        std::fs::write(plans.join("api.md"), "# API\n").unwrap();
+        std::fs::write(plans.join("empty.md"), "\n").unwrap();
+        std::fs::write(plans.join("notes.txt"), "not a plan").unwrap();
+        assert_eq!(list(dir.path()).unwrap(), names(&["api", "docs"]));
+    }
+
+    #[test]
+    fn test_next_focus_rotates_over_pending_plans() {
+        let plans = names(&["a", "b", "c"]);
+        assert_eq!(next_focus(&plans, &[], None).as_deref(), Some("a"));
+        assert_eq!(next_focus(&plans, &[], Some("a")).as_deref(), Some("b"));
+        assert_eq!(next_focus(&plans, &[], Some("c")).as_deref(), Some("a"));
+        // Finished plans are skipped, even the current one
+        let done = names(&["b"]);
+        assert_eq!(next_focus(&plans, &done, Some("a")).as_deref(), Some("c"));
+        let done = names(&["a", "c"]);
+        assert_eq!(next_focus(&plans, &done, Some("a")).as_deref(), Some("b"));
+        assert_eq!(next_focus(&plans, &done, Some("b")).as_deref(), Some("b"));
+        // A plan removed since the last session still orders the rotation
+        assert_eq!(next_focus(&plans, &[], Some("bb")).as_deref(), Some("c"));
+        assert_eq!(next_focus(&plans, &plans, Some("a")), None);
+    }
+}
@@ -1,6 +1,49 @@
 // src/process.rs
 use anyhow::{Context, Result};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+/// True if a process with this PID exists.
+pub fn is_alive(pid: u32) -> bool {
+    let ret = unsafe { libc::kill(pid as i32, 0) };
+    if ret == 0 {
+        return true;
+    }
+    // EPERM means process exists but we lack permission — still alive
+    let errno = std::io::Error::last_os_error().raw_os_error().unwrap_or(0);
+    errno == libc::EPERM
+}
+
+/// Opaque start-time token for `pid` (None if it can't be read). With the
+/// PID it identifies a process across PID reuse.
+#[cfg(target_os = "linux")]
+pub fn start_time(pid: u32) -> Option<String> {
+    // Field 22 of /proc/<pid>/stat, counted after the parenthesized command
+    // name (which may itself contain spaces).
+    let stat = std::fs::read_to_string(format!("/proc/{pid}/stat")).ok()?;
+    let after_comm = &stat[stat.rfind(')')? + 1..];
+    after_comm.split_whitespace().nth(19).map(String::from)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn start_time(pid: u32) -> Option<String> {
+    ps_field(pid, "lstart=")
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn ps_field(pid: u32, field: &str) -> Option<String> {
+    let output = std::process::Command::new("ps")
+        .args(["-o", field, "-p", &pid.to_string()])
+        .output()
+        .ok()?;
+    let value = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !value.is_empty()).then_some(value)
+}
+
+/// True if `pid` is alive and, when `started` was recorded, is still the
+/// process that started then rather than a later one reusing the PID.
+pub fn is_same_process(pid: u32, started: Option<&str>) -> bool {
+    is_alive(pid) && started.is_none_or(|s| start_time(pid).as_deref() == Some(s))
+}
 
 /// Send a signal to a process. Returns true if delivered, false on failure.
 pub fn send_signal(pid: u32, signal: i32) -> bool {
@@ -14,12 +57,36 @@ pub fn send_signal(pid: u32, signal: i32) -> bool {
     }
 }
 
-/// Send SIGUSR1 to the daemon to force an immediate wake.
-/// Returns true if the signal was delivered successfully.
+/// Send a signal to every process in group `pgid`. Returns true if it
+/// reached at least one.
+pub fn signal_group(pgid: u32, signal: i32) -> bool {
+    let ret = unsafe { libc::killpg(pgid as i32, signal) };
+    if ret != 0 {
+        let err = std::io::Error::last_os_error();
+        eprintln!("Warning: failed to send signal {signal} to process group {pgid}: {err}");
+        false
+    } else {
+        true
+    }
+}
+
+/// True if any process in group `pgid` still exists.
+pub fn is_group_alive(pgid: u32) -> bool {
+    let ret = unsafe { libc::killpg(pgid as i32, 0) };
+    ret == 0 || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+/// Send SIGUSR1 to the daemon to force an immediate wake, or a `wake`
+/// request over the socket to an embedded one. Returns true if it was
+/// delivered.
 pub fn signal_daemon_wake(dir: &Path) -> bool {
     if let Ok(Some(st)) = crate::state::load_state(&crate::state::state_path(dir)) {
         if let Some(pid) = st.pid {
             if crate::state::is_locked(&st) {
+                if st.embedded {
+                    return crate::socket::send_request(dir, &crate::socket::Request::Wake)
+                        .is_ok_and(|r| r.ok);
+                }
                 return send_signal(pid, libc::SIGUSR1);
             }
         }
@@ -27,8 +94,41 @@ pub fn signal_daemon_wake(dir: &Path) -> bool {
     false
 }
 
+/// Stop the daemon recorded in `state`: `terminate_pid` for a daemon
+/// process, a `stop` request over the socket for one embedded in another
+/// program, whose host must keep running.
+pub fn stop_daemon(dir: &Path, state: &crate::state::CryoState) -> Result<()> {
+    let Some(pid) = state.pid else {
+        return Ok(());
+    };
+    if !state.embedded {
+        return terminate_pid(pid, state.pid_started.as_deref());
+    }
+    println!("Asking the daemon embedded in process {pid} to stop...");
+    let resp = crate::socket::send_request(dir, &crate::socket::Request::Stop)
+        .context("Failed to reach the embedded daemon")?;
+    if !resp.ok {
+        anyhow::bail!("Embedded daemon refused to stop: {}", resp.message);
+    }
+    // Stopping a running agent can take its kill grace period
+    for _ in 0..300 {
+        let st = crate::state::load_state(&crate::state::state_path(dir))?;
+        if !st.is_some_and(|st| crate::state::is_locked(&st) && st.pid == Some(pid)) {
+            return Ok(());
+        }
+        std::thread::sleep(std::time::Duration::from_millis(100));
+    }
+    anyhow::bail!("The daemon embedded in process {pid} did not stop within 30s")
+}
+
 /// Send SIGTERM to a process, wait for it to exit, escalate to SIGKILL if needed.
-pub fn terminate_pid(pid: u32) -> Result<()> {
+/// `started` is the start time recorded with the PID: if the PID now belongs
+/// to another process (after a reboot or PID reuse), nothing is signalled.
+pub fn terminate_pid(pid: u32, started: Option<&str>) -> Result<()> {
+    if !is_same_process(pid, started) {
+        println!("Process {pid} is no longer running; nothing to stop.");
+        return Ok(());
+    }
     println!("Sending SIGTERM to process {pid}...");
     send_signal(pid, libc::SIGTERM);
 
@@ -51,6 +151,128 @@ pub fn terminate_pid(pid: u32) -> Result<()> {
     Ok(())
 }
 
+/// PID file of a project's sync daemon, e.g. `.cryo/gh-sync.pid` for
+/// `name` "gh-sync".
+pub fn sync_pid_path(dir: &Path, name: &str) -> PathBuf {
+    dir.join(".cryo").join(format!("{name}.pid"))
+}
+
+/// PID recorded in a lock file, if the file exists and parses.
+pub fn read_pid(path: &Path) -> Option<u32> {
+    read_lock(path).map(|(pid, _)| pid)
+}
+
+/// PID and start time in a lock file: the PID on the first line, the start
+/// time (if known) on the second.
+fn read_lock(path: &Path) -> Option<(u32, Option<String>)> {
+    let content = std::fs::read_to_string(path).ok()?;
+    let mut lines = content.lines();
+    let pid = lines.next()?.trim().parse().ok()?;
+    let started = lines.next().map(str::trim).filter(|s| !s.is_empty());
+    Some((pid, started.map(String::from)))
+}
+
+/// The live process holding the lock at `path`. None if the file is absent,
+/// or its PID is dead or now belongs to a different process.
+pub fn lock_holder(path: &Path) -> Option<u32> {
+    let (pid, started) = read_lock(path)?;
+    is_same_process(pid, started.as_deref()).then_some(pid)
+}
+
+/// A PID file held for the life of a process, so a second instance for the
+/// same project can refuse to start. Removed on drop.
+pub struct PidLock {
+    path: PathBuf,
+}
+
+impl PidLock {
+    /// Write our PID and start time to `path`. Fails if the file names
+    /// another live process; a stale file (its process is gone, or the PID
+    /// was reused) is replaced.
+    pub fn acquire(path: &Path) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let own = std::process::id();
+        // Written in full under a private name, then linked into place: a
+        // reader never sees a half-written lock, and the link fails if
+        // another process got there first
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        let tmp = path.with_file_name(format!(".{name}.{own}.tmp"));
+        let started = start_time(own).unwrap_or_default();
+        std::fs::write(&tmp, format!("{own}\n{started}\n"))
+            .with_context(|| format!("Failed to write {}", tmp.display()))?;
+        let result = Self::link(&tmp, path, own);
+        let _ = std::fs::remove_file(&tmp);
+        result
+    }
+
+    fn link(tmp: &Path, path: &Path, own: u32) -> Result<Self> {
+        for _ in 0..2 {
+            match std::fs::hard_link(tmp, path) {
+                Ok(()) => {
+                    return Ok(Self {
+                        path: path.to_path_buf(),
+                    })
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                    let seen = read_lock(path);
+                    if let Some((pid, _)) = seen.as_ref().filter(|(pid, started)| {
+                        *pid != own && is_same_process(*pid, started.as_deref())
+                    }) {
+                        anyhow::bail!(
+                            "Already running as PID {pid} (lock file {})",
+                            path.display()
+                        );
+                    }
+                    // Move the stale lock aside rather than removing it by
+                    // name: if another process replaced it in the meantime,
+                    // its fresh lock is what got moved, and goes back
+                    let aside = tmp.with_extension("stale");
+                    if std::fs::rename(path, &aside).is_ok() {
+                        let moved = read_lock(&aside);
+                        let restored = moved != seen && std::fs::hard_link(&aside, path).is_ok();
+                        let _ = std::fs::remove_file(&aside);
+                        if let Some((pid, _)) = moved.filter(|_| restored) {
+                            anyhow::bail!(
+                                "Already running as PID {pid} (lock file {})",
+                                path.display()
+                            );
+                        }
+                    }
+                }
+                Err(e) => {
+                    return Err(e).with_context(|| format!("Failed to create {}", path.display()))
+                }
+            }
+        }
+        anyhow::bail!("Could not take lock {}", path.display())
+    }
+}
+
+impl Drop for PidLock {
+    fn drop(&mut self) {
+        // Leave the file alone if another process has since replaced it
+        if read_pid(&self.path) == Some(std::process::id()) {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// Stop whatever process holds the lock at `path` and remove the file.
+/// Returns the PID stopped, or `None` if the lock was stale or absent.
+pub fn release_pid_lock(path: &Path) -> Result<Option<u32>> {
+    let holder = read_lock(path).filter(|(pid, started)| is_same_process(*pid, started.as_deref()));
+    if let Some((pid, started)) = &holder {
+        terminate_pid(*pid, started.as_deref())?;
+    }
+    if path.exists() {
+        std::fs::remove_file(path)
+            .with_context(|| format!("Failed to remove {}", path.display()))?;
+    }
+    Ok(holder.map(|(pid, _)| pid))
+}
+
 /// Spawn the daemon subprocess in the background.
 pub fn spawn_daemon(dir: &Path) -> Result<()> {
     let exe = std::env::current_exe().context("Failed to resolve cryo executable path")?;
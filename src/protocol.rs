@@ -1,6 +1,7 @@
 // src/protocol.rs
-use anyhow::Result;
-use std::path::Path;
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 /// Protocol content written to the agent's working directory as CLAUDE.md or AGENTS.md.
 /// Source: templates/protocol.md
@@ -36,14 +37,93 @@ pub fn protocol_filename(agent_cmd: &str) -> &'static str {
     }
 }
 
+/// Line that separates the built-in protocol from appended house rules.
+pub const TEMPLATE_MARKER: &str = "<!-- cryo: house rules from the protocol template -->";
+
+/// First line of a protocol template that replaces the built-in protocol
+/// instead of being appended to it.
+pub const REPLACE_DIRECTIVE: &str = "<!-- cryo: replace -->";
+
+/// Mechanics a replacement protocol must explain, or sessions never end.
+const REQUIRED_MENTIONS: &[&str] = &["cryo-agent hibernate", "plan.md"];
+
+/// User-level protocol template: `$XDG_CONFIG_HOME/cryo/protocol.md`,
+/// falling back to `~/.config/cryo/protocol.md`. `None` if neither is set.
+pub fn user_template_path() -> Option<PathBuf> {
+    let config_home = std::env::var("XDG_CONFIG_HOME")
+        .ok()
+        .filter(|s| !s.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| {
+            std::env::var("HOME")
+                .ok()
+                .map(|home| PathBuf::from(home).join(".config"))
+        })?;
+    Some(config_home.join("cryo").join("protocol.md"))
+}
+
+/// Read the protocol template for `cryo init`: `explicit` if given (which
+/// must exist), else the user-level template if present.
+pub fn load_template(explicit: Option<&Path>) -> Result<Option<(PathBuf, String)>> {
+    let path = match explicit {
+        Some(path) => path.to_path_buf(),
+        None => match user_template_path() {
+            Some(path) if path.is_file() => path,
+            _ => return Ok(None),
+        },
+    };
+    let content = std::fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read protocol template {}", path.display()))?;
+    Ok(Some((path, content)))
+}
+
+/// Protocol content with `template` merged in. A template starting with
+/// [`REPLACE_DIRECTIVE`] is the whole protocol; any other template is
+/// appended after [`TEMPLATE_MARKER`], keeping the built-in rules intact.
+pub fn render_protocol(template: Option<&str>) -> String {
+    match template {
+        None => PROTOCOL_CONTENT.to_string(),
+        Some(t) => match t.strip_prefix(REPLACE_DIRECTIVE) {
+            Some(body) => body.trim_start().to_string(),
+            None => format!(
+                "{}\n\n{TEMPLATE_MARKER}\n\n{}\n",
+                PROTOCOL_CONTENT.trim_end(),
+                t.trim()
+            ),
+        },
+    }
+}
+
+/// Problems with a protocol template. Only a replacing template can drop
+/// the required mechanics; an appended one keeps the built-in protocol.
+pub fn template_warnings(template: &str) -> Vec<String> {
+    if !template.starts_with(REPLACE_DIRECTIVE) {
+        return Vec::new();
+    }
+    REQUIRED_MENTIONS
+        .iter()
+        .filter(|needle| !template.contains(*needle))
+        .map(|needle| format!("replacement protocol never mentions `{needle}`"))
+        .collect()
+}
+
 /// Write the protocol file to the given directory.
 /// Skips writing if the file already exists (no-clobber). Returns true if written.
 pub fn write_protocol_file(dir: &Path, filename: &str) -> Result<bool> {
+    write_protocol_file_with(dir, filename, None)
+}
+
+/// Like [`write_protocol_file`], merging in a protocol template (see [`render_protocol`]).
+pub fn write_protocol_file_with(
+    dir: &Path,
+    filename: &str,
+    template: Option<&str>,
+) -> Result<bool> {
     let path = dir.join(filename);
     if path.exists() {
         return Ok(false);
     }
-    std::fs::write(path, PROTOCOL_CONTENT)?;
+    std::fs::write(path, render_protocol(template))?;
     Ok(true)
 }
 
@@ -61,14 +141,77 @@ pub fn find_protocol_file(dir: &Path) -> Option<&'static str> {
 
 /// Write a template plan.md if none exists. Returns true if written.
 pub fn write_template_plan(dir: &Path) -> Result<bool> {
+    write_plan(dir, TEMPLATE_PLAN)
+}
+
+/// Write `content` as plan.md if none exists. Returns true if written.
+pub fn write_plan(dir: &Path, content: &str) -> Result<bool> {
     let path = dir.join("plan.md");
     if path.exists() {
         return Ok(false);
     }
-    std::fs::write(path, TEMPLATE_PLAN)?;
+    std::fs::write(path, content)?;
     Ok(true)
 }
 
+/// How long `cryo init --plan-url` waits for the whole download.
+pub const PLAN_FETCH_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Fetch plan content for `cryo init --plan-url`. Fails on a non-text
+/// response, an empty body, or after `timeout`.
+pub fn fetch_plan(url: &str, agent: &ureq::Agent, timeout: Duration) -> Result<String> {
+    let mut resp = agent
+        .get(url)
+        .config()
+        .timeout_global(Some(timeout))
+        .build()
+        .call()
+        .with_context(|| format!("Failed to fetch plan from {url}"))?;
+    let content_type = resp
+        .headers()
+        .get("content-type")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("")
+        .to_lowercase();
+    if !content_type.is_empty()
+        && !content_type.starts_with("text/")
+        && !content_type.contains("markdown")
+    {
+        anyhow::bail!("{url} returned {content_type}, not a text plan");
+    }
+    let body = resp
+        .body_mut()
+        .read_to_string()
+        .with_context(|| format!("{url} did not return UTF-8 text"))?;
+    if body.trim().is_empty() {
+        anyhow::bail!("{url} returned an empty plan");
+    }
+    Ok(body)
+}
+
+/// Hints that `content` is not the Markdown plan it was meant to be (an
+/// HTML page behind a wiki link, plain prose without structure).
+pub fn plan_warnings(content: &str) -> Vec<String> {
+    let head = content.trim_start().to_lowercase();
+    if head.starts_with("<!doctype html") || head.starts_with("<html") {
+        return vec!["looks like an HTML page, not Markdown (use the raw URL)".to_string()];
+    }
+    let structured = content.lines().map(str::trim_start).any(|line| {
+        line.starts_with('#')
+            || line.starts_with("- ")
+            || line.starts_with("* ")
+            || line.starts_with("```")
+            || line
+                .split_once(". ")
+                .is_some_and(|(n, _)| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+    });
+    if structured {
+        Vec::new()
+    } else {
+        vec!["has no headings or lists; it may not be Markdown".to_string()]
+    }
+}
+
 /// Write cryo.toml config file if none exists. Returns true if written.
 /// Substitutes `{{agent}}` with the given agent command.
 pub fn write_config_file(dir: &Path, agent_cmd: &str) -> Result<bool> {
@@ -0,0 +1,207 @@
+// src/recording.rs
+//! Opt-in session recordings (`record_session = true`): the exact prompt,
+//! agent argv, provider env keys and captured output of one agent run,
+//! saved to `.cryo/recordings/session-<n>.json` for `cryo replay`.
+use anyhow::{Context, Result};
+use chrono::{Local, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SessionRecording {
+    pub session: u32,
+    pub started_at: NaiveDateTime,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<NaiveDateTime>,
+    /// Agent command from cryo.toml (or the `--agent` override)
+    pub agent: String,
+    /// Resolved program and arguments, prompt included
+    pub argv: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+    /// Provider env var names; values are always `***`
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    pub prompt: String,
+    #[serde(default)]
+    pub stdout: String,
+    #[serde(default)]
+    pub stderr: String,
+}
+
+pub fn recordings_dir(dir: &Path) -> PathBuf {
+    dir.join(".cryo").join("recordings")
+}
+
+pub fn recording_path(dir: &Path, session: u32) -> PathBuf {
+    recordings_dir(dir).join(format!("session-{session}.json"))
+}
+
+pub fn save(dir: &Path, recording: &SessionRecording) -> Result<PathBuf> {
+    let path = recording_path(dir, recording.session);
+    std::fs::create_dir_all(recordings_dir(dir))?;
+    std::fs::write(&path, serde_json::to_string_pretty(recording)?)?;
+    Ok(path)
+}
+
+pub fn load(dir: &Path, session: u32) -> Result<SessionRecording> {
+    let path = recording_path(dir, session);
+    let contents = std::fs::read_to_string(&path)
+        .with_context(|| format!("No recording for session {session}"))?;
+    serde_json::from_str(&contents).with_context(|| format!("Corrupt recording {}", path.display()))
+}
+
+/// Session numbers that have a recording, oldest first.
+pub fn list(dir: &Path) -> Result<Vec<u32>> {
+    let rec_dir = recordings_dir(dir);
+    if !rec_dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut sessions: Vec<u32> = std::fs::read_dir(rec_dir)?
+        .filter_map(|e| e.ok())
+        .filter_map(|e| {
+            let name = e.file_name().to_string_lossy().to_string();
+            name.strip_prefix("session-")?
+                .strip_suffix(".json")?
+                .parse()
+                .ok()
+        })
+        .collect();
+    sessions.sort_unstable();
+    Ok(sessions)
+}
+
+/// Delete all but the newest `keep` recordings (0 = keep all).
+/// Returns how many were removed.
+pub fn prune(dir: &Path, keep: usize) -> Result<usize> {
+    if keep == 0 {
+        return Ok(0);
+    }
+    let sessions = list(dir)?;
+    let excess = sessions.len().saturating_sub(keep);
+    for session in &sessions[..excess] {
+        std::fs::remove_file(recording_path(dir, *session))?;
+    }
+    Ok(excess)
+}
+
+/// Agent stdout/stderr collected by the reader threads of
+/// [`crate::agent::spawn_agent_recorded`].
+#[derive(Clone, Default)]
+pub struct OutputCapture {
+    stdout: Arc<Mutex<String>>,
+    stderr: Arc<Mutex<String>>,
+}
+
+impl OutputCapture {
+    pub(crate) fn stdout_sink(&self) -> Arc<Mutex<String>> {
+        Arc::clone(&self.stdout)
+    }
+
+    pub(crate) fn stderr_sink(&self) -> Arc<Mutex<String>> {
+        Arc::clone(&self.stderr)
+    }
+
+    /// What the reader threads captured. The daemon waits for them (see
+    /// [`crate::agent::OutputCopy`]) before the recording is saved.
+    pub fn finish(&self) -> (String, String) {
+        (
+            self.stdout.lock().unwrap().clone(),
+            self.stderr.lock().unwrap().clone(),
+        )
+    }
+}
+
+/// Saves its recording when dropped, so every way a session can end
+/// (hibernate, crash, timeout, shutdown) leaves one behind.
+pub struct Recorder {
+    dir: PathBuf,
+    keep: usize,
+    recording: SessionRecording,
+    capture: OutputCapture,
+}
+
+impl Recorder {
+    pub fn new(dir: &Path, keep: usize, recording: SessionRecording) -> Self {
+        Self {
+            dir: dir.to_path_buf(),
+            keep,
+            recording,
+            capture: OutputCapture::default(),
+        }
+    }
+
+    pub fn capture(&self) -> &OutputCapture {
+        &self.capture
+    }
+}
+
+impl Drop for Recorder {
+    fn drop(&mut self) {
+        let (stdout, stderr) = self.capture.finish();
+        self.recording.stdout = stdout;
+        self.recording.stderr = stderr;
+        self.recording.finished_at = Some(Local::now().naive_local());
+        let saved = save(&self.dir, &self.recording).and_then(|_| prune(&self.dir, self.keep));
+        if let Err(e) = saved {
+            eprintln!("Daemon: failed to save session recording: {e}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recording(session: u32) -> SessionRecording {
+        SessionRecording {
+            session,
+            agent: "mock".to_string(),
+            prompt: format!("prompt {session}"),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_save_load_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut rec = recording(3);
+        rec.env
+            .insert("ANTHROPIC_API_KEY".to_string(), "***".to_string());
+        let path = save(dir.path(), &rec).unwrap();
+        assert!(path.ends_with(".cryo/recordings/session-3.json"));
+        assert_eq!(load(dir.path(), 3).unwrap(), rec);
+        assert!(load(dir.path(), 4).is_err());
+    }
+
+    #[test]
+    fn test_prune_keeps_newest() {
+        let dir = tempfile::tempdir().unwrap();
+        for n in [1, 2, 10, 11] {
+            save(dir.path(), &recording(n)).unwrap();
+        }
+        assert_eq!(list(dir.path()).unwrap(), vec![1, 2, 10, 11]);
+        assert_eq!(prune(dir.path(), 0).unwrap(), 0);
+        assert_eq!(prune(dir.path(), 2).unwrap(), 2);
+        assert_eq!(list(dir.path()).unwrap(), vec![10, 11]);
+    }
+
+    #[test]
+    fn test_recorder_saves_on_drop() {
+        let dir = tempfile::tempdir().unwrap();
+        {
+            let recorder = Recorder::new(dir.path(), 20, recording(1));
+            recorder
+                .capture()
+                .stdout_sink()
+                .lock()
+                .unwrap()
+                .push_str("hello\n");
+        }
+        let rec = load(dir.path(), 1).unwrap();
+        assert_eq!(rec.stdout, "hello\n");
+        assert!(rec.finished_at.is_some());
+    }
+}
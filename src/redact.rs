@@ -0,0 +1,159 @@
+// src/redact.rs
+//! Scrub secrets (provider API keys, user-supplied patterns) from text
+//! before it is written to logs or handed to the agent.
+use anyhow::{Context, Result};
+use regex::Regex;
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::config::CryoConfig;
+
+/// Replacement text for redacted secrets.
+pub const REDACTED: &str = "***";
+
+/// Provider env vars whose name contains one of these are treated as secrets.
+/// Other values (model names, base URLs) stay readable in the logs.
+const SECRET_KEY_MARKERS: &[&str] = &["KEY", "TOKEN", "SECRET", "PASSWORD", "AUTH", "CREDENTIAL"];
+
+fn is_secret_var(name: &str) -> bool {
+    let upper = name.to_ascii_uppercase();
+    SECRET_KEY_MARKERS.iter().any(|m| upper.contains(m))
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Redactor {
+    secrets: Vec<String>,
+    patterns: Vec<Regex>,
+}
+
+impl Redactor {
+    /// Build a redactor from secret-looking provider env values plus
+    /// `redact_patterns`.
+    pub fn from_config(config: &CryoConfig) -> Result<Self> {
+        let envs: Vec<_> = config.providers.iter().map(|p| p.env.clone()).collect();
+        Self::build(config, &envs)
+    }
+
+    /// Like [`Redactor::from_config`], but also scrubs values loaded from
+    /// each provider's `env_file` in the project `dir`.
+    pub fn for_project(config: &CryoConfig, dir: &Path) -> Result<Self> {
+        let envs = config
+            .providers
+            .iter()
+            .map(|p| p.load_env(dir))
+            .collect::<Result<Vec<_>>>()?;
+        Self::build(config, &envs)
+    }
+
+    fn build(config: &CryoConfig, envs: &[HashMap<String, String>]) -> Result<Self> {
+        let mut secrets: Vec<String> = envs
+            .iter()
+            .flat_map(|env| env.iter())
+            .filter(|(k, v)| is_secret_var(k) && !v.is_empty())
+            .map(|(_, v)| v.clone())
+            .collect();
+        // Longest first, so a secret containing another is replaced whole.
+        secrets.sort_by_key(|s| std::cmp::Reverse(s.len()));
+        secrets.dedup();
+
+        let patterns = config
+            .redact_patterns
+            .iter()
+            .map(|p| Regex::new(p).with_context(|| format!("Invalid redact pattern: {p}")))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self { secrets, patterns })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.secrets.is_empty() && self.patterns.is_empty()
+    }
+
+    /// Replace every known secret and pattern match in `text` with `***`.
+    pub fn redact(&self, text: &str) -> String {
+        let mut out = text.to_string();
+        for secret in &self.secrets {
+            if out.contains(secret.as_str()) {
+                out = out.replace(secret.as_str(), REDACTED);
+            }
+        }
+        for re in &self.patterns {
+            out = re.replace_all(&out, REDACTED).into_owned();
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ProviderConfig;
+
+    fn config_with_key(key: &str) -> CryoConfig {
+        CryoConfig {
+            providers: vec![ProviderConfig {
+                name: "anthropic".to_string(),
+                env: HashMap::from([
+                    ("ANTHROPIC_API_KEY".to_string(), key.to_string()),
+                    ("OPENCODE_MODEL".to_string(), "claude-sonnet".to_string()),
+                ]),
+                env_file: None,
+                max_session_duration: None,
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_redacts_provider_env_values() {
+        let r = Redactor::from_config(&config_with_key("sk-ant-secret-123")).unwrap();
+        assert_eq!(
+            r.redact("key=sk-ant-secret-123 model=claude-sonnet"),
+            "key=*** model=claude-sonnet",
+            "non-secret env values must stay readable"
+        );
+    }
+
+    #[test]
+    fn test_redacts_patterns() {
+        let config = CryoConfig {
+            redact_patterns: vec![r"ghp_[A-Za-z0-9]+".to_string()],
+            ..Default::default()
+        };
+        let r = Redactor::from_config(&config).unwrap();
+        assert_eq!(r.redact("token ghp_abc123 used"), "token *** used");
+    }
+
+    #[test]
+    fn test_invalid_pattern_is_error() {
+        let config = CryoConfig {
+            redact_patterns: vec!["(unclosed".to_string()],
+            ..Default::default()
+        };
+        assert!(Redactor::from_config(&config).is_err());
+    }
+
+    #[test]
+    fn test_for_project_redacts_env_file_values() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("keys.env"), "OPENAI_API_KEY=sk-from-file\n").unwrap();
+        let config = CryoConfig {
+            providers: vec![ProviderConfig {
+                name: "openai".to_string(),
+                env: HashMap::new(),
+                env_file: Some("keys.env".to_string()),
+                max_session_duration: None,
+            }],
+            ..Default::default()
+        };
+        let r = Redactor::for_project(&config, dir.path()).unwrap();
+        assert_eq!(r.redact("key=sk-from-file"), "key=***");
+    }
+
+    #[test]
+    fn test_default_is_noop() {
+        let r = Redactor::default();
+        assert!(r.is_empty());
+        assert_eq!(r.redact("sk-anything"), "sk-anything");
+    }
+}
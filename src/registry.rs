@@ -3,7 +3,12 @@
 //!
 //! Each daemon registers itself in `$XDG_RUNTIME_DIR/cryo/` (or `~/.cryo/daemons/`)
 //! on startup and removes the file on clean exit. `cryo ps` reads the directory
-//! to list all known daemons. Stale entries (dead PIDs) are auto-cleaned on read.
+//! to list all known daemons. Stale entries are auto-cleaned on read: a dead
+//! PID, a PID reused by another process (start time differs), or a process
+//! that isn't `cryo daemon`. The exception is a daemon that died without
+//! unregistering while its project's `timer.json` still records its PID: that
+//! entry is kept and listed as crashed until the project is restarted or
+//! cancelled.
 
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
@@ -15,14 +20,30 @@ pub struct DaemonEntry {
     pub dir: String,
     #[serde(default)]
     pub socket_path: Option<String>,
+    /// Process start time, to tell a reused PID from the daemon that registered.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_time: Option<String>,
+    /// Local wall-clock time the daemon registered (`%Y-%m-%dT%H:%M:%S`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<String>,
+    /// Set by [`list`] when the process is gone but `timer.json` still
+    /// names it as the running daemon.
+    #[serde(skip)]
+    pub crashed: bool,
 }
 
 /// Return the registry directory, creating it if needed.
 ///
-/// Prefers `$XDG_RUNTIME_DIR/cryo/` (auto-cleaned on reboot by the OS),
-/// falls back to `~/.cryo/daemons/`.
+/// Uses `$CRYO_RUNTIME_DIR/daemons/` if set, else prefers
+/// `$XDG_RUNTIME_DIR/cryo/` (auto-cleaned on reboot by the OS),
+/// falls back to `~/.cryo/daemons/`. A project's `runtime_dir` in cryo.toml
+/// is not consulted: the registry is shared by every project, and `cryo ps`
+/// has to find it without one.
 fn registry_dir() -> Result<PathBuf> {
-    let dir = if let Ok(runtime) = std::env::var("XDG_RUNTIME_DIR") {
+    let cryo_runtime = std::env::var(crate::socket::RUNTIME_DIR_ENV).ok();
+    let dir = if let Some(runtime) = cryo_runtime.filter(|s| !s.is_empty()) {
+        PathBuf::from(runtime).join("daemons")
+    } else if let Ok(runtime) = std::env::var("XDG_RUNTIME_DIR") {
         PathBuf::from(runtime).join("cryo")
     } else {
         let home = std::env::var("HOME").context("HOME not set")?;
@@ -32,12 +53,15 @@ fn registry_dir() -> Result<PathBuf> {
     Ok(dir)
 }
 
+/// Stable key for a given working directory (16 hex digits): FNV-1a, so
+/// every cryo build finds the same registry entry and runtime dir.
+pub fn dir_key(dir: &Path) -> String {
+    format!("{:016x}", crate::fnv1a(dir.as_os_str().as_encoded_bytes()))
+}
+
 /// Stable filename for a given working directory.
 fn entry_filename(dir: &Path) -> String {
-    use std::hash::{Hash, Hasher};
-    let mut hasher = std::collections::hash_map::DefaultHasher::new();
-    dir.hash(&mut hasher);
-    format!("{:016x}.json", hasher.finish())
+    format!("{}.json", dir_key(dir))
 }
 
 /// Register this daemon in the global registry.
@@ -47,6 +71,14 @@ pub fn register(dir: &Path, socket_path: Option<&Path>) -> Result<()> {
         pid: std::process::id(),
         dir: dir.to_string_lossy().to_string(),
         socket_path: socket_path.map(|p| p.to_string_lossy().to_string()),
+        start_time: crate::process::start_time(std::process::id()),
+        started_at: Some(
+            chrono::Local::now()
+                .naive_local()
+                .format("%Y-%m-%dT%H:%M:%S")
+                .to_string(),
+        ),
+        crashed: false,
     };
     let path = reg.join(entry_filename(dir));
     std::fs::write(&path, serde_json::to_string(&entry)?)?;
@@ -61,14 +93,18 @@ pub fn unregister(dir: &Path) {
     }
 }
 
-/// List all registered daemons. Dead entries are auto-cleaned.
+/// List all registered daemons, including crashed ones (see [`DaemonEntry::crashed`]).
+/// Other stale entries are auto-cleaned.
 pub fn list() -> Result<Vec<DaemonEntry>> {
-    let reg = registry_dir()?;
+    list_in(&registry_dir()?)
+}
+
+fn list_in(reg: &Path) -> Result<Vec<DaemonEntry>> {
     let mut alive = Vec::new();
 
-    let dir = match std::fs::read_dir(&reg) {
+    let dir = match std::fs::read_dir(reg) {
         Ok(d) => d,
-        Err(_) => return Ok(alive),
+        Err(_) => return Ok(Vec::new()),
     };
 
     for file in dir {
@@ -88,24 +124,117 @@ pub fn list() -> Result<Vec<DaemonEntry>> {
             }
         };
 
-        if is_pid_alive(entry.pid) {
-            alive.push(entry);
+        if is_live_daemon(&entry) {
+            alive.push((file.path(), entry));
+        } else if state_claims_pid(&entry) {
+            alive.push((
+                file.path(),
+                DaemonEntry {
+                    crashed: true,
+                    ..entry
+                },
+            ));
         } else {
             // Auto-clean stale entry
             let _ = std::fs::remove_file(file.path());
         }
     }
 
-    Ok(alive)
+    let (keep, dropped) = dedup_by_dir(alive);
+    for path in dropped {
+        let _ = std::fs::remove_file(path);
+    }
+    Ok(keep)
 }
 
-fn is_pid_alive(pid: u32) -> bool {
-    let ret = unsafe { libc::kill(pid as i32, 0) };
-    if ret == 0 {
-        return true;
+/// True if `entry.pid` is still the cryo daemon that registered it.
+fn is_live_daemon(entry: &DaemonEntry) -> bool {
+    if !crate::process::is_alive(entry.pid) {
+        return false;
+    }
+    // A matching start time identifies the process whatever its command
+    // line, e.g. a supervisor embedding the daemon with `Daemon::spawn`.
+    // Entries written before start times were recorded can only be checked
+    // by command line.
+    match &entry.start_time {
+        Some(recorded) => crate::process::start_time(entry.pid).as_ref() == Some(recorded),
+        None => is_cryo_daemon(entry.pid),
     }
-    let errno = std::io::Error::last_os_error().raw_os_error().unwrap_or(0);
-    errno == libc::EPERM
+}
+
+/// True if the project's `timer.json` still holds `entry.pid` as its lock.
+/// A daemon that exits cleanly clears the PID, so a dead entry that is still
+/// claimed crashed (or was killed) without cleaning up.
+fn state_claims_pid(entry: &DaemonEntry) -> bool {
+    crate::state::load_state(&crate::state::state_path(Path::new(&entry.dir)))
+        .ok()
+        .flatten()
+        .is_some_and(|st| st.pid == Some(entry.pid))
+}
+
+/// Keep one entry per project directory. Different spellings of the same
+/// path (symlinks, trailing components) hash to different files, so compare
+/// canonical paths. When a directory has several entries, prefer the PID
+/// holding the project's `timer.json` lock. Returns the kept entries and the
+/// registry files of the dropped ones.
+fn dedup_by_dir(entries: Vec<(PathBuf, DaemonEntry)>) -> (Vec<DaemonEntry>, Vec<PathBuf>) {
+    let mut by_dir: Vec<(PathBuf, Vec<(PathBuf, DaemonEntry)>)> = Vec::new();
+    for (file, entry) in entries {
+        let dir = std::fs::canonicalize(&entry.dir).unwrap_or_else(|_| PathBuf::from(&entry.dir));
+        match by_dir.iter_mut().find(|(d, _)| *d == dir) {
+            Some((_, group)) => group.push((file, entry)),
+            None => by_dir.push((dir, vec![(file, entry)])),
+        }
+    }
+
+    let mut keep = Vec::new();
+    let mut dropped = Vec::new();
+    for (dir, mut group) in by_dir {
+        let owner = crate::state::load_state(&crate::state::state_path(&dir))
+            .ok()
+            .flatten()
+            .and_then(|st| st.pid);
+        let best = group
+            .iter()
+            .position(|(_, e)| Some(e.pid) == owner)
+            .unwrap_or(0);
+        keep.push(group.swap_remove(best).1);
+        dropped.extend(group.into_iter().map(|(file, _)| file));
+    }
+    (keep, dropped)
+}
+
+/// Command-line arguments of `pid`.
+#[cfg(target_os = "linux")]
+fn process_args(pid: u32) -> Option<Vec<String>> {
+    let raw = std::fs::read(format!("/proc/{pid}/cmdline")).ok()?;
+    Some(
+        raw.split(|&b| b == 0)
+            .filter(|a| !a.is_empty())
+            .map(|a| String::from_utf8_lossy(a).into_owned())
+            .collect(),
+    )
+}
+
+#[cfg(not(target_os = "linux"))]
+fn process_args(pid: u32) -> Option<Vec<String>> {
+    crate::process::ps_field(pid, "command=")
+        .map(|c| c.split_whitespace().map(String::from).collect())
+}
+
+/// True if `pid` runs `cryo daemon`, for entries without a start time. If
+/// the command line can't be read (e.g. another user's process), trust the
+/// PID check alone.
+fn is_cryo_daemon(pid: u32) -> bool {
+    let Some(args) = process_args(pid) else {
+        return true;
+    };
+    let is_cryo = args.first().is_some_and(|exe| {
+        Path::new(exe)
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy() == "cryo")
+    });
+    is_cryo && args.get(1).is_some_and(|a| a == "daemon")
 }
 
 #[cfg(test)]
@@ -118,8 +247,177 @@ mod tests {
             pid: 1234,
             dir: "/tmp/test".to_string(),
             socket_path: Some("/tmp/test/.cryo/cryo.sock".to_string()),
+            start_time: None,
+            started_at: None,
+            crashed: false,
         };
         let json = serde_json::to_string(&entry).unwrap();
         assert!(json.contains("cryo.sock"));
     }
+
+    #[test]
+    fn test_dir_key_is_stable() {
+        // Pinned: a different key would orphan running daemons' sockets
+        assert_eq!(dir_key(Path::new("/tmp/project")), "ebab4cfadaecf751");
+    }
+
+    fn write_entry(reg: &Path, name: &str, entry: &DaemonEntry) -> PathBuf {
+        let path = reg.join(format!("{name}.json"));
+        std::fs::write(&path, serde_json::to_string(entry).unwrap()).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_list_cleans_dead_pid() {
+        let reg = tempfile::tempdir().unwrap();
+        let mut child = std::process::Command::new("true").spawn().unwrap();
+        let pid = child.id();
+        child.wait().unwrap();
+        let path = write_entry(
+            reg.path(),
+            "dead",
+            &DaemonEntry {
+                pid,
+                dir: "/tmp/gone".to_string(),
+                socket_path: None,
+                start_time: None,
+                started_at: None,
+                crashed: false,
+            },
+        );
+
+        assert!(list_in(reg.path()).unwrap().is_empty());
+        assert!(!path.exists(), "stale entry should be removed");
+    }
+
+    #[test]
+    fn test_list_keeps_crashed_daemon() {
+        let reg = tempfile::tempdir().unwrap();
+        let project = tempfile::tempdir().unwrap();
+        let mut child = std::process::Command::new("true").spawn().unwrap();
+        let pid = child.id();
+        child.wait().unwrap();
+        // The daemon died without clearing its lock in timer.json
+        let state = crate::state::CryoState {
+            pid: Some(pid),
+            ..Default::default()
+        };
+        crate::state::save_state(&crate::state::state_path(project.path()), &state).unwrap();
+        let path = write_entry(
+            reg.path(),
+            "crashed",
+            &DaemonEntry {
+                pid,
+                dir: project.path().to_string_lossy().to_string(),
+                socket_path: None,
+                start_time: None,
+                started_at: None,
+                crashed: false,
+            },
+        );
+
+        let entries = list_in(reg.path()).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert!(entries[0].crashed);
+        assert!(path.exists(), "crashed entry should be kept");
+
+        // Once the lock is released (restart or cancel), the entry is stale
+        crate::state::save_state(
+            &crate::state::state_path(project.path()),
+            &crate::state::CryoState::default(),
+        )
+        .unwrap();
+        assert!(list_in(reg.path()).unwrap().is_empty());
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_list_rejects_reused_pid() {
+        let reg = tempfile::tempdir().unwrap();
+        let pid = std::process::id();
+        // Same PID, different start time: the PID was recycled
+        let reused = write_entry(
+            reg.path(),
+            "reused",
+            &DaemonEntry {
+                pid,
+                dir: "/tmp/a".to_string(),
+                socket_path: None,
+                start_time: Some("0".to_string()),
+                started_at: None,
+                crashed: false,
+            },
+        );
+        // No start time to go by, and a test binary is not `cryo daemon`
+        let foreign = write_entry(
+            reg.path(),
+            "foreign",
+            &DaemonEntry {
+                pid,
+                dir: "/tmp/b".to_string(),
+                socket_path: None,
+                start_time: None,
+                started_at: None,
+                crashed: false,
+            },
+        );
+
+        assert!(list_in(reg.path()).unwrap().is_empty());
+        assert!(!reused.exists());
+        assert!(!foreign.exists());
+    }
+
+    #[test]
+    fn test_list_keeps_embedded_daemon() {
+        let reg = tempfile::tempdir().unwrap();
+        let pid = std::process::id();
+        // A daemon run with `Daemon::spawn` inside another program: the
+        // start time matches even though the command line isn't `cryo daemon`
+        let embedded = write_entry(
+            reg.path(),
+            "embedded",
+            &DaemonEntry {
+                pid,
+                dir: "/tmp/c".to_string(),
+                socket_path: None,
+                start_time: crate::process::start_time(pid),
+                started_at: None,
+                crashed: false,
+            },
+        );
+
+        let entries = list_in(reg.path()).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].dir, "/tmp/c");
+        assert!(embedded.exists());
+    }
+
+    #[test]
+    fn test_dedup_prefers_lock_holder() {
+        let project = tempfile::tempdir().unwrap();
+        let state = crate::state::CryoState {
+            pid: Some(222),
+            ..Default::default()
+        };
+        crate::state::save_state(&crate::state::state_path(project.path()), &state).unwrap();
+        // Same directory spelled two ways
+        let plain = project.path().to_string_lossy().to_string();
+        let dotted = project.path().join(".").to_string_lossy().to_string();
+        let entry = |pid, dir: &str| DaemonEntry {
+            pid,
+            dir: dir.to_string(),
+            socket_path: None,
+            start_time: None,
+            started_at: None,
+            crashed: false,
+        };
+
+        let (keep, dropped) = dedup_by_dir(vec![
+            (PathBuf::from("a.json"), entry(111, &plain)),
+            (PathBuf::from("b.json"), entry(222, &dotted)),
+        ]);
+        assert_eq!(keep.len(), 1);
+        assert_eq!(keep[0].pid, 222);
+        assert_eq!(dropped, vec![PathBuf::from("a.json")]);
+    }
 }
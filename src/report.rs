@@ -1,8 +1,12 @@
 use anyhow::Result;
 use chrono::{NaiveDateTime, NaiveTime, Utc};
+use std::collections::BTreeMap;
 use std::path::Path;
 
 use crate::log::{self, SessionOutcome};
+use crate::notifier::Notifier;
+use crate::store::MessageStore;
+use crate::usage::Usage;
 
 /// Aggregated report for a time period.
 #[derive(Debug, Clone)]
@@ -10,44 +14,262 @@ pub struct ReportSummary {
     pub total_sessions: usize,
     pub failed_sessions: usize,
     pub period_hours: u64,
+    /// Custom agent events (`cryo-agent event`) counted by kind.
+    pub events: BTreeMap<String, usize>,
+    /// `(failed, total)` sessions per provider, for sessions that logged one.
+    pub providers: BTreeMap<String, (usize, usize)>,
+    /// Tokens and cost summed over sessions that logged a `usage:` event;
+    /// `None` if none did.
+    pub usage: Option<Usage>,
 }
 
-/// Generate a report summarizing sessions in the given time window.
-pub fn generate_report(log_path: &Path, since: NaiveDateTime) -> Result<ReportSummary> {
-    let summaries = log::parse_sessions_since(log_path, since)?;
-    let failed = summaries
+impl ReportSummary {
+    /// "12345 tokens, $0.42"
+    pub fn usage_line(&self) -> Option<String> {
+        self.usage
+            .map(|u| format!("{} tokens, ${:.2}", u.tokens, u.cost))
+    }
+
+    /// "provider-1 failed 3/5, provider-2 failed 0/2"
+    pub fn provider_line(&self) -> Option<String> {
+        if self.providers.is_empty() {
+            return None;
+        }
+        let parts: Vec<String> = self
+            .providers
+            .iter()
+            .map(|(name, (failed, total))| format!("{name} failed {failed}/{total}"))
+            .collect();
+        Some(parts.join(", "))
+    }
+}
+
+fn is_failure(outcome: &SessionOutcome) -> bool {
+    matches!(
+        outcome,
+        SessionOutcome::Failed | SessionOutcome::Interrupted
+    )
+}
+
+/// Generate a report summarizing sessions in the given time window
+/// (`since` is UTC; `format` is how cryo.log was written).
+pub fn generate_report(
+    log_path: &Path,
+    since: NaiveDateTime,
+    format: &log::LogFormat,
+) -> Result<ReportSummary> {
+    let summaries = log::parse_sessions_since(log_path, since, format)?;
+    let failed = summaries.iter().filter(|s| is_failure(&s.outcome)).count();
+    let mut events = BTreeMap::new();
+    for kind in summaries.iter().flat_map(|s| &s.events) {
+        *events.entry(kind.clone()).or_insert(0) += 1;
+    }
+    let mut providers: BTreeMap<String, (usize, usize)> = BTreeMap::new();
+    for s in &summaries {
+        if let Some(name) = &s.provider {
+            let entry = providers.entry(name.clone()).or_default();
+            entry.0 += usize::from(is_failure(&s.outcome));
+            entry.1 += 1;
+        }
+    }
+    let usage = summaries
         .iter()
-        .filter(|s| {
-            matches!(
-                s.outcome,
-                SessionOutcome::Failed | SessionOutcome::Interrupted
-            )
-        })
-        .count();
+        .filter_map(|s| s.usage)
+        .reduce(|mut total, u| {
+            total.add(u);
+            total
+        });
     let now = Utc::now().naive_utc();
     let period_hours = (now - since).num_hours().max(0) as u64;
     Ok(ReportSummary {
         total_sessions: summaries.len(),
         failed_sessions: failed,
         period_hours,
+        events,
+        providers,
+        usage,
     })
 }
 
-/// Send a desktop notification with the report summary.
-pub fn send_report_notification(summary: &ReportSummary, project_name: &str) -> Result<()> {
+/// Structured header for a session pushed to GitHub or Zulip.
+#[derive(Debug, Clone)]
+pub struct SessionDigest {
+    pub session_number: u32,
+    /// "outcome — summary" of the last session, from timer.json
+    pub result: Option<String>,
+    /// `(checked, total)` plan.md checkboxes, if the plan has any
+    pub plan_progress: Option<(usize, usize)>,
+    /// Inbox messages (pending or archived) newer than the last push
+    pub messages_received: usize,
+    /// Outbox messages (pending or delivered) newer than the last push
+    pub messages_sent: usize,
+    /// Last session outcome alone ("success", "failed", ...)
+    pub outcome: Option<String>,
+    /// Last session's hibernate summary
+    pub summary: Option<String>,
+    /// Notes left in the latest session that has any
+    pub notes: Vec<String>,
+    /// Next scheduled wake, or "waiting for message"
+    pub wake: Option<String>,
+}
+
+impl SessionDigest {
+    /// Gather the digest of session `session_number` for the project in
+    /// `dir`, counting messages newer than `since` (all messages if None).
+    /// The caller names the session, since timer.json may already count
+    /// the next one.
+    pub fn collect(dir: &Path, session_number: u32, since: Option<NaiveDateTime>) -> Result<Self> {
+        let state = crate::state::load_state(&crate::state::state_path(dir))?.unwrap_or_default();
+        let plan_progress = std::fs::read_to_string(dir.join("plan.md"))
+            .ok()
+            .and_then(|plan| crate::session::plan_progress(&plan));
+        let is_new = |ts: &NaiveDateTime| since.is_none_or(|s| *ts > s);
+
+        let store = crate::store::open(dir)?;
+        let messages_received = store
+            .read_inbox()?
+            .into_iter()
+            .chain(store.read_inbox_archive()?)
+            .filter(|(_, m)| is_new(&m.timestamp))
+            .count();
+        let messages_sent = store
+            .read_outbox()?
+            .into_iter()
+            .map(|(_, m)| m)
+            .chain(store.read_outbox_archive()?.into_iter().map(|(_, m, _)| m))
+            .filter(|m| is_new(&m.timestamp))
+            .count();
+
+        let notes = log::parse_latest_session_notes(&log::log_path(dir))?;
+        let wake = if state.awaiting_message {
+            Some("waiting for message".to_string())
+        } else {
+            state.next_wake.clone()
+        };
+
+        Ok(Self {
+            session_number,
+            result: state.last_result(),
+            plan_progress,
+            messages_received,
+            messages_sent,
+            outcome: state.last_outcome.as_ref().map(|o| o.to_string()),
+            summary: state.last_summary.clone(),
+            notes,
+            wake,
+        })
+    }
+
+    /// Render with the user's `summary_template`, or the built-in header if None.
+    pub fn render(&self, template: Option<&str>) -> String {
+        match template {
+            Some(template) => self.render_template(template),
+            None => self.render_header(),
+        }
+    }
+
+    /// Fill `{name}` placeholders in `template`. Unknown placeholders and
+    /// unmatched braces are left as written; missing values render empty.
+    pub fn render_template(&self, template: &str) -> String {
+        let mut out = String::with_capacity(template.len());
+        let mut rest = template;
+        while let Some(open) = rest.find('{') {
+            out.push_str(&rest[..open]);
+            let after = &rest[open + 1..];
+            match after.find('}').and_then(|close| {
+                let value = self.placeholder(&after[..close])?;
+                Some((close, value))
+            }) {
+                Some((close, value)) => {
+                    out.push_str(&value);
+                    rest = &after[close + 1..];
+                }
+                None => {
+                    out.push('{');
+                    rest = after;
+                }
+            }
+        }
+        out.push_str(rest);
+        out
+    }
+
+    fn placeholder(&self, name: &str) -> Option<String> {
+        Some(match name {
+            "session" => self.session_number.to_string(),
+            "outcome" => self.outcome.clone().unwrap_or_default(),
+            "summary" => self.summary.clone().unwrap_or_default(),
+            "notes" => self.notes.join("; "),
+            "wake" => self.wake.clone().unwrap_or_default(),
+            "replies" => self.messages_sent.to_string(),
+            "received" => self.messages_received.to_string(),
+            "plan" => self
+                .plan_progress
+                .map(|(checked, total)| format!("{checked}/{total}"))
+                .unwrap_or_default(),
+            _ => return None,
+        })
+    }
+
+    /// Render as a Markdown heading plus one bullet per field.
+    pub fn render_header(&self) -> String {
+        let mut out = format!("## Session {}\n\n", self.session_number);
+        if let Some(result) = &self.result {
+            out.push_str(&format!("- **Result:** {result}\n"));
+        }
+        if let Some((checked, total)) = self.plan_progress {
+            out.push_str(&format!("- **Plan:** {checked}/{total} tasks done\n"));
+        }
+        out.push_str(&format!(
+            "- **Messages since last push:** {} received, {} sent\n",
+            self.messages_received, self.messages_sent
+        ));
+        out
+    }
+}
+
+/// Send the report summary as a desktop notification, or write it to the
+/// outbox when `notifier` has none. `header` is the [`SessionDigest`]
+/// rendered with the user's `summary_template`, if one is set; it goes
+/// above the period counts. Returns the notifier's warning the first time
+/// it falls back, for the caller to log.
+pub fn send_report_notification(
+    summary: &ReportSummary,
+    header: Option<&str>,
+    project_name: &str,
+    notifier: &Notifier,
+    store: &dyn MessageStore,
+) -> Result<Option<String>> {
     let period_label = match summary.period_hours {
         0..=23 => format!("{}h", summary.period_hours),
         24..=167 => format!("{}d", summary.period_hours / 24),
         _ => format!("{}w", summary.period_hours / 168),
     };
-    let body = format!(
+    let mut body = match header {
+        Some(header) => format!("{}\n\n", header.trim_end()),
+        None => String::new(),
+    };
+    body.push_str(&format!(
         "Last {}: {} sessions, {} failed",
         period_label, summary.total_sessions, summary.failed_sessions,
-    );
+    ));
+    if !summary.events.is_empty() {
+        let counts: Vec<String> = summary
+            .events
+            .iter()
+            .map(|(kind, n)| format!("{kind} ×{n}"))
+            .collect();
+        body.push_str(&format!("\nEvents: {}", counts.join(", ")));
+    }
+    if let Some(providers) = summary.provider_line() {
+        body.push_str(&format!("\nProviders: {providers}"));
+    }
+    if let Some(usage) = summary.usage_line() {
+        body.push_str(&format!("\nUsage: {usage}"));
+    }
+    let title = format!("Cryochamber Report: {}", project_name);
     let mut notification = notify_rust::Notification::new();
-    notification
-        .summary(&format!("Cryochamber Report: {}", project_name))
-        .body(&body);
+    notification.summary(&title).body(&body);
     #[cfg(target_os = "linux")]
     {
         notification.urgency(notify_rust::Urgency::Normal);
@@ -58,8 +280,32 @@ pub fn send_report_notification(summary: &ReportSummary, project_name: &str) ->
         notification.subtitle("Periodic report");
         notification.sound_name("Tink");
     }
-    notification.show()?;
-    Ok(())
+    if notifier.show(&notification, "report") {
+        return Ok(None);
+    }
+    let msg = crate::message::Message {
+        from: "cryochamber".to_string(),
+        subject: title,
+        body,
+        timestamp: chrono::Local::now().naive_local(),
+        metadata: BTreeMap::from([("report".to_string(), "periodic".to_string())]),
+        attachments: Vec::new(),
+    };
+    store.write("outbox", &msg)?;
+    Ok(notifier.take_warning())
+}
+
+/// The outbox message carrying the agent's `--complete` summary as the
+/// plan's final report (`require_summary = true`).
+pub fn final_report_message(project_name: &str, summary: &str) -> crate::message::Message {
+    crate::message::Message {
+        from: "cryochamber".to_string(),
+        subject: format!("Final report: {project_name}"),
+        body: summary.to_string(),
+        timestamp: chrono::Local::now().naive_local(),
+        metadata: BTreeMap::from([("report".to_string(), "final".to_string())]),
+        attachments: Vec::new(),
+    }
 }
 
 /// Compute the next report time based on config and last report.
@@ -105,8 +351,121 @@ pub fn compute_next_report_time(
 mod tests {
     use super::*;
     use crate::log::EventLogger;
+    use crate::message;
     use chrono::{Local, Timelike};
 
+    #[test]
+    fn test_report_falls_back_to_outbox_without_notifier() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = crate::store::open(dir.path()).unwrap();
+        store.ensure().unwrap();
+        let notifier =
+            Notifier::unavailable("no D-Bus session bus (DBUS_SESSION_BUS_ADDRESS is unset)");
+        let summary = ReportSummary {
+            total_sessions: 3,
+            failed_sessions: 1,
+            period_hours: 24,
+            events: BTreeMap::new(),
+            providers: BTreeMap::new(),
+            usage: None,
+        };
+
+        let warning =
+            send_report_notification(&summary, None, "alpha", &notifier, &*store).unwrap();
+        assert!(warning.unwrap().contains("no D-Bus session bus"));
+        // The warning is given once; later reports go to the outbox quietly
+        let warning = send_report_notification(
+            &summary,
+            Some("Session 4: success"),
+            "beta",
+            &notifier,
+            &*store,
+        )
+        .unwrap();
+        assert_eq!(warning, None);
+
+        let outbox = store.read_outbox().unwrap();
+        let mut subjects: Vec<_> = outbox.iter().map(|(_, m)| m.subject.as_str()).collect();
+        subjects.sort();
+        assert_eq!(
+            subjects,
+            ["Cryochamber Report: alpha", "Cryochamber Report: beta"]
+        );
+        for (_, msg) in &outbox {
+            assert!(msg.body.contains("Last 1d: 3 sessions, 1 failed"));
+        }
+        let beta = outbox.iter().find(|(_, m)| m.subject.ends_with("beta"));
+        assert!(beta
+            .unwrap()
+            .1
+            .body
+            .starts_with("Session 4: success\n\nLast 1d"));
+    }
+
+    #[test]
+    fn test_session_digest_header() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("plan.md"), "- [x] one\n- [ ] two\n").unwrap();
+        let state = crate::state::CryoState {
+            session_number: 4,
+            last_outcome: Some(SessionOutcome::Success),
+            last_summary: Some("Shipped the handler".to_string()),
+            ..Default::default()
+        };
+        crate::state::save_state(&crate::state::state_path(dir.path()), &state).unwrap();
+
+        let at = |ts: &str| NaiveDateTime::parse_from_str(ts, "%Y-%m-%dT%H:%M:%S").unwrap();
+        let msg = |subject: &str, ts: &str| message::Message {
+            from: "alice".to_string(),
+            subject: subject.to_string(),
+            body: "hi".to_string(),
+            timestamp: at(ts),
+            metadata: Default::default(),
+            attachments: Vec::new(),
+        };
+        message::write_message(dir.path(), "inbox", &msg("old", "2026-01-01T08:00:00")).unwrap();
+        message::write_message(dir.path(), "inbox", &msg("new", "2026-01-02T08:00:00")).unwrap();
+        message::write_message(dir.path(), "outbox", &msg("reply", "2026-01-02T09:00:00")).unwrap();
+
+        let digest =
+            SessionDigest::collect(dir.path(), 4, Some(at("2026-01-01T12:00:00"))).unwrap();
+        assert_eq!(
+            digest.render_header(),
+            "## Session 4\n\n\
+             - **Result:** success — Shipped the handler\n\
+             - **Plan:** 1/2 tasks done\n\
+             - **Messages since last push:** 1 received, 1 sent\n"
+        );
+
+        // First push counts everything
+        let digest = SessionDigest::collect(dir.path(), 4, None).unwrap();
+        assert_eq!(digest.messages_received, 2);
+    }
+
+    #[test]
+    fn test_session_digest_template() {
+        let digest = SessionDigest {
+            session_number: 7,
+            result: None,
+            plan_progress: Some((3, 5)),
+            messages_received: 2,
+            messages_sent: 1,
+            outcome: Some("success".to_string()),
+            summary: Some("Fixed CI".to_string()),
+            notes: vec!["bumped deps".to_string(), "flaky test".to_string()],
+            wake: Some("2026-03-01T09:00".to_string()),
+        };
+        assert_eq!(
+            digest.render(Some(
+                "#{session} {outcome}: {summary} | notes: {notes} | wake {wake} | \
+                 {replies} replies, {received} received, plan {plan} | {unknown} {"
+            )),
+            "#7 success: Fixed CI | notes: bumped deps; flaky test | wake 2026-03-01T09:00 | \
+             1 replies, 2 received, plan 3/5 | {unknown} {"
+        );
+        assert_eq!(digest.render(None), digest.render_header());
+    }
+
     #[test]
     fn test_generate_report_counts() {
         let dir = tempfile::tempdir().unwrap();
@@ -114,17 +473,30 @@ mod tests {
 
         // 2 success + 1 failure
         let mut logger = EventLogger::begin(&log_path, 1, "t1", "agent", &[]).unwrap();
+        logger.log_event("provider: primary").unwrap();
         logger.log_event("agent started (pid 1)").unwrap();
         logger.log_event("agent exited (code 0)").unwrap();
+        logger.log_event("usage: tokens=1000 cost=0.1000").unwrap();
         logger.finish("session complete").unwrap();
 
         let mut logger = EventLogger::begin(&log_path, 2, "t2", "agent", &[]).unwrap();
+        logger.log_event("provider: primary").unwrap();
         logger.log_event("agent started (pid 2)").unwrap();
         logger.log_event("agent exited (code 1)").unwrap();
+        logger.log_event("usage: tokens=500 cost=0.0300").unwrap();
         logger.finish("agent exited without hibernate").unwrap();
 
         let mut logger = EventLogger::begin(&log_path, 3, "t3", "agent", &[]).unwrap();
         logger.log_event("agent started (pid 3)").unwrap();
+        logger
+            .log_event(&log::format_custom_event(
+                "deploy",
+                Some(r#"{"version":"1.2"}"#),
+            ))
+            .unwrap();
+        logger
+            .log_event(&log::format_custom_event("deploy", None))
+            .unwrap();
         logger
             .log_event("hibernate: wake=2026-03-01T09:00, exit=0")
             .unwrap();
@@ -139,9 +511,16 @@ mod tests {
 
         let since =
             NaiveDateTime::parse_from_str("2020-01-01T00:00:00Z", "%Y-%m-%dT%H:%M:%SZ").unwrap();
-        let report = generate_report(&log_path, since).unwrap();
+        let report = generate_report(&log_path, since, &log::LogFormat::default()).unwrap();
         assert_eq!(report.total_sessions, 4);
         assert_eq!(report.failed_sessions, 2);
+        assert_eq!(report.events.get("deploy"), Some(&2));
+        assert_eq!(report.providers.get("primary"), Some(&(1, 2)));
+        assert_eq!(
+            report.provider_line().as_deref(),
+            Some("primary failed 1/2")
+        );
+        assert_eq!(report.usage_line().as_deref(), Some("1500 tokens, $0.13"));
     }
 
     #[test]
@@ -151,9 +530,11 @@ mod tests {
 
         let since =
             NaiveDateTime::parse_from_str("2020-01-01T00:00:00Z", "%Y-%m-%dT%H:%M:%SZ").unwrap();
-        let report = generate_report(&log_path, since).unwrap();
+        let report = generate_report(&log_path, since, &log::LogFormat::default()).unwrap();
         assert_eq!(report.total_sessions, 0);
         assert_eq!(report.failed_sessions, 0);
+        assert_eq!(report.provider_line(), None);
+        assert_eq!(report.usage_line(), None);
     }
 
     #[test]
@@ -0,0 +1,414 @@
+// src/review.rs
+//! Approval gates for risky actions. `cryo-agent request-review` records a
+//! review under `messages/reviews/<id>.json` and hibernates the agent; a
+//! human approves or rejects it (`cryo review`, the web UI, or a channel
+//! reply such as `approve <id>` from a sender in `review_approvers`), and
+//! the decision reaches the agent as an inbox message on the next wake.
+use anyhow::{bail, Context, Result};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::message::Message;
+
+/// Frontmatter key naming the review a decision message answers.
+pub const REVIEW_KEY: &str = "review";
+/// Frontmatter key holding the decision: "approved" or "rejected".
+pub const DECISION_KEY: &str = "decision";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ReviewStatus {
+    #[default]
+    Pending,
+    Approved,
+    Rejected,
+}
+
+impl ReviewStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReviewStatus::Pending => "pending",
+            ReviewStatus::Approved => "approved",
+            ReviewStatus::Rejected => "rejected",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Review {
+    pub id: String,
+    /// The question put to the human, e.g. "Deploy to prod?"
+    pub prompt: String,
+    pub requested_at: NaiveDateTime,
+    /// When an unanswered review raises the fallback alert
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deadline: Option<NaiveDateTime>,
+    #[serde(default)]
+    pub status: ReviewStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decided_by: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decided_at: Option<NaiveDateTime>,
+}
+
+impl Review {
+    pub fn is_pending(&self) -> bool {
+        self.status == ReviewStatus::Pending
+    }
+
+    /// Outbox message announcing the review, with how to answer it.
+    pub fn request_message(&self) -> Message {
+        let mut body = format!(
+            "{}\n\nReply `approve {id}` or `reject {id}` (optionally followed by a note), \
+             run `cryo review approve {id}`, or use the web UI.",
+            self.prompt,
+            id = self.id
+        );
+        if let Some(deadline) = self.deadline {
+            body.push_str(&format!(
+                "\n\nUnanswered by {} it raises an alert; the agent will not proceed on its own.",
+                deadline.format("%Y-%m-%d %H:%M")
+            ));
+        }
+        Message {
+            from: "agent".to_string(),
+            subject: format!("Review requested: {}", self.id),
+            body,
+            timestamp: self.requested_at,
+            metadata: BTreeMap::from([(REVIEW_KEY.to_string(), self.id.clone())]),
+            attachments: Vec::new(),
+        }
+    }
+
+    /// Inbox message carrying the decision back to the agent.
+    pub fn decision_message(&self) -> Message {
+        let status = self.status.as_str();
+        let mut body = format!("Review {} was {status}: {}", self.id, self.prompt);
+        if let Some(note) = &self.note {
+            body.push_str(&format!("\n\nNote: {note}"));
+        }
+        Message {
+            from: self
+                .decided_by
+                .clone()
+                .unwrap_or_else(|| "human".to_string()),
+            subject: format!("Review {status}: {}", self.id),
+            body,
+            timestamp: self
+                .decided_at
+                .unwrap_or_else(|| chrono::Local::now().naive_local()),
+            metadata: BTreeMap::from([
+                (REVIEW_KEY.to_string(), self.id.clone()),
+                (DECISION_KEY.to_string(), status.to_string()),
+            ]),
+            attachments: Vec::new(),
+        }
+    }
+}
+
+/// `messages/reviews/` in the project.
+pub fn reviews_dir(dir: &Path) -> PathBuf {
+    dir.join("messages").join("reviews")
+}
+
+fn review_path(dir: &Path, id: &str) -> PathBuf {
+    reviews_dir(dir).join(format!("{id}.json"))
+}
+
+fn validate_id(id: &str) -> Result<()> {
+    if id.is_empty()
+        || !id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        || id.starts_with('.')
+    {
+        bail!("Invalid review id {id:?}: use letters, digits, '-', '_' or '.'");
+    }
+    Ok(())
+}
+
+pub fn save(dir: &Path, review: &Review) -> Result<()> {
+    let reviews = reviews_dir(dir);
+    std::fs::create_dir_all(&reviews)
+        .with_context(|| format!("Failed to create {}", reviews.display()))?;
+    let json = serde_json::to_string_pretty(review)?;
+    std::fs::write(review_path(dir, &review.id), json)
+        .with_context(|| format!("Failed to save review {}", review.id))
+}
+
+pub fn load(dir: &Path, id: &str) -> Result<Option<Review>> {
+    validate_id(id)?;
+    let path = review_path(dir, id);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let contents = std::fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    let review = serde_json::from_str(&contents)
+        .with_context(|| format!("Failed to parse {}", path.display()))?;
+    Ok(Some(review))
+}
+
+/// Every review, oldest request first.
+pub fn list(dir: &Path) -> Result<Vec<Review>> {
+    let reviews = reviews_dir(dir);
+    if !reviews.exists() {
+        return Ok(Vec::new());
+    }
+    let mut all = Vec::new();
+    for entry in std::fs::read_dir(&reviews)? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|e| e == "json") {
+            let contents = std::fs::read_to_string(&path)?;
+            if let Ok(review) = serde_json::from_str::<Review>(&contents) {
+                all.push(review);
+            }
+        }
+    }
+    all.sort_by(|a, b| a.requested_at.cmp(&b.requested_at).then(a.id.cmp(&b.id)));
+    Ok(all)
+}
+
+/// Delete a review, e.g. one whose request could not be sent.
+pub fn remove(dir: &Path, id: &str) -> Result<()> {
+    validate_id(id)?;
+    let path = review_path(dir, id);
+    std::fs::remove_file(&path).with_context(|| format!("Failed to remove {}", path.display()))
+}
+
+/// Reviews still waiting for a decision.
+pub fn pending(dir: &Path) -> Result<Vec<Review>> {
+    Ok(list(dir)?.into_iter().filter(Review::is_pending).collect())
+}
+
+/// Record a new pending review. Without `id`, one is made from the time
+/// (`review-20260301-093000`). `deadline_hours` of 0 means no deadline.
+pub fn request(
+    dir: &Path,
+    id: Option<&str>,
+    prompt: &str,
+    now: NaiveDateTime,
+    deadline_hours: u64,
+) -> Result<Review> {
+    if prompt.trim().is_empty() {
+        bail!("Review prompt is empty");
+    }
+    let id = match id {
+        Some(id) => id.to_string(),
+        None => format!("review-{}", now.format("%Y%m%d-%H%M%S")),
+    };
+    validate_id(&id)?;
+    if load(dir, &id)?.is_some() {
+        bail!("Review {id} already exists");
+    }
+    let review = Review {
+        id,
+        prompt: prompt.to_string(),
+        requested_at: now,
+        deadline: (deadline_hours > 0)
+            .then(|| now + chrono::Duration::hours(deadline_hours as i64)),
+        status: ReviewStatus::Pending,
+        note: None,
+        decided_by: None,
+        decided_at: None,
+    };
+    save(dir, &review)?;
+    Ok(review)
+}
+
+/// Approve or reject pending review `id`.
+pub fn decide(
+    dir: &Path,
+    id: &str,
+    approve: bool,
+    note: Option<&str>,
+    by: &str,
+    now: NaiveDateTime,
+) -> Result<Review> {
+    let Some(mut review) = load(dir, id)? else {
+        bail!("No review with id {id}");
+    };
+    if !review.is_pending() {
+        bail!("Review {id} was already {}", review.status.as_str());
+    }
+    review.status = if approve {
+        ReviewStatus::Approved
+    } else {
+        ReviewStatus::Rejected
+    };
+    review.note = note
+        .map(str::trim)
+        .filter(|n| !n.is_empty())
+        .map(String::from);
+    review.decided_by = Some(by.to_string());
+    review.decided_at = Some(now);
+    save(dir, &review)?;
+    Ok(review)
+}
+
+/// Decide review `id` and put the decision in the agent's inbox, as
+/// `cryo review` and the web UI do.
+pub fn answer(
+    dir: &Path,
+    store: &dyn crate::store::MessageStore,
+    id: &str,
+    approve: bool,
+    note: Option<&str>,
+    by: &str,
+    now: NaiveDateTime,
+) -> Result<Review> {
+    let review = decide(dir, id, approve, note, by, now)?;
+    store.write("inbox", &review.decision_message())?;
+    Ok(review)
+}
+
+/// A reply of the form `approve <id> [note]` or `reject <id> [note]`
+/// (a leading `/` is allowed). Returns (approve, id, note).
+pub fn parse_decision(text: &str) -> Option<(bool, String, Option<String>)> {
+    let text = text.trim();
+    let text = text.strip_prefix('/').unwrap_or(text);
+    let (verb, rest) = text.split_once(char::is_whitespace)?;
+    let approve = match verb.to_ascii_lowercase().as_str() {
+        "approve" => true,
+        "reject" => false,
+        _ => return None,
+    };
+    let rest = rest.trim_start();
+    let (id, note) = match rest.split_once(char::is_whitespace) {
+        Some((id, note)) => (id, Some(note.trim().to_string())),
+        None => (rest, None),
+    };
+    validate_id(id).ok()?;
+    Some((approve, id.to_string(), note.filter(|n| !n.is_empty())))
+}
+
+/// Resolve pending reviews answered by inbox messages, e.g. a channel reply
+/// `approve review-1 ship it`. Only replies whose `from` is in `approvers`
+/// count; with no approvers, reviews are decided by `cryo review` and the
+/// web UI alone. The replies stay in the inbox for the agent. Returns the
+/// reviews decided.
+pub fn resolve_replies(
+    dir: &Path,
+    inbox: &[(String, Message)],
+    approvers: &[String],
+    now: NaiveDateTime,
+) -> Result<Vec<Review>> {
+    let mut decided = Vec::new();
+    for (_, msg) in inbox {
+        if msg.metadata.contains_key(DECISION_KEY)
+            || !approvers.iter().any(|a| a.trim() == msg.from.trim())
+        {
+            continue;
+        }
+        let Some((approve, id, note)) = parse_decision(&msg.body) else {
+            continue;
+        };
+        if load(dir, &id)?.is_some_and(|r| r.is_pending()) {
+            decided.push(decide(dir, &id, approve, note.as_deref(), &msg.from, now)?);
+        }
+    }
+    Ok(decided)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(h: u32, m: u32) -> NaiveDateTime {
+        chrono::NaiveDate::from_ymd_opt(2026, 3, 1)
+            .unwrap()
+            .and_hms_opt(h, m, 0)
+            .unwrap()
+    }
+
+    fn reply(from: &str, body: &str) -> (String, Message) {
+        let msg = Message {
+            from: from.to_string(),
+            subject: String::new(),
+            body: body.to_string(),
+            timestamp: at(10, 0),
+            metadata: BTreeMap::new(),
+            attachments: Vec::new(),
+        };
+        ("reply.md".to_string(), msg)
+    }
+
+    #[test]
+    fn test_request_and_decide() {
+        let dir = tempfile::tempdir().unwrap();
+        let review = request(dir.path(), None, "Deploy to prod?", at(9, 30), 24).unwrap();
+        assert_eq!(review.id, "review-20260301-093000");
+        assert_eq!(
+            review.deadline,
+            Some(at(9, 30) + chrono::Duration::hours(24))
+        );
+        assert_eq!(pending(dir.path()).unwrap().len(), 1);
+
+        let decided = decide(dir.path(), &review.id, true, Some("go"), "alice", at(10, 0)).unwrap();
+        assert_eq!(decided.status, ReviewStatus::Approved);
+        assert!(pending(dir.path()).unwrap().is_empty());
+
+        let msg = decided.decision_message();
+        assert_eq!(msg.from, "alice");
+        assert_eq!(msg.metadata[DECISION_KEY], "approved");
+        assert!(msg.body.contains("Note: go"));
+
+        let err = decide(dir.path(), &review.id, false, None, "bob", at(11, 0)).unwrap_err();
+        assert!(err.to_string().contains("already approved"));
+    }
+
+    #[test]
+    fn test_request_rejects_duplicate_and_bad_ids() {
+        let dir = tempfile::tempdir().unwrap();
+        request(dir.path(), Some("deploy"), "Deploy?", at(9, 0), 0).unwrap();
+        assert!(request(dir.path(), Some("deploy"), "Again?", at(9, 1), 0).is_err());
+        assert!(request(dir.path(), Some("../x"), "Escape?", at(9, 1), 0).is_err());
+        assert!(decide(dir.path(), "missing", true, None, "alice", at(9, 2)).is_err());
+
+        // A removed review frees its id
+        remove(dir.path(), "deploy").unwrap();
+        assert!(pending(dir.path()).unwrap().is_empty());
+        request(dir.path(), Some("deploy"), "Deploy now?", at(9, 3), 0).unwrap();
+    }
+
+    #[test]
+    fn test_parse_decision() {
+        assert_eq!(
+            parse_decision("approve deploy-1 looks good"),
+            Some((true, "deploy-1".into(), Some("looks good".into())))
+        );
+        assert_eq!(
+            parse_decision("/reject deploy-1"),
+            Some((false, "deploy-1".into(), None))
+        );
+        assert_eq!(parse_decision("I approve of this"), None);
+        assert_eq!(parse_decision("approve"), None);
+    }
+
+    #[test]
+    fn test_resolve_replies_decides_pending_reviews() {
+        let dir = tempfile::tempdir().unwrap();
+        request(dir.path(), Some("deploy"), "Deploy?", at(9, 0), 24).unwrap();
+        let inbox = vec![
+            reply("mallory", "approve deploy"),
+            reply("bob", "unrelated"),
+            reply("alice", "reject deploy not on a Friday"),
+            reply("carol", "approve deploy"),
+        ];
+        // Without approvers, channel replies decide nothing
+        assert!(resolve_replies(dir.path(), &inbox, &[], at(10, 0))
+            .unwrap()
+            .is_empty());
+
+        let approvers = vec!["alice".to_string(), "carol".to_string()];
+        let decided = resolve_replies(dir.path(), &inbox, &approvers, at(10, 0)).unwrap();
+        assert_eq!(decided.len(), 1);
+        assert_eq!(decided[0].status, ReviewStatus::Rejected);
+        assert_eq!(decided[0].decided_by.as_deref(), Some("alice"));
+        assert_eq!(decided[0].note.as_deref(), Some("not on a Friday"));
+    }
+}
@@ -0,0 +1,334 @@
+// src/schedule.rs
+//! Cron-style recurring schedules ("0 9 * * 1-5") for `hibernate --every`,
+//! relative durations ("90m", "1h30m") for `hibernate --wake-in`, and the
+//! daily `quiet_hours` window ("22:00-07:00").
+use anyhow::{bail, Context, Result};
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+
+/// Longest duration [`parse_wake_in`] accepts: a year.
+pub const MAX_WAKE_IN_DAYS: i64 = 366;
+
+/// Parse a `--wake-in` duration: one or more `<number><unit>` parts with
+/// units `s`, `m`, `h`, `d`, e.g. "90m", "1h30m", "2d". Must be positive and
+/// at most [`MAX_WAKE_IN_DAYS`] days.
+pub fn parse_wake_in(s: &str) -> Result<Duration> {
+    let usage = "expected e.g. \"30s\", \"90m\", \"1h30m\" or \"2d\"";
+    let mut total = Duration::zero();
+    let mut digits = String::new();
+    for c in s.trim().chars() {
+        if c.is_ascii_digit() {
+            digits.push(c);
+            continue;
+        }
+        let n: i64 = digits
+            .parse()
+            .with_context(|| format!("Invalid duration {s:?}: {usage}"))?;
+        digits.clear();
+        let part = match c {
+            's' => Duration::try_seconds(n),
+            'm' => Duration::try_minutes(n),
+            'h' => Duration::try_hours(n),
+            'd' => Duration::try_days(n),
+            _ => bail!("Invalid duration {s:?}: unknown unit {c:?}, {usage}"),
+        };
+        total = part
+            .and_then(|part| total.checked_add(&part))
+            .with_context(|| format!("Duration {s:?} is too long"))?;
+    }
+    if !digits.is_empty() {
+        bail!("Invalid duration {s:?}: missing unit after {digits}, {usage}");
+    }
+    if total <= Duration::zero() {
+        bail!("Invalid duration {s:?}: {usage}");
+    }
+    if total > Duration::days(MAX_WAKE_IN_DAYS) {
+        bail!("Duration {s:?} is too long: at most {MAX_WAKE_IN_DAYS}d");
+    }
+    Ok(total)
+}
+
+/// A parsed five-field cron expression: minute, hour, day of month, month,
+/// day of week (0-7, where both 0 and 7 are Sunday). Each field accepts `*`,
+/// numbers, ranges (`1-5`), lists (`1,15`) and steps (`*/15`, `0-30/10`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct CronSchedule {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    /// Day of month and day of week both restricted: either may match (cron semantics)
+    day_or: bool,
+}
+
+/// Parse one field into a bitmask of allowed values in `min..=max`.
+fn parse_field(field: &str, min: u32, max: u32, name: &str) -> Result<u64> {
+    let mut mask = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((r, s)) => {
+                let step: u32 = s
+                    .parse()
+                    .with_context(|| format!("Invalid step {s:?} in {name} field"))?;
+                if step == 0 {
+                    bail!("Step must be positive in {name} field");
+                }
+                (r, step)
+            }
+            None => (part, 1),
+        };
+        let (lo, hi) = if range == "*" {
+            (min, max)
+        } else if let Some((a, b)) = range.split_once('-') {
+            let a: u32 = a
+                .parse()
+                .with_context(|| format!("Invalid value {a:?} in {name} field"))?;
+            let b: u32 = b
+                .parse()
+                .with_context(|| format!("Invalid value {b:?} in {name} field"))?;
+            (a, b)
+        } else {
+            let v: u32 = range
+                .parse()
+                .with_context(|| format!("Invalid value {range:?} in {name} field"))?;
+            // "5/10" means 5, 15, 25, ...
+            (v, if step > 1 { max } else { v })
+        };
+        if lo < min || hi > max || lo > hi {
+            bail!("{name} field out of range {min}-{max}: {part:?}");
+        }
+        for v in (lo..=hi).step_by(step as usize) {
+            mask |= 1 << v;
+        }
+    }
+    Ok(mask)
+}
+
+impl CronSchedule {
+    pub fn parse(expr: &str) -> Result<Self> {
+        let fields: Vec<&str> = expr.split_whitespace().collect();
+        if fields.len() != 5 {
+            bail!(
+                "Expected 5 fields (minute hour day-of-month month day-of-week), got {}: {expr:?}",
+                fields.len()
+            );
+        }
+        let mut weekdays = parse_field(fields[4], 0, 7, "day-of-week")?;
+        if weekdays & (1 << 7) != 0 {
+            weekdays |= 1; // 7 is Sunday too
+        }
+        Ok(Self {
+            minutes: parse_field(fields[0], 0, 59, "minute")?,
+            hours: parse_field(fields[1], 0, 23, "hour")?,
+            days: parse_field(fields[2], 1, 31, "day-of-month")?,
+            months: parse_field(fields[3], 1, 12, "month")?,
+            weekdays,
+            day_or: fields[2] != "*" && fields[4] != "*",
+        })
+    }
+
+    fn day_matches(&self, date: NaiveDate) -> bool {
+        let dom = self.days & (1 << date.day()) != 0;
+        let dow = self.weekdays & (1 << date.weekday().num_days_from_sunday()) != 0;
+        if self.day_or {
+            dom || dow
+        } else {
+            dom && dow
+        }
+    }
+
+    /// The first matching minute strictly after `after`, or `None` if nothing
+    /// matches within the next five years (e.g. "0 0 31 2 *").
+    pub fn next_after(&self, after: NaiveDateTime) -> Option<NaiveDateTime> {
+        let start = after.with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        let mut date = start.date();
+        let limit = date + Duration::days(5 * 366);
+        let mut first_day = true;
+        while date <= limit {
+            if self.months & (1 << date.month()) != 0 && self.day_matches(date) {
+                let from = if first_day {
+                    start.time()
+                } else {
+                    NaiveTime::MIN
+                };
+                for hour in from.hour()..24 {
+                    if self.hours & (1 << hour) == 0 {
+                        continue;
+                    }
+                    let min_start = if hour == from.hour() {
+                        from.minute()
+                    } else {
+                        0
+                    };
+                    for minute in min_start..60 {
+                        if self.minutes & (1 << minute) != 0 {
+                            return date.and_hms_opt(hour, minute, 0);
+                        }
+                    }
+                }
+            }
+            date = date.succ_opt()?;
+            first_day = false;
+        }
+        None
+    }
+}
+
+/// A daily window ("22:00-07:00") in which no session starts. It spans
+/// midnight when the end is before the start.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuietHours {
+    start: NaiveTime,
+    end: NaiveTime,
+}
+
+impl QuietHours {
+    pub fn parse(s: &str) -> Result<Self> {
+        let usage = "expected \"HH:MM-HH:MM\", e.g. \"22:00-07:00\"";
+        let invalid = || format!("Invalid quiet hours {s:?}: {usage}");
+        let (start, end) = s.split_once('-').with_context(invalid)?;
+        let start = NaiveTime::parse_from_str(start.trim(), "%H:%M").with_context(invalid)?;
+        let end = NaiveTime::parse_from_str(end.trim(), "%H:%M").with_context(invalid)?;
+        if start == end {
+            bail!("Invalid quiet hours {s:?}: start and end are the same");
+        }
+        Ok(Self { start, end })
+    }
+
+    /// End of the quiet window `now` falls in, or None outside the window.
+    pub fn end_after(&self, now: NaiveDateTime) -> Option<NaiveDateTime> {
+        let time = now.time();
+        let end = now.date().and_time(self.end);
+        if self.start < self.end {
+            (self.start <= time && time < self.end).then_some(end)
+        } else if time >= self.start {
+            Some(end + Duration::days(1))
+        } else {
+            (time < self.end).then_some(end)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M").unwrap()
+    }
+
+    #[test]
+    fn test_weekdays_at_nine() {
+        let s = CronSchedule::parse("0 9 * * 1-5").unwrap();
+        // Friday 2026-03-06 10:00 -> Monday 09:00
+        assert_eq!(
+            s.next_after(at("2026-03-06T10:00")),
+            Some(at("2026-03-09T09:00"))
+        );
+        // Before nine on a weekday -> same day
+        assert_eq!(
+            s.next_after(at("2026-03-09T08:59")),
+            Some(at("2026-03-09T09:00"))
+        );
+        // Exactly at the fire time -> the next one, never the same minute
+        assert_eq!(
+            s.next_after(at("2026-03-09T09:00")),
+            Some(at("2026-03-10T09:00"))
+        );
+    }
+
+    #[test]
+    fn test_steps_lists_and_sunday_alias() {
+        let s = CronSchedule::parse("*/15 * * * *").unwrap();
+        assert_eq!(
+            s.next_after(at("2026-03-09T10:07")),
+            Some(at("2026-03-09T10:15"))
+        );
+        assert_eq!(
+            s.next_after(at("2026-03-09T10:45")),
+            Some(at("2026-03-09T11:00"))
+        );
+
+        let s = CronSchedule::parse("30 8,20 * * 7").unwrap();
+        // 2026-03-08 is a Sunday
+        assert_eq!(
+            s.next_after(at("2026-03-08T09:00")),
+            Some(at("2026-03-08T20:30"))
+        );
+    }
+
+    #[test]
+    fn test_day_of_month_or_weekday() {
+        // Both restricted: the 1st of the month OR any Monday
+        let s = CronSchedule::parse("0 0 1 * 1").unwrap();
+        assert_eq!(
+            s.next_after(at("2026-03-03T12:00")),
+            Some(at("2026-03-09T00:00"))
+        );
+        assert_eq!(
+            s.next_after(at("2026-03-30T12:00")),
+            Some(at("2026-04-01T00:00"))
+        );
+    }
+
+    #[test]
+    fn test_parse_wake_in() {
+        assert_eq!(parse_wake_in("90m").unwrap(), Duration::minutes(90));
+        assert_eq!(
+            parse_wake_in("1h30m").unwrap(),
+            Duration::hours(1) + Duration::minutes(30)
+        );
+        assert_eq!(parse_wake_in("2d").unwrap(), Duration::days(2));
+        assert_eq!(parse_wake_in(" 45s ").unwrap(), Duration::seconds(45));
+        for bad in ["", "90", "m", "0m", "1w", "1.5h", "-5m", "1h 30m"] {
+            assert!(parse_wake_in(bad).is_err(), "{bad:?} should be rejected");
+        }
+        assert_eq!(parse_wake_in("366d").unwrap(), Duration::days(366));
+        for too_long in ["367d", "1000000000d", "99999999999999999s", "365d24h1s"] {
+            let err = parse_wake_in(too_long).unwrap_err();
+            assert!(
+                format!("{err:#}").contains("too long"),
+                "{too_long}: {err:#}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_invalid_expressions() {
+        assert!(CronSchedule::parse("0 9 * *").is_err());
+        assert!(CronSchedule::parse("60 9 * * *").is_err());
+        assert!(CronSchedule::parse("0 9 * * mon").is_err());
+        assert!(CronSchedule::parse("*/0 9 * * *").is_err());
+        let never = CronSchedule::parse("0 0 31 2 *").unwrap();
+        assert_eq!(never.next_after(at("2026-01-01T00:00")), None);
+    }
+
+    #[test]
+    fn test_quiet_hours() {
+        let night = QuietHours::parse("22:00-07:00").unwrap();
+        assert_eq!(
+            night.end_after(at("2026-03-06T23:30")),
+            Some(at("2026-03-07T07:00"))
+        );
+        assert_eq!(
+            night.end_after(at("2026-03-07T06:59")),
+            Some(at("2026-03-07T07:00"))
+        );
+        assert_eq!(night.end_after(at("2026-03-07T07:00")), None);
+        assert_eq!(night.end_after(at("2026-03-07T21:59")), None);
+
+        let lunch = QuietHours::parse("12:00 - 13:30").unwrap();
+        assert_eq!(
+            lunch.end_after(at("2026-03-07T12:00")),
+            Some(at("2026-03-07T13:30"))
+        );
+        assert_eq!(lunch.end_after(at("2026-03-07T13:30")), None);
+
+        for bad in ["", "22:00", "22:00-25:00", "10pm-7am", "09:00-09:00"] {
+            assert!(
+                QuietHours::parse(bad).is_err(),
+                "{bad:?} should be rejected"
+            );
+        }
+    }
+}
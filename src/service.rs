@@ -3,6 +3,7 @@
 //! user services that survive reboots.
 
 use anyhow::{Context, Result};
+use chrono::NaiveDateTime;
 use std::path::Path;
 
 /// Derive a short hex hash from a path for unique service naming.
@@ -162,6 +163,165 @@ pub fn is_installed(label_prefix: &str, dir: &Path) -> bool {
         .unwrap_or(false)
 }
 
+/// Wake job plists for `dir` in `~/Library/LaunchAgents`. Each wake gets
+/// its own label, `com.cryo.wake.<hash>.<yyyymmddHHMM>`.
+#[cfg(target_os = "macos")]
+fn wake_plists(dir: &Path) -> Result<Vec<std::path::PathBuf>> {
+    let agents_dir = dirs::home_dir()
+        .context("Cannot determine home directory")?
+        .join("Library/LaunchAgents");
+    let prefix = format!("{}.", service_label(WAKE_TIMER_PREFIX, dir));
+    let Ok(entries) = std::fs::read_dir(&agents_dir) else {
+        return Ok(Vec::new());
+    };
+    let mut plists: Vec<_> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| {
+            p.file_name()
+                .map(|n| n.to_string_lossy())
+                .is_some_and(|n| n.starts_with(&prefix) && n.ends_with(".plist"))
+        })
+        .collect();
+    plists.sort();
+    Ok(plists)
+}
+
+/// Unload and delete one wake job. The job this process was started by
+/// (launchd names it in `XPC_SERVICE_NAME`) is only deleted: unloading it
+/// would stop this daemon. Without its plist it is gone at the next login.
+#[cfg(target_os = "macos")]
+fn remove_wake_plist(plist_path: &Path) -> Result<()> {
+    let label = plist_path
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    if std::env::var("XPC_SERVICE_NAME").ok().as_deref() != Some(label.as_str()) {
+        let _ = std::process::Command::new("launchctl")
+            .args(["unload", "-w"])
+            .arg(plist_path)
+            .status();
+    }
+    std::fs::remove_file(plist_path)?;
+    Ok(())
+}
+
+/// Register a launchd job that starts `cryo daemon` at `wake`. Calendar
+/// entries have no year, so the job is one-shot only because the daemon
+/// removes it when the next session starts (see `uninstall_wake_timer`).
+/// A wake missed while the Mac was asleep runs when it wakes up; one missed
+/// while it was powered off is not caught.
+#[cfg(target_os = "macos")]
+pub fn install_wake_timer(
+    dir: &Path,
+    exe: &Path,
+    wake: NaiveDateTime,
+    log_file: &Path,
+) -> Result<()> {
+    use chrono::{Datelike, Timelike};
+
+    for old in wake_plists(dir)? {
+        remove_wake_plist(&old)?;
+    }
+    let label = format!(
+        "{}.{}",
+        service_label(WAKE_TIMER_PREFIX, dir),
+        wake.format("%Y%m%d%H%M")
+    );
+    let agents_dir = dirs::home_dir()
+        .context("Cannot determine home directory")?
+        .join("Library/LaunchAgents");
+    std::fs::create_dir_all(&agents_dir)?;
+    let plist_path = agents_dir.join(format!("{label}.plist"));
+    let path_env = std::env::var("PATH").unwrap_or_default();
+
+    let plist = format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN"
+  "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+  <key>Label</key>
+  <string>{label}</string>
+  <key>ProgramArguments</key>
+  <array>
+    <string>{exe}</string>
+    <string>daemon</string>
+  </array>
+  <key>WorkingDirectory</key>
+  <string>{dir}</string>
+  <key>EnvironmentVariables</key>
+  <dict>
+    <key>PATH</key>
+    <string>{path}</string>
+  </dict>
+  <key>StartCalendarInterval</key>
+  <dict>
+    <key>Month</key>
+    <integer>{month}</integer>
+    <key>Day</key>
+    <integer>{day}</integer>
+    <key>Hour</key>
+    <integer>{hour}</integer>
+    <key>Minute</key>
+    <integer>{minute}</integer>
+  </dict>
+  <key>StandardOutPath</key>
+  <string>{log}</string>
+  <key>StandardErrorPath</key>
+  <string>{log}</string>
+</dict>
+</plist>"#,
+        label = xml_escape(&label),
+        exe = xml_escape(&exe.display().to_string()),
+        dir = xml_escape(&dir.display().to_string()),
+        path = xml_escape(&path_env),
+        month = wake.month(),
+        day = wake.day(),
+        hour = wake.hour(),
+        minute = wake.minute(),
+        log = xml_escape(&log_file.display().to_string()),
+    );
+
+    std::fs::write(&plist_path, plist)?;
+    let status = std::process::Command::new("launchctl")
+        .args(["load", "-w"])
+        .arg(&plist_path)
+        .status()
+        .context("Failed to run launchctl")?;
+    if !status.success() {
+        anyhow::bail!("launchctl load failed");
+    }
+    Ok(())
+}
+
+/// Check that the wake job is loaded in launchd.
+#[cfg(target_os = "macos")]
+pub fn verify_wake_timer(dir: &Path) -> bool {
+    let Some(label) = wake_plists(dir)
+        .ok()
+        .and_then(|plists| plists.last().cloned())
+        .and_then(|p| p.file_stem().map(|s| s.to_string_lossy().into_owned()))
+    else {
+        return false;
+    };
+    std::process::Command::new("launchctl")
+        .args(["list", &label])
+        .output()
+        .map(|o| o.status.success())
+        .unwrap_or(false)
+}
+
+/// Remove the wake job. Returns true if one was installed.
+#[cfg(target_os = "macos")]
+pub fn uninstall_wake_timer(dir: &Path) -> Result<bool> {
+    let plists = wake_plists(dir)?;
+    for plist in &plists {
+        remove_wake_plist(plist)?;
+    }
+    Ok(!plists.is_empty())
+}
+
 #[cfg(target_os = "linux")]
 pub fn install(
     label_prefix: &str,
@@ -265,6 +425,162 @@ pub fn is_installed(label_prefix: &str, dir: &Path) -> bool {
         .unwrap_or(false)
 }
 
+/// Label prefix for the one-shot wake timer used by `persist_wake_across_reboot`.
+pub const WAKE_TIMER_PREFIX: &str = "wake";
+
+/// Register an OS timer that starts `cryo daemon` at `wake` (or at the next
+/// boot if the machine was off then). The daemon restores its schedule from
+/// `timer.json`; if one is already running the extra start exits harmlessly.
+#[cfg(target_os = "linux")]
+pub fn install_wake_timer(
+    dir: &Path,
+    exe: &Path,
+    wake: NaiveDateTime,
+    log_file: &Path,
+) -> Result<()> {
+    let label = service_label(WAKE_TIMER_PREFIX, dir);
+    let unit_dir = dirs::home_dir()
+        .context("Cannot determine home directory")?
+        .join(".config/systemd/user");
+    std::fs::create_dir_all(&unit_dir)?;
+
+    let path_env = std::env::var("PATH").unwrap_or_default();
+    let service = format!(
+        "[Unit]\n\
+         Description=Cryochamber wake ({dir})\n\
+         \n\
+         [Service]\n\
+         ExecStart=\"{exe}\" \"daemon\"\n\
+         WorkingDirectory={dir}\n\
+         Environment=PATH={path}\n\
+         StandardOutput=append:{log}\n\
+         StandardError=append:{log}\n",
+        dir = dir.display(),
+        exe = exe.display(),
+        path = path_env,
+        log = log_file.display(),
+    );
+    // Persistent=true fires a missed timer at the next boot.
+    let timer = format!(
+        "[Unit]\n\
+         Description=Cryochamber wake timer ({dir})\n\
+         \n\
+         [Timer]\n\
+         OnCalendar={wake}\n\
+         Persistent=true\n\
+         \n\
+         [Install]\n\
+         WantedBy=timers.target\n",
+        dir = dir.display(),
+        wake = wake.format("%Y-%m-%d %H:%M:%S"),
+    );
+    std::fs::write(unit_dir.join(format!("{label}.service")), service)?;
+    std::fs::write(unit_dir.join(format!("{label}.timer")), timer)?;
+
+    let timer_unit = format!("{label}.timer");
+    for args in [
+        vec!["--user", "daemon-reload"],
+        vec!["--user", "enable", &timer_unit],
+        vec!["--user", "restart", &timer_unit],
+    ] {
+        let status = std::process::Command::new("systemctl")
+            .args(&args)
+            .status()
+            .context("Failed to run systemctl")?;
+        if !status.success() {
+            anyhow::bail!("systemctl {} failed", args[1..].join(" "));
+        }
+    }
+    Ok(())
+}
+
+/// Check that the wake timer is registered and active.
+#[cfg(target_os = "linux")]
+pub fn verify_wake_timer(dir: &Path) -> bool {
+    let label = service_label(WAKE_TIMER_PREFIX, dir);
+    std::process::Command::new("systemctl")
+        .args(["--user", "is-active", "--quiet", &format!("{label}.timer")])
+        .status()
+        .map(|s| s.success())
+        .unwrap_or(false)
+}
+
+/// Remove the wake timer. Returns true if one was installed.
+#[cfg(target_os = "linux")]
+pub fn uninstall_wake_timer(dir: &Path) -> Result<bool> {
+    let label = service_label(WAKE_TIMER_PREFIX, dir);
+    let unit_dir = dirs::home_dir()
+        .context("Cannot determine home directory")?
+        .join(".config/systemd/user");
+    let timer_path = unit_dir.join(format!("{label}.timer"));
+    if !timer_path.exists() {
+        return Ok(false);
+    }
+
+    let _ = std::process::Command::new("systemctl")
+        .args(["--user", "disable", "--now", &format!("{label}.timer")])
+        .status();
+    std::fs::remove_file(&timer_path)?;
+    let _ = std::fs::remove_file(unit_dir.join(format!("{label}.service")));
+    let _ = std::process::Command::new("systemctl")
+        .args(["--user", "daemon-reload"])
+        .status();
+    Ok(true)
+}
+
+/// Reasons a systemd user service would not survive logout or reboot, each
+/// with its remedy. Advisory only: `cryo start` warns and installs anyway.
+#[cfg(target_os = "linux")]
+pub fn persistence_warnings() -> Vec<String> {
+    let mut warnings = Vec::new();
+
+    let bus = std::env::var_os("XDG_RUNTIME_DIR")
+        .filter(|d| !d.is_empty())
+        .map(|d| Path::new(&d).join("bus"));
+    match bus {
+        None => warnings.push(
+            "XDG_RUNTIME_DIR is unset, so there is no systemd user bus; \
+             `systemctl --user` may fail. Log in through a full session (not `su`), \
+             or run `loginctl enable-linger $USER` and log in again."
+                .to_string(),
+        ),
+        Some(bus) if !bus.exists() => warnings.push(format!(
+            "No systemd user bus at {}; `systemctl --user` may fail. \
+             Run `loginctl enable-linger $USER` and log in again.",
+            bus.display()
+        )),
+        Some(_) => {}
+    }
+
+    let uid = unsafe { libc::getuid() };
+    let linger = std::process::Command::new("loginctl")
+        .args([
+            "show-user",
+            &uid.to_string(),
+            "--property=Linger",
+            "--value",
+        ])
+        .output();
+    // No loginctl (or no logind) means we can't tell; stay quiet.
+    if let Ok(out) = linger {
+        if out.status.success() && String::from_utf8_lossy(&out.stdout).trim() == "no" {
+            let user = std::env::var("USER").unwrap_or_else(|_| uid.to_string());
+            warnings.push(format!(
+                "Lingering is disabled for {user}: the daemon stops when you log out \
+                 and won't start after a reboot until you log in. \
+                 Fix: loginctl enable-linger {user}"
+            ));
+        }
+    }
+
+    warnings
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn persistence_warnings() -> Vec<String> {
+    Vec::new()
+}
+
 #[cfg(not(any(target_os = "macos", target_os = "linux")))]
 pub fn install(
     _label_prefix: &str,
@@ -286,3 +602,23 @@ pub fn uninstall(_label_prefix: &str, _dir: &Path) -> Result<bool> {
 pub fn is_installed(_label_prefix: &str, _dir: &Path) -> bool {
     false
 }
+
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+pub fn install_wake_timer(
+    _dir: &Path,
+    _exe: &Path,
+    _wake: NaiveDateTime,
+    _log_file: &Path,
+) -> Result<()> {
+    anyhow::bail!("OS timers are not supported on this platform")
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+pub fn verify_wake_timer(_dir: &Path) -> bool {
+    false
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+pub fn uninstall_wake_timer(_dir: &Path) -> Result<bool> {
+    Ok(false)
+}
@@ -12,3 +12,41 @@ pub fn should_copy_plan(source: &Path, dest: &Path) -> bool {
         _ => true,
     }
 }
+
+/// Check whether `dest` was modified more recently than `source`.
+///
+/// Returns false if either file is missing or has no readable mtime, so a
+/// fresh project always accepts the copy.
+pub fn dest_is_newer(source: &Path, dest: &Path) -> bool {
+    let mtime = |p: &Path| std::fs::metadata(p).and_then(|m| m.modified()).ok();
+    match (mtime(source), mtime(dest)) {
+        (Some(src), Some(dst)) => dst > src,
+        _ => false,
+    }
+}
+
+/// Count Markdown task-list checkboxes (`- [ ]` / `- [x]`) in a plan.
+///
+/// Returns `(checked, total)`, or None if the plan has no checkboxes.
+pub fn plan_progress(plan: &str) -> Option<(usize, usize)> {
+    let mut checked = 0;
+    let mut total = 0;
+    for line in plan.lines() {
+        let item = line.trim_start();
+        let Some(rest) = item
+            .strip_prefix("- [")
+            .or_else(|| item.strip_prefix("* ["))
+        else {
+            continue;
+        };
+        match rest.get(..2) {
+            Some("x]") | Some("X]") => {
+                checked += 1;
+                total += 1;
+            }
+            Some(" ]") => total += 1,
+            _ => {}
+        }
+    }
+    (total > 0).then_some((checked, total))
+}
@@ -4,19 +4,169 @@ use std::path::{Path, PathBuf};
 
 use serde::{Deserialize, Serialize};
 
+/// Version of the socket protocol, sent as `"v"` next to every request and
+/// response. Bump it when a change can break an older peer; additive
+/// request kinds only need an entry in [`CAPABILITIES`].
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Request kinds (`cmd`) this daemon understands, reported by
+/// [`Request::Hello`].
+pub const CAPABILITIES: &[&str] = &[
+    "hello",
+    "ping",
+    "env",
+    "hibernate",
+    "note",
+    "next_task",
+    "focus_plan",
+    "rotate_provider",
+    "query_messages",
+    "defer",
+    "set_summary",
+    "summary_append",
+    "checkpoint_save",
+    "checkpoint_load",
+    "event",
+    "attach_log",
+    "abort",
+    "request_review",
+    "wait_for_reply",
+    "ack_inbox",
+    "handled",
+    "watch_inbox",
+    "alert",
+    "reply",
+    "wake",
+    "stop",
+];
+
 /// Request from CLI to daemon via Unix socket.
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(tag = "cmd", rename_all = "snake_case")]
 pub enum Request {
+    /// Protocol handshake; the daemon answers with a [`Hello`] as JSON.
+    Hello,
+    /// Liveness check; the daemon answers with its PID and session number.
+    Ping,
+    /// Active provider and the environment the daemon injected for this
+    /// session; values are redacted unless `show_values`.
+    Env {
+        #[serde(default)]
+        show_values: bool,
+    },
     Hibernate {
         wake: Option<String>,
         complete: bool,
         exit_code: u8,
         summary: Option<String>,
+        /// Sleep with no scheduled wake; only an inbox message wakes the agent.
+        #[serde(default)]
+        until_message: bool,
+        /// Also wake early if an inbox message arrives before `wake`,
+        /// even when `watch_inbox` is off.
+        #[serde(default)]
+        wake_on_message: bool,
+        /// JSON result payload attached to `--complete`, saved to `.cryo/result.json`.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        result: Option<String>,
+        /// Cron expression to wake on from now on (replaces the stored schedule).
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        every: Option<String>,
+        /// Wake after this long ("90m", "1h30m"), resolved by the daemon.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        wake_in: Option<String>,
     },
     Note {
         text: String,
     },
+    /// Task for the next session, used verbatim instead of the log heuristic.
+    NextTask {
+        task: String,
+    },
+    /// `plans/<name>.md` to focus on in the next session.
+    FocusPlan {
+        name: String,
+    },
+    /// Start the next session on the next `[[providers]]` entry.
+    RotateProvider {
+        reason: String,
+    },
+    /// Search inbox and outbox, archives included. The response message is
+    /// a JSON array of matching messages, newest first.
+    QueryMessages {
+        #[serde(default)]
+        query: crate::store::MessageQuery,
+    },
+    /// Ask for more time: move the session deadline (and a wake already
+    /// requested this session) later by `by` ("30m"), within the hard cap.
+    Defer {
+        by: String,
+    },
+    /// Working summary of the running session, kept if it never hibernates.
+    SetSummary {
+        summary: String,
+    },
+    /// Add `text` to the end of the running session's working summary.
+    SummaryAppend {
+        text: String,
+    },
+    /// Store an opaque JSON blob in `.cryo/checkpoint.json`, replacing the
+    /// previous one; it is shown in the next session's prompt.
+    CheckpointSave {
+        data: String,
+    },
+    /// Return the saved checkpoint as the response message.
+    CheckpointLoad,
+    /// Custom structured event: a kind plus an opaque JSON payload.
+    Event {
+        kind: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        data: Option<String>,
+    },
+    /// Output of a tool the agent ran, recorded as a labeled block in cryo.log.
+    AttachLog {
+        label: String,
+        /// Tail of the file (the CLI already trims it to the configured max)
+        content: String,
+        /// Size of the whole file, to note truncation
+        total_bytes: u64,
+    },
+    /// Agent chose to end the session: not a failure, so no retry or rotation.
+    Abort {
+        reason: String,
+        /// Optional wake time; without one the daemon waits for a message.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        wake: Option<String>,
+    },
+    /// Ask a human to approve or reject a risky step, then sleep until the
+    /// decision arrives. The daemon records it under `messages/reviews/`.
+    RequestReview {
+        prompt: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        id: Option<String>,
+    },
+    /// Block until an inbox message answers question `id` (any new message
+    /// if `None`), or `timeout_secs` elapse. The daemon defers the response.
+    WaitForReply {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        id: Option<String>,
+        timeout_secs: u64,
+    },
+    /// Inbox files the agent has read (`cryo-agent receive`), so they are not
+    /// reported as ignored.
+    AckInbox {
+        files: Vec<String>,
+    },
+    /// Inbox files the agent is done with (disposed of after the session),
+    /// or with `keep`, wants to see again next session.
+    Handled {
+        files: Vec<String>,
+        #[serde(default)]
+        keep: bool,
+    },
+    /// Keep the connection open and receive one response line per new inbox
+    /// message until the session ends. One watcher per session.
+    WatchInbox,
     Alert {
         action: String,
         target: String,
@@ -24,7 +174,31 @@ pub enum Request {
     },
     Reply {
         text: String,
+        /// Files already stored in `messages/attachments/`
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        attachments: Vec<String>,
+        /// People to address; empty broadcasts to the channel
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        to: Vec<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        subject: Option<String>,
     },
+    /// Wake the agent now, like SIGUSR1. How `cryo wake` reaches a daemon
+    /// embedded in another process, where a signal would hit the host.
+    Wake,
+    /// Stop the daemon, like SIGTERM, for an embedded daemon.
+    Stop,
+}
+
+impl Request {
+    /// The `cmd` tag, e.g. "hibernate": names the request in logs without
+    /// its payload (message bodies, summaries).
+    pub fn cmd(&self) -> String {
+        serde_json::to_value(self)
+            .ok()
+            .and_then(|v| v.get("cmd")?.as_str().map(str::to_string))
+            .unwrap_or_default()
+    }
 }
 
 /// Response from daemon to CLI.
@@ -34,28 +208,187 @@ pub struct Response {
     pub message: String,
 }
 
+/// The daemon's answer to [`Request::Hello`].
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Hello {
+    pub version: u32,
+    pub capabilities: Vec<String>,
+}
+
+impl Hello {
+    /// This build's protocol version and capabilities.
+    pub fn current() -> Self {
+        Hello {
+            version: PROTOCOL_VERSION,
+            capabilities: CAPABILITIES.iter().map(|c| c.to_string()).collect(),
+        }
+    }
+}
+
+/// Serialize `value` as one protocol line, tagged with [`PROTOCOL_VERSION`].
+fn encode_line<T: Serialize>(value: &T) -> anyhow::Result<String> {
+    let mut value = serde_json::to_value(value)?;
+    if let Some(obj) = value.as_object_mut() {
+        obj.insert("v".to_string(), PROTOCOL_VERSION.into());
+    }
+    let mut line = serde_json::to_string(&value)?;
+    line.push('\n');
+    Ok(line)
+}
+
+/// Protocol version a peer tagged `value` with (0 = before versioning).
+fn peer_version(value: &serde_json::Value) -> u64 {
+    value.get("v").and_then(|v| v.as_u64()).unwrap_or(0)
+}
+
+/// Parse a response line from the daemon. A daemon older than the request
+/// drops the connection without answering; a newer one may answer in a form
+/// this build can't read. Both are reported as a protocol mismatch.
+fn decode_response(line: &str) -> anyhow::Result<Response> {
+    if line.trim().is_empty() {
+        anyhow::bail!(
+            "The daemon closed the connection without answering: agent/daemon protocol \
+             mismatch — upgrade the daemon (install the current cryo, then `cryo restart`)"
+        );
+    }
+    let value: serde_json::Value = serde_json::from_str(line.trim())?;
+    let version = peer_version(&value);
+    serde_json::from_value(value).map_err(|e| {
+        if version > u64::from(PROTOCOL_VERSION) {
+            anyhow::anyhow!(
+                "agent/daemon protocol mismatch — upgrade cryo-agent (daemon speaks v{version}, \
+                 cryo-agent v{PROTOCOL_VERSION}): {e}"
+            )
+        } else {
+            e.into()
+        }
+    })
+}
+
+/// Why a well-formed JSON request can't be served, and which side to upgrade.
+fn rejection_message(value: &serde_json::Value, error: &serde_json::Error) -> String {
+    let cmd = value.get("cmd").and_then(|c| c.as_str()).unwrap_or("?");
+    let client = peer_version(value);
+    let ours = u64::from(PROTOCOL_VERSION);
+    let known = CAPABILITIES.contains(&cmd);
+    let upgrade = if client < ours {
+        "upgrade cryo-agent"
+    } else if client > ours || !known {
+        "upgrade the daemon (install the current cryo, then `cryo restart`)"
+    } else {
+        return format!("Invalid '{cmd}' request: {error}");
+    };
+    format!(
+        "Unsupported '{cmd}' request: agent/daemon protocol mismatch — {upgrade} \
+         (cryo-agent speaks v{client}, daemon v{ours})"
+    )
+}
+
+/// Ask the daemon for its protocol version and capabilities.
+pub fn hello(dir: &Path) -> anyhow::Result<Hello> {
+    let resp = send_request(dir, &Request::Hello)?;
+    if !resp.ok {
+        anyhow::bail!("{}", resp.message);
+    }
+    Ok(serde_json::from_str(&resp.message)?)
+}
+
+/// Env var that overrides `runtime_dir` in cryo.toml.
+pub const RUNTIME_DIR_ENV: &str = "CRYO_RUNTIME_DIR";
+
+/// Directory holding a project's runtime files (the daemon socket).
+///
+/// Defaults to `.cryo/` in the project. When `CRYO_RUNTIME_DIR` or
+/// `runtime_dir` in cryo.toml is set, uses `<runtime_dir>/<hash-of-dir>`
+/// instead, since Unix sockets don't work on NFS/SMB mounts. Fails if
+/// cryo.toml can't be parsed, rather than quietly using the default.
+pub fn runtime_dir(dir: &Path) -> anyhow::Result<PathBuf> {
+    let base = match std::env::var(RUNTIME_DIR_ENV)
+        .ok()
+        .filter(|s| !s.is_empty())
+    {
+        Some(base) => Some(base),
+        None => crate::config::load_config(&crate::config::config_path(dir))?
+            .and_then(|c| c.runtime_dir),
+    };
+    Ok(match base {
+        Some(base) => dir.join(base).join(crate::registry::dir_key(dir)),
+        None => dir.join(".cryo"),
+    })
+}
+
 /// Returns the socket path for a project directory.
-pub fn socket_path(dir: &Path) -> PathBuf {
-    dir.join(".cryo").join("cryo.sock")
+pub fn socket_path(dir: &Path) -> anyhow::Result<PathBuf> {
+    Ok(runtime_dir(dir)?.join("cryo.sock"))
 }
 
 /// Send a request to the daemon and return the response.
 pub fn send_request(dir: &Path, request: &Request) -> anyhow::Result<Response> {
-    let path = socket_path(dir);
+    let path = socket_path(dir)?;
+    let mut stream = UnixStream::connect(&path).map_err(|e| {
+        anyhow::anyhow!("Cannot connect to daemon socket at {}: {e}", path.display())
+    })?;
+
+    stream.write_all(encode_line(request)?.as_bytes())?;
+    stream.flush()?;
+
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    decode_response(&line)
+}
+
+/// Like [`send_request`], but give up waiting for the response after
+/// `timeout`. For best-effort notices that may reach a daemon between
+/// sessions, when nothing answers the socket.
+pub fn send_request_timeout(
+    dir: &Path,
+    request: &Request,
+    timeout: std::time::Duration,
+) -> anyhow::Result<Response> {
+    let path = socket_path(dir)?;
     let mut stream = UnixStream::connect(&path).map_err(|e| {
         anyhow::anyhow!("Cannot connect to daemon socket at {}: {e}", path.display())
     })?;
+    stream.set_read_timeout(Some(timeout))?;
 
-    let mut payload = serde_json::to_string(request)?;
-    payload.push('\n');
-    stream.write_all(payload.as_bytes())?;
+    stream.write_all(encode_line(request)?.as_bytes())?;
     stream.flush()?;
 
     let mut reader = BufReader::new(stream);
     let mut line = String::new();
     reader.read_line(&mut line)?;
-    let response: Response = serde_json::from_str(line.trim())?;
-    Ok(response)
+    decode_response(&line)
+}
+
+/// Send a request that opens a stream (e.g. [`Request::WatchInbox`]) and
+/// pass each response line to `on_response` until the daemon closes the
+/// connection. A first response with `ok: false` is returned as an error.
+pub fn subscribe(
+    dir: &Path,
+    request: &Request,
+    mut on_response: impl FnMut(&Response) -> anyhow::Result<()>,
+) -> anyhow::Result<()> {
+    let path = socket_path(dir)?;
+    let mut stream = UnixStream::connect(&path).map_err(|e| {
+        anyhow::anyhow!("Cannot connect to daemon socket at {}: {e}", path.display())
+    })?;
+
+    stream.write_all(encode_line(request)?.as_bytes())?;
+    stream.flush()?;
+
+    for line in BufReader::new(stream).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = decode_response(&line)?;
+        if !response.ok {
+            anyhow::bail!("{}", response.message);
+        }
+        on_response(&response)?;
+    }
+    Ok(())
 }
 
 /// Server side of the Unix socket. Daemon creates this on startup.
@@ -70,9 +403,32 @@ pub struct Responder {
 
 impl Responder {
     pub fn respond(mut self, response: &Response) -> anyhow::Result<()> {
-        let mut payload = serde_json::to_string(response)?;
-        payload.push('\n');
-        self.stream.write_all(payload.as_bytes())?;
+        self.stream.write_all(encode_line(response)?.as_bytes())?;
+        self.stream.flush()?;
+        Ok(())
+    }
+
+    /// Keep the connection open to send several responses over time.
+    pub fn into_stream(self) -> anyhow::Result<StreamResponder> {
+        // A client that stops reading must not stall the daemon
+        self.stream
+            .set_write_timeout(Some(std::time::Duration::from_secs(1)))?;
+        Ok(StreamResponder {
+            stream: self.stream,
+        })
+    }
+}
+
+/// Long-lived connection for pushing responses to a subscribed client.
+/// Dropping it closes the connection, which ends [`subscribe`].
+pub struct StreamResponder {
+    stream: UnixStream,
+}
+
+impl StreamResponder {
+    /// Send one response line; fails once the client has disconnected.
+    pub fn send(&mut self, response: &Response) -> anyhow::Result<()> {
+        self.stream.write_all(encode_line(response)?.as_bytes())?;
         self.stream.flush()?;
         Ok(())
     }
@@ -97,8 +453,19 @@ impl SocketServer {
         if line.trim().is_empty() {
             return Ok(None);
         }
-        let request: Request = serde_json::from_str(line.trim())?;
-        Ok(Some((request, Responder { stream })))
+        let value: serde_json::Value = serde_json::from_str(line.trim())?;
+        let responder = Responder { stream };
+        match serde_json::from_value::<Request>(value.clone()) {
+            Ok(request) => Ok(Some((request, responder))),
+            Err(e) => {
+                // Answer instead of hanging up, so the client can tell why
+                let _ = responder.respond(&Response {
+                    ok: false,
+                    message: rejection_message(&value, &e),
+                });
+                Ok(None)
+            }
+        }
     }
 
     /// Set the listener to non-blocking mode.
@@ -129,12 +496,57 @@ mod tests {
             complete: false,
             exit_code: 0,
             summary: Some("Done".to_string()),
+            until_message: false,
+            wake_on_message: true,
+            result: None,
+            every: None,
+            wake_in: None,
         };
         let json = serde_json::to_string(&req).unwrap();
         let parsed: Request = serde_json::from_str(&json).unwrap();
         assert!(matches!(parsed, Request::Hibernate { .. }));
     }
 
+    #[test]
+    fn test_hibernate_until_message_defaults_false() {
+        let json =
+            r#"{"cmd":"hibernate","wake":null,"complete":true,"exit_code":0,"summary":null}"#;
+        let parsed: Request = serde_json::from_str(json).unwrap();
+        assert!(matches!(
+            parsed,
+            Request::Hibernate {
+                until_message: false,
+                wake_on_message: false,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_serialize_ping_request() {
+        let json = serde_json::to_string(&Request::Ping).unwrap();
+        assert_eq!(json, r#"{"cmd":"ping"}"#);
+        assert!(matches!(
+            serde_json::from_str::<Request>(&json).unwrap(),
+            Request::Ping
+        ));
+    }
+
+    #[test]
+    fn test_serialize_attach_log_request() {
+        let req = Request::AttachLog {
+            label: "cargo test".to_string(),
+            content: "test ok\n".to_string(),
+            total_bytes: 8,
+        };
+        let json = serde_json::to_string(&req).unwrap();
+        assert!(json.contains(r#""cmd":"attach_log""#), "{json}");
+        assert!(matches!(
+            serde_json::from_str::<Request>(&json).unwrap(),
+            Request::AttachLog { total_bytes: 8, .. }
+        ));
+    }
+
     #[test]
     fn test_serialize_note_request() {
         let req = Request::Note {
@@ -144,6 +556,57 @@ mod tests {
         assert!(json.contains("progress update"));
     }
 
+    #[test]
+    fn test_serialize_set_summary_request() {
+        let req = Request::SetSummary {
+            summary: "halfway there".to_string(),
+        };
+        let json = serde_json::to_string(&req).unwrap();
+        let parsed: Request = serde_json::from_str(&json).unwrap();
+        assert!(matches!(parsed, Request::SetSummary { summary } if summary == "halfway there"));
+    }
+
+    #[test]
+    fn test_request_cmd_leaves_out_payload() {
+        let req = Request::SetSummary {
+            summary: "token=hunter2".to_string(),
+        };
+        assert_eq!(req.cmd(), "set_summary");
+        assert_eq!(Request::Ping.cmd(), "ping");
+    }
+
+    #[test]
+    fn test_parse_summary_append_request() {
+        let req: Request =
+            serde_json::from_str(r#"{"cmd":"summary_append","text":"fixed the parser"}"#).unwrap();
+        assert!(matches!(req, Request::SummaryAppend { text } if text == "fixed the parser"));
+    }
+
+    #[test]
+    fn test_parse_request_review() {
+        let req: Request =
+            serde_json::from_str(r#"{"cmd":"request_review","prompt":"Deploy?"}"#).unwrap();
+        assert!(matches!(req, Request::RequestReview { prompt, id: None } if prompt == "Deploy?"));
+    }
+
+    #[test]
+    fn test_parse_defer_request() {
+        let req: Request = serde_json::from_str(r#"{"cmd":"defer","by":"30m"}"#).unwrap();
+        assert!(matches!(req, Request::Defer { by } if by == "30m"));
+    }
+
+    #[test]
+    fn test_query_messages_defaults() {
+        let req: Request = serde_json::from_str(r#"{"cmd":"query_messages"}"#).unwrap();
+        match req {
+            Request::QueryMessages { query } => {
+                assert_eq!(query.direction, crate::store::MessageDirection::All);
+                assert!(query.from.is_none() && query.limit.is_none());
+            }
+            other => panic!("unexpected request: {other:?}"),
+        }
+    }
+
     #[test]
     fn test_serialize_response_ok() {
         let resp = Response {
@@ -170,19 +633,45 @@ mod tests {
     fn test_serialize_reply_request() {
         let req = Request::Reply {
             text: "done with phase 1".to_string(),
+            attachments: Vec::new(),
+            to: Vec::new(),
+            subject: None,
         };
         let json = serde_json::to_string(&req).unwrap();
         assert!(json.contains("done with phase 1"));
+        assert!(!json.contains("attachments"));
+        assert!(!json.contains("\"to\""));
     }
 
     #[test]
     fn test_socket_path() {
         let dir = std::path::Path::new("/tmp/test-cryo");
-        let path = socket_path(dir);
+        let path = socket_path(dir).unwrap();
         assert!(path.ends_with("cryo.sock"));
         assert!(path.to_str().unwrap().contains(".cryo"));
     }
 
+    #[test]
+    fn test_runtime_dir_from_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let runtime = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("cryo.toml"),
+            format!("runtime_dir = \"{}\"\n", runtime.path().display()),
+        )
+        .unwrap();
+        let path = socket_path(dir.path()).unwrap();
+        let key = crate::registry::dir_key(dir.path());
+        assert_eq!(path, runtime.path().join(key).join("cryo.sock"));
+    }
+
+    #[test]
+    fn test_runtime_dir_rejects_broken_config() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("cryo.toml"), "runtime_dir = [\n").unwrap();
+        assert!(runtime_dir(dir.path()).is_err());
+    }
+
     #[test]
     fn test_send_request_no_server() {
         let dir = tempfile::tempdir().unwrap();
@@ -195,7 +684,7 @@ mod tests {
     #[test]
     fn test_socket_server_roundtrip() {
         let dir = tempfile::tempdir().unwrap();
-        let sock = socket_path(dir.path());
+        let sock = socket_path(dir.path()).unwrap();
         std::fs::create_dir_all(sock.parent().unwrap()).unwrap();
 
         let (tx, rx) = mpsc::channel();
@@ -232,6 +721,38 @@ mod tests {
         handle.join().unwrap();
     }
 
+    #[test]
+    fn test_subscribe_streams_until_closed() {
+        let dir = tempfile::tempdir().unwrap();
+        let sock = socket_path(dir.path()).unwrap();
+        std::fs::create_dir_all(sock.parent().unwrap()).unwrap();
+        let server = SocketServer::bind(&sock).unwrap();
+
+        let handle = std::thread::spawn(move || {
+            let (req, responder) = server.accept_one().unwrap().unwrap();
+            assert!(matches!(req, Request::WatchInbox));
+            let mut stream = responder.into_stream().unwrap();
+            for message in ["watching", "new: a.md", "new: b.md"] {
+                stream
+                    .send(&Response {
+                        ok: true,
+                        message: message.into(),
+                    })
+                    .unwrap();
+            }
+            // Dropping the stream ends the subscription
+        });
+
+        let mut received = Vec::new();
+        subscribe(dir.path(), &Request::WatchInbox, |resp| {
+            received.push(resp.message.clone());
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(received, ["watching", "new: a.md", "new: b.md"]);
+        handle.join().unwrap();
+    }
+
     #[test]
     fn test_accept_empty_line() {
         let dir = tempfile::tempdir().unwrap();
@@ -276,6 +797,64 @@ mod tests {
         handle.join().unwrap();
     }
 
+    #[test]
+    fn test_capabilities_name_real_requests() {
+        for cmd in CAPABILITIES {
+            let result = serde_json::from_value::<Request>(serde_json::json!({ "cmd": cmd }));
+            if let Err(e) = result {
+                assert!(!e.to_string().contains("unknown variant"), "{cmd}: {e}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_unknown_request_reports_protocol_mismatch() {
+        let dir = tempfile::tempdir().unwrap();
+        let sock_path = dir.path().join("test.sock");
+        let server = SocketServer::bind(&sock_path).unwrap();
+        server.set_nonblocking(false).unwrap();
+
+        for (json, upgrade) in [
+            (r#"{"cmd":"teleport","v":99}"#, "upgrade the daemon"),
+            (r#"{"cmd":"teleport"}"#, "upgrade cryo-agent"),
+        ] {
+            let handle = std::thread::spawn({
+                let sock_path = sock_path.clone();
+                move || {
+                    let mut stream = UnixStream::connect(&sock_path).unwrap();
+                    stream.write_all(json.as_bytes()).unwrap();
+                    stream.write_all(b"\n").unwrap();
+                    let mut line = String::new();
+                    BufReader::new(stream).read_line(&mut line).unwrap();
+                    decode_response(&line).unwrap()
+                }
+            });
+            assert!(server.accept_one().unwrap().is_none());
+            let resp = handle.join().unwrap();
+            assert!(!resp.ok);
+            assert!(
+                resp.message.contains("protocol mismatch"),
+                "{}",
+                resp.message
+            );
+            assert!(resp.message.contains(upgrade), "{}", resp.message);
+        }
+    }
+
+    #[test]
+    fn test_decode_response_versions() {
+        let resp = decode_response(r#"{"ok":true,"message":"hi","v":1}"#).unwrap();
+        assert_eq!(resp.message, "hi");
+        // A daemon from before versioning omits "v"
+        assert!(decode_response(r#"{"ok":true,"message":"hi"}"#).unwrap().ok);
+        let err = decode_response("").unwrap_err().to_string();
+        assert!(err.contains("upgrade the daemon"), "{err}");
+        let err = decode_response(r#"{"status":"ok","v":9}"#)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("upgrade cryo-agent"), "{err}");
+    }
+
     #[test]
     fn test_accept_unknown_fields_ignored() {
         let dir = tempfile::tempdir().unwrap();
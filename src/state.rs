@@ -1,12 +1,26 @@
 // src/state.rs
-use anyhow::Result;
+use anyhow::{Context, Result};
+use chrono::NaiveDateTime;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+use crate::fallback::FallbackAction;
+use crate::log::SessionOutcome;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct CryoState {
     pub session_number: u32,
     pub pid: Option<u32>,
+    /// Start time of the process in `pid`, so a reused PID isn't mistaken
+    /// for the daemon
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pid_started: Option<String>,
+    /// True when the daemon runs inside another program (`Daemon::spawn`):
+    /// `pid` is the host's, so it is stopped and woken over the socket
+    /// rather than signalled.
+    #[serde(default)]
+    pub embedded: bool,
     /// Current retry count for the active wake cycle. Reset to 0 on success.
     #[serde(default)]
     pub retry_count: u32,
@@ -17,10 +31,18 @@ pub struct CryoState {
     pub max_retries_override: Option<u32>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub max_session_duration_override: Option<u64>,
+    /// First-session task from `cryo start --task`, replacing `first_task`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub first_task_override: Option<String>,
     /// Scheduled next wake time (ISO 8601 format), set by daemon on hibernate.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub next_wake: Option<String>,
 
+    /// Recurring wake schedule (cron expression) from `hibernate --every`.
+    /// A later `hibernate` without `--wake` reuses it; `--complete` clears it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schedule: Option<String>,
+
     /// Last time a periodic report was sent, stored as an ISO 8601 local time
     /// string without timezone offset (from `Local::now().naive_local()`).
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -31,12 +53,277 @@ pub struct CryoState {
     /// session updates it).
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub provider_index: Option<usize>,
+
+    /// Reason the agent gave with `cryo-agent rotate-provider`. The next
+    /// session starts on the provider after `provider_index`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider_rotation: Option<String>,
+
+    /// True when the agent hibernated with `--until-message`: no scheduled
+    /// wake, the next session starts only when an inbox message arrives.
+    #[serde(default)]
+    pub awaiting_message: bool,
+
+    /// When a wait for a message alerts the operator (`YYYY-MM-DDTHH:MM`),
+    /// kept so a restarted daemon re-arms the alert.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message_deadline: Option<String>,
+
+    /// The alert sent at `message_deadline` (None = the default reminder).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message_fallback: Option<FallbackAction>,
+
+    /// True when the agent hibernated with `--wake-on-message`: an inbox
+    /// message wakes it before `next_wake` even if `watch_inbox` is off.
+    #[serde(default)]
+    pub wake_on_message: bool,
+
+    /// Why the daemon is skipping sessions (e.g. "plan.md missing").
+    /// Cleared once a session runs again.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blocked_reason: Option<String>,
+
+    /// Outcome of the most recent session, set when it ends.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_outcome: Option<SessionOutcome>,
+
+    /// Summary the agent passed to `cryo-agent hibernate` in that session.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_summary: Option<String>,
+
+    /// Hash of `plan.md` when the last session ended; a different hash at
+    /// the next wake means a human edited the plan in between.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub plan_hash: Option<String>,
+
+    /// When the plan's first session started (local time, ISO 8601), the
+    /// origin for `max_total_runtime_hours`. `cryo start` resets it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub plan_started_at: Option<String>,
+
+    /// Sessions each inbox message was left unmarked while the agent marked
+    /// others with `cryo-agent handled` (see `inbox_grace_sessions`).
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub inbox_unhandled: BTreeMap<String, u32>,
+
+    /// The `plans/` plan the current or last session focused on.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub focus_plan: Option<String>,
+
+    /// Plan the next session focuses on, from `cryo-agent focus-plan`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub focus_request: Option<String>,
+
+    /// `plans/` plans finished with `hibernate --complete`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub completed_plans: Vec<String>,
+
+    /// Task for each `plans/` plan's next session, set when one of its
+    /// sessions ends.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub plan_tasks: BTreeMap<String, String>,
+
+    /// Why the daemon last stopped; cleared when a daemon starts again.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stop_reason: Option<StopReason>,
+
+    /// When the daemon stopped (local time, ISO 8601).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stopped_at: Option<String>,
+
+    /// Local date (YYYY-MM-DD) `sessions_today` counts, for
+    /// `max_sessions_per_day`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_day: Option<String>,
+
+    /// Sessions started on `session_day`.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub sessions_today: u32,
+}
+
+fn is_zero(n: &u32) -> bool {
+    *n == 0
+}
+
+/// Why a daemon is no longer running.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum StopReason {
+    /// The agent marked the plan complete.
+    Completed,
+    /// Stopped on request: SIGTERM, `cryo cancel` or `cryo restart`.
+    Cancelled,
+    /// Gave up: retries exhausted or `max_total_runtime_hours` reached.
+    Failed,
+    /// Died without recording a reason (e.g. SIGKILL or a crash).
+    Killed,
+    /// The event loop hit an unrecoverable error.
+    Error,
+}
+
+impl std::fmt::Display for StopReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            StopReason::Completed => "completed",
+            StopReason::Cancelled => "cancelled",
+            StopReason::Failed => "failed",
+            StopReason::Killed => "killed",
+            StopReason::Error => "error",
+        })
+    }
+}
+
+const PLAN_STARTED_FMT: &str = "%Y-%m-%dT%H:%M:%S";
+
+impl CryoState {
+    /// One-line "outcome: summary" description of the last session, if any.
+    pub fn last_result(&self) -> Option<String> {
+        let outcome = self.last_outcome.as_ref()?;
+        Some(match &self.last_summary {
+            Some(summary) => format!("{outcome} — {summary}"),
+            None => outcome.to_string(),
+        })
+    }
+
+    /// Count a session started on `day`.
+    pub fn count_session(&mut self, day: chrono::NaiveDate) {
+        let day = day.format("%Y-%m-%d").to_string();
+        if self.session_day.as_deref() != Some(day.as_str()) {
+            self.session_day = Some(day);
+            self.sessions_today = 0;
+        }
+        self.sessions_today += 1;
+    }
+
+    /// Sessions started on `day`.
+    pub fn sessions_on(&self, day: chrono::NaiveDate) -> u32 {
+        if self.session_day == Some(day.format("%Y-%m-%d").to_string()) {
+            self.sessions_today
+        } else {
+            0
+        }
+    }
+
+    /// Record that the daemon stopped for `reason` at `now`.
+    pub fn mark_stopped(&mut self, reason: StopReason, now: NaiveDateTime) {
+        self.stop_reason = Some(reason);
+        self.stopped_at = Some(now.format(PLAN_STARTED_FMT).to_string());
+    }
+
+    /// "reason at time" for a daemon that is not running. A daemon that
+    /// left its PID behind without recording a reason was killed.
+    pub fn stop_description(&self) -> Option<String> {
+        if is_locked(self) {
+            return None;
+        }
+        let reason = match self.stop_reason {
+            Some(reason) => reason,
+            None if self.pid.is_some() => StopReason::Killed,
+            None => return None,
+        };
+        Some(match &self.stopped_at {
+            Some(at) => format!("{reason} at {at}"),
+            None => reason.to_string(),
+        })
+    }
+
+    /// Record `now` as the plan's start, unless one is already recorded.
+    pub fn mark_plan_started(&mut self, now: NaiveDateTime) {
+        if self.plan_elapsed(now).is_none() {
+            self.plan_started_at = Some(now.format(PLAN_STARTED_FMT).to_string());
+        }
+    }
+
+    /// Wall-clock time since the plan started, if recorded.
+    pub fn plan_elapsed(&self, now: NaiveDateTime) -> Option<chrono::Duration> {
+        let started =
+            NaiveDateTime::parse_from_str(self.plan_started_at.as_deref()?, PLAN_STARTED_FMT)
+                .ok()?;
+        Some(now - started)
+    }
+
+    /// True once the plan has run for `cap_hours` or more (0 = no cap).
+    pub fn runtime_cap_reached(&self, cap_hours: u64, now: NaiveDateTime) -> bool {
+        cap_hours > 0
+            && self
+                .plan_elapsed(now)
+                .is_some_and(|elapsed| elapsed >= chrono::Duration::hours(cap_hours as i64))
+    }
+
+    /// Merge CLI overrides given to `cryo restart` into the preserved ones;
+    /// `None` keeps the current value. Returns the changes as "key = value".
+    pub fn merge_overrides(
+        &mut self,
+        agent: Option<String>,
+        max_retries: Option<u32>,
+        max_session_duration: Option<u64>,
+    ) -> Vec<String> {
+        let mut changes = Vec::new();
+        if let Some(agent) = agent {
+            changes.push(format!("agent = {agent}"));
+            self.agent_override = Some(agent);
+        }
+        if let Some(n) = max_retries {
+            changes.push(format!("max_retries = {n}"));
+            self.max_retries_override = Some(n);
+        }
+        if let Some(secs) = max_session_duration {
+            changes.push(format!("max_session_duration = {secs}"));
+            self.max_session_duration_override = Some(secs);
+        }
+        changes
+    }
 }
 
 pub fn state_path(dir: &Path) -> PathBuf {
     dir.join("timer.json")
 }
 
+/// Structured result the agent attached when completing the plan.
+pub fn result_path(dir: &Path) -> PathBuf {
+    dir.join(".cryo").join("result.json")
+}
+
+/// Opaque JSON the agent keeps across sessions (`cryo-agent checkpoint`).
+pub fn checkpoint_path(dir: &Path) -> PathBuf {
+    dir.join(".cryo").join("checkpoint.json")
+}
+
+/// The saved checkpoint, if any.
+pub fn load_checkpoint(dir: &Path) -> Option<String> {
+    std::fs::read_to_string(checkpoint_path(dir))
+        .ok()
+        .filter(|s| !s.trim().is_empty())
+}
+
+/// Last session number kept by `cryo clean --keep-session-number`, so the
+/// next daemon continues the numbering instead of starting at 1.
+pub fn session_counter_path(dir: &Path) -> PathBuf {
+    dir.join(".cryo").join("session-counter")
+}
+
+pub fn save_session_counter(dir: &Path, session_number: u32) -> Result<()> {
+    let path = session_counter_path(dir);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, format!("{session_number}\n"))?;
+    Ok(())
+}
+
+pub fn load_session_counter(dir: &Path) -> Result<Option<u32>> {
+    let path = session_counter_path(dir);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let contents = std::fs::read_to_string(&path)?;
+    let n = contents
+        .trim()
+        .parse()
+        .with_context(|| format!("Invalid session counter in {}", path.display()))?;
+    Ok(Some(n))
+}
+
 pub fn save_state(path: &Path, state: &CryoState) -> Result<()> {
     let json = serde_json::to_string_pretty(state)?;
     std::fs::write(path, json)?;
@@ -57,17 +344,9 @@ pub fn load_state(path: &Path) -> Result<Option<CryoState>> {
 }
 
 pub fn is_locked(state: &CryoState) -> bool {
-    if let Some(pid) = state.pid {
-        let ret = unsafe { libc::kill(pid as i32, 0) };
-        if ret == 0 {
-            return true;
-        }
-        // EPERM means process exists but we lack permission — still locked
-        let errno = std::io::Error::last_os_error().raw_os_error().unwrap_or(0);
-        errno == libc::EPERM
-    } else {
-        false
-    }
+    state
+        .pid
+        .is_some_and(|pid| crate::process::is_same_process(pid, state.pid_started.as_deref()))
 }
 
 #[cfg(test)]
@@ -123,6 +402,7 @@ mod tests {
             max_session_duration_override: None,
             last_report_time: None,
             provider_index: None,
+            ..Default::default()
         };
         assert!(!is_locked(&state), "Dead PID should not be locked");
     }
@@ -139,10 +419,53 @@ mod tests {
             max_session_duration_override: None,
             last_report_time: None,
             provider_index: None,
+            ..Default::default()
         };
         assert!(!is_locked(&state), "No PID should not be locked");
     }
 
+    #[test]
+    fn test_merge_overrides_keeps_unset_ones() {
+        let mut state = CryoState {
+            session_number: 7,
+            agent_override: Some("opencode".to_string()),
+            max_retries_override: Some(2),
+            ..Default::default()
+        };
+        assert!(state.merge_overrides(None, None, None).is_empty());
+        assert_eq!(state.agent_override.as_deref(), Some("opencode"));
+
+        let changes = state.merge_overrides(Some("claude".to_string()), None, Some(600));
+        assert_eq!(changes, ["agent = claude", "max_session_duration = 600"]);
+        assert_eq!(state.agent_override.as_deref(), Some("claude"));
+        assert_eq!(state.max_retries_override, Some(2));
+        assert_eq!(state.max_session_duration_override, Some(600));
+        assert_eq!(state.session_number, 7);
+    }
+
+    #[test]
+    fn test_runtime_cap() {
+        let start = chrono::NaiveDate::from_ymd_opt(2026, 3, 1)
+            .unwrap()
+            .and_hms_opt(9, 0, 0)
+            .unwrap();
+        let mut state = CryoState::default();
+        assert!(!state.runtime_cap_reached(48, start));
+        state.mark_plan_started(start);
+        // A later session keeps the original start
+        state.mark_plan_started(start + chrono::Duration::hours(5));
+        assert_eq!(
+            state.plan_started_at.as_deref(),
+            Some("2026-03-01T09:00:00")
+        );
+
+        let later = start + chrono::Duration::hours(47);
+        assert_eq!(state.plan_elapsed(later), Some(chrono::Duration::hours(47)));
+        assert!(!state.runtime_cap_reached(48, later));
+        assert!(state.runtime_cap_reached(48, later + chrono::Duration::hours(1)));
+        assert!(!state.runtime_cap_reached(0, later + chrono::Duration::hours(100)));
+    }
+
     #[test]
     fn test_is_locked_own_pid() {
         let state = CryoState {
@@ -155,7 +478,44 @@ mod tests {
             max_session_duration_override: None,
             last_report_time: None,
             provider_index: None,
+            ..Default::default()
         };
         assert!(is_locked(&state), "Own PID should be locked");
+
+        // The PID was recorded for a process that started at another time
+        let reused = CryoState {
+            pid_started: Some("not-our-start-time".to_string()),
+            ..state
+        };
+        assert!(!is_locked(&reused), "A reused PID is not the daemon");
+    }
+
+    #[test]
+    fn test_stop_description() {
+        let mut state = CryoState::default();
+        assert_eq!(state.stop_description(), None, "never started");
+
+        let at = NaiveDateTime::parse_from_str("2026-03-01T09:00:00", PLAN_STARTED_FMT).unwrap();
+        state.mark_stopped(StopReason::Completed, at);
+        assert_eq!(
+            state.stop_description().as_deref(),
+            Some("completed at 2026-03-01T09:00:00")
+        );
+        let json = serde_json::to_string(&state).unwrap();
+        assert!(json.contains(r#""stop_reason":"completed""#), "{json}");
+
+        // Still running: no stop reason to show
+        state.pid = Some(std::process::id());
+        assert_eq!(state.stop_description(), None);
+
+        // Left a dead PID behind without recording why
+        let mut child = std::process::Command::new("true").spawn().unwrap();
+        let pid = child.id();
+        child.wait().unwrap();
+        let state = CryoState {
+            pid: Some(pid),
+            ..Default::default()
+        };
+        assert_eq!(state.stop_description().as_deref(), Some("killed"));
     }
 }
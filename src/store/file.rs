@@ -0,0 +1,87 @@
+use anyhow::{Context, Result};
+use std::path::PathBuf;
+
+use crate::config::InboxDisposition;
+use crate::message::{self, DeliveryFailure, DeliveryReceipt, Message};
+use crate::store::MessageStore;
+
+/// Default store: one markdown file per message under `messages/`.
+/// Ids are filenames; the agent reads these files directly.
+pub struct FileStore {
+    dir: PathBuf,
+}
+
+impl FileStore {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+}
+
+impl MessageStore for FileStore {
+    fn ensure(&self) -> Result<()> {
+        message::ensure_dirs(&self.dir)
+    }
+
+    fn write(&self, box_name: &str, msg: &Message) -> Result<String> {
+        let path = message::write_message(&self.dir, box_name, msg)?;
+        Ok(path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default())
+    }
+
+    fn read(&self, box_name: &str, id: &str) -> Result<Message> {
+        let path = message::project_dirs(&self.dir)?.box_dir(box_name).join(id);
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        message::parse_message(&content)
+    }
+
+    fn read_inbox(&self) -> Result<Vec<(String, Message)>> {
+        message::read_inbox(&self.dir)
+    }
+
+    fn list_inbox(&self) -> Result<Vec<String>> {
+        message::list_inbox(&self.dir)
+    }
+
+    fn read_outbox(&self) -> Result<Vec<(String, Message)>> {
+        message::read_outbox(&self.dir)
+    }
+
+    fn read_inbox_archive(&self) -> Result<Vec<(String, Message)>> {
+        message::read_inbox_archive(&self.dir)
+    }
+
+    fn read_outbox_archive(&self) -> Result<Vec<(String, Message, Option<DeliveryReceipt>)>> {
+        message::read_outbox_archive(&self.dir)
+    }
+
+    fn archive(&self, ids: &[String]) -> Result<()> {
+        message::archive_messages(&self.dir, ids)
+    }
+
+    fn dispose(&self, ids: &[String], disposition: InboxDisposition) -> Result<()> {
+        message::dispose_messages(&self.dir, ids, disposition)
+    }
+
+    fn archive_outbox(&self, id: &str, receipt: &DeliveryReceipt) -> Result<()> {
+        message::archive_outbox_message(&self.dir, id, receipt)
+    }
+
+    fn record_outbox_failure(&self, id: &str, error: &str) -> Result<DeliveryFailure> {
+        message::record_outbox_failure(&self.dir, id, error)
+    }
+
+    fn dead_letter_outbox(&self, id: &str) -> Result<()> {
+        message::dead_letter_outbox_message(&self.dir, id)
+    }
+
+    fn read_dead_letters(&self) -> Result<Vec<(String, Message, Option<DeliveryFailure>)>> {
+        message::read_dead_letters(&self.dir)
+    }
+
+    fn requeue_dead_letters(&self) -> Result<Vec<String>> {
+        message::requeue_dead_letters(&self.dir)
+    }
+}
@@ -0,0 +1,219 @@
+pub mod file;
+pub mod sqlite;
+
+use anyhow::{Context, Result};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::config::{self, InboxDisposition, MessageStoreKind};
+use crate::message::{DeliveryFailure, DeliveryReceipt, Message};
+
+pub use file::FileStore;
+pub use sqlite::SqliteStore;
+
+/// Storage backend for inbox/outbox messages. Each message is identified by
+/// an id unique within its box (for `FileStore`, the filename). The daemon
+/// and CLI go through this trait instead of touching `messages/` directly.
+pub trait MessageStore: Send + Sync {
+    /// Create whatever the backend needs before the first read or write.
+    fn ensure(&self) -> Result<()>;
+
+    /// Store `msg` in `box_name` ("inbox" or "outbox"). Returns its id.
+    fn write(&self, box_name: &str, msg: &Message) -> Result<String>;
+
+    /// Read one message by id from `box_name`.
+    fn read(&self, box_name: &str, id: &str) -> Result<Message>;
+
+    /// Unread inbox messages, oldest first, as (id, Message) pairs.
+    fn read_inbox(&self) -> Result<Vec<(String, Message)>>;
+
+    /// Ids of unread inbox messages, oldest first, without parsing them.
+    fn list_inbox(&self) -> Result<Vec<String>>;
+
+    /// Outbox messages not yet delivered, oldest first.
+    fn read_outbox(&self) -> Result<Vec<(String, Message)>>;
+
+    /// Inbox messages already handled, oldest first.
+    fn read_inbox_archive(&self) -> Result<Vec<(String, Message)>>;
+
+    /// Delivered outbox messages with their receipts, oldest first.
+    fn read_outbox_archive(&self) -> Result<Vec<(String, Message, Option<DeliveryReceipt>)>>;
+
+    /// Move handled inbox messages to the archive.
+    fn archive(&self, ids: &[String]) -> Result<()>;
+
+    /// Dispose of handled inbox messages according to `disposition`.
+    fn dispose(&self, ids: &[String], disposition: InboxDisposition) -> Result<()>;
+
+    /// Mark an outbox message delivered, recording where it went.
+    fn archive_outbox(&self, id: &str, receipt: &DeliveryReceipt) -> Result<()>;
+
+    /// Count a failed delivery of an outbox message. Returns the updated record.
+    fn record_outbox_failure(&self, id: &str, error: &str) -> Result<DeliveryFailure>;
+
+    /// Take an outbox message out of the delivery queue, keeping its failures.
+    fn dead_letter_outbox(&self, id: &str) -> Result<()>;
+
+    /// Dead-lettered outbox messages with their failures, oldest first.
+    fn read_dead_letters(&self) -> Result<Vec<(String, Message, Option<DeliveryFailure>)>>;
+
+    /// Return every dead letter to the outbox with a fresh attempt count.
+    /// Returns the ids requeued.
+    fn requeue_dead_letters(&self) -> Result<Vec<String>>;
+}
+
+/// The message store for the project in `dir`, as selected by
+/// `message_store` in its `cryo.toml` (file-based when unset).
+pub fn open(dir: &Path) -> Result<Box<dyn MessageStore>> {
+    let kind = config::load_config(&config::config_path(dir))?
+        .map(|c| c.message_store)
+        .unwrap_or_default();
+    open_kind(dir, kind)
+}
+
+/// Open a store of the given kind for the project in `dir`.
+pub fn open_kind(dir: &Path, kind: MessageStoreKind) -> Result<Box<dyn MessageStore>> {
+    Ok(match kind {
+        MessageStoreKind::File => Box::new(FileStore::new(dir.to_path_buf())),
+        MessageStoreKind::Sqlite => Box::new(SqliteStore::open(dir)?),
+    })
+}
+
+/// Which mailbox a [`MessageQuery`] searches. Each covers its archive too.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MessageDirection {
+    /// Messages to the agent, unread and handled
+    Inbox,
+    /// Messages from the agent, pending and delivered
+    Outbox,
+    #[default]
+    All,
+}
+
+/// Filters for `cryo-agent messages`. Every set filter must match.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MessageQuery {
+    #[serde(default)]
+    pub direction: MessageDirection,
+    /// Sender, case-insensitive. "human" matches anyone but the agent and
+    /// cryochamber itself.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from: Option<String>,
+    /// A duration back from now ("2h", "1d12h") or a local time
+    /// ("2026-03-01T09:00")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub since: Option<String>,
+    /// Keep only the newest `limit` matches
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+}
+
+/// One message found by [`query_messages`], in the compact form sent to agents.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MessageRecord {
+    pub id: String,
+    /// "inbox" or "outbox"
+    pub direction: String,
+    /// Inbox: "unread" or "read". Outbox: "pending" or "delivered".
+    pub status: String,
+    pub from: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub subject: String,
+    pub timestamp: String,
+    pub body: String,
+}
+
+/// Senders that are never "human".
+const NON_HUMAN_SENDERS: &[&str] = &["agent", "cryochamber"];
+
+impl MessageQuery {
+    /// Resolve `since` to a cutoff time relative to `now`.
+    fn cutoff(&self, now: NaiveDateTime) -> Result<Option<NaiveDateTime>> {
+        let Some(since) = self.since.as_deref() else {
+            return Ok(None);
+        };
+        if let Ok(duration) = crate::schedule::parse_wake_in(since) {
+            return Ok(Some(now - duration));
+        }
+        ["%Y-%m-%dT%H:%M:%S", "%Y-%m-%dT%H:%M"]
+            .iter()
+            .find_map(|fmt| NaiveDateTime::parse_from_str(since.trim(), fmt).ok())
+            .map(Some)
+            .with_context(|| {
+                format!("Invalid --since {since:?}: expected a duration like \"2h\" or a time like \"2026-03-01T09:00\"")
+            })
+    }
+
+    fn matches_sender(&self, sender: &str) -> bool {
+        match self.from.as_deref() {
+            None => true,
+            Some(want) if want.eq_ignore_ascii_case("human") => !NON_HUMAN_SENDERS
+                .iter()
+                .any(|s| s.eq_ignore_ascii_case(sender)),
+            Some(want) => want.eq_ignore_ascii_case(sender),
+        }
+    }
+}
+
+/// Messages in `store` matching `query`, newest first.
+pub fn query_messages(
+    store: &dyn MessageStore,
+    query: &MessageQuery,
+    now: NaiveDateTime,
+) -> Result<Vec<MessageRecord>> {
+    let cutoff = query.cutoff(now)?;
+    let mut found: Vec<(String, &str, &str, Message)> = Vec::new();
+    if query.direction != MessageDirection::Outbox {
+        found.extend(
+            store
+                .read_inbox()?
+                .into_iter()
+                .map(|(id, m)| (id, "inbox", "unread", m)),
+        );
+        found.extend(
+            store
+                .read_inbox_archive()?
+                .into_iter()
+                .map(|(id, m)| (id, "inbox", "read", m)),
+        );
+    }
+    if query.direction != MessageDirection::Inbox {
+        found.extend(
+            store
+                .read_outbox()?
+                .into_iter()
+                .map(|(id, m)| (id, "outbox", "pending", m)),
+        );
+        found.extend(
+            store
+                .read_outbox_archive()?
+                .into_iter()
+                .map(|(id, m, _)| (id, "outbox", "delivered", m)),
+        );
+    }
+    found.retain(|(_, _, _, m)| {
+        query.matches_sender(&m.from) && cutoff.is_none_or(|c| m.timestamp >= c)
+    });
+    found.sort_by(|a, b| {
+        b.3.timestamp
+            .cmp(&a.3.timestamp)
+            .then_with(|| b.0.cmp(&a.0))
+    });
+    if let Some(limit) = query.limit {
+        found.truncate(limit);
+    }
+    Ok(found
+        .into_iter()
+        .map(|(id, direction, status, m)| MessageRecord {
+            id,
+            direction: direction.to_string(),
+            status: status.to_string(),
+            from: m.from,
+            subject: m.subject,
+            timestamp: m.timestamp.format("%Y-%m-%dT%H:%M:%S").to_string(),
+            body: m.body,
+        })
+        .collect())
+}
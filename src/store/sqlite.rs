@@ -0,0 +1,393 @@
+use anyhow::{Context, Result};
+use chrono::NaiveDateTime;
+use rusqlite::{params, Connection, OptionalExtension};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::config::InboxDisposition;
+use crate::message::{self, DeliveryFailure, DeliveryReceipt, Message};
+use crate::store::MessageStore;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS messages (
+    box         TEXT NOT NULL,
+    id          TEXT NOT NULL,
+    state       TEXT NOT NULL DEFAULT 'new',
+    sender      TEXT NOT NULL,
+    subject     TEXT NOT NULL,
+    body        TEXT NOT NULL,
+    timestamp   TEXT NOT NULL,
+    metadata    TEXT NOT NULL,
+    attachments TEXT NOT NULL,
+    receipt     TEXT,
+    PRIMARY KEY (box, id)
+);
+CREATE INDEX IF NOT EXISTS messages_by_state ON messages (box, state, id);
+CREATE TABLE IF NOT EXISTS delivery_failures (
+    id              TEXT PRIMARY KEY,
+    attempts        INTEGER NOT NULL,
+    last_error      TEXT NOT NULL,
+    last_attempt_at TEXT NOT NULL
+);
+";
+
+/// Row states. `new` is unread inbox / undelivered outbox; `archived` is a
+/// handled inbox message or a delivered outbox message; `consumed` mirrors
+/// `inbox_disposition = "flag"`; `dead` is an outbox message that failed
+/// delivery too often.
+const NEW: &str = "new";
+const ARCHIVED: &str = "archived";
+const CONSUMED: &str = "consumed";
+const DEAD: &str = "dead";
+
+const TS_FMT: &str = "%Y-%m-%dT%H:%M:%S";
+
+/// Database file used by `message_store = "sqlite"`: `messages/messages.db`.
+pub fn db_path(dir: &Path) -> PathBuf {
+    dir.join("messages").join("messages.db")
+}
+
+/// All messages in one SQLite database. Ids follow the `FileStore`
+/// filename scheme, so migrated messages keep their names.
+pub struct SqliteStore {
+    conn: Mutex<Connection>,
+}
+
+impl SqliteStore {
+    /// Open (creating if needed) the database for the project in `dir`.
+    pub fn open(dir: &Path) -> Result<Self> {
+        let path = db_path(dir);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let conn = Connection::open(&path)
+            .with_context(|| format!("Failed to open {}", path.display()))?;
+        // The daemon, CLI and sync utilities all open the database; wait for
+        // each other's writes instead of failing with SQLITE_BUSY.
+        conn.busy_timeout(Duration::from_secs(10))?;
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    fn conn(&self) -> std::sync::MutexGuard<'_, Connection> {
+        self.conn.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Insert `msg` under `id`, keeping `id` if it is free, otherwise
+    /// appending `_2`, `_3`, ... Returns the id used.
+    pub fn insert(&self, box_name: &str, id: &str, msg: &Message, state: &str) -> Result<String> {
+        let conn = self.conn();
+        let stem = id.strip_suffix(".md").unwrap_or(id);
+        let mut candidate = id.to_string();
+        for n in 2.. {
+            let inserted = conn.execute(
+                "INSERT OR IGNORE INTO messages
+                 (box, id, state, sender, subject, body, timestamp, metadata, attachments)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                params![
+                    box_name,
+                    candidate,
+                    state,
+                    msg.from,
+                    msg.subject,
+                    msg.body,
+                    msg.timestamp.format(TS_FMT).to_string(),
+                    serde_json::to_string(&msg.metadata)?,
+                    serde_json::to_string(&msg.attachments)?,
+                ],
+            )?;
+            if inserted == 1 {
+                break;
+            }
+            candidate = format!("{stem}_{n}.md");
+        }
+        Ok(candidate)
+    }
+
+    /// Store `failure` as the delivery record of outbox message `id`,
+    /// replacing any existing one.
+    fn import_failure(&self, id: &str, failure: &DeliveryFailure) -> Result<()> {
+        self.conn().execute(
+            "INSERT OR REPLACE INTO delivery_failures (id, attempts, last_error, last_attempt_at)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![
+                id,
+                failure.attempts,
+                failure.last_error,
+                failure.last_attempt_at.format(TS_FMT).to_string()
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Messages matching `filter` (an SQL condition over `params`), by id.
+    fn select(
+        &self,
+        filter: &str,
+        params: impl rusqlite::Params,
+    ) -> Result<Vec<(String, Message, Option<String>)>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT id, sender, subject, body, timestamp, metadata, attachments, receipt
+             FROM messages WHERE {filter} ORDER BY id"
+        ))?;
+        let rows = stmt.query_map(params, |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, String>(4)?,
+                row.get::<_, String>(5)?,
+                row.get::<_, String>(6)?,
+                row.get::<_, Option<String>>(7)?,
+            ))
+        })?;
+        let mut messages = Vec::new();
+        for row in rows {
+            let (id, from, subject, body, ts, metadata, attachments, receipt) = row?;
+            let msg = Message {
+                from,
+                subject,
+                body,
+                timestamp: NaiveDateTime::parse_from_str(&ts, TS_FMT)
+                    .with_context(|| format!("Invalid timestamp for message {id}"))?,
+                metadata: serde_json::from_str(&metadata)?,
+                attachments: serde_json::from_str(&attachments)?,
+            };
+            messages.push((id, msg, receipt));
+        }
+        Ok(messages)
+    }
+
+    fn query(&self, box_name: &str, state: &str) -> Result<Vec<(String, Message, Option<String>)>> {
+        self.select("box = ?1 AND state = ?2", params![box_name, state])
+    }
+
+    fn set_state(&self, box_name: &str, ids: &[String], state: &str) -> Result<()> {
+        let conn = self.conn();
+        for id in ids {
+            conn.execute(
+                "UPDATE messages SET state = ?3 WHERE box = ?1 AND id = ?2",
+                params![box_name, id, state],
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl MessageStore for SqliteStore {
+    fn ensure(&self) -> Result<()> {
+        // The schema is created on open
+        Ok(())
+    }
+
+    fn write(&self, box_name: &str, msg: &Message) -> Result<String> {
+        self.insert(box_name, &message::message_filename(msg), msg, NEW)
+    }
+
+    fn read(&self, box_name: &str, id: &str) -> Result<Message> {
+        self.select("box = ?1 AND id = ?2", params![box_name, id])?
+            .pop()
+            .map(|(_, msg, _)| msg)
+            .with_context(|| format!("No message {id} in {box_name}"))
+    }
+
+    fn read_inbox(&self) -> Result<Vec<(String, Message)>> {
+        let rows = self.query("inbox", NEW)?;
+        Ok(rows.into_iter().map(|(id, msg, _)| (id, msg)).collect())
+    }
+
+    fn list_inbox(&self) -> Result<Vec<String>> {
+        let conn = self.conn();
+        let mut stmt =
+            conn.prepare("SELECT id FROM messages WHERE box = 'inbox' AND state = ?1 ORDER BY id")?;
+        let ids = stmt
+            .query_map(params![NEW], |row| row.get(0))?
+            .collect::<rusqlite::Result<Vec<String>>>()?;
+        Ok(ids)
+    }
+
+    fn read_outbox(&self) -> Result<Vec<(String, Message)>> {
+        let rows = self.query("outbox", NEW)?;
+        Ok(rows.into_iter().map(|(id, msg, _)| (id, msg)).collect())
+    }
+
+    fn read_inbox_archive(&self) -> Result<Vec<(String, Message)>> {
+        let rows = self.query("inbox", ARCHIVED)?;
+        Ok(rows.into_iter().map(|(id, msg, _)| (id, msg)).collect())
+    }
+
+    fn read_outbox_archive(&self) -> Result<Vec<(String, Message, Option<DeliveryReceipt>)>> {
+        let rows = self.query("outbox", ARCHIVED)?;
+        Ok(rows
+            .into_iter()
+            .map(|(id, msg, receipt)| {
+                let receipt = receipt.and_then(|r| serde_json::from_str(&r).ok());
+                (id, msg, receipt)
+            })
+            .collect())
+    }
+
+    fn archive(&self, ids: &[String]) -> Result<()> {
+        self.set_state("inbox", ids, ARCHIVED)
+    }
+
+    fn dispose(&self, ids: &[String], disposition: InboxDisposition) -> Result<()> {
+        match disposition {
+            InboxDisposition::Archive => self.archive(ids),
+            InboxDisposition::Flag => self.set_state("inbox", ids, CONSUMED),
+            InboxDisposition::Delete => {
+                let conn = self.conn();
+                for id in ids {
+                    conn.execute(
+                        "DELETE FROM messages WHERE box = 'inbox' AND id = ?1",
+                        params![id],
+                    )?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn archive_outbox(&self, id: &str, receipt: &DeliveryReceipt) -> Result<()> {
+        self.conn().execute(
+            "UPDATE messages SET state = ?2, receipt = ?3 WHERE box = 'outbox' AND id = ?1",
+            params![id, ARCHIVED, serde_json::to_string(receipt)?],
+        )?;
+        self.conn()
+            .execute("DELETE FROM delivery_failures WHERE id = ?1", params![id])?;
+        Ok(())
+    }
+
+    fn record_outbox_failure(&self, id: &str, error: &str) -> Result<DeliveryFailure> {
+        let now = chrono::Local::now().naive_local();
+        let conn = self.conn();
+        conn.execute(
+            "INSERT INTO delivery_failures (id, attempts, last_error, last_attempt_at)
+             VALUES (?1, 1, ?2, ?3)
+             ON CONFLICT (id) DO UPDATE SET attempts = attempts + 1,
+                 last_error = excluded.last_error, last_attempt_at = excluded.last_attempt_at",
+            params![id, error, now.format(TS_FMT).to_string()],
+        )?;
+        let attempts = conn.query_row(
+            "SELECT attempts FROM delivery_failures WHERE id = ?1",
+            params![id],
+            |row| row.get(0),
+        )?;
+        Ok(DeliveryFailure {
+            attempts,
+            last_error: error.to_string(),
+            last_attempt_at: now,
+        })
+    }
+
+    fn dead_letter_outbox(&self, id: &str) -> Result<()> {
+        self.set_state("outbox", &[id.to_string()], DEAD)
+    }
+
+    fn read_dead_letters(&self) -> Result<Vec<(String, Message, Option<DeliveryFailure>)>> {
+        let rows = self.query("outbox", DEAD)?;
+        let conn = self.conn();
+        let mut stmt = conn.prepare(
+            "SELECT attempts, last_error, last_attempt_at FROM delivery_failures WHERE id = ?1",
+        )?;
+        let mut letters = Vec::new();
+        for (id, msg, _) in rows {
+            let failure = stmt
+                .query_row(params![id], |row| {
+                    Ok((row.get(0)?, row.get(1)?, row.get::<_, String>(2)?))
+                })
+                .optional()?
+                .and_then(|(attempts, last_error, at)| {
+                    let last_attempt_at = NaiveDateTime::parse_from_str(&at, TS_FMT).ok()?;
+                    Some(DeliveryFailure {
+                        attempts,
+                        last_error,
+                        last_attempt_at,
+                    })
+                });
+            letters.push((id, msg, failure));
+        }
+        Ok(letters)
+    }
+
+    fn requeue_dead_letters(&self) -> Result<Vec<String>> {
+        let ids: Vec<String> = self
+            .query("outbox", DEAD)?
+            .into_iter()
+            .map(|(id, _, _)| id)
+            .collect();
+        self.set_state("outbox", &ids, NEW)?;
+        let conn = self.conn();
+        for id in &ids {
+            conn.execute("DELETE FROM delivery_failures WHERE id = ?1", params![id])?;
+        }
+        Ok(ids)
+    }
+}
+
+/// Import file-based messages into the SQLite store, keeping their ids and
+/// inbox/outbox/archive/dead-letter state and delivery failure counts, then
+/// remove the imported files. Messages already in the database are skipped.
+/// Returns the number imported.
+pub fn migrate_files(dir: &Path, store: &SqliteStore) -> Result<usize> {
+    let mut imported = 0;
+    let mut import = |box_name: &str, id: &str, msg: &Message, state: &str| -> Result<()> {
+        let exists = store
+            .conn()
+            .query_row(
+                "SELECT 1 FROM messages WHERE box = ?1 AND id = ?2",
+                params![box_name, id],
+                |_| Ok(()),
+            )
+            .optional()?
+            .is_some();
+        if !exists {
+            store.insert(box_name, id, msg, state)?;
+            imported += 1;
+        }
+        Ok(())
+    };
+
+    let message::MessageDirs { inbox, outbox } = message::project_dirs(dir)?;
+    for (id, msg) in message::read_inbox(dir)? {
+        import("inbox", &id, &msg, NEW)?;
+        std::fs::remove_file(inbox.join(&id))?;
+    }
+    for (id, msg) in message::read_inbox_archive(dir)? {
+        import("inbox", &id, &msg, ARCHIVED)?;
+        std::fs::remove_file(inbox.join("archive").join(&id))?;
+    }
+    for (id, msg) in message::read_outbox(dir)? {
+        import("outbox", &id, &msg, NEW)?;
+        if let Some(failure) = message::read_outbox_failure(dir, &id)? {
+            store.import_failure(&id, &failure)?;
+        }
+        std::fs::remove_file(outbox.join(&id))?;
+        let _ = std::fs::remove_file(outbox.join(message::failure_filename(&id)));
+    }
+    let dead = outbox.join("dead-letter");
+    for (id, msg, failure) in message::read_dead_letters(dir)? {
+        import("outbox", &id, &msg, DEAD)?;
+        if let Some(failure) = failure {
+            store.import_failure(&id, &failure)?;
+        }
+        std::fs::remove_file(dead.join(&id))?;
+        let _ = std::fs::remove_file(dead.join(message::failure_filename(&id)));
+    }
+    for (id, msg, receipt) in message::read_outbox_archive(dir)? {
+        import("outbox", &id, &msg, ARCHIVED)?;
+        if let Some(receipt) = receipt {
+            store.archive_outbox(&id, &receipt)?;
+        }
+        let archive = outbox.join("archive");
+        std::fs::remove_file(archive.join(&id))?;
+        let _ = std::fs::remove_file(archive.join(message::receipt_filename(&id)));
+    }
+    Ok(imported)
+}
@@ -0,0 +1,150 @@
+// src/usage.rs
+//! Token and cost accounting from agent output. `usage_pattern` in cryo.toml
+//! is a regex with named groups `tokens` and/or `cost`; every match in a
+//! session's output is summed into one `usage:` event in cryo.log, which
+//! periodic reports roll up.
+use anyhow::{bail, Context, Result};
+use regex::Regex;
+
+use crate::config::CryoConfig;
+
+const USAGE_EVENT_PREFIX: &str = "usage: ";
+
+/// Tokens and cost summed over a session (or a report period).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Usage {
+    pub tokens: u64,
+    pub cost: f64,
+}
+
+impl Usage {
+    pub fn add(&mut self, other: Usage) {
+        self.tokens += other.tokens;
+        self.cost += other.cost;
+    }
+
+    /// Log line body: `usage: tokens=12345 cost=0.4200`.
+    pub fn to_event(&self) -> String {
+        format!(
+            "{USAGE_EVENT_PREFIX}tokens={} cost={:.4}",
+            self.tokens, self.cost
+        )
+    }
+
+    /// Read back a `[HH:MM:SS] usage: tokens=.. cost=..` log line.
+    pub fn parse_event(line: &str) -> Option<Usage> {
+        let (_, rest) = line.strip_prefix('[')?.split_once("] ")?;
+        let rest = rest.strip_prefix(USAGE_EVENT_PREFIX)?;
+        let mut usage = Usage::default();
+        for field in rest.split_whitespace() {
+            match field.split_once('=')? {
+                ("tokens", v) => usage.tokens = v.parse().ok()?,
+                ("cost", v) => usage.cost = v.parse().ok()?,
+                _ => {}
+            }
+        }
+        Some(usage)
+    }
+}
+
+/// Parse a captured number, ignoring thousands separators and currency signs
+/// ("12,345", "$0.42").
+fn parse_number(s: &str) -> Option<f64> {
+    let cleaned: String = s
+        .chars()
+        .filter(|c| c.is_ascii_digit() || *c == '.')
+        .collect();
+    cleaned.parse().ok()
+}
+
+/// Compiled `usage_pattern`.
+#[derive(Debug, Clone)]
+pub struct UsageExtractor {
+    re: Regex,
+}
+
+impl UsageExtractor {
+    /// Compile `pattern`; it must have a `tokens` or `cost` named group.
+    pub fn new(pattern: &str) -> Result<Self> {
+        let re =
+            Regex::new(pattern).with_context(|| format!("Invalid usage_pattern: {pattern}"))?;
+        let has_group = |name| re.capture_names().flatten().any(|n| n == name);
+        if !has_group("tokens") && !has_group("cost") {
+            bail!("usage_pattern needs a (?P<tokens>..) or (?P<cost>..) group: {pattern}");
+        }
+        Ok(Self { re })
+    }
+
+    /// The extractor for `usage_pattern`, or `None` when it is unset.
+    pub fn from_config(config: &CryoConfig) -> Result<Option<Self>> {
+        config.usage_pattern.as_deref().map(Self::new).transpose()
+    }
+
+    /// Sum every match in `output`. `None` if nothing matched.
+    pub fn extract(&self, output: &str) -> Option<Usage> {
+        let mut total: Option<Usage> = None;
+        for caps in self.re.captures_iter(output) {
+            let tokens = caps
+                .name("tokens")
+                .and_then(|m| parse_number(m.as_str()))
+                .unwrap_or(0.0);
+            let cost = caps
+                .name("cost")
+                .and_then(|m| parse_number(m.as_str()))
+                .unwrap_or(0.0);
+            total.get_or_insert_with(Usage::default).add(Usage {
+                tokens: tokens as u64,
+                cost,
+            });
+        }
+        total
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_claude_style_cost_line() {
+        let ex = UsageExtractor::new(r"Total cost:\s+\$(?P<cost>[\d.]+)").unwrap();
+        let out = "working...\nTotal cost: $0.1234\nmore\nTotal cost: $0.0100\n";
+        let usage = ex.extract(out).unwrap();
+        assert_eq!(usage.tokens, 0);
+        assert!((usage.cost - 0.1334).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_codex_style_tokens_line() {
+        let ex = UsageExtractor::new(r"tokens used:\s*(?P<tokens>[\d,]+)").unwrap();
+        let out = "[2026-03-01] tokens used: 12,345\n[2026-03-01] tokens used: 655\n";
+        assert_eq!(ex.extract(out).unwrap().tokens, 13_000);
+    }
+
+    #[test]
+    fn test_tokens_and_cost_in_one_line() {
+        let ex =
+            UsageExtractor::new(r"usage: (?P<tokens>\d+) tokens \(\$(?P<cost>[\d.]+)\)").unwrap();
+        let usage = ex.extract("usage: 2048 tokens ($0.03)").unwrap();
+        assert_eq!(usage.tokens, 2048);
+        assert!((usage.cost - 0.03).abs() < 1e-9);
+        assert!(ex.extract("no usage here").is_none());
+    }
+
+    #[test]
+    fn test_pattern_needs_a_named_group() {
+        assert!(UsageExtractor::new(r"cost: \d+").is_err());
+        assert!(UsageExtractor::new(r"(?P<cost>[").is_err());
+    }
+
+    #[test]
+    fn test_event_roundtrip() {
+        let usage = Usage {
+            tokens: 1500,
+            cost: 0.25,
+        };
+        let line = format!("[12:00:00] {}", usage.to_event());
+        assert_eq!(Usage::parse_event(&line), Some(usage));
+        assert_eq!(Usage::parse_event("[12:00:00] note: \"x\""), None);
+    }
+}
@@ -50,7 +50,10 @@ pub fn build_router(project_dir: PathBuf) -> Router {
         .route("/api/messages", get(get_messages))
         .route("/api/send", post(post_send))
         .route("/api/wake", post(post_wake))
+        .route("/api/reviews", get(get_reviews))
+        .route("/api/reviews/decide", post(post_review_decide))
         .route("/api/events", get(get_events))
+        .route("/metrics", get(get_metrics))
         .with_state(state)
 }
 
@@ -85,7 +88,7 @@ async fn get_status(State(state): State<Arc<AppState>>) -> Json<Value> {
         .flatten()
         .unwrap_or_default();
 
-    let (running, session, agent, next_wake) =
+    let (running, session, agent, next_wake, awaiting_message, last_result) =
         match state::load_state(&state::state_path(dir)).ok().flatten() {
             Some(st) => {
                 let is_running = state::is_locked(&st);
@@ -94,9 +97,16 @@ async fn get_status(State(state): State<Arc<AppState>>) -> Json<Value> {
                     .as_deref()
                     .unwrap_or(&cfg.agent)
                     .to_string();
-                (is_running, st.session_number, effective_agent, st.next_wake)
+                (
+                    is_running,
+                    st.session_number,
+                    effective_agent,
+                    st.next_wake.clone(),
+                    st.awaiting_message,
+                    st.last_result(),
+                )
             }
-            None => (false, 0, cfg.agent.clone(), None),
+            None => (false, 0, cfg.agent.clone(), None, false, None),
         };
 
     let log_file = log::log_path(dir);
@@ -114,12 +124,16 @@ async fn get_status(State(state): State<Arc<AppState>>) -> Json<Value> {
     let effective_wake =
         next_wake.or_else(|| log::parse_latest_session_wake(&log_file).ok().flatten());
 
-    let next_wake_rel = effective_wake.as_deref().and_then(|w| {
-        let wake = chrono::NaiveDateTime::parse_from_str(w, "%Y-%m-%dT%H:%M").ok()?;
-        let now = chrono::Local::now().naive_local();
-        let diff_ms = (wake - now).num_milliseconds();
-        Some(format!("{w} ({})", format_relative_time(diff_ms)))
-    });
+    let next_wake_rel = if awaiting_message {
+        Some("waiting for message".to_string())
+    } else {
+        effective_wake.as_deref().and_then(|w| {
+            let wake = chrono::NaiveDateTime::parse_from_str(w, "%Y-%m-%dT%H:%M").ok()?;
+            let now = chrono::Local::now().naive_local();
+            let diff_ms = (wake - now).num_milliseconds();
+            Some(format!("{w} ({})", format_relative_time(diff_ms)))
+        })
+    };
 
     Json(json!({
         "running": running,
@@ -127,6 +141,7 @@ async fn get_status(State(state): State<Arc<AppState>>) -> Json<Value> {
         "agent": agent,
         "log_tail": log_tail,
         "next_wake": next_wake_rel,
+        "last_result": last_result,
         "notes": notes,
         "task": task,
     }))
@@ -136,22 +151,25 @@ async fn get_messages(State(state): State<Arc<AppState>>) -> Json<Value> {
     let dir = &state.project_dir;
 
     let mut all_messages: Vec<Value> = Vec::new();
+    let Ok(store) = crate::store::open(dir) else {
+        return Json(Value::Array(all_messages));
+    };
 
     // Include archived inbox messages (already processed by agent)
-    if let Ok(archived) = message::read_inbox_archive(dir) {
+    if let Ok(archived) = store.read_inbox_archive() {
         for (_filename, msg) in archived {
             all_messages.push(message_to_json(&msg, "inbox"));
         }
     }
 
     // Include pending inbox messages (not yet processed)
-    if let Ok(inbox) = message::read_inbox(dir) {
+    if let Ok(inbox) = store.read_inbox() {
         for (_filename, msg) in inbox {
             all_messages.push(message_to_json(&msg, "inbox"));
         }
     }
 
-    if let Ok(outbox) = message::read_outbox(dir) {
+    if let Ok(outbox) = store.read_outbox() {
         for (_filename, msg) in outbox {
             all_messages.push(message_to_json(&msg, "outbox"));
         }
@@ -171,6 +189,7 @@ fn message_to_json(msg: &message::Message, direction: &str) -> Value {
     json!({
         "direction": direction,
         "from": msg.from,
+        "to": msg.recipients(),
         "subject": msg.subject,
         "body": msg.body,
         "timestamp": msg.timestamp.format("%Y-%m-%dT%H:%M:%S").to_string(),
@@ -182,6 +201,9 @@ struct SendRequest {
     body: String,
     from: Option<String>,
     subject: Option<String>,
+    /// Signal the daemon after writing, like `cryo send --wake`
+    #[serde(default)]
+    wake: bool,
 }
 
 async fn post_send(
@@ -198,12 +220,18 @@ async fn post_send(
         body: req.body.clone(),
         timestamp: chrono::Local::now().naive_local(),
         metadata: std::collections::BTreeMap::new(),
+        attachments: Vec::new(),
     };
 
-    match message::write_message(dir, "inbox", &msg) {
-        Ok(_) => Json(json!({"ok": true, "message": "Message sent"})),
-        Err(e) => Json(json!({"ok": false, "message": format!("Failed: {e}")})),
+    if let Err(e) = crate::store::open(dir).and_then(|store| store.write("inbox", &msg)) {
+        return Json(json!({"ok": false, "message": format!("Failed: {e}")}));
     }
+    let message = match (req.wake, req.wake && signal_daemon(dir)) {
+        (false, _) => "Message sent",
+        (true, true) => "Message sent, wake signal sent",
+        (true, false) => "Message queued (no daemon running)",
+    };
+    Json(json!({"ok": true, "message": message}))
 }
 
 #[derive(Deserialize)]
@@ -227,9 +255,10 @@ async fn post_wake(
         body: body.to_string(),
         timestamp: chrono::Local::now().naive_local(),
         metadata: std::collections::BTreeMap::new(),
+        attachments: Vec::new(),
     };
 
-    if let Err(e) = message::write_message(dir, "inbox", &msg) {
+    if let Err(e) = crate::store::open(dir).and_then(|store| store.write("inbox", &msg)) {
         return Json(json!({"ok": false, "message": format!("Failed to write: {e}")}));
     }
 
@@ -242,6 +271,139 @@ async fn post_wake(
     }))
 }
 
+async fn get_reviews(State(state): State<Arc<AppState>>) -> Json<Value> {
+    let reviews = crate::review::list(&state.project_dir).unwrap_or_default();
+    Json(json!(reviews))
+}
+
+#[derive(Deserialize)]
+struct ReviewDecision {
+    id: String,
+    approve: bool,
+    note: Option<String>,
+    from: Option<String>,
+}
+
+async fn post_review_decide(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<ReviewDecision>,
+) -> Json<Value> {
+    let dir = &state.project_dir;
+    let from = req
+        .from
+        .as_deref()
+        .filter(|f| !f.is_empty())
+        .unwrap_or("human");
+    let result = crate::store::open(dir).and_then(|store| {
+        crate::review::answer(
+            dir,
+            store.as_ref(),
+            &req.id,
+            req.approve,
+            req.note.as_deref(),
+            from,
+            chrono::Local::now().naive_local(),
+        )
+    });
+    match result {
+        Ok(review) => {
+            signal_daemon(dir);
+            Json(json!({
+                "ok": true,
+                "message": format!("Review {} {}", review.id, review.status.as_str())
+            }))
+        }
+        Err(e) => Json(json!({"ok": false, "message": format!("{e:#}")})),
+    }
+}
+
+async fn get_metrics(
+    State(state): State<Arc<AppState>>,
+) -> ([(axum::http::header::HeaderName, &'static str); 1], String) {
+    let body = render_metrics(&state.project_dir, chrono::Local::now().naive_local());
+    (
+        [(
+            axum::http::header::CONTENT_TYPE,
+            "text/plain; version=0.0.4; charset=utf-8",
+        )],
+        body,
+    )
+}
+
+/// Render the project's metrics in the Prometheus text exposition format.
+/// Every sample carries a `project` label so one scraper can watch many
+/// projects; `cryo_next_wake_seconds` is left out when no wake is scheduled.
+pub fn render_metrics(dir: &Path, now: chrono::NaiveDateTime) -> String {
+    let cfg = config::load_config(&config::config_path(dir))
+        .ok()
+        .flatten()
+        .unwrap_or_default();
+    let st = state::load_state(&state::state_path(dir)).ok().flatten();
+    let sessions = log::parse_sessions_since(
+        &log::log_path(dir),
+        chrono::NaiveDateTime::MIN,
+        &log::LogFormat::from_config(&cfg),
+    )
+    .unwrap_or_default();
+    let failed = sessions
+        .iter()
+        .filter(|s| s.outcome == log::SessionOutcome::Failed)
+        .count();
+    let running = st.as_ref().is_some_and(state::is_locked);
+    let next_wake = st
+        .as_ref()
+        .filter(|st| !st.awaiting_message)
+        .and_then(|st| st.next_wake.as_deref())
+        .and_then(|w| chrono::NaiveDateTime::parse_from_str(w, "%Y-%m-%dT%H:%M").ok());
+    let unread = crate::store::open(dir)
+        .and_then(|store| store.read_inbox())
+        .map_or(0, |inbox| inbox.len());
+
+    let project = cfg
+        .project_name(dir)
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n");
+    let mut out = String::new();
+    let mut metric = |name: &str, kind: &str, help: &str, value: Option<i64>| {
+        out.push_str(&format!("# HELP {name} {help}\n# TYPE {name} {kind}\n"));
+        if let Some(value) = value {
+            out.push_str(&format!("{name}{{project=\"{project}\"}} {value}\n"));
+        }
+    };
+    metric(
+        "cryo_sessions_total",
+        "counter",
+        "Sessions recorded in cryo.log.",
+        Some(sessions.len() as i64),
+    );
+    metric(
+        "cryo_sessions_failed_total",
+        "counter",
+        "Sessions that ended in failure.",
+        Some(failed as i64),
+    );
+    metric(
+        "cryo_running",
+        "gauge",
+        "1 if the daemon holds the project lock, else 0.",
+        Some(running as i64),
+    );
+    metric(
+        "cryo_next_wake_seconds",
+        "gauge",
+        "Seconds until the next scheduled wake (negative when overdue).",
+        next_wake.map(|wake| (wake - now).num_seconds()),
+    );
+    metric(
+        "cryo_inbox_unread",
+        "gauge",
+        "Messages waiting in the inbox.",
+        Some(unread as i64),
+    );
+    out
+}
+
 async fn get_events(
     State(state): State<Arc<AppState>>,
 ) -> Sse<impl tokio_stream::Stream<Item = Result<Event, Infallible>>> {
@@ -277,17 +439,61 @@ async fn get_events(
     Sse::new(stream).keep_alive(KeepAlive::default())
 }
 
+/// Read the complete lines appended to `path` since byte `offset` and
+/// advance `offset` past them. A trailing partial line is left for the next
+/// call, so a multi-byte character split across writes is never cut. If the
+/// file shrank (rotation or truncation), reading restarts from the top.
+pub fn read_new_lines(path: &Path, offset: &mut u64) -> Vec<String> {
+    use std::io::{Read, Seek, SeekFrom};
+
+    let Ok(mut file) = std::fs::File::open(path) else {
+        return Vec::new();
+    };
+    let len = file.metadata().map(|m| m.len()).unwrap_or(0);
+    if len < *offset {
+        *offset = 0;
+    }
+    if len == *offset || file.seek(SeekFrom::Start(*offset)).is_err() {
+        return Vec::new();
+    }
+    let mut buf = Vec::new();
+    if file.read_to_end(&mut buf).is_err() {
+        return Vec::new();
+    }
+    let Some(end) = buf.iter().rposition(|&b| b == b'\n') else {
+        return Vec::new();
+    };
+    *offset += end as u64 + 1;
+    String::from_utf8_lossy(&buf[..end])
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
 /// Spawn file watchers on inbox/, outbox/, and cryo.log.
+/// cryo.log is polled every `poll_ms` and timer.json every `4 * poll_ms`.
 /// Detected changes are broadcast as SseEvents.
-pub fn spawn_watchers(project_dir: &Path, tx: tokio::sync::broadcast::Sender<SseEvent>) {
+pub fn spawn_watchers(
+    project_dir: &Path,
+    tx: tokio::sync::broadcast::Sender<SseEvent>,
+    poll_ms: u64,
+) {
+    let poll = std::time::Duration::from_millis(poll_ms.max(1));
     let dir = project_dir.to_path_buf();
     let tx_clone = tx.clone();
 
     // Watch messages/ for new files
     std::thread::spawn(move || {
         let tx = tx_clone;
-        let inbox = dir.join("messages/inbox");
-        let outbox = dir.join("messages/outbox");
+        let crate::message::MessageDirs { inbox, outbox } = match crate::message::project_dirs(&dir)
+        {
+            Ok(dirs) => dirs,
+            Err(e) => {
+                eprintln!("Web: not watching messages: {e:#}");
+                return;
+            }
+        };
 
         let tx2 = tx.clone();
         let inbox2 = inbox.clone();
@@ -330,9 +536,9 @@ pub fn spawn_watchers(project_dir: &Path, tx: tokio::sync::broadcast::Sender<Sse
         let _ = watcher.watch(&inbox, RecursiveMode::NonRecursive);
         let _ = watcher.watch(&outbox, RecursiveMode::NonRecursive);
 
-        // Keep the watcher alive
+        // Keep the watcher alive; events arrive on notify's own thread
         loop {
-            std::thread::sleep(std::time::Duration::from_secs(3600));
+            std::thread::park();
         }
     });
 
@@ -341,23 +547,12 @@ pub fn spawn_watchers(project_dir: &Path, tx: tokio::sync::broadcast::Sender<Sse
     let tx_log = tx.clone();
     std::thread::spawn(move || {
         let log_path = crate::log::log_path(&dir2);
-        let mut last_size = log_path.metadata().map(|m| m.len()).unwrap_or(0);
+        let mut offset = log_path.metadata().map(|m| m.len()).unwrap_or(0);
 
         loop {
-            std::thread::sleep(std::time::Duration::from_millis(500));
-            if let Ok(meta) = log_path.metadata() {
-                let current_size = meta.len();
-                if current_size > last_size {
-                    if let Ok(content) = std::fs::read_to_string(&log_path) {
-                        let new_bytes = &content[last_size as usize..];
-                        for line in new_bytes.lines() {
-                            if !line.trim().is_empty() {
-                                let _ = tx_log.send(SseEvent::LogLine(line.to_string()));
-                            }
-                        }
-                    }
-                    last_size = current_size;
-                }
+            std::thread::sleep(poll);
+            for line in read_new_lines(&log_path, &mut offset) {
+                let _ = tx_log.send(SseEvent::LogLine(line));
             }
         }
     });
@@ -370,7 +565,7 @@ pub fn spawn_watchers(project_dir: &Path, tx: tokio::sync::broadcast::Sender<Sse
         let mut last_content = std::fs::read_to_string(&state_path).unwrap_or_default();
 
         loop {
-            std::thread::sleep(std::time::Duration::from_secs(2));
+            std::thread::sleep(poll * 4);
             if let Ok(content) = std::fs::read_to_string(&state_path) {
                 if content != last_content {
                     let _ = tx_state.send(SseEvent::StatusChange);
@@ -385,9 +580,14 @@ fn signal_daemon(dir: &std::path::Path) -> bool {
     crate::process::signal_daemon_wake(dir)
 }
 
-pub async fn serve(project_dir: PathBuf, host: &str, port: u16) -> anyhow::Result<()> {
+pub async fn serve(
+    project_dir: PathBuf,
+    host: &str,
+    port: u16,
+    poll_ms: u64,
+) -> anyhow::Result<()> {
     // Ensure message dirs exist
-    crate::message::ensure_dirs(&project_dir)?;
+    crate::store::open(&project_dir)?.ensure()?;
 
     let (tx, _rx) = tokio::sync::broadcast::channel::<SseEvent>(256);
     let state = Arc::new(AppState {
@@ -395,7 +595,7 @@ pub async fn serve(project_dir: PathBuf, host: &str, port: u16) -> anyhow::Resul
         tx: tx.clone(),
     });
 
-    spawn_watchers(&project_dir, tx);
+    spawn_watchers(&project_dir, tx, poll_ms);
 
     let app = Router::new()
         .route("/", get(get_index))
@@ -403,7 +603,10 @@ pub async fn serve(project_dir: PathBuf, host: &str, port: u16) -> anyhow::Resul
         .route("/api/messages", get(get_messages))
         .route("/api/send", post(post_send))
         .route("/api/wake", post(post_wake))
+        .route("/api/reviews", get(get_reviews))
+        .route("/api/reviews/decide", post(post_review_decide))
         .route("/api/events", get(get_events))
+        .route("/metrics", get(get_metrics))
         .with_state(state);
 
     let addr = format!("{host}:{port}");
@@ -418,6 +621,68 @@ mod tests {
     use super::*;
     use axum::extract::State;
 
+    #[test]
+    fn test_render_metrics() {
+        let dir = tempfile::tempdir().unwrap();
+        crate::message::ensure_dirs(dir.path()).unwrap();
+        let log_path = log::log_path(dir.path());
+        for (n, exit) in [(1, "agent exited (code 0)"), (2, "agent exited (code 1)")] {
+            let mut logger = log::EventLogger::begin(&log_path, n, "task", "mock", &[]).unwrap();
+            if n == 1 {
+                logger
+                    .log_event("hibernate: wake=2026-03-01T09:00")
+                    .unwrap();
+            }
+            logger.log_event(exit).unwrap();
+            logger.finish("done").unwrap();
+        }
+        let st = state::CryoState {
+            session_number: 2,
+            next_wake: Some("2026-03-01T09:00".to_string()),
+            ..Default::default()
+        };
+        state::save_state(&state::state_path(dir.path()), &st).unwrap();
+        let msg = crate::message::Message {
+            from: "human".to_string(),
+            subject: "Hi".to_string(),
+            body: "hello".to_string(),
+            timestamp: chrono::Local::now().naive_local(),
+            metadata: Default::default(),
+            attachments: Vec::new(),
+        };
+        crate::message::write_message(dir.path(), "inbox", &msg).unwrap();
+
+        let now =
+            chrono::NaiveDateTime::parse_from_str("2026-03-01T08:00", "%Y-%m-%dT%H:%M").unwrap();
+        let text = render_metrics(dir.path(), now);
+        let project = config::CryoConfig::default().project_name(dir.path());
+        let sample = |name: &str| format!("{name}{{project=\"{project}\"}} ");
+        assert!(
+            text.contains("# TYPE cryo_sessions_total counter"),
+            "{text}"
+        );
+        assert!(
+            text.contains(&format!("{}2\n", sample("cryo_sessions_total"))),
+            "{text}"
+        );
+        assert!(
+            text.contains(&format!("{}1\n", sample("cryo_sessions_failed_total"))),
+            "{text}"
+        );
+        assert!(
+            text.contains(&format!("{}0\n", sample("cryo_running"))),
+            "{text}"
+        );
+        assert!(
+            text.contains(&format!("{}3600\n", sample("cryo_next_wake_seconds"))),
+            "{text}"
+        );
+        assert!(
+            text.contains(&format!("{}1\n", sample("cryo_inbox_unread"))),
+            "{text}"
+        );
+    }
+
     #[tokio::test]
     async fn test_get_status_no_daemon() {
         let dir = tempfile::tempdir().unwrap();
@@ -461,6 +726,7 @@ mod tests {
                 .and_hms_opt(10, 0, 0)
                 .unwrap(),
             metadata: std::collections::BTreeMap::new(),
+            attachments: Vec::new(),
         };
         crate::message::write_message(dir.path(), "inbox", &msg).unwrap();
 
@@ -474,6 +740,7 @@ mod tests {
                 .and_hms_opt(10, 5, 0)
                 .unwrap(),
             metadata: std::collections::BTreeMap::new(),
+            attachments: Vec::new(),
         };
         crate::message::write_message(dir.path(), "outbox", &reply).unwrap();
 
@@ -505,6 +772,7 @@ mod tests {
                 .and_hms_opt(9, 0, 0)
                 .unwrap(),
             metadata: std::collections::BTreeMap::new(),
+            attachments: Vec::new(),
         };
         crate::message::write_message(dir.path(), "inbox", &msg).unwrap();
         let inbox = crate::message::read_inbox(dir.path()).unwrap();
@@ -537,6 +805,7 @@ mod tests {
             body: "Please fix the bug".to_string(),
             from: Some("alice".to_string()),
             subject: Some("Bug report".to_string()),
+            wake: false,
         });
         let resp = post_send(State(state), body).await;
         assert!(resp.0["ok"].as_bool().unwrap());
@@ -562,6 +831,7 @@ mod tests {
             body: "Hello".to_string(),
             from: None,
             subject: None,
+            wake: false,
         });
         let resp = post_send(State(state), body).await;
         assert!(resp.0["ok"].as_bool().unwrap());
@@ -570,6 +840,64 @@ mod tests {
         assert_eq!(msgs[0].1.from, "human");
     }
 
+    #[tokio::test]
+    async fn test_post_send_with_wake_writes_one_message() {
+        let dir = tempfile::tempdir().unwrap();
+        crate::message::ensure_dirs(dir.path()).unwrap();
+        let (tx, _rx) = tokio::sync::broadcast::channel::<SseEvent>(16);
+        let state = Arc::new(AppState {
+            project_dir: dir.path().to_path_buf(),
+            tx,
+        });
+        let req: SendRequest =
+            serde_json::from_value(json!({"body": "look at this", "wake": true})).unwrap();
+        let resp = post_send(State(state), Json(req)).await;
+        assert_eq!(resp.0["ok"], true);
+        assert_eq!(resp.0["message"], "Message queued (no daemon running)");
+        let inbox = crate::message::read_inbox(dir.path()).unwrap();
+        assert_eq!(inbox.len(), 1);
+        assert_eq!(inbox[0].1.body, "look at this");
+    }
+
+    #[tokio::test]
+    async fn test_review_decide_writes_decision_to_inbox() {
+        let dir = tempfile::tempdir().unwrap();
+        crate::message::ensure_dirs(dir.path()).unwrap();
+        let now = chrono::Local::now().naive_local();
+        crate::review::request(dir.path(), Some("deploy"), "Deploy?", now, 24).unwrap();
+        let (tx, _rx) = tokio::sync::broadcast::channel::<SseEvent>(16);
+        let state = Arc::new(AppState {
+            project_dir: dir.path().to_path_buf(),
+            tx,
+        });
+
+        let resp = get_reviews(State(state.clone())).await;
+        assert_eq!(resp.0[0]["status"], "pending");
+
+        let body = Json(ReviewDecision {
+            id: "deploy".to_string(),
+            approve: false,
+            note: Some("not today".to_string()),
+            from: None,
+        });
+        let resp = post_review_decide(State(state.clone()), body).await;
+        assert!(resp.0["ok"].as_bool().unwrap());
+
+        let msgs = crate::message::read_inbox(dir.path()).unwrap();
+        assert_eq!(msgs.len(), 1);
+        assert_eq!(msgs[0].1.metadata["decision"], "rejected");
+
+        // A second decision is refused
+        let body = Json(ReviewDecision {
+            id: "deploy".to_string(),
+            approve: true,
+            note: None,
+            from: None,
+        });
+        let resp = post_review_decide(State(state), body).await;
+        assert!(!resp.0["ok"].as_bool().unwrap());
+    }
+
     #[tokio::test]
     async fn test_broadcast_channel() {
         let (tx, mut rx1) = tokio::sync::broadcast::channel::<SseEvent>(16);
@@ -581,6 +909,30 @@ mod tests {
         assert!(matches!(rx2.recv().await.unwrap(), SseEvent::StatusChange));
     }
 
+    #[test]
+    fn test_read_new_lines_utf8_and_truncation() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cryo.log");
+        std::fs::write(&path, "first\n").unwrap();
+        let mut offset = 0;
+        assert_eq!(read_new_lines(&path, &mut offset), vec!["first"]);
+
+        // A write that stops mid-character: only complete lines are consumed
+        let mut bytes = "héllo\nwörld".as_bytes().to_vec();
+        bytes.truncate(bytes.len() - 4);
+        let mut content = b"first\n".to_vec();
+        content.extend_from_slice(&bytes);
+        std::fs::write(&path, &content).unwrap();
+        assert_eq!(read_new_lines(&path, &mut offset), vec!["héllo"]);
+        std::fs::write(&path, "first\nhéllo\nwörld\n").unwrap();
+        assert_eq!(read_new_lines(&path, &mut offset), vec!["wörld"]);
+
+        // Rotation: the file shrinks, reading restarts from the top
+        std::fs::write(&path, "ñew\n").unwrap();
+        assert_eq!(read_new_lines(&path, &mut offset), vec!["ñew"]);
+        assert!(read_new_lines(&path, &mut offset).is_empty());
+    }
+
     #[test]
     fn test_format_relative_time_now() {
         assert_eq!(format_relative_time(0), "now");
@@ -0,0 +1,121 @@
+// src/webhook.rs
+//! Session webhooks. The daemon POSTs a JSON event to `session_start_webhook`
+//! when a session starts and to `session_end_webhook` when it ends, so an
+//! external system can follow the agent without polling `cryo status`.
+//! Delivery is best effort: a failed POST is logged and the session goes on.
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::time::Duration;
+
+/// Per-POST timeout. Short, since the start event is sent before the agent
+/// launches.
+pub const TIMEOUT_SECS: u64 = 10;
+
+/// Body of a webhook POST, tagged with `"event"`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum SessionEvent {
+    SessionStart {
+        project: String,
+        session: u32,
+        task: String,
+        provider: Option<String>,
+        timestamp: String,
+    },
+    SessionEnd {
+        project: String,
+        session: u32,
+        /// `success`, `failed`, `interrupted` or `aborted`, as in `cryo.log`.
+        outcome: String,
+        summary: Option<String>,
+        duration_secs: u64,
+        timestamp: String,
+    },
+}
+
+/// POST `event` as JSON to `url`. Fails on a connection error, on a non-2xx
+/// status, or after `timeout_secs` (0 = no timeout).
+pub fn post(agent: &ureq::Agent, url: &str, event: &SessionEvent, timeout_secs: u64) -> Result<()> {
+    let body = serde_json::to_string(event)?;
+    let timeout = (timeout_secs > 0).then(|| Duration::from_secs(timeout_secs));
+    agent
+        .post(url)
+        .config()
+        .timeout_global(timeout)
+        .build()
+        .header("Content-Type", "application/json")
+        .send(&body)
+        .with_context(|| format!("POST {url}"))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+
+    /// Accept one request, answer it with `status`, and return its body.
+    fn serve_once(status: &'static str) -> (String, std::thread::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let handle = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line.trim().is_empty() {
+                    break;
+                }
+                if let Some((name, value)) = line.split_once(':') {
+                    if name.eq_ignore_ascii_case("content-length") {
+                        length = value.trim().parse().unwrap();
+                    }
+                }
+            }
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body).unwrap();
+            let response = format!("HTTP/1.1 {status}\r\nContent-Length: 0\r\n\r\n");
+            reader.get_mut().write_all(response.as_bytes()).unwrap();
+            String::from_utf8(body).unwrap()
+        });
+        (url, handle)
+    }
+
+    fn end_event() -> SessionEvent {
+        SessionEvent::SessionEnd {
+            project: "demo".to_string(),
+            session: 3,
+            outcome: "success".to_string(),
+            summary: Some("Fixed the build".to_string()),
+            duration_secs: 42,
+            timestamp: "2026-03-01T09:00:00".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_post_session_event() {
+        let (url, server) = serve_once("200 OK");
+        let agent = ureq::Agent::new_with_defaults();
+        post(&agent, &url, &end_event(), 10).unwrap();
+
+        let body: serde_json::Value = serde_json::from_str(&server.join().unwrap()).unwrap();
+        assert_eq!(body["event"], "session_end");
+        assert_eq!(body["project"], "demo");
+        assert_eq!(body["session"], 3);
+        assert_eq!(body["outcome"], "success");
+        assert_eq!(body["summary"], "Fixed the build");
+        assert_eq!(body["duration_secs"], 42);
+    }
+
+    #[test]
+    fn test_post_fails_on_error_status() {
+        let (url, server) = serve_once("500 Internal Server Error");
+        let agent = ureq::Agent::new_with_defaults();
+        let err = post(&agent, &url, &end_event(), 10).unwrap_err();
+        assert!(format!("{err:#}").contains("POST http://"), "{err:#}");
+        server.join().unwrap();
+    }
+}
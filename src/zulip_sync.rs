@@ -1,4 +1,5 @@
 use anyhow::Result;
+use chrono::NaiveDateTime;
 use serde::{Deserialize, Serialize};
 use std::path::Path;
 
@@ -20,9 +21,15 @@ pub struct ZulipSyncState {
     /// ID of the last fetched message (anchor for polling)
     #[serde(default)]
     pub last_message_id: Option<u64>,
+    /// ID of the last fetched direct message to the bot
+    #[serde(default)]
+    pub last_dm_id: Option<u64>,
     /// Last session number that was pushed (to prevent duplicate posts)
     #[serde(default)]
     pub last_pushed_session: Option<u32>,
+    /// Local time of the last push (message counts in the next push start here)
+    #[serde(default)]
+    pub last_pushed_at: Option<NaiveDateTime>,
 }
 
 impl ZulipSyncState {
@@ -32,6 +39,21 @@ impl ZulipSyncState {
     }
 }
 
+impl crate::channel::PushTracker for ZulipSyncState {
+    fn last_pushed_session(&self) -> Option<u32> {
+        self.last_pushed_session
+    }
+
+    fn last_pushed_at(&self) -> Option<NaiveDateTime> {
+        self.last_pushed_at
+    }
+
+    fn record_push(&mut self, session: u32, at: NaiveDateTime) {
+        self.last_pushed_session = Some(session);
+        self.last_pushed_at = Some(at);
+    }
+}
+
 pub fn save_sync_state(path: &Path, state: &ZulipSyncState) -> Result<()> {
     let json = serde_json::to_string_pretty(state)?;
     std::fs::write(path, json)?;
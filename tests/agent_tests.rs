@@ -1,5 +1,7 @@
 // tests/agent_tests.rs
-use cryochamber::agent::{build_prompt, AgentConfig};
+use cryochamber::agent::{build_prompt, command_argv, sort_messages, AgentConfig, LaunchOptions};
+use cryochamber::config::MessageOrder;
+use cryochamber::message::Message;
 
 #[test]
 fn test_build_prompt_first_session() {
@@ -7,6 +9,14 @@ fn test_build_prompt_first_session() {
         session_number: 1,
         task: "Start the PR review plan".to_string(),
         delayed_wake: None,
+        plan_changed: false,
+        checkpoint: None,
+        focus: None,
+        attachments: Vec::new(),
+        inbox_dir: "messages/inbox".to_string(),
+        require_inbox_ack: false,
+        history: None,
+        messages: Vec::new(),
     };
     let prompt = build_prompt(&config);
     assert!(prompt.contains("Session number: 1"));
@@ -22,6 +32,14 @@ fn test_build_prompt_references_log() {
         session_number: 3,
         task: "Follow up on PRs".to_string(),
         delayed_wake: None,
+        plan_changed: false,
+        checkpoint: None,
+        focus: None,
+        attachments: Vec::new(),
+        inbox_dir: "messages/inbox".to_string(),
+        require_inbox_ack: false,
+        history: None,
+        messages: Vec::new(),
     };
     let prompt = build_prompt(&config);
     assert!(prompt.contains("Session number: 3"));
@@ -34,6 +52,14 @@ fn test_build_prompt_contains_cli_reminders() {
         session_number: 1,
         task: "Do the thing".to_string(),
         delayed_wake: None,
+        plan_changed: false,
+        checkpoint: None,
+        focus: None,
+        attachments: Vec::new(),
+        inbox_dir: "messages/inbox".to_string(),
+        require_inbox_ack: false,
+        history: None,
+        messages: Vec::new(),
     };
     let prompt = build_prompt(&config);
     assert!(prompt.contains("cryo-agent hibernate"));
@@ -47,6 +73,14 @@ fn test_build_prompt_references_inbox() {
         session_number: 2,
         task: "Continue".to_string(),
         delayed_wake: None,
+        plan_changed: false,
+        checkpoint: None,
+        focus: None,
+        attachments: Vec::new(),
+        inbox_dir: "messages/inbox".to_string(),
+        require_inbox_ack: false,
+        history: None,
+        messages: Vec::new(),
     };
     let prompt = build_prompt(&config);
     assert!(prompt.contains("messages/inbox/"));
@@ -58,30 +92,94 @@ fn test_build_prompt_delayed_wake() {
         session_number: 4,
         task: "Check status".to_string(),
         delayed_wake: Some("DELAYED WAKE: 2h late".to_string()),
+        plan_changed: false,
+        checkpoint: None,
+        focus: None,
+        attachments: Vec::new(),
+        inbox_dir: "messages/inbox".to_string(),
+        require_inbox_ack: false,
+        history: None,
+        messages: Vec::new(),
     };
     let prompt = build_prompt(&config);
     assert!(prompt.contains("DELAYED WAKE: 2h late"));
     assert!(prompt.contains("System Notice"));
 }
 
+#[test]
+fn test_build_prompt_plan_changed() {
+    let mut config = AgentConfig {
+        session_number: 5,
+        task: "Continue the plan".to_string(),
+        delayed_wake: None,
+        plan_changed: true,
+        checkpoint: None,
+        focus: None,
+        attachments: Vec::new(),
+        inbox_dir: "messages/inbox".to_string(),
+        require_inbox_ack: false,
+        history: None,
+        messages: Vec::new(),
+    };
+    assert!(build_prompt(&config).contains("## Plan Changed"));
+    config.plan_changed = false;
+    assert!(!build_prompt(&config).contains("Plan Changed"));
+}
+
 #[test]
 fn test_spawn_agent_fire_and_forget() {
-    let mut child =
-        cryochamber::agent::spawn_agent("echo", "hello", None, &std::collections::HashMap::new())
-            .unwrap();
+    let mut child = cryochamber::agent::spawn_agent(
+        std::path::Path::new("."),
+        "echo",
+        "hello",
+        &LaunchOptions::default(),
+        None,
+        &std::collections::HashMap::new(),
+    )
+    .unwrap();
     let exit = child.wait().unwrap();
     assert!(exit.success());
 }
 
 #[test]
 fn test_spawn_agent_empty_command() {
-    let result =
-        cryochamber::agent::spawn_agent("", "test prompt", None, &std::collections::HashMap::new());
+    let result = cryochamber::agent::spawn_agent(
+        std::path::Path::new("."),
+        "",
+        "test prompt",
+        &LaunchOptions::default(),
+        None,
+        &std::collections::HashMap::new(),
+    );
     assert!(result.is_err());
     let err = result.err().unwrap().to_string();
     assert!(err.contains("empty"), "Expected 'empty' in error: {err}");
 }
 
+#[test]
+fn test_spawn_errors_flag_broken_commands() {
+    use cryochamber::agent::AgentCommandError;
+    let spawn = |command: &str| {
+        cryochamber::agent::spawn_agent(
+            std::path::Path::new("."),
+            command,
+            "test prompt",
+            &LaunchOptions::default(),
+            None,
+            &std::collections::HashMap::new(),
+        )
+        .expect_err("spawn should fail")
+    };
+    let err = spawn("/nonexistent/agent-binary");
+    let typed = AgentCommandError::find(&err).expect("missing binary is a command error");
+    assert_eq!(typed.command, "/nonexistent/agent-binary");
+    let err = spawn("claude --model 'unterminated");
+    assert!(
+        AgentCommandError::find(&err).is_some_and(|e| e.reason.contains("parse")),
+        "{err}"
+    );
+}
+
 #[test]
 fn test_spawn_agent_with_env_vars() {
     use std::collections::HashMap;
@@ -93,8 +191,15 @@ fn test_spawn_agent_with_env_vars() {
     let mut env = HashMap::new();
     env.insert("TEST_CRYO_KEY".to_string(), "test_value_123".to_string());
 
-    let mut child =
-        cryochamber::agent::spawn_agent("printenv", "TEST_CRYO_KEY", Some(log_file), &env).unwrap();
+    let mut child = cryochamber::agent::spawn_agent(
+        std::path::Path::new("."),
+        "printenv",
+        "TEST_CRYO_KEY",
+        &LaunchOptions::default(),
+        Some(log_file),
+        &env,
+    )
+    .unwrap();
     let status = child.wait().unwrap();
     assert!(status.success());
 
@@ -105,21 +210,145 @@ fn test_spawn_agent_with_env_vars() {
     );
 }
 
+#[test]
+fn test_spawn_agent_redacted_scrubs_provider_key() {
+    use std::collections::HashMap;
+
+    let dir = tempfile::tempdir().unwrap();
+    let log_path = dir.path().join("agent.log");
+    let log_file = std::fs::File::create(&log_path).unwrap();
+
+    let env = HashMap::from([(
+        "TEST_CRYO_KEY".to_string(),
+        "sk-test-secret-123".to_string(),
+    )]);
+    let config = cryochamber::config::CryoConfig {
+        providers: vec![cryochamber::config::ProviderConfig {
+            name: "test".to_string(),
+            env: env.clone(),
+            env_file: None,
+            max_session_duration: None,
+        }],
+        ..Default::default()
+    };
+    let redactor = cryochamber::redact::Redactor::from_config(&config).unwrap();
+
+    let (mut child, mut copy) = cryochamber::agent::spawn_agent_redacted(
+        std::path::Path::new("."),
+        "printenv",
+        "TEST_CRYO_KEY",
+        &LaunchOptions::default(),
+        log_file,
+        &env,
+        &redactor,
+    )
+    .unwrap();
+    assert!(child.wait().unwrap().success());
+    assert!(copy.wait(std::time::Duration::from_secs(5)));
+
+    let output = std::fs::read_to_string(&log_path).unwrap();
+    assert_eq!(output.trim(), "***");
+}
+
 #[test]
 fn test_spawn_agent_with_empty_env_vars() {
     use std::collections::HashMap;
     let env = HashMap::new();
 
-    let child = cryochamber::agent::spawn_agent("echo", "hello", None, &env);
+    let child = cryochamber::agent::spawn_agent(
+        std::path::Path::new("."),
+        "echo",
+        "hello",
+        &LaunchOptions::default(),
+        None,
+        &env,
+    );
     assert!(child.is_ok());
     let mut child = child.unwrap();
     let _ = child.wait();
 }
 
+#[test]
+fn test_agent_args_follow_injected_subcommand() {
+    let launch = LaunchOptions {
+        extra_args: vec!["--profile".to_string(), "work".to_string()],
+        ..Default::default()
+    };
+    assert_eq!(
+        command_argv("opencode", "do it", &launch).unwrap(),
+        ["opencode", "run", "--profile", "work", "do it"]
+    );
+    assert_eq!(
+        command_argv("claude --verbose", "do it", &launch).unwrap(),
+        ["claude", "--verbose", "--profile", "work", "-p", "do it"]
+    );
+    // Unset: the usual command line
+    assert_eq!(
+        command_argv("codex", "do it", &LaunchOptions::default()).unwrap(),
+        [
+            "codex",
+            "exec",
+            "--full-auto",
+            "--skip-git-repo-check",
+            "do it"
+        ]
+    );
+}
+
+#[test]
+fn test_spawn_agent_prompt_on_stdin() {
+    let dir = tempfile::tempdir().unwrap();
+    let log_path = dir.path().join("agent.log");
+    let launch = LaunchOptions {
+        prompt_position: cryochamber::config::PromptPosition::Stdin,
+        ..Default::default()
+    };
+    assert_eq!(command_argv("cat", "the prompt", &launch).unwrap(), ["cat"]);
+
+    let mut child = cryochamber::agent::spawn_agent(
+        std::path::Path::new("."),
+        "cat",
+        "the prompt",
+        &launch,
+        Some(std::fs::File::create(&log_path).unwrap()),
+        &std::collections::HashMap::new(),
+    )
+    .unwrap();
+    assert!(
+        child.wait().unwrap().success(),
+        "cat should see stdin close"
+    );
+    assert_eq!(std::fs::read_to_string(&log_path).unwrap(), "the prompt");
+}
+
+#[test]
+fn test_spawn_agent_in_own_process_group() {
+    for process_group in [true, false] {
+        let launch = LaunchOptions {
+            process_group,
+            ..Default::default()
+        };
+        let mut child = cryochamber::agent::spawn_agent(
+            std::path::Path::new("."),
+            "sleep",
+            "5",
+            &launch,
+            None,
+            &std::collections::HashMap::new(),
+        )
+        .unwrap();
+        let pgid = unsafe { libc::getpgid(child.id() as i32) };
+        let _ = child.kill();
+        let _ = child.wait();
+        assert_eq!(pgid == child.id() as i32, process_group, "pgid {pgid}");
+    }
+}
+
 #[test]
 fn test_resolve_mock_agent() {
     // "mock" should resolve to "sh" running "scenario.sh"
-    let cmd = cryochamber::agent::build_command("mock", "test prompt").unwrap();
+    let cmd = cryochamber::agent::build_command("mock", "test prompt", &LaunchOptions::default())
+        .unwrap();
     let program = format!("{:?}", cmd);
     assert!(
         program.contains("sh"),
@@ -132,3 +361,203 @@ fn test_mock_agent_program() {
     let program = cryochamber::agent::agent_program("mock").unwrap();
     assert_eq!(program, "sh");
 }
+
+#[test]
+fn test_build_prompt_lists_attachments() {
+    let config = AgentConfig {
+        session_number: 2,
+        task: "Continue".to_string(),
+        delayed_wake: None,
+        plan_changed: false,
+        checkpoint: None,
+        focus: None,
+        attachments: vec!["2026-03-01T10-00-00_build.log".to_string()],
+        inbox_dir: "messages/inbox".to_string(),
+        require_inbox_ack: false,
+        history: None,
+        messages: Vec::new(),
+    };
+    let prompt = build_prompt(&config);
+    assert!(prompt.contains("- Attached file: messages/attachments/2026-03-01T10-00-00_build.log"));
+}
+
+#[test]
+fn test_build_prompt_uses_configured_inbox_dir() {
+    let config = AgentConfig {
+        session_number: 1,
+        task: "Do the thing".to_string(),
+        delayed_wake: None,
+        plan_changed: false,
+        checkpoint: None,
+        focus: None,
+        attachments: Vec::new(),
+        inbox_dir: ".agent/in/".to_string(),
+        require_inbox_ack: false,
+        history: None,
+        messages: Vec::new(),
+    };
+    let prompt = build_prompt(&config);
+    assert!(prompt.contains("Check .agent/in/ (or run"));
+    assert!(!prompt.contains("messages/inbox"));
+}
+
+#[test]
+fn test_build_prompt_history_section() {
+    let mut config = AgentConfig {
+        session_number: 3,
+        task: "Continue".to_string(),
+        delayed_wake: None,
+        plan_changed: false,
+        checkpoint: None,
+        focus: None,
+        attachments: Vec::new(),
+        inbox_dir: "messages/inbox".to_string(),
+        require_inbox_ack: false,
+        history: None,
+        messages: Vec::new(),
+    };
+    let prompt = build_prompt(&config);
+    assert!(prompt.contains("- Read cryo.log for previous session history"));
+    assert!(!prompt.contains("## Previous Sessions"));
+
+    config.history =
+        Some("--- CRYO SESSION 2 | 2026-03-01T10:00:00Z ---\n--- CRYO END ---\n".into());
+    let prompt = build_prompt(&config);
+    assert!(prompt.contains("## Previous Sessions"));
+    assert!(prompt.contains("--- CRYO SESSION 2 |"));
+}
+
+#[test]
+fn test_build_prompt_embeds_messages() {
+    let mut config = AgentConfig {
+        session_number: 2,
+        task: "Continue".to_string(),
+        delayed_wake: None,
+        plan_changed: false,
+        checkpoint: None,
+        focus: None,
+        attachments: Vec::new(),
+        inbox_dir: "messages/inbox".to_string(),
+        require_inbox_ack: false,
+        history: None,
+        messages: Vec::new(),
+    };
+    let prompt = build_prompt(&config);
+    assert!(prompt.contains("(or run `cryo-agent receive`)"));
+    assert!(!prompt.contains("## Messages"));
+
+    config.require_inbox_ack = true;
+    let prompt = build_prompt(&config);
+    assert!(prompt.contains("read them with `cryo-agent receive`"));
+    config.require_inbox_ack = false;
+
+    config.messages = vec![(
+        "2026-03-01T10-00-00_alice.md".to_string(),
+        Message {
+            from: "alice".to_string(),
+            subject: "Priorities".to_string(),
+            body: "Please look at the flaky test first.".to_string(),
+            timestamp: chrono::NaiveDateTime::default(),
+            metadata: Default::default(),
+            attachments: Vec::new(),
+        },
+    )];
+    let prompt = build_prompt(&config);
+    assert!(prompt.contains("(or see Messages below)"));
+    assert!(prompt.contains("## Messages"));
+    assert!(prompt.contains("### 2026-03-01T10-00-00_alice.md"));
+    assert!(prompt.contains("From: alice\nSubject: Priorities\n"));
+    assert!(prompt.contains("Please look at the flaky test first."));
+}
+
+#[test]
+fn test_build_prompt_caps_embedded_messages() {
+    let message = |name: &str, body: String| {
+        (
+            name.to_string(),
+            Message {
+                from: "bot".to_string(),
+                subject: String::new(),
+                body,
+                timestamp: chrono::NaiveDateTime::default(),
+                metadata: Default::default(),
+                attachments: Vec::new(),
+            },
+        )
+    };
+    let config = AgentConfig {
+        session_number: 2,
+        task: "Continue".to_string(),
+        delayed_wake: None,
+        plan_changed: false,
+        checkpoint: None,
+        focus: None,
+        attachments: Vec::new(),
+        inbox_dir: "messages/inbox".to_string(),
+        require_inbox_ack: false,
+        history: None,
+        messages: vec![
+            message("a.md", "short note".to_string()),
+            message("b.md", "x".repeat(200 * 1024)),
+            message("c.md", "another note".to_string()),
+        ],
+    };
+    let prompt = build_prompt(&config);
+    assert!(prompt.len() < 128 * 1024, "{}", prompt.len());
+    assert!(prompt.contains("short note"));
+    assert!(!prompt.contains("another note"));
+    assert!(prompt.contains("2 more message(s) too long to include here"));
+    assert!(prompt.contains("read them in messages/inbox/"));
+    assert!(prompt.contains("- b.md\n- c.md\n"));
+}
+
+#[test]
+fn test_build_prompt_checkpoint() {
+    let mut config = AgentConfig {
+        session_number: 4,
+        task: "Continue the bisect".to_string(),
+        delayed_wake: None,
+        plan_changed: false,
+        checkpoint: Some("{\"low\":100,\"high\":200}\n".to_string()),
+        focus: None,
+        attachments: Vec::new(),
+        inbox_dir: "messages/inbox".to_string(),
+        require_inbox_ack: false,
+        history: None,
+        messages: Vec::new(),
+    };
+    let prompt = build_prompt(&config);
+    assert!(prompt.contains("## Checkpoint"));
+    assert!(prompt.contains("```json\n{\"low\":100,\"high\":200}\n```"));
+    config.checkpoint = None;
+    assert!(!build_prompt(&config).contains("## Checkpoint"));
+}
+
+#[test]
+fn test_sort_messages_by_timestamp() {
+    let at = |name: &str, time: &str| {
+        let timestamp = chrono::NaiveDateTime::parse_from_str(time, "%Y-%m-%dT%H:%M:%S").unwrap();
+        (
+            name.to_string(),
+            Message {
+                from: "human".to_string(),
+                subject: name.to_string(),
+                body: String::new(),
+                timestamp,
+                metadata: Default::default(),
+                attachments: Vec::new(),
+            },
+        )
+    };
+    let mut messages = vec![
+        at("b.md", "2026-03-01T10:00:00"),
+        at("z-early.md", "2026-03-01T09:00:00"),
+        at("a.md", "2026-03-01T10:00:00"),
+    ];
+    let names = |m: &[(String, Message)]| m.iter().map(|(n, _)| n.clone()).collect::<Vec<_>>();
+
+    sort_messages(&mut messages, MessageOrder::OldestFirst);
+    assert_eq!(names(&messages), ["z-early.md", "a.md", "b.md"]);
+    sort_messages(&mut messages, MessageOrder::NewestFirst);
+    assert_eq!(names(&messages), ["b.md", "a.md", "z-early.md"]);
+}
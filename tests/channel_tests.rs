@@ -11,6 +11,7 @@ fn make_message(from: &str, subject: &str, body: &str, ts: &str) -> Message {
         body: body.to_string(),
         timestamp: NaiveDateTime::parse_from_str(ts, "%Y-%m-%dT%H:%M:%S").unwrap(),
         metadata: BTreeMap::new(),
+        attachments: Vec::new(),
     }
 }
 
@@ -60,3 +61,211 @@ fn test_file_channel_post_reply() {
     let content = std::fs::read_to_string(entries[0].path()).unwrap();
     assert!(content.contains("Session 3 complete."));
 }
+
+#[test]
+fn test_file_channel_post_reply_with_attachments() {
+    let dir = tempfile::tempdir().unwrap();
+    let channel = FileChannel::new(dir.path().to_path_buf());
+    let stored = message::save_attachment(dir.path(), "report.txt", b"all green").unwrap();
+    channel
+        .post_reply_with_attachments("See report.", std::slice::from_ref(&stored))
+        .unwrap();
+
+    let outbox = message::read_outbox(dir.path()).unwrap();
+    assert_eq!(outbox.len(), 1);
+    assert_eq!(outbox[0].1.attachments, vec![stored.clone()]);
+    assert_eq!(outbox[0].1.body, "See report.");
+    assert!(message::attachments_dir(dir.path()).join(&stored).exists());
+}
+
+#[test]
+fn test_with_attachment_list() {
+    use cryochamber::channel::with_attachment_list;
+    assert_eq!(with_attachment_list("hi", &[]), "hi");
+    let body = with_attachment_list("hi", &["a.log".to_string(), "b.png".to_string()]);
+    assert!(body.starts_with("hi\n\nAttachments"), "{body}");
+    assert!(body.contains("- `a.log`\n- `b.png`"), "{body}");
+}
+
+/// Captures posted bodies instead of sending them anywhere.
+#[derive(Default)]
+struct RecordingChannel {
+    posted: std::cell::RefCell<Vec<String>>,
+}
+
+impl MessageChannel for RecordingChannel {
+    fn read_inbox(&self) -> anyhow::Result<Vec<Message>> {
+        Ok(Vec::new())
+    }
+
+    fn post_reply(&self, body: &str) -> anyhow::Result<()> {
+        self.posted.borrow_mut().push(body.to_string());
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+struct Tracker {
+    session: Option<u32>,
+    at: Option<NaiveDateTime>,
+}
+
+impl cryochamber::channel::PushTracker for Tracker {
+    fn last_pushed_session(&self) -> Option<u32> {
+        self.session
+    }
+
+    fn last_pushed_at(&self) -> Option<NaiveDateTime> {
+        self.at
+    }
+
+    fn record_push(&mut self, session: u32, at: NaiveDateTime) {
+        self.session = Some(session);
+        self.at = Some(at);
+    }
+}
+
+fn write_session(dir: &std::path::Path, session: u32) {
+    std::fs::write(
+        dir.join("cryo.log"),
+        format!(
+            "--- CRYO SESSION {session} | 2026-03-01T09:00:00Z ---\n\
+             task: Review PRs\n\
+             [09:00:01] agent started (pid 42)\n\
+             --- CRYO END ---\n"
+        ),
+    )
+    .unwrap();
+    let state = cryochamber::state::CryoState {
+        session_number: session,
+        ..Default::default()
+    };
+    cryochamber::state::save_state(&cryochamber::state::state_path(dir), &state).unwrap();
+}
+
+#[test]
+fn test_push_session_posts_once_per_session() {
+    let dir = tempfile::tempdir().unwrap();
+    let channel = RecordingChannel::default();
+    let mut tracker = Tracker::default();
+
+    // No log yet: nothing to push
+    let pushed = cryochamber::channel::push_session(&channel, dir.path(), "test", &mut tracker);
+    assert_eq!(pushed.unwrap(), None);
+
+    write_session(dir.path(), 3);
+    let pushed = cryochamber::channel::push_session(&channel, dir.path(), "test", &mut tracker);
+    assert_eq!(pushed.unwrap(), Some(3));
+    assert_eq!(tracker.session, Some(3));
+    assert!(tracker.at.is_some());
+    {
+        let posted = channel.posted.borrow();
+        assert_eq!(posted.len(), 1);
+        assert!(posted[0].starts_with("## Session 3\n"), "{}", posted[0]);
+        assert!(
+            posted[0].contains("\n<details><summary>Session log</summary>\n\n```\n"),
+            "{}",
+            posted[0]
+        );
+        assert!(posted[0].contains("task: Review PRs"), "{}", posted[0]);
+        assert!(posted[0].ends_with("```\n\n</details>"), "{}", posted[0]);
+    }
+
+    // Same session again is skipped
+    let pushed = cryochamber::channel::push_session(&channel, dir.path(), "test", &mut tracker);
+    assert_eq!(pushed.unwrap(), None);
+    assert_eq!(channel.posted.borrow().len(), 1);
+}
+
+#[test]
+fn test_push_session_numbers_the_logged_session() {
+    let dir = tempfile::tempdir().unwrap();
+    let channel = RecordingChannel::default();
+    let mut tracker = Tracker::default();
+
+    // Session 4 has started, but the last finished one is 3
+    write_session(dir.path(), 3);
+    let state = cryochamber::state::CryoState {
+        session_number: 4,
+        ..Default::default()
+    };
+    cryochamber::state::save_state(&cryochamber::state::state_path(dir.path()), &state).unwrap();
+
+    let pushed = cryochamber::channel::push_session(&channel, dir.path(), "test", &mut tracker);
+    assert_eq!(pushed.unwrap(), Some(3));
+    let posted = channel.posted.borrow();
+    assert!(posted[0].starts_with("## Session 3\n"), "{}", posted[0]);
+}
+
+#[test]
+fn test_record_post_failure_dead_letters_after_max_attempts() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = cryochamber::store::open(dir.path()).unwrap();
+    store.ensure().unwrap();
+    let id = store
+        .write(
+            "outbox",
+            &make_message("agent", "Report", "huge", "2026-03-01T09:00:00"),
+        )
+        .unwrap();
+
+    let fail = || anyhow::anyhow!("HTTP 422: body too long");
+    let e = cryochamber::channel::record_post_failure(&*store, &id, fail(), 2);
+    assert_eq!(e.to_string(), "HTTP 422: body too long (attempt 1 of 2)");
+    assert_eq!(store.read_outbox().unwrap().len(), 1);
+
+    let e = cryochamber::channel::record_post_failure(&*store, &id, fail(), 2);
+    let e = e.to_string();
+    assert!(
+        e.ends_with("(moved to dead-letter after 2 attempts)"),
+        "{e}"
+    );
+    assert!(store.read_outbox().unwrap().is_empty());
+    let dead = dir.path().join("messages/outbox/dead-letter");
+    assert!(dead.join(&id).exists());
+    assert!(dead.join(message::failure_filename(&id)).exists());
+}
+
+#[test]
+fn test_proxy_from_config_and_env() {
+    use cryochamber::channel::proxy_from;
+    use cryochamber::config::CryoConfig;
+
+    let env = |var: &str| match var {
+        "HTTPS_PROXY" => Some("http://env-proxy:8080".to_string()),
+        "NO_PROXY" => Some("localhost, .internal".to_string()),
+        _ => None,
+    };
+    let no_env = |_: &str| None;
+
+    // Nothing configured: no proxy
+    assert!(proxy_from(&CryoConfig::default(), no_env)
+        .unwrap()
+        .is_none());
+
+    // Env vars are honored, including NO_PROXY
+    let proxy = proxy_from(&CryoConfig::default(), env).unwrap().unwrap();
+    assert_eq!(proxy.host(), "env-proxy");
+    assert_eq!(proxy.port(), 8080);
+    let uri = |s: &str| s.parse::<ureq::http::Uri>().unwrap();
+    assert!(proxy.is_no_proxy(&uri("https://zulip.internal/api")));
+    assert!(!proxy.is_no_proxy(&uri("https://chat.zulip.org/api")));
+
+    // The config wins over the env and adds to the bypass list
+    let config = CryoConfig {
+        proxy: Some("http://user:pw@corp-proxy:3128".to_string()),
+        no_proxy: vec!["github.com".to_string()],
+        ..Default::default()
+    };
+    let proxy = proxy_from(&config, env).unwrap().unwrap();
+    assert_eq!(proxy.host(), "corp-proxy");
+    assert_eq!(proxy.username(), Some("user"));
+    assert!(proxy.is_no_proxy(&uri("https://github.com/x.png")));
+    assert!(proxy.is_no_proxy(&uri("http://localhost:9000")));
+
+    let bad = CryoConfig {
+        proxy: Some("::not a url".to_string()),
+        ..Default::default()
+    };
+    assert!(proxy_from(&bad, no_env).is_err());
+}
@@ -74,7 +74,8 @@ fn test_send_no_daemon() {
         .args(["send", "Hello from test"])
         .current_dir(dir.path())
         .assert()
-        .success();
+        .success()
+        .stdout(predicates::str::contains("Message sent to messages/inbox/"));
 
     let inbox = dir.path().join("messages/inbox");
     assert!(inbox.exists(), "Inbox directory should exist after init");
@@ -132,7 +133,8 @@ fn test_start_while_running() {
         .env("CRYO_NO_SERVICE", "1")
         .current_dir(dir.path())
         .assert()
-        .failure();
+        .code(3)
+        .stderr(predicates::str::contains("already running"));
 
     // Clean up
     let _ = cryo_bin().args(["cancel"]).current_dir(dir.path()).output();
@@ -187,7 +189,7 @@ fn test_start_missing_plan() {
         .env("CRYO_NO_SERVICE", "1")
         .current_dir(dir.path())
         .assert()
-        .failure();
+        .code(2);
 }
 
 #[test]
@@ -201,7 +203,7 @@ fn test_start_corrupted_config() {
         .env("CRYO_NO_SERVICE", "1")
         .current_dir(dir.path())
         .assert()
-        .failure();
+        .code(2);
 }
 
 #[test]
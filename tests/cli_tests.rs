@@ -7,7 +7,9 @@ use std::fs;
 
 fn cmd() -> Command {
     #[allow(deprecated)]
-    Command::cargo_bin("cryo").unwrap()
+    let mut cmd = Command::cargo_bin("cryo").unwrap();
+    cmd.env_remove("CRYO_AGENT");
+    cmd
 }
 
 fn agent_cmd() -> Command {
@@ -45,6 +47,138 @@ fn test_init_creates_protocol_and_plan() {
     assert!(config_content.contains("agent = \"opencode\""));
 }
 
+#[test]
+fn test_init_protocol_template() {
+    let dir = tempfile::tempdir().unwrap();
+    let config_home = tempfile::tempdir().unwrap();
+    fs::create_dir_all(config_home.path().join("cryo")).unwrap();
+    fs::write(
+        config_home.path().join("cryo/protocol.md"),
+        "Run `make lint` before every commit.",
+    )
+    .unwrap();
+    let explicit = dir.path().join("rules.md");
+    fs::write(&explicit, "<!-- cryo: replace -->\nDo whatever.").unwrap();
+
+    // The user-level template is picked up by default
+    cmd()
+        .arg("init")
+        .env("XDG_CONFIG_HOME", config_home.path())
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("AGENTS.md (created, template"));
+    let content = fs::read_to_string(dir.path().join("AGENTS.md")).unwrap();
+    assert!(content.contains("cryo-agent hibernate"));
+    assert!(content.contains("Run `make lint` before every commit."));
+
+    // --protocol-template wins, and a replacement missing the mechanics warns
+    cmd()
+        .args([
+            "init",
+            "--agent",
+            "claude",
+            "--protocol-template",
+            "rules.md",
+        ])
+        .env("XDG_CONFIG_HOME", config_home.path())
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "never mentions `cryo-agent hibernate`",
+        ));
+    let content = fs::read_to_string(dir.path().join("CLAUDE.md")).unwrap();
+    assert_eq!(content, "Do whatever.");
+
+    // A missing explicit template is an error
+    cmd()
+        .args(["init", "--protocol-template", "missing.md"])
+        .current_dir(dir.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Failed to read protocol template"));
+}
+
+/// Serve `body` with `content_type` to a single HTTP request on localhost.
+fn serve_once(content_type: &'static str, body: &'static str) -> String {
+    use std::io::{Read, Write};
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut buf = [0u8; 4096];
+        let _ = stream.read(&mut buf);
+        let _ = write!(
+            stream,
+            "HTTP/1.1 200 OK\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        );
+    });
+    format!("http://{addr}/plan.md")
+}
+
+#[test]
+fn test_init_plan_sources() {
+    let dir = tempfile::tempdir().unwrap();
+    let url = serve_once("text/plain; charset=utf-8", "# Shared plan\n\n- Step one\n");
+    cmd()
+        .args(["init", "--plan-url", &url])
+        .env_remove("HTTP_PROXY")
+        .env_remove("http_proxy")
+        .env_remove("ALL_PROXY")
+        .env_remove("all_proxy")
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("plan.md (created from http://"));
+    assert_eq!(
+        fs::read_to_string(dir.path().join("plan.md")).unwrap(),
+        "# Shared plan\n\n- Step one\n"
+    );
+
+    // A local file works the same, and an unstructured one warns
+    let other = tempfile::tempdir().unwrap();
+    fs::write(other.path().join("notes.txt"), "Just ship it.").unwrap();
+    cmd()
+        .args(["init", "--plan", "notes.txt"])
+        .current_dir(other.path())
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("may not be Markdown"));
+    assert_eq!(
+        fs::read_to_string(other.path().join("plan.md")).unwrap(),
+        "Just ship it."
+    );
+
+    // An empty response is rejected before anything is written
+    let empty = tempfile::tempdir().unwrap();
+    let url = serve_once("text/plain", "  \n");
+    cmd()
+        .args(["init", "--plan-url", &url])
+        .env_remove("HTTP_PROXY")
+        .env_remove("http_proxy")
+        .env_remove("ALL_PROXY")
+        .env_remove("all_proxy")
+        .current_dir(empty.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("returned an empty plan"));
+    assert!(!empty.path().join("cryo.toml").exists());
+
+    // An existing plan.md is kept without fetching the URL at all
+    let closed = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/plan.md", closed.local_addr().unwrap());
+    drop(closed);
+    cmd()
+        .args(["init", "--plan-url", &url])
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("plan.md (exists, kept;"))
+        .stdout(predicate::str::contains("not used)"));
+}
+
 #[test]
 fn test_init_claude_agent() {
     let dir = tempfile::tempdir().unwrap();
@@ -62,6 +196,81 @@ fn test_init_claude_agent() {
     assert!(config_content.contains("agent = \"claude\""));
 }
 
+#[test]
+fn test_init_uses_cryo_agent_env() {
+    let dir = tempfile::tempdir().unwrap();
+    cmd()
+        .arg("init")
+        .env("CRYO_AGENT", "claude")
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("CLAUDE.md"));
+    let config_content = fs::read_to_string(dir.path().join("cryo.toml")).unwrap();
+    assert!(config_content.contains("agent = \"claude\""));
+
+    // --agent beats the env var
+    let dir = tempfile::tempdir().unwrap();
+    cmd()
+        .args(["init", "--agent", "codex"])
+        .env("CRYO_AGENT", "claude")
+        .current_dir(dir.path())
+        .assert()
+        .success();
+    let config_content = fs::read_to_string(dir.path().join("cryo.toml")).unwrap();
+    assert!(config_content.contains("agent = \"codex\""));
+}
+
+#[test]
+fn test_start_falls_back_to_cryo_agent_env() {
+    let dir = tempfile::tempdir().unwrap();
+    init_dir(dir.path());
+    // cryo.toml without an agent line: CRYO_AGENT decides
+    fs::write(dir.path().join("cryo.toml"), "max_retries = 1\n").unwrap();
+    cmd()
+        .arg("start")
+        .env("CRYO_AGENT", "cryo-no-such-agent-xyz")
+        .current_dir(dir.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cryo-no-such-agent-xyz"));
+
+    // cryo.toml wins over CRYO_AGENT
+    fs::write(
+        dir.path().join("cryo.toml"),
+        "agent = \"cryo-toml-agent-xyz\"\n",
+    )
+    .unwrap();
+    cmd()
+        .arg("start")
+        .env("CRYO_AGENT", "cryo-no-such-agent-xyz")
+        .current_dir(dir.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cryo-toml-agent-xyz"));
+}
+
+#[test]
+fn test_start_warns_about_unknown_config_keys() {
+    let dir = tempfile::tempdir().unwrap();
+    init_dir(dir.path());
+    let toml = fs::read_to_string(dir.path().join("cryo.toml")).unwrap();
+    fs::write(
+        dir.path().join("cryo.toml"),
+        format!("{toml}\nwatch_inbx = false\n"),
+    )
+    .unwrap();
+    cmd()
+        .args(["start", "--agent", "cryo-no-such-agent-xyz"])
+        .current_dir(dir.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Warning: cryo.toml: line"))
+        .stderr(predicate::str::contains(
+            "unknown key `watch_inbx` is ignored (did you mean `watch_inbox`?)",
+        ));
+}
+
 #[test]
 fn test_init_idempotent() {
     let dir = tempfile::tempdir().unwrap();
@@ -91,7 +300,7 @@ fn test_status_no_instance() {
         .arg("status")
         .current_dir(dir.path())
         .assert()
-        .failure()
+        .code(2)
         .stderr(predicate::str::contains("No cryochamber project"));
 }
 
@@ -117,7 +326,54 @@ fn test_status_with_state() {
         .success()
         .stdout(predicate::str::contains("Daemon: stopped"))
         .stdout(predicate::str::contains("Session: 3"))
-        .stdout(predicate::str::contains("Agent: opencode"));
+        .stdout(predicate::str::contains("Agent: opencode"))
+        .stdout(predicate::str::contains(
+            "Inbox: new messages wake the agent (watch_inbox)",
+        ));
+}
+
+#[test]
+fn test_status_shows_project_name() {
+    let dir = tempfile::tempdir().unwrap();
+    init_dir(dir.path());
+    let config = fs::read_to_string(dir.path().join("cryo.toml")).unwrap();
+    fs::write(
+        dir.path().join("cryo.toml"),
+        format!("project_name = \"Nightly Bench\"\n{config}"),
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join("timer.json"),
+        r#"{"session_number": 1, "pid": null, "retry_count": 0}"#,
+    )
+    .unwrap();
+
+    cmd()
+        .arg("status")
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Project: Nightly Bench"));
+}
+
+#[test]
+fn test_status_shows_last_result() {
+    let dir = tempfile::tempdir().unwrap();
+    init_dir(dir.path());
+    fs::write(
+        dir.path().join("timer.json"),
+        r#"{"session_number": 3, "pid": null, "last_outcome": "success", "last_summary": "Benchmarks posted"}"#,
+    )
+    .unwrap();
+
+    cmd()
+        .arg("status")
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Last result: success — Benchmarks posted",
+        ));
 }
 
 #[test]
@@ -176,6 +432,105 @@ fn test_log_with_content() {
         .stdout(predicate::str::contains("CRYO SESSION"));
 }
 
+#[test]
+fn test_log_redacts_provider_keys() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(
+        dir.path().join("cryo.toml"),
+        "[[providers]]\nname = \"p\"\nenv = { API_KEY = \"sk-secret-value-42\" }\n",
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join("cryo.log"),
+        "--- CRYO SESSION 1 | 2026-02-23T10:00:00Z ---\nkey is sk-secret-value-42\n--- CRYO END ---\n",
+    )
+    .unwrap();
+
+    cmd()
+        .arg("log")
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("key is ***"))
+        .stdout(predicate::str::contains("sk-secret-value-42").not());
+
+    cmd()
+        .args(["log", "--no-redact"])
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("key is sk-secret-value-42"));
+}
+
+// --- Plan ---
+
+#[test]
+fn test_plan_set_and_show() {
+    let dir = tempfile::tempdir().unwrap();
+    init_dir(dir.path());
+    let external = tempfile::tempdir().unwrap();
+    let src = external.path().join("my-plan.md");
+    fs::write(&src, "# External plan\nDo the thing.\n").unwrap();
+
+    // Backdate the template plan.md so the external plan counts as newer.
+    fs::File::options()
+        .write(true)
+        .open(dir.path().join("plan.md"))
+        .unwrap()
+        .set_modified(std::time::SystemTime::now() - std::time::Duration::from_secs(60))
+        .unwrap();
+
+    cmd()
+        .args(["plan", "set", src.to_str().unwrap()])
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    cmd()
+        .args(["plan", "show"])
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Do the thing."));
+}
+
+#[test]
+fn test_plan_set_refuses_newer_local_edit() {
+    let dir = tempfile::tempdir().unwrap();
+    init_dir(dir.path());
+    let external = tempfile::tempdir().unwrap();
+    let src = external.path().join("old-plan.md");
+    fs::write(&src, "# Old plan\n").unwrap();
+    fs::File::options()
+        .write(true)
+        .open(&src)
+        .unwrap()
+        .set_modified(std::time::SystemTime::now() - std::time::Duration::from_secs(60))
+        .unwrap();
+    fs::write(dir.path().join("plan.md"), "# Local edit\n").unwrap();
+
+    cmd()
+        .args(["plan", "set", src.to_str().unwrap()])
+        .current_dir(dir.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--force"));
+    assert_eq!(
+        fs::read_to_string(dir.path().join("plan.md")).unwrap(),
+        "# Local edit\n"
+    );
+
+    cmd()
+        .args(["plan", "set", "--force", src.to_str().unwrap()])
+        .current_dir(dir.path())
+        .assert()
+        .success();
+    assert_eq!(
+        fs::read_to_string(dir.path().join("plan.md")).unwrap(),
+        "# Old plan\n"
+    );
+}
+
 // --- Cancel ---
 
 #[test]
@@ -242,10 +597,31 @@ fn test_start_no_plan_md() {
         .arg("start")
         .current_dir(dir.path())
         .assert()
-        .failure()
+        .code(2)
         .stderr(predicate::str::contains("No plan.md found"));
 }
 
+#[test]
+fn test_start_missing_provider_env_file() {
+    let dir = tempfile::tempdir().unwrap();
+    init_dir(dir.path());
+    let toml = fs::read_to_string(dir.path().join("cryo.toml")).unwrap();
+    fs::write(
+        dir.path().join("cryo.toml"),
+        format!("{toml}\n[[providers]]\nname = \"openai\"\nenv_file = \".secrets/openai.env\"\n"),
+    )
+    .unwrap();
+    cmd()
+        .args(["start", "--agent", "mock"])
+        .current_dir(dir.path())
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains(
+            "Provider 'openai': cannot load env_file .secrets/openai.env",
+        ));
+    assert!(!dir.path().join("timer.json").exists());
+}
+
 // --- Help ---
 
 #[test]
@@ -339,6 +715,65 @@ fn test_send_creates_inbox_message() {
     assert!(content.contains("e2e4"));
 }
 
+#[test]
+fn test_send_with_attachment() {
+    let dir = tempfile::tempdir().unwrap();
+    init_dir(dir.path());
+    std::fs::write(dir.path().join("notes.txt"), "details").unwrap();
+    cmd()
+        .args(["send", "--attach", "notes.txt", "see attached"])
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    let inbox = cryochamber::message::read_inbox(dir.path()).unwrap();
+    assert_eq!(inbox.len(), 1);
+    let attachments = &inbox[0].1.attachments;
+    assert_eq!(attachments.len(), 1);
+    assert!(attachments[0].ends_with("_notes.txt"), "{attachments:?}");
+    let stored = cryochamber::message::attachments_dir(dir.path()).join(&attachments[0]);
+    assert_eq!(std::fs::read_to_string(stored).unwrap(), "details");
+}
+
+#[test]
+fn test_messages_migrate_then_sqlite_send_and_receive() {
+    let dir = tempfile::tempdir().unwrap();
+    init_dir(dir.path());
+    cmd()
+        .args(["send", "before migration"])
+        .current_dir(dir.path())
+        .assert()
+        .success();
+    cmd()
+        .args(["messages", "migrate"])
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Imported 1 message(s)"));
+    assert!(cryochamber::message::read_inbox(dir.path())
+        .unwrap()
+        .is_empty());
+
+    let toml = dir.path().join("cryo.toml");
+    let mut config = std::fs::read_to_string(&toml).unwrap();
+    config.push_str("\nmessage_store = \"sqlite\"\n");
+    std::fs::write(&toml, config).unwrap();
+
+    cmd()
+        .args(["send", "after migration"])
+        .current_dir(dir.path())
+        .assert()
+        .success();
+    let store = cryochamber::store::SqliteStore::open(dir.path()).unwrap();
+    let mut bodies: Vec<String> = cryochamber::store::MessageStore::read_inbox(&store)
+        .unwrap()
+        .into_iter()
+        .map(|(_, m)| m.body)
+        .collect();
+    bodies.sort();
+    assert_eq!(bodies, vec!["after migration", "before migration"]);
+}
+
 #[test]
 fn test_send_with_subject_and_from() {
     let dir = tempfile::tempdir().unwrap();
@@ -369,6 +804,34 @@ fn test_send_with_subject_and_from() {
     assert!(content.contains("subject: chess move"));
 }
 
+#[test]
+fn test_send_auto_wake_on_send() {
+    let dir = tempfile::tempdir().unwrap();
+    init_dir(dir.path());
+    let config_path = dir.path().join("cryo.toml");
+    let config = fs::read_to_string(&config_path).unwrap();
+    fs::write(
+        &config_path,
+        format!("{config}\nauto_wake_on_send = true\n"),
+    )
+    .unwrap();
+
+    // No daemon: the automatic wake reports that the message is queued
+    cmd()
+        .args(["send", "hello"])
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("no daemon is running"));
+
+    cmd()
+        .args(["send", "--no-wake", "quiet"])
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("no daemon").not());
+}
+
 #[test]
 fn test_send_no_body_fails() {
     let dir = tempfile::tempdir().unwrap();
@@ -407,6 +870,7 @@ fn test_receive_shows_outbox_messages() {
         )
         .unwrap(),
         metadata: std::collections::BTreeMap::new(),
+        attachments: Vec::new(),
     };
     cryochamber::message::write_message(dir.path(), "outbox", &msg).unwrap();
 
@@ -419,6 +883,89 @@ fn test_receive_shows_outbox_messages() {
         .stdout(predicates::str::contains("AI played Nf3"));
 }
 
+#[test]
+fn test_receive_json_and_fail_if_empty() {
+    let dir = tempfile::tempdir().unwrap();
+    cmd()
+        .args(["receive", "--json"])
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout("[]\n");
+    cmd()
+        .args(["receive", "--fail-if-empty"])
+        .current_dir(dir.path())
+        .assert()
+        .code(5)
+        .stdout(predicates::str::contains("No messages"));
+
+    let msg = cryochamber::message::Message {
+        from: "cryochamber".to_string(),
+        subject: "Board update".to_string(),
+        body: "AI played Nf3".to_string(),
+        timestamp: chrono::NaiveDateTime::parse_from_str(
+            "2026-02-23T10:00:00",
+            "%Y-%m-%dT%H:%M:%S",
+        )
+        .unwrap(),
+        metadata: std::collections::BTreeMap::new(),
+        attachments: Vec::new(),
+    };
+    cryochamber::message::write_message(dir.path(), "outbox", &msg).unwrap();
+
+    let output = cmd()
+        .args(["receive", "--json", "--fail-if-empty"])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let messages: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let messages = messages.as_array().unwrap();
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0]["from"], "cryochamber");
+    assert_eq!(messages[0]["subject"], "Board update");
+    assert_eq!(messages[0]["timestamp"], "2026-02-23T10:00:00");
+    assert_eq!(messages[0]["body"], "AI played Nf3");
+    assert_eq!(messages[0]["archived"], false);
+    assert!(messages[0]["filename"].as_str().unwrap().ends_with(".md"));
+}
+
+#[test]
+fn test_receive_delivered_shows_receipt() {
+    let dir = tempfile::tempdir().unwrap();
+    let msg = cryochamber::message::Message {
+        from: "agent".to_string(),
+        subject: "Status".to_string(),
+        body: "All green".to_string(),
+        timestamp: chrono::NaiveDateTime::parse_from_str(
+            "2026-02-23T10:00:00",
+            "%Y-%m-%dT%H:%M:%S",
+        )
+        .unwrap(),
+        metadata: std::collections::BTreeMap::new(),
+        attachments: Vec::new(),
+    };
+    let path = cryochamber::message::write_message(dir.path(), "outbox", &msg).unwrap();
+    let filename = path.file_name().unwrap().to_string_lossy().to_string();
+    let receipt = cryochamber::message::DeliveryReceipt {
+        channel: "github".to_string(),
+        remote_id: Some("DC_1".to_string()),
+        url: Some("https://github.com/o/r/discussions/1#discussioncomment-1".to_string()),
+        delivered_at: msg.timestamp,
+    };
+    cryochamber::message::archive_outbox_message(dir.path(), &filename, &receipt).unwrap();
+
+    cmd()
+        .args(["receive", "--delivered"])
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Status"))
+        .stdout(predicates::str::contains(
+            "Delivered: github https://github.com/o/r/discussions/1#discussioncomment-1",
+        ));
+}
+
 // --- Backward compat ---
 
 #[test]
@@ -694,6 +1241,17 @@ fn test_agent_hibernate_no_daemon() {
         .stderr(predicate::str::contains("Cannot connect"));
 }
 
+#[test]
+fn test_agent_ping_no_daemon() {
+    let dir = tempfile::tempdir().unwrap();
+    agent_cmd()
+        .arg("ping")
+        .current_dir(dir.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Cannot connect"));
+}
+
 #[test]
 fn test_agent_note_no_daemon() {
     let dir = tempfile::tempdir().unwrap();
@@ -705,6 +1263,112 @@ fn test_agent_note_no_daemon() {
         .stderr(predicate::str::contains("Cannot connect"));
 }
 
+#[test]
+fn test_agent_next_task_no_daemon() {
+    let dir = tempfile::tempdir().unwrap();
+    agent_cmd()
+        .args(["next-task", "Implement the REST handler"])
+        .current_dir(dir.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Cannot connect"));
+}
+
+#[test]
+fn test_agent_event_rejects_invalid_json() {
+    let dir = tempfile::tempdir().unwrap();
+    agent_cmd()
+        .args(["event", "--kind", "deploy", "--data", "{not json"])
+        .current_dir(dir.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("not valid JSON"));
+}
+
+#[test]
+fn test_agent_complete_rejects_invalid_result() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("result.json"), "not json").unwrap();
+    agent_cmd()
+        .args(["hibernate", "--complete", "--result", "result.json"])
+        .current_dir(dir.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("not valid JSON"));
+}
+
+#[test]
+fn test_agent_event_no_daemon() {
+    let dir = tempfile::tempdir().unwrap();
+    agent_cmd()
+        .args([
+            "event",
+            "--kind",
+            "deploy",
+            "--data",
+            r#"{"version":"1.2"}"#,
+        ])
+        .current_dir(dir.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Cannot connect"));
+}
+
+#[test]
+fn test_agent_discovers_project_from_subdirectory() {
+    let dir = tempfile::tempdir().unwrap();
+    init_dir(dir.path());
+    let nested = dir.path().join("src/deep");
+    fs::create_dir_all(&nested).unwrap();
+    let root = dir.path().canonicalize().unwrap();
+    agent_cmd()
+        .args(["note", "from a subdirectory"])
+        .current_dir(&nested)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(format!(
+            "Cannot connect to daemon socket at {}",
+            root.join(".cryo/cryo.sock").display()
+        )));
+}
+
+#[test]
+fn test_agent_uses_cryo_runtime_dir() {
+    let dir = tempfile::tempdir().unwrap();
+    let runtime = tempfile::tempdir().unwrap();
+    init_dir(dir.path());
+    agent_cmd()
+        .args(["note", "hello"])
+        .env("CRYO_RUNTIME_DIR", runtime.path())
+        .current_dir(dir.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(format!(
+            "Cannot connect to daemon socket at {}",
+            runtime.path().display()
+        )));
+}
+
+#[test]
+fn test_agent_does_not_take_home_registry_for_a_project() {
+    let home = tempfile::tempdir().unwrap();
+    let home_path = home.path().canonicalize().unwrap();
+    // ~/.cryo/ holds the daemon registry, which is no project marker
+    fs::create_dir_all(home_path.join(".cryo/daemons")).unwrap();
+    let nested = home_path.join("notes");
+    fs::create_dir_all(&nested).unwrap();
+    agent_cmd()
+        .args(["note", "outside any project"])
+        .current_dir(&nested)
+        .env("HOME", &home_path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(format!(
+            "Cannot connect to daemon socket at {}",
+            nested.join(".cryo/cryo.sock").display()
+        )));
+}
+
 #[test]
 fn test_agent_hibernate_requires_wake_or_complete() {
     let dir = tempfile::tempdir().unwrap();
@@ -714,3 +1378,14 @@ fn test_agent_hibernate_requires_wake_or_complete() {
         .assert()
         .failure();
 }
+
+#[test]
+fn test_agent_hibernate_rejects_bad_wake_in() {
+    let dir = tempfile::tempdir().unwrap();
+    agent_cmd()
+        .args(["hibernate", "--wake-in", "90"])
+        .current_dir(dir.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("missing unit"));
+}
@@ -53,6 +53,7 @@ fn test_config_partial_toml_uses_defaults() {
     assert_eq!(loaded.max_retries, 5); // default
     assert_eq!(loaded.max_session_duration, 0); // default
     assert!(loaded.watch_inbox); // default
+    assert_eq!(loaded.web_poll_ms, 500); // default
 }
 
 #[test]
@@ -68,6 +69,7 @@ fn test_apply_overrides_all() {
         next_wake: None,
         last_report_time: None,
         provider_index: None,
+        ..Default::default()
     };
 
     config.apply_overrides(&state);
@@ -97,6 +99,7 @@ fn test_apply_overrides_none_keeps_config() {
         next_wake: None,
         last_report_time: None,
         provider_index: None,
+        ..Default::default()
     };
 
     config.apply_overrides(&state);
@@ -128,6 +131,7 @@ fn test_apply_overrides_partial() {
         next_wake: None,
         last_report_time: None,
         provider_index: None,
+        ..Default::default()
     };
 
     config.apply_overrides(&state);
@@ -192,6 +196,7 @@ env = { ANTHROPIC_API_KEY = "sk-ant-test" }
 [[providers]]
 name = "openai"
 env = { OPENAI_API_KEY = "sk-test", OPENAI_BASE_URL = "https://api.openai.com/v1" }
+max_session_duration = 7200
 "#;
     std::fs::write(&path, toml_content).unwrap();
     let loaded = load_config(&path).unwrap().unwrap();
@@ -205,6 +210,8 @@ env = { OPENAI_API_KEY = "sk-test", OPENAI_BASE_URL = "https://api.openai.com/v1
     );
     assert_eq!(loaded.providers[1].name, "openai");
     assert_eq!(loaded.providers[1].env.len(), 2);
+    assert_eq!(loaded.providers[0].max_session_duration, None);
+    assert_eq!(loaded.providers[1].max_session_duration, Some(7200));
 }
 
 #[test]
@@ -218,6 +225,21 @@ fn test_config_without_providers_backward_compatible() {
     assert!(loaded.providers.is_empty());
 }
 
+#[test]
+fn test_kill_signal_and_grace() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = config_path(dir.path());
+    std::fs::write(&path, "agent = \"opencode\"\n").unwrap();
+    let loaded = load_config(&path).unwrap().unwrap();
+    assert_eq!(loaded.kill_signal, cryochamber::config::KillSignal::Term);
+    assert_eq!(loaded.kill_grace_secs, 2);
+
+    std::fs::write(&path, "kill_signal = \"int\"\nkill_grace_secs = 10\n").unwrap();
+    let loaded = load_config(&path).unwrap().unwrap();
+    assert_eq!(loaded.kill_signal, cryochamber::config::KillSignal::Int);
+    assert_eq!(loaded.kill_grace_secs, 10);
+}
+
 #[test]
 fn test_rotate_on_any_failure() {
     let dir = tempfile::tempdir().unwrap();
@@ -227,3 +249,154 @@ fn test_rotate_on_any_failure() {
     let loaded = load_config(&path).unwrap().unwrap();
     assert_eq!(loaded.rotate_on, cryochamber::config::RotateOn::AnyFailure);
 }
+
+#[test]
+fn test_prompt_style_embedded_defaults_history_to_last_session() {
+    use cryochamber::config::{HistoryMode, PromptStyle};
+    let dir = tempfile::tempdir().unwrap();
+    let path = config_path(dir.path());
+    std::fs::write(&path, "agent = \"opencode\"\n").unwrap();
+    let loaded = load_config(&path).unwrap().unwrap();
+    assert_eq!(loaded.prompt_style, PromptStyle::Slim);
+    assert_eq!(loaded.effective_history_mode(), HistoryMode::None);
+
+    std::fs::write(&path, "prompt_style = \"embedded\"\n").unwrap();
+    let loaded = load_config(&path).unwrap().unwrap();
+    assert_eq!(loaded.effective_history_mode(), HistoryMode::LastSession);
+
+    std::fs::write(
+        &path,
+        "prompt_style = \"embedded\"\nhistory_mode = \"full\"\n",
+    )
+    .unwrap();
+    let loaded = load_config(&path).unwrap().unwrap();
+    assert_eq!(loaded.effective_history_mode(), HistoryMode::Full);
+
+    // An explicit "none" is respected
+    std::fs::write(
+        &path,
+        "prompt_style = \"embedded\"\nhistory_mode = \"none\"\n",
+    )
+    .unwrap();
+    let loaded = load_config(&path).unwrap().unwrap();
+    assert_eq!(loaded.effective_history_mode(), HistoryMode::None);
+}
+
+#[test]
+fn test_message_dirs_default_and_override() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = config_path(dir.path());
+    std::fs::write(&path, "agent = \"opencode\"\n").unwrap();
+    let loaded = load_config(&path).unwrap().unwrap();
+    let dirs = cryochamber::message::dirs(dir.path(), &loaded);
+    assert_eq!(dirs.inbox, dir.path().join("messages").join("inbox"));
+    assert_eq!(dirs.outbox, dir.path().join("messages").join("outbox"));
+
+    std::fs::write(
+        &path,
+        "inbox_dir = \".agent/in\"\noutbox_dir = \".agent/out\"\n",
+    )
+    .unwrap();
+    let loaded = load_config(&path).unwrap().unwrap();
+    let dirs = cryochamber::message::dirs(dir.path(), &loaded);
+    assert_eq!(dirs.inbox, dir.path().join(".agent/in"));
+    assert_eq!(dirs.outbox_archive(), dir.path().join(".agent/out/archive"));
+}
+
+#[test]
+fn test_provider_env_file_merged_under_inline_env() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir_all(dir.path().join(".secrets")).unwrap();
+    std::fs::write(
+        dir.path().join(".secrets/openai.env"),
+        "# OpenAI\nexport OPENAI_API_KEY=\"sk-file\"\nOPENAI_MODEL='gpt-file'\n\nOPENAI_BASE_URL=https://x\n",
+    )
+    .unwrap();
+    let path = config_path(dir.path());
+    std::fs::write(
+        &path,
+        r#"
+[[providers]]
+name = "openai"
+env_file = ".secrets/openai.env"
+env = { OPENAI_MODEL = "gpt-inline" }
+"#,
+    )
+    .unwrap();
+
+    let config = load_config(&path).unwrap().unwrap();
+    let env = config.providers[0].load_env(dir.path()).unwrap();
+    assert_eq!(env["OPENAI_API_KEY"], "sk-file");
+    assert_eq!(env["OPENAI_MODEL"], "gpt-inline", "inline env wins");
+    assert_eq!(env["OPENAI_BASE_URL"], "https://x");
+    assert!(config.check_env_files(dir.path()).is_ok());
+
+    std::fs::write(dir.path().join(".secrets/openai.env"), "not a pair\n").unwrap();
+    let err = config.check_env_files(dir.path()).unwrap_err();
+    assert!(format!("{err:#}").contains("line 1"), "{err:#}");
+
+    std::fs::remove_file(dir.path().join(".secrets/openai.env")).unwrap();
+    assert!(config.check_env_files(dir.path()).is_err());
+}
+
+#[test]
+fn test_config_warnings_unknown_keys_and_values() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = config_path(dir.path());
+    std::fs::write(
+        &path,
+        r#"agent = "claude"
+max_retires = 3
+rotate_on = "any-failure"
+report_interval = 24
+report_time = "9am"
+
+[[providers]]
+name = ""
+env_flie = "keys.env"
+"#,
+    )
+    .unwrap();
+
+    let warnings = cryochamber::config::config_warnings(&path).unwrap();
+    let all = warnings.join("\n");
+    assert!(
+        all.contains("line 2: unknown key `max_retires` is ignored (did you mean `max_retries`?)"),
+        "{all}"
+    );
+    assert!(
+        all.contains(
+            "unknown key `env_flie` in [[providers]] is ignored (did you mean `env_file`?)"
+        ),
+        "{all}"
+    );
+    assert!(all.contains("provider name is empty"), "{all}");
+    assert!(all.contains("line 3: rotate_on has no effect"), "{all}");
+    assert!(
+        all.contains("line 5: report_time \"9am\" is not HH:MM"),
+        "{all}"
+    );
+    assert_eq!(warnings.len(), 5, "{all}");
+}
+
+#[test]
+fn test_config_warnings_bad_clean_exit_wake_in() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = config_path(dir.path());
+    std::fs::write(&path, "agent = \"claude\"\nclean_exit_wake_in = \"soon\"\n").unwrap();
+
+    let warnings = cryochamber::config::config_warnings(&path).unwrap();
+    assert_eq!(warnings.len(), 1, "{warnings:?}");
+    assert!(
+        warnings[0].starts_with("line 2: clean_exit_wake_in: "),
+        "{warnings:?}"
+    );
+}
+
+#[test]
+fn test_config_warnings_clean_for_template() {
+    let dir = tempfile::tempdir().unwrap();
+    cryochamber::protocol::write_config_file(dir.path(), "opencode").unwrap();
+    let warnings = cryochamber::config::config_warnings(&config_path(dir.path())).unwrap();
+    assert!(warnings.is_empty(), "{warnings:?}");
+}
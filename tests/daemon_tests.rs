@@ -1,5 +1,5 @@
 // tests/daemon_tests.rs
-use cryochamber::daemon::RetryState;
+use cryochamber::daemon::{FailureKind, RetryState};
 
 #[test]
 fn test_retry_state_provider_rotation_advances() {
@@ -50,6 +50,16 @@ fn test_retry_state_reset_clears_provider_index() {
     assert_eq!(retry.provider_index, 0);
 }
 
+#[test]
+fn test_retry_state_gives_up_only_on_logic_failures() {
+    let mut retry = RetryState::new(2, 1);
+    retry.record_failure();
+    assert!(!retry.gives_up(FailureKind::Logic));
+    retry.record_failure();
+    assert!(retry.gives_up(FailureKind::Logic));
+    assert!(!retry.gives_up(FailureKind::Transient));
+}
+
 #[test]
 fn test_retry_state_single_provider_no_rotation() {
     let mut retry = RetryState::new(5, 1);
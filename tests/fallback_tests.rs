@@ -1,5 +1,6 @@
 // tests/fallback_tests.rs
 use cryochamber::fallback::FallbackAction;
+use cryochamber::notifier::Notifier;
 
 #[test]
 fn test_fallback_action_display() {
@@ -43,7 +44,9 @@ fn test_execute_writes_to_outbox() {
         target: "user@example.com".to_string(),
         message: "session did not run".to_string(),
     };
-    action.execute(dir.path(), "outbox").unwrap();
+    action
+        .execute(dir.path(), "outbox", &Notifier::probe())
+        .unwrap();
 
     // Verify outbox file was created
     let outbox = dir.path().join("messages/outbox");
@@ -67,7 +70,9 @@ fn test_execute_webhook_writes_to_outbox() {
         target: "https://hooks.slack.com/xxx".to_string(),
         message: "alert".to_string(),
     };
-    action.execute(dir.path(), "outbox").unwrap();
+    action
+        .execute(dir.path(), "outbox", &Notifier::probe())
+        .unwrap();
 
     let outbox = dir.path().join("messages/outbox");
     let entries: Vec<_> = std::fs::read_dir(&outbox)
@@ -79,3 +84,27 @@ fn test_execute_webhook_writes_to_outbox() {
     let content = std::fs::read_to_string(entries[0].path()).unwrap();
     assert!(content.contains("fallback_action: webhook"));
 }
+
+#[test]
+fn test_notify_without_notifier_still_writes_outbox() {
+    let dir = tempfile::tempdir().unwrap();
+    let action = FallbackAction {
+        action: "plan_missing".to_string(),
+        target: "operator".to_string(),
+        message: "plan.md is missing".to_string(),
+    };
+    let retry = FallbackAction {
+        action: "retry_exhausted".to_string(),
+        ..action.clone()
+    };
+    let notifier =
+        Notifier::unavailable("no D-Bus session bus (DBUS_SESSION_BUS_ADDRESS is unset)");
+    action.execute(dir.path(), "notify", &notifier).unwrap();
+    retry.execute(dir.path(), "notify", &notifier).unwrap();
+
+    let outbox = dir.path().join("messages/outbox");
+    assert_eq!(std::fs::read_dir(&outbox).unwrap().count(), 2);
+    // The warning was printed by the first alert and is not repeated
+    assert_eq!(notifier.take_warning(), None);
+    assert!(!notifier.is_available());
+}
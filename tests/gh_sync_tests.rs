@@ -12,6 +12,8 @@ fn test_sync_state_roundtrip() {
         last_read_cursor: Some("Y3Vyc29y".to_string()),
         self_login: None,
         last_pushed_session: None,
+        last_pushed_at: None,
+        category: None,
     };
     save_sync_state(&path, &state).unwrap();
     let loaded = load_sync_state(&path).unwrap().unwrap();
@@ -42,6 +44,8 @@ fn test_sync_state_no_cursor() {
         last_read_cursor: None,
         self_login: None,
         last_pushed_session: None,
+        last_pushed_at: None,
+        category: None,
     };
     save_sync_state(&path, &state).unwrap();
     let loaded = load_sync_state(&path).unwrap().unwrap();
@@ -57,6 +61,8 @@ fn test_sync_state_owner_repo_split() {
         last_read_cursor: None,
         self_login: None,
         last_pushed_session: None,
+        last_pushed_at: None,
+        category: None,
     };
     let (owner, repo) = state.owner_repo().unwrap();
     assert_eq!(owner, "GiggleLiu");
@@ -75,6 +81,8 @@ fn test_sync_state_new_fields_roundtrip() {
         last_read_cursor: None,
         self_login: Some("mybot".to_string()),
         last_pushed_session: Some(3),
+        last_pushed_at: None,
+        category: None,
     };
     save_sync_state(&path, &state).unwrap();
     let loaded = load_sync_state(&path).unwrap().unwrap();
@@ -96,3 +104,58 @@ fn test_sync_state_legacy_json_compat() {
     assert_eq!(loaded.self_login, None);
     assert_eq!(loaded.last_pushed_session, None);
 }
+
+#[test]
+fn test_sync_daemon_pid_lock() {
+    use cryochamber::process::{read_pid, release_pid_lock, sync_pid_path, PidLock};
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = sync_pid_path(dir.path(), "gh-sync");
+    assert!(path.ends_with(".cryo/gh-sync.pid"));
+
+    // A live holder blocks a second instance
+    let mut other = std::process::Command::new("sleep")
+        .arg("30")
+        .spawn()
+        .unwrap();
+    let other_pid = other.id();
+    // Reap it as soon as it dies, so it doesn't linger as a zombie
+    let reaper = std::thread::spawn(move || other.wait());
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(&path, format!("{other_pid}\n")).unwrap();
+    let err = PidLock::acquire(&path)
+        .err()
+        .expect("live lock must refuse");
+    assert!(err.to_string().contains(&other_pid.to_string()));
+
+    // unsync stops the holder and removes the file
+    assert_eq!(release_pid_lock(&path).unwrap(), Some(other_pid));
+    reaper.join().unwrap().unwrap();
+    assert!(!path.exists());
+
+    // A stale file is taken over, and the lock is released on drop
+    std::fs::write(&path, format!("{other_pid}\n")).unwrap();
+    let lock = PidLock::acquire(&path).unwrap();
+    assert_eq!(read_pid(&path), Some(std::process::id()));
+    drop(lock);
+    assert!(!path.exists());
+    let leftovers: Vec<_> = std::fs::read_dir(path.parent().unwrap()).unwrap().collect();
+    assert!(leftovers.is_empty(), "temp file left behind: {leftovers:?}");
+
+    // A live PID that started at another time was reused: never signalled,
+    // and the lock is stale
+    let mut reused = std::process::Command::new("sleep")
+        .arg("30")
+        .spawn()
+        .unwrap();
+    std::fs::write(&path, format!("{}\nnot-its-start-time\n", reused.id())).unwrap();
+    assert_eq!(release_pid_lock(&path).unwrap(), None);
+    assert!(
+        reused.try_wait().unwrap().is_none(),
+        "reused PID was killed"
+    );
+    std::fs::write(&path, format!("{}\nnot-its-start-time\n", reused.id())).unwrap();
+    drop(PidLock::acquire(&path).unwrap());
+    reused.kill().unwrap();
+    reused.wait().unwrap();
+}
@@ -1,6 +1,7 @@
 use cryochamber::channel::github::{
-    build_create_discussion_mutation, build_fetch_comments_query, build_post_comment_mutation,
-    parse_create_discussion_response, parse_discussion_comments,
+    attachment_urls, build_create_discussion_mutation, build_fetch_comments_query,
+    build_post_comment_mutation, outbox_comment_body, parse_create_discussion_response,
+    parse_discussion_comments, parse_post_comment_response, select_category,
 };
 
 #[test]
@@ -135,3 +136,96 @@ fn test_parse_create_discussion_response() {
     assert_eq!(node_id, "D_kwDOtest");
     assert_eq!(number, 42);
 }
+
+#[test]
+fn test_select_category() {
+    let categories = vec![
+        serde_json::json!({"id": "DC_ann", "name": "Announcements"}),
+        serde_json::json!({"id": "DC_gen", "name": "General"}),
+        serde_json::json!({"id": "DC_qa", "name": "Q&A"}),
+    ];
+    assert_eq!(select_category(&categories, None).unwrap(), "DC_gen");
+    assert_eq!(
+        select_category(&categories, Some("announcements")).unwrap(),
+        "DC_ann"
+    );
+    let err = select_category(&categories, Some("Ideas"))
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("\"Ideas\" not found"), "{err}");
+    assert!(err.contains("Announcements, General, Q&A"), "{err}");
+    // Without a General category the first one is used
+    assert_eq!(select_category(&categories[2..], None).unwrap(), "DC_qa");
+}
+
+#[test]
+fn test_parse_post_comment_response() {
+    let json = serde_json::json!({
+        "data": {
+            "addDiscussionComment": {
+                "comment": {
+                    "id": "DC_kwDOtest",
+                    "url": "https://github.com/o/r/discussions/1#discussioncomment-9"
+                }
+            }
+        }
+    });
+    let (id, url) = parse_post_comment_response(&json).unwrap();
+    assert_eq!(id, "DC_kwDOtest");
+    assert_eq!(
+        url.as_deref(),
+        Some("https://github.com/o/r/discussions/1#discussioncomment-9")
+    );
+}
+
+#[test]
+fn test_parse_post_comment_response_missing_id() {
+    let json = serde_json::json!({"data": {"addDiscussionComment": {"comment": {}}}});
+    assert!(parse_post_comment_response(&json).is_err());
+}
+
+#[test]
+fn test_attachment_urls() {
+    let body = "Build log: [build.log](https://github.com/user-attachments/files/123/build.log)\n\
+                ![shot](https://github.com/user-attachments/assets/abc-def)\n\
+                again https://github.com/user-attachments/files/123/build.log and \
+                https://example.com/not-an-attachment";
+    assert_eq!(
+        attachment_urls(body),
+        vec![
+            "https://github.com/user-attachments/files/123/build.log",
+            "https://github.com/user-attachments/assets/abc-def",
+        ]
+    );
+    assert!(attachment_urls("no links here").is_empty());
+}
+
+fn outbox_message(to: Option<&str>) -> cryochamber::message::Message {
+    let mut metadata = std::collections::BTreeMap::new();
+    if let Some(to) = to {
+        metadata.insert("to".to_string(), to.to_string());
+    }
+    cryochamber::message::Message {
+        from: "agent".to_string(),
+        subject: "Deploy".to_string(),
+        body: "v1.2 is live".to_string(),
+        timestamp: chrono::NaiveDate::from_ymd_opt(2026, 3, 1)
+            .unwrap()
+            .and_hms_opt(9, 0, 0)
+            .unwrap(),
+        metadata,
+        attachments: Vec::new(),
+    }
+}
+
+#[test]
+fn test_outbox_comment_mentions_recipients() {
+    assert_eq!(
+        outbox_comment_body(&outbox_message(None)),
+        "**agent** (Deploy)\n\nv1.2 is live"
+    );
+    assert_eq!(
+        outbox_comment_body(&outbox_message(Some("alice, @bob"))),
+        "**agent** (Deploy) to @alice @bob\n\nv1.2 is live"
+    );
+}
@@ -34,6 +34,14 @@ fn test_build_prompt_with_context() {
         session_number: 3,
         task: "Continue work".to_string(),
         delayed_wake: None,
+        plan_changed: false,
+        checkpoint: None,
+        focus: None,
+        attachments: Vec::new(),
+        inbox_dir: "messages/inbox".to_string(),
+        require_inbox_ack: false,
+        history: None,
+        messages: Vec::new(),
     };
     let prompt = build_prompt(&config);
     assert!(prompt.contains("Session number: 3"));
@@ -55,6 +63,7 @@ fn test_state_roundtrip() {
         next_wake: None,
         last_report_time: None,
         provider_index: None,
+        ..Default::default()
     };
     save_state(&state_path, &state).unwrap();
 
@@ -80,6 +89,7 @@ fn test_file_channel_roundtrip() {
         )
         .unwrap(),
         metadata: std::collections::BTreeMap::new(),
+        attachments: Vec::new(),
     };
     cryochamber::message::write_message(dir.path(), "inbox", &msg).unwrap();
 
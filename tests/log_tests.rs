@@ -1,7 +1,9 @@
 // tests/log_tests.rs
+use cryochamber::config::HistoryMode;
 use cryochamber::log::{
     parse_latest_session_notes, parse_latest_session_task, parse_latest_session_wake,
-    read_current_session, read_latest_session, session_count, EventLogger,
+    read_current_session, read_history, read_latest_plan_session, read_latest_session,
+    session_count, EventLogger, FULL_HISTORY_MAX_BYTES, SESSION_END,
 };
 use std::fs;
 
@@ -58,6 +60,87 @@ fn test_read_latest_session() {
     assert!(!latest.contains("first session work"));
 }
 
+#[test]
+fn test_read_history_modes() {
+    let dir = tempfile::tempdir().unwrap();
+    let log_path = dir.path().join("cryo.log");
+    assert!(read_history(&log_path, HistoryMode::Full)
+        .unwrap()
+        .is_none());
+
+    for (n, work) in [(1, "first session work"), (2, "second session work")] {
+        let mut logger = EventLogger::begin(&log_path, n, "Task", "agent", &[]).unwrap();
+        logger.log_event(work).unwrap();
+        logger.finish("done").unwrap();
+    }
+
+    assert!(read_history(&log_path, HistoryMode::None)
+        .unwrap()
+        .is_none());
+    let last = read_history(&log_path, HistoryMode::LastSession)
+        .unwrap()
+        .unwrap();
+    assert!(last.contains("second session work") && !last.contains("first session work"));
+    let full = read_history(&log_path, HistoryMode::Full).unwrap().unwrap();
+    assert!(full.contains("first session work") && full.contains("second session work"));
+}
+
+#[test]
+fn test_read_history_full_drops_oldest_sessions() {
+    let dir = tempfile::tempdir().unwrap();
+    let log_path = dir.path().join("cryo.log");
+    let filler = "x".repeat(1000);
+    for n in 1..=100 {
+        let mut logger = EventLogger::begin(&log_path, n, "Task", "agent", &[]).unwrap();
+        logger
+            .log_event(&format!("work of session {n}: {filler}"))
+            .unwrap();
+        logger.finish("done").unwrap();
+    }
+
+    let full = read_history(&log_path, HistoryMode::Full).unwrap().unwrap();
+    assert!(full.len() <= FULL_HISTORY_MAX_BYTES + 100);
+    assert!(full.starts_with("(earlier sessions omitted)\n--- CRYO SESSION"));
+    assert!(full.contains("work of session 100:"));
+    assert!(!full.contains("work of session 1:"));
+}
+
+#[test]
+fn test_read_latest_plan_session() {
+    let dir = tempfile::tempdir().unwrap();
+    let log_path = dir.path().join("cryo.log");
+    assert!(read_latest_plan_session(&log_path, "api")
+        .unwrap()
+        .is_none());
+
+    for (n, plan) in [(1, "api"), (2, "docs"), (3, "api-v2")] {
+        let mut logger = EventLogger::begin(&log_path, n, "Task", "agent", &[]).unwrap();
+        logger.log_event(&format!("focus plan: {plan}")).unwrap();
+        logger.log_event(&format!("{plan} work")).unwrap();
+        logger.finish("done").unwrap();
+    }
+    // Unfinished sessions don't count
+    let mut logger = EventLogger::begin(&log_path, 4, "Task", "agent", &[]).unwrap();
+    logger.log_event("focus plan: docs").unwrap();
+
+    let api = read_latest_plan_session(&log_path, "api").unwrap().unwrap();
+    assert!(
+        api.contains("--- CRYO SESSION 1") && api.contains("api work"),
+        "{api}"
+    );
+    assert!(
+        !api.contains("docs work") && api.ends_with(SESSION_END),
+        "{api}"
+    );
+    let docs = read_latest_plan_session(&log_path, "docs")
+        .unwrap()
+        .unwrap();
+    assert!(docs.contains("--- CRYO SESSION 2") && !docs.contains("SESSION 4"));
+    assert!(read_latest_plan_session(&log_path, "web")
+        .unwrap()
+        .is_none());
+}
+
 #[test]
 fn test_read_latest_session_empty_file() {
     let dir = tempfile::tempdir().unwrap();
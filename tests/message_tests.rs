@@ -1,8 +1,11 @@
 // tests/message_tests.rs
 use chrono::NaiveDateTime;
+use cryochamber::config::InboxDisposition;
 use cryochamber::message::{
-    archive_messages, ensure_dirs, list_inbox, message_to_markdown, parse_message, read_inbox,
-    read_inbox_archive, write_message, Message,
+    archive_messages, archive_outbox_message, attachments_dir, dispose_messages, ensure_dirs,
+    import_drop_dir, list_inbox, message_to_markdown, parse_message, read_inbox,
+    read_inbox_archive, read_outbox, read_outbox_archive, receipt_filename, save_attachment,
+    write_message, DeliveryReceipt, Message, IN_REPLY_TO_KEY,
 };
 use std::collections::BTreeMap;
 
@@ -13,6 +16,7 @@ fn make_message(from: &str, subject: &str, body: &str, ts: &str) -> Message {
         body: body.to_string(),
         timestamp: NaiveDateTime::parse_from_str(ts, "%Y-%m-%dT%H:%M:%S").unwrap(),
         metadata: BTreeMap::new(),
+        attachments: Vec::new(),
     }
 }
 
@@ -114,6 +118,7 @@ fn test_message_roundtrip() {
         timestamp: NaiveDateTime::parse_from_str("2026-02-23T15:30:00", "%Y-%m-%dT%H:%M:%S")
             .unwrap(),
         metadata,
+        attachments: Vec::new(),
     };
 
     let markdown = message_to_markdown(&msg);
@@ -142,6 +147,7 @@ fn test_write_outbox() {
         timestamp: NaiveDateTime::parse_from_str("2026-02-23T12:00:00", "%Y-%m-%dT%H:%M:%S")
             .unwrap(),
         metadata,
+        attachments: Vec::new(),
     };
 
     let path = write_message(dir.path(), "outbox", &msg).unwrap();
@@ -276,3 +282,187 @@ fn test_list_inbox_ignores_non_md_files() {
     assert_eq!(filenames.len(), 1);
     assert!(filenames[0].ends_with(".md"));
 }
+
+#[test]
+fn test_receipt_filename() {
+    assert_eq!(
+        receipt_filename("2026-03-01T10-00-00_reply.md"),
+        "2026-03-01T10-00-00_reply.receipt.json"
+    );
+}
+
+#[test]
+fn test_archive_outbox_message_writes_receipt() {
+    let dir = tempfile::tempdir().unwrap();
+    let msg = make_message("agent", "Reply", "Done", "2026-03-01T10:00:00");
+    let path = write_message(dir.path(), "outbox", &msg).unwrap();
+    let filename = path.file_name().unwrap().to_string_lossy().to_string();
+
+    let receipt = DeliveryReceipt {
+        channel: "zulip".to_string(),
+        remote_id: Some("12345".to_string()),
+        url: None,
+        delivered_at: NaiveDateTime::parse_from_str("2026-03-01T10:05:00", "%Y-%m-%dT%H:%M:%S")
+            .unwrap(),
+    };
+    archive_outbox_message(dir.path(), &filename, &receipt).unwrap();
+
+    assert!(read_outbox(dir.path()).unwrap().is_empty());
+    let archived = read_outbox_archive(dir.path()).unwrap();
+    assert_eq!(archived.len(), 1);
+    assert_eq!(archived[0].0, filename);
+    assert_eq!(archived[0].1.body, "Done");
+    assert_eq!(archived[0].2.as_ref(), Some(&receipt));
+}
+
+#[test]
+fn test_read_outbox_archive_without_receipt() {
+    let dir = tempfile::tempdir().unwrap();
+    let archive = dir.path().join("messages/outbox/archive");
+    std::fs::create_dir_all(&archive).unwrap();
+    let msg = make_message("agent", "Old", "legacy", "2026-03-01T09:00:00");
+    std::fs::write(archive.join("old.md"), message_to_markdown(&msg)).unwrap();
+
+    let archived = read_outbox_archive(dir.path()).unwrap();
+    assert_eq!(archived.len(), 1);
+    assert!(archived[0].2.is_none());
+}
+
+#[test]
+fn test_import_drop_dir_moves_files_into_inbox() {
+    let dir = tempfile::tempdir().unwrap();
+    let drop = dir.path().join("drops");
+    std::fs::create_dir_all(&drop).unwrap();
+    std::fs::write(drop.join("alert.txt"), "disk almost full\n").unwrap();
+    let msg = make_message("ci", "Build failed", "see logs", "2026-02-23T08:00:00");
+    std::fs::write(drop.join("ci.md"), message_to_markdown(&msg)).unwrap();
+    std::fs::write(drop.join(".partial"), "still writing").unwrap();
+
+    assert_eq!(import_drop_dir(dir.path(), &drop).unwrap(), 2);
+
+    let inbox = read_inbox(dir.path()).unwrap();
+    assert_eq!(inbox.len(), 2);
+    let plain = inbox.iter().find(|(_, m)| m.subject == "alert").unwrap();
+    assert_eq!(plain.1.from, "drops");
+    assert_eq!(plain.1.body, "disk almost full");
+    assert!(inbox
+        .iter()
+        .any(|(_, m)| m.from == "ci" && m.body == "see logs"));
+
+    // Imported files are removed; hidden files are left for their writer
+    assert!(!drop.join("alert.txt").exists());
+    assert!(!drop.join("ci.md").exists());
+    assert!(drop.join(".partial").exists());
+}
+
+#[test]
+fn test_import_drop_dir_moves_unreadable_file_aside() {
+    let dir = tempfile::tempdir().unwrap();
+    let drop = dir.path().join("drops");
+    std::fs::create_dir_all(&drop).unwrap();
+    std::fs::write(drop.join("a-binary.bin"), [0xff, 0xfe, 0x00, 0x80]).unwrap();
+    std::fs::write(drop.join("b-alert.txt"), "disk almost full\n").unwrap();
+
+    assert_eq!(import_drop_dir(dir.path(), &drop).unwrap(), 1);
+
+    let inbox = read_inbox(dir.path()).unwrap();
+    assert_eq!(inbox.len(), 1);
+    assert_eq!(inbox[0].1.subject, "b-alert");
+    assert!(!drop.join("a-binary.bin").exists());
+    assert!(drop.join("failed/a-binary.bin").exists());
+    // The moved file is not picked up again
+    assert_eq!(import_drop_dir(dir.path(), &drop).unwrap(), 0);
+}
+
+#[test]
+fn test_import_drop_dir_missing_is_noop() {
+    let dir = tempfile::tempdir().unwrap();
+    assert_eq!(
+        import_drop_dir(dir.path(), &dir.path().join("nope")).unwrap(),
+        0
+    );
+}
+
+#[test]
+fn test_dispose_messages_delete() {
+    let dir = tempfile::tempdir().unwrap();
+    let msg = make_message("alice", "Hi", "Hello", "2026-02-23T08:00:00");
+    write_message(dir.path(), "inbox", &msg).unwrap();
+    let filenames = list_inbox(dir.path()).unwrap();
+
+    dispose_messages(dir.path(), &filenames, InboxDisposition::Delete).unwrap();
+
+    assert!(list_inbox(dir.path()).unwrap().is_empty());
+    assert!(read_inbox_archive(dir.path()).unwrap().is_empty());
+}
+
+#[test]
+fn test_dispose_messages_flag_keeps_file_in_place() {
+    let dir = tempfile::tempdir().unwrap();
+    let msg = make_message("alice", "Hi", "Hello", "2026-02-23T08:00:00");
+    write_message(dir.path(), "inbox", &msg).unwrap();
+    let filenames = list_inbox(dir.path()).unwrap();
+
+    dispose_messages(dir.path(), &filenames, InboxDisposition::Flag).unwrap();
+
+    // Flagged files stay in inbox/ but are no longer pending
+    assert!(list_inbox(dir.path()).unwrap().is_empty());
+    assert!(read_inbox(dir.path()).unwrap().is_empty());
+    let flagged = dir
+        .path()
+        .join("messages/inbox")
+        .join(format!("{}.consumed", filenames[0]));
+    assert!(flagged.exists());
+}
+
+#[test]
+fn test_message_answers_question_id() {
+    let mut msg = make_message("human", "Re", "ship it", "2026-03-01T10:00:00");
+    assert!(msg.answers(None));
+    assert!(!msg.answers(Some("deploy-ok")));
+
+    msg.metadata
+        .insert(IN_REPLY_TO_KEY.to_string(), "deploy-ok".to_string());
+    let parsed = parse_message(&message_to_markdown(&msg)).unwrap();
+    assert!(parsed.answers(Some("deploy-ok")));
+    assert!(!parsed.answers(Some("other")));
+}
+
+#[test]
+fn test_attachments_roundtrip_through_frontmatter() {
+    let mut msg = make_message("human", "Logs", "see attached", "2026-03-01T10:00:00");
+    msg.attachments = vec!["build.log".to_string(), "shot.png".to_string()];
+    let md = message_to_markdown(&msg);
+    assert!(md.contains("attachments: build.log, shot.png"), "{md}");
+
+    let parsed = parse_message(&md).unwrap();
+    assert_eq!(parsed.attachments, msg.attachments);
+    assert!(!parsed.metadata.contains_key("attachments"));
+}
+
+#[test]
+fn test_save_attachment_sanitizes_and_dedups_names() {
+    let dir = tempfile::tempdir().unwrap();
+    let first = save_attachment(dir.path(), "../my report,v2.txt", b"one").unwrap();
+    let second = save_attachment(dir.path(), "../my report,v2.txt", b"two").unwrap();
+    assert!(first.ends_with("_my-report-v2.txt"), "{first}");
+    assert_ne!(first, second);
+
+    let att_dir = attachments_dir(dir.path());
+    assert_eq!(std::fs::read(att_dir.join(&first)).unwrap(), b"one");
+    assert_eq!(std::fs::read(att_dir.join(&second)).unwrap(), b"two");
+}
+
+#[test]
+fn test_broken_cryo_toml_is_an_error_not_the_default_dirs() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("cryo.toml"),
+        "inbox_dir = \".agent/in\"\nagent = \n",
+    )
+    .unwrap();
+    let msg = make_message("alice", "Hi", "Hello", "2026-02-23T08:00:00");
+
+    assert!(write_message(dir.path(), "inbox", &msg).is_err());
+    assert!(!dir.path().join("messages").exists());
+}
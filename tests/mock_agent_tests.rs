@@ -116,7 +116,88 @@ fn test_mock_crash_retries_then_exits() {
         "Log should show agent exited without hibernate"
     );
 
-    // Cancel the daemon (it retries indefinitely)
+    cancel_and_wait(dir.path());
+}
+
+/// Run `scenario` with `complete_marker = mode` and check the daemon treats
+/// the agent's exit as plan completion.
+fn assert_completion_detected(scenario: &str, mode: &str, how: &str) {
+    let dir = tempfile::tempdir().unwrap();
+    setup_scenario(dir.path(), scenario);
+    let mut config = fs::read_to_string(dir.path().join("cryo.toml")).unwrap();
+    config.push_str(&format!("complete_marker = \"{mode}\"\n"));
+    fs::write(dir.path().join("cryo.toml"), config).unwrap();
+
+    cryo_bin()
+        .args(["start", "--agent", "mock"])
+        .env("CRYO_NO_SERVICE", "1")
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    assert!(
+        wait_for_daemon_exit(dir.path(), Duration::from_secs(15)),
+        "Daemon should stop once the plan is complete"
+    );
+    let log = fs::read_to_string(dir.path().join("cryo.log")).unwrap();
+    assert!(
+        log.contains(&format!("plan complete, detected {how}")),
+        "log: {log}"
+    );
+    assert!(
+        !log.contains("agent exited without hibernate"),
+        "log: {log}"
+    );
+    assert!(!dir.path().join(".cryo/COMPLETE").exists());
+}
+
+#[test]
+fn test_mock_complete_marker_in_output() {
+    assert_completion_detected("complete-marker.sh", "output", "output marker");
+}
+
+#[test]
+fn test_mock_complete_file() {
+    assert_completion_detected("complete-file.sh", "any", ".cryo/COMPLETE");
+}
+
+#[test]
+fn test_logic_failure_stops_retrying_after_max_retries() {
+    let dir = tempfile::tempdir().unwrap();
+    setup_scenario(dir.path(), "crash.sh");
+    let config = fs::read_to_string(dir.path().join("cryo.toml")).unwrap();
+    let config = config.replace("max_retries = 5", "max_retries = 2");
+    fs::write(dir.path().join("cryo.toml"), config).unwrap();
+
+    cryo_bin()
+        .args(["start", "--agent", "mock"])
+        .env("CRYO_NO_SERVICE", "1")
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    // Two crashes (5s backoff between them), then the daemon parks
+    assert!(
+        wait_for_log_content(dir.path(), "Waiting for a message", Duration::from_secs(30)),
+        "Daemon should stop retrying after max_retries logic failures"
+    );
+    std::thread::sleep(Duration::from_millis(500));
+    let state: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(dir.path().join("timer.json")).unwrap()).unwrap();
+    assert_eq!(state["awaiting_message"], true, "state: {state}");
+    assert!(
+        state["blocked_reason"]
+            .as_str()
+            .is_some_and(|r| r.contains("without hibernating")),
+        "state: {state}"
+    );
+    assert!(!state["pid"].is_null(), "daemon should still be running");
+
+    // No third session while parked
+    std::thread::sleep(Duration::from_secs(6));
+    let log = fs::read_to_string(dir.path().join("cryo.log")).unwrap();
+    assert_eq!(log.matches("--- CRYO SESSION").count(), 2, "{log}");
+
     cancel_and_wait(dir.path());
 }
 
@@ -165,6 +246,154 @@ fn test_mock_timeout_kills_agent() {
     cancel_and_wait(dir.path());
 }
 
+#[test]
+fn test_mock_timeout_keeps_working_summary() {
+    let dir = tempfile::tempdir().unwrap();
+    setup_scenario(dir.path(), "set-summary-then-timeout.sh");
+
+    cryo_bin()
+        .args(["start", "--agent", "mock", "--max-session-duration", "3"])
+        .env("CRYO_NO_SERVICE", "1")
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    assert!(
+        wait_for_log_content(
+            dir.path(),
+            "last summary: \"Migrated 3 of 5 tables\"",
+            Duration::from_secs(15)
+        ),
+        "Log should record the working summary on timeout"
+    );
+    let state = cryochamber::state::load_state(&cryochamber::state::state_path(dir.path()))
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        state.last_summary.as_deref(),
+        Some("Migrated 3 of 5 tables")
+    );
+
+    cancel_and_wait(dir.path());
+}
+
+#[test]
+fn test_mock_summary_append_becomes_hibernate_summary() {
+    let dir = tempfile::tempdir().unwrap();
+    setup_scenario(dir.path(), "summary-append.sh");
+
+    cryo_bin()
+        .args(["start", "--once", "--agent", "mock"])
+        .current_dir(dir.path())
+        .timeout(Duration::from_secs(30))
+        .assert()
+        .success();
+
+    let log = fs::read_to_string(dir.path().join("cryo.log")).unwrap();
+    assert!(log.contains("summary+: \"Fixed the parser\""), "{log}");
+    assert!(log.contains("summary+: \"Added tests\""), "{log}");
+    assert!(
+        log.contains("summary=\"Fixed the parser; Added tests\""),
+        "hibernate should use the appended summary:\n{log}"
+    );
+}
+
+#[test]
+fn test_mock_defer_extends_deadline() {
+    let dir = tempfile::tempdir().unwrap();
+    setup_scenario(dir.path(), "defer-then-hibernate.sh");
+
+    // Without the deferral the 3s timeout would kill the agent mid-sleep
+    cryo_bin()
+        .args(["start", "--agent", "mock", "--max-session-duration", "3"])
+        .env("CRYO_NO_SERVICE", "1")
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    assert!(
+        wait_for_log_content(dir.path(), "plan complete", Duration::from_secs(20)),
+        "Deferred session should finish"
+    );
+    let log = std::fs::read_to_string(dir.path().join("cryo.log")).unwrap();
+    assert!(log.contains("defer: deadline +3s"), "log: {log}");
+    assert!(!log.contains("session timeout"), "log: {log}");
+
+    cancel_and_wait(dir.path());
+}
+
+#[test]
+fn test_mock_defer_overflow_is_refused() {
+    let dir = tempfile::tempdir().unwrap();
+    setup_scenario(dir.path(), "defer-past-end-of-time.sh");
+
+    cryo_bin()
+        .args(["start", "--once", "--agent", "mock"])
+        .current_dir(dir.path())
+        .timeout(Duration::from_secs(30))
+        .assert()
+        .success();
+
+    let result = fs::read_to_string(dir.path().join(".defer-result")).unwrap();
+    assert_eq!(result, "refused\n");
+    let error = fs::read_to_string(dir.path().join(".defer-error")).unwrap();
+    assert!(error.contains("out of range"), "{error}");
+    let log = fs::read_to_string(dir.path().join("cryo.log")).unwrap();
+    assert!(log.contains("hibernate: wake=+262142-12-31T23:00"), "{log}");
+}
+
+#[test]
+fn test_mock_request_review_waits_for_approval() {
+    let dir = tempfile::tempdir().unwrap();
+    setup_scenario(dir.path(), "request-review.sh");
+
+    cryo_bin()
+        .args(["start", "--agent", "mock"])
+        .env("CRYO_NO_SERVICE", "1")
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    assert!(
+        wait_for_log_content(
+            dir.path(),
+            "review requested: deploy",
+            Duration::from_secs(15)
+        ),
+        "Agent should request a review"
+    );
+    // The request is announced in the outbox and the agent stays asleep
+    assert!(wait_for_log_content(
+        dir.path(),
+        "waiting for an inbox message",
+        Duration::from_secs(10)
+    ));
+    cryo_bin()
+        .args(["review", "list"])
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Deploy to prod?"));
+
+    cryo_bin()
+        .args(["review", "approve", "deploy", "--note", "go ahead"])
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    assert!(
+        wait_for_log_content(dir.path(), "plan complete", Duration::from_secs(15)),
+        "Approval should wake the agent"
+    );
+    let review: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string(dir.path().join("messages/reviews/deploy.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(review["status"], "approved");
+
+    cancel_and_wait(dir.path());
+}
+
 #[test]
 fn test_mock_multi_session_lifecycle() {
     let dir = tempfile::tempdir().unwrap();
@@ -222,10 +451,16 @@ fn test_mock_ipc_all_commands() {
     );
     assert!(log.contains("reply:"), "Missing reply in log: {log}");
     assert!(log.contains("alert:"), "Missing alert in log: {log}");
+    assert!(
+        log.contains("attach-log: \"unit tests\" (24 bytes)\n  | running 2 tests\n  | test ok\n"),
+        "Missing attached block in log: {log}"
+    );
     assert!(
         log.contains("plan complete"),
         "Missing plan complete: {log}"
     );
+    let ping = fs::read_to_string(dir.path().join(".mock-ping")).unwrap();
+    assert!(ping.contains("session 1"), "Unexpected ping reply: {ping}");
 
     // Verify outbox message was written
     let outbox = dir.path().join("messages/outbox");
@@ -402,6 +637,39 @@ fn test_mock_orphan_child() {
     );
 }
 
+#[test]
+fn test_mock_timeout_kills_grandchild() {
+    let dir = tempfile::tempdir().unwrap();
+    setup_scenario(dir.path(), "grandchild-then-hang.sh");
+
+    cryo_bin()
+        .args(["start", "--agent", "mock", "--max-session-duration", "3"])
+        .env("CRYO_NO_SERVICE", "1")
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    assert!(
+        wait_for_log_content(dir.path(), "agent killed", Duration::from_secs(15)),
+        "Session should time out"
+    );
+    let pid: u32 = fs::read_to_string(dir.path().join(".grandchild-pid"))
+        .unwrap()
+        .trim()
+        .parse()
+        .unwrap();
+    cancel_and_wait(dir.path());
+    // Reparented to init once the agent died; give it a moment to be reaped
+    let deadline = std::time::Instant::now() + Duration::from_secs(5);
+    while cryochamber::process::is_alive(pid) && std::time::Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(100));
+    }
+    assert!(
+        !cryochamber::process::is_alive(pid),
+        "grandchild {pid} should be killed with the agent's process group"
+    );
+}
+
 #[test]
 fn test_mock_hibernate_then_crash() {
     let dir = tempfile::tempdir().unwrap();
@@ -653,6 +921,117 @@ MOCK_VAR = "hello"
     assert_eq!(content.trim(), "hello", "MOCK_VAR should be injected");
 }
 
+#[test]
+fn test_agent_env_reports_provider() {
+    let dir = tempfile::tempdir().unwrap();
+    setup_scenario(dir.path(), "env-inspect.sh");
+    let config = r#"agent = "mock"
+
+[[providers]]
+name = "test-provider"
+[providers.env]
+MOCK_VAR = "hello"
+"#;
+    fs::write(dir.path().join("cryo.toml"), config).unwrap();
+
+    cryo_bin()
+        .args(["start", "--once", "--agent", "mock"])
+        .current_dir(dir.path())
+        .timeout(Duration::from_secs(30))
+        .assert()
+        .success();
+
+    let redacted = fs::read_to_string(dir.path().join(".env-redacted")).unwrap();
+    assert_eq!(redacted, "provider: test-provider\nMOCK_VAR=***\n");
+    let values = fs::read_to_string(dir.path().join(".env-values")).unwrap();
+    assert_eq!(values, "provider: test-provider\nMOCK_VAR=hello\n");
+}
+
+#[test]
+fn test_rotate_provider_switches_next_session() {
+    let dir = tempfile::tempdir().unwrap();
+    setup_scenario(dir.path(), "rotate-provider.sh");
+    let config = r#"agent = "mock"
+
+[[providers]]
+name = "first"
+
+[[providers]]
+name = "second"
+"#;
+    fs::write(dir.path().join("cryo.toml"), config).unwrap();
+    let run_once = || {
+        cryo_bin()
+            .args(["start", "--once", "--agent", "mock"])
+            .current_dir(dir.path())
+            .timeout(Duration::from_secs(30))
+            .assert()
+            .success();
+    };
+
+    run_once();
+    let log = fs::read_to_string(dir.path().join("cryo.log")).unwrap();
+    assert!(
+        log.contains("rotate provider requested: \"rate limited\" (first -> second)"),
+        "{log}"
+    );
+    cryo_bin()
+        .args(["status"])
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(
+            "rotation pending: next session uses second (\"rate limited\")",
+        ));
+
+    run_once();
+    let env = fs::read_to_string(dir.path().join(".env-session2")).unwrap();
+    assert_eq!(env, "provider: second\n");
+    let state = fs::read_to_string(dir.path().join("timer.json")).unwrap();
+    assert!(!state.contains("provider_rotation"), "{state}");
+}
+
+#[test]
+fn test_plan_front_matter_sets_cadence_and_daily_cap() {
+    let dir = tempfile::tempdir().unwrap();
+    setup_scenario(dir.path(), "bare-hibernate.sh");
+    let plan = "---\nwake_cadence: 2h\nmax_sessions_per_day: 1\nwake_cadense: 1h\n---\n\
+                # Test Plan\nDo mock things.\n";
+    fs::write(dir.path().join("plan.md"), plan).unwrap();
+    let run_once = || {
+        cryo_bin()
+            .args(["start", "--once", "--agent", "mock"])
+            .current_dir(dir.path())
+            .timeout(Duration::from_secs(30))
+            .assert()
+            .success()
+    };
+
+    let before = chrono::Local::now().naive_local();
+    run_once().stderr(predicates::str::contains(
+        "Warning: plan.md: line 4: unknown key `wake_cadense` is ignored",
+    ));
+    let log = fs::read_to_string(dir.path().join("cryo.log")).unwrap();
+    let wake = log
+        .split("hibernate: wake=")
+        .nth(1)
+        .and_then(|rest| rest.get(..16))
+        .unwrap_or_else(|| panic!("no wake time in log:\n{log}"));
+    let wake = chrono::NaiveDateTime::parse_from_str(wake, "%Y-%m-%dT%H:%M").unwrap();
+    let gap = wake - before;
+    assert!(
+        gap > chrono::Duration::minutes(118) && gap <= chrono::Duration::hours(2),
+        "bare hibernate should sleep for wake_cadence, woke at {wake}"
+    );
+
+    // The second session of the day is held until midnight
+    run_once().stdout(predicates::str::contains(
+        "No session started: max_sessions_per_day (1) reached",
+    ));
+    let log = fs::read_to_string(dir.path().join("cryo.log")).unwrap();
+    assert_eq!(log.matches("agent started").count(), 1, "{log}");
+}
+
 // --- Fallback, delayed wake, and periodic report tests ---
 
 #[test]
@@ -818,6 +1197,50 @@ fn test_fallback_cancelled_on_success() {
     }
 }
 
+#[test]
+fn test_fallback_from_failed_session_cleared_by_recovery() {
+    let dir = tempfile::tempdir().unwrap();
+    setup_scenario(dir.path(), "alert-crash-then-succeed.sh");
+
+    let config = fs::read_to_string(dir.path().join("cryo.toml")).unwrap();
+    let config = format!("{config}\nfallback_alert = \"outbox\"\n");
+    fs::write(dir.path().join("cryo.toml"), config).unwrap();
+
+    cryo_bin()
+        .args(["start", "--agent", "mock"])
+        .env("CRYO_NO_SERVICE", "1")
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    // Attempt 1 registers an alert and crashes; attempt 2 completes the plan.
+    assert!(
+        wait_for_daemon_exit(dir.path(), Duration::from_secs(30)),
+        "Daemon should exit after the retry completes the plan"
+    );
+
+    let log = fs::read_to_string(dir.path().join("cryo.log")).unwrap();
+    assert!(log.contains("alert: notify -> operator"), "{log}");
+    assert!(log.contains("plan complete"), "{log}");
+    assert!(
+        !log.contains("executing fallback action"),
+        "Stale fallback must not fire: {log}"
+    );
+
+    let outbox = dir.path().join("messages/outbox");
+    if outbox.exists() {
+        for entry in fs::read_dir(&outbox).unwrap().filter_map(|e| e.ok()) {
+            if entry.path().is_file() {
+                let content = fs::read_to_string(entry.path()).unwrap();
+                assert!(
+                    !content.contains("fallback"),
+                    "Outbox should not contain a fallback alert: {content}"
+                );
+            }
+        }
+    }
+}
+
 #[test]
 fn test_delayed_wake_detection() {
     let dir = tempfile::tempdir().unwrap();
@@ -943,20 +1366,1311 @@ fn test_invalid_report_time_warns() {
     );
 }
 
-// --- Inbox wake tests ---
-
-/// Write an inbox message file directly (simulates what cryo-zulip sync does).
-fn write_inbox_message(dir: &std::path::Path, filename: &str, body: &str) {
-    let inbox = dir.join("messages").join("inbox");
-    fs::create_dir_all(&inbox).unwrap();
-    let content = format!(
-        "---\nfrom: test-user\nsubject: test\n---\n{body}"
-    );
-    fs::write(inbox.join(filename), content).unwrap();
-}
+// --- Single-session (--once) tests ---
 
 #[test]
-fn test_inbox_wake_coalesces_multiple_events() {
+fn test_start_once_runs_sessions_in_foreground() {
+    let dir = tempfile::tempdir().unwrap();
+    setup_scenario(dir.path(), "until-message.sh");
+
+    // First run: agent hibernates; the process exits 0 with no daemon left behind
+    cryo_bin()
+        .args(["start", "--once", "--agent", "mock"])
+        .current_dir(dir.path())
+        .timeout(Duration::from_secs(30))
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("waiting for a message"));
+    let state: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(dir.path().join("timer.json")).unwrap()).unwrap();
+    assert!(state["pid"].is_null(), "state: {state}");
+    assert_eq!(state["session_number"], 1);
+    assert_eq!(state["last_outcome"], "success");
+    assert_eq!(state["last_summary"], "Waiting for human");
+
+    // Second run continues the session count and completes the plan
+    cryo_bin()
+        .args(["start", "--once", "--agent", "mock"])
+        .current_dir(dir.path())
+        .timeout(Duration::from_secs(30))
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("plan complete"));
+    let log = fs::read_to_string(dir.path().join("cryo.log")).unwrap();
+    assert!(log.contains("--- CRYO SESSION 2"), "{log}");
+}
+
+#[test]
+fn test_start_resume_and_fresh() {
+    let dir = tempfile::tempdir().unwrap();
+    setup_scenario(dir.path(), "until-message.sh");
+    cryo_bin()
+        .args(["start", "--agent", "mock"])
+        .env("CRYO_NO_SERVICE", "1")
+        .current_dir(dir.path())
+        .assert()
+        .success();
+    assert!(
+        wait_for_log_content(
+            dir.path(),
+            "waiting for an inbox message",
+            Duration::from_secs(15)
+        ),
+        "Session 1 should hibernate until a message"
+    );
+
+    // Stop the daemon without `cryo cancel`, leaving its state behind
+    let state: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(dir.path().join("timer.json")).unwrap()).unwrap();
+    let pid = state["pid"].as_u64().unwrap().to_string();
+    std::process::Command::new("kill")
+        .arg(&pid)
+        .status()
+        .unwrap();
+    assert!(wait_for_daemon_exit(dir.path(), Duration::from_secs(10)));
+
+    // --resume keeps the session number and the wait for a message
+    cryo_bin()
+        .args(["start", "--resume", "--agent", "mock"])
+        .env("CRYO_NO_SERVICE", "1")
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Resuming after session 1"));
+    // Give the daemon time to run a session, if it (wrongly) would
+    std::thread::sleep(Duration::from_secs(2));
+    let log = fs::read_to_string(dir.path().join("cryo.log")).unwrap();
+    assert!(!log.contains("--- CRYO SESSION 2"), "{log}");
+
+    write_inbox_message(dir.path(), "msg.md", "carry on");
+    assert!(
+        wait_for_daemon_exit(dir.path(), Duration::from_secs(20)),
+        "Resumed daemon should wake on the message and complete"
+    );
+    let log = fs::read_to_string(dir.path().join("cryo.log")).unwrap();
+    assert!(log.contains("--- CRYO SESSION 2"), "{log}");
+
+    // --fresh discards the previous run
+    cryo_bin()
+        .args(["start", "--once", "--fresh", "--agent", "mock"])
+        .current_dir(dir.path())
+        .timeout(Duration::from_secs(30))
+        .assert()
+        .success();
+    let log = fs::read_to_string(dir.path().join("cryo.log")).unwrap();
+    assert_eq!(log.matches("--- CRYO SESSION 1").count(), 2, "{log}");
+    assert!(!log.contains("--- CRYO SESSION 3"), "{log}");
+}
+
+#[test]
+fn test_status_shows_stop_reason() {
+    let dir = tempfile::tempdir().unwrap();
+    setup_scenario(dir.path(), "until-message.sh");
+    cryo_bin()
+        .args(["start", "--agent", "mock"])
+        .env("CRYO_NO_SERVICE", "1")
+        .current_dir(dir.path())
+        .assert()
+        .success();
+    assert!(
+        wait_for_log_content(
+            dir.path(),
+            "waiting for an inbox message",
+            Duration::from_secs(15)
+        ),
+        "Session 1 should hibernate until a message"
+    );
+
+    // SIGTERM is a shutdown request
+    let state: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(dir.path().join("timer.json")).unwrap()).unwrap();
+    let pid = state["pid"].as_u64().unwrap().to_string();
+    std::process::Command::new("kill")
+        .arg(&pid)
+        .status()
+        .unwrap();
+    assert!(wait_for_daemon_exit(dir.path(), Duration::from_secs(10)));
+    cryo_bin()
+        .arg("status")
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Daemon: stopped (cancelled at "));
+
+    // A resumed daemon clears the reason, then records completion
+    cryo_bin()
+        .args(["start", "--resume", "--agent", "mock"])
+        .env("CRYO_NO_SERVICE", "1")
+        .current_dir(dir.path())
+        .assert()
+        .success();
+    std::thread::sleep(Duration::from_secs(1));
+    cryo_bin()
+        .arg("status")
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Daemon: running"));
+    write_inbox_message(dir.path(), "msg.md", "carry on");
+    assert!(
+        wait_for_daemon_exit(dir.path(), Duration::from_secs(20)),
+        "Resumed daemon should wake on the message and complete"
+    );
+    cryo_bin()
+        .arg("status")
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Daemon: stopped (completed at "));
+    let log = fs::read_to_string(dir.path().join("cryo.log")).unwrap();
+    assert!(log.contains("Daemon: stopped (completed)"), "{log}");
+}
+
+#[test]
+fn test_multiple_plans_complete_one_by_one() {
+    let dir = tempfile::tempdir().unwrap();
+    setup_scenario(dir.path(), "multi-plan.sh");
+    fs::create_dir_all(dir.path().join("plans")).unwrap();
+    fs::write(dir.path().join("plans/alpha.md"), "# Alpha\n- step 1\n").unwrap();
+    fs::write(dir.path().join("plans/beta.md"), "# Beta\n- step 1\n").unwrap();
+
+    cryo_bin()
+        .args(["start", "--agent", "mock"])
+        .env("CRYO_NO_SERVICE", "1")
+        .current_dir(dir.path())
+        .assert()
+        .success();
+    assert!(
+        wait_for_daemon_exit(dir.path(), Duration::from_secs(30)),
+        "Daemon should stop once every plan is complete"
+    );
+
+    let log = fs::read_to_string(dir.path().join("cryo.log")).unwrap();
+    assert_eq!(log.matches("] focus plan: alpha").count(), 2, "{log}");
+    assert_eq!(log.matches("] focus plan: beta").count(), 1, "{log}");
+    assert!(log.contains("task: Alpha step 2"), "{log}");
+    assert!(
+        log.contains("task: Execute the first task from plans/beta.md"),
+        "{log}"
+    );
+    assert!(
+        log.contains("plan plans/alpha.md complete, 1 of 2 plans left"),
+        "{log}"
+    );
+    assert!(!log.contains("--- CRYO SESSION 4"), "{log}");
+
+    let prompt = fs::read_to_string(dir.path().join(".mock-prompt-3")).unwrap();
+    assert!(
+        prompt.contains("This session works on plans/beta.md. Other plans: alpha (complete)."),
+        "{prompt}"
+    );
+    cryo_bin()
+        .arg("status")
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Plans: 2 of 2 complete"))
+        .stdout(predicates::str::contains("Daemon: stopped (completed at "));
+}
+
+#[test]
+fn test_schedule_survives_until_last_plan_completes() {
+    let dir = tempfile::tempdir().unwrap();
+    setup_scenario(dir.path(), "multi-plan-every.sh");
+    fs::create_dir_all(dir.path().join("plans")).unwrap();
+    fs::write(dir.path().join("plans/alpha.md"), "# Alpha\n- step 1\n").unwrap();
+    fs::write(dir.path().join("plans/beta.md"), "# Beta\n- step 1\n").unwrap();
+    let run_once = || {
+        cryo_bin()
+            .args(["start", "--once", "--agent", "mock"])
+            .current_dir(dir.path())
+            .timeout(Duration::from_secs(30))
+            .assert()
+            .success();
+    };
+    let schedule = || {
+        cryochamber::state::load_state(&cryochamber::state::state_path(dir.path()))
+            .unwrap()
+            .unwrap()
+            .schedule
+    };
+
+    run_once();
+    assert_eq!(schedule().as_deref(), Some("0 9 * * *"));
+    // alpha completes, beta is still open
+    run_once();
+    assert_eq!(schedule().as_deref(), Some("0 9 * * *"));
+    run_once();
+    assert_eq!(schedule(), None);
+}
+
+#[test]
+fn test_plans_dir_edit_between_sessions_is_flagged() {
+    let dir = tempfile::tempdir().unwrap();
+    setup_scenario(dir.path(), "next-task.sh");
+    fs::create_dir_all(dir.path().join("plans")).unwrap();
+    fs::write(dir.path().join("plans/alpha.md"), "# Alpha\n- step 1\n").unwrap();
+    let run_once = || {
+        cryo_bin()
+            .args(["start", "--once", "--agent", "mock"])
+            .current_dir(dir.path())
+            .timeout(Duration::from_secs(30))
+            .assert()
+            .success();
+    };
+
+    run_once();
+    fs::write(
+        dir.path().join("plans/alpha.md"),
+        "# Alpha\n- step 1\n- step 2\n",
+    )
+    .unwrap();
+    run_once();
+
+    let log = fs::read_to_string(dir.path().join("cryo.log")).unwrap();
+    let session2 = log.find("--- CRYO SESSION 2").expect(&log);
+    assert!(log[session2..].contains("plan changed"), "{log}");
+}
+
+#[test]
+fn test_min_session_gap_defers_inbox_wake() {
+    let dir = tempfile::tempdir().unwrap();
+    setup_scenario(dir.path(), "until-message.sh");
+    let toml = fs::read_to_string(dir.path().join("cryo.toml")).unwrap();
+    fs::write(
+        dir.path().join("cryo.toml"),
+        format!("{toml}\nmin_session_gap_secs = 4\n"),
+    )
+    .unwrap();
+
+    cryo_bin()
+        .args(["start", "--agent", "mock"])
+        .env("CRYO_NO_SERVICE", "1")
+        .current_dir(dir.path())
+        .assert()
+        .success();
+    assert!(
+        wait_for_log_content(
+            dir.path(),
+            "waiting for an inbox message",
+            Duration::from_secs(15)
+        ),
+        "Session 1 should hibernate until a message"
+    );
+
+    write_inbox_message(dir.path(), "msg.md", "right away");
+    assert!(
+        wait_for_log_content(dir.path(), "deferring wake", Duration::from_secs(5)),
+        "Inbox wake inside the gap should be deferred"
+    );
+    let log = fs::read_to_string(dir.path().join("cryo.log")).unwrap();
+    assert!(!log.contains("--- CRYO SESSION 2"), "{log}");
+
+    assert!(
+        wait_for_daemon_exit(dir.path(), Duration::from_secs(20)),
+        "Deferred wake should run once the gap has passed"
+    );
+    let log = fs::read_to_string(dir.path().join("cryo.log")).unwrap();
+    assert!(log.contains("session gap elapsed"), "{log}");
+    assert!(log.contains("--- CRYO SESSION 2"), "{log}");
+}
+
+#[test]
+fn test_cryo_wake_is_not_held_by_session_gap() {
+    let dir = tempfile::tempdir().unwrap();
+    setup_scenario(dir.path(), "until-message.sh");
+    let toml = fs::read_to_string(dir.path().join("cryo.toml")).unwrap();
+    fs::write(
+        dir.path().join("cryo.toml"),
+        format!("{toml}\nmin_session_gap_secs = 60\n"),
+    )
+    .unwrap();
+
+    cryo_bin()
+        .args(["start", "--agent", "mock"])
+        .env("CRYO_NO_SERVICE", "1")
+        .current_dir(dir.path())
+        .assert()
+        .success();
+    assert!(
+        wait_for_log_content(
+            dir.path(),
+            "waiting for an inbox message",
+            Duration::from_secs(15)
+        ),
+        "Session 1 should hibernate until a message"
+    );
+
+    cryo_bin()
+        .args(["wake", "right away"])
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Wake signal sent"));
+    assert!(
+        wait_for_daemon_exit(dir.path(), Duration::from_secs(20)),
+        "cryo wake should run a session despite the gap"
+    );
+    let log = fs::read_to_string(dir.path().join("cryo.log")).unwrap();
+    assert!(log.contains("--- CRYO SESSION 2"), "{log}");
+}
+
+#[test]
+fn test_record_session_and_replay() {
+    let dir = tempfile::tempdir().unwrap();
+    setup_scenario(dir.path(), "until-message.sh");
+    let toml = fs::read_to_string(dir.path().join("cryo.toml")).unwrap();
+    fs::write(
+        dir.path().join("cryo.toml"),
+        format!("{toml}\nrecord_session = true\n"),
+    )
+    .unwrap();
+
+    cryo_bin()
+        .args(["start", "--once", "--agent", "mock"])
+        .current_dir(dir.path())
+        .timeout(Duration::from_secs(30))
+        .assert()
+        .success();
+    assert!(dir.path().join(".cryo/recordings/session-1.json").exists());
+
+    let out = cryo_bin()
+        .args(["replay"])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    assert!(out.status.success());
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("Session #1"), "{stdout}");
+    assert!(
+        stdout.contains("Argv:     sh scenario.sh '<prompt>'"),
+        "{stdout}"
+    );
+    assert!(stdout.contains("Session number: 1"), "{stdout}");
+
+    cryo_bin()
+        .args(["replay", "7"])
+        .current_dir(dir.path())
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("recorded: 1"));
+}
+
+#[test]
+fn test_next_task_carries_into_next_session() {
+    let dir = tempfile::tempdir().unwrap();
+    setup_scenario(dir.path(), "next-task.sh");
+
+    for _ in 0..2 {
+        cryo_bin()
+            .args(["start", "--once", "--agent", "mock"])
+            .current_dir(dir.path())
+            .timeout(Duration::from_secs(30))
+            .assert()
+            .success();
+    }
+
+    let log = fs::read_to_string(dir.path().join("cryo.log")).unwrap();
+    let session2 = &log[log.find("--- CRYO SESSION 2").expect(&log)..];
+    assert!(
+        session2.contains("task: Implement the REST handler"),
+        "{log}"
+    );
+}
+
+#[test]
+fn test_clean_exit_without_hibernate_modes() {
+    for (mode, expected) in [
+        ("complete", "plan complete, clean exit without hibernate"),
+        ("reschedule", "hibernate: wake="),
+    ] {
+        let dir = tempfile::tempdir().unwrap();
+        setup_scenario(dir.path(), "quick-exit.sh");
+        let toml = fs::read_to_string(dir.path().join("cryo.toml")).unwrap();
+        fs::write(
+            dir.path().join("cryo.toml"),
+            format!("{toml}\non_clean_exit_without_hibernate = \"{mode}\"\n"),
+        )
+        .unwrap();
+
+        cryo_bin()
+            .args(["start", "--once", "--agent", "mock"])
+            .current_dir(dir.path())
+            .timeout(Duration::from_secs(30))
+            .assert()
+            .success();
+        let log = fs::read_to_string(dir.path().join("cryo.log")).unwrap();
+        assert!(log.contains(expected), "{mode}: {log}");
+        assert!(log.contains("session complete"), "{mode}: {log}");
+        assert!(!log.contains("quick exit detected"), "{mode}: {log}");
+    }
+}
+
+#[test]
+fn test_first_task_then_continue() {
+    let dir = tempfile::tempdir().unwrap();
+    setup_scenario(dir.path(), "until-message.sh");
+
+    cryo_bin()
+        .args([
+            "start",
+            "--once",
+            "--agent",
+            "mock",
+            "--task",
+            "Read plan.md",
+        ])
+        .current_dir(dir.path())
+        .timeout(Duration::from_secs(30))
+        .assert()
+        .success();
+    cryo_bin()
+        .args(["start", "--once", "--agent", "mock"])
+        .current_dir(dir.path())
+        .timeout(Duration::from_secs(30))
+        .assert()
+        .success();
+
+    let log = fs::read_to_string(dir.path().join("cryo.log")).unwrap();
+    let session2 = log.find("--- CRYO SESSION 2").expect(&log);
+    assert!(log[..session2].contains("task: Read plan.md"), "{log}");
+    assert!(log[session2..].contains("task: Continue the plan"), "{log}");
+}
+
+#[test]
+fn test_fresh_start_uses_first_task_despite_old_log() {
+    let dir = tempfile::tempdir().unwrap();
+    setup_scenario(dir.path(), "next-task.sh");
+    cryo_bin()
+        .args(["start", "--once", "--agent", "mock"])
+        .current_dir(dir.path())
+        .timeout(Duration::from_secs(30))
+        .assert()
+        .success();
+    let log = fs::read_to_string(dir.path().join("cryo.log")).unwrap();
+    assert!(log.contains("Implement the REST handler"), "{log}");
+
+    // cryo.log still holds the old run's next task
+    cryo_bin()
+        .args(["start", "--once", "--fresh", "--agent", "mock"])
+        .args(["--task", "Start over from the top"])
+        .current_dir(dir.path())
+        .timeout(Duration::from_secs(30))
+        .assert()
+        .success();
+    let log = fs::read_to_string(dir.path().join("cryo.log")).unwrap();
+    let last = log.rfind("--- CRYO SESSION 1").expect(&log);
+    assert!(
+        log[last..].contains("task: Start over from the top"),
+        "{log}"
+    );
+}
+
+#[test]
+fn test_plan_edit_between_sessions_is_flagged() {
+    let dir = tempfile::tempdir().unwrap();
+    setup_scenario(dir.path(), "next-task.sh");
+    let run_once = || {
+        cryo_bin()
+            .args(["start", "--once", "--agent", "mock"])
+            .current_dir(dir.path())
+            .timeout(Duration::from_secs(30))
+            .assert()
+            .success();
+    };
+
+    run_once();
+    let plan = fs::read_to_string(dir.path().join("plan.md")).unwrap();
+    fs::write(
+        dir.path().join("plan.md"),
+        format!("{plan}\n- New task: update the changelog\n"),
+    )
+    .unwrap();
+    run_once();
+    run_once();
+
+    let log = fs::read_to_string(dir.path().join("cryo.log")).unwrap();
+    assert_eq!(log.matches("plan changed").count(), 1, "{log}");
+    let session2 = &log[log.find("--- CRYO SESSION 2").expect(&log)..];
+    let session3 = log.find("--- CRYO SESSION 3").expect(&log);
+    assert!(session2.contains("plan changed"));
+    assert!(!log[session3..].contains("plan changed"));
+}
+
+#[test]
+fn test_checkpoint_carries_into_next_session() {
+    let dir = tempfile::tempdir().unwrap();
+    setup_scenario(dir.path(), "checkpoint.sh");
+
+    for _ in 0..2 {
+        cryo_bin()
+            .args(["start", "--once", "--agent", "mock"])
+            .current_dir(dir.path())
+            .timeout(Duration::from_secs(30))
+            .assert()
+            .success();
+    }
+
+    assert_eq!(
+        fs::read_to_string(dir.path().join(".cryo/checkpoint.json")).unwrap(),
+        r#"{"low":100,"high":200}"#
+    );
+    let log = fs::read_to_string(dir.path().join("cryo.log")).unwrap();
+    assert!(
+        log.contains(r#"checkpoint: {"low":100,"high":200}"#),
+        "{log}"
+    );
+    assert!(
+        log.contains(r#"summary="Resumed from {"low":100,"high":200}""#),
+        "{log}"
+    );
+
+    // A fresh start drops the old run's checkpoint
+    cryo_bin()
+        .args(["start", "--once", "--fresh", "--agent", "mock"])
+        .current_dir(dir.path())
+        .timeout(Duration::from_secs(30))
+        .assert()
+        .success();
+    let log = fs::read_to_string(dir.path().join("cryo.log")).unwrap();
+    assert!(log.ends_with("--- CRYO END ---\n"));
+    let last = &log[log.rfind("--- CRYO SESSION").unwrap()..];
+    assert!(last.contains("summary=\"Range saved\""), "{log}");
+}
+
+#[test]
+fn test_agent_finds_socket_from_subdirectory() {
+    let dir = tempfile::tempdir().unwrap();
+    setup_scenario(dir.path(), "subdir-agent.sh");
+
+    cryo_bin()
+        .args(["start", "--once", "--agent", "mock"])
+        .current_dir(dir.path())
+        .timeout(Duration::from_secs(30))
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("plan complete"));
+    let log = fs::read_to_string(dir.path().join("cryo.log")).unwrap();
+    assert!(
+        log.contains("note: \"Working from a subdirectory\""),
+        "{log}"
+    );
+}
+
+#[test]
+fn test_inbox_disposition_flag() {
+    let dir = tempfile::tempdir().unwrap();
+    setup_scenario(dir.path(), "inbox-wake.sh");
+    let mut config = fs::read_to_string(dir.path().join("cryo.toml")).unwrap();
+    config.push_str("\ninbox_disposition = \"flag\"\n");
+    fs::write(dir.path().join("cryo.toml"), config).unwrap();
+    write_inbox_message(dir.path(), "msg.md", "please handle");
+
+    cryo_bin()
+        .args(["start", "--once", "--agent", "mock"])
+        .current_dir(dir.path())
+        .timeout(Duration::from_secs(30))
+        .assert()
+        .success();
+
+    let inbox = dir.path().join("messages/inbox");
+    assert!(inbox.join("msg.md.consumed").exists());
+    assert!(!inbox.join("msg.md").exists());
+    assert!(!inbox.join("archive/msg.md").exists());
+}
+
+#[test]
+fn test_runtime_dir_moves_socket_out_of_project() {
+    let dir = tempfile::tempdir().unwrap();
+    let runtime = tempfile::tempdir().unwrap();
+    setup_scenario(dir.path(), "inbox-wake.sh");
+    let mut config = fs::read_to_string(dir.path().join("cryo.toml")).unwrap();
+    config.push_str(&format!(
+        "\nruntime_dir = \"{}\"\n",
+        runtime.path().display()
+    ));
+    fs::write(dir.path().join("cryo.toml"), config).unwrap();
+
+    cryo_bin()
+        .args(["start", "--once", "--agent", "mock"])
+        .current_dir(dir.path())
+        .timeout(Duration::from_secs(30))
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("plan complete"));
+
+    let log = fs::read_to_string(dir.path().join("cryo.log")).unwrap();
+    assert!(log.contains("note: \"Inbox wake session\""), "{log}");
+    assert!(!dir.path().join(".cryo").exists());
+    assert_eq!(fs::read_dir(runtime.path()).unwrap().count(), 1);
+}
+
+#[test]
+fn test_complete_with_result_saves_artifact() {
+    let dir = tempfile::tempdir().unwrap();
+    setup_scenario(dir.path(), "complete-with-result.sh");
+
+    cryo_bin()
+        .args(["start", "--once", "--agent", "mock"])
+        .current_dir(dir.path())
+        .timeout(Duration::from_secs(30))
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("plan complete"));
+
+    let result: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(dir.path().join(".cryo/result.json")).unwrap())
+            .unwrap();
+    assert_eq!(result["status"], "ok");
+
+    cryo_bin()
+        .args(["status"])
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Result file: .cryo/result.json"));
+}
+
+/// Answer `count` HTTP requests with 200 and return their bodies in order.
+fn serve_webhooks(count: usize) -> (String, std::thread::JoinHandle<Vec<serde_json::Value>>) {
+    use std::io::{BufRead, Read, Write};
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/hook", listener.local_addr().unwrap());
+    let handle = std::thread::spawn(move || {
+        let mut bodies = Vec::new();
+        for stream in listener.incoming().take(count) {
+            let mut reader = std::io::BufReader::new(stream.unwrap());
+            let mut length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line.trim().is_empty() {
+                    break;
+                }
+                if let Some((name, value)) = line.split_once(':') {
+                    if name.eq_ignore_ascii_case("content-length") {
+                        length = value.trim().parse().unwrap();
+                    }
+                }
+            }
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body).unwrap();
+            reader
+                .get_mut()
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n")
+                .unwrap();
+            bodies.push(serde_json::from_slice(&body).unwrap());
+        }
+        bodies
+    });
+    (url, handle)
+}
+
+#[test]
+fn test_session_webhooks_post_start_and_end() {
+    let dir = tempfile::tempdir().unwrap();
+    setup_scenario(dir.path(), "complete-with-result.sh");
+    let (url, server) = serve_webhooks(2);
+    let toml = fs::read_to_string(dir.path().join("cryo.toml")).unwrap();
+    fs::write(
+        dir.path().join("cryo.toml"),
+        format!(
+            "{toml}\nno_proxy = [\"127.0.0.1\"]\n\
+             session_start_webhook = \"{url}\"\nsession_end_webhook = \"{url}\"\n"
+        ),
+    )
+    .unwrap();
+
+    cryo_bin()
+        .args(["start", "--once", "--agent", "mock"])
+        .current_dir(dir.path())
+        .timeout(Duration::from_secs(30))
+        .assert()
+        .success();
+
+    let events = server.join().unwrap();
+    assert_eq!(events[0]["event"], "session_start");
+    assert_eq!(events[0]["session"], 1);
+    assert!(events[0]["task"].is_string(), "{events:?}");
+    assert_eq!(events[1]["event"], "session_end");
+    assert_eq!(events[1]["session"], 1);
+    assert_eq!(events[1]["outcome"], "success");
+    assert_eq!(events[1]["summary"], "Done with result");
+    assert!(events[1]["duration_secs"].is_u64(), "{events:?}");
+}
+
+#[test]
+fn test_session_webhook_failure_is_not_fatal() {
+    let dir = tempfile::tempdir().unwrap();
+    setup_scenario(dir.path(), "complete-with-result.sh");
+    // Nothing listens on a port freed right after binding
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let toml = fs::read_to_string(dir.path().join("cryo.toml")).unwrap();
+    fs::write(
+        dir.path().join("cryo.toml"),
+        format!(
+            "{toml}\nno_proxy = [\"127.0.0.1\"]\n\
+             session_start_webhook = \"http://127.0.0.1:{port}/hook\"\n"
+        ),
+    )
+    .unwrap();
+
+    cryo_bin()
+        .args(["start", "--once", "--agent", "mock"])
+        .current_dir(dir.path())
+        .timeout(Duration::from_secs(30))
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("plan complete"))
+        .stderr(predicates::str::contains("session webhook failed"));
+}
+
+#[test]
+fn test_require_summary_rejects_bare_hibernate() {
+    let dir = tempfile::tempdir().unwrap();
+    setup_scenario(dir.path(), "summary-required.sh");
+    let toml = fs::read_to_string(dir.path().join("cryo.toml")).unwrap();
+    fs::write(
+        dir.path().join("cryo.toml"),
+        format!("{toml}\nrequire_summary = true\n"),
+    )
+    .unwrap();
+
+    cryo_bin()
+        .args(["start", "--once", "--agent", "mock"])
+        .current_dir(dir.path())
+        .timeout(Duration::from_secs(30))
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("plan complete"));
+
+    let log = fs::read_to_string(dir.path().join("cryo.log")).unwrap();
+    assert!(log.contains("hibernate rejected: no summary"), "{log}");
+    assert!(log.contains("final report sent to outbox"), "{log}");
+    let agent_log = fs::read_to_string(dir.path().join("cryo-agent.log")).unwrap();
+    assert!(agent_log.contains("A summary is required"), "{agent_log}");
+    assert!(!agent_log.contains("bare hibernate was accepted"));
+
+    let outbox: Vec<String> = fs::read_dir(dir.path().join("messages/outbox"))
+        .unwrap()
+        .filter_map(|e| fs::read_to_string(e.unwrap().path()).ok())
+        .collect();
+    assert!(
+        outbox
+            .iter()
+            .any(|m| m.contains("report: final") && m.contains("Shipped the release notes")),
+        "{outbox:?}"
+    );
+}
+
+#[test]
+fn test_clean_keep_session_number_continues_numbering() {
+    let dir = tempfile::tempdir().unwrap();
+    setup_scenario(dir.path(), "complete-with-result.sh");
+    let run_once = || {
+        cryo_bin()
+            .args(["start", "--once", "--agent", "mock"])
+            .current_dir(dir.path())
+            .timeout(Duration::from_secs(30))
+            .assert()
+            .success();
+    };
+
+    run_once();
+    cryo_bin()
+        .args(["clean", "--force", "--keep-session-number"])
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Kept session number 1"));
+    assert!(!dir.path().join("cryo.log").exists());
+    assert_eq!(
+        fs::read_to_string(dir.path().join(".cryo/session-counter")).unwrap(),
+        "1\n"
+    );
+
+    run_once();
+    let log = fs::read_to_string(dir.path().join("cryo.log")).unwrap();
+    assert!(log.contains("--- CRYO SESSION 2 |"), "{log}");
+    // timer.json carries the number from here on
+    assert!(!dir.path().join(".cryo/session-counter").exists());
+
+    // Without the flag, numbering starts over
+    cryo_bin()
+        .args(["clean", "--force"])
+        .current_dir(dir.path())
+        .assert()
+        .success();
+    run_once();
+    let log = fs::read_to_string(dir.path().join("cryo.log")).unwrap();
+    assert!(log.contains("--- CRYO SESSION 1 |"), "{log}");
+}
+
+#[test]
+fn test_abort_ends_session_without_failure() {
+    let dir = tempfile::tempdir().unwrap();
+    setup_scenario(dir.path(), "abort.sh");
+
+    cryo_bin()
+        .args(["start", "--once", "--agent", "mock"])
+        .current_dir(dir.path())
+        .timeout(Duration::from_secs(30))
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Session aborted"));
+
+    let state: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(dir.path().join("timer.json")).unwrap()).unwrap();
+    assert_eq!(state["last_outcome"], "aborted", "state: {state}");
+    assert_eq!(state["last_summary"], "wrong branch checked out");
+    assert_eq!(state["retry_count"], 0, "state: {state}");
+
+    let log = fs::read_to_string(dir.path().join("cryo.log")).unwrap();
+    assert!(log.contains("abort: \"wrong branch checked out\""), "{log}");
+    assert!(log.contains("session aborted"), "{log}");
+    assert!(!log.contains("without hibernate"), "{log}");
+}
+
+#[test]
+fn test_abort_keeps_the_stored_schedule() {
+    let dir = tempfile::tempdir().unwrap();
+    setup_scenario(dir.path(), "abort.sh");
+    let state = cryochamber::state::CryoState {
+        schedule: Some("0 9 * * 1-5".to_string()),
+        ..Default::default()
+    };
+    let handle = spawn_embedded_with(dir.path(), state);
+    assert!(
+        wait_for_log_content(dir.path(), "CRYO END", Duration::from_secs(10)),
+        "Session should end"
+    );
+    std::thread::sleep(Duration::from_millis(500));
+    let state = handle.status().unwrap().unwrap();
+    handle.cancel().unwrap();
+
+    assert!(!state.awaiting_message, "{state:?}");
+    let wake = state.next_wake.expect("abort should re-arm the schedule");
+    let wake = chrono::NaiveDateTime::parse_from_str(&wake, "%Y-%m-%dT%H:%M").unwrap();
+    assert_eq!(wake.format("%H:%M").to_string(), "09:00");
+    assert!(
+        chrono::Datelike::weekday(&wake).number_from_monday() <= 5,
+        "{wake}"
+    );
+    assert!(wake > chrono::Local::now().naive_local(), "{wake}");
+}
+
+#[test]
+fn test_start_once_setup_failure_is_not_a_failed_session() {
+    let dir = tempfile::tempdir().unwrap();
+    setup_scenario(dir.path(), "crash.sh");
+    // The socket lives in .cryo/, which can't be created over a file
+    fs::write(dir.path().join(".cryo"), "not a directory").unwrap();
+
+    cryo_bin()
+        .args(["start", "--once", "--agent", "mock"])
+        .current_dir(dir.path())
+        .timeout(Duration::from_secs(30))
+        .assert()
+        .code(6)
+        .stderr(predicates::str::contains("Could not start a session"));
+
+    let log = fs::read_to_string(dir.path().join("cryo.log")).unwrap_or_default();
+    assert!(!log.contains("CRYO SESSION"), "{log}");
+}
+
+#[test]
+fn test_start_once_fails_on_crash() {
+    let dir = tempfile::tempdir().unwrap();
+    setup_scenario(dir.path(), "crash.sh");
+
+    cryo_bin()
+        .args(["start", "--once", "--agent", "mock"])
+        .current_dir(dir.path())
+        .timeout(Duration::from_secs(30))
+        .assert()
+        .code(4)
+        .stderr(predicates::str::contains("Session failed"));
+
+    let log = fs::read_to_string(dir.path().join("cryo.log")).unwrap();
+    assert_eq!(
+        log.matches("CRYO SESSION").count(),
+        1,
+        "--once must not retry: {log}"
+    );
+    let state: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(dir.path().join("timer.json")).unwrap()).unwrap();
+    assert_eq!(state["last_outcome"], "failed", "state: {state}");
+}
+
+// --- Log level tests ---
+
+/// Run a one-session scenario with the given `log_level` and return cryo.log.
+fn run_with_log_level(level: &str) -> String {
+    let dir = tempfile::tempdir().unwrap();
+    setup_scenario(dir.path(), "inbox-wake.sh");
+    let mut config = fs::read_to_string(dir.path().join("cryo.toml")).unwrap();
+    config.push_str(&format!("\nlog_level = \"{level}\"\n"));
+    fs::write(dir.path().join("cryo.toml"), config).unwrap();
+
+    cryo_bin()
+        .args(["start", "--agent", "mock", "--max-session-duration", "30"])
+        .env("CRYO_NO_SERVICE", "1")
+        .current_dir(dir.path())
+        .assert()
+        .success();
+    assert!(
+        wait_for_daemon_exit(dir.path(), Duration::from_secs(30)),
+        "Daemon should exit after plan completion"
+    );
+    fs::read_to_string(dir.path().join("cryo.log")).unwrap()
+}
+
+#[test]
+fn test_log_level_quiet_hides_chatter() {
+    let log = run_with_log_level("quiet");
+    assert!(log.contains("plan complete"), "{log}");
+    assert!(
+        log.contains("Running agent"),
+        "session boundaries kept: {log}"
+    );
+    assert!(!log.contains("Daemon: socket listening"), "{log}");
+    assert!(!log.contains("Daemon: socket request"), "{log}");
+    assert!(log.contains("Daemon: stopped (completed)"), "{log}");
+    assert!(!log.contains("Daemon: exited cleanly"), "{log}");
+}
+
+#[test]
+fn test_log_level_verbose_logs_requests() {
+    let log = run_with_log_level("verbose");
+    assert!(log.contains("Daemon: socket listening"), "{log}");
+    assert!(log.contains("Daemon: socket request: note\n"), "{log}");
+    assert!(log.contains("Daemon: socket request: hibernate\n"), "{log}");
+    // Only the kind is logged, never the payload
+    assert!(!log.contains("request: Note {"), "{log}");
+}
+
+#[test]
+fn test_watch_dirs_wake_on_dropped_file() {
+    let dir = tempfile::tempdir().unwrap();
+    setup_scenario(dir.path(), "until-message.sh");
+    let mut config = fs::read_to_string(dir.path().join("cryo.toml")).unwrap();
+    config.push_str("\nwatch_dirs = [\"drops\", \"missing\"]\n");
+    fs::write(dir.path().join("cryo.toml"), config).unwrap();
+    fs::create_dir_all(dir.path().join("drops")).unwrap();
+
+    cryo_bin()
+        .args(["start", "--agent", "mock", "--max-session-duration", "30"])
+        .env("CRYO_NO_SERVICE", "1")
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    // Wait for the first session to hibernate until a message arrives
+    assert!(wait_for_log_content(
+        dir.path(),
+        "waiting for an inbox message",
+        Duration::from_secs(15)
+    ));
+
+    fs::write(dir.path().join("drops/alert.txt"), "wake up").unwrap();
+
+    assert!(
+        wait_for_log_content(dir.path(), "plan complete", Duration::from_secs(30)),
+        "Dropped file should wake the agent"
+    );
+    wait_for_daemon_exit(dir.path(), Duration::from_secs(5));
+    let log = fs::read_to_string(dir.path().join("cryo.log")).unwrap();
+    assert!(log.contains("missing does not exist, skipping"), "{log}");
+    assert!(!dir.path().join("drops/alert.txt").exists());
+    let archive = cryochamber::message::read_inbox_archive(dir.path()).unwrap();
+    assert!(
+        archive.iter().any(|(_, m)| m.body == "wake up"),
+        "{archive:?}"
+    );
+}
+
+#[test]
+fn test_plan_deleted_mid_run_skips_session() {
+    let dir = tempfile::tempdir().unwrap();
+    setup_scenario(dir.path(), "until-message.sh");
+    let config = fs::read_to_string(dir.path().join("cryo.toml")).unwrap();
+    let config = config.replace("watch_inbox = false", "watch_inbox = true");
+    fs::write(dir.path().join("cryo.toml"), config).unwrap();
+    fs::create_dir_all(dir.path().join("messages/inbox")).unwrap();
+
+    cryo_bin()
+        .args(["start", "--agent", "mock", "--max-session-duration", "30"])
+        .env("CRYO_NO_SERVICE", "1")
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    assert!(wait_for_log_content(
+        dir.path(),
+        "waiting for an inbox message",
+        Duration::from_secs(15)
+    ));
+
+    // Plan disappears while the daemon sleeps; a message then wakes it
+    fs::remove_file(dir.path().join("plan.md")).unwrap();
+    write_inbox_message(dir.path(), "msg.md", "are you there?");
+
+    assert!(wait_for_log_content(
+        dir.path(),
+        "plan.md is missing or empty",
+        Duration::from_secs(15)
+    ));
+
+    cryo_bin()
+        .args(["status"])
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Blocked: plan.md missing"))
+        .stdout(predicates::str::contains("Session: 1"));
+    cancel_and_wait(dir.path());
+
+    let log = fs::read_to_string(dir.path().join("cryo.log")).unwrap();
+    assert!(
+        !log.contains("--- CRYO SESSION 2"),
+        "agent must not run: {log}"
+    );
+    let outbox = cryochamber::message::read_outbox(dir.path()).unwrap();
+    assert!(
+        outbox
+            .iter()
+            .any(|(_, m)| m.subject == "Fallback Alert: plan_missing"),
+        "{outbox:?}"
+    );
+}
+
+// --- Inbox wake tests ---
+
+/// Write an inbox message file directly (simulates what cryo-zulip sync does).
+fn write_inbox_message(dir: &std::path::Path, filename: &str, body: &str) {
+    let inbox = dir.join("messages").join("inbox");
+    fs::create_dir_all(&inbox).unwrap();
+    let content = format!("---\nfrom: test-user\nsubject: test\n---\n{body}");
+    fs::write(inbox.join(filename), content).unwrap();
+}
+
+#[test]
+fn test_wakes_during_session_coalesce_into_one() {
+    let dir = tempfile::tempdir().unwrap();
+    setup_scenario(dir.path(), "slow-then-wait.sh");
+
+    cryo_bin()
+        .args(["start", "--agent", "mock"])
+        .env("CRYO_NO_SERVICE", "1")
+        .current_dir(dir.path())
+        .assert()
+        .success();
+    assert!(
+        wait_for_log_content(dir.path(), "agent started", Duration::from_secs(10)),
+        "Session 1 should start"
+    );
+
+    // Fire a forced wake and inbox events while session 1 is still working
+    cryo_bin()
+        .arg("wake")
+        .current_dir(dir.path())
+        .assert()
+        .success();
+    std::thread::sleep(Duration::from_millis(500));
+    for i in 0..5 {
+        write_inbox_message(dir.path(), &format!("burst-{i}.md"), "hello");
+        std::thread::sleep(Duration::from_millis(100));
+    }
+
+    assert!(
+        wait_for_log_content(dir.path(), "Session 2 waiting", Duration::from_secs(15)),
+        "The queued wakes should run one follow-up session"
+    );
+    std::thread::sleep(Duration::from_secs(3));
+    let log = fs::read_to_string(dir.path().join("cryo.log")).unwrap();
+    assert!(!log.contains("--- CRYO SESSION 3"), "{log}");
+    // Sessions never overlap: each one ends before the next begins
+    let markers: Vec<&str> = log
+        .lines()
+        .filter(|l| l.starts_with("--- CRYO SESSION") || l.starts_with("--- CRYO END"))
+        .collect();
+    assert_eq!(markers.len(), 4, "{log}");
+    for pair in markers.chunks(2) {
+        assert!(pair[0].starts_with("--- CRYO SESSION"), "{log}");
+        assert!(pair[1].starts_with("--- CRYO END"), "{log}");
+    }
+    cancel_and_wait(dir.path());
+}
+
+#[test]
+fn test_require_inbox_ack_keeps_ignored_messages() {
+    let dir = tempfile::tempdir().unwrap();
+    setup_scenario(dir.path(), "inbox-ack.sh");
+    let toml = fs::read_to_string(dir.path().join("cryo.toml")).unwrap();
+    fs::write(
+        dir.path().join("cryo.toml"),
+        format!("{toml}\nrequire_inbox_ack = true\n"),
+    )
+    .unwrap();
+    write_inbox_message(dir.path(), "early.md", "please look at this");
+
+    cryo_bin()
+        .args(["start", "--agent", "mock"])
+        .env("CRYO_NO_SERVICE", "1")
+        .current_dir(dir.path())
+        .assert()
+        .success();
+    assert!(
+        wait_for_log_content(
+            dir.path(),
+            "waiting for an inbox message",
+            Duration::from_secs(15)
+        ),
+        "Session 1 should hibernate until a message"
+    );
+    let log = fs::read_to_string(dir.path().join("cryo.log")).unwrap();
+    assert!(
+        log.contains("inbox messages may have been ignored (kept for next session): early.md"),
+        "{log}"
+    );
+    let inbox = dir.path().join("messages/inbox");
+    assert!(inbox.join("early.md").exists(), "Ignored message stays");
+
+    // Session 2 reads both messages, so both are archived without a warning
+    write_inbox_message(dir.path(), "later.md", "ping");
+    assert!(
+        wait_for_daemon_exit(dir.path(), Duration::from_secs(20)),
+        "Session 2 should complete the plan"
+    );
+    let log = fs::read_to_string(dir.path().join("cryo.log")).unwrap();
+    assert_eq!(log.matches("may have been ignored").count(), 1, "{log}");
+    assert!(!inbox.join("early.md").exists());
+    assert!(inbox.join("archive/early.md").exists());
+    assert!(inbox.join("archive/later.md").exists());
+}
+
+#[test]
+fn test_require_inbox_ack_gives_up_after_grace_sessions() {
+    let dir = tempfile::tempdir().unwrap();
+    setup_scenario(dir.path(), "inbox-ack.sh");
+    let toml = fs::read_to_string(dir.path().join("cryo.toml")).unwrap();
+    fs::write(
+        dir.path().join("cryo.toml"),
+        format!("{toml}\nrequire_inbox_ack = true\ninbox_grace_sessions = 1\n"),
+    )
+    .unwrap();
+    write_inbox_message(dir.path(), "early.md", "please look at this");
+
+    cryo_bin()
+        .args(["start", "--once", "--agent", "mock"])
+        .current_dir(dir.path())
+        .timeout(Duration::from_secs(30))
+        .assert()
+        .success();
+    let log = fs::read_to_string(dir.path().join("cryo.log")).unwrap();
+    assert!(
+        log.contains("warning: inbox messages ignored for 1 sessions, disposing: early.md"),
+        "{log}"
+    );
+    assert!(!log.contains("kept for next session"), "{log}");
+    let inbox = dir.path().join("messages/inbox");
+    assert!(!inbox.join("early.md").exists());
+    assert!(inbox.join("archive/early.md").exists());
+}
+
+#[test]
+fn test_message_sent_mid_session_reaches_next_session() {
+    let dir = tempfile::tempdir().unwrap();
+    setup_scenario(dir.path(), "send-mid-session.sh");
+    write_inbox_message(dir.path(), "early.md", "before the session");
+
+    cryo_bin()
+        .args(["start", "--agent", "mock"])
+        .env("CRYO_NO_SERVICE", "1")
+        .current_dir(dir.path())
+        .assert()
+        .success();
+    assert!(
+        wait_for_daemon_exit(dir.path(), Duration::from_secs(30)),
+        "Session 2 should complete the plan"
+    );
+
+    let log = fs::read_to_string(dir.path().join("cryo.log")).unwrap();
+    let sessions: Vec<&str> = log.split("CRYO SESSION").skip(1).collect();
+    assert_eq!(sessions.len(), 2, "{log}");
+    let marker = "inbox arrived during session (kept for next session): ";
+    let late = sessions[0]
+        .lines()
+        .find_map(|l| l.split_once(marker).map(|(_, f)| f.trim().to_string()))
+        .unwrap_or_else(|| panic!("Session 1 should keep the late message: {log}"));
+    assert!(
+        sessions[1].contains(&format!("inbox: 1 messages ({late})")),
+        "{log}"
+    );
+    let received = fs::read_to_string(dir.path().join(".mock-received")).unwrap();
+    assert!(received.contains("sent mid-session"), "{received}");
+    let archive = dir.path().join("messages/inbox/archive");
+    assert!(archive.join("early.md").exists());
+    assert!(archive.join(&late).exists());
+}
+
+#[test]
+fn test_handled_marks_control_inbox_disposal() {
+    let dir = tempfile::tempdir().unwrap();
+    setup_scenario(dir.path(), "inbox-handled.sh");
+    let toml = fs::read_to_string(dir.path().join("cryo.toml")).unwrap();
+    fs::write(
+        dir.path().join("cryo.toml"),
+        format!("{toml}\ninbox_grace_sessions = 2\n"),
+    )
+    .unwrap();
+    for name in ["a.md", "b.md", "c.md"] {
+        write_inbox_message(dir.path(), name, name);
+    }
+    let inbox = dir.path().join("messages/inbox");
+
+    cryo_bin()
+        .args(["start", "--once", "--agent", "mock"])
+        .current_dir(dir.path())
+        .timeout(Duration::from_secs(30))
+        .assert()
+        .success();
+    let log = fs::read_to_string(dir.path().join("cryo.log")).unwrap();
+    assert!(log.contains("inbox handled: a.md"), "{log}");
+    assert!(log.contains("inbox kept: b.md"), "{log}");
+    assert!(
+        log.contains("inbox not handled (kept for next session): c.md"),
+        "{log}"
+    );
+    let error = fs::read_to_string(dir.path().join(".mock-handled-error")).unwrap();
+    assert!(error.contains("Not in the inbox: missing.md"), "{error}");
+    assert!(inbox.join("archive/a.md").exists());
+    assert!(inbox.join("b.md").exists() && inbox.join("c.md").exists());
+
+    // c.md is left unmarked a second time, which uses up its grace
+    cryo_bin()
+        .args(["start", "--once", "--agent", "mock"])
+        .current_dir(dir.path())
+        .timeout(Duration::from_secs(30))
+        .assert()
+        .success();
+    let log = fs::read_to_string(dir.path().join("cryo.log")).unwrap();
+    assert!(
+        log.contains("warning: inbox not handled for 2 sessions, disposing: c.md"),
+        "{log}"
+    );
+    assert!(inbox.join("archive/b.md").exists());
+    assert!(inbox.join("archive/c.md").exists());
+    let state = fs::read_to_string(dir.path().join("timer.json")).unwrap();
+    assert!(!state.contains("inbox_unhandled"), "{state}");
+}
+
+#[test]
+fn test_inbox_wake_coalesces_multiple_events() {
     // Regression test: multiple inbox files created rapidly should trigger
     // only one session, not one per file.
     let dir = tempfile::tempdir().unwrap();
@@ -992,10 +2706,7 @@ fn test_inbox_wake_coalesces_multiple_events() {
     );
 
     let log = fs::read_to_string(dir.path().join("cryo.log")).unwrap();
-    assert!(
-        log.contains("plan complete"),
-        "Plan should complete: {log}"
-    );
+    assert!(log.contains("plan complete"), "Plan should complete: {log}");
 
     // Only ONE session should have run (events coalesced)
     let session_count = log.matches("CRYO SESSION").count();
@@ -1048,10 +2759,7 @@ fn test_inbox_wake_no_delayed_wake_notice() {
     );
 
     let log = fs::read_to_string(dir.path().join("cryo.log")).unwrap();
-    assert!(
-        log.contains("plan complete"),
-        "Plan should complete: {log}"
-    );
+    assert!(log.contains("plan complete"), "Plan should complete: {log}");
 
     // The key assertion: no "delayed wake" notice should appear.
     // Session 2 was triggered by inbox (InboxChanged queued during session 1),
@@ -1062,3 +2770,435 @@ fn test_inbox_wake_no_delayed_wake_notice() {
         "Inbox-triggered wake should NOT produce delayed wake notice: {log}"
     );
 }
+
+#[test]
+fn test_hibernate_until_message_waits_for_inbox() {
+    let dir = tempfile::tempdir().unwrap();
+    setup_scenario(dir.path(), "until-message.sh");
+
+    // Enable watch_inbox
+    let config = fs::read_to_string(dir.path().join("cryo.toml")).unwrap();
+    let config = config.replace("watch_inbox = false", "watch_inbox = true");
+    fs::write(dir.path().join("cryo.toml"), config).unwrap();
+    fs::create_dir_all(dir.path().join("messages/inbox")).unwrap();
+
+    cryo_bin()
+        .args(["start", "--agent", "mock", "--max-session-duration", "30"])
+        .env("CRYO_NO_SERVICE", "1")
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    assert!(
+        wait_for_log_content(
+            dir.path(),
+            "hibernate: until message",
+            Duration::from_secs(10)
+        ),
+        "Session 1 should hibernate until a message arrives"
+    );
+    // Let the session finish and the daemon persist its state
+    assert!(
+        wait_for_log_content(dir.path(), "CRYO END", Duration::from_secs(10)),
+        "Session 1 should end"
+    );
+    std::thread::sleep(Duration::from_millis(500));
+
+    let state: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(dir.path().join("timer.json")).unwrap()).unwrap();
+    assert_eq!(state["awaiting_message"], true, "state: {state}");
+    assert!(state["next_wake"].is_null(), "state: {state}");
+
+    let output = cryo_bin()
+        .args(["status"])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("Next wake: waiting for message"),
+        "status should show waiting for message: {stdout}"
+    );
+
+    write_inbox_message(dir.path(), "hello.md", "hi");
+
+    assert!(
+        wait_for_daemon_exit(dir.path(), Duration::from_secs(15)),
+        "Daemon should exit after plan completion"
+    );
+    let log = fs::read_to_string(dir.path().join("cryo.log")).unwrap();
+    assert!(
+        log.contains("Got message"),
+        "Session 2 should complete: {log}"
+    );
+    assert_eq!(log.matches("CRYO SESSION").count(), 2, "{log}");
+}
+
+#[test]
+fn test_wake_on_message_overrides_watch_inbox_off() {
+    let dir = tempfile::tempdir().unwrap();
+    setup_scenario(dir.path(), "wake-on-message.sh");
+    // The scenario config keeps watch_inbox = false
+    fs::create_dir_all(dir.path().join("messages/inbox")).unwrap();
+
+    cryo_bin()
+        .args(["start", "--agent", "mock", "--max-session-duration", "30"])
+        .env("CRYO_NO_SERVICE", "1")
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    assert!(
+        wait_for_log_content(
+            dir.path(),
+            "wake on message: enabled",
+            Duration::from_secs(10)
+        ),
+        "Session 1 should hibernate with --wake-on-message"
+    );
+    assert!(
+        wait_for_log_content(dir.path(), "CRYO END", Duration::from_secs(10)),
+        "Session 1 should end"
+    );
+    std::thread::sleep(Duration::from_millis(500));
+
+    let state: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(dir.path().join("timer.json")).unwrap()).unwrap();
+    assert_eq!(state["wake_on_message"], true, "state: {state}");
+
+    write_inbox_message(dir.path(), "hello.md", "hi");
+
+    assert!(
+        wait_for_log_content(dir.path(), "plan complete", Duration::from_secs(15)),
+        "An inbox message should wake the agent before the scheduled wake"
+    );
+    let log = fs::read_to_string(dir.path().join("cryo.log")).unwrap();
+    assert!(log.contains("Woken by message"), "{log}");
+    cancel_and_wait(dir.path());
+}
+
+#[test]
+fn test_watch_inbox_streams_new_messages() {
+    let dir = tempfile::tempdir().unwrap();
+    setup_scenario(dir.path(), "watch-inbox.sh");
+
+    cryo_bin()
+        .args(["start", "--agent", "mock", "--max-session-duration", "60"])
+        .env("CRYO_NO_SERVICE", "1")
+        .current_dir(dir.path())
+        .assert()
+        .success();
+    assert!(
+        wait_for_log_content(
+            dir.path(),
+            "inbox watcher connected",
+            Duration::from_secs(10)
+        ),
+        "The agent should subscribe to the inbox"
+    );
+
+    cryo_bin()
+        .args(["send", "--subject", "Heads up", "new requirement"])
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    assert!(
+        wait_for_log_content(dir.path(), "CRYO END", Duration::from_secs(15)),
+        "The session should finish once the agent is notified"
+    );
+    let log = fs::read_to_string(dir.path().join("cryo.log")).unwrap();
+    assert!(log.contains("inbox watcher notified: "), "{log}");
+    assert!(log.contains("summary=\"Notified: Heads up\""), "{log}");
+    cancel_and_wait(dir.path());
+}
+
+#[test]
+fn test_wait_for_reply_returns_matching_answer() {
+    let dir = tempfile::tempdir().unwrap();
+    setup_scenario(dir.path(), "wait-for-reply.sh");
+    fs::create_dir_all(dir.path().join("messages/inbox")).unwrap();
+
+    cryo_bin()
+        .args(["start", "--agent", "mock", "--max-session-duration", "60"])
+        .env("CRYO_NO_SERVICE", "1")
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    assert!(
+        wait_for_log_content(
+            dir.path(),
+            "waiting for reply: \"deploy-ok\"",
+            Duration::from_secs(10)
+        ),
+        "The agent should block on its question"
+    );
+
+    // An unrelated message must not satisfy the wait
+    cryo_bin()
+        .args(["send", "unrelated chatter"])
+        .current_dir(dir.path())
+        .assert()
+        .success();
+    std::thread::sleep(Duration::from_millis(500));
+    cryo_bin()
+        .args(["send", "--reply-to", "deploy-ok", "ship it"])
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    assert!(
+        wait_for_log_content(dir.path(), "CRYO END", Duration::from_secs(15)),
+        "The session should finish once the reply arrives"
+    );
+    let log = fs::read_to_string(dir.path().join("cryo.log")).unwrap();
+    assert!(log.contains("reply received: \"deploy-ok\""), "{log}");
+    assert!(log.contains("summary=\"Got reply: ship it\""), "{log}");
+    cancel_and_wait(dir.path());
+
+    // The unrelated message is left for the next session
+    let inbox = fs::read_dir(dir.path().join("messages/inbox"))
+        .unwrap()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().extension().is_some_and(|ext| ext == "md"))
+        .count();
+    assert_eq!(inbox, 1);
+}
+
+#[test]
+fn test_kill_signal_escalates_after_grace() {
+    let dir = tempfile::tempdir().unwrap();
+    setup_scenario(dir.path(), "slow-to-die.sh");
+    let config = fs::read_to_string(dir.path().join("cryo.toml")).unwrap();
+    let config = format!("{config}\nkill_signal = \"int\"\nkill_grace_secs = 3\n");
+    fs::write(dir.path().join("cryo.toml"), config).unwrap();
+
+    let started = std::time::Instant::now();
+    cryo_bin()
+        .args([
+            "start",
+            "--once",
+            "--agent",
+            "mock",
+            "--max-session-duration",
+            "2",
+        ])
+        .current_dir(dir.path())
+        .timeout(Duration::from_secs(30))
+        .assert()
+        .code(4);
+    // 2s session timeout, then the full 3s grace before SIGKILL
+    assert!(
+        started.elapsed() >= Duration::from_secs(5),
+        "SIGKILL came before the grace period expired ({:?})",
+        started.elapsed()
+    );
+
+    let signals = fs::read_to_string(dir.path().join(".signals")).unwrap_or_default();
+    assert_eq!(
+        signals.trim(),
+        "int",
+        "only the configured signal is sent first"
+    );
+    let log = fs::read_to_string(dir.path().join("cryo.log")).unwrap();
+    assert!(
+        log.contains("agent still running 3s after SIGINT — sent SIGKILL"),
+        "{log}"
+    );
+    assert!(log.contains("session timeout — agent killed"), "{log}");
+}
+
+#[test]
+fn test_hibernate_every_stores_recurring_schedule() {
+    let dir = tempfile::tempdir().unwrap();
+    setup_scenario(dir.path(), "every.sh");
+
+    let read_state = || -> serde_json::Value {
+        serde_json::from_str(&fs::read_to_string(dir.path().join("timer.json")).unwrap()).unwrap()
+    };
+    let assert_weekday_nine = |wake: &str| {
+        let wake = chrono::NaiveDateTime::parse_from_str(wake, "%Y-%m-%dT%H:%M").unwrap();
+        assert_eq!(wake.format("%H:%M").to_string(), "09:00");
+        assert!(
+            chrono::Datelike::weekday(&wake).number_from_monday() <= 5,
+            "{wake}"
+        );
+    };
+
+    // --once leaves scheduling to the caller, so read the wake from the log
+    for _ in 0..2 {
+        cryo_bin()
+            .args(["start", "--once", "--agent", "mock"])
+            .current_dir(dir.path())
+            .timeout(Duration::from_secs(30))
+            .assert()
+            .success();
+        let state = read_state();
+        assert_eq!(state["schedule"], "0 9 * * 1-5", "state: {state}");
+    }
+
+    let log = fs::read_to_string(dir.path().join("cryo.log")).unwrap();
+    let wakes: Vec<&str> = log
+        .lines()
+        .filter_map(|l| l.split_once("hibernate: wake=")?.1.split(',').next())
+        .collect();
+    assert_eq!(wakes.len(), 2, "{log}");
+    wakes.iter().for_each(|w| assert_weekday_nine(w));
+    assert_eq!(
+        log.matches("schedule: every \"0 9 * * 1-5\"").count(),
+        1,
+        "{log}"
+    );
+    assert!(log.contains("summary=\"Same time next weekday\""), "{log}");
+
+    cryo_bin()
+        .arg("status")
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Schedule: every \"0 9 * * 1-5\""));
+}
+
+/// Start a daemon in this process for a project set up with `setup_scenario`.
+fn spawn_embedded(dir: &std::path::Path) -> cryochamber::DaemonHandle {
+    spawn_embedded_with(dir, cryochamber::state::CryoState::default())
+}
+
+/// `spawn_embedded` starting from `state` in timer.json.
+fn spawn_embedded_with(
+    dir: &std::path::Path,
+    state: cryochamber::state::CryoState,
+) -> cryochamber::DaemonHandle {
+    // cryo-agent sits next to cryo in target/debug, not next to this test binary
+    let bin_dir = std::path::Path::new(env!("CARGO_BIN_EXE_cryo"))
+        .parent()
+        .unwrap();
+    let path = std::env::var("PATH").unwrap_or_default();
+    if !path.split(':').any(|p| std::path::Path::new(p) == bin_dir) {
+        std::env::set_var("PATH", format!("{}:{path}", bin_dir.display()));
+    }
+    let state = cryochamber::state::CryoState {
+        max_session_duration_override: Some(30),
+        ..state
+    };
+    cryochamber::state::save_state(&dir.join("timer.json"), &state).unwrap();
+    let store = cryochamber::store::open(dir).unwrap();
+    cryochamber::Daemon::new(dir.to_path_buf(), store)
+        .spawn()
+        .unwrap()
+}
+
+#[test]
+fn test_embedded_daemon_wake_and_cancel() {
+    // wake(): session 1 waits for a message, the handle wakes it, session 2 completes
+    let dir = tempfile::tempdir().unwrap();
+    setup_scenario(dir.path(), "until-message.sh");
+    let handle = spawn_embedded(dir.path());
+    assert!(
+        wait_for_log_content(dir.path(), "CRYO END", Duration::from_secs(10)),
+        "Session 1 should end"
+    );
+    std::thread::sleep(Duration::from_millis(500));
+    let state = handle.status().unwrap().unwrap();
+    assert!(state.awaiting_message, "{state:?}");
+    assert_eq!(state.pid, Some(std::process::id()));
+    assert!(handle.is_running());
+
+    handle.wake();
+    handle.join().unwrap();
+    let log = fs::read_to_string(dir.path().join("cryo.log")).unwrap();
+    assert!(
+        log.contains("Got message"),
+        "Session 2 should complete: {log}"
+    );
+
+    // cancel(): stops a daemon that is waiting and releases the project
+    let dir = tempfile::tempdir().unwrap();
+    setup_scenario(dir.path(), "until-message.sh");
+    let handle = spawn_embedded(dir.path());
+    assert!(
+        wait_for_log_content(dir.path(), "CRYO END", Duration::from_secs(10)),
+        "Session 1 should end"
+    );
+    handle.cancel().unwrap();
+    let state = cryochamber::state::load_state(&dir.path().join("timer.json")).unwrap();
+    assert!(
+        state.is_none_or(|s| s.pid.is_none()),
+        "cancel should release the PID lock"
+    );
+}
+
+#[test]
+fn test_embedded_daemon_woken_and_stopped_by_cli() {
+    // The CLI must reach the daemon over its socket: a signal to the
+    // recorded PID would hit this test process
+    let dir = tempfile::tempdir().unwrap();
+    setup_scenario(dir.path(), "until-message.sh");
+    let config = fs::read_to_string(dir.path().join("cryo.toml")).unwrap();
+    let config = config.replace("watch_inbox = true", "watch_inbox = false");
+    fs::write(dir.path().join("cryo.toml"), config).unwrap();
+    let handle = spawn_embedded(dir.path());
+    assert!(
+        wait_for_log_content(dir.path(), "CRYO END", Duration::from_secs(10)),
+        "Session 1 should end"
+    );
+    std::thread::sleep(Duration::from_millis(500));
+    assert!(handle.status().unwrap().unwrap().embedded);
+
+    cryo_bin()
+        .arg("wake")
+        .current_dir(dir.path())
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Wake signal sent"));
+    handle.join().unwrap();
+    let log = fs::read_to_string(dir.path().join("cryo.log")).unwrap();
+    assert!(log.contains("Got message"), "Session 2 should run: {log}");
+
+    let dir = tempfile::tempdir().unwrap();
+    setup_scenario(dir.path(), "until-message.sh");
+    let handle = spawn_embedded(dir.path());
+    assert!(
+        wait_for_log_content(dir.path(), "CRYO END", Duration::from_secs(10)),
+        "Session 1 should end"
+    );
+    cryo_bin()
+        .arg("cancel")
+        .current_dir(dir.path())
+        .timeout(Duration::from_secs(30))
+        .assert()
+        .success();
+    handle.join().unwrap();
+}
+
+#[test]
+fn test_embedded_daemon_pauses_on_invalid_agent_command() {
+    let dir = tempfile::tempdir().unwrap();
+    setup_scenario(dir.path(), "until-message.sh");
+    let config = fs::read_to_string(dir.path().join("cryo.toml")).unwrap();
+    let config = config.replace("agent = \"mock\"", "agent = \"/nonexistent/agent-binary\"");
+    fs::write(dir.path().join("cryo.toml"), config).unwrap();
+    let handle = spawn_embedded(dir.path());
+
+    // No backoff loop: the daemon records why and waits for a message
+    let deadline = std::time::Instant::now() + Duration::from_secs(10);
+    let state = loop {
+        let state = handle.status().unwrap().unwrap_or_default();
+        if state.blocked_reason.is_some() || std::time::Instant::now() > deadline {
+            break state;
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    };
+    let reason = state.blocked_reason.unwrap_or_default();
+    assert!(reason.starts_with("agent command invalid"), "{reason}");
+    assert!(reason.contains("/nonexistent/agent-binary"), "{reason}");
+    assert!(state.awaiting_message);
+    assert_eq!(state.session_number, 0);
+    let outbox = fs::read_dir(dir.path().join("messages/outbox"))
+        .unwrap()
+        .count();
+    assert_eq!(outbox, 1, "exactly one alert should be sent");
+
+    handle.cancel().unwrap();
+}
@@ -74,6 +74,29 @@ fn test_write_protocol_file_no_clobber() {
     assert_eq!(content, "custom protocol");
 }
 
+#[test]
+fn test_render_protocol_appends_template() {
+    let content = protocol::render_protocol(Some("Never run `git push --force`.\n"));
+    assert!(content.starts_with(protocol::PROTOCOL_CONTENT.trim_end()));
+    let (_, rules) = content.split_once(protocol::TEMPLATE_MARKER).unwrap();
+    assert_eq!(rules.trim(), "Never run `git push --force`.");
+    assert!(protocol::template_warnings("Never run `git push --force`.").is_empty());
+}
+
+#[test]
+fn test_render_protocol_replace_directive() {
+    let template = format!(
+        "{}\n# Our protocol\nEnd with cryo-agent hibernate.\n",
+        protocol::REPLACE_DIRECTIVE
+    );
+    let content = protocol::render_protocol(Some(&template));
+    assert_eq!(content, "# Our protocol\nEnd with cryo-agent hibernate.\n");
+
+    let warnings = protocol::template_warnings(&template);
+    assert_eq!(warnings.len(), 1, "{warnings:?}");
+    assert!(warnings[0].contains("plan.md"));
+}
+
 #[test]
 fn test_find_protocol_file_none() {
     let dir = tempfile::tempdir().unwrap();
@@ -116,6 +139,16 @@ fn test_write_template_plan_skips_existing() {
     assert_eq!(content, "existing plan");
 }
 
+#[test]
+fn test_plan_warnings() {
+    assert!(protocol::plan_warnings(protocol::TEMPLATE_PLAN).is_empty());
+    assert!(protocol::plan_warnings("1. Fetch data\n2. Summarize").is_empty());
+    let html = protocol::plan_warnings("<!DOCTYPE html>\n<html><body># Plan</body></html>");
+    assert!(html[0].contains("HTML page"), "{html:?}");
+    let prose = protocol::plan_warnings("Just do the thing, thanks.");
+    assert!(prose[0].contains("may not be Markdown"), "{prose:?}");
+}
+
 #[test]
 fn test_protocol_mentions_hibernate() {
     let content = cryochamber::protocol::PROTOCOL_CONTENT;
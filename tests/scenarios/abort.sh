@@ -0,0 +1,6 @@
+#!/bin/sh
+# Mock agent: finds itself in a bad state and aborts the session on purpose,
+# then exits non-zero (which must not be treated as a crash).
+
+cryo-agent abort --reason "wrong branch checked out"
+exit 1
@@ -0,0 +1,21 @@
+#!/bin/sh
+# Mock agent: arms a fallback alert and crashes the first time, then
+# completes the plan on retry. The fallback must never fire.
+
+COUNTER_FILE=".mock-crash-count"
+
+if [ -f "$COUNTER_FILE" ]; then
+    COUNT=$(cat "$COUNTER_FILE")
+else
+    COUNT=0
+fi
+
+COUNT=$((COUNT + 1))
+echo "$COUNT" > "$COUNTER_FILE"
+
+if [ "$COUNT" -ge 2 ]; then
+    cryo-agent hibernate --complete --summary "Recovered on attempt $COUNT"
+else
+    cryo-agent alert notify operator "fallback: agent did not recover"
+    exit 1
+fi
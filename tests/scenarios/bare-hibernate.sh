@@ -0,0 +1,4 @@
+#!/bin/sh
+# Mock agent: hibernates with no wake time, relying on wake_cadence.
+
+cryo-agent hibernate --summary "Checked in"
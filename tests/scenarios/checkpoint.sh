@@ -0,0 +1,11 @@
+#!/bin/sh
+# Mock agent: narrows a search range across two sessions using a checkpoint.
+# Session 1 saves the range; session 2 loads it and completes.
+
+if CHECKPOINT=$(cryo-agent checkpoint load); then
+    echo "loaded $CHECKPOINT"
+    cryo-agent hibernate --complete --summary "Resumed from $CHECKPOINT"
+else
+    cryo-agent checkpoint save '{"low":100,"high":200}'
+    cryo-agent hibernate --until-message --summary "Range saved"
+fi
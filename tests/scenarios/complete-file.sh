@@ -0,0 +1,5 @@
+#!/bin/sh
+# Mock agent: finishes the plan but forgets `hibernate --complete`,
+# touching .cryo/COMPLETE instead.
+# Tests: complete_marker = "file".
+touch .cryo/COMPLETE
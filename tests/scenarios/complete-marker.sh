@@ -0,0 +1,6 @@
+#!/bin/sh
+# Mock agent: finishes the plan but forgets `hibernate --complete`,
+# printing the completion marker instead.
+# Tests: complete_marker = "output".
+echo "All tasks done."
+echo "[CRYO:PLAN-COMPLETE]"
@@ -0,0 +1,5 @@
+#!/bin/sh
+# Mock agent: completes the plan and attaches a structured result file.
+
+echo '{"status":"ok","artifacts":["report.pdf"]}' > result.json
+cryo-agent hibernate --complete --result result.json --summary "Done with result"
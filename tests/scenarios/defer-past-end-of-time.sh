@@ -0,0 +1,9 @@
+#!/bin/sh
+# Mock agent: hibernates at the latest time chrono can hold, then defers.
+# Tests: a defer that would overflow the wake time is refused, not fatal.
+cryo-agent hibernate --wake "+262142-12-31T23:00" --summary "See you later"
+if cryo-agent defer --by 2h 2> .defer-error; then
+    echo "accepted" > .defer-result
+else
+    echo "refused" > .defer-result
+fi
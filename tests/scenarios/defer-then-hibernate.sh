@@ -0,0 +1,6 @@
+#!/bin/sh
+# Mock agent: asks for more time, then finishes after the original timeout.
+# Tests: `cryo-agent defer` extends the session deadline.
+cryo-agent defer --by 3s
+sleep 4
+cryo-agent hibernate --complete --summary "Finished after deferring"
@@ -0,0 +1,6 @@
+#!/bin/sh
+# Mock agent: records what `cryo-agent env` reports, then completes.
+
+cryo-agent env > .env-redacted
+cryo-agent env --show-values > .env-values
+cryo-agent hibernate --complete --summary "Checked env"
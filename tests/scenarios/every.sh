@@ -0,0 +1,20 @@
+#!/bin/sh
+# Mock agent: session 1 sets a recurring weekday-morning schedule; session 2
+# hibernates without --wake and relies on the stored schedule.
+
+COUNTER_FILE=".mock-session-count"
+
+if [ -f "$COUNTER_FILE" ]; then
+    COUNT=$(cat "$COUNTER_FILE")
+else
+    COUNT=0
+fi
+
+COUNT=$((COUNT + 1))
+echo "$COUNT" > "$COUNTER_FILE"
+
+if [ "$COUNT" -ge 2 ]; then
+    cryo-agent hibernate --summary "Same time next weekday"
+else
+    cryo-agent hibernate --every "0 9 * * 1-5" --summary "Weekday mornings from now on"
+fi
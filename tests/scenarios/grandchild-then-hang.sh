@@ -0,0 +1,6 @@
+#!/bin/sh
+# Mock agent: forks a long-lived grandchild, records its PID, then hangs.
+# Tests: a session timeout stops the grandchild along with the agent.
+sleep 300 &
+echo $! > .grandchild-pid
+sleep 99999
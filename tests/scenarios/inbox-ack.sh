@@ -0,0 +1,21 @@
+#!/bin/sh
+# Mock agent: session 1 ignores its inbox and waits for a message.
+# Session 2 reads the inbox with `cryo-agent receive` and completes the plan.
+
+COUNTER_FILE=".mock-session-count"
+
+if [ -f "$COUNTER_FILE" ]; then
+    COUNT=$(cat "$COUNTER_FILE")
+else
+    COUNT=0
+fi
+
+COUNT=$((COUNT + 1))
+echo "$COUNT" > "$COUNTER_FILE"
+
+if [ "$COUNT" -ge 2 ]; then
+    cryo-agent receive > /dev/null
+    cryo-agent hibernate --complete --summary "Read the inbox"
+else
+    cryo-agent hibernate --until-message --summary "Not reading mail"
+fi
@@ -0,0 +1,23 @@
+#!/bin/sh
+# Mock agent: session 1 handles a.md, keeps b.md and leaves c.md unmarked.
+# Session 2 handles b.md and leaves c.md unmarked again.
+
+COUNTER_FILE=".mock-session-count"
+
+if [ -f "$COUNTER_FILE" ]; then
+    COUNT=$(cat "$COUNTER_FILE")
+else
+    COUNT=0
+fi
+
+COUNT=$((COUNT + 1))
+echo "$COUNT" > "$COUNTER_FILE"
+
+if [ "$COUNT" -ge 2 ]; then
+    cryo-agent handled b.md
+else
+    cryo-agent handled a.md
+    cryo-agent handled --keep b.md
+    cryo-agent handled missing.md 2> .mock-handled-error
+fi
+cryo-agent hibernate --until-message --summary "Inbox triaged"
@@ -1,8 +1,11 @@
 #!/bin/sh
-# Mock agent: calls note, send, alert, then hibernates complete.
+# Mock agent: calls ping, note, send, alert, then hibernates complete.
 # Tests: all IPC command handling in the daemon socket server.
 
+cryo-agent ping > .mock-ping
 cryo-agent note "Starting IPC test"
+printf 'running 2 tests\ntest ok\n' > .mock-build.log
+cryo-agent attach-log --file .mock-build.log --label "unit tests"
 cryo-agent send "Status update for operator"
 cryo-agent alert notify desktop "Check on mock agent"
 cryo-agent note "All IPC commands sent"
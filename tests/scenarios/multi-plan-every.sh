@@ -0,0 +1,20 @@
+#!/bin/sh
+# Mock agent for a chamber with plans/alpha.md and plans/beta.md.
+# Session 1 sets a recurring schedule; every later session completes its plan.
+
+COUNTER_FILE=".mock-session-count"
+
+if [ -f "$COUNTER_FILE" ]; then
+    COUNT=$(cat "$COUNTER_FILE")
+else
+    COUNT=0
+fi
+
+COUNT=$((COUNT + 1))
+echo "$COUNT" > "$COUNTER_FILE"
+
+if [ "$COUNT" -ge 2 ]; then
+    cryo-agent hibernate --complete --summary "Plan done"
+else
+    cryo-agent hibernate --every "0 9 * * *" --summary "Scheduled"
+fi
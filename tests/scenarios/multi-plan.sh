@@ -0,0 +1,24 @@
+#!/bin/sh
+# Mock agent for a chamber with plans/alpha.md and plans/beta.md.
+# Session 1 (alpha) sets its next task and keeps the focus on alpha.
+# Session 2 (alpha again) completes alpha; session 3 (beta) completes beta.
+
+COUNTER_FILE=".mock-session-count"
+
+if [ -f "$COUNTER_FILE" ]; then
+    COUNT=$(cat "$COUNTER_FILE")
+else
+    COUNT=0
+fi
+
+COUNT=$((COUNT + 1))
+echo "$COUNT" > "$COUNTER_FILE"
+echo "$1" > ".mock-prompt-$COUNT"
+
+if [ "$COUNT" -ge 2 ]; then
+    cryo-agent hibernate --complete --summary "Plan done"
+else
+    cryo-agent next-task "Alpha step 2"
+    cryo-agent focus-plan alpha
+    cryo-agent hibernate --wake-in 1s --summary "Alpha step 1 done"
+fi
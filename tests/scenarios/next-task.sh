@@ -0,0 +1,21 @@
+#!/bin/sh
+# Mock agent: session 1 sets the next task explicitly and sleeps.
+# Session 2 completes the plan.
+
+COUNTER_FILE=".mock-session-count"
+
+if [ -f "$COUNTER_FILE" ]; then
+    COUNT=$(cat "$COUNTER_FILE")
+else
+    COUNT=0
+fi
+
+COUNT=$((COUNT + 1))
+echo "$COUNT" > "$COUNTER_FILE"
+
+if [ "$COUNT" -ge 2 ]; then
+    cryo-agent hibernate --complete --summary "Handler done"
+else
+    cryo-agent next-task "Implement the REST handler"
+    cryo-agent hibernate --until-message --summary "Planned next step"
+fi
@@ -0,0 +1,9 @@
+#!/bin/sh
+# Mock agent: asks for approval before a risky step, then completes once
+# the approval is in the inbox.
+# Tests: cryo-agent request-review and `cryo review approve`.
+if grep -qs "decision: approved" messages/inbox/*.md; then
+    cryo-agent hibernate --complete --summary "Deployed after approval"
+else
+    cryo-agent request-review --id deploy --prompt "Deploy to prod?"
+fi
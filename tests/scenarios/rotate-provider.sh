@@ -0,0 +1,22 @@
+#!/bin/sh
+# Mock agent: session 1 asks to move off its provider and hibernates.
+# Session 2 records which provider it got and completes.
+
+COUNTER_FILE=".mock-session-count"
+
+if [ -f "$COUNTER_FILE" ]; then
+    COUNT=$(cat "$COUNTER_FILE")
+else
+    COUNT=0
+fi
+
+COUNT=$((COUNT + 1))
+echo "$COUNT" > "$COUNTER_FILE"
+
+if [ "$COUNT" -ge 2 ]; then
+    cryo-agent env > .env-session2
+    cryo-agent hibernate --complete --summary "Ran on the other provider"
+else
+    cryo-agent rotate-provider --reason "rate limited"
+    cryo-agent hibernate --wake-in 1s --summary "Rotating"
+fi
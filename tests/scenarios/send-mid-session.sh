@@ -0,0 +1,24 @@
+#!/bin/sh
+# Mock agent: session 1 gets a message from `cryo send --wake` while it runs,
+# after its prompt was built, and waits for a message without reading it.
+# Session 2 must be presented that message; it reads it and completes.
+
+COUNTER_FILE=".mock-session-count"
+
+if [ -f "$COUNTER_FILE" ]; then
+    COUNT=$(cat "$COUNTER_FILE")
+else
+    COUNT=0
+fi
+
+COUNT=$((COUNT + 1))
+echo "$COUNT" > "$COUNTER_FILE"
+
+if [ "$COUNT" -ge 2 ]; then
+    cryo-agent receive > .mock-received
+    cryo-agent hibernate --complete --summary "Read the late message"
+else
+    cryo send --wake "sent mid-session"
+    sleep 1
+    cryo-agent hibernate --until-message --summary "Waiting"
+fi
@@ -0,0 +1,5 @@
+#!/bin/sh
+# Mock agent: reports progress with set-summary, then hangs without hibernating.
+# Tests: the working summary survives a session timeout.
+cryo-agent set-summary "Migrated 3 of 5 tables"
+sleep 99999
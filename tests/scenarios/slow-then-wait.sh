@@ -0,0 +1,19 @@
+#!/bin/sh
+# Mock agent: session 1 works for a few seconds (while the test fires wake
+# events at it), then every session hibernates until a message arrives.
+
+COUNTER_FILE=".mock-session-count"
+
+if [ -f "$COUNTER_FILE" ]; then
+    COUNT=$(cat "$COUNTER_FILE")
+else
+    COUNT=0
+fi
+
+COUNT=$((COUNT + 1))
+echo "$COUNT" > "$COUNTER_FILE"
+
+if [ "$COUNT" -eq 1 ]; then
+    sleep 3
+fi
+cryo-agent hibernate --until-message --summary "Session $COUNT waiting"
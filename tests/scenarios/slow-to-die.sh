@@ -0,0 +1,9 @@
+#!/bin/sh
+# Mock agent: never hibernates and shrugs off polite stop signals (recording
+# each one), so the daemon has to escalate to SIGKILL after the grace period.
+trap 'echo int >> .signals' INT
+trap 'echo term >> .signals' TERM
+echo "Agent will ignore stop signals..."
+while true; do
+    sleep 0.2
+done
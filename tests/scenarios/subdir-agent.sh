@@ -0,0 +1,8 @@
+#!/bin/sh
+# Mock agent: runs cryo-agent from a subdirectory of the project.
+# The socket must be discovered by walking up to the project root.
+
+mkdir -p build/nested
+cd build/nested || exit 1
+cryo-agent note "Working from a subdirectory"
+cryo-agent hibernate --complete --summary "Done from subdir"
@@ -0,0 +1,6 @@
+#!/bin/sh
+# Mock agent: narrates progress with summary-append, then completes without
+# --summary. Tests: the appended pieces become the hibernate summary.
+cryo-agent summary-append "Fixed the parser"
+cryo-agent summary-append "Added tests"
+cryo-agent hibernate --complete
@@ -0,0 +1,8 @@
+#!/bin/sh
+# Mock agent: tries to complete without a summary, then retries with one.
+
+if cryo-agent hibernate --complete; then
+    echo "bare hibernate was accepted"
+    exit 0
+fi
+cryo-agent hibernate --complete --summary "Shipped the release notes"
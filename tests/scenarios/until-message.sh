@@ -0,0 +1,20 @@
+#!/bin/sh
+# Mock agent: session 1 hibernates until a message arrives (no wake time).
+# Session 2 (triggered by an inbox message) completes the plan.
+
+COUNTER_FILE=".mock-session-count"
+
+if [ -f "$COUNTER_FILE" ]; then
+    COUNT=$(cat "$COUNTER_FILE")
+else
+    COUNT=0
+fi
+
+COUNT=$((COUNT + 1))
+echo "$COUNT" > "$COUNTER_FILE"
+
+if [ "$COUNT" -ge 2 ]; then
+    cryo-agent hibernate --complete --summary "Got message"
+else
+    cryo-agent hibernate --until-message --summary "Waiting for human"
+fi
@@ -0,0 +1,10 @@
+#!/bin/sh
+# Mock agent: asks a question, then blocks in-session until the human
+# answers it with `cryo send --reply-to deploy-ok`.
+
+cryo-agent send "Deploy to production? (reply with --reply-to deploy-ok)"
+if ANSWER=$(cryo-agent wait-for-reply --id deploy-ok --timeout 20); then
+    cryo-agent hibernate --complete --summary "Got reply: $ANSWER"
+else
+    cryo-agent hibernate --complete --exit 1 --summary "No reply"
+fi
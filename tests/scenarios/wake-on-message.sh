@@ -0,0 +1,20 @@
+#!/bin/sh
+# Mock agent: session 1 hibernates for a day but asks to be woken early by
+# a message. Session 2 (triggered by an inbox message) completes the plan.
+
+COUNTER_FILE=".mock-session-count"
+
+if [ -f "$COUNTER_FILE" ]; then
+    COUNT=$(cat "$COUNTER_FILE")
+else
+    COUNT=0
+fi
+
+COUNT=$((COUNT + 1))
+echo "$COUNT" > "$COUNTER_FILE"
+
+if [ "$COUNT" -ge 2 ]; then
+    cryo-agent hibernate --complete --summary "Woken by message"
+else
+    cryo-agent hibernate --wake "$(cryo-agent time '+1 day')" --wake-on-message --summary "Sleeping a day"
+fi
@@ -0,0 +1,19 @@
+#!/bin/sh
+# Mock agent: subscribes to inbox arrivals mid-session, then finishes as
+# soon as the first notification comes in (or gives up after 20s).
+
+cryo-agent watch-inbox > watch-inbox.out &
+WATCHER=$!
+
+i=0
+while [ ! -s watch-inbox.out ] && [ "$i" -lt 200 ]; do
+    sleep 0.1
+    i=$((i + 1))
+done
+kill "$WATCHER" 2>/dev/null
+
+if [ -s watch-inbox.out ]; then
+    cryo-agent hibernate --complete --summary "Notified: $(cut -f3 watch-inbox.out | head -n 1)"
+else
+    cryo-agent hibernate --complete --exit 1 --summary "No notification"
+fi
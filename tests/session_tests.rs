@@ -37,3 +37,65 @@ fn test_should_copy_source_nonexistent() {
     std::fs::write(&dst, "# Plan").unwrap();
     assert!(cryochamber::session::should_copy_plan(&src, &dst));
 }
+
+// --- dest_is_newer ---
+
+fn set_mtime(path: &std::path::Path, secs_ago: u64) {
+    let t = std::time::SystemTime::now() - std::time::Duration::from_secs(secs_ago);
+    std::fs::File::options()
+        .write(true)
+        .open(path)
+        .unwrap()
+        .set_modified(t)
+        .unwrap();
+}
+
+#[test]
+fn test_dest_is_newer_local_edit() {
+    let dir = tempfile::tempdir().unwrap();
+    let src = dir.path().join("source.md");
+    let dst = dir.path().join("plan.md");
+    std::fs::write(&src, "# Source").unwrap();
+    std::fs::write(&dst, "# Edited").unwrap();
+    set_mtime(&src, 60);
+    assert!(cryochamber::session::dest_is_newer(&src, &dst));
+}
+
+#[test]
+fn test_dest_is_newer_source_updated() {
+    let dir = tempfile::tempdir().unwrap();
+    let src = dir.path().join("source.md");
+    let dst = dir.path().join("plan.md");
+    std::fs::write(&src, "# Source").unwrap();
+    std::fs::write(&dst, "# Old").unwrap();
+    set_mtime(&dst, 60);
+    assert!(!cryochamber::session::dest_is_newer(&src, &dst));
+}
+
+#[test]
+fn test_dest_is_newer_dest_missing() {
+    let dir = tempfile::tempdir().unwrap();
+    let src = dir.path().join("source.md");
+    std::fs::write(&src, "# Source").unwrap();
+    assert!(!cryochamber::session::dest_is_newer(
+        &src,
+        &dir.path().join("plan.md")
+    ));
+}
+
+// --- plan_progress ---
+
+#[test]
+fn test_plan_progress_counts_checkboxes() {
+    let plan =
+        "# Plan\n\n- [x] Set up repo\n- [ ] Write handler\n  * [X] Nested done\n- not a task\n";
+    assert_eq!(cryochamber::session::plan_progress(plan), Some((2, 3)));
+}
+
+#[test]
+fn test_plan_progress_none_without_checkboxes() {
+    assert_eq!(
+        cryochamber::session::plan_progress("# Plan\nJust prose."),
+        None
+    );
+}
@@ -16,6 +16,7 @@ fn test_save_and_load_state() {
         next_wake: None,
         last_report_time: None,
         provider_index: None,
+        ..Default::default()
     };
 
     save_state(&state_path, &state).unwrap();
@@ -49,6 +50,7 @@ fn test_lock_mechanism() {
         next_wake: None,
         last_report_time: None,
         provider_index: None,
+        ..Default::default()
     };
     save_state(&state_path, &state).unwrap();
 
@@ -71,6 +73,7 @@ fn test_is_locked_dead_process() {
         next_wake: None,
         last_report_time: None,
         provider_index: None,
+        ..Default::default()
     };
     assert!(!is_locked(&state));
 }
@@ -88,6 +91,7 @@ fn test_is_locked_no_pid() {
         next_wake: None,
         last_report_time: None,
         provider_index: None,
+        ..Default::default()
     };
     assert!(!is_locked(&state));
 }
@@ -141,6 +145,7 @@ fn test_override_fields_roundtrip() {
         next_wake: None,
         last_report_time: None,
         provider_index: None,
+        ..Default::default()
     };
     save_state(&state_path, &state).unwrap();
     let loaded = load_state(&state_path).unwrap().unwrap();
@@ -164,6 +169,7 @@ fn test_none_overrides_not_serialized() {
         next_wake: None,
         last_report_time: None,
         provider_index: None,
+        ..Default::default()
     };
     save_state(&state_path, &state).unwrap();
     let json = std::fs::read_to_string(&state_path).unwrap();
@@ -189,6 +195,7 @@ fn test_last_report_time_roundtrip() {
         next_wake: None,
         last_report_time: Some("2026-02-28T09:00:00".to_string()),
         provider_index: None,
+        ..Default::default()
     };
     save_state(&state_path, &state).unwrap();
     let loaded = load_state(&state_path).unwrap().unwrap();
@@ -216,6 +223,7 @@ fn test_next_wake_roundtrip() {
         next_wake: Some("2026-03-01T09:00".to_string()),
         last_report_time: None,
         provider_index: None,
+        ..Default::default()
     };
     save_state(&state_path, &state).unwrap();
     let loaded = load_state(&state_path).unwrap().unwrap();
@@ -240,6 +248,7 @@ fn test_provider_index_roundtrip() {
         next_wake: None,
         last_report_time: None,
         provider_index: Some(2),
+        ..Default::default()
     };
     save_state(&state_path, &state).unwrap();
     let loaded = load_state(&state_path).unwrap().unwrap();
@@ -0,0 +1,430 @@
+// tests/store_tests.rs
+use chrono::NaiveDateTime;
+use cryochamber::config::{InboxDisposition, MessageStoreKind};
+use cryochamber::message::{DeliveryReceipt, Message};
+use cryochamber::store::{self, sqlite, FileStore, MessageStore, SqliteStore};
+use std::collections::BTreeMap;
+
+fn make_message(subject: &str, ts: &str) -> Message {
+    Message {
+        from: "human".to_string(),
+        subject: subject.to_string(),
+        body: format!("body of {subject}"),
+        timestamp: NaiveDateTime::parse_from_str(ts, "%Y-%m-%dT%H:%M:%S").unwrap(),
+        metadata: BTreeMap::new(),
+        attachments: Vec::new(),
+    }
+}
+
+fn make_receipt() -> DeliveryReceipt {
+    DeliveryReceipt {
+        channel: "github".to_string(),
+        remote_id: Some("42".to_string()),
+        url: None,
+        delivered_at: NaiveDateTime::parse_from_str("2026-03-01T09:05:00", "%Y-%m-%dT%H:%M:%S")
+            .unwrap(),
+    }
+}
+
+#[test]
+fn test_file_store_uses_configured_dirs() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("cryo.toml"),
+        "inbox_dir = \".agent/in\"\noutbox_dir = \".agent/out\"\n",
+    )
+    .unwrap();
+    let store = store::open(dir.path()).unwrap();
+    store.ensure().unwrap();
+
+    let id = store
+        .write("inbox", &make_message("Hello", "2026-03-01T09:00:00"))
+        .unwrap();
+    assert!(dir.path().join(".agent/in").join(&id).exists());
+    assert!(!dir.path().join("messages/inbox").join(&id).exists());
+    assert_eq!(store.read("inbox", &id).unwrap().subject, "Hello");
+
+    store.archive(std::slice::from_ref(&id)).unwrap();
+    assert!(dir.path().join(".agent/in/archive").join(&id).exists());
+
+    let out = store
+        .write("outbox", &make_message("Reply", "2026-03-01T10:00:00"))
+        .unwrap();
+    assert!(dir.path().join(".agent/out").join(&out).exists());
+}
+
+#[test]
+fn test_file_store_roundtrip() {
+    let dir = tempfile::tempdir().unwrap();
+    let store: Box<dyn MessageStore> = Box::new(FileStore::new(dir.path().to_path_buf()));
+    store.ensure().unwrap();
+
+    let second = store
+        .write("inbox", &make_message("Second", "2026-03-01T10:00:00"))
+        .unwrap();
+    let first = store
+        .write("inbox", &make_message("First", "2026-03-01T09:00:00"))
+        .unwrap();
+
+    // Ids are the filenames the agent sees under messages/inbox/
+    assert!(dir.path().join("messages/inbox").join(&first).is_file());
+    assert_eq!(
+        store.list_inbox().unwrap(),
+        vec![first.clone(), second.clone()]
+    );
+    let inbox = store.read_inbox().unwrap();
+    assert_eq!(inbox[0].1.subject, "First");
+    assert_eq!(store.read("inbox", &second).unwrap().subject, "Second");
+
+    store.archive(std::slice::from_ref(&first)).unwrap();
+    assert_eq!(store.list_inbox().unwrap(), vec![second.clone()]);
+    assert_eq!(store.read_inbox_archive().unwrap()[0].0, first);
+
+    store.dispose(&[second], InboxDisposition::Delete).unwrap();
+    assert!(store.read_inbox().unwrap().is_empty());
+}
+
+#[test]
+fn test_sqlite_store_roundtrip() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = SqliteStore::open(dir.path()).unwrap();
+
+    let mut msg = make_message("Hello", "2026-03-01T09:00:00");
+    msg.metadata.insert("in_reply_to".into(), "q1".into());
+    msg.attachments.push("2026-03-01T09-00-00_log.txt".into());
+    let first = store.write("inbox", &msg).unwrap();
+    // Same subject in the same second gets a distinct id
+    let second = store.write("inbox", &msg).unwrap();
+    assert_ne!(first, second);
+    let third = store
+        .write("inbox", &make_message("Later", "2026-03-01T10:00:00"))
+        .unwrap();
+
+    assert_eq!(
+        store.list_inbox().unwrap(),
+        vec![first.clone(), second.clone(), third.clone()]
+    );
+    let read = store.read("inbox", &first).unwrap();
+    assert_eq!(read.body, "body of Hello");
+    assert!(read.answers(Some("q1")));
+    assert_eq!(read.attachments, msg.attachments);
+    assert!(store.read("outbox", &first).is_err());
+
+    store.archive(std::slice::from_ref(&first)).unwrap();
+    store
+        .dispose(std::slice::from_ref(&second), InboxDisposition::Flag)
+        .unwrap();
+    store
+        .dispose(std::slice::from_ref(&third), InboxDisposition::Delete)
+        .unwrap();
+    assert!(store.list_inbox().unwrap().is_empty());
+    let archived = store.read_inbox_archive().unwrap();
+    assert_eq!(archived.len(), 1);
+    assert_eq!(archived[0].0, first);
+
+    // Nothing was written as files
+    assert!(!dir.path().join("messages/inbox").exists());
+}
+
+#[test]
+fn test_outbox_delivery_in_both_stores() {
+    for kind in [MessageStoreKind::File, MessageStoreKind::Sqlite] {
+        let dir = tempfile::tempdir().unwrap();
+        let store = store::open_kind(dir.path(), kind).unwrap();
+        store.ensure().unwrap();
+
+        let id = store
+            .write("outbox", &make_message("Reply", "2026-03-01T09:00:00"))
+            .unwrap();
+        assert_eq!(store.read_outbox().unwrap().len(), 1, "{kind:?}");
+
+        store.archive_outbox(&id, &make_receipt()).unwrap();
+        assert!(store.read_outbox().unwrap().is_empty(), "{kind:?}");
+        let delivered = store.read_outbox_archive().unwrap();
+        assert_eq!(delivered[0].0, id, "{kind:?}");
+        assert_eq!(delivered[0].2.as_ref(), Some(&make_receipt()), "{kind:?}");
+    }
+}
+
+#[test]
+fn test_query_messages_filters_in_both_stores() {
+    use cryochamber::store::{query_messages, MessageDirection, MessageQuery};
+
+    let now = NaiveDateTime::parse_from_str("2026-03-01T12:00:00", "%Y-%m-%dT%H:%M:%S").unwrap();
+    for kind in [MessageStoreKind::File, MessageStoreKind::Sqlite] {
+        let dir = tempfile::tempdir().unwrap();
+        let store = store::open_kind(dir.path(), kind).unwrap();
+        store.ensure().unwrap();
+
+        // Handled instruction, then an unread one, plus the agent's replies
+        let old = store
+            .write("inbox", &make_message("Old", "2026-03-01T07:00:00"))
+            .unwrap();
+        store.archive(&[old]).unwrap();
+        store
+            .write("inbox", &make_message("New", "2026-03-01T11:00:00"))
+            .unwrap();
+        let mut reply = make_message("Done", "2026-03-01T11:30:00");
+        reply.from = "agent".to_string();
+        let sent = store.write("outbox", &reply).unwrap();
+        store.archive_outbox(&sent, &make_receipt()).unwrap();
+        let mut alert = make_message("Alert", "2026-03-01T11:45:00");
+        alert.from = "cryochamber".to_string();
+        store.write("outbox", &alert).unwrap();
+
+        let subjects = |query: MessageQuery| -> Vec<(String, String)> {
+            query_messages(store.as_ref(), &query, now)
+                .unwrap()
+                .into_iter()
+                .map(|m| (m.subject, m.status))
+                .collect()
+        };
+        let pair = |s: &str, st: &str| (s.to_string(), st.to_string());
+
+        // Everything, newest first, archives included
+        assert_eq!(
+            subjects(MessageQuery::default()),
+            [
+                pair("Alert", "pending"),
+                pair("Done", "delivered"),
+                pair("New", "unread"),
+                pair("Old", "read")
+            ],
+            "{kind:?}"
+        );
+        // Direction
+        let inbox = MessageQuery {
+            direction: MessageDirection::Inbox,
+            ..Default::default()
+        };
+        assert_eq!(
+            subjects(inbox),
+            [pair("New", "unread"), pair("Old", "read")],
+            "{kind:?}"
+        );
+        // "human" skips the agent and cryochamber; limit keeps the newest
+        let latest_human = MessageQuery {
+            from: Some("human".to_string()),
+            limit: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(subjects(latest_human), [pair("New", "unread")], "{kind:?}");
+        let from_agent = MessageQuery {
+            from: Some("Agent".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            subjects(from_agent),
+            [pair("Done", "delivered")],
+            "{kind:?}"
+        );
+        // since: a duration or a time
+        let recent = MessageQuery {
+            since: Some("45m".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            subjects(recent),
+            [pair("Alert", "pending"), pair("Done", "delivered")],
+            "{kind:?}"
+        );
+        let since_time = MessageQuery {
+            direction: MessageDirection::Inbox,
+            since: Some("2026-03-01T08:00".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(subjects(since_time), [pair("New", "unread")], "{kind:?}");
+
+        let bad = MessageQuery {
+            since: Some("yesterday".to_string()),
+            ..Default::default()
+        };
+        assert!(query_messages(store.as_ref(), &bad, now).is_err());
+    }
+}
+
+#[test]
+fn test_dead_letter_and_requeue_in_both_stores() {
+    for kind in [MessageStoreKind::File, MessageStoreKind::Sqlite] {
+        let dir = tempfile::tempdir().unwrap();
+        let store = store::open_kind(dir.path(), kind).unwrap();
+        store.ensure().unwrap();
+        let id = store
+            .write("outbox", &make_message("Too long", "2026-03-01T09:00:00"))
+            .unwrap();
+
+        assert_eq!(store.record_outbox_failure(&id, "413").unwrap().attempts, 1);
+        let failure = store.record_outbox_failure(&id, "413 again").unwrap();
+        assert_eq!(failure.attempts, 2, "{kind:?}");
+
+        store.dead_letter_outbox(&id).unwrap();
+        assert!(store.read_outbox().unwrap().is_empty(), "{kind:?}");
+        let dead = store.read_dead_letters().unwrap();
+        assert_eq!(dead.len(), 1, "{kind:?}");
+        assert_eq!(dead[0].0, id, "{kind:?}");
+        let failure = dead[0].2.as_ref().unwrap();
+        assert_eq!(
+            (failure.attempts, failure.last_error.as_str()),
+            (2, "413 again"),
+            "{kind:?}"
+        );
+
+        // Requeued messages start counting again
+        assert_eq!(store.requeue_dead_letters().unwrap(), vec![id.clone()]);
+        assert!(store.read_dead_letters().unwrap().is_empty(), "{kind:?}");
+        assert_eq!(store.read_outbox().unwrap().len(), 1, "{kind:?}");
+        assert_eq!(store.record_outbox_failure(&id, "x").unwrap().attempts, 1);
+    }
+}
+
+#[test]
+fn test_open_selects_store_from_config() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("cryo.toml"), "message_store = \"sqlite\"\n").unwrap();
+    let store = store::open(dir.path()).unwrap();
+    store
+        .write("inbox", &make_message("Hi", "2026-03-01T09:00:00"))
+        .unwrap();
+    assert!(sqlite::db_path(dir.path()).is_file());
+    assert_eq!(
+        SqliteStore::open(dir.path())
+            .unwrap()
+            .read_inbox()
+            .unwrap()
+            .len(),
+        1
+    );
+}
+
+#[test]
+fn test_sqlite_concurrent_writes_and_reads() {
+    let dir = tempfile::tempdir().unwrap();
+    SqliteStore::open(dir.path()).unwrap();
+
+    // Several writers (CLI, sync utilities) and a reader (the daemon), each
+    // with its own connection, as separate processes would have.
+    let writers: Vec<_> = (0..4)
+        .map(|w| {
+            let path = dir.path().to_path_buf();
+            std::thread::spawn(move || {
+                let store = SqliteStore::open(&path).unwrap();
+                for i in 0..25 {
+                    // Same timestamp and subject everywhere: ids must not clash
+                    let mut msg = make_message("Ping", "2026-03-01T09:00:00");
+                    msg.body = format!("writer {w} message {i}");
+                    store.write("inbox", &msg).unwrap();
+                }
+            })
+        })
+        .collect();
+    let reader = {
+        let path = dir.path().to_path_buf();
+        std::thread::spawn(move || {
+            let store = SqliteStore::open(&path).unwrap();
+            let mut archived = 0;
+            while archived < 100 {
+                let ids = store.list_inbox().unwrap();
+                for (id, msg) in store.read_inbox().unwrap() {
+                    assert!(msg.body.starts_with("writer "), "{id}: {}", msg.body);
+                }
+                archived += ids.len();
+                store.archive(&ids).unwrap();
+            }
+        })
+    };
+    for w in writers {
+        w.join().unwrap();
+    }
+    reader.join().unwrap();
+
+    let store = SqliteStore::open(dir.path()).unwrap();
+    let archived = store.read_inbox_archive().unwrap();
+    assert_eq!(archived.len(), 100);
+    let bodies: std::collections::HashSet<_> = archived.iter().map(|(_, m)| &m.body).collect();
+    assert_eq!(bodies.len(), 100, "every write must be kept");
+}
+
+#[test]
+fn test_migrate_files_into_sqlite() {
+    let dir = tempfile::tempdir().unwrap();
+    let files = FileStore::new(dir.path().to_path_buf());
+    files.ensure().unwrap();
+    let unread = files
+        .write("inbox", &make_message("Unread", "2026-03-01T09:00:00"))
+        .unwrap();
+    let handled = files
+        .write("inbox", &make_message("Handled", "2026-03-01T08:00:00"))
+        .unwrap();
+    files.archive(std::slice::from_ref(&handled)).unwrap();
+    let pending = files
+        .write("outbox", &make_message("Pending", "2026-03-01T09:30:00"))
+        .unwrap();
+    let delivered = files
+        .write("outbox", &make_message("Delivered", "2026-03-01T08:30:00"))
+        .unwrap();
+    files.archive_outbox(&delivered, &make_receipt()).unwrap();
+
+    let db = SqliteStore::open(dir.path()).unwrap();
+    assert_eq!(sqlite::migrate_files(dir.path(), &db).unwrap(), 4);
+
+    assert_eq!(db.list_inbox().unwrap(), vec![unread]);
+    assert_eq!(db.read_inbox_archive().unwrap()[0].0, handled);
+    assert_eq!(db.read_outbox().unwrap()[0].0, pending);
+    let sent = db.read_outbox_archive().unwrap();
+    assert_eq!(sent[0].0, delivered);
+    assert_eq!(sent[0].2.as_ref(), Some(&make_receipt()));
+
+    // The files are gone, so running it again imports nothing
+    assert!(files.read_inbox().unwrap().is_empty());
+    assert!(files.read_outbox_archive().unwrap().is_empty());
+    assert_eq!(sqlite::migrate_files(dir.path(), &db).unwrap(), 0);
+}
+
+#[test]
+fn test_migrate_files_keeps_delivery_failures() {
+    let dir = tempfile::tempdir().unwrap();
+    let files = FileStore::new(dir.path().to_path_buf());
+    files.ensure().unwrap();
+    let retrying = files
+        .write("outbox", &make_message("Retrying", "2026-03-01T09:00:00"))
+        .unwrap();
+    files.record_outbox_failure(&retrying, "timeout").unwrap();
+    files.record_outbox_failure(&retrying, "timeout").unwrap();
+    let dead = files
+        .write("outbox", &make_message("Dead", "2026-03-01T08:00:00"))
+        .unwrap();
+    files.record_outbox_failure(&dead, "403 Forbidden").unwrap();
+    files.dead_letter_outbox(&dead).unwrap();
+
+    let db = SqliteStore::open(dir.path()).unwrap();
+    assert_eq!(sqlite::migrate_files(dir.path(), &db).unwrap(), 2);
+
+    assert_eq!(db.read_outbox().unwrap()[0].0, retrying);
+    assert_eq!(
+        db.record_outbox_failure(&retrying, "timeout")
+            .unwrap()
+            .attempts,
+        3
+    );
+    let letters = db.read_dead_letters().unwrap();
+    assert_eq!(letters.len(), 1);
+    assert_eq!(letters[0].0, dead);
+    let failure = letters[0].2.as_ref().unwrap();
+    assert_eq!(
+        (failure.attempts, failure.last_error.as_str()),
+        (1, "403 Forbidden")
+    );
+
+    // No messages or failure records are left behind on disk
+    let outbox = dir.path().join("messages/outbox");
+    assert!(!outbox
+        .join(cryochamber::message::failure_filename(&retrying))
+        .exists());
+    assert!(files.read_dead_letters().unwrap().is_empty());
+    assert_eq!(
+        std::fs::read_dir(outbox.join("dead-letter"))
+            .unwrap()
+            .count(),
+        0
+    );
+}
@@ -1,7 +1,8 @@
 use cryochamber::channel::zulip::{
     parse_get_messages_response, parse_get_profile_response, parse_get_stream_id_response,
-    ZulipClient,
+    route_outbox_message, OutboxRoute, ZulipClient,
 };
+use cryochamber::channel::ChannelWorker;
 
 #[test]
 fn test_parse_zuliprc() {
@@ -20,6 +21,30 @@ fn test_parse_zuliprc() {
     assert_eq!(creds.site, "https://zulip.example.com");
 }
 
+#[test]
+fn test_zulip_channel_collapses_with_spoiler() {
+    use cryochamber::channel::zulip::ZulipChannel;
+    use cryochamber::channel::MessageChannel;
+
+    let dir = tempfile::tempdir().unwrap();
+    let rc_path = dir.path().join("zuliprc");
+    std::fs::write(
+        &rc_path,
+        "[api]\nemail=bot@example.com\nkey=abc123secret\nsite=https://zulip.example.com\n",
+    )
+    .unwrap();
+    let channel = ZulipChannel {
+        client: ZulipClient::from_zuliprc(&rc_path).unwrap(),
+        stream_id: 1,
+        topic: "cryochamber".to_string(),
+    };
+    // Zulip renders no <details>, so session pushes collapse the log this way
+    assert_eq!(
+        channel.collapsible("Session log", "```\nlog\n```"),
+        "````spoiler Session log\n```\nlog\n```\n````"
+    );
+}
+
 #[test]
 fn test_parse_zuliprc_with_spaces() {
     let dir = tempfile::tempdir().unwrap();
@@ -161,6 +186,32 @@ fn test_parse_get_messages_no_self_filter() {
     assert_eq!(raw_max_id, Some(100));
 }
 
+#[test]
+fn test_parse_get_messages_direct_message_subject() {
+    let json = serde_json::json!({
+        "result": "success",
+        "msg": "",
+        "messages": [
+            {
+                "id": 200,
+                "sender_id": 42,
+                "sender_email": "alice@example.com",
+                "sender_full_name": "Alice",
+                "content": "Sounds good, ship it",
+                "subject": "",
+                "timestamp": 1740700000,
+                "type": "private"
+            }
+        ],
+        "found_newest": true,
+        "found_oldest": false
+    });
+    let (messages, _, _) = parse_get_messages_response(&json, Some("bot@example.com")).unwrap();
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0].from, "Alice");
+    assert_eq!(messages[0].subject, "Direct message");
+}
+
 #[test]
 fn test_parse_get_messages_cursor_advances_when_all_filtered() {
     // When all messages are from the skip_email sender, the filtered messages
@@ -201,3 +252,92 @@ fn test_parse_get_messages_cursor_advances_when_all_filtered() {
     // But raw_max_id advances to 201 so pagination can continue
     assert_eq!(raw_max_id, Some(201));
 }
+
+#[test]
+fn test_channel_timeout_on_slow_endpoint() {
+    // A server that accepts connections but never answers
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    std::thread::spawn(move || {
+        let mut held = Vec::new();
+        for stream in listener.incoming().flatten() {
+            held.push(stream);
+        }
+    });
+
+    let dir = tempfile::tempdir().unwrap();
+    let rc_path = dir.path().join("zuliprc");
+    std::fs::write(
+        &rc_path,
+        format!("[api]\nemail=bot@example.com\nkey=k\nsite=http://127.0.0.1:{port}\n"),
+    )
+    .unwrap();
+    let client = ZulipClient::from_zuliprc(&rc_path).unwrap();
+
+    let worker = ChannelWorker::new(1);
+    let started = std::time::Instant::now();
+    let slow = client.clone();
+    let err = worker
+        .run("get profile", move || slow.get_profile())
+        .unwrap_err();
+    assert!(err.to_string().contains("timed out after 1s"), "{err}");
+    assert!(started.elapsed() < std::time::Duration::from_secs(5));
+
+    // The hung request is still in flight, so the next call fails fast
+    let err = worker
+        .run("get profile", move || client.get_profile())
+        .unwrap_err();
+    assert!(err.to_string().contains("still running"), "{err}");
+}
+
+#[test]
+fn test_channel_worker_passes_result_through() {
+    let worker = ChannelWorker::new(5);
+    assert_eq!(worker.run("op", || Ok(42)).unwrap(), 42);
+    let err = worker
+        .run::<(), _>("op", || anyhow::bail!("boom"))
+        .unwrap_err();
+    assert_eq!(err.to_string(), "boom");
+    // No timeout: runs inline
+    assert_eq!(ChannelWorker::new(0).run("op", || Ok(1)).unwrap(), 1);
+}
+
+fn outbox_message(to: Option<&str>) -> cryochamber::message::Message {
+    let mut metadata = std::collections::BTreeMap::new();
+    if let Some(to) = to {
+        metadata.insert("to".to_string(), to.to_string());
+    }
+    cryochamber::message::Message {
+        from: "agent".to_string(),
+        subject: "Deploy".to_string(),
+        body: "v1.2 is live".to_string(),
+        timestamp: chrono::NaiveDate::from_ymd_opt(2026, 3, 1)
+            .unwrap()
+            .and_hms_opt(9, 0, 0)
+            .unwrap(),
+        metadata,
+        attachments: Vec::new(),
+    }
+}
+
+#[test]
+fn test_route_outbox_message() {
+    let content = "**agent** (Deploy)\n\nv1.2 is live".to_string();
+    assert_eq!(
+        route_outbox_message(&outbox_message(None)),
+        OutboxRoute::Topic(content.clone())
+    );
+    // Emails get a direct message
+    assert_eq!(
+        route_outbox_message(&outbox_message(Some("alice@example.com"))),
+        OutboxRoute::Direct {
+            to: vec!["alice@example.com".to_string()],
+            content: content.clone(),
+        }
+    );
+    // Names are @-mentioned in the topic
+    assert_eq!(
+        route_outbox_message(&outbox_message(Some("Alice Smith, bob@example.com"))),
+        OutboxRoute::Topic(format!("@**Alice Smith** @**bob@example.com**\n{content}"))
+    );
+}
@@ -12,7 +12,9 @@ fn test_zulip_sync_state_roundtrip() {
         self_email: "bot@example.com".to_string(),
         topic: Some("my-project".to_string()),
         last_message_id: Some(12345),
+        last_dm_id: Some(12400),
         last_pushed_session: Some(3),
+        last_pushed_at: None,
     };
     save_sync_state(&path, &state).unwrap();
     let loaded = load_sync_state(&path).unwrap().unwrap();
@@ -23,6 +25,7 @@ fn test_zulip_sync_state_roundtrip() {
     assert_eq!(loaded.self_email, "bot@example.com");
     assert_eq!(loaded.topic, Some("my-project".to_string()));
     assert_eq!(loaded.last_message_id, Some(12345));
+    assert_eq!(loaded.last_dm_id, Some(12400));
     assert_eq!(loaded.last_pushed_session, Some(3));
 }
 
@@ -46,12 +49,15 @@ fn test_zulip_sync_state_defaults() {
         self_email: "bot@z.example.com".to_string(),
         topic: None,
         last_message_id: None,
+        last_dm_id: None,
         last_pushed_session: None,
+        last_pushed_at: None,
     };
     save_sync_state(&path, &state).unwrap();
     let loaded = load_sync_state(&path).unwrap().unwrap();
     assert!(loaded.topic.is_none());
     assert!(loaded.last_message_id.is_none());
+    assert!(loaded.last_dm_id.is_none());
     assert!(loaded.last_pushed_session.is_none());
 }
 
@@ -68,5 +74,6 @@ fn test_zulip_sync_state_legacy_json_compat() {
     let loaded = load_sync_state(&path).unwrap().unwrap();
     assert!(loaded.topic.is_none());
     assert!(loaded.last_message_id.is_none());
+    assert!(loaded.last_dm_id.is_none());
     assert!(loaded.last_pushed_session.is_none());
 }